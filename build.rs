@@ -1,11 +1,14 @@
+use std::{env, path::PathBuf, process::Command};
+
 /// Rivet build script.
 ///
-/// Phase 1 role: validate that the host targets Windows and reserve the spot
-/// where Scintilla C++ compilation will be wired up (Phase 2+).
+/// Validates that the host targets Windows, compiles the resource script
+/// (the application icon and manifest), and with the `static-scintilla`
+/// feature links Scintilla and Lexilla into the executable.
 fn main() {
     // Hard gate: Rivet is Windows-only. Fail loudly on any other target
     // rather than silently producing a broken binary.
-    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
     if target_os != "windows" {
         panic!(
             "Rivet only builds for Windows \
@@ -13,22 +16,150 @@ fn main() {
         );
     }
 
-    // Only re-run the build script when it changes.
-    // Phase 2+ will scope this to `vendor/scintilla/` once the source tree
-    // is vendored.
+    // Only re-run the build script when its inputs change.
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=res/rivet.rc");
+    println!("cargo:rerun-if-changed=res/rivet.ico");
+    println!("cargo:rerun-if-changed=res/rivet.manifest");
+    println!("cargo:rerun-if-env-changed=RC");
+    println!("cargo:rerun-if-env-changed=CI");
+    println!("cargo:rerun-if-env-changed=SCINTILLA_LIB_DIR");
+
+    compile_resources();
+
+    if env::var_os("CARGO_FEATURE_STATIC_SCINTILLA").is_some() {
+        link_scintilla();
+    }
+}
 
-    // ── Scintilla placeholder ─────────────────────────────────────────────────
-    // Integration decision (Phase 1): SciLexer.dll (DLL-hosting approach).
-    //
-    // When/if we switch to a static lib, this is where the `cc` crate
-    // compilation goes:
-    //
-    //   cc::Build::new()
-    //       .cpp(true)
-    //       .include("vendor/scintilla/include")
-    //       .files(scintilla_sources())
-    //       .compile("scintilla");
-    //
-    // For now, SciLexer.dll is loaded at runtime via LoadLibraryW.
+/// Compile `res/rivet.rc` with the toolchain's resource compiler and link
+/// the result into the binary: `rc.exe` for MSVC (taken from the newest
+/// Windows SDK when it is not on `PATH`), `windres` for GNU, or the tool
+/// named by `RC`.
+///
+/// The resources carry the application icon and the manifest that declares
+/// `longPathAware`, so a release or CI build (`CI` set) without them fails.
+/// An ordinary local build only warns: it still runs, with the stock icon
+/// and paths limited to `MAX_PATH` wherever std does not add the `\\?\`
+/// prefix itself.
+fn compile_resources() {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let msvc = env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc");
+    let output = if msvc {
+        out_dir.join("rivet.res")
+    } else {
+        out_dir.join("rivet_res.o")
+    };
+    let tool = match env::var_os("RC") {
+        Some(tool) => PathBuf::from(tool),
+        None if msvc => sdk_rc().unwrap_or_else(|| PathBuf::from("rc.exe")),
+        None => PathBuf::from("windres"),
+    };
+
+    let mut command = Command::new(&tool);
+    if msvc {
+        command
+            .args(["/nologo", "/i", "res", "/fo"])
+            .arg(&output)
+            .arg("res/rivet.rc");
+    } else {
+        command
+            .args(["--include-dir", "res", "--output-format=coff", "--input"])
+            .arg("res/rivet.rc")
+            .arg("--output")
+            .arg(&output);
+    }
+
+    let tool = tool.display();
+    let problem = match command.status() {
+        Ok(status) if status.success() => {
+            println!("cargo:rustc-link-arg-bins={}", output.display());
+            return;
+        }
+        Ok(status) => format!("{tool} failed ({status})"),
+        Err(e) => format!(
+            "{tool} not found ({e}); install the Windows SDK or set RC to the resource \
+             compiler's path"
+        ),
+    };
+    let required = env::var("PROFILE").as_deref() == Ok("release") || env::var_os("CI").is_some();
+    if required {
+        panic!("{problem}: a release or CI build needs the application icon and manifest");
+    }
+    println!("cargo:warning={problem}; building without the application icon and manifest");
+}
+
+/// `rc.exe` from the newest installed Windows 10/11 SDK for the build host:
+/// the one a Developer prompt names in `WindowsSdkVerBinPath`, or else the
+/// highest version under `Windows Kits\10\bin`.
+fn sdk_rc() -> Option<PathBuf> {
+    let arch = match env::var("HOST").unwrap_or_default() {
+        host if host.starts_with("x86_64") => "x64",
+        host if host.starts_with("aarch64") => "arm64",
+        _ => "x86",
+    };
+    println!("cargo:rerun-if-env-changed=WindowsSdkVerBinPath");
+    if let Some(bin) = env::var_os("WindowsSdkVerBinPath") {
+        let rc = PathBuf::from(bin).join(arch).join("rc.exe");
+        if rc.is_file() {
+            return Some(rc);
+        }
+    }
+    let kits = PathBuf::from(env::var_os("ProgramFiles(x86)")?)
+        .join("Windows Kits")
+        .join("10")
+        .join("bin");
+    let mut versions: Vec<(Vec<u32>, PathBuf)> = std::fs::read_dir(&kits)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let version = name
+                .split('.')
+                .map(str::parse)
+                .collect::<Result<Vec<u32>, _>>()
+                .ok()?;
+            Some((version, entry.path().join(arch).join("rc.exe")))
+        })
+        .collect();
+    versions.sort();
+    versions
+        .into_iter()
+        .rev()
+        .map(|(_, rc)| rc)
+        .find(|rc| rc.is_file())
+}
+
+/// Link the static Scintilla and Lexilla libraries from `SCINTILLA_LIB_DIR`,
+/// plus the system libraries they call into.
+///
+/// The libraries come from Scintilla's and Lexilla's own makefiles
+/// (`nmake -f scintilla.mak` in `scintilla/win32`, `nmake -f lexilla.mak` in
+/// `lexilla/src`), built with the same toolchain as Rivet.  Without the
+/// variable the build fails here rather than at link time.
+fn link_scintilla() {
+    let Some(dir) = env::var_os("SCINTILLA_LIB_DIR") else {
+        panic!(
+            "the static-scintilla feature needs SCINTILLA_LIB_DIR: a folder holding \
+             libscintilla.lib and liblexilla.lib"
+        );
+    };
+    let dir = PathBuf::from(dir);
+    for lib in ["libscintilla", "liblexilla"] {
+        println!(
+            "cargo:rerun-if-changed={}",
+            dir.join(format!("{lib}.lib")).display()
+        );
+    }
+    println!("cargo:rustc-link-search=native={}", dir.display());
+    println!("cargo:rustc-link-lib=static=libscintilla");
+    println!("cargo:rustc-link-lib=static=liblexilla");
+    // What Scintilla's Win32 platform layer calls: windowing, GDI and
+    // AlphaBlend, the IME, and OLE drag and drop with its interface IDs.
+    // Direct2D and DirectWrite are loaded on demand and need no import.
+    for lib in [
+        "user32", "gdi32", "imm32", "msimg32", "ole32", "oleaut32", "uuid",
+    ] {
+        println!("cargo:rustc-link-lib=dylib={lib}");
+    }
 }
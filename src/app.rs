@@ -5,40 +5,160 @@
 // `platform::win32::WindowState` so that this module stays testable without
 // a Win32 environment.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::editor::LARGE_FILE_THRESHOLD_BYTES;
+use crate::{config::LargeFileSettings, editor::clip_ring::ClipRing, languages::Language};
 
 // ── Encoding ──────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Encoding {
     Utf8,
+    /// UTF-8 written with a leading byte-order mark (EF BB BF).
+    Utf8Bom,
     Utf16Le,
     Utf16Be,
+    /// The configured ANSI code page (`App::ansi`).
     Ansi,
+    /// A legacy code page chosen by the user, one of [`CODE_PAGES`].
+    CodePage(u32),
 }
 
+/// Legacy code pages offered besides ANSI: identifier, short label for the
+/// status bar and session, and the name shown in menus.
+pub(crate) const CODE_PAGES: [(u32, &str, &str); 12] = [
+    (932, "Shift-JIS", "Japanese (Shift-JIS)"),
+    (936, "GBK", "Chinese Simplified (GBK)"),
+    (949, "EUC-KR", "Korean (EUC-KR)"),
+    (1250, "Windows-1250", "Central European (Windows-1250)"),
+    (1251, "Windows-1251", "Cyrillic (Windows-1251)"),
+    (1252, "Windows-1252", "Western European (Windows-1252)"),
+    (1253, "Windows-1253", "Greek (Windows-1253)"),
+    (1254, "Windows-1254", "Turkish (Windows-1254)"),
+    (1255, "Windows-1255", "Hebrew (Windows-1255)"),
+    (1256, "Windows-1256", "Arabic (Windows-1256)"),
+    (1257, "Windows-1257", "Baltic (Windows-1257)"),
+    (1258, "Windows-1258", "Vietnamese (Windows-1258)"),
+];
+
 impl Encoding {
+    /// Every encoding, in the order File > Document Properties… and
+    /// Format > Encoding list them.
+    pub(crate) const ALL: [Encoding; 17] = [
+        Self::Utf8,
+        Self::Utf8Bom,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Ansi,
+        Self::CodePage(932),
+        Self::CodePage(936),
+        Self::CodePage(949),
+        Self::CodePage(1250),
+        Self::CodePage(1251),
+        Self::CodePage(1252),
+        Self::CodePage(1253),
+        Self::CodePage(1254),
+        Self::CodePage(1255),
+        Self::CodePage(1256),
+        Self::CodePage(1257),
+        Self::CodePage(1258),
+    ];
+
     pub(crate) fn as_str(self) -> &'static str {
         match self {
             Self::Utf8 => "UTF-8",
+            Self::Utf8Bom => "UTF-8 BOM",
             Self::Utf16Le => "UTF-16 LE",
             Self::Utf16Be => "UTF-16 BE",
             Self::Ansi => "ANSI",
+            Self::CodePage(cp) => CODE_PAGES
+                .iter()
+                .find(|&&(id, ..)| id == cp)
+                .map_or("Code page", |&(_, label, _)| label),
+        }
+    }
+
+    /// Name for menus and the properties dialog: the status-bar label, or
+    /// the script and label of a code page.
+    pub(crate) fn display_name(self) -> &'static str {
+        match self {
+            Self::CodePage(cp) => CODE_PAGES
+                .iter()
+                .find(|&&(id, ..)| id == cp)
+                .map_or("Code page", |&(.., name)| name),
+            _ => self.as_str(),
         }
     }
 
-    #[allow(dead_code)]
     pub(crate) fn from_str(s: &str) -> Option<Self> {
         match s {
             "UTF-8" => Some(Self::Utf8),
+            "UTF-8 BOM" => Some(Self::Utf8Bom),
             "UTF-16 LE" => Some(Self::Utf16Le),
             "UTF-16 BE" => Some(Self::Utf16Be),
             "ANSI" => Some(Self::Ansi),
-            _ => None,
+            _ => CODE_PAGES
+                .iter()
+                .find(|&&(_, label, _)| label == s)
+                .map(|&(cp, ..)| Self::CodePage(cp)),
         }
     }
+
+    /// Whether this is a single- or double-byte code page rather than a
+    /// Unicode encoding.
+    pub(crate) fn is_code_page(self) -> bool {
+        matches!(self, Self::Ansi | Self::CodePage(_))
+    }
+}
+
+// ── ANSI code pages ───────────────────────────────────────────────────────────
+
+/// Converts text in a legacy ANSI code page to and from UTF-8.
+///
+/// The Win32 layer installs one backed by `MultiByteToWideChar` /
+/// `WideCharToMultiByte` (see `platform::win32::encoding`).  The default maps
+/// bytes to and from Latin-1, so headless tests need no Win32.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AnsiCodec {
+    /// Windows code page identifier, e.g. 1252.
+    pub(crate) code_page: u32,
+    pub(crate) to_utf8: fn(u32, &[u8]) -> Vec<u8>,
+    pub(crate) from_utf8: fn(u32, &[u8]) -> Vec<u8>,
+}
+
+impl Default for AnsiCodec {
+    fn default() -> Self {
+        Self {
+            code_page: 28591, // ISO 8859-1
+            to_utf8: |_, bytes| bytes.iter().map(|&b| b as char).collect::<String>().into(),
+            from_utf8: |_, utf8| {
+                String::from_utf8_lossy(utf8)
+                    .chars()
+                    .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                    .collect()
+            },
+        }
+    }
+}
+
+impl AnsiCodec {
+    /// `bytes` in this code page, as UTF-8.
+    pub(crate) fn decode(self, bytes: &[u8]) -> Vec<u8> {
+        (self.to_utf8)(self.code_page, bytes)
+    }
+
+    /// UTF-8 `utf8` in this code page.  Characters the code page lacks
+    /// become its default character, usually `?`.
+    pub(crate) fn encode(self, utf8: &[u8]) -> Vec<u8> {
+        (self.from_utf8)(self.code_page, utf8)
+    }
+}
+
+/// Length of the longest prefix of ANSI `bytes` that decodes on its own:
+/// up to and including the last byte below 0x40, which is never part of a
+/// double-byte character in any Windows code page.
+pub(crate) fn ansi_boundary(bytes: &[u8]) -> usize {
+    bytes.iter().rposition(|&b| b < 0x40).map_or(0, |i| i + 1)
 }
 
 // ── EOL mode ──────────────────────────────────────────────────────────────────
@@ -51,6 +171,9 @@ pub(crate) enum EolMode {
 }
 
 impl EolMode {
+    /// Every mode, in the order File > Document Properties… lists them.
+    pub(crate) const ALL: [EolMode; 3] = [Self::Crlf, Self::Lf, Self::Cr];
+
     pub(crate) fn as_str(self) -> &'static str {
         match self {
             Self::Crlf => "CRLF",
@@ -59,6 +182,15 @@ impl EolMode {
         }
     }
 
+    /// The line ending itself.
+    pub(crate) fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::Crlf => b"\r\n",
+            Self::Lf => b"\n",
+            Self::Cr => b"\r",
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn from_str(s: &str) -> Option<Self> {
         match s {
@@ -72,6 +204,126 @@ impl EolMode {
 
 // ── DocumentState ─────────────────────────────────────────────────────────────
 
+// ── Indentation ───────────────────────────────────────────────────────────────
+
+/// Indentation style of one document: what the Tab key inserts and how wide
+/// a tab stop is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Indentation {
+    /// Indent with tab characters (`true`) or spaces (`false`).
+    pub(crate) use_tabs: bool,
+    /// Columns per indent level / tab stop.
+    pub(crate) width: usize,
+}
+
+impl Default for Indentation {
+    /// Four spaces — used for new buffers and files with no indented lines.
+    fn default() -> Self {
+        Self {
+            use_tabs: false,
+            width: 4,
+        }
+    }
+}
+
+/// Lines scanned by `detect_indentation`; enough to be representative while
+/// keeping large files cheap.
+const INDENT_SCAN_LINES: usize = 1000;
+
+/// Infer the indentation style of `utf8` from its leading whitespace.
+///
+/// Tabs vs spaces is a majority vote over indented lines.  For spaces the
+/// width is the most common increase in indent between consecutive indented
+/// lines, restricted to 2–8 so that one-space continuation (e.g. ` * ` inside
+/// C block comments) does not skew the result.  Returns `None` when the text
+/// has no indented lines.
+pub(crate) fn detect_indentation(utf8: &[u8]) -> Option<Indentation> {
+    let (mut tab_lines, mut space_lines) = (0usize, 0usize);
+    let mut deltas = [0usize; 9]; // index = indent increase in columns
+    let mut prev_spaces = 0usize;
+
+    for line in utf8.split(|&b| b == b'\n').take(INDENT_SCAN_LINES) {
+        // Blank / whitespace-only lines carry no information.
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+        match line.first() {
+            Some(b'\t') => {
+                tab_lines += 1;
+                prev_spaces = 0;
+            }
+            Some(b' ') => {
+                space_lines += 1;
+                let spaces = line.iter().take_while(|&&b| b == b' ').count();
+                if spaces > prev_spaces {
+                    if let Some(n) = deltas.get_mut(spaces - prev_spaces) {
+                        *n += 1;
+                    }
+                }
+                prev_spaces = spaces;
+            }
+            _ => prev_spaces = 0,
+        }
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    let default = Indentation::default();
+    if tab_lines > space_lines {
+        return Some(Indentation {
+            use_tabs: true,
+            ..default
+        });
+    }
+    // Ties go to the smaller width (max_by_key keeps the last maximum, so
+    // iterate from wide to narrow).
+    let width = (2..=8)
+        .rev()
+        .filter(|&w| deltas[w] > 0)
+        .max_by_key(|&w| deltas[w])
+        .unwrap_or(default.width);
+    Some(Indentation {
+        use_tabs: false,
+        width,
+    })
+}
+
+/// Features a document does without because of its size; chosen from the
+/// file size by `LargeFileSettings::degraded`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Degraded {
+    /// Large File Mode: loaded in the background, `[Large]` in the status
+    /// bar, no content in session checkpoints, EOL conversion on a worker.
+    pub(crate) large_file: bool,
+    /// Plain text instead of syntax highlighting.
+    pub(crate) plain_text: bool,
+    /// Word wrap off and unavailable.
+    pub(crate) no_wrap: bool,
+    /// No word completion, occurrence highlighting or git gutter.
+    pub(crate) no_scan: bool,
+    /// No undo history: edits are not recorded, and Undo and Redo stay
+    /// grayed.
+    pub(crate) no_undo: bool,
+    /// `plain_text` and `no_wrap` are on because of a line longer than
+    /// `LargeFileSettings::line_length_max`, which View > Highlight and Wrap
+    /// Long Lines overrides.  `[Long Lines]` in the status bar.
+    pub(crate) long_lines: bool,
+}
+
+impl Degraded {
+    /// Everything off, for the huge-file viewer.  Its lines are not the
+    /// reason, so the override does not apply.
+    pub(crate) const ALL: Self = Self {
+        large_file: true,
+        plain_text: true,
+        no_wrap: true,
+        no_scan: true,
+        no_undo: true,
+        long_lines: false,
+    };
+}
+
 /// Per-document state.
 ///
 /// Phase 4 keeps one `DocumentState` per tab in `App::tabs`.
@@ -82,8 +334,24 @@ pub(crate) struct DocumentState {
     pub(crate) encoding: Encoding,
     pub(crate) eol: EolMode,
     pub(crate) dirty: bool,
-    pub(crate) large_file: bool,
+    /// Dirty because an undo stepped back past the last save: the saved
+    /// text is on the redo side.  Cleared when the save point is reached
+    /// again.
+    pub(crate) undone_past_save: bool,
+    pub(crate) degraded: Degraded,
     pub(crate) word_wrap: bool,
+    /// Opened as a read-only copy because another Rivet window holds unsaved
+    /// changes to the same file.  Save is redirected to Save As.
+    pub(crate) read_only: bool,
+    /// Detected language; drives the lexer, keywords, theme, and status bar.
+    /// Refreshed on open, on save (the extension may change), and when a
+    /// paste gives an untitled buffer a shebang line.
+    pub(crate) language: Language,
+    /// Tabs vs spaces and indent width; detected on open.
+    pub(crate) indent: Indentation,
+    /// Tab label for a generated buffer with no file, such as a diff; shown
+    /// instead of `"Untitled"`.
+    pub(crate) title: Option<String>,
 }
 
 impl DocumentState {
@@ -93,19 +361,67 @@ impl DocumentState {
             encoding: Encoding::Utf8,
             eol: EolMode::Crlf,
             dirty: false,
-            large_file: false,
+            undone_past_save: false,
+            degraded: Degraded::default(),
             word_wrap: false,
+            read_only: false,
+            language: Language::PlainText,
+            indent: Indentation::default(),
+            title: None,
         }
     }
 
-    /// Bare filename for display, or `"Untitled"`.
+    /// Format > Add BOM (`bom`) / Remove BOM: switch a UTF-8 document
+    /// between being saved with and without a byte-order mark, and mark it
+    /// dirty so the next save writes the change.  Returns `false`, changing
+    /// nothing, if the document is not UTF-8 or already as asked.
+    pub(crate) fn set_bom(&mut self, bom: bool) -> bool {
+        self.encoding = match (self.encoding, bom) {
+            (Encoding::Utf8, true) => Encoding::Utf8Bom,
+            (Encoding::Utf8Bom, false) => Encoding::Utf8,
+            _ => return false,
+        };
+        self.dirty = true;
+        true
+    }
+
+    /// `true` for a clean untitled buffer that an opened file may take over.
+    pub(crate) fn is_blank(&self) -> bool {
+        self.path.is_none() && self.title.is_none() && !self.dirty
+    }
+
+    /// `true` for the scratch tab (see `crate::scratch`), which saves itself
+    /// instead of prompting.
+    pub(crate) fn is_scratch(&self) -> bool {
+        self.path.as_deref().is_some_and(crate::scratch::is_scratch)
+    }
+
+    /// Bare filename for display, the generated buffer's title, or
+    /// `"Untitled"`.  The scratch tab is `"Scratch"`.
     pub(crate) fn display_name(&self) -> String {
+        if self.is_scratch() {
+            return "Scratch".to_owned();
+        }
         self.path
             .as_deref()
             .and_then(|p| p.file_name())
             .map(|n| n.to_string_lossy().into_owned())
+            .or_else(|| self.title.clone())
             .unwrap_or_else(|| "Untitled".to_owned())
     }
+
+    /// Path for tooltips: relative to `root` (an open workspace's folder)
+    /// when the file lies under it, the full path otherwise, or `"Untitled"`.
+    pub(crate) fn display_path(&self, root: Option<&Path>) -> String {
+        let Some(path) = self.path.as_deref() else {
+            return self.display_name();
+        };
+        root.and_then(|r| path.strip_prefix(r).ok())
+            .filter(|rel| !rel.as_os_str().is_empty())
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
 }
 
 // ── App ───────────────────────────────────────────────────────────────────────
@@ -119,6 +435,12 @@ pub(crate) struct App {
     pub(crate) tabs: Vec<DocumentState>,
     /// Index of the currently visible tab.
     pub(crate) active_idx: usize,
+    /// Recent Cut / Copy text for Edit > Paste from History.
+    pub(crate) clip_ring: ClipRing,
+    /// Code page for documents detected as ANSI.
+    pub(crate) ansi: AnsiCodec,
+    /// Size limits for Large File Mode and the features it drops.
+    pub(crate) large_files: LargeFileSettings,
 }
 
 impl App {
@@ -127,6 +449,9 @@ impl App {
         Self {
             tabs: vec![DocumentState::new_untitled()],
             active_idx: 0,
+            clip_ring: ClipRing::default(),
+            ansi: AnsiCodec::default(),
+            large_files: LargeFileSettings::default(),
         }
     }
 
@@ -140,19 +465,27 @@ impl App {
 
     /// Window title for the currently active tab.
     ///
-    /// | State           | Title                  |
-    /// |-----------------|------------------------|
-    /// | Untitled, clean | `"Rivet"`              |
-    /// | Named, clean    | `"name — Rivet"`       |
-    /// | Named, dirty    | `"*name — Rivet"`      |
-    /// | Untitled, dirty | `"*Untitled — Rivet"`  |
+    /// | State            | Title                           |
+    /// |------------------|---------------------------------|
+    /// | Untitled, clean  | `"Rivet"`                       |
+    /// | Named, clean     | `"name — Rivet"`                |
+    /// | Named, dirty     | `"*name — Rivet"`               |
+    /// | Untitled, dirty  | `"*Untitled — Rivet"`           |
+    /// | Read-only        | `"name [Read-Only] — Rivet"`    |
+    /// | Undone past save | `"*name [Before Save] — Rivet"` |
     pub(crate) fn window_title(&self) -> String {
         let doc = self.active_doc();
-        if doc.path.is_none() && !doc.dirty {
+        if doc.is_blank() {
             return "Rivet".to_owned();
         }
         let dirty = if doc.dirty { "*" } else { "" };
-        format!("{dirty}{} \u{2014} Rivet", doc.display_name())
+        let undone = if doc.dirty && doc.undone_past_save {
+            " [Before Save]"
+        } else {
+            ""
+        };
+        let ro = if doc.read_only { " [Read-Only]" } else { "" };
+        format!("{dirty}{}{undone}{ro} \u{2014} Rivet", doc.display_name())
     }
 
     /// Append a new untitled tab entry and return its index.
@@ -171,14 +504,49 @@ impl App {
     ///
     /// Returns the new `active_idx` after removal.
     pub(crate) fn remove_tab(&mut self, idx: usize) -> usize {
-        self.tabs.remove(idx);
+        self.take_tab(idx);
+        self.active_idx
+    }
+
+    /// Remove the tab at `idx` and return its document, adjusting
+    /// `active_idx` as [`remove_tab`](Self::remove_tab) does.  Used to move a
+    /// tab to another window.
+    pub(crate) fn take_tab(&mut self, idx: usize) -> DocumentState {
+        let doc = self.tabs.remove(idx);
         // Clamp active_idx to the new valid range.
         if self.active_idx >= self.tabs.len() {
             self.active_idx = self.tabs.len().saturating_sub(1);
         } else if self.active_idx > idx {
             self.active_idx -= 1;
         }
-        self.active_idx
+        doc
+    }
+
+    /// Append `doc` (taken from another window) as the last tab and return
+    /// its index.  The caller must push its view the same way.
+    pub(crate) fn push_tab(&mut self, doc: DocumentState) -> usize {
+        self.tabs.push(doc);
+        self.tabs.len() - 1
+    }
+
+    /// Move the tab at `from` so it ends up at index `to`, keeping the same
+    /// document active.
+    ///
+    /// Panics if either index is out of range.  The caller must move the
+    /// matching `ScintillaView` in `WindowState::sci_views` the same way.
+    pub(crate) fn move_tab(&mut self, from: usize, to: usize) {
+        let doc = self.tabs.remove(from);
+        self.tabs.insert(to, doc);
+        let a = self.active_idx;
+        self.active_idx = if a == from {
+            to
+        } else if from < a && a <= to {
+            a - 1
+        } else if to <= a && a < from {
+            a + 1
+        } else {
+            a
+        };
     }
 
     /// Number of open tabs.
@@ -189,22 +557,115 @@ impl App {
     // ── File open ─────────────────────────────────────────────────────────────
 
     /// Update the active document state after reading `bytes` from `path`.
+    /// The encoding is detected, or with `code_page` (`Ansi` or a
+    /// `CodePage`) taken as given unless the bytes start with a BOM.
     ///
     /// Returns the UTF-8 content to pass to `ScintillaView::set_text`.
-    pub(crate) fn open_file(&mut self, path: PathBuf, bytes: &[u8]) -> Vec<u8> {
+    pub(crate) fn open_file(
+        &mut self,
+        path: PathBuf,
+        bytes: &[u8],
+        code_page: Option<Encoding>,
+    ) -> Vec<u8> {
+        let has_bom = bytes.starts_with(&[0xFF, 0xFE])
+            || bytes.starts_with(&[0xFE, 0xFF])
+            || bytes.starts_with(&[0xEF, 0xBB, 0xBF]);
+        let (encoding, utf8) = match code_page.filter(|e| e.is_code_page() && !has_bom) {
+            Some(e) => (e, self.codec(e).decode(bytes)),
+            None => Self::detect_and_decode(bytes, self.ansi),
+        };
+        let degraded = self.large_files.with_long_lines(
+            self.large_files.degraded(bytes.len() as u64),
+            longest_line(&utf8) as u64,
+        );
         let doc = self.active_doc_mut();
-        doc.large_file = bytes.len() as u64 > LARGE_FILE_THRESHOLD_BYTES;
+        doc.degraded = degraded;
         doc.dirty = false;
-
-        let (encoding, utf8) = Self::detect_and_decode(bytes);
+        doc.undone_past_save = false;
+        doc.read_only = false;
         doc.encoding = encoding;
         doc.eol = Self::detect_eol(&utf8);
+        doc.indent = detect_indentation(&utf8).unwrap_or_default();
+        doc.language = crate::languages::detect_language(Some(&path), &utf8);
         doc.path = Some(path);
         utf8
     }
 
-    /// Detect encoding and transcode to UTF-8.
-    fn detect_and_decode(bytes: &[u8]) -> (Encoding, Vec<u8>) {
+    /// Re-read the active document from `bytes` (File > Reload from Disk).
+    ///
+    /// Encoding, EOL, indentation, and language are detected afresh; the
+    /// path, read-only state, and word-wrap choice are kept.  `code_page`
+    /// overrides the encoding as in [`App::open_file`] (Format >
+    /// Encoding > Reopen With).  Returns the UTF-8 content, or `None` if the
+    /// document has no path.
+    pub(crate) fn reload_file(
+        &mut self,
+        bytes: &[u8],
+        code_page: Option<Encoding>,
+    ) -> Option<Vec<u8>> {
+        let doc = self.active_doc();
+        let path = doc.path.clone()?;
+        let (read_only, word_wrap) = (doc.read_only, doc.word_wrap);
+        let utf8 = self.open_file(path, bytes, code_page);
+        let doc = self.active_doc_mut();
+        doc.read_only = read_only;
+        doc.word_wrap = word_wrap;
+        Some(utf8)
+    }
+
+    /// Give the document at `idx`, `size` bytes long, back the word wrap and
+    /// highlighting its long lines took away (View > Highlight and Wrap Long
+    /// Lines); its size may still keep them off.  Returns `false` if its
+    /// lines were not what turned them off.
+    pub(crate) fn allow_long_lines(&mut self, idx: usize, size: u64) -> bool {
+        let by_size = self.large_files.degraded(size);
+        let degraded = &mut self.tabs[idx].degraded;
+        if !degraded.long_lines {
+            return false;
+        }
+        *degraded = Degraded {
+            long_lines: false,
+            plain_text: by_size.plain_text,
+            no_wrap: by_size.no_wrap,
+            ..*degraded
+        };
+        true
+    }
+
+    /// Prepare the active document for a background load of `path`, `size`
+    /// bytes long.
+    ///
+    /// The content arrives later in chunks; the first decoded chunk is passed
+    /// to [`App::apply_load_sample`] and the encoding is set when the load
+    /// finishes.
+    pub(crate) fn begin_load(&mut self, path: PathBuf, size: u64) {
+        let degraded = Degraded {
+            large_file: true,
+            ..self.large_files.degraded(size)
+        };
+        let doc = self.active_doc_mut();
+        doc.degraded = degraded;
+        doc.dirty = false;
+        doc.undone_past_save = false;
+        doc.read_only = false;
+        doc.language = crate::languages::detect_language(Some(&path), b"");
+        doc.path = Some(path);
+    }
+
+    /// Detect EOL style, indentation, and language for the document at `idx`
+    /// from the first decoded chunk of a background load.
+    ///
+    /// A chunk is several megabytes, so the sample is representative.
+    pub(crate) fn apply_load_sample(&mut self, idx: usize, sample: &[u8]) {
+        let doc = &mut self.tabs[idx];
+        doc.eol = Self::detect_eol(sample);
+        doc.indent = detect_indentation(sample).unwrap_or_default();
+        doc.language = crate::languages::detect_language(doc.path.as_deref(), sample);
+    }
+
+    /// Detect encoding and transcode to UTF-8.  Bytes with no BOM that are
+    /// not valid UTF-8 are ANSI, decoded with `ansi`.
+    pub(crate) fn detect_and_decode(bytes: &[u8], ansi: AnsiCodec) -> (Encoding, Vec<u8>) {
         if bytes.starts_with(&[0xFF, 0xFE]) {
             let units: Vec<u16> = bytes[2..]
                 .chunks_exact(2)
@@ -226,12 +687,12 @@ impl App {
             );
         }
         if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
-            return (Encoding::Utf8, bytes[3..].to_vec());
+            return (Encoding::Utf8Bom, bytes[3..].to_vec());
         }
         if std::str::from_utf8(bytes).is_ok() {
             return (Encoding::Utf8, bytes.to_vec());
         }
-        (Encoding::Ansi, bytes.to_vec())
+        (Encoding::Ansi, ansi.decode(bytes))
     }
 
     /// Detect the dominant EOL style.
@@ -270,20 +731,23 @@ impl App {
 
     /// Write `utf8_content` to `path` using the active document's encoding.
     ///
-    /// On success, updates `active_doc().path` (for Save As) and clears
-    /// `active_doc().dirty`.  The caller must call `ScintillaView::set_save_point()`.
+    /// On success, updates `active_doc().path` (for Save As) and
+    /// `active_doc().language`, and clears `active_doc().dirty`.  The caller must call `ScintillaView::set_save_point()`.
     pub(crate) fn save(&mut self, path: PathBuf, utf8_content: &[u8]) -> crate::error::Result<()> {
         let bytes = self.encode_for_disk(utf8_content);
         std::fs::write(&path, &bytes)?;
         let doc = self.active_doc_mut();
+        doc.language = crate::languages::detect_language(Some(&path), utf8_content);
         doc.path = Some(path);
         doc.dirty = false;
+        doc.undone_past_save = false;
         Ok(())
     }
 
     fn encode_for_disk(&self, utf8: &[u8]) -> Vec<u8> {
         match self.active_doc().encoding {
             Encoding::Utf8 => utf8.to_vec(),
+            Encoding::Utf8Bom => [&[0xEF, 0xBB, 0xBF][..], utf8].concat(),
             Encoding::Utf16Le => {
                 let s = String::from_utf8_lossy(utf8);
                 let mut out = vec![0xFF_u8, 0xFE];
@@ -300,17 +764,57 @@ impl App {
                 }
                 out
             }
-            Encoding::Ansi => utf8.to_vec(),
+            encoding @ (Encoding::Ansi | Encoding::CodePage(_)) => {
+                self.codec(encoding).encode(utf8)
+            }
+        }
+    }
+
+    /// The codec for a code-page `encoding`: the configured ANSI one, or
+    /// the same conversion in the chosen code page.
+    pub(crate) fn codec(&self, encoding: Encoding) -> AnsiCodec {
+        match encoding {
+            Encoding::CodePage(code_page) => AnsiCodec {
+                code_page,
+                ..self.ansi
+            },
+            _ => self.ansi,
         }
     }
 }
 
+/// Length in bytes of the longest line in `text`, not counting its line
+/// ending.  A lone CR ends a line too.
+fn longest_line(text: &[u8]) -> usize {
+    text.split(|&b| b == b'\n' || b == b'\r')
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn display_path_relative_to_root() {
+        let mut doc = DocumentState::new_untitled();
+        assert_eq!(doc.display_path(Some(Path::new("/w"))), "Untitled");
+        doc.path = Some(PathBuf::from("/w/src/main.rs"));
+        let rel = Path::new("src").join("main.rs");
+        assert_eq!(
+            doc.display_path(Some(Path::new("/w"))),
+            rel.to_string_lossy()
+        );
+        assert_eq!(
+            doc.display_path(Some(Path::new("/other"))),
+            "/w/src/main.rs"
+        );
+        assert_eq!(doc.display_path(None), "/w/src/main.rs");
+    }
+
     #[test]
     fn title_clean_untitled() {
         assert_eq!(App::new().window_title(), "Rivet");
@@ -323,6 +827,20 @@ mod tests {
         assert_eq!(app.window_title(), "todo.txt \u{2014} Rivet");
     }
 
+    #[test]
+    fn title_marks_undo_past_save() {
+        let mut app = App::new();
+        app.tabs[0].path = Some(PathBuf::from("todo.txt"));
+        app.tabs[0].dirty = true;
+        app.tabs[0].undone_past_save = true;
+        assert_eq!(app.window_title(), "*todo.txt [Before Save] \u{2014} Rivet");
+        // Saving again clears it.
+        let path = std::env::temp_dir().join("rivet_title_marks_undo_past_save.txt");
+        app.save(path.clone(), b"x").expect("save");
+        let _ = std::fs::remove_file(&path);
+        assert!(!app.tabs[0].undone_past_save);
+    }
+
     #[test]
     fn title_dirty_with_path() {
         let mut app = App::new();
@@ -338,6 +856,55 @@ mod tests {
         assert_eq!(app.window_title(), "*Untitled \u{2014} Rivet");
     }
 
+    #[test]
+    fn title_read_only() {
+        let mut app = App::new();
+        app.tabs[0].path = Some(PathBuf::from(r"C:\notes\todo.txt"));
+        app.tabs[0].read_only = true;
+        assert_eq!(app.window_title(), "todo.txt [Read-Only] \u{2014} Rivet");
+    }
+
+    #[test]
+    fn open_file_detects_language() {
+        let mut app = App::new();
+        app.open_file(PathBuf::from(r"C:\lib.rs"), b"fn main() {}", None);
+        assert_eq!(app.active_doc().language, Language::Rust);
+        app.open_file(PathBuf::from(r"C:\deploy"), b"#!/bin/bash\nset -e\n", None);
+        assert_eq!(app.active_doc().language, Language::Shell);
+    }
+
+    #[test]
+    fn save_redetects_language() {
+        let path = std::env::temp_dir().join("rivet_save_redetects_language.py");
+        let mut app = App::new();
+        app.save(path.clone(), b"print(1)\n").expect("save");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(app.active_doc().language, Language::Python);
+    }
+
+    #[test]
+    fn open_file_clears_read_only() {
+        let mut app = App::new();
+        app.tabs[0].read_only = true;
+        app.open_file(PathBuf::from(r"C:\a.txt"), b"x", None);
+        assert!(!app.active_doc().read_only);
+    }
+
+    #[test]
+    fn reload_redetects_but_keeps_read_only() {
+        let mut app = App::new();
+        assert!(app.reload_file(b"x", None).is_none());
+        app.open_file(PathBuf::from(r"C:\a.txt"), b"a\r\nb\r\n", None);
+        app.tabs[0].read_only = true;
+        app.tabs[0].dirty = true;
+        let utf8 = app.reload_file(b"a\nb\n", None).unwrap();
+        assert_eq!(utf8, b"a\nb\n");
+        let doc = app.active_doc();
+        assert_eq!(doc.eol, EolMode::Lf);
+        assert!(doc.read_only);
+        assert!(!doc.dirty);
+    }
+
     #[test]
     fn push_and_remove_tabs() {
         let mut app = App::new();
@@ -350,25 +917,213 @@ mod tests {
         assert_eq!(app.active_idx, 0);
     }
 
+    #[test]
+    fn take_tab_moves_document_between_apps() {
+        let mut from = App::new();
+        from.push_untitled();
+        from.tabs[1].path = Some(PathBuf::from("moved.txt"));
+        from.tabs[1].dirty = true;
+        from.active_idx = 1;
+        let doc = from.take_tab(1);
+        assert_eq!(from.tab_count(), 1);
+        assert_eq!(from.active_idx, 0);
+
+        let mut to = App::new();
+        let idx = to.push_tab(doc);
+        assert_eq!(idx, 1);
+        assert_eq!(to.tabs[1].display_name(), "moved.txt");
+        assert!(to.tabs[1].dirty);
+    }
+
+    #[test]
+    fn move_tab_keeps_active_document() {
+        let mut app = App::new();
+        for _ in 0..3 {
+            app.push_untitled();
+        }
+        for (i, doc) in app.tabs.iter_mut().enumerate() {
+            doc.path = Some(PathBuf::from(format!("{i}")));
+        }
+        let order = |app: &App| -> String { app.tabs.iter().map(|d| d.display_name()).collect() };
+        app.active_idx = 1;
+
+        // Active tab itself moves right.
+        app.move_tab(1, 3);
+        assert_eq!(order(&app), "0231");
+        assert_eq!(app.active_idx, 3);
+
+        // Active tab moves to the front.
+        app.move_tab(3, 0);
+        assert_eq!(order(&app), "1023");
+        assert_eq!(app.active_idx, 0);
+
+        // A tab moves from after the active one to before it…
+        app.move_tab(3, 0);
+        assert_eq!(order(&app), "3102");
+        assert_eq!(app.active_idx, 1);
+
+        // …and back past it again.
+        app.move_tab(0, 3);
+        assert_eq!(order(&app), "1023");
+        assert_eq!(app.active_idx, 0);
+        assert_eq!(app.active_doc().display_name(), "1");
+    }
+
+    #[test]
+    fn indent_tabs() {
+        let src = b"fn main() {\n\tlet x = 1;\n\tif x {\n\t\ty();\n\t}\n}\n";
+        assert_eq!(
+            detect_indentation(src),
+            Some(Indentation {
+                use_tabs: true,
+                width: 4
+            })
+        );
+    }
+
+    #[test]
+    fn indent_two_spaces() {
+        let src = b"a:\n  b:\n    c: 1\n  d: 2\n";
+        assert_eq!(
+            detect_indentation(src),
+            Some(Indentation {
+                use_tabs: false,
+                width: 2
+            })
+        );
+    }
+
+    #[test]
+    fn indent_four_spaces_ignores_block_comment_stars() {
+        let src = b"/**\n * Doc.\n */\nvoid f() {\n    if (x) {\n        g();\n    }\n}\n";
+        assert_eq!(detect_indentation(src).map(|i| i.width), Some(4));
+    }
+
+    #[test]
+    fn indent_mixed_majority_wins() {
+        let src = b"x\n    a\n    b\n    c\n\td\n";
+        assert_eq!(detect_indentation(src).map(|i| i.use_tabs), Some(false));
+    }
+
+    #[test]
+    fn indent_none_when_flat() {
+        assert_eq!(detect_indentation(b"one\ntwo\n\n   \nthree"), None);
+    }
+
+    #[test]
+    fn open_file_sets_indent() {
+        let mut app = App::new();
+        app.open_file(PathBuf::from("a.py"), b"def f():\n  return 1\n", None);
+        assert_eq!(app.active_doc().indent.width, 2);
+        app.open_file(PathBuf::from("b.txt"), b"flat\n", None);
+        assert_eq!(app.active_doc().indent, Indentation::default());
+    }
+
+    #[test]
+    fn background_load_fills_in_from_sample() {
+        let mut app = App::new();
+        app.active_doc_mut().read_only = true;
+        app.begin_load(PathBuf::from("build_all"), 1);
+        let doc = app.active_doc();
+        assert!(doc.degraded.large_file);
+        assert!(!doc.read_only);
+        assert_eq!(doc.path, Some(PathBuf::from("build_all")));
+
+        app.apply_load_sample(0, b"#!/usr/bin/env python3\n\tx = 1\n");
+        let doc = app.active_doc();
+        assert_eq!(doc.eol, EolMode::Lf);
+        assert!(doc.indent.use_tabs);
+        assert_eq!(doc.language, Language::Python);
+    }
+
     #[test]
     fn detect_encoding_utf16le() {
         let bytes = b"\xFF\xFEh\x00i\x00";
-        let (enc, utf8) = App::detect_and_decode(bytes);
+        let (enc, utf8) = App::detect_and_decode(bytes, AnsiCodec::default());
         assert_eq!(enc, Encoding::Utf16Le);
         assert_eq!(utf8, b"hi");
     }
 
     #[test]
     fn detect_encoding_utf8_bom() {
-        let (enc, utf8) = App::detect_and_decode(b"\xEF\xBB\xBFhello");
-        assert_eq!(enc, Encoding::Utf8);
+        let (enc, utf8) = App::detect_and_decode(b"\xEF\xBB\xBFhello", AnsiCodec::default());
+        assert_eq!(enc, Encoding::Utf8Bom);
         assert_eq!(utf8, b"hello");
     }
 
+    #[test]
+    fn bom_written_only_when_requested() {
+        let mut app = App::new();
+        let doc = app.active_doc_mut();
+        assert!(doc.set_bom(true));
+        assert!(doc.dirty);
+        assert!(!doc.set_bom(true));
+        assert_eq!(app.encode_for_disk(b"hi"), b"\xEF\xBB\xBFhi");
+        assert!(app.active_doc_mut().set_bom(false));
+        assert_eq!(app.encode_for_disk(b"hi"), b"hi");
+        app.active_doc_mut().encoding = Encoding::Utf16Le;
+        assert!(!app.active_doc_mut().set_bom(false));
+    }
+
     #[test]
     fn detect_encoding_ansi_fallback() {
-        let (enc, _) = App::detect_and_decode(b"\x80\x81\x82");
+        let (enc, utf8) = App::detect_and_decode(b"caf\xE9", AnsiCodec::default());
         assert_eq!(enc, Encoding::Ansi);
+        assert_eq!(utf8, "caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    fn ansi_boundary_never_splits_a_double_byte_character() {
+        // Shift-JIS: 0x82 0xA0 is one character; 0x0A ends the line.
+        assert_eq!(ansi_boundary(b"ab\n\x82\xA0\x82"), 3);
+        assert_eq!(ansi_boundary(b"\x82\xA0"), 0);
+        assert_eq!(ansi_boundary(b"x = 1;"), 6);
+    }
+
+    #[test]
+    fn chosen_code_page_is_kept_unless_the_file_has_a_bom() {
+        for enc in Encoding::ALL {
+            assert_eq!(Encoding::from_str(enc.as_str()), Some(enc));
+        }
+        assert_eq!(
+            Encoding::CodePage(1251).display_name(),
+            "Cyrillic (Windows-1251)"
+        );
+
+        let mut app = App::new();
+        let cyrillic = Some(Encoding::CodePage(1251));
+        // Valid UTF-8 as well, but the user said otherwise.
+        app.open_file(PathBuf::from("a.txt"), b"plain", cyrillic);
+        assert_eq!(app.active_doc().encoding, Encoding::CodePage(1251));
+        assert_eq!(app.codec(app.active_doc().encoding).code_page, 1251);
+        app.open_file(PathBuf::from("a.txt"), b"\xEF\xBB\xBFplain", cyrillic);
+        assert_eq!(app.active_doc().encoding, Encoding::Utf8Bom);
+        // Unicode encodings are never forced.
+        app.open_file(PathBuf::from("a.txt"), b"\xE9", Some(Encoding::Utf16Le));
+        assert_eq!(app.active_doc().encoding, Encoding::Ansi);
+    }
+
+    #[test]
+    fn long_line_disables_wrap_and_highlighting_until_allowed() {
+        let mut app = App::new();
+        app.large_files.line_length_max = 8;
+        app.open_file(PathBuf::from("min.js"), b"short\r\nvar a=1;b=2;\n", None);
+        let d = app.active_doc().degraded;
+        assert!(d.long_lines && d.plain_text && d.no_wrap && !d.large_file);
+
+        assert!(app.allow_long_lines(0, 20));
+        assert_eq!(app.active_doc().degraded, Degraded::default());
+        assert!(!app.allow_long_lines(0, 20));
+
+        app.open_file(PathBuf::from("a.js"), b"var a;\rvar b;", None);
+        assert!(!app.active_doc().degraded.long_lines);
+    }
+
+    #[test]
+    fn longest_line_ignores_line_endings() {
+        assert_eq!(longest_line(b""), 0);
+        assert_eq!(longest_line(b"ab\r\nabcd\nabc"), 4);
+        assert_eq!(longest_line(b"ab\rabc\r"), 3);
     }
 
     #[test]
@@ -383,12 +1138,7 @@ mod tests {
 
     #[test]
     fn encoding_roundtrip_str() {
-        for enc in [
-            Encoding::Utf8,
-            Encoding::Utf16Le,
-            Encoding::Utf16Be,
-            Encoding::Ansi,
-        ] {
+        for enc in Encoding::ALL {
             assert_eq!(Encoding::from_str(enc.as_str()), Some(enc));
         }
     }
@@ -0,0 +1,156 @@
+// ── File associations ─────────────────────────────────────────────────────────
+//
+// The per-user registry layout behind Tools > File Associations…: an
+// "Edit with Rivet" entry on every file's Explorer context menu, and a
+// `Rivet.Document` ProgID listed under each chosen extension's
+// `OpenWithProgids`.  Everything lives under `HKCU\Software\Classes`, so no
+// elevation is needed and unregistering removes every trace.
+//
+// Windows no longer lets a program make itself the default handler; listing
+// the ProgID puts Rivet in "Open with" and Windows asks the user the next
+// time such a file is opened.
+//
+// This module only names keys and values; `platform::win32::associations`
+// writes them.  No `unsafe` — pure safe Rust.
+
+use std::path::Path;
+
+/// Root of the per-user class registrations, relative to `HKEY_CURRENT_USER`.
+const CLASSES: &str = r"Software\Classes";
+
+/// ProgID for documents Rivet opens.
+pub(crate) const PROG_ID: &str = "Rivet.Document";
+
+/// One string value to write: `(key, value name or default, data)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RegEntry {
+    /// Key path relative to `HKEY_CURRENT_USER`.
+    pub(crate) key: String,
+    /// `None` is the key's default value.
+    pub(crate) name: Option<String>,
+    pub(crate) data: String,
+}
+
+impl RegEntry {
+    fn new(key: String, name: Option<&str>, data: String) -> Self {
+        Self {
+            key,
+            name: name.map(str::to_owned),
+            data,
+        }
+    }
+}
+
+/// Key of the "Edit with Rivet" context-menu verb.
+pub(crate) fn context_menu_key() -> String {
+    format!(r"{CLASSES}\*\shell\Rivet")
+}
+
+/// Key of the `Rivet.Document` ProgID.
+pub(crate) fn prog_id_key() -> String {
+    format!(r"{CLASSES}\{PROG_ID}")
+}
+
+/// Key holding the ProgIDs offered for `ext` (`.txt`).
+pub(crate) fn open_with_key(ext: &str) -> String {
+    format!(r"{CLASSES}\{ext}\OpenWithProgids")
+}
+
+/// Command line Explorer runs to open `%1` in Rivet.
+fn open_command(exe: &Path) -> String {
+    format!("\"{}\" \"%1\"", exe.display())
+}
+
+/// Values for the "Edit with Rivet" context-menu entry.
+pub(crate) fn context_menu_entries(exe: &Path) -> Vec<RegEntry> {
+    let key = context_menu_key();
+    vec![
+        RegEntry::new(key.clone(), None, "Edit with &Rivet".to_owned()),
+        RegEntry::new(key.clone(), Some("Icon"), format!("{},0", exe.display())),
+        RegEntry::new(format!(r"{key}\command"), None, open_command(exe)),
+    ]
+}
+
+/// Values for the ProgID and for listing it under each of `extensions`.
+pub(crate) fn association_entries(exe: &Path, extensions: &[String]) -> Vec<RegEntry> {
+    let key = prog_id_key();
+    let mut entries = vec![
+        RegEntry::new(key.clone(), None, "Text Document (Rivet)".to_owned()),
+        RegEntry::new(
+            format!(r"{key}\DefaultIcon"),
+            None,
+            format!("{},0", exe.display()),
+        ),
+        RegEntry::new(
+            format!(r"{key}\shell\open\command"),
+            None,
+            open_command(exe),
+        ),
+    ];
+    entries.extend(
+        extensions
+            .iter()
+            .map(|ext| RegEntry::new(open_with_key(ext), Some(PROG_ID), String::new())),
+    );
+    entries
+}
+
+/// Parse a list of extensions typed by the user ("txt, .md  log"):
+/// lower-cased, with a leading dot, duplicates dropped.  Errors name the
+/// first entry that is not a plain extension.
+pub(crate) fn parse_extensions(text: &str) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
+    for word in text.split(|c: char| c == ',' || c == ';' || c.is_whitespace()) {
+        let bare = word.strip_prefix('.').unwrap_or(word);
+        if bare.is_empty() {
+            continue;
+        }
+        let valid = bare
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '.'));
+        if !valid || bare.ends_with('.') {
+            return Err(word.to_owned());
+        }
+        let ext = format!(".{}", bare.to_ascii_lowercase());
+        if !out.contains(&ext) {
+            out.push(ext);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mixed_separators() {
+        assert_eq!(
+            parse_extensions("txt, .MD;log  txt").unwrap(),
+            [".txt", ".md", ".log"]
+        );
+        assert_eq!(parse_extensions("  ").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_extensions("tar.gz").unwrap(), [".tar.gz"]);
+    }
+
+    #[test]
+    fn rejects_paths_and_wildcards() {
+        assert_eq!(parse_extensions(r"txt ..\x").unwrap_err(), r"..\x");
+        assert_eq!(parse_extensions("*").unwrap_err(), "*");
+        assert_eq!(parse_extensions("md.").unwrap_err(), "md.");
+    }
+
+    #[test]
+    fn entries_point_at_the_exe() {
+        let exe = Path::new(r"C:\Tools\rivet.exe");
+        let menu = context_menu_entries(exe);
+        assert_eq!(menu[2].key, r"Software\Classes\*\shell\Rivet\command");
+        assert_eq!(menu[2].data, r#""C:\Tools\rivet.exe" "%1""#);
+
+        let assoc = association_entries(exe, &[".md".to_owned()]);
+        let last = assoc.last().unwrap();
+        assert_eq!(last.key, r"Software\Classes\.md\OpenWithProgids");
+        assert_eq!(last.name.as_deref(), Some(PROG_ID));
+        assert!(last.data.is_empty());
+    }
+}
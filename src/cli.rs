@@ -0,0 +1,206 @@
+// ── Command line ──────────────────────────────────────────────────────────────
+//
+// `rivet.exe [--clean] [--] [FILE]...`
+//
+//   --clean   start with an empty window instead of restoring the last
+//             session (the setting under File > On Startup is not changed)
+//   FILE      open these files once the window is up; `FILE:LINE`,
+//             `FILE:LINE:COL` (compiler style) or `FILE(LINE,COL)` (MSVC
+//             style) also put the caret there
+//
+// Anything after `--` is a file, even if it starts with `-`.  Unknown
+// options are ignored rather than opened as files.  File > Go to File…
+// reads the same `FILE:LINE:COL` forms.
+// No `unsafe` — pure safe Rust.
+
+use std::{ffi::OsString, path::PathBuf};
+
+/// Parsed command-line arguments.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Args {
+    /// `--clean`: skip session restore for this start.
+    pub(crate) clean: bool,
+    /// Files to open, in order.
+    pub(crate) files: Vec<FileArg>,
+}
+
+/// A file to open, with the 1-based line and column to put the caret on.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct FileArg {
+    pub(crate) path: PathBuf,
+    pub(crate) line: Option<usize>,
+    pub(crate) column: Option<usize>,
+}
+
+impl FileArg {
+    /// Split a trailing `:LINE`, `:LINE:COL`, `(LINE)` or `(LINE,COL)` off
+    /// `text`.  Quotes around the path and a final `:` (as in `grep -n`
+    /// output) are dropped.  A colon after a lone drive letter stays part of
+    /// the path.
+    pub(crate) fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let (path, numbers) = split_colon_numbers(text.strip_suffix(':').unwrap_or(text))
+            .or_else(|| split_paren_numbers(text))
+            .unwrap_or((text, Vec::new()));
+        Self {
+            path: PathBuf::from(path.trim().trim_matches('"')),
+            line: numbers.first().copied(),
+            column: numbers.get(1).copied(),
+        }
+    }
+}
+
+/// `s` as a line or column number: ASCII digits only.
+fn number(s: &str) -> Option<usize> {
+    s.bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| s.parse().ok())
+        .flatten()
+}
+
+/// `path:LINE` or `path:LINE:COL` → the path and the numbers in order.
+fn split_colon_numbers(text: &str) -> Option<(&str, Vec<usize>)> {
+    let mut rest = text;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        let Some((head, tail)) = rest.rsplit_once(':') else {
+            break;
+        };
+        let Some(n) = number(tail) else {
+            break;
+        };
+        // `C:5` is a drive-relative path, not drive `C` at line 5.
+        if head.is_empty() || (head.len() == 1 && head.as_bytes()[0].is_ascii_alphabetic()) {
+            break;
+        }
+        numbers.insert(0, n);
+        rest = head;
+    }
+    (!numbers.is_empty()).then_some((rest, numbers))
+}
+
+/// `path(LINE)` or `path(LINE,COL)` → the path and the numbers in order.
+/// `name (2)` with a space before the bracket is a file name, not a line.
+fn split_paren_numbers(text: &str) -> Option<(&str, Vec<usize>)> {
+    let (path, inner) = text.strip_suffix(')')?.rsplit_once('(')?;
+    if path.is_empty() || path.ends_with(' ') {
+        return None;
+    }
+    let numbers = inner
+        .split(',')
+        .map(|n| number(n.trim()))
+        .collect::<Option<Vec<_>>>()?;
+    (numbers.len() <= 2).then_some((path, numbers))
+}
+
+impl Args {
+    /// Parse the arguments after the program name.
+    pub(crate) fn parse(args: impl IntoIterator<Item = OsString>) -> Self {
+        let mut out = Self::default();
+        let mut options_done = false;
+        for arg in args {
+            if !options_done {
+                match arg.to_str() {
+                    Some("--") => {
+                        options_done = true;
+                        continue;
+                    }
+                    Some("--clean") => {
+                        out.clean = true;
+                        continue;
+                    }
+                    Some(opt) if opt.starts_with("--") => continue,
+                    _ => {}
+                }
+            }
+            out.files.push(match arg.to_str() {
+                Some(text) => FileArg::parse(text),
+                None => FileArg {
+                    path: PathBuf::from(arg),
+                    ..FileArg::default()
+                },
+            });
+        }
+        out
+    }
+
+    /// Parse this process's command line.
+    pub(crate) fn from_env() -> Self {
+        Self::parse(std::env::args_os().skip(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse(args.iter().map(OsString::from))
+    }
+
+    fn paths(args: &Args) -> Vec<&Path> {
+        args.files.iter().map(|f| f.path.as_path()).collect()
+    }
+
+    fn at(path: &str, line: Option<usize>, column: Option<usize>) -> FileArg {
+        FileArg {
+            path: PathBuf::from(path),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn files_and_clean_flag() {
+        let args = parse(&[r"C:\a.txt", "--clean", r"C:\b.txt"]);
+        assert!(args.clean);
+        assert_eq!(
+            paths(&args),
+            [Path::new(r"C:\a.txt"), Path::new(r"C:\b.txt")]
+        );
+        assert_eq!(parse(&[]), Args::default());
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let args = parse(&["--", "--clean", "--x"]);
+        assert!(!args.clean);
+        assert_eq!(paths(&args), [Path::new("--clean"), Path::new("--x")]);
+    }
+
+    #[test]
+    fn unknown_options_are_skipped() {
+        let args = parse(&["--verbose", "notes.md"]);
+        assert_eq!(paths(&args), [Path::new("notes.md")]);
+    }
+
+    #[test]
+    fn line_and_column_suffixes() {
+        assert_eq!(
+            FileArg::parse(r"src\main.rs:120:5"),
+            at(r"src\main.rs", Some(120), Some(5))
+        );
+        assert_eq!(
+            FileArg::parse(r"C:\x\notes.txt:12:"),
+            at(r"C:\x\notes.txt", Some(12), None)
+        );
+        assert_eq!(
+            FileArg::parse(r"C:\src\main.cpp(42,7)"),
+            at(r"C:\src\main.cpp", Some(42), Some(7))
+        );
+        assert_eq!(
+            FileArg::parse(r#" "C:\My Files\a.txt":3 "#),
+            at(r"C:\My Files\a.txt", Some(3), None)
+        );
+    }
+
+    #[test]
+    fn plain_paths_stay_whole() {
+        assert_eq!(FileArg::parse(r"C:\a.txt"), at(r"C:\a.txt", None, None));
+        assert_eq!(FileArg::parse("C:5"), at("C:5", None, None));
+        assert_eq!(FileArg::parse("report (2)"), at("report (2)", None, None));
+        assert_eq!(FileArg::parse("a.txt:x:3"), at("a.txt:x", Some(3), None));
+    }
+}
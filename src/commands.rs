@@ -0,0 +1,443 @@
+// ── Commands ──────────────────────────────────────────────────────────────────
+//
+// Every menu / accelerator action as a `Command` value, and `execute`, which
+// runs the ones that need only the document model (`App`) and the active
+// `EditorView`.  The Win32 layer translates a `WM_COMMAND` ID into a
+// `Command`, calls `execute`, and handles whatever comes back as
+// `Outcome::Host` itself (dialogs, menu checkmarks, window chrome).
+// No `unsafe` — pure safe Rust, testable without a window.
+
+use crate::{
+    app::{App, DocumentState, Encoding, EolMode, Indentation},
+    config::{NotFoundFeedback, RestoreSession},
+    editor::{line_ops::LineOp, EditorView},
+    transform::Transform,
+};
+
+/// One user action.  Variants are grouped by the menu they appear in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Command {
+    // ── File ──────────────────────────────────────────────────────────────────
+    FileNew,
+    /// Open another top-level window.
+    FileNewWindow,
+    FileOpen,
+    FileReload,
+    FileSave,
+    FileSaveAs,
+    FileSaveWorkspace,
+    FileOpenWorkspace,
+    /// File > Open Folder…: list a folder in the folder panel.
+    FileOpenFolder,
+    /// File > Document Properties…: path, size, encoding, EOL, language.
+    FileProperties,
+    /// File > Go to File…: open `path:line:col` typed or pasted.
+    FileGoto,
+    /// File > On Startup choice.
+    RestoreSession(RestoreSession),
+    /// File > On Startup > Keep Unsaved Changes.
+    ToggleKeepUnsaved,
+    /// Close the active tab.
+    FileClose,
+    FileExit,
+
+    // ── Edit ──────────────────────────────────────────────────────────────────
+    Undo,
+    Redo,
+    /// Edit > Clear Undo History: forget every undo and redo step, freeing
+    /// their memory.
+    ClearUndoHistory,
+    Cut,
+    Copy,
+    Paste,
+    Delete,
+    SelectAll,
+    CopyPath,
+    /// Edit > Paste from History…: pick a recent cut or copy to paste.
+    PasteFromHistory,
+    /// Edit > Copy Special > Copy as RTF.
+    CopyAsRtf,
+    /// Edit > Copy Special > Copy as HTML.
+    CopyAsHtml,
+    /// Edit > Column Editor…: fill a rectangular selection with text or
+    /// numbers.
+    ColumnEditor,
+    CompleteWord,
+    /// Edit > Edit Snippets…: open the active language's snippets file.
+    EditSnippets,
+    ToggleAutoClose,
+    LineComment,
+    BlockComment,
+    /// An Edit > Line Operations command.
+    LineOperation(LineOp),
+
+    // ── Format ────────────────────────────────────────────────────────────────
+    /// Convert the active document's line endings.
+    ConvertEol(EolMode),
+    /// Format > Add BOM (`true`) / Remove BOM (`false`).
+    SetBom(bool),
+    /// Format > Encoding: save the active document in this encoding.
+    SetEncoding(Encoding),
+    /// Format > Encoding > Reopen With: re-read the file in this code page.
+    ReopenWithEncoding(Encoding),
+
+    // ── View ──────────────────────────────────────────────────────────────────
+    ToggleWordWrap,
+    ToggleDarkMode,
+    /// View > Follow System Theme: track the Windows app light / dark mode.
+    ToggleFollowTheme,
+    TabsTop,
+    TabsLeft,
+    TabsRight,
+    ToggleAutoHideMenu,
+    ToggleTitleBarTabs,
+    /// View > Multiline Tabs: wrap tabs onto extra rows.
+    ToggleMultilineTabs,
+    /// View > Minimize to Tray.
+    ToggleTray,
+    CaretSettings,
+    NextChunk,
+    PrevChunk,
+    ToggleAccent,
+    /// View > Highlight Occurrences.
+    ToggleOccurrences,
+    SpeakPosition,
+    ToggleMonitor,
+    ToggleRelativePaths,
+    RefreshHighlighting,
+    /// View > Highlight and Wrap Long Lines.
+    AllowLongLines,
+    SwitchTab,
+    /// View > Toolbar.
+    ToggleToolbar,
+    /// View > File Icons in Tabs.
+    ToggleTabIcons,
+    /// View > Folder Panel.
+    ToggleFolderPanel,
+    /// View > Outline Panel.
+    ToggleOutline,
+    /// View > Document Map.
+    ToggleDocumentMap,
+    /// View > Next Pane (F6) / Previous Pane (Shift+F6): move the keyboard
+    /// focus between the editor, the tab strip and the panels.
+    FocusPane {
+        forward: bool,
+    },
+
+    // ── Search ────────────────────────────────────────────────────────────────
+    Find,
+    Replace,
+    FindNext,
+    FindPrev,
+    /// Find the next match of the selection (or the word at the caret).
+    FindSelected,
+    /// Search > Find All: list every match in the results panel.
+    FindAll,
+    CloseResults,
+    /// Cancel the active tab's load, Replace All or line-ending conversion
+    /// (the status bar's Cancel button, or Esc in the editor).
+    CancelTask,
+    /// Search > Replace All in All Opened Documents.
+    ReplaceAllOpen,
+    GotoLine,
+    /// Search > Next / Previous Change: step between edited lines.
+    NextChange,
+    PrevChange,
+    /// Search > When Not Found choice.
+    NotFoundFeedback(NotFoundFeedback),
+    SelectMatches,
+    AddMatches,
+    /// Search > Extended Mode: expand `\n`, `\t`, `\xNN`… in searches.
+    ToggleExtended,
+
+    // ── Tools ─────────────────────────────────────────────────────────────────
+    /// Tools > Run…: launch a typed command line.
+    Run,
+    ConfigureTools,
+    Shortcuts,
+    CompareFile,
+    CompareTab,
+    /// Tools > File Associations…: Explorer integration.
+    FileAssociations,
+    /// Tools > Transform Selection: encode or decode the selection.
+    Transform(Transform),
+    /// Tools > Format JSON: pretty-print the selection or document.
+    FormatJson,
+    /// Tools > Minify JSON.
+    MinifyJson,
+    /// Tools > Generate Hash…: MD5 / SHA-1 / SHA-256 of the file or selection.
+    GenerateHash,
+    /// Tools > Text Pipeline…: run a recipe of line steps over the selection.
+    TextPipeline,
+    /// Launch user-defined tool `.0` (index into `Settings::tools`).
+    Tool(usize),
+
+    // ── Help ──────────────────────────────────────────────────────────────────
+    About,
+}
+
+/// What the caller still has to do after `execute`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// Fully handled.
+    Done,
+    /// Handled; the active document's state changed, so refresh the status
+    /// bar and menu checkmarks.
+    Updated,
+    /// Handled; the text changed in a way that may change the detected
+    /// language (e.g. a pasted shebang line).
+    Redetect,
+    /// Not handled: the command needs the window (a dialog, the tab strip,
+    /// settings, …).
+    Host,
+}
+
+/// Run `cmd` against the active document of `app`, shown in `view`.
+pub(crate) fn execute(cmd: Command, app: &mut App, view: &dyn EditorView) -> Outcome {
+    match cmd {
+        Command::Undo => view.undo(),
+        Command::Redo => view.redo(),
+        Command::ClearUndoHistory => {
+            view.empty_undo_buffer();
+            return Outcome::Updated;
+        }
+        Command::Cut => {
+            app.clip_ring.push(&view.selected_text());
+            view.cut();
+        }
+        Command::Copy => {
+            app.clip_ring.push(&view.selected_text());
+            view.copy_to_clipboard();
+        }
+        Command::Paste => {
+            view.paste();
+            return Outcome::Redetect;
+        }
+        Command::Delete => view.delete_selection(),
+        Command::SelectAll => view.select_all(),
+        Command::ConvertEol(eol) => {
+            // Convert the existing line endings and set the mode for new
+            // keystrokes.  The editor reports the edit as a save-point change,
+            // so `dirty` follows through the notification path.
+            view.convert_eols(eol);
+            view.set_eol_mode(eol);
+            app.active_doc_mut().eol = eol;
+            return Outcome::Updated;
+        }
+        Command::ToggleWordWrap => {
+            let doc = app.active_doc_mut();
+            if doc.degraded.no_wrap {
+                return Outcome::Done;
+            }
+            doc.word_wrap = !doc.word_wrap;
+            view.set_word_wrap(doc.word_wrap);
+            return Outcome::Updated;
+        }
+        _ => return Outcome::Host,
+    }
+    Outcome::Done
+}
+
+/// Whether `cmd` has anything to act on in `doc`, shown in `view`, for
+/// graying its menu item and toolbar button.  `clipboard_text` says whether
+/// the clipboard holds text.  Commands without a condition are always
+/// enabled.
+pub(crate) fn is_enabled(
+    cmd: Command,
+    doc: &DocumentState,
+    view: &dyn EditorView,
+    clipboard_text: bool,
+) -> bool {
+    let selection = view.selection_start() != view.selection_end();
+    match cmd {
+        Command::Undo => view.can_undo(),
+        Command::Redo => view.can_redo(),
+        Command::Cut | Command::Delete => selection && !doc.read_only,
+        Command::Copy => selection,
+        Command::Paste => clipboard_text && !doc.read_only,
+        Command::FileSave => doc.dirty,
+        Command::AllowLongLines => doc.degraded.long_lines,
+        _ => true,
+    }
+}
+
+/// Paste entry `i` of the clipboard ring over the selection as one undo
+/// step, leaving the caret after it.  The entry becomes the newest.
+pub(crate) fn paste_from_history(app: &mut App, view: &dyn EditorView, i: usize) -> Outcome {
+    let Some(text) = app.clip_ring.take(i) else {
+        return Outcome::Done;
+    };
+    let start = view.selection_start();
+    view.begin_undo_action();
+    view.set_target(start, view.selection_end());
+    let len = view.replace_target(&text);
+    view.end_undo_action();
+    view.set_caret_pos(start + len);
+    Outcome::Redetect
+}
+
+/// Show a freshly opened document: `utf8` is the text `App::open_file`
+/// returned for `doc`.  Leaves the view clean (at its save point).
+pub(crate) fn show_document(doc: &DocumentState, view: &dyn EditorView, utf8: &[u8]) {
+    view.set_eol_mode(doc.eol);
+    view.set_indentation(doc.indent);
+    view.set_word_wrap(true);
+    view.set_text(utf8);
+    view.set_save_point();
+}
+
+/// Turn the last remaining tab back into an empty untitled document instead
+/// of closing it.  The window re-applies highlighting and chrome afterwards.
+pub(crate) fn reset_to_untitled(doc: &mut DocumentState, view: &dyn EditorView) {
+    *doc = DocumentState {
+        word_wrap: true,
+        ..DocumentState::new_untitled()
+    };
+    // Must precede set_text: the editor ignores new text while read-only.
+    view.set_read_only(false);
+    view.set_eol_mode(EolMode::Crlf);
+    view.set_indentation(Indentation::default());
+    view.set_word_wrap(true);
+    view.set_text(b"");
+    view.set_save_point();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{editor::memory::MemoryView, languages::Language};
+
+    #[test]
+    fn cut_then_paste_moves_selection() {
+        let mut app = App::new();
+        let view = MemoryView::with_text(b"hello world");
+        view.set_sel(0, 6);
+        assert_eq!(execute(Command::Cut, &mut app, &view), Outcome::Done);
+        assert_eq!(view.get_text(), b"world");
+        view.set_caret_pos(5);
+        assert_eq!(execute(Command::Paste, &mut app, &view), Outcome::Redetect);
+        assert_eq!(view.get_text(), b"worldhello ");
+    }
+
+    #[test]
+    fn cuts_and_copies_fill_the_ring_for_paste_from_history() {
+        let mut app = App::new();
+        let view = MemoryView::with_text(b"one two three");
+        view.set_sel(0, 3);
+        execute(Command::Copy, &mut app, &view);
+        view.set_sel(4, 8);
+        execute(Command::Cut, &mut app, &view);
+        assert_eq!(view.get_text(), b"one three");
+        assert_eq!(
+            execute(Command::PasteFromHistory, &mut app, &view),
+            Outcome::Host
+        );
+
+        view.set_sel(0, 3);
+        let out = paste_from_history(&mut app, &view, 1);
+        assert_eq!(out, Outcome::Redetect);
+        assert_eq!(view.get_text(), b"one three");
+        assert_eq!(view.caret_pos(), 3);
+        assert_eq!(app.clip_ring.entries()[0], b"one");
+        view.set_caret_pos(0);
+        paste_from_history(&mut app, &view, 1);
+        assert_eq!(view.get_text(), b"two one three");
+    }
+
+    #[test]
+    fn editing_commands_need_something_to_act_on() {
+        let mut doc = DocumentState::new_untitled();
+        let view = MemoryView::with_text(b"abc");
+        let enabled = |cmd, doc: &DocumentState, clip| is_enabled(cmd, doc, &view, clip);
+        for cmd in [Command::Cut, Command::Copy, Command::Delete, Command::Undo] {
+            assert!(!enabled(cmd, &doc, true));
+        }
+        assert!(!enabled(Command::Paste, &doc, false));
+        assert!(enabled(Command::Paste, &doc, true));
+        assert!(!enabled(Command::FileSave, &doc, true));
+        assert!(enabled(Command::SelectAll, &doc, false));
+        assert!(!enabled(Command::AllowLongLines, &doc, false));
+
+        view.set_sel(0, 2);
+        doc.dirty = true;
+        assert!(enabled(Command::Copy, &doc, false));
+        assert!(enabled(Command::FileSave, &doc, false));
+        doc.read_only = true;
+        assert!(!enabled(Command::Cut, &doc, true));
+        assert!(!enabled(Command::Paste, &doc, true));
+        assert!(enabled(Command::Copy, &doc, true));
+
+        view.type_text(b"x");
+        assert!(enabled(Command::Undo, &doc, false));
+        assert!(!enabled(Command::Redo, &doc, false));
+    }
+
+    #[test]
+    fn select_all_then_delete_empties_document() {
+        let mut app = App::new();
+        let view = MemoryView::with_text(b"abc");
+        execute(Command::SelectAll, &mut app, &view);
+        execute(Command::Delete, &mut app, &view);
+        assert_eq!(view.doc_len(), 0);
+    }
+
+    #[test]
+    fn convert_eol_updates_document_and_view() {
+        let mut app = App::new();
+        let view = MemoryView::with_text(b"a\r\nb\r\n");
+        let out = execute(Command::ConvertEol(EolMode::Lf), &mut app, &view);
+        assert_eq!(out, Outcome::Updated);
+        assert_eq!(view.get_text(), b"a\nb\n");
+        assert_eq!(view.eol_mode(), EolMode::Lf);
+        assert_eq!(app.active_doc().eol, EolMode::Lf);
+    }
+
+    #[test]
+    fn word_wrap_toggles_active_document() {
+        let mut app = App::new();
+        app.active_idx = app.push_untitled();
+        let view = MemoryView::default();
+        execute(Command::ToggleWordWrap, &mut app, &view);
+        assert!(app.tabs[1].word_wrap);
+        assert!(!app.tabs[0].word_wrap);
+        assert!(view.is_word_wrap());
+        execute(Command::ToggleWordWrap, &mut app, &view);
+        assert!(!app.tabs[1].word_wrap);
+        assert!(!view.is_word_wrap());
+
+        // Too large to wrap.
+        app.active_doc_mut().degraded.no_wrap = true;
+        execute(Command::ToggleWordWrap, &mut app, &view);
+        assert!(!app.tabs[1].word_wrap);
+        assert!(!view.is_word_wrap());
+    }
+
+    #[test]
+    fn window_commands_are_left_to_the_host() {
+        let mut app = App::new();
+        let view = MemoryView::with_text(b"x");
+        for cmd in [Command::FileOpen, Command::FileClose, Command::Tool(0)] {
+            assert_eq!(execute(cmd, &mut app, &view), Outcome::Host);
+        }
+        assert_eq!(view.get_text(), b"x");
+    }
+
+    #[test]
+    fn reset_clears_read_only_file() {
+        let mut app = App::new();
+        app.open_file(PathBuf::from(r"C:\lib.rs"), b"fn main() {}\n", None);
+        app.active_doc_mut().read_only = true;
+        app.active_doc_mut().dirty = true;
+        let view = MemoryView::with_text(b"fn main() {}\n");
+        view.set_read_only(true);
+        reset_to_untitled(app.active_doc_mut(), &view);
+        let doc = app.active_doc();
+        assert!(doc.path.is_none());
+        assert!(!doc.dirty && !doc.read_only);
+        assert_eq!(doc.language, Language::PlainText);
+        assert_eq!(view.doc_len(), 0);
+        assert!(view.is_word_wrap());
+    }
+}
@@ -0,0 +1,779 @@
+// ── User configuration ────────────────────────────────────────────────────────
+//
+// Reads and writes `%APPDATA%\Rivet\settings.json`.
+// Unlike `session.json` (which mirrors whatever happens to be open), this file
+// holds preferences the user edits deliberately.  No `unsafe` — pure safe
+// Rust + serde_json.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::Degraded,
+    editor::{LARGE_FILE_THRESHOLD_BYTES, LONG_LINE_THRESHOLD_BYTES},
+    tools::ExternalTool,
+};
+
+// ── On-disk types ─────────────────────────────────────────────────────────────
+
+/// Root of the JSON settings file.
+///
+/// Every field carries `#[serde(default)]` so that files written by older
+/// builds (or edited by hand with keys removed) still parse.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Settings {
+    pub(crate) version: u32,
+    /// Entries shown in the Tools menu, in menu order.
+    #[serde(default = "crate::tools::default_tools")]
+    pub(crate) tools: Vec<ExternalTool>,
+    /// Command line last entered in Tools > Run…, pre-filled next time.
+    #[serde(default)]
+    pub(crate) last_run_command: String,
+    /// Keep the menu bar detached until Alt (or an Alt+letter mnemonic) is
+    /// pressed.
+    #[serde(default)]
+    pub(crate) auto_hide_menu: bool,
+    /// Draw the tab strip in the caption area (tabs at the top only).
+    /// Implies an auto-hidden menu bar.
+    #[serde(default)]
+    pub(crate) tabs_in_title_bar: bool,
+    /// Wrap tabs that do not fit onto extra rows instead of scrolling them
+    /// (tabs at the top outside the title bar; side tabs always wrap).
+    #[serde(default)]
+    pub(crate) multiline_tabs: bool,
+    /// Show each tab's file-type icon (View > File Icons in Tabs).
+    #[serde(default)]
+    pub(crate) tab_icons: bool,
+    /// Caret shape, width and blink rate for every editor view.
+    #[serde(default)]
+    pub(crate) caret: CaretSettings,
+    /// Derive the selection and caret-line colours from the Windows accent
+    /// colour.
+    #[serde(default)]
+    pub(crate) accent_highlight: bool,
+    /// Switch dark mode with the Windows app mode (Settings >
+    /// Personalisation > Colours).  View > Dark Mode turns this off, so a
+    /// manual choice sticks.
+    #[serde(default)]
+    pub(crate) follow_system_theme: bool,
+    /// When the editor drops its light / dark palettes for the Windows
+    /// system colours.
+    #[serde(default)]
+    pub(crate) high_contrast: HighContrast,
+    /// How a search that finds nothing is signalled.
+    #[serde(default)]
+    pub(crate) not_found_feedback: NotFoundFeedback,
+    /// Show paths relative to the open workspace's folder in tab tooltips.
+    #[serde(default = "default_true")]
+    pub(crate) workspace_relative_paths: bool,
+    /// Open the word-completion list once this many word characters have
+    /// been typed; 0 = only on Edit > Complete Word.
+    #[serde(default)]
+    pub(crate) autocomplete_after: u32,
+    /// Insert the closing bracket or quote when an opening one is typed.
+    #[serde(default)]
+    pub(crate) auto_close_brackets: bool,
+    /// Extensions registered to open with Rivet (Tools > File
+    /// Associations…), e.g. `.txt`; remembered so they can be unregistered.
+    #[serde(default)]
+    pub(crate) associated_extensions: Vec<String>,
+    /// Whether the last session's tabs reopen at startup.
+    #[serde(default)]
+    pub(crate) restore_session: RestoreSession,
+    /// Exit without asking about unsaved changes; they are kept with the
+    /// session and come back, still unsaved, at the next start.
+    #[serde(default)]
+    pub(crate) keep_unsaved_on_exit: bool,
+    /// Keep an icon in the notification area; minimizing or closing the
+    /// window hides it there instead of to the taskbar or exiting.
+    #[serde(default)]
+    pub(crate) minimize_to_tray: bool,
+    /// System-wide hot key that opens the scratch tab, e.g. `"Win+Shift+N"`;
+    /// empty to register none.
+    #[serde(default = "default_scratch_hotkey")]
+    pub(crate) scratch_hotkey: String,
+    /// Box the other occurrences of the word at the caret.
+    #[serde(default = "default_true")]
+    pub(crate) highlight_occurrences: bool,
+    /// Expand `\n`, `\t`, `\xNN` and the other Extended mode escapes in
+    /// find and replace strings.
+    #[serde(default)]
+    pub(crate) search_extended: bool,
+    /// Recent find and replace strings for the Find / Replace dialogs.
+    #[serde(default)]
+    pub(crate) search_history: crate::search::history::SearchHistory,
+    /// Show the folder panel (View > Folder Panel).
+    #[serde(default)]
+    pub(crate) show_folder_panel: bool,
+    /// Folder the panel lists; chosen with File > Open Folder….
+    #[serde(default)]
+    pub(crate) folder_panel_root: Option<PathBuf>,
+    /// Show the outline panel (View > Outline Panel).
+    #[serde(default)]
+    pub(crate) show_outline: bool,
+    /// Show the document map beside the editor (View > Document Map).
+    #[serde(default)]
+    pub(crate) show_document_map: bool,
+    /// Width last entered in Edit > Line Operations > Split Lines….
+    #[serde(default = "default_split_width")]
+    pub(crate) split_width: u32,
+    /// Windows code page for ANSI (non-Unicode) files, e.g. 1252 or 932;
+    /// 0 = the system's ANSI code page.
+    #[serde(default)]
+    pub(crate) ansi_code_page: u32,
+    /// File sizes above which documents drop features.
+    #[serde(default)]
+    pub(crate) large_files: LargeFileSettings,
+    /// The optional toolbar below the menu bar.
+    #[serde(default)]
+    pub(crate) toolbar: ToolbarSettings,
+    /// How editor views draw and style; set per document as it opens.
+    #[serde(default)]
+    pub(crate) rendering: RenderSettings,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_split_width() -> u32 {
+    80
+}
+
+fn default_scratch_hotkey() -> String {
+    crate::scratch::DEFAULT_HOTKEY.to_owned()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            tools: crate::tools::default_tools(),
+            last_run_command: String::new(),
+            auto_hide_menu: false,
+            tabs_in_title_bar: false,
+            multiline_tabs: false,
+            tab_icons: false,
+            caret: CaretSettings::default(),
+            accent_highlight: false,
+            follow_system_theme: false,
+            high_contrast: HighContrast::default(),
+            not_found_feedback: NotFoundFeedback::default(),
+            workspace_relative_paths: true,
+            autocomplete_after: 0,
+            auto_close_brackets: false,
+            associated_extensions: Vec::new(),
+            restore_session: RestoreSession::default(),
+            keep_unsaved_on_exit: false,
+            minimize_to_tray: false,
+            scratch_hotkey: default_scratch_hotkey(),
+            highlight_occurrences: true,
+            search_extended: false,
+            search_history: crate::search::history::SearchHistory::default(),
+            show_folder_panel: false,
+            folder_panel_root: None,
+            show_outline: false,
+            show_document_map: false,
+            split_width: default_split_width(),
+            ansi_code_page: 0,
+            large_files: LargeFileSettings::default(),
+            toolbar: ToolbarSettings::default(),
+            rendering: RenderSettings::default(),
+        }
+    }
+}
+
+/// Caret shape.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CaretStyle {
+    /// Thin vertical bar between characters.
+    #[default]
+    Line,
+    /// Box over the character after the caret.
+    Block,
+}
+
+/// When to draw with the Windows system colours.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum HighContrast {
+    /// While Windows High Contrast mode is on.
+    #[default]
+    Auto,
+    /// Always, even without High Contrast mode.
+    Always,
+    /// Never; keep the light / dark palette.
+    Never,
+}
+
+impl HighContrast {
+    /// Whether system colours are used, given whether Windows is in High
+    /// Contrast mode.
+    pub(crate) fn applies(self, system_high_contrast: bool) -> bool {
+        match self {
+            Self::Auto => system_high_contrast,
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Signal for a search that finds nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum NotFoundFeedback {
+    /// System default sound.
+    #[default]
+    Beep,
+    /// "Not found" in the status bar for a moment.
+    Status,
+    /// Flash the window caption and taskbar button.
+    Taskbar,
+    /// Nothing.
+    Silent,
+}
+
+/// What happens to the previous session's tabs at startup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RestoreSession {
+    /// Reopen them.
+    #[default]
+    Always,
+    /// Start with one empty tab.
+    Never,
+    /// Ask each time.
+    Ask,
+}
+
+/// File sizes, in bytes, above which a document gives up features, so a
+/// fast machine can keep them for bigger files.  Every size limit but
+/// `undo_max` defaults to `LARGE_FILE_THRESHOLD_BYTES`; `line_length_max`
+/// limits a single line instead.  Files above
+/// `HUGE_FILE_THRESHOLD_BYTES` open in the viewer whatever these say.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct LargeFileSettings {
+    /// Large File Mode: read in the background, `[Large]` in the status bar,
+    /// no content in session checkpoints.
+    pub(crate) threshold: u64,
+    /// Syntax highlighting.
+    pub(crate) highlighting_max: u64,
+    /// Word wrap.
+    pub(crate) word_wrap_max: u64,
+    /// Word completion and occurrence highlighting, which scan the text.
+    pub(crate) scan_max: u64,
+    /// Undo history, which can grow to several times the document.  No
+    /// limit by default.
+    pub(crate) undo_max: u64,
+    /// Longest line still wrapped and highlighted; a file with a longer one
+    /// opens without either, however small.  Defaults to
+    /// `LONG_LINE_THRESHOLD_BYTES`.
+    pub(crate) line_length_max: u64,
+}
+
+impl LargeFileSettings {
+    /// What a document of `size` bytes does without.
+    pub(crate) fn degraded(&self, size: u64) -> Degraded {
+        Degraded {
+            large_file: size > self.threshold,
+            plain_text: size > self.highlighting_max,
+            no_wrap: size > self.word_wrap_max,
+            no_scan: size > self.scan_max,
+            no_undo: size > self.undo_max,
+            long_lines: false,
+        }
+    }
+
+    /// `degraded`, plus what a document whose longest line is `longest`
+    /// bytes does without.
+    pub(crate) fn with_long_lines(&self, degraded: Degraded, longest: u64) -> Degraded {
+        if longest <= self.line_length_max {
+            return degraded;
+        }
+        Degraded {
+            long_lines: true,
+            plain_text: true,
+            no_wrap: true,
+            ..degraded
+        }
+    }
+}
+
+impl Default for LargeFileSettings {
+    fn default() -> Self {
+        Self {
+            threshold: LARGE_FILE_THRESHOLD_BYTES,
+            highlighting_max: LARGE_FILE_THRESHOLD_BYTES,
+            word_wrap_max: LARGE_FILE_THRESHOLD_BYTES,
+            scan_max: LARGE_FILE_THRESHOLD_BYTES,
+            undo_max: u64::MAX,
+            line_length_max: LONG_LINE_THRESHOLD_BYTES,
+        }
+    }
+}
+
+/// Scintilla's drawing and styling options.  `auto` (or, for
+/// `buffered_draw`, no value) leaves the choice to the document's size:
+/// from `auto_above` bytes a document is drawn with DirectWrite, unbuffered,
+/// and styled beyond the visible lines while idle, which keeps scrolling a
+/// big highlighted file smooth.  Smaller ones keep Scintilla's defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct RenderSettings {
+    pub(crate) technology: Technology,
+    /// Draw each line off-screen first.  DirectWrite buffers on its own, so
+    /// by default only GDI does.
+    pub(crate) buffered_draw: Option<bool>,
+    pub(crate) idle_styling: IdleStyling,
+    pub(crate) phases_draw: PhasesDraw,
+    /// Size from which a document gets the big-file choice for each `auto`.
+    pub(crate) auto_above: u64,
+}
+
+impl RenderSettings {
+    /// The choices for a document of `size` bytes.
+    pub(crate) fn resolve(&self, size: u64) -> Rendering {
+        let big = size >= self.auto_above;
+        let direct_write = match self.technology {
+            Technology::Auto => big,
+            Technology::Gdi => false,
+            Technology::DirectWrite => true,
+        };
+        let idle_styling = match self.idle_styling {
+            IdleStyling::Auto if big => IdleStyling::AfterVisible,
+            IdleStyling::Auto => IdleStyling::None,
+            choice => choice,
+        };
+        let phases_draw = match self.phases_draw {
+            PhasesDraw::Auto => PhasesDraw::Two,
+            choice => choice,
+        };
+        Rendering {
+            direct_write,
+            buffered_draw: self.buffered_draw.unwrap_or(!direct_write),
+            idle_styling,
+            phases_draw,
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            technology: Technology::Auto,
+            buffered_draw: None,
+            idle_styling: IdleStyling::Auto,
+            phases_draw: PhasesDraw::Auto,
+            auto_above: RENDER_AUTO_ABOVE_BYTES,
+        }
+    }
+}
+
+/// Default for `RenderSettings::auto_above`.
+const RENDER_AUTO_ABOVE_BYTES: u64 = 1_024 * 1_024; // 1 MiB
+
+/// What draws the text (`SCI_SETTECHNOLOGY`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Technology {
+    /// DirectWrite for big documents, GDI otherwise.
+    #[default]
+    Auto,
+    Gdi,
+    /// Direct2D and DirectWrite: hardware-accelerated where available.
+    DirectWrite,
+}
+
+/// How much text is styled in the background (`SCI_SETIDLESTYLING`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum IdleStyling {
+    /// `after_visible` for big documents, `none` otherwise.
+    #[default]
+    Auto,
+    /// Style everything up to what is shown before drawing it.
+    None,
+    /// Draw the visible lines first and style the text before them while
+    /// idle.
+    ToVisible,
+    /// Style the text after the visible lines while idle too.
+    AfterVisible,
+    /// Style the whole document while idle.
+    All,
+}
+
+/// How many passes draw a line (`SCI_SETPHASESDRAW`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PhasesDraw {
+    /// `two` whatever the size.
+    #[default]
+    Auto,
+    /// Backgrounds of all lines, then text: the Scintilla default.
+    Two,
+    /// Each layer over the whole view in turn; slower, but indicators and
+    /// carets are never overdrawn by neighbouring lines.
+    Multiple,
+}
+
+/// `RenderSettings` resolved for one document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rendering {
+    pub(crate) direct_write: bool,
+    pub(crate) buffered_draw: bool,
+    /// Never `Auto`.
+    pub(crate) idle_styling: IdleStyling,
+    /// Never `Auto`.
+    pub(crate) phases_draw: PhasesDraw,
+}
+
+/// The toolbar (View > Toolbar) and its buttons.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ToolbarSettings {
+    pub(crate) visible: bool,
+    /// Command-palette names (`"file.save"`) in button order; `"|"` is a
+    /// separator.  Unknown names are skipped.
+    pub(crate) buttons: Vec<String>,
+}
+
+impl Default for ToolbarSettings {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            buttons: crate::ui::toolbar::default_buttons(),
+        }
+    }
+}
+
+/// Caret appearance and movement shared by all editor views.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct CaretSettings {
+    pub(crate) style: CaretStyle,
+    /// Width of the line caret in pixels; ignored by the block caret.
+    pub(crate) width: u32,
+    /// Blink half-period in milliseconds; 0 keeps the caret steady.
+    pub(crate) blink_ms: u32,
+    /// Home goes to the first non-blank character, then to column 0.
+    pub(crate) smart_home: bool,
+    /// Lines kept visible above and below the caret when scrolling.
+    pub(crate) scroll_margin: u32,
+    /// Let the caret move past the end of a line.
+    pub(crate) virtual_space: bool,
+    /// Insert switches between insert and overtype; off for those who
+    /// press it by accident.
+    pub(crate) insert_key: bool,
+}
+
+impl CaretSettings {
+    /// Widest line caret offered.
+    pub(crate) const MAX_WIDTH: u32 = 3;
+    /// Slowest blink offered.
+    pub(crate) const MAX_BLINK_MS: u32 = 5000;
+    /// Widest scroll margin offered.
+    pub(crate) const MAX_SCROLL_MARGIN: u32 = 20;
+
+    /// Copy with the numeric fields pulled into their supported ranges,
+    /// for values typed by hand into `settings.json`.
+    pub(crate) fn clamped(self) -> Self {
+        Self {
+            width: self.width.clamp(1, Self::MAX_WIDTH),
+            blink_ms: self.blink_ms.min(Self::MAX_BLINK_MS),
+            scroll_margin: self.scroll_margin.min(Self::MAX_SCROLL_MARGIN),
+            ..self
+        }
+    }
+}
+
+impl Default for CaretSettings {
+    fn default() -> Self {
+        Self {
+            style: CaretStyle::Line,
+            width: 1,
+            // The Windows default caret blink time.
+            blink_ms: 530,
+            smart_home: true,
+            scroll_margin: 0,
+            virtual_space: false,
+            insert_key: true,
+        }
+    }
+}
+
+// ── Format version ────────────────────────────────────────────────────────────
+
+const SETTINGS_VERSION: u32 = 1;
+
+// ── Path ──────────────────────────────────────────────────────────────────────
+
+/// Return the path to the settings file: `%APPDATA%\Rivet\settings.json`.
+///
+/// Returns `None` if the `APPDATA` environment variable is not set.
+pub(crate) fn settings_path() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("settings.json");
+    Some(p)
+}
+
+// ── Save ──────────────────────────────────────────────────────────────────────
+
+/// Write `settings` to `%APPDATA%\Rivet\settings.json`.
+///
+/// Creates the `Rivet` directory if it does not exist.
+pub(crate) fn save(settings: &Settings) -> io::Result<()> {
+    let path = settings_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+    save_to(&path, settings)
+}
+
+/// Write `settings` to `path` through a temporary file renamed over it, so
+/// a crash mid-write leaves the previous settings intact.
+fn save_to(path: &Path, settings: &Settings) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = sibling(path, ".tmp");
+    let file = fs::File::create(&tmp)?;
+    serde_json::to_writer_pretty(&file, settings).map_err(io::Error::other)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// ── Load ──────────────────────────────────────────────────────────────────────
+
+/// Read and parse the settings file.
+///
+/// Returns `Settings::default()` if the file is missing or unusable.  A
+/// file that exists but fails to parse, or has an unrecognised version, is
+/// first moved aside to `settings.json.bad`, so saving the defaults later
+/// cannot destroy a hand edit with a typo in it.
+pub(crate) fn load() -> Settings {
+    settings_path()
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+}
+
+fn load_from(path: &Path) -> Settings {
+    let Ok(data) = fs::read(path) else {
+        return Settings::default();
+    };
+    match serde_json::from_slice::<Settings>(&data) {
+        Ok(s) if s.version == SETTINGS_VERSION => s,
+        _ => {
+            let _ = fs::rename(path, sibling(path, ".bad"));
+            Settings::default()
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unparsable_settings_are_kept_aside() {
+        let dir = std::env::temp_dir().join(format!("rivet-settings-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("settings.json");
+        let s = Settings {
+            tools: Vec::new(),
+            ..Settings::default()
+        };
+        save_to(&path, &s).expect("save");
+        assert!(load_from(&path).tools.is_empty());
+        assert!(!sibling(&path, ".tmp").exists());
+
+        fs::write(&path, br#"{"version":1,"tools":[}"#).unwrap();
+        assert!(!load_from(&path).tools.is_empty());
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read(sibling(&path, ".bad")).unwrap(),
+            br#"{"version":1,"tools":[}"#
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn roundtrip_tools() {
+        let s = Settings {
+            tools: vec![ExternalTool {
+                name: "Echo".to_owned(),
+                command: "cmd /c echo $(FILE_NAME)".to_owned(),
+            }],
+            ..Settings::default()
+        };
+        let json = serde_json::to_string(&s).expect("serialize");
+        let s2: Settings = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(s2.tools.len(), 1);
+        assert_eq!(s2.tools[0].name, "Echo");
+        assert_eq!(s2.tools[0].command, "cmd /c echo $(FILE_NAME)");
+    }
+
+    /// A settings file without a `tools` key gets the built-in defaults.
+    #[test]
+    fn missing_tools_gets_defaults() {
+        let s: Settings = serde_json::from_str(r#"{"version":1}"#).expect("deserialize");
+        assert!(!s.tools.is_empty());
+    }
+
+    #[test]
+    fn large_file_limits_apply_separately() {
+        let s: Settings = serde_json::from_str(
+            r#"{"version":1,"large_files":{"highlighting_max":100,"word_wrap_max":200}}"#,
+        )
+        .expect("parse");
+        let limits = s.large_files;
+        assert_eq!(limits.threshold, LARGE_FILE_THRESHOLD_BYTES);
+        assert_eq!(limits.degraded(100), Degraded::default());
+        let d = limits.degraded(150);
+        assert!(d.plain_text && !d.no_wrap && !d.large_file && !d.no_scan);
+        assert_eq!(
+            limits.degraded(LARGE_FILE_THRESHOLD_BYTES + 1),
+            Degraded {
+                no_undo: false,
+                ..Degraded::ALL
+            }
+        );
+    }
+
+    #[test]
+    fn undo_limit_is_opt_in() {
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"large_files":{"undo_max":100}}"#).expect("parse");
+        assert!(!s.large_files.degraded(100).no_undo);
+        assert!(s.large_files.degraded(101).no_undo);
+        assert!(!LargeFileSettings::default().degraded(u64::MAX).no_undo);
+    }
+
+    #[test]
+    fn rendering_follows_size_unless_chosen() {
+        let auto = RenderSettings::default();
+        let small = auto.resolve(1_000);
+        assert!(!small.direct_write && small.buffered_draw);
+        assert_eq!(small.idle_styling, IdleStyling::None);
+        assert_eq!(small.phases_draw, PhasesDraw::Two);
+        let big = auto.resolve(RENDER_AUTO_ABOVE_BYTES);
+        assert!(big.direct_write && !big.buffered_draw);
+        assert_eq!(big.idle_styling, IdleStyling::AfterVisible);
+
+        let s: Settings = serde_json::from_str(
+            r#"{"version":1,"rendering":{"technology":"gdi","buffered_draw":false,
+                "idle_styling":"to_visible","phases_draw":"multiple"}}"#,
+        )
+        .expect("parse");
+        let chosen = s.rendering.resolve(u64::MAX);
+        assert!(!chosen.direct_write && !chosen.buffered_draw);
+        assert_eq!(chosen.idle_styling, IdleStyling::ToVisible);
+        assert_eq!(chosen.phases_draw, PhasesDraw::Multiple);
+    }
+
+    #[test]
+    fn long_lines_degrade_whatever_the_size() {
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"large_files":{"line_length_max":80}}"#)
+                .expect("parse");
+        let limits = s.large_files;
+        assert_eq!(
+            limits.with_long_lines(Degraded::default(), 80),
+            Degraded::default()
+        );
+        let d = limits.with_long_lines(Degraded::default(), 81);
+        assert!(d.long_lines && d.plain_text && d.no_wrap);
+        assert!(!d.large_file && !d.no_scan && !d.no_undo);
+        assert_eq!(
+            LargeFileSettings::default().line_length_max,
+            LONG_LINE_THRESHOLD_BYTES
+        );
+    }
+
+    #[test]
+    fn missing_caret_gets_defaults() {
+        let s: Settings = serde_json::from_str(r#"{"version":1}"#).expect("deserialize");
+        assert_eq!(s.caret, CaretSettings::default());
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"caret":{"style":"block"}}"#).expect("parse");
+        assert_eq!(s.caret.style, CaretStyle::Block);
+        assert_eq!(s.caret.width, 1);
+        assert!(s.caret.smart_home);
+        assert!(!s.caret.virtual_space);
+        assert!(s.caret.insert_key);
+    }
+
+    #[test]
+    fn not_found_feedback_parses_and_defaults() {
+        let s: Settings = serde_json::from_str(r#"{"version":1}"#).expect("deserialize");
+        assert_eq!(s.not_found_feedback, NotFoundFeedback::Beep);
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"not_found_feedback":"status"}"#).expect("parse");
+        assert_eq!(s.not_found_feedback, NotFoundFeedback::Status);
+    }
+
+    #[test]
+    fn autocomplete_popup_off_by_default() {
+        let s: Settings = serde_json::from_str(r#"{"version":1}"#).expect("deserialize");
+        assert_eq!(s.autocomplete_after, 0);
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"autocomplete_after":3}"#).expect("parse");
+        assert_eq!(s.autocomplete_after, 3);
+    }
+
+    #[test]
+    fn caret_clamped_to_supported_range() {
+        let c = CaretSettings {
+            style: CaretStyle::Line,
+            width: 0,
+            blink_ms: 60_000,
+            scroll_margin: 1000,
+            ..CaretSettings::default()
+        }
+        .clamped();
+        assert_eq!(c.width, 1);
+        assert_eq!(c.blink_ms, CaretSettings::MAX_BLINK_MS);
+        assert_eq!(c.scroll_margin, CaretSettings::MAX_SCROLL_MARGIN);
+        let c = CaretSettings {
+            width: 9,
+            ..CaretSettings::default()
+        };
+        assert_eq!(c.clamped().width, CaretSettings::MAX_WIDTH);
+    }
+
+    #[test]
+    fn high_contrast_follows_windows_unless_overridden() {
+        let s: Settings = serde_json::from_str(r#"{"version":1}"#).expect("deserialize");
+        assert_eq!(s.high_contrast, HighContrast::Auto);
+        assert!(s.high_contrast.applies(true));
+        assert!(!s.high_contrast.applies(false));
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"high_contrast":"never"}"#).expect("parse");
+        assert!(!s.high_contrast.applies(true));
+        assert!(HighContrast::Always.applies(false));
+    }
+
+    #[test]
+    fn toolbar_buttons_default_when_missing() {
+        let s: Settings =
+            serde_json::from_str(r#"{"version":1,"toolbar":{"visible":true}}"#).expect("parse");
+        assert!(s.toolbar.visible);
+        assert_eq!(s.toolbar.buttons, crate::ui::toolbar::default_buttons());
+    }
+}
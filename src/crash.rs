@@ -0,0 +1,119 @@
+// ── Crash reports ─────────────────────────────────────────────────────────────
+//
+// Report files written to `%APPDATA%\Rivet\crashes\` when Rivet panics or
+// hits an unhandled exception, and the note that tells the next start one
+// was written.  Installing the hooks and writing the minidump is Win32 work
+// (`platform::win32::crash`); this module is the file layout.
+//
+// Unsaved text is not written here: a crash can leave editor state half
+// updated, so recovery relies on the backups the periodic session checkpoint
+// already keeps (see `session::write_backup`).
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// File in the crashes folder naming the latest report not yet shown.
+const PENDING_FILE: &str = "pending.txt";
+
+/// Return the crash-report folder: `%APPDATA%\Rivet\crashes`.
+pub(crate) fn crashes_dir() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("crashes");
+    Some(p)
+}
+
+/// File stem shared by one crash's report and minidump, e.g.
+/// `crash-1760550000`.
+pub(crate) fn report_stem(now: SystemTime) -> String {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    format!("crash-{secs}")
+}
+
+/// Text of a panic report.
+pub(crate) fn panic_report(message: &str, location: Option<&str>, backtrace: &str) -> String {
+    format!(
+        "Rivet {} panicked\n\nMessage:  {message}\nLocation: {}\n\nBacktrace:\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        location.unwrap_or("unknown"),
+    )
+}
+
+/// Text of a report for an unhandled structured exception.
+pub(crate) fn exception_report(code: u32, address: usize, dump: Option<&Path>) -> String {
+    let dump = dump.map_or_else(|| "not written".to_owned(), |p| p.display().to_string());
+    format!(
+        "Rivet {} crashed\n\nException: 0x{code:08X}\nAddress:   0x{address:X}\nMinidump:  {dump}\n",
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// Write `text` as `<stem>.txt` in `dir` and mark it pending for the next
+/// start.  Returns the report's path.
+pub(crate) fn write_report(dir: &Path, stem: &str, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{stem}.txt"));
+    fs::write(&path, text)?;
+    fs::write(dir.join(PENDING_FILE), path.to_string_lossy().as_bytes())?;
+    Ok(path)
+}
+
+/// The report written since the last start, if any.  Clears the mark, so
+/// each report is offered once.
+pub(crate) fn take_pending(dir: &Path) -> Option<PathBuf> {
+    let marker = dir.join(PENDING_FILE);
+    let text = fs::read_to_string(&marker).ok()?;
+    let _ = fs::remove_file(&marker);
+    let path = PathBuf::from(text.trim());
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn stem_uses_unix_seconds() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_760_550_000);
+        assert_eq!(report_stem(t), "crash-1760550000");
+    }
+
+    #[test]
+    fn panic_report_names_location() {
+        let text = panic_report("boom", Some("src/app.rs:10:5"), "<frames>");
+        assert!(text.contains("Message:  boom"));
+        assert!(text.contains("Location: src/app.rs:10:5"));
+        assert!(text.ends_with("<frames>\n"));
+        assert!(panic_report("x", None, "").contains("Location: unknown"));
+    }
+
+    #[test]
+    fn exception_report_formats_code() {
+        let text = exception_report(0xC000_0005, 0x7FF6_1234, None);
+        assert!(text.contains("Exception: 0xC0000005"));
+        assert!(text.contains("Address:   0x7FF61234"));
+        assert!(text.contains("Minidump:  not written"));
+    }
+
+    #[test]
+    fn pending_report_is_taken_once() {
+        let dir = std::env::temp_dir().join(format!("rivet-crash-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(take_pending(&dir), None);
+
+        let path = write_report(&dir, "crash-1", "report").expect("write");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "report");
+        assert_eq!(take_pending(&dir), Some(path));
+        assert_eq!(take_pending(&dir), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,309 @@
+// ── Line diff ─────────────────────────────────────────────────────────────────
+//
+// Myers' O(ND) difference algorithm over lines, and the unified-diff text
+// that Tools > Compare With… shows in a read-only tab highlighted by the Diff
+// lexer.  Lines are compared without their EOLs, so a CRLF and an LF copy of
+// the same file compare equal.  No `unsafe` — pure safe Rust.
+
+/// Unchanged lines shown around each change.
+pub(crate) const CONTEXT: usize = 3;
+
+/// Edit distance past which the search gives up and reports the rest of the
+/// differing region as one replaced block.  The search keeps about
+/// `MAX_COST²/2` positions, so this bounds its memory.
+const MAX_COST: usize = 4000;
+
+/// One step of an edit script; the indices are 0-based line numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Edit {
+    /// Line `.0` of the old text is line `.1` of the new text.
+    Keep(usize, usize),
+    /// Line `.0` of the old text was removed.
+    Delete(usize),
+    /// Line `.0` of the new text was added.
+    Insert(usize),
+}
+
+/// Split `text` into lines without their `\r\n` / `\n` endings.  A final
+/// line ending does not start another (empty) line.
+pub(crate) fn split_lines(text: &[u8]) -> Vec<&[u8]> {
+    text.split_inclusive(|&b| b == b'\n')
+        .map(|l| {
+            let l = l.strip_suffix(b"\n").unwrap_or(l);
+            l.strip_suffix(b"\r").unwrap_or(l)
+        })
+        .collect()
+}
+
+/// Shortest edit script turning `old` into `new`.
+pub(crate) fn diff(old: &[&[u8]], new: &[&[u8]]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    match shortest_path(a, b) {
+        Some(middle) => edits.extend(middle.into_iter().map(|e| match e {
+            Edit::Keep(x, y) => Edit::Keep(prefix + x, prefix + y),
+            Edit::Delete(x) => Edit::Delete(prefix + x),
+            Edit::Insert(y) => Edit::Insert(prefix + y),
+        })),
+        None => {
+            edits.extend((0..a.len()).map(|x| Edit::Delete(prefix + x)));
+            edits.extend((0..b.len()).map(|y| Edit::Insert(prefix + y)));
+        }
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    edits.extend((0..suffix).map(|i| Edit::Keep(old_end + i, new_end + i)));
+    edits
+}
+
+/// Furthest x reached on diagonal `k` in the round that produced `row`
+/// (`row[i]` is diagonal `2i - d`), or -1 if none.
+fn reach(row: &[isize], d: isize, k: isize) -> isize {
+    if k < -d || k > d {
+        return -1;
+    }
+    row[((k + d) / 2) as usize]
+}
+
+/// Where round `d` enters diagonal `k` from round `d - 1`: the x before the
+/// snake and the diagonal it came from, or `None` if neither neighbour can
+/// step onto `k` inside the `n` × `m` grid.
+fn step(prev: &[isize], d: isize, k: isize, n: isize, m: isize) -> Option<(isize, isize)> {
+    // Down from k + 1 (an insertion) keeps x; right from k - 1 (a deletion)
+    // adds one.
+    let down = reach(prev, d - 1, k + 1);
+    let down = (down >= 0 && down - k <= m).then_some(down);
+    let right = reach(prev, d - 1, k - 1);
+    let right = (right >= 0 && right < n).then_some(right + 1);
+    match (down, right) {
+        (Some(x), Some(r)) if x >= r => Some((x, k + 1)),
+        (_, Some(r)) => Some((r, k - 1)),
+        (Some(x), None) => Some((x, k + 1)),
+        (None, None) => None,
+    }
+}
+
+/// Myers' greedy forward search plus backtrack.  `None` if the edit
+/// distance exceeds [`MAX_COST`].
+fn shortest_path(a: &[&[u8]], b: &[&[u8]]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let snake = |mut x: isize, k: isize| {
+        while x < n && x - k < m && a[x as usize] == b[(x - k) as usize] {
+            x += 1;
+        }
+        x
+    };
+
+    let mut trace: Vec<Vec<isize>> = vec![vec![snake(0, 0)]];
+    let mut d: isize = 0;
+    while reach(&trace[d as usize], d, n - m) != n {
+        if d as usize >= MAX_COST {
+            return None;
+        }
+        d += 1;
+        let prev = &trace[d as usize - 1];
+        let row = (-d..=d)
+            .step_by(2)
+            .map(|k| step(prev, d, k, n, m).map_or(-1, |(x, _)| snake(x, k)))
+            .collect();
+        trace.push(row);
+    }
+
+    // Walk back from (n, m), emitting edits in reverse.
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=d).rev() {
+        let k = x - y;
+        let (start, from) = step(&trace[d as usize - 1], d, k, n, m)?;
+        while x > start {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if from == k + 1 {
+            y -= 1;
+            edits.push(Edit::Insert(y as usize));
+        } else {
+            x -= 1;
+            edits.push(Edit::Delete(x as usize));
+        }
+    }
+    while x > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push(Edit::Keep(x as usize, y as usize));
+    }
+    edits.reverse();
+    Some(edits)
+}
+
+/// Unified diff of `old` against `new` with [`CONTEXT`] lines around each
+/// change, headed `--- old_label` / `+++ new_label`.  `None` if every line
+/// matches.
+pub(crate) fn unified(old_label: &str, new_label: &str, old: &[u8], new: &[u8]) -> Option<Vec<u8>> {
+    let (a, b) = (split_lines(old), split_lines(new));
+    let edits = diff(&a, &b);
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Keep(..)))
+        .map(|(i, _)| i)
+        .collect();
+    let first = *changes.first()?;
+
+    // Group changes separated by at most 2 × CONTEXT unchanged lines.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    let mut start = first;
+    let mut end = first;
+    for &i in &changes[1..] {
+        if i - end > 2 * CONTEXT + 1 {
+            hunks.push((start, end));
+            start = i;
+        }
+        end = i;
+    }
+    hunks.push((start, end));
+
+    // Lines of each side consumed before edit `i`.
+    let mut before = Vec::with_capacity(edits.len() + 1);
+    let (mut oi, mut ni) = (0, 0);
+    for e in &edits {
+        before.push((oi, ni));
+        match e {
+            Edit::Keep(..) => (oi, ni) = (oi + 1, ni + 1),
+            Edit::Delete(_) => oi += 1,
+            Edit::Insert(_) => ni += 1,
+        }
+    }
+    before.push((oi, ni));
+
+    let mut out = Vec::new();
+    out.extend_from_slice(format!("--- {old_label}\n+++ {new_label}\n").as_bytes());
+    for (first, last) in hunks {
+        let lo = first.saturating_sub(CONTEXT);
+        let hi = (last + CONTEXT + 1).min(edits.len());
+        let (o0, n0) = before[lo];
+        let (o1, n1) = before[hi];
+        let range = |start: usize, len: usize| {
+            // An empty range names the line before it, as `diff -u` does.
+            let start = if len == 0 { start } else { start + 1 };
+            format!("{start},{len}")
+        };
+        out.extend_from_slice(
+            format!("@@ -{} +{} @@\n", range(o0, o1 - o0), range(n0, n1 - n0)).as_bytes(),
+        );
+        for e in &edits[lo..hi] {
+            let (mark, line) = match *e {
+                Edit::Keep(x, _) => (b' ', a[x]),
+                Edit::Delete(x) => (b'-', a[x]),
+                Edit::Insert(y) => (b'+', b[y]),
+            };
+            out.push(mark);
+            out.extend_from_slice(line);
+            out.push(b'\n');
+        }
+    }
+    Some(out)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply `edits` to `old` and check they produce `new`, keeping only
+    /// equal lines.
+    fn check(old: &[&[u8]], new: &[&[u8]], edits: &[Edit]) {
+        let mut out: Vec<&[u8]> = Vec::new();
+        let (mut x, mut y) = (0, 0);
+        for e in edits {
+            match *e {
+                Edit::Keep(i, j) => {
+                    assert_eq!((i, j), (x, y));
+                    assert_eq!(old[i], new[j]);
+                    out.push(old[i]);
+                    x += 1;
+                    y += 1;
+                }
+                Edit::Delete(i) => {
+                    assert_eq!(i, x);
+                    x += 1;
+                }
+                Edit::Insert(j) => {
+                    assert_eq!(j, y);
+                    out.push(new[j]);
+                    y += 1;
+                }
+            }
+        }
+        assert_eq!((x, y), (old.len(), new.len()));
+        assert_eq!(out, new);
+    }
+
+    fn cost(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Keep(..)))
+            .count()
+    }
+
+    #[test]
+    fn classic_example_has_minimal_cost() {
+        // Myers' paper: ABCABBA → CBABAC takes 5 edits.
+        let a: Vec<&[u8]> = "ABCABBA".as_bytes().chunks(1).collect();
+        let b: Vec<&[u8]> = "CBABAC".as_bytes().chunks(1).collect();
+        let edits = diff(&a, &b);
+        check(&a, &b, &edits);
+        assert_eq!(cost(&edits), 5);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let x: &[u8] = b"x";
+        let y: &[u8] = b"y";
+        for (a, b) in [
+            (vec![], vec![]),
+            (vec![x], vec![]),
+            (vec![], vec![x, y]),
+            (vec![x, y], vec![x, y]),
+            (vec![x], vec![y]),
+            (vec![x, x, y], vec![y, x, x]),
+        ] {
+            check(&a, &b, &diff(&a, &b));
+        }
+    }
+
+    #[test]
+    fn eols_are_ignored() {
+        assert_eq!(split_lines(b"a\r\nb\n\nc"), [&b"a"[..], b"b", b"", b"c"]);
+        assert!(unified("a", "b", b"x\r\ny\r\n", b"x\ny\n").is_none());
+    }
+
+    #[test]
+    fn unified_output() {
+        let old = b"1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = b"1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        let got = unified("old.txt", "new.txt", old, new).unwrap();
+        let want = "--- old.txt\n+++ new.txt\n\
+                    @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+                    @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n";
+        assert_eq!(String::from_utf8(got).unwrap(), want);
+    }
+
+    #[test]
+    fn unified_insert_into_empty_file() {
+        let got = unified("a", "b", b"", b"x\n").unwrap();
+        assert_eq!(got, b"--- a\n+++ b\n@@ -0,0 +1,1 @@\n+x\n");
+    }
+}
@@ -0,0 +1,110 @@
+// ── Auto-close brackets and quotes ────────────────────────────────────────────
+//
+// Decides, after each typed character, whether to insert the matching closer
+// or to swallow a typed closer that duplicates an auto-inserted one.  The
+// Win32 layer supplies the surrounding bytes and whether the byte after the
+// caret was auto-inserted (tracked with a hidden Scintilla indicator), then
+// carries out the returned `Action`.  No Win32 calls here.
+
+use super::completion::is_word_byte;
+
+/// What to do after a character was typed.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Insert this closer after the caret.
+    Close(u8),
+    /// The typed closer matches the auto-inserted one after the caret:
+    /// delete that one, so typing appears to step over it.
+    Overtype,
+    /// Leave the text alone.
+    Nothing,
+}
+
+/// Decide the action for `typed`, which has just been inserted.
+///
+/// `prev` is the byte before `typed`, `next` the byte after the caret, and
+/// `next_auto` whether `next` was inserted by a previous `Close`.
+///
+/// A closer is only added where nothing would run into it: before the end of
+/// the line, whitespace, or another closer.  Quotes are also not paired
+/// directly after a word character (`it's`, `r"`, `x'`).
+pub(crate) fn action(
+    pairs: &[(u8, u8)],
+    typed: u8,
+    prev: Option<u8>,
+    next: Option<u8>,
+    next_auto: bool,
+) -> Action {
+    if next_auto && next == Some(typed) && pairs.iter().any(|&(_, c)| c == typed) {
+        return Action::Overtype;
+    }
+    let Some(&(open, close)) = pairs.iter().find(|&&(o, _)| o == typed) else {
+        return Action::Nothing;
+    };
+    let open_space = match next {
+        None => true,
+        Some(b) => b.is_ascii_whitespace() || pairs.iter().any(|&(_, c)| c == b),
+    };
+    let is_quote = open == close;
+    if !open_space || (is_quote && prev.is_some_and(is_word_byte)) {
+        return Action::Nothing;
+    }
+    Action::Close(close)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIRS: &[(u8, u8)] = &[(b'(', b')'), (b'[', b']'), (b'"', b'"')];
+
+    #[test]
+    fn closes_openers_before_space_or_closers() {
+        assert_eq!(action(PAIRS, b'(', None, None, false), Action::Close(b')'));
+        assert_eq!(
+            action(PAIRS, b'[', Some(b'x'), Some(b')'), false),
+            Action::Close(b']')
+        );
+        assert_eq!(
+            action(PAIRS, b'(', Some(b' '), Some(b'\r'), false),
+            Action::Close(b')')
+        );
+        assert_eq!(
+            action(PAIRS, b'(', None, Some(b'x'), false),
+            Action::Nothing
+        );
+        assert_eq!(action(PAIRS, b'{', None, None, false), Action::Nothing);
+    }
+
+    #[test]
+    fn quotes_not_paired_after_words() {
+        assert_eq!(
+            action(PAIRS, b'"', Some(b'='), None, false),
+            Action::Close(b'"')
+        );
+        assert_eq!(
+            action(PAIRS, b'"', Some(b'r'), None, false),
+            Action::Nothing
+        );
+    }
+
+    #[test]
+    fn overtypes_only_auto_inserted_closers() {
+        assert_eq!(
+            action(PAIRS, b')', Some(b'x'), Some(b')'), true),
+            Action::Overtype
+        );
+        assert_eq!(
+            action(PAIRS, b')', Some(b'x'), Some(b')'), false),
+            Action::Nothing
+        );
+        // A closing quote steps over the auto-inserted one instead of
+        // opening a new pair.
+        assert_eq!(
+            action(PAIRS, b'"', Some(b'a'), Some(b'"'), true),
+            Action::Overtype
+        );
+    }
+}
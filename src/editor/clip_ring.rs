@@ -0,0 +1,95 @@
+// ── Clipboard ring ────────────────────────────────────────────────────────────
+//
+// The text of Rivet's recent Cut and Copy commands, newest first, for
+// Edit > Paste from History (Ctrl+Shift+V).  Only cuts and copies made
+// through the Edit commands are recorded; the system clipboard itself is not
+// watched.  Kept for the session only.  No Win32 calls here.
+
+/// Entries kept.
+pub(crate) const MAX_ENTRIES: usize = 20;
+
+/// Cuts or copies larger than this are not remembered (they still reach the
+/// clipboard), so the ring cannot pin megabytes of text.
+pub(crate) const MAX_ENTRY_BYTES: usize = 1024 * 1024;
+
+/// Characters of an entry shown in the Paste from History list.
+const LABEL_CHARS: usize = 60;
+
+/// Recent cut / copied text, newest first, without duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ClipRing(Vec<Vec<u8>>);
+
+impl ClipRing {
+    /// Make `text` the newest entry, dropping an older copy and anything
+    /// beyond `MAX_ENTRIES`.  Empty and oversized text is ignored.
+    pub(crate) fn push(&mut self, text: &[u8]) {
+        if text.is_empty() || text.len() > MAX_ENTRY_BYTES {
+            return;
+        }
+        self.0.retain(|e| e != text);
+        self.0.insert(0, text.to_vec());
+        self.0.truncate(MAX_ENTRIES);
+    }
+
+    /// The entries, newest first.
+    pub(crate) fn entries(&self) -> &[Vec<u8>] {
+        &self.0
+    }
+
+    /// Take entry `i` for pasting: it becomes the newest.
+    pub(crate) fn take(&mut self, i: usize) -> Option<Vec<u8>> {
+        let entry = self.0.get(i)?.clone();
+        self.push(&entry);
+        Some(entry)
+    }
+}
+
+/// One line describing `entry` for the history list: its first non-blank
+/// line, trimmed and shortened, plus a count of the lines after it.
+pub(crate) fn label(entry: &[u8]) -> String {
+    let text = String::from_utf8_lossy(entry);
+    let lines: Vec<&str> = text.lines().collect();
+    let first = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(0);
+    let line = lines.get(first).map_or("", |l| l.trim());
+    let mut label: String = line.chars().take(LABEL_CHARS).collect();
+    if line.chars().count() > LABEL_CHARS {
+        label.push('\u{2026}');
+    }
+    match lines.len().saturating_sub(first + 1) {
+        0 => label,
+        1 => format!("{label}  (+1 line)"),
+        more => format!("{label}  (+{more} lines)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_moves_repeats_to_the_front_and_caps_the_ring() {
+        let mut ring = ClipRing::default();
+        ring.push(b"a");
+        ring.push(b"b");
+        ring.push(b"");
+        ring.push(b"a");
+        assert_eq!(ring.entries(), [b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(ring.take(1), Some(b"b".to_vec()));
+        assert_eq!(ring.entries()[0], b"b");
+        ring.push(&vec![b'x'; MAX_ENTRY_BYTES + 1]);
+        for i in 0..30 {
+            ring.push(i.to_string().as_bytes());
+        }
+        assert_eq!(ring.entries().len(), MAX_ENTRIES);
+        assert_eq!(ring.entries()[0], b"29");
+    }
+
+    #[test]
+    fn label_shows_the_first_line_and_what_follows() {
+        assert_eq!(label(b"  let x = 1;  "), "let x = 1;");
+        assert_eq!(label(b"\r\nfn a() {\r\n}\r\n"), "fn a() {  (+1 line)");
+        assert_eq!(label(b"a\nb\nc"), "a  (+2 lines)");
+        let long = "x".repeat(LABEL_CHARS + 5);
+        assert_eq!(label(long.as_bytes()).chars().count(), LABEL_CHARS + 1);
+    }
+}
@@ -0,0 +1,129 @@
+// ── Column editor ─────────────────────────────────────────────────────────────
+//
+// What Edit > Column Editor… inserts into each line of a rectangular
+// selection: the same text on every line, or a number sequence.  The caller
+// supplies where each line's insertion point is (a position plus any virtual
+// space past the end of the line); this module works out the edits.  No
+// Win32 calls here.
+
+/// What to insert on each line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Fill {
+    /// The same text on every line.
+    Text(String),
+    /// `start`, `start + step`, … one number per line.
+    Numbers(Sequence),
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Fill::Numbers(Sequence::default())
+    }
+}
+
+/// A number sequence for [`Fill::Numbers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Sequence {
+    pub(crate) start: i64,
+    pub(crate) step: i64,
+    /// Pad every number with zeros to the width of the widest one.
+    pub(crate) leading_zeros: bool,
+    /// Upper-case hexadecimal instead of decimal.
+    pub(crate) hex: bool,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Sequence {
+            start: 1,
+            step: 1,
+            leading_zeros: false,
+            hex: false,
+        }
+    }
+}
+
+/// The text for each of `count` lines, top to bottom.
+pub(crate) fn values(fill: &Fill, count: usize) -> Vec<String> {
+    let seq = match fill {
+        Fill::Text(text) => return vec![text.clone(); count],
+        Fill::Numbers(seq) => seq,
+    };
+    let digits: Vec<(bool, String)> = (0..count as i64)
+        .map(|i| {
+            let n = seq.start.saturating_add(seq.step.saturating_mul(i));
+            let magnitude = n.unsigned_abs();
+            let digits = if seq.hex {
+                format!("{magnitude:X}")
+            } else {
+                magnitude.to_string()
+            };
+            (n < 0, digits)
+        })
+        .collect();
+    let width = if seq.leading_zeros {
+        digits.iter().map(|(_, d)| d.len()).max().unwrap_or(0)
+    } else {
+        0
+    };
+    digits
+        .into_iter()
+        .map(|(negative, d)| format!("{}{d:0>width$}", if negative { "-" } else { "" }))
+        .collect()
+}
+
+/// The insertions for a rectangular selection, last line first so that
+/// applying them in order leaves the earlier positions valid.
+///
+/// `points` holds each line's insertion point top to bottom as
+/// `(position, virtual space)`; a line that ends left of the column is padded
+/// with spaces out to it.
+pub(crate) fn edits(points: &[(usize, usize)], values: &[String]) -> Vec<(usize, Vec<u8>)> {
+    points
+        .iter()
+        .zip(values)
+        .rev()
+        .map(|(&(pos, virtual_space), value)| {
+            let mut text = vec![b' '; virtual_space];
+            text.extend_from_slice(value.as_bytes());
+            (pos, text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(start: i64, step: i64, leading_zeros: bool, hex: bool) -> Fill {
+        Fill::Numbers(Sequence {
+            start,
+            step,
+            leading_zeros,
+            hex,
+        })
+    }
+
+    #[test]
+    fn number_sequences_in_decimal_and_hex() {
+        assert_eq!(values(&numbers(1, 1, false, false), 3), ["1", "2", "3"]);
+        assert_eq!(values(&numbers(8, 2, true, false), 3), ["08", "10", "12"]);
+        assert_eq!(values(&numbers(14, 1, true, true), 3), ["0E", "0F", "10"]);
+        assert_eq!(values(&numbers(1, -1, true, false), 3), ["1", "0", "-1"]);
+        assert_eq!(values(&Fill::Text("x".into()), 2), ["x", "x"]);
+        assert!(values(&Fill::default(), 0).is_empty());
+    }
+
+    #[test]
+    fn edits_run_bottom_up_and_pad_virtual_space() {
+        let values = values(&numbers(1, 1, false, false), 3);
+        assert_eq!(
+            edits(&[(4, 0), (10, 2), (13, 0)], &values),
+            [
+                (13, b"3".to_vec()),
+                (10, b"  2".to_vec()),
+                (4, b"1".to_vec())
+            ]
+        );
+    }
+}
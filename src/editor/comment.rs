@@ -0,0 +1,139 @@
+// ── Comment / uncomment ───────────────────────────────────────────────────────
+//
+// Text transforms behind Edit > Toggle Line Comment and Toggle Block Comment.
+// The Win32 layer reads the affected range, passes it here, and writes the
+// result back as one target replacement inside one undo action.
+// No Win32 calls here.
+
+/// Leading spaces and tabs of `line`.
+fn indent_len(line: &[u8]) -> usize {
+    line.iter()
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .count()
+}
+
+/// `line` without its trailing `\r` / `\n`.
+fn content(line: &[u8]) -> &[u8] {
+    let end = line
+        .iter()
+        .rposition(|&b| b != b'\r' && b != b'\n')
+        .map_or(0, |i| i + 1);
+    &line[..end]
+}
+
+/// Split `text` into lines that keep their EOLs.
+fn lines(text: &[u8]) -> impl Iterator<Item = &[u8]> {
+    text.split_inclusive(|&b| b == b'\n')
+}
+
+/// Comment or uncomment every line of `text` (whole lines, EOLs included)
+/// with the line-comment `token`.
+///
+/// If every non-blank line already starts with `token` after its
+/// indentation, the token and one following space are removed.  Otherwise
+/// `token` plus a space is inserted into each non-blank line at the smallest
+/// indentation of the block, so the comment markers line up.  Blank lines
+/// are left alone either way.
+pub(crate) fn toggle_line_comment(text: &[u8], token: &[u8]) -> Vec<u8> {
+    let non_blank = || lines(text).filter(|l| !content(l).trim_ascii().is_empty());
+    let commented = non_blank().all(|l| l[indent_len(l)..].starts_with(token));
+    let mut out = Vec::with_capacity(text.len() + 16);
+    if commented {
+        for line in lines(text) {
+            let i = indent_len(line);
+            if !line[i..].starts_with(token) {
+                out.extend_from_slice(line);
+                continue;
+            }
+            let mut rest = &line[i + token.len()..];
+            if rest.first() == Some(&b' ') {
+                rest = &rest[1..];
+            }
+            out.extend_from_slice(&line[..i]);
+            out.extend_from_slice(rest);
+        }
+    } else {
+        let at = non_blank().map(indent_len).min().unwrap_or(0);
+        for line in lines(text) {
+            if content(line).trim_ascii().is_empty() {
+                out.extend_from_slice(line);
+                continue;
+            }
+            out.extend_from_slice(&line[..at]);
+            out.extend_from_slice(token);
+            out.push(b' ');
+            out.extend_from_slice(&line[at..]);
+        }
+    }
+    out
+}
+
+/// Wrap `text` in `open` … `close`, or unwrap it if it is already wrapped
+/// (ignoring surrounding whitespace).  One space inside each marker is added
+/// when wrapping and removed when unwrapping.
+pub(crate) fn toggle_block_comment(text: &[u8], open: &[u8], close: &[u8]) -> Vec<u8> {
+    let lead = text.len() - text.trim_ascii_start().len();
+    let inner = text.trim_ascii();
+    if inner.len() >= open.len() + close.len() && inner.starts_with(open) && inner.ends_with(close)
+    {
+        let mut body = &inner[open.len()..inner.len() - close.len()];
+        if body.first() == Some(&b' ') {
+            body = &body[1..];
+        }
+        if body.last() == Some(&b' ') {
+            body = &body[..body.len() - 1];
+        }
+        let mut out = Vec::with_capacity(text.len());
+        out.extend_from_slice(&text[..lead]);
+        out.extend_from_slice(body);
+        out.extend_from_slice(&text[lead + inner.len()..]);
+        return out;
+    }
+    let mut out = Vec::with_capacity(text.len() + open.len() + close.len() + 2);
+    out.extend_from_slice(open);
+    out.push(b' ');
+    out.extend_from_slice(text);
+    out.push(b' ');
+    out.extend_from_slice(close);
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_comments_align_and_skip_blank_lines() {
+        let text = b"    a();\r\n\r\n  if x {\r\n";
+        let done = toggle_line_comment(text, b"//");
+        assert_eq!(done, b"  //   a();\r\n\r\n  // if x {\r\n");
+        assert_eq!(
+            toggle_line_comment(&done, b"//"),
+            b"    a();\r\n\r\n  if x {\r\n"
+        );
+    }
+
+    #[test]
+    fn mixed_block_is_commented_not_uncommented() {
+        let text = b"# x\ny\n";
+        assert_eq!(toggle_line_comment(text, b"#"), b"# # x\n# y\n");
+    }
+
+    #[test]
+    fn uncomment_without_space_and_last_line_without_eol() {
+        assert_eq!(toggle_line_comment(b"--a\n-- b", b"--"), b"a\nb");
+    }
+
+    #[test]
+    fn block_comment_round_trip() {
+        let wrapped = toggle_block_comment(b"color: red;", b"/*", b"*/");
+        assert_eq!(wrapped, b"/* color: red; */");
+        assert_eq!(toggle_block_comment(&wrapped, b"/*", b"*/"), b"color: red;");
+        assert_eq!(
+            toggle_block_comment(b"  <!--x-->\n", b"<!--", b"-->"),
+            b"  x\n"
+        );
+    }
+}
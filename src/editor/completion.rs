@@ -0,0 +1,94 @@
+// ── Word completion ───────────────────────────────────────────────────────────
+//
+// Builds the candidate list for Edit > Complete Word (Ctrl+Space): words
+// harvested from the document around the caret plus the language's keyword
+// lists from `languages::keywords`.  `ScintillaView::show_completions` shows
+// the result.  No Win32 calls here.
+
+use std::collections::HashSet;
+
+/// Bytes of document text scanned on each side of the caret.  Keeps the
+/// harvest cheap in long documents; words further away are not offered.
+pub(crate) const HARVEST_RADIUS: usize = 256 * 1024;
+
+/// Most candidates offered at once.
+pub(crate) const MAX_CANDIDATES: usize = 500;
+
+/// Identifier-ish bytes: ASCII letters, digits, `_`, and any byte of a
+/// multi-byte UTF-8 sequence.
+pub(crate) fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// Length of the word that ends at the end of `before` (the text up to the
+/// caret), i.e. the prefix being completed.
+pub(crate) fn prefix_len(before: &[u8]) -> usize {
+    before
+        .iter()
+        .rev()
+        .take_while(|&&b| is_word_byte(b))
+        .count()
+}
+
+/// Words that extend `prefix` (ASCII case-insensitively), drawn from `text`
+/// and from the whitespace-separated `keyword_lists`.
+///
+/// Numbers and the prefix itself are left out.  The result is deduplicated,
+/// sorted case-insensitively, and cut to [`MAX_CANDIDATES`].
+pub(crate) fn candidates<'a>(
+    prefix: &[u8],
+    text: &'a [u8],
+    keyword_lists: &[&'a [u8]],
+) -> Vec<&'a [u8]> {
+    let words = text.split(|&b| !is_word_byte(b));
+    let keywords = keyword_lists
+        .iter()
+        .flat_map(|list| list.split(|&b| b.is_ascii_whitespace() || b == 0));
+    let mut found: Vec<&[u8]> = words
+        .chain(keywords)
+        .filter(|w| {
+            w.len() > prefix.len()
+                && !w[0].is_ascii_digit()
+                && w[..prefix.len()].eq_ignore_ascii_case(prefix)
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    found.sort_by(|a, b| {
+        a.to_ascii_lowercase()
+            .cmp(&b.to_ascii_lowercase())
+            .then(a.cmp(b))
+    });
+    found.truncate(MAX_CANDIDATES);
+    found
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_stops_at_non_word_bytes() {
+        assert_eq!(prefix_len(b"let foo_ba"), 6);
+        assert_eq!(prefix_len(b"x."), 0);
+        assert_eq!(prefix_len("caf\u{e9}".as_bytes()), 5);
+        assert_eq!(prefix_len(b""), 0);
+    }
+
+    #[test]
+    fn harvests_document_words_and_keywords() {
+        let text = b"fn parse(input: &str) { let parsed = Parser::new(input); parse }";
+        let keywords: &[&[u8]] = &[b"pub package\0"];
+        let got = candidates(b"pa", text, keywords);
+        assert_eq!(got, [&b"package"[..], b"parse", b"parsed", b"Parser"]);
+    }
+
+    #[test]
+    fn skips_numbers_prefix_and_duplicates() {
+        let got = candidates(b"", b"x1 12 abc abc ab", &[]);
+        assert_eq!(got, [&b"ab"[..], b"abc", b"x1"]);
+        assert!(candidates(b"abc", b"abc abc", &[]).is_empty());
+    }
+}
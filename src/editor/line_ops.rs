@@ -0,0 +1,247 @@
+// ── Line operations ───────────────────────────────────────────────────────────
+//
+// Text transforms behind Edit > Line Operations: joining, splitting and
+// removing duplicate or empty lines.  The Win32 layer reads the selected
+// whole lines (or the whole document), passes them here, and writes the
+// result back as one target replacement inside one undo action.
+// No Win32 calls here.
+
+/// One Edit > Line Operations command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LineOp {
+    /// Join Lines.
+    Join,
+    /// Split Lines…: rewrap to a width the user enters.
+    Split,
+    /// Remove Duplicate Lines, keeping the first of each.
+    RemoveDuplicates,
+    /// Remove Consecutive Duplicate Lines.
+    RemoveConsecutiveDuplicates,
+    /// Remove Empty Lines.
+    RemoveEmpty,
+    /// Remove Empty and Blank Lines (whitespace only).
+    RemoveBlank,
+}
+
+impl LineOp {
+    /// Whether the operation applies to the whole document when nothing is
+    /// selected (rather than to the caret line).
+    pub(crate) fn whole_document_default(self) -> bool {
+        !matches!(self, LineOp::Join | LineOp::Split)
+    }
+}
+
+/// Each line of `text` as `(content, EOL)`.  A final EOL does not start
+/// another line; empty text is one empty line.
+pub(super) fn lines(text: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'\r' | b'\n' => {
+                let eol = if text[i..].starts_with(b"\r\n") { 2 } else { 1 };
+                lines.push((&text[start..i], &text[i..i + eol]));
+                i += eol;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    if start < text.len() || lines.is_empty() {
+        lines.push((&text[start..], &b""[..]));
+    }
+    lines
+}
+
+/// The lines of `text` without their EOLs, and the EOL after the last one
+/// (empty if the text does not end with one).
+fn split(text: &[u8]) -> (Vec<&[u8]>, &[u8]) {
+    let lines = lines(text);
+    let eol = lines.last().map_or(&b""[..], |&(_, eol)| eol);
+    (lines.into_iter().map(|(l, _)| l).collect(), eol)
+}
+
+/// The lines of `text` for which `keep` holds, with their EOLs.  If `text`
+/// does not end with an EOL, neither does the result.
+fn retain_lines(text: &[u8], mut keep: impl FnMut(&[u8]) -> bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for (line, eol) in lines(text) {
+        if keep(line) {
+            out.extend_from_slice(line);
+            out.extend_from_slice(eol);
+        }
+    }
+    if !text.ends_with(b"\n") && !text.ends_with(b"\r") {
+        let eol = if out.ends_with(b"\r\n") {
+            2
+        } else {
+            usize::from(out.ends_with(b"\n") || out.ends_with(b"\r"))
+        };
+        out.truncate(out.len() - eol);
+    }
+    out
+}
+
+/// Characters in UTF-8 `bytes`.
+fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+/// Leading spaces and tabs of `line`.
+fn indent(line: &[u8]) -> &[u8] {
+    let n = line
+        .iter()
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .count();
+    &line[..n]
+}
+
+/// Merge the lines of `text` into one, separated by single spaces.
+///
+/// The first line keeps its indentation; the other lines lose theirs, every
+/// line loses trailing whitespace, and blank lines are dropped.  A final EOL
+/// is kept.
+pub(crate) fn join_lines(text: &[u8]) -> Vec<u8> {
+    let (lines, eol) = split(text);
+    let mut out = Vec::with_capacity(text.len());
+    for line in lines {
+        let body = line.trim_ascii();
+        if body.is_empty() {
+            continue;
+        }
+        if out.is_empty() {
+            out.extend_from_slice(indent(line));
+        } else {
+            out.push(b' ');
+        }
+        out.extend_from_slice(body);
+    }
+    out.extend_from_slice(eol);
+    out
+}
+
+/// Rewrap each paragraph of `text` so no line is wider than `width`
+/// characters, breaking only between words.
+///
+/// Paragraphs are runs of non-blank lines; blank lines between them are
+/// kept.  Every line of a paragraph gets the indentation of its first line.
+/// A word wider than `width` gets a line to itself.  New lines end with
+/// `eol`; a final EOL is kept.
+pub(crate) fn split_lines(text: &[u8], width: usize, eol: &[u8]) -> Vec<u8> {
+    let (lines, final_eol) = split(text);
+    let mut out: Vec<Vec<u8>> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].trim_ascii().is_empty() {
+            out.push(Vec::new());
+            i += 1;
+            continue;
+        }
+        let prefix = indent(lines[i]);
+        let mut line = prefix.to_vec();
+        let mut words = 0;
+        while i < lines.len() && !lines[i].trim_ascii().is_empty() {
+            for word in lines[i].split(u8::is_ascii_whitespace) {
+                if word.is_empty() {
+                    continue;
+                }
+                if words > 0 && char_count(&line) + 1 + char_count(word) > width {
+                    out.push(std::mem::replace(&mut line, prefix.to_vec()));
+                    words = 0;
+                }
+                if words > 0 {
+                    line.push(b' ');
+                }
+                line.extend_from_slice(word);
+                words += 1;
+            }
+            i += 1;
+        }
+        out.push(line);
+    }
+    let mut joined = out.join(eol);
+    joined.extend_from_slice(final_eol);
+    joined
+}
+
+/// `text` without repeated lines: every line equal to an earlier one, or
+/// with `consecutive_only`, to the line just before it.  Lines compare
+/// without their EOLs.
+pub(crate) fn remove_duplicate_lines(text: &[u8], consecutive_only: bool) -> Vec<u8> {
+    let mut seen = std::collections::HashSet::new();
+    let mut previous: Option<&[u8]> = None;
+    let lines = lines(text);
+    let mut keep = lines.iter().map(|&(line, _)| {
+        if consecutive_only {
+            previous.replace(line) != Some(line)
+        } else {
+            seen.insert(line)
+        }
+    });
+    retain_lines(text, |_| keep.next().unwrap_or(true))
+}
+
+/// `text` without empty lines, and with `blank_too` without lines of only
+/// spaces and tabs either.
+pub(crate) fn remove_empty_lines(text: &[u8], blank_too: bool) -> Vec<u8> {
+    retain_lines(text, |line| {
+        if blank_too {
+            !line.iter().all(|&b| b == b' ' || b == b'\t')
+        } else {
+            !line.is_empty()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_merges_with_single_spaces() {
+        assert_eq!(
+            join_lines(b"  one  \r\n\ttwo\r\n\r\nthree \r\n"),
+            b"  one two three\r\n"
+        );
+        assert_eq!(join_lines(b"a\nb"), b"a b");
+        assert_eq!(join_lines(b"single\n"), b"single\n");
+        assert_eq!(join_lines(b"\n\n"), b"\n");
+        assert_eq!(join_lines(b""), b"");
+    }
+
+    #[test]
+    fn split_wraps_at_word_boundaries() {
+        assert_eq!(
+            split_lines(b"the quick brown fox jumps\n", 10, b"\n"),
+            b"the quick\nbrown fox\njumps\n"
+        );
+        // Paragraphs stay apart and keep their indentation.
+        assert_eq!(
+            split_lines(b"  aa bb\r\n  cc\r\n\r\ndd ee", 5, b"\r\n"),
+            b"  aa\r\n  bb\r\n  cc\r\n\r\ndd ee"
+        );
+        // A word wider than the limit is not broken.
+        assert_eq!(
+            split_lines("héllo wörld-wide x".as_bytes(), 5, b"\n"),
+            "héllo\nwörld-wide\nx".as_bytes()
+        );
+    }
+
+    #[test]
+    fn duplicates_keep_the_first_occurrence() {
+        let text = b"a\r\nb\r\nb\r\na\nc\r\na";
+        assert_eq!(remove_duplicate_lines(text, false), b"a\r\nb\r\nc");
+        assert_eq!(remove_duplicate_lines(text, true), b"a\r\nb\r\na\nc\r\na");
+        assert_eq!(remove_duplicate_lines(b"x\nx\n", true), b"x\n");
+    }
+
+    #[test]
+    fn empty_and_blank_lines() {
+        let text = b"a\n\n  \n\tb\n\t\n";
+        assert_eq!(remove_empty_lines(text, false), b"a\n  \n\tb\n\t\n");
+        assert_eq!(remove_empty_lines(text, true), b"a\n\tb\n");
+        assert_eq!(remove_empty_lines(b"a\n\n", false), b"a\n");
+        assert_eq!(remove_empty_lines(b"a\n  ", true), b"a");
+    }
+}
@@ -0,0 +1,384 @@
+// ── In-memory editor backend (tests only) ─────────────────────────────────────
+//
+// `MemoryView` implements `EditorView` over a byte buffer so command and
+// document logic can be exercised without a window.  It models what that
+// logic observes — text, selection, EOL mode, wrap, read-only, undo — and
+// accepts styling calls without doing anything.
+
+use std::cell::{Cell, RefCell};
+
+use crate::app::{EolMode, Indentation};
+
+use super::EditorView;
+
+/// An editor buffer held entirely in memory.
+///
+/// Every edit is its own undo step; `begin_undo_action` grouping is not
+/// modelled.
+#[derive(Default)]
+pub(crate) struct MemoryView {
+    text: RefCell<Vec<u8>>,
+    /// `(anchor, caret)`.
+    sel: Cell<(usize, usize)>,
+    target: Cell<(usize, usize)>,
+    clipboard: RefCell<Vec<u8>>,
+    undo: RefCell<Vec<Vec<u8>>>,
+    redo: RefCell<Vec<Vec<u8>>>,
+    /// `undo` depth at the last save point.
+    save_point: Cell<usize>,
+    eol: Cell<Option<EolMode>>,
+    indent: Cell<Indentation>,
+    wrap: Cell<bool>,
+    read_only: Cell<bool>,
+    first_line: Cell<usize>,
+}
+
+impl MemoryView {
+    pub(crate) fn with_text(text: &[u8]) -> Self {
+        let view = Self::default();
+        view.set_text(text);
+        view
+    }
+
+    /// Type `text` at the caret, replacing the selection.
+    pub(crate) fn type_text(&self, text: &[u8]) {
+        let (start, end) = (self.selection_start(), self.selection_end());
+        self.edit(start, end, text);
+        self.set_caret_pos(start + text.len());
+    }
+
+    /// Whether the text differs from the last save point.
+    pub(crate) fn is_modified(&self) -> bool {
+        self.undo.borrow().len() != self.save_point.get()
+    }
+
+    pub(crate) fn indentation(&self) -> Indentation {
+        self.indent.get()
+    }
+
+    /// Replace `start..end` with `with`, recording an undo step.  Ignored
+    /// while read-only, like Scintilla.
+    fn edit(&self, start: usize, end: usize, with: &[u8]) {
+        if self.read_only.get() {
+            return;
+        }
+        let mut text = self.text.borrow_mut();
+        self.undo.borrow_mut().push(text.clone());
+        self.redo.borrow_mut().clear();
+        text.splice(start..end, with.iter().copied());
+    }
+
+    /// Start offsets of every line.
+    fn line_starts(&self) -> Vec<usize> {
+        let text = self.text.borrow();
+        let mut starts = vec![0];
+        for (i, &b) in text.iter().enumerate() {
+            let ends_line = b == b'\n' || (b == b'\r' && text.get(i + 1) != Some(&b'\n'));
+            if ends_line {
+                starts.push(i + 1);
+            }
+        }
+        starts
+    }
+}
+
+impl EditorView for MemoryView {
+    fn set_text(&self, text: &[u8]) {
+        if self.read_only.get() {
+            return;
+        }
+        *self.text.borrow_mut() = text.to_vec();
+        self.undo.borrow_mut().clear();
+        self.redo.borrow_mut().clear();
+        self.save_point.set(0);
+        self.sel.set((0, 0));
+    }
+
+    fn get_text(&self) -> Vec<u8> {
+        self.text.borrow().clone()
+    }
+
+    fn text_range(&self, start: usize, end: usize) -> Vec<u8> {
+        self.text.borrow()[start..end].to_vec()
+    }
+
+    fn doc_len(&self) -> usize {
+        self.text.borrow().len()
+    }
+
+    fn set_save_point(&self) {
+        self.save_point.set(self.undo.borrow().len());
+    }
+
+    fn set_read_only(&self, read_only: bool) {
+        self.read_only.set(read_only);
+    }
+
+    fn set_target(&self, start: usize, end: usize) {
+        self.target.set((start, end));
+    }
+
+    fn replace_target(&self, text: &[u8]) -> usize {
+        let (start, end) = self.target.get();
+        self.edit(start, end, text);
+        self.target.set((start, start + text.len()));
+        text.len()
+    }
+
+    fn begin_undo_action(&self) {}
+
+    fn end_undo_action(&self) {}
+
+    fn eol_mode(&self) -> EolMode {
+        self.eol.get().unwrap_or(EolMode::Crlf)
+    }
+
+    fn set_eol_mode(&self, eol: EolMode) {
+        self.eol.set(Some(eol));
+    }
+
+    fn convert_eols(&self, eol: EolMode) {
+        let sep: &[u8] = match eol {
+            EolMode::Crlf => b"\r\n",
+            EolMode::Lf => b"\n",
+            EolMode::Cr => b"\r",
+        };
+        let text = self.get_text();
+        let mut out = Vec::with_capacity(text.len());
+        let mut i = 0;
+        while i < text.len() {
+            match text[i] {
+                b'\r' if text.get(i + 1) == Some(&b'\n') => {
+                    out.extend_from_slice(sep);
+                    i += 1;
+                }
+                b'\r' | b'\n' => out.extend_from_slice(sep),
+                b => out.push(b),
+            }
+            i += 1;
+        }
+        if out != text {
+            self.edit(0, text.len(), &out);
+        }
+    }
+
+    fn undo(&self) {
+        if let Some(prev) = self.undo.borrow_mut().pop() {
+            let cur = std::mem::replace(&mut *self.text.borrow_mut(), prev);
+            self.redo.borrow_mut().push(cur);
+            self.set_caret_pos(self.caret_pos().min(self.doc_len()));
+        }
+    }
+
+    fn redo(&self) {
+        if let Some(next) = self.redo.borrow_mut().pop() {
+            let cur = std::mem::replace(&mut *self.text.borrow_mut(), next);
+            self.undo.borrow_mut().push(cur);
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        !self.undo.borrow().is_empty()
+    }
+
+    fn can_redo(&self) -> bool {
+        !self.redo.borrow().is_empty()
+    }
+
+    fn empty_undo_buffer(&self) {
+        // A modified text can no longer reach its save point.
+        let save_point = if self.is_modified() { usize::MAX } else { 0 };
+        self.undo.borrow_mut().clear();
+        self.redo.borrow_mut().clear();
+        self.save_point.set(save_point);
+    }
+
+    fn cut(&self) {
+        self.copy_to_clipboard();
+        self.delete_selection();
+    }
+
+    fn copy_to_clipboard(&self) {
+        *self.clipboard.borrow_mut() = self.selected_text();
+    }
+
+    fn paste(&self) {
+        let clip = self.clipboard.borrow().clone();
+        self.type_text(&clip);
+    }
+
+    fn delete_selection(&self) {
+        self.type_text(b"");
+    }
+
+    fn select_all(&self) {
+        self.sel.set((0, self.doc_len()));
+    }
+
+    fn caret_pos(&self) -> usize {
+        self.sel.get().1
+    }
+
+    fn set_caret_pos(&self, pos: usize) {
+        let pos = pos.min(self.doc_len());
+        self.sel.set((pos, pos));
+    }
+
+    fn selection_start(&self) -> usize {
+        let (a, c) = self.sel.get();
+        a.min(c)
+    }
+
+    fn selection_end(&self) -> usize {
+        let (a, c) = self.sel.get();
+        a.max(c)
+    }
+
+    fn set_sel(&self, anchor: usize, caret: usize) {
+        let len = self.doc_len();
+        self.sel.set((anchor.min(len), caret.min(len)));
+    }
+
+    fn selected_text(&self) -> Vec<u8> {
+        self.text_range(self.selection_start(), self.selection_end())
+    }
+
+    fn scroll_caret(&self) {}
+
+    fn first_visible_line(&self) -> usize {
+        self.first_line.get()
+    }
+
+    fn set_first_visible_line(&self, line: usize) {
+        self.first_line.set(line);
+    }
+
+    fn line_count(&self) -> usize {
+        self.line_starts().len()
+    }
+
+    fn position_from_line(&self, line: usize) -> usize {
+        let starts = self.line_starts();
+        starts.get(line).copied().unwrap_or(self.doc_len())
+    }
+
+    fn line_from_position(&self, pos: usize) -> usize {
+        self.line_starts().partition_point(|&s| s <= pos) - 1
+    }
+
+    fn find_next(&self, text: &[u8], _flags: u32, forward: bool) -> bool {
+        if text.is_empty() {
+            return false;
+        }
+        let hay = self.get_text();
+        let starts: Vec<usize> = hay
+            .windows(text.len())
+            .enumerate()
+            .filter(|(_, w)| *w == text)
+            .map(|(i, _)| i)
+            .collect();
+        let hit = if forward {
+            let from = self.selection_end();
+            starts.iter().find(|&&s| s >= from).or(starts.first())
+        } else {
+            let before = self.selection_start();
+            starts.iter().rev().find(|&&s| s < before).or(starts.last())
+        };
+        match hit {
+            Some(&s) => {
+                self.set_sel(s, s + text.len());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn style_clear_all(&self) {}
+
+    fn style_set_fore(&self, _style: u32, _colour: u32) {}
+
+    fn style_set_back(&self, _style: u32, _colour: u32) {}
+
+    fn style_set_bold(&self, _style: u32, _bold: bool) {}
+
+    fn style_set_font(&self, _style: u32, _font_name: &[u8]) {}
+
+    fn style_set_size(&self, _style: u32, _size: i32) {}
+
+    fn set_indentation(&self, indent: Indentation) {
+        self.indent.set(indent);
+    }
+
+    fn set_word_wrap(&self, enabled: bool) {
+        self.wrap.set(enabled);
+    }
+
+    fn is_word_wrap(&self) -> bool {
+        self.wrap.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_and_undo_track_the_save_point() {
+        let view = MemoryView::with_text(b"abc");
+        assert!(!view.is_modified());
+        view.set_caret_pos(3);
+        view.type_text(b"d");
+        assert_eq!(view.get_text(), b"abcd");
+        assert!(view.is_modified());
+        view.undo();
+        assert_eq!(view.get_text(), b"abc");
+        assert!(!view.is_modified());
+        view.redo();
+        assert_eq!(view.get_text(), b"abcd");
+    }
+
+    #[test]
+    fn emptying_undo_keeps_modified_state() {
+        let view = MemoryView::with_text(b"abc");
+        view.type_text(b"x");
+        view.undo();
+        assert!(view.can_redo());
+        view.type_text(b"y");
+        view.empty_undo_buffer();
+        assert!(!view.can_undo() && !view.can_redo());
+        assert!(view.is_modified());
+        assert_eq!(view.get_text(), b"aybc");
+    }
+
+    #[test]
+    fn lines_follow_mixed_line_endings() {
+        let view = MemoryView::with_text(b"a\r\nb\nc\rd");
+        assert_eq!(view.line_count(), 4);
+        assert_eq!(view.position_from_line(2), 5);
+        assert_eq!(view.position_from_line(3), 7);
+        assert_eq!(view.line_from_position(6), 2);
+        assert_eq!(view.line_from_position(1), 0);
+    }
+
+    #[test]
+    fn find_next_wraps_around() {
+        let view = MemoryView::with_text(b"one two one");
+        view.set_caret_pos(5);
+        assert!(view.find_next(b"one", 0, true));
+        assert_eq!(view.selected_text(), b"one");
+        assert_eq!(view.selection_start(), 8);
+        assert!(view.find_next(b"one", 0, true));
+        assert_eq!(view.selection_start(), 0);
+        assert!(!view.find_next(b"three", 0, true));
+    }
+
+    #[test]
+    fn read_only_refuses_edits() {
+        let view = MemoryView::with_text(b"abc");
+        view.set_read_only(true);
+        view.select_all();
+        view.type_text(b"x");
+        view.set_text(b"y");
+        assert_eq!(view.get_text(), b"abc");
+    }
+}
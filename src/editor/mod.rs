@@ -3,21 +3,159 @@
 // Exposes a safe Rust API over the underlying Scintilla editor control.
 // Callers interact with `ScintillaView` (defined in `scintilla::`) through
 // the public methods on this module; they never touch Win32 handles directly.
+//
+// `EditorView` is the backend-neutral subset of that API: text, caret and
+// selection, search, and styling.  Code written against it works with any
+// backend; `ScintillaView` is the first implementation.
 
 // Items below are stubs whose users arrive in Phase 2+.
 #![allow(dead_code)]
 
+pub(crate) mod autoclose; // Edit > Auto-Close Brackets decisions
+pub(crate) mod clip_ring; // Edit > Paste from History entries
+pub(crate) mod column; // Edit > Column Editor text and number sequences
+pub(crate) mod comment; // Edit > Toggle Line / Block Comment transforms
+pub(crate) mod completion; // word list for Edit > Complete Word
+pub(crate) mod line_ops; // Edit > Line Operations transforms
+#[cfg(test)]
+pub(crate) mod memory; // in-memory EditorView for headless tests
+pub(crate) mod path_completion; // file paths for Edit > Complete Word
+pub(crate) mod pipeline; // Tools > Text Pipeline… recipes
 pub mod scintilla;
+pub(crate) mod snippets; // user snippets and their tab stops
+pub(crate) mod viewer; // windowed read-only access to huge files
+
+use crate::app::{EolMode, Indentation};
 
 // ── Large-file threshold ──────────────────────────────────────────────────────
 
-/// Files larger than this byte count are opened in **Large File Mode**:
+/// Default for every limit in `config::LargeFileSettings`: files larger than
+/// this byte count are opened in **Large File Mode**:
 ///
 /// * Word-wrap is disabled.
 /// * Full syntax highlighting is replaced by plain-text lexing.
+/// * Word completion and occurrence highlighting are off.
 /// * Session checkpoints save metadata only (no file content).
 /// * A status-bar indicator is shown to inform the user.
 ///
-/// Adjust this constant to tune the trade-off between features and
-/// performance on the target machine class.
+/// Users tune the trade-off per feature in `settings.json` (`large_files`).
 pub(crate) const LARGE_FILE_THRESHOLD_BYTES: u64 = 50 * 1_024 * 1_024; // 50 MiB
+
+/// Default for `config::LargeFileSettings::line_length_max`: a file with a
+/// longer line (minified JavaScript or JSON, typically) opens without word
+/// wrap and syntax highlighting whatever its size, as Scintilla re-lays out
+/// and re-styles a line as a whole.
+pub(crate) const LONG_LINE_THRESHOLD_BYTES: u64 = 64 * 1_024; // 64 KiB
+
+/// Files larger than this byte count open in the read-only **viewer**
+/// (`viewer::FileWindow`): only a window of the file is held in memory and
+/// search streams over the file on disk.
+pub(crate) const HUGE_FILE_THRESHOLD_BYTES: u64 = 1_024 * 1_024 * 1_024; // 1 GiB
+
+// ── EditorView ────────────────────────────────────────────────────────────────
+
+/// Operations on one editor buffer, independent of the control behind it.
+///
+/// Positions are byte offsets into the UTF-8 text.  Methods take `&self`
+/// because a backend is a handle to state it owns elsewhere (a child window
+/// for Scintilla).
+pub(crate) trait EditorView {
+    // ── Text ──────────────────────────────────────────────────────────────────
+
+    /// Replace all text and reset the undo history and save point.
+    fn set_text(&self, text: &[u8]);
+    /// The full text.
+    fn get_text(&self) -> Vec<u8>;
+    /// Bytes `start..end` of the text.
+    fn text_range(&self, start: usize, end: usize) -> Vec<u8>;
+    /// Length of the text in bytes.
+    fn doc_len(&self) -> usize;
+    /// Mark the current text as saved (clean).
+    fn set_save_point(&self);
+    /// Refuse (or allow) edits.
+    fn set_read_only(&self, read_only: bool);
+
+    // ── Editing ───────────────────────────────────────────────────────────────
+
+    /// Set the range the next `replace_target` replaces.
+    fn set_target(&self, start: usize, end: usize);
+    /// Replace the target range with `text`; returns the length of `text`.
+    fn replace_target(&self, text: &[u8]) -> usize;
+    /// Start grouping edits into one undo step.
+    fn begin_undo_action(&self);
+    /// Close the group opened by `begin_undo_action`.
+    fn end_undo_action(&self);
+    /// EOL inserted when the user presses Enter.
+    fn eol_mode(&self) -> EolMode;
+    /// Set the EOL inserted when the user presses Enter.
+    fn set_eol_mode(&self, eol: EolMode);
+    /// Rewrite every line ending in the text as `eol`.
+    fn convert_eols(&self, eol: EolMode);
+
+    // ── Clipboard and history ─────────────────────────────────────────────────
+
+    fn undo(&self);
+    fn redo(&self);
+    /// Whether `undo` has anything to undo.
+    fn can_undo(&self) -> bool;
+    /// Whether `redo` has anything to redo.
+    fn can_redo(&self) -> bool;
+    /// Forget the undo and redo history, keeping the text and whether it is
+    /// modified.
+    fn empty_undo_buffer(&self);
+    /// Move the selection to the clipboard.
+    fn cut(&self);
+    /// Copy the selection to the clipboard.
+    fn copy_to_clipboard(&self);
+    /// Insert the clipboard text at the caret, replacing the selection.
+    fn paste(&self);
+    /// Delete the selection without touching the clipboard.
+    fn delete_selection(&self);
+    fn select_all(&self);
+
+    // ── Caret and selection ───────────────────────────────────────────────────
+
+    fn caret_pos(&self) -> usize;
+    /// Move the caret to `pos`, collapsing the selection.
+    fn set_caret_pos(&self, pos: usize);
+    fn selection_start(&self) -> usize;
+    fn selection_end(&self) -> usize;
+    /// Select from `anchor` to `caret`.
+    fn set_sel(&self, anchor: usize, caret: usize);
+    /// The selected text.
+    fn selected_text(&self) -> Vec<u8>;
+    /// Scroll so the caret is visible.
+    fn scroll_caret(&self);
+    fn first_visible_line(&self) -> usize;
+    fn set_first_visible_line(&self, line: usize);
+    fn line_count(&self) -> usize;
+    /// Position of the start of `line` (0-based).
+    fn position_from_line(&self, line: usize) -> usize;
+    /// Line (0-based) containing `pos`.
+    fn line_from_position(&self, pos: usize) -> usize;
+
+    // ── Search ────────────────────────────────────────────────────────────────
+
+    /// Select the next match of `text` after (or, if `!forward`, before)
+    /// the selection, wrapping around.  `flags` are `SCFIND_*` bits.
+    /// Returns `false` if there is none.
+    fn find_next(&self, text: &[u8], flags: u32, forward: bool) -> bool;
+
+    // ── Styling ───────────────────────────────────────────────────────────────
+
+    /// Copy the default style into every style slot.
+    fn style_clear_all(&self);
+    /// Foreground COLORREF of `style`.
+    fn style_set_fore(&self, style: u32, colour: u32);
+    /// Background COLORREF of `style`.
+    fn style_set_back(&self, style: u32, colour: u32);
+    fn style_set_bold(&self, style: u32, bold: bool);
+    /// Font face of `style`; `font_name` is null-terminated.
+    fn style_set_font(&self, style: u32, font_name: &[u8]);
+    /// Font size of `style` in points.
+    fn style_set_size(&self, style: u32, size: i32);
+    /// Tab width and tabs-vs-spaces.
+    fn set_indentation(&self, indent: Indentation);
+    fn set_word_wrap(&self, enabled: bool);
+    fn is_word_wrap(&self) -> bool;
+}
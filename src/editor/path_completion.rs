@@ -0,0 +1,198 @@
+// ── Path completion ───────────────────────────────────────────────────────────
+//
+// Candidates for completing a file path typed in the editor (`C:\Us`,
+// `./src/ma`): finds where a path starts before the caret, then lists the
+// matching entries of its directory.  Edit > Complete Word and the automatic
+// popup try this before falling back to words.  No Win32 calls here;
+// directories are read with `std::fs`.
+
+use std::path::{Path, PathBuf};
+
+use super::completion::MAX_CANDIDATES;
+
+/// Most bytes looked back from the caret for the start of a path.
+pub(crate) const MAX_TYPED_PATH: usize = 1024;
+
+/// A path typed before the caret, split after its last separator.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TypedPath<'a> {
+    /// Up to and including the last separator, e.g. `C:\Users\`.
+    pub(crate) dir: &'a str,
+    /// The partial entry name after it, e.g. `Pub`.
+    pub(crate) name: &'a str,
+    /// The separator the path uses, given to completed directories.
+    pub(crate) separator: char,
+}
+
+/// Bytes that end a path when looking back from the caret: line breaks,
+/// quotes, brackets and the characters Windows forbids in names.
+fn ends_path(b: u8) -> bool {
+    matches!(
+        b,
+        b'\n'
+            | b'\r'
+            | b'\t'
+            | b'"'
+            | b'\''
+            | b'`'
+            | b'<'
+            | b'>'
+            | b'|'
+            | b'*'
+            | b'?'
+            | b'('
+            | b')'
+            | b'['
+            | b']'
+            | b'{'
+            | b'}'
+            | b'='
+    )
+}
+
+/// Whether a path starts at `at`: a drive (`C:\`, `C:/`) not glued to a
+/// preceding word, or `./`, `../` (either separator) at the start of a word.
+fn starts_path(text: &[u8], at: usize) -> bool {
+    let rest = &text[at..];
+    let prev = at.checked_sub(1).map(|i| text[i]);
+    let drive = rest.len() >= 3
+        && rest[0].is_ascii_alphabetic()
+        && rest[1] == b':'
+        && matches!(rest[2], b'\\' | b'/')
+        && !prev.is_some_and(|b| b.is_ascii_alphanumeric());
+    let relative = [&b"./"[..], b".\\", b"../", b"..\\"]
+        .iter()
+        .any(|p| rest.starts_with(p))
+        && prev.map_or(true, |b| b.is_ascii_whitespace());
+    drive || relative
+}
+
+/// Every path that could end at the end of `before` (the text up to the
+/// caret), longest first.  A path may contain spaces, so `see C:\Program
+/// Files\Co` yields `C:\Program Files\Co`; the caller keeps the first whose
+/// directory exists.
+pub(crate) fn typed_paths(before: &[u8]) -> Vec<TypedPath<'_>> {
+    let from = before.len().saturating_sub(MAX_TYPED_PATH);
+    let span_start = before[from..]
+        .iter()
+        .rposition(|&b| ends_path(b))
+        .map_or(from, |i| from + i + 1);
+    let span = &before[span_start..];
+    (0..span.len())
+        .filter(|&i| starts_path(span, i))
+        .filter_map(|i| {
+            let path = std::str::from_utf8(&span[i..]).ok()?;
+            let cut = path.rfind(['\\', '/'])?;
+            Some(TypedPath {
+                dir: &path[..=cut],
+                name: &path[cut + 1..],
+                separator: path[cut..].chars().next()?,
+            })
+        })
+        .collect()
+}
+
+/// The directory `dir` names: relative ones (`./`, `../`) against `base`,
+/// the document's folder.  `None` for a relative path without a base.
+pub(crate) fn resolve(dir: &str, base: Option<&Path>) -> Option<PathBuf> {
+    if dir.starts_with('.') {
+        base.map(|b| b.join(dir))
+    } else {
+        Some(PathBuf::from(dir))
+    }
+}
+
+/// Names in `dir` that start with `name` (ASCII case-insensitively),
+/// directories with `separator` appended.  Sorted case-insensitively and cut
+/// to [`MAX_CANDIDATES`]; `None` if `dir` cannot be read.
+pub(crate) fn entries(dir: &Path, name: &str, separator: char) -> Option<Vec<String>> {
+    let mut found: Vec<String> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let mut entry_name = entry.file_name().into_string().ok()?;
+            let fits = entry_name.len() >= name.len()
+                && entry_name.as_bytes()[..name.len()].eq_ignore_ascii_case(name.as_bytes());
+            if !fits {
+                return None;
+            }
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                entry_name.push(separator);
+            } else if entry_name.len() == name.len() {
+                // Already typed in full.
+                return None;
+            }
+            Some(entry_name)
+        })
+        .collect();
+    found.sort_by(|a, b| {
+        a.to_ascii_lowercase()
+            .cmp(&b.to_ascii_lowercase())
+            .then(a.cmp(b))
+    });
+    found.truncate(MAX_CANDIDATES);
+    Some(found)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(dir: &'static str, name: &'static str, separator: char) -> TypedPath<'static> {
+        TypedPath {
+            dir,
+            name,
+            separator,
+        }
+    }
+
+    #[test]
+    fn finds_drive_and_relative_paths() {
+        assert_eq!(
+            typed_paths(br#"cd "C:\Program Files\Mi"#),
+            [typed(r"C:\Program Files\", "Mi", '\\')]
+        );
+        assert_eq!(
+            typed_paths(b"source ./src/ma"),
+            [typed("./src/", "ma", '/')]
+        );
+        assert_eq!(typed_paths(br"x = ..\lib\"), [typed(r"..\lib\", "", '\\')]);
+        assert!(typed_paths(b"plain words").is_empty());
+        assert!(typed_paths(b"http://example").is_empty());
+        assert!(typed_paths(b"a.b/c").is_empty());
+    }
+
+    #[test]
+    fn offers_every_start_longest_first() {
+        let got = typed_paths(b"see ./a and ./b");
+        assert_eq!(got, [typed("./a and ./", "b", '/'), typed("./", "b", '/')]);
+    }
+
+    #[test]
+    fn relative_paths_need_a_base() {
+        let base = Path::new("base");
+        assert_eq!(resolve("./src/", Some(base)), Some(base.join("./src/")));
+        assert_eq!(resolve("./src/", None), None);
+        assert_eq!(resolve(r"C:\", None), Some(PathBuf::from(r"C:\")));
+    }
+
+    #[test]
+    fn lists_matching_entries() {
+        let dir = std::env::temp_dir().join(format!("rivet-paths-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Source")).unwrap();
+        std::fs::write(dir.join("setup.py"), b"").unwrap();
+        std::fs::write(dir.join("readme"), b"").unwrap();
+        let got = entries(&dir, "s", '/').unwrap();
+        assert_eq!(got, ["setup.py", "Source/"]);
+        assert_eq!(
+            entries(&dir, "setup.py", '/').unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(entries(&dir, "source", '\\').unwrap(), ["Source\\"]);
+        assert!(entries(&dir.join("missing"), "", '/').is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,178 @@
+// ── Text pipeline ─────────────────────────────────────────────────────────────
+//
+// The recipe language behind Tools > Text Pipeline…: one step per line, run
+// top to bottom over the selected lines.  Parsing and running are pure so
+// the dialog can preview the result on every keystroke.
+// No Win32 calls here.
+
+use super::line_ops::lines;
+
+/// Step names and arguments, shown in the dialog as a reminder.
+pub(crate) const SYNTAX: &str =
+    "trim, sort [desc], unique, prefix TEXT, suffix TEXT, number [START]";
+
+/// One step of a pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// Strip leading and trailing whitespace from every line.
+    Trim,
+    /// Sort the lines by their bytes, descending with `desc`.
+    Sort { descending: bool },
+    /// Drop lines equal to an earlier one.
+    Unique,
+    /// Put the text before every line.
+    Prefix(String),
+    /// Put the text after every line.
+    Suffix(String),
+    /// Number the lines from `start`, right-aligned: `" 9. "`, `"10. "`.
+    Number { start: u64 },
+}
+
+/// Why a recipe does not parse: the 1-based recipe line and the problem.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RecipeError {
+    pub(crate) line: usize,
+    pub(crate) message: String,
+}
+
+/// Parse a recipe.  Blank lines and lines starting with `#` are skipped.
+/// The argument of `prefix` and `suffix` is everything after the first
+/// space, trailing spaces included.
+pub(crate) fn parse(recipe: &str) -> Result<Vec<Step>, RecipeError> {
+    let mut steps = Vec::new();
+    for (i, line) in recipe.lines().enumerate() {
+        let line = line.trim_start();
+        if line.trim_end().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let err = |message: String| RecipeError {
+            line: i + 1,
+            message,
+        };
+        let step = match (name.to_ascii_lowercase().as_str(), arg.trim()) {
+            ("trim", "") => Step::Trim,
+            ("sort", "") => Step::Sort { descending: false },
+            ("sort", a) if a.eq_ignore_ascii_case("desc") => Step::Sort { descending: true },
+            ("unique", "") => Step::Unique,
+            ("prefix", _) => Step::Prefix(arg.to_owned()),
+            ("suffix", _) => Step::Suffix(arg.to_owned()),
+            ("number", "") => Step::Number { start: 1 },
+            ("number", a) => match a.parse() {
+                Ok(start) => Step::Number { start },
+                Err(_) => return Err(err(format!("`{a}` is not a line number"))),
+            },
+            ("trim" | "sort" | "unique", a) => {
+                return Err(err(format!("unexpected `{a}` after `{name}`")))
+            }
+            _ => return Err(err(format!("unknown step `{name}`"))),
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// Line counts before and after a run, for the dialog's summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Stats {
+    pub(crate) lines_in: usize,
+    pub(crate) lines_out: usize,
+}
+
+/// Run `steps` over the lines of `text`.  Lines are joined with `eol`; a
+/// final EOL is kept.
+pub(crate) fn run(steps: &[Step], text: &[u8], eol: &[u8]) -> (Vec<u8>, Stats) {
+    let all = lines(text);
+    let final_eol = all.last().map_or(&b""[..], |&(_, eol)| eol);
+    let lines_in = all.len();
+    let mut lines: Vec<Vec<u8>> = all.into_iter().map(|(l, _)| l.to_vec()).collect();
+    for step in steps {
+        match step {
+            Step::Trim => {
+                for line in &mut lines {
+                    *line = line.trim_ascii().to_vec();
+                }
+            }
+            Step::Sort { descending } => {
+                lines.sort();
+                if *descending {
+                    lines.reverse();
+                }
+            }
+            Step::Unique => {
+                let mut seen = std::collections::HashSet::new();
+                lines.retain(|line| seen.insert(line.clone()));
+            }
+            Step::Prefix(prefix) => {
+                for line in &mut lines {
+                    line.splice(0..0, prefix.bytes());
+                }
+            }
+            Step::Suffix(suffix) => {
+                for line in &mut lines {
+                    line.extend_from_slice(suffix.as_bytes());
+                }
+            }
+            Step::Number { start } => {
+                let last = start + lines.len().saturating_sub(1) as u64;
+                let width = last.to_string().len();
+                for (n, line) in (*start..).zip(&mut lines) {
+                    line.splice(0..0, format!("{n:>width$}. ").into_bytes());
+                }
+            }
+        }
+    }
+    let stats = Stats {
+        lines_in,
+        lines_out: lines.len(),
+    };
+    let mut out = lines.join(eol);
+    if !final_eol.is_empty() {
+        out.extend_from_slice(eol);
+    }
+    (out, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reports_the_bad_line() {
+        assert_eq!(
+            parse("# tidy\ntrim\n\nSORT desc\nprefix - \nnumber 9").unwrap(),
+            vec![
+                Step::Trim,
+                Step::Sort { descending: true },
+                Step::Prefix("- ".to_owned()),
+                Step::Number { start: 9 },
+            ]
+        );
+        assert_eq!(
+            parse("trim\nshuffle").unwrap_err(),
+            RecipeError {
+                line: 2,
+                message: "unknown step `shuffle`".to_owned()
+            }
+        );
+        assert_eq!(parse("number x").unwrap_err().line, 1);
+        assert_eq!(parse("unique please").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn steps_run_in_order() {
+        let steps = parse("trim\nsort\nunique\nnumber 9\nsuffix ;").unwrap();
+        let (out, stats) = run(&steps, b" pear\r\napple \r\npear\r\nfig\r\n", b"\r\n");
+        assert_eq!(out, b" 9. apple;\r\n10. fig;\r\n11. pear;\r\n");
+        assert_eq!(
+            stats,
+            Stats {
+                lines_in: 4,
+                lines_out: 3
+            }
+        );
+        let (out, _) = run(&[Step::Sort { descending: true }], b"a\nc\nb", b"\n");
+        assert_eq!(out, b"c\nb\na");
+        assert_eq!(run(&[], b"x\ny\n", b"\n").0, b"x\ny\n");
+    }
+}
@@ -0,0 +1,228 @@
+// ── Scintilla and Lexilla as DLLs ─────────────────────────────────────────────
+//
+// The default `SciDll` backend: both libraries are loaded at run time.  With
+// the `static-scintilla` feature `linked` takes its place.
+//
+// ── DLL ownership model (Phase 4) ─────────────────────────────────────────────
+//
+// `SciDll` owns the `LoadLibraryExW` calls for `Scintilla.dll` and
+// `Lexilla.dll`.  It is stored in `WindowState` and lives longer than all
+// `ScintillaView` instances.
+// `ScintillaView` holds only a child `HWND`; it no longer owns the DLL.
+//
+// Drop order inside `WindowState` (Rust drops fields in declaration order):
+//   1. `app` (pure Rust, no HWNDs) — dropped first
+//   2. `sci_views` — structs with stale HWNDs (Windows already destroyed them
+//      as part of parent-window teardown before WM_DESTROY fired); no-op drop
+//   3. `sci_dll` — `FreeLibrary` called here, after all windows are gone ✓
+//
+// ── DLL location (Phase 10) ───────────────────────────────────────────────────
+//
+// `SciDll::load()` resolves every path it loads from to an absolute one and
+// calls `LoadLibraryExW` with `LOAD_WITH_ALTERED_SEARCH_PATH`, so neither the
+// CWD nor PATH can supply a DLL.  Each DLL is taken from the directory of
+// `rivet.exe` (found with `GetModuleFileNameW`) when present there; otherwise
+// the copy embedded at compile time via `include_bytes!` from `vendor/` is
+// extracted to `%TEMP%\rivet\`.  Because that directory is writable by other
+// processes, an extracted file is only loaded after it has been compared
+// byte-for-byte with the embedded copy (and rewritten once if it differs).
+// After loading, each DLL must export its expected entry point.  Any failure
+// is reported with the path the DLL was expected at.
+
+use std::os::windows::ffi::{OsStrExt as _, OsStringExt as _};
+
+use windows::{
+    core::{s, PCWSTR},
+    Win32::{
+        Foundation::{FreeLibrary, GetLastError, HANDLE, HMODULE},
+        System::LibraryLoader::{
+            GetModuleFileNameW, GetProcAddress, LoadLibraryExW, LOAD_WITH_ALTERED_SEARCH_PATH,
+        },
+    },
+};
+
+use crate::error::{Result, RivetError};
+
+// ── DLL identity ──────────────────────────────────────────────────────────────
+
+/// `Scintilla.dll` bytes embedded at compile time from `vendor/Scintilla.dll`.
+static SCINTILLA_BYTES: &[u8] = include_bytes!("../../../vendor/Scintilla.dll");
+
+/// `Lexilla.dll` bytes embedded at compile time from `vendor/Lexilla.dll`.
+static LEXILLA_BYTES: &[u8] = include_bytes!("../../../vendor/Lexilla.dll");
+
+/// Signature of Lexilla's `CreateLexer` C export.
+type CreateLexerFn = unsafe extern "C" fn(*const u8) -> *mut std::ffi::c_void;
+
+// ── SciDll ────────────────────────────────────────────────────────────────────
+
+/// RAII handles to the loaded `Scintilla.dll` and `Lexilla.dll`.
+///
+/// Loading `Scintilla.dll` registers the `"Scintilla"` window class.
+/// `Lexilla.dll` provides the `CreateLexer` function for syntax highlighting.
+/// Both are freed on `Drop`, after all `ScintillaView` child windows are gone.
+pub(crate) struct SciDll {
+    scintilla: HMODULE,
+    lexilla: HMODULE,
+    create_lexer_fn: CreateLexerFn,
+}
+
+impl SciDll {
+    /// Locate, verify, and load `Scintilla.dll` + `Lexilla.dll`.
+    ///
+    /// Each is loaded from the executable's directory if present there, or
+    /// else from a verified extraction of the embedded copy (see the module
+    /// header).  Loading registers the `"Scintilla"` class and resolves
+    /// `CreateLexer`, making `ScintillaView::create` usable.
+    pub(crate) fn load() -> Result<Self> {
+        let exe_dir = exe_dir()?;
+        let scintilla_path = locate_dll(&exe_dir, "Scintilla.dll", SCINTILLA_BYTES)?;
+        let lexilla_path = locate_dll(&exe_dir, "Lexilla.dll", LEXILLA_BYTES)?;
+
+        let scintilla = load_dll(&scintilla_path)?;
+        // Every Scintilla 5 build exports its direct-call entry point.
+        // SAFETY: scintilla is a valid HMODULE; the name is a valid PCSTR.
+        if unsafe { GetProcAddress(scintilla, s!("Scintilla_DirectFunction")) }.is_none() {
+            // SAFETY: scintilla came from LoadLibraryExW and is not used again.
+            let _ = unsafe { FreeLibrary(scintilla) };
+            return Err(RivetError::Dll {
+                detail: "Scintilla.dll is not a Scintilla 5 build.",
+                path: scintilla_path,
+            });
+        }
+
+        let lexilla = match load_dll(&lexilla_path) {
+            Ok(m) => m,
+            Err(e) => {
+                // SAFETY: scintilla came from LoadLibraryExW and is not used again.
+                let _ = unsafe { FreeLibrary(scintilla) };
+                return Err(e);
+            }
+        };
+        // SAFETY: lexilla is a valid HMODULE; "CreateLexer\0" is a valid PCSTR.
+        let Some(proc) = (unsafe { GetProcAddress(lexilla, s!("CreateLexer")) }) else {
+            // SAFETY: both modules came from LoadLibraryExW and are not used again.
+            unsafe {
+                let _ = FreeLibrary(lexilla);
+                let _ = FreeLibrary(scintilla);
+            }
+            return Err(RivetError::Dll {
+                detail: "Lexilla.dll does not export CreateLexer.",
+                path: lexilla_path,
+            });
+        };
+        // SAFETY: CreateLexer is exported from Lexilla with this exact C signature.
+        // On x64 Windows, extern "system" and extern "C" share the same ABI.
+        let create_lexer_fn: CreateLexerFn = unsafe { std::mem::transmute(proc) };
+
+        Ok(Self {
+            scintilla,
+            lexilla,
+            create_lexer_fn,
+        })
+    }
+
+    /// Call Lexilla's `CreateLexer` with a null-terminated ASCII name (e.g. `b"cpp\0"`).
+    ///
+    /// Returns a null pointer if the lexer name is unrecognised; callers pass
+    /// the result straight to `ScintillaView::set_ilexer`, which treats null as
+    /// "plain text / no highlighting".
+    pub(crate) fn create_lexer(&self, name: &[u8]) -> *mut std::ffi::c_void {
+        // SAFETY: create_lexer_fn is valid; name is a null-terminated ASCII slice.
+        unsafe { (self.create_lexer_fn)(name.as_ptr()) }
+    }
+}
+
+/// Directory containing `rivet.exe`, from `GetModuleFileNameW`.
+fn exe_dir() -> Result<std::path::PathBuf> {
+    // Large enough for any extended-length (`\\?\`) path.
+    let mut buf = vec![0u16; 32_768];
+    // SAFETY: a null module handle names the running executable; buf is a
+    // writable slice and its length is passed implicitly.
+    let len = unsafe { GetModuleFileNameW(HMODULE::default(), &mut buf) } as usize;
+    if len == 0 || len >= buf.len() {
+        return Err(RivetError::Win32 {
+            function: "GetModuleFileNameW",
+            // SAFETY: called immediately after the failing call.
+            code: unsafe { GetLastError().0 },
+        });
+    }
+    let exe = std::path::PathBuf::from(std::ffi::OsString::from_wide(&buf[..len]));
+    Ok(exe
+        .parent()
+        .map(std::path::Path::to_path_buf)
+        .unwrap_or(exe))
+}
+
+/// Absolute path to load `name` from: the copy next to the executable if
+/// there is one, otherwise `%TEMP%\rivet\name` holding exactly `embedded`.
+fn locate_dll(
+    exe_dir: &std::path::Path,
+    name: &str,
+    embedded: &[u8],
+) -> Result<std::path::PathBuf> {
+    let beside_exe = exe_dir.join(name);
+    if beside_exe.is_file() {
+        return Ok(beside_exe);
+    }
+
+    let dir = std::env::temp_dir().join("rivet");
+    let _ = std::fs::create_dir_all(&dir);
+    let path = dir.join(name);
+    let matches = |p: &std::path::Path| std::fs::read(p).is_ok_and(|b| b == embedded);
+    if !matches(&path) {
+        // Missing, stale, or tampered with.  A running instance may hold
+        // the file open, in which case the write fails and the check below
+        // decides.
+        let _ = std::fs::write(&path, embedded);
+    }
+    if !path.is_file() {
+        return Err(RivetError::Dll {
+            detail: "A required editor component could not be found or extracted.",
+            path,
+        });
+    }
+    if !matches(&path) {
+        return Err(RivetError::Dll {
+            detail: "An editor component does not match the copy built into Rivet \
+                     and could not be replaced.",
+            path,
+        });
+    }
+    Ok(path)
+}
+
+/// Load the DLL at absolute `path` with `LOAD_WITH_ALTERED_SEARCH_PATH` to
+/// prevent DLL hijacking.
+fn load_dll(path: &std::path::Path) -> Result<HMODULE> {
+    let path_wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    // SAFETY: path_wide is a valid null-terminated UTF-16 absolute path.
+    unsafe {
+        LoadLibraryExW(
+            PCWSTR(path_wide.as_ptr()),
+            HANDLE::default(),
+            LOAD_WITH_ALTERED_SEARCH_PATH,
+        )
+    }
+    .map_err(|_| RivetError::Dll {
+        detail: "A required editor component could not be loaded.",
+        path: path.to_path_buf(),
+    })
+}
+
+impl Drop for SciDll {
+    fn drop(&mut self) {
+        // SAFETY: both HMODULEs came from successful LoadLibraryExW calls and
+        // have not been freed.  All ScintillaView HWNDs are already destroyed
+        // (Windows destroys child windows before WM_DESTROY fires on the parent,
+        // and WindowState field order ensures sci_views drops before sci_dll).
+        unsafe {
+            let _ = FreeLibrary(self.lexilla);
+            let _ = FreeLibrary(self.scintilla);
+        }
+    }
+}
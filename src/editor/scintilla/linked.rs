@@ -0,0 +1,80 @@
+// ── Scintilla and Lexilla linked in ───────────────────────────────────────────
+//
+// The `SciDll` backend for builds with the `static-scintilla` feature.  The
+// static libraries Scintilla's and Lexilla's own makefiles produce
+// (`libscintilla.lib`, `liblexilla.lib`) are linked into `rivet.exe` by
+// build.rs, so nothing is loaded, extracted or verified at run time and no
+// DLL ships with the executable.
+//
+// Scintilla registers its window class only when asked: `load` calls
+// `Scintilla_RegisterClasses`, and `Drop` calls `Scintilla_ReleaseResources`,
+// which unregisters it again so a later `load` (a new window after all
+// others closed) can register it afresh.  The class is registered with
+// `CS_GLOBALCLASS`, so `ScintillaView::create` finds it whatever instance
+// handle it passes.
+
+use windows::Win32::{
+    Foundation::{GetLastError, HMODULE},
+    System::LibraryLoader::GetModuleHandleW,
+};
+
+use crate::error::{Result, RivetError};
+
+extern "C" {
+    /// Register the `"Scintilla"` window class for `hinstance`; 0 on failure.
+    fn Scintilla_RegisterClasses(hinstance: *mut std::ffi::c_void) -> i32;
+    /// Unregister the class and free Scintilla's global resources.
+    fn Scintilla_ReleaseResources() -> i32;
+    /// Lexilla's factory: a new lexer for `name`, or null if it is unknown.
+    fn CreateLexer(name: *const u8) -> *mut std::ffi::c_void;
+}
+
+// ── SciDll ────────────────────────────────────────────────────────────────────
+
+/// Registration of the statically linked Scintilla window class.
+///
+/// Kept in `WindowState` like the DLL backend, so the class outlives every
+/// `ScintillaView` and is released on `Drop`.
+pub(crate) struct SciDll {
+    _registered: (),
+}
+
+impl SciDll {
+    /// Register the `"Scintilla"` class, making `ScintillaView::create`
+    /// usable.  Lexilla needs no setup.
+    pub(crate) fn load() -> Result<Self> {
+        // SAFETY: a null module name asks for the running executable's handle.
+        let module: HMODULE = unsafe { GetModuleHandleW(None) }?;
+        // SAFETY: module is the executable's own instance handle, valid for
+        // the life of the process.
+        if unsafe { Scintilla_RegisterClasses(module.0) } == 0 {
+            return Err(RivetError::Win32 {
+                function: "Scintilla_RegisterClasses",
+                // SAFETY: called immediately after the failing call.
+                code: unsafe { GetLastError().0 },
+            });
+        }
+        Ok(Self { _registered: () })
+    }
+
+    /// Call Lexilla's `CreateLexer` with a null-terminated ASCII name (e.g. `b"cpp\0"`).
+    ///
+    /// Returns a null pointer if the lexer name is unrecognised; callers pass
+    /// the result straight to `ScintillaView::set_ilexer`, which treats null as
+    /// "plain text / no highlighting".
+    pub(crate) fn create_lexer(&self, name: &[u8]) -> *mut std::ffi::c_void {
+        // SAFETY: CreateLexer is linked from Lexilla with this C signature;
+        // name is a null-terminated ASCII slice.
+        unsafe { CreateLexer(name.as_ptr()) }
+    }
+}
+
+impl Drop for SciDll {
+    fn drop(&mut self) {
+        // SAFETY: the class was registered by `load`; all ScintillaView HWNDs
+        // are already destroyed (see the ownership notes in `dll`).
+        unsafe {
+            Scintilla_ReleaseResources();
+        }
+    }
+}
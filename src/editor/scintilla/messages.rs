@@ -21,12 +21,30 @@ pub(super) const SCI_GETLENGTH: u32 = 2006;
 pub(super) const SCI_GETTEXT: u32 = 2182;
 /// Mark the current state as the save point.
 pub(super) const SCI_SETSAVEPOINT: u32 = 2014;
+/// Make the document read-only (WPARAM = 1) or editable (WPARAM = 0).
+/// While read-only every modifying message, including `SCI_SETTEXT`, is ignored.
+pub(super) const SCI_SETREADONLY: u32 = 2171;
+/// Show Scintilla's own right-click menu (WPARAM = 1) or not (0).  Without
+/// it, `WM_CONTEXTMENU` goes on to the parent window.
+pub(super) const SCI_USEPOPUP: u32 = 2371;
+/// Append bytes at the end without moving the caret.  WPARAM=length; LPARAM=ptr.
+pub(super) const SCI_APPENDTEXT: u32 = 2282;
+/// Start (WPARAM = 1) or stop (WPARAM = 0) recording undo actions.
+pub(super) const SCI_SETUNDOCOLLECTION: u32 = 2012;
+/// Discard all undo and redo history.
+pub(super) const SCI_EMPTYUNDOBUFFER: u32 = 2175;
+/// Non-zero if there is an action to undo.
+pub(super) const SCI_CANUNDO: u32 = 2174;
+/// Non-zero if there is an undone action to redo.
+pub(super) const SCI_CANREDO: u32 = 2016;
 
 // ── Lexer / Large File Mode ───────────────────────────────────────────────────
 
 /// Set lexer via ILexer5* (Scintilla 5.x / Lexilla).
 /// WPARAM = 0; LPARAM = ILexer5* from Lexilla CreateLexer(), or 0 for plain text.
 pub(super) const SCI_SETILEXER: u32 = 4033;
+/// Style a range now.  WPARAM = start; LPARAM = end, or -1 for the whole document.
+pub(super) const SCI_COLOURISE: u32 = 4003;
 
 // ── Style operation messages ───────────────────────────────────────────────────
 
@@ -310,6 +328,36 @@ pub(crate) const SCE_POWERSHELL_HERE_CHARACTER: u32 = 16;
 #[allow(dead_code)]
 pub(crate) const SCE_POWERSHELL_COMMENTDOCKEYWORD: u32 = 17;
 
+// ── Indentation ───────────────────────────────────────────────────────────────
+
+/// Set the tab-stop width in characters.  WPARAM = width.
+pub(super) const SCI_SETTABWIDTH: u32 = 2036;
+/// Indent with tabs (WPARAM = 1) or spaces (WPARAM = 0).
+pub(super) const SCI_SETUSETABS: u32 = 2124;
+/// Set the indent size; 0 means "same as the tab width".  WPARAM = size.
+pub(super) const SCI_SETINDENT: u32 = 2122;
+
+// ── Drawing ───────────────────────────────────────────────────────────────────
+
+/// Choose what draws the text.  WPARAM = SC_TECHNOLOGY_*.
+pub(super) const SCI_SETTECHNOLOGY: u32 = 2630;
+/// GDI.
+pub(super) const SC_TECHNOLOGY_DEFAULT: usize = 0;
+/// Direct2D and DirectWrite.
+pub(super) const SC_TECHNOLOGY_DIRECTWRITE: usize = 1;
+/// Draw through an off-screen bitmap.  WPARAM = boolean.
+pub(super) const SCI_SETBUFFEREDDRAW: u32 = 2035;
+/// How much text to style while idle.  WPARAM = SC_IDLESTYLING_*.
+pub(super) const SCI_SETIDLESTYLING: u32 = 2692;
+pub(super) const SC_IDLESTYLING_NONE: usize = 0;
+pub(super) const SC_IDLESTYLING_TOVISIBLE: usize = 1;
+pub(super) const SC_IDLESTYLING_AFTERVISIBLE: usize = 2;
+pub(super) const SC_IDLESTYLING_ALL: usize = 3;
+/// Drawing passes per line.  WPARAM = SC_PHASES_*.
+pub(super) const SCI_SETPHASESDRAW: u32 = 2673;
+pub(super) const SC_PHASES_TWO: usize = 1;
+pub(super) const SC_PHASES_MULTIPLE: usize = 2;
+
 // ── Word wrap ─────────────────────────────────────────────────────────────────
 
 /// Set word-wrap mode.
@@ -321,16 +369,104 @@ pub(super) const SC_WRAP_NONE: usize = 0;
 /// Wrap at word boundaries.
 pub(super) const SC_WRAP_WORD: usize = 1;
 
+// ── Caret appearance ──────────────────────────────────────────────────────────
+
+/// Set the caret shape.  WPARAM = `CARETSTYLE_*`.
+pub(super) const SCI_SETCARETSTYLE: u32 = 2512;
+/// Line caret between characters.
+pub(super) const CARETSTYLE_LINE: usize = 1;
+/// Block caret over the next character.
+pub(super) const CARETSTYLE_BLOCK: usize = 2;
+/// Set the line caret width in pixels.  WPARAM = width.
+pub(super) const SCI_SETCARETWIDTH: u32 = 2188;
+/// Set the blink half-period in milliseconds; 0 stops blinking.
+pub(super) const SCI_SETCARETPERIOD: u32 = 2076;
+
+// ── Caret movement ────────────────────────────────────────────────────────────
+
+/// Bind a key to a command.  WPARAM = key | (modifiers << 16), LPARAM = SCI_*.
+pub(super) const SCI_ASSIGNCMDKEY: u32 = 2070;
+/// Key code for Home in `SCI_ASSIGNCMDKEY`.
+pub(super) const SCK_HOME: usize = 2306;
+/// Shift modifier in `SCI_ASSIGNCMDKEY`.
+pub(super) const SCMOD_SHIFT: usize = 1;
+/// Caret to column 0.
+pub(super) const SCI_HOME: u32 = 2312;
+/// Extend the selection to column 0.
+pub(super) const SCI_HOMEEXTEND: u32 = 2313;
+/// Caret to the first non-blank character, or to column 0 if already there.
+pub(super) const SCI_VCHOME: u32 = 2331;
+/// Extend the selection like `SCI_VCHOME`.
+pub(super) const SCI_VCHOMEEXTEND: u32 = 2332;
+/// Remove a key binding.  WPARAM = key | (modifiers << 16).
+pub(super) const SCI_CLEARCMDKEY: u32 = 2071;
+/// Key code for Insert in `SCI_ASSIGNCMDKEY`.
+pub(super) const SCK_INSERT: usize = 2309;
+/// Switch between insert and overtype.
+pub(super) const SCI_EDITTOGGLEOVERTYPE: u32 = 2324;
+/// Set overtype mode.  WPARAM = 1 to overwrite, 0 to insert.
+pub(super) const SCI_SETOVERTYPE: u32 = 2186;
+/// Whether typing overwrites the next character.
+pub(super) const SCI_GETOVERTYPE: u32 = 2187;
+/// How the view scrolls to keep the caret visible vertically.
+/// WPARAM = `CARET_*` flags, LPARAM = slop in lines.
+pub(super) const SCI_SETYCARETPOLICY: u32 = 2403;
+/// Keep the caret out of a `slop`-line margin at the top and bottom.
+pub(super) const CARET_SLOP: usize = 0x01;
+/// Enforce the policy on every caret move, not only when the caret leaves
+/// the view.
+pub(super) const CARET_STRICT: usize = 0x04;
+/// Treat the top and bottom margins alike.
+pub(super) const CARET_EVEN: usize = 0x08;
+/// Where the caret may go past the end of a line.  WPARAM = `SCVS_*` flags.
+pub(super) const SCI_SETVIRTUALSPACEOPTIONS: u32 = 2596;
+/// No virtual space.
+pub(super) const SCVS_NONE: usize = 0;
+/// Virtual space in rectangular selections.
+pub(super) const SCVS_RECTANGULARSELECTION: usize = 1;
+/// Arrow keys and clicks may place the caret past the end of a line.
+pub(super) const SCVS_USERACCESSIBLE: usize = 2;
+/// Selection background.  WPARAM = use the colour (0 restores the default),
+/// LPARAM = COLORREF.
+pub(super) const SCI_SETSELBACK: u32 = 2068;
+/// Selected text colour.  WPARAM = use the colour (0 restores the
+/// default), LPARAM = COLORREF.
+pub(super) const SCI_SETSELFORE: u32 = 2067;
+/// Caret colour.  WPARAM = COLORREF.
+pub(super) const SCI_SETCARETFORE: u32 = 2069;
+/// Highlight the line containing the caret.  WPARAM = bool.
+pub(super) const SCI_SETCARETLINEVISIBLE: u32 = 2096;
+/// Background of the caret line.  WPARAM = COLORREF.
+pub(super) const SCI_SETCARETLINEBACK: u32 = 2098;
+
 // ── Caret / position ──────────────────────────────────────────────────────────
 
 /// Return the byte position of the caret.
 pub(super) const SCI_GETCURRENTPOS: u32 = 2008;
+/// Client x-coordinate of position LPARAM.
+pub(super) const SCI_POINTXFROMPOSITION: u32 = 2164;
+/// Client y-coordinate of the top of position LPARAM.
+pub(super) const SCI_POINTYFROMPOSITION: u32 = 2165;
+/// Height in pixels of line WPARAM.
+pub(super) const SCI_TEXTHEIGHT: u32 = 2279;
 /// Move the caret to a byte position (also scrolls into view).
 pub(super) const SCI_GOTOPOS: u32 = 2025;
 /// Convert a byte position to a 0-based line number.
 pub(super) const SCI_LINEFROMPOSITION: u32 = 2166;
 /// Return the visible column of a position (tab-aware).
+#[allow(dead_code)]
 pub(super) const SCI_GETCOLUMN: u32 = 2129;
+/// Count characters (not bytes) between two positions.
+/// WPARAM = start; LPARAM = end.
+pub(super) const SCI_COUNTCHARACTERS: u32 = 2633;
+/// Ask Scintilla to maintain a per-line character index.
+/// WPARAM = `SC_LINECHARACTERINDEX_*`.
+pub(super) const SCI_ALLOCATELINECHARACTERINDEX: u32 = 2711;
+/// Character index of the start of a line (requires the index above).
+/// WPARAM = line; LPARAM = `SC_LINECHARACTERINDEX_*`.
+pub(super) const SCI_INDEXPOSITIONFROMLINE: u32 = 2714;
+/// Index characters as UTF-32 code points.
+pub(super) const SC_LINECHARACTERINDEX_UTF32: usize = 1;
 
 // ── Scroll ────────────────────────────────────────────────────────────────────
 
@@ -407,6 +543,196 @@ pub(super) const SCI_GETSELECTIONEND: u32 = 2145;
 pub(super) const SCI_SETSEL: u32 = 2163;
 /// Scroll to make the caret visible.
 pub(super) const SCI_SCROLLCARET: u32 = 2169;
+/// Copy the selected text.  LPARAM = buffer ptr, or 0 to query the length
+/// (excluding the null terminator).
+pub(super) const SCI_GETSELTEXT: u32 = 2161;
+/// Allow more than one selection (Ctrl+click, Select All Matches).
+pub(super) const SCI_SETMULTIPLESELECTION: u32 = 2563;
+/// Type into every selection at once, not only the main one.
+pub(super) const SCI_SETADDITIONALSELECTIONTYPING: u32 = 2565;
+/// Replace all selections with one.  WPARAM = caret; LPARAM = anchor.
+pub(super) const SCI_SETSELECTION: u32 = 2572;
+/// Add a selection and make it the main one.  WPARAM = caret; LPARAM = anchor.
+pub(super) const SCI_ADDSELECTION: u32 = 2573;
+
+// ── Autocompletion ────────────────────────────────────────────────────────────
+
+/// Show a completion list.  WPARAM = length of the typed prefix; LPARAM =
+/// null-terminated words split by the `SCI_AUTOCSETSEPARATOR` byte.
+pub(super) const SCI_AUTOCSHOW: u32 = 2100;
+/// Byte between the words of an `SCI_AUTOCSHOW` list (default space).
+pub(super) const SCI_AUTOCSETSEPARATOR: u32 = 2106;
+/// Non-zero while a completion list is shown.
+pub(super) const SCI_AUTOCACTIVE: u32 = 2102;
+/// Match the typed prefix against the list ignoring case.  WPARAM = bool.
+pub(super) const SCI_AUTOCSETIGNORECASE: u32 = 2115;
+/// Order of `SCI_AUTOCSHOW` lists.  WPARAM = `SC_ORDER_*`.
+pub(super) const SCI_AUTOCSETORDER: u32 = 2660;
+/// Let Scintilla sort the completion list itself.
+pub(super) const SC_ORDER_PERFORMSORT: usize = 1;
+/// Copy a byte range.  LPARAM = `Sci_TextRangeFull*` (Scintilla 5.3+).
+pub(super) const SCI_GETTEXTRANGEFULL: u32 = 2039;
+
+// ── Auto-close brackets ───────────────────────────────────────────────────────
+
+/// Byte at a position, or 0 past the end.  WPARAM = position.
+pub(super) const SCI_GETCHARAT: u32 = 2007;
+/// Insert text without moving a caret at the insertion point.
+/// WPARAM = position; LPARAM = null-terminated text.
+pub(super) const SCI_INSERTTEXT: u32 = 2003;
+/// Delete a range.  WPARAM = start; LPARAM = length.
+pub(super) const SCI_DELETERANGE: u32 = 2645;
+/// Set an indicator's drawing style.  WPARAM = indicator; LPARAM = `INDIC_*`.
+pub(super) const SCI_INDICSETSTYLE: u32 = 2080;
+/// Choose the indicator that fill/clear operations apply to.
+pub(super) const SCI_SETINDICATORCURRENT: u32 = 2500;
+/// Set the current indicator over a range.  WPARAM = start; LPARAM = length.
+pub(super) const SCI_INDICATORFILLRANGE: u32 = 2504;
+/// Indicator value at a position.  WPARAM = indicator; LPARAM = position.
+pub(super) const SCI_INDICATORVALUEAT: u32 = 2508;
+/// Indicator style that draws nothing: used purely to tag text.
+pub(super) const INDIC_HIDDEN: isize = 5;
+/// Container indicator tagging closers inserted by auto-close.  Indicators
+/// move with the text, so the tag survives edits elsewhere in the line.
+pub(super) const INDICATOR_AUTO_CLOSE: usize = 8;
+
+// ── Occurrence highlighting ───────────────────────────────────────────────────
+
+/// Set an indicator's colour.  WPARAM = indicator; LPARAM = COLORREF.
+pub(super) const SCI_INDICSETFORE: u32 = 2082;
+/// Draw an indicator under the text instead of over it.
+/// WPARAM = indicator; LPARAM = bool.
+pub(super) const SCI_INDICSETUNDER: u32 = 2510;
+/// Fill opacity of a box indicator.  WPARAM = indicator; LPARAM = 0–255.
+pub(super) const SCI_INDICSETALPHA: u32 = 2523;
+/// Outline opacity of a box indicator.  WPARAM = indicator; LPARAM = 0–255.
+pub(super) const SCI_INDICSETOUTLINEALPHA: u32 = 2558;
+/// Clear the current indicator over a range.  WPARAM = start; LPARAM = length.
+pub(super) const SCI_INDICATORCLEARRANGE: u32 = 2505;
+/// Indicator style: a filled box with rounded corners.
+pub(super) const INDIC_ROUNDBOX: isize = 7;
+/// Container indicator marking the other occurrences of the word at the
+/// caret.
+pub(super) const INDICATOR_OCCURRENCE: usize = 9;
+
+// ── Change history ────────────────────────────────────────────────────────────
+
+/// Track which lines changed since load (Scintilla 5.3+).  WPARAM =
+/// `SC_CHANGE_HISTORY_*` flags.  Can only be turned on while the undo buffer
+/// is empty; emptying it later resets the history.
+pub(super) const SCI_SETCHANGEHISTORY: u32 = 2780;
+/// `SCI_SETCHANGEHISTORY` flag: record changes.
+pub(super) const SC_CHANGE_HISTORY_ENABLED: usize = 1;
+/// `SCI_SETCHANGEHISTORY` flag: show them as markers in the symbol margin.
+pub(super) const SC_CHANGE_HISTORY_MARKERS: usize = 2;
+/// Marker for lines changed and then undone back to the loaded text.
+pub(super) const SC_MARKNUM_HISTORY_REVERTED_TO_ORIGIN: u32 = 21;
+/// Marker for lines changed before the last save.
+pub(super) const SC_MARKNUM_HISTORY_SAVED: u32 = 22;
+/// Marker for lines changed since the last save.
+pub(super) const SC_MARKNUM_HISTORY_MODIFIED: u32 = 23;
+/// Marker for lines changed, saved, and then undone past the save.
+pub(super) const SC_MARKNUM_HISTORY_REVERTED_TO_MODIFIED: u32 = 24;
+/// Set a marker's outline colour.  WPARAM = marker; LPARAM = COLORREF.
+pub(super) const SCI_MARKERSETFORE: u32 = 2041;
+/// Set a marker's fill colour.  WPARAM = marker; LPARAM = COLORREF.
+pub(super) const SCI_MARKERSETBACK: u32 = 2042;
+/// First line at or after WPARAM carrying a marker in the LPARAM mask, or -1.
+pub(super) const SCI_MARKERNEXT: u32 = 2047;
+/// Last line at or before WPARAM carrying a marker in the LPARAM mask, or -1.
+pub(super) const SCI_MARKERPREVIOUS: u32 = 2048;
+
+// ── Git gutter ────────────────────────────────────────────────────────────────
+
+/// Markers for lines added, modified, and with lines deleted above them,
+/// relative to the file's git HEAD.  Below the change-history markers.
+pub(super) const MARKER_GIT_ADDED: u32 = 10;
+pub(super) const MARKER_GIT_MODIFIED: u32 = 11;
+pub(super) const MARKER_GIT_DELETED: u32 = 12;
+/// The margin that shows only the git markers, between the symbol margin
+/// and the text.
+pub(super) const MARGIN_GIT: usize = 3;
+/// Set a marker's symbol.  WPARAM = marker; LPARAM = `SC_MARK_*`.
+pub(super) const SCI_MARKERDEFINE: u32 = 2040;
+/// Add a marker to a line.  WPARAM = line; LPARAM = marker.
+pub(super) const SCI_MARKERADD: u32 = 2043;
+/// Remove a marker from every line.  WPARAM = marker (-1 for all).
+pub(super) const SCI_MARKERDELETEALL: u32 = 2045;
+/// Set which markers a margin shows.  WPARAM = margin; LPARAM = bit mask.
+pub(super) const SCI_SETMARGINMASKN: u32 = 2244;
+/// Which markers a margin shows.  WPARAM = margin.
+pub(super) const SCI_GETMARGINMASKN: u32 = 2245;
+/// Marker symbol filling the margin width.
+pub(super) const SC_MARK_FULLRECT: usize = 26;
+/// Marker symbol filling the bottom edge of the line in the margin.
+pub(super) const SC_MARK_UNDERLINE: usize = 29;
+
+// ── Document map ──────────────────────────────────────────────────────────────
+
+/// Return the view's document (an opaque pointer).
+pub(super) const SCI_GETDOCPOINTER: u32 = 2357;
+/// Show another view's document.  LPARAM = pointer from `SCI_GETDOCPOINTER`;
+/// Scintilla reference-counts it, so either view may be destroyed first.
+pub(super) const SCI_SETDOCPOINTER: u32 = 2358;
+/// Magnify or shrink text by WPARAM points (−10 to +20).
+pub(super) const SCI_SETZOOM: u32 = 2373;
+/// Number of whole lines that fit in the view.
+pub(super) const SCI_LINESONSCREEN: u32 = 2370;
+/// Set a margin's width in pixels.  WPARAM = margin; LPARAM = width.
+pub(super) const SCI_SETMARGINWIDTHN: u32 = 2242;
+/// Show or hide the horizontal scroll bar.  WPARAM = bool.
+pub(super) const SCI_SETHSCROLLBAR: u32 = 2130;
+/// Show or hide the vertical scroll bar.  WPARAM = bool.
+pub(super) const SCI_SETVSCROLLBAR: u32 = 2280;
+/// Position nearest a client point.  WPARAM = x; LPARAM = y.
+pub(super) const SCI_POSITIONFROMPOINT: u32 = 2022;
+/// Document line shown on display line WPARAM (differs under word wrap).
+pub(super) const SCI_DOCLINEFROMVISIBLE: u32 = 2221;
+/// First display line of document line WPARAM.
+pub(super) const SCI_VISIBLEFROMDOCLINE: u32 = 2220;
+/// Extend the selection colour to the right edge on selected line ends.
+pub(super) const SCI_SETSELEOLFILLED: u32 = 2480;
+/// Set an element's colour.  WPARAM = `SC_ELEMENT_*`; LPARAM = colour with
+/// alpha in the high byte.
+pub(super) const SCI_SETELEMENTCOLOUR: u32 = 2753;
+/// Element: selection background while the view does not have focus.
+pub(super) const SC_ELEMENT_SELECTION_INACTIVE_BACK: usize = 17;
+/// `SCI_SETCARETSTYLE` value: draw no caret.
+pub(super) const CARETSTYLE_INVISIBLE: usize = 0;
+
+// ── Styled export ─────────────────────────────────────────────────────────────
+
+/// Copy a range as (byte, style) pairs.  LPARAM = `Sci_TextRangeFull*` whose
+/// buffer holds two bytes per position plus two null bytes.
+pub(super) const SCI_GETSTYLEDTEXTFULL: u32 = 2778;
+/// Return a style's text colour (COLORREF).  WPARAM = style.
+pub(super) const SCI_STYLEGETFORE: u32 = 2481;
+/// Return a style's background colour (COLORREF).  WPARAM = style.
+pub(super) const SCI_STYLEGETBACK: u32 = 2482;
+/// Return whether a style is bold.  WPARAM = style.
+pub(super) const SCI_STYLEGETBOLD: u32 = 2483;
+/// Return whether a style is italic.  WPARAM = style.
+pub(super) const SCI_STYLEGETITALIC: u32 = 2484;
+/// Return a style's font size in points.  WPARAM = style.
+pub(super) const SCI_STYLEGETSIZE: u32 = 2485;
+/// Copy a style's font name.  WPARAM = style; LPARAM = buffer ptr, or 0 to
+/// query the length (excluding the null terminator).
+pub(super) const SCI_STYLEGETFONT: u32 = 2486;
+
+// ── Column editor ─────────────────────────────────────────────────────────────
+
+/// Non-zero if the selection is rectangular (Alt+drag, Alt+Shift+arrows).
+pub(super) const SCI_SELECTIONISRECTANGLE: u32 = 2372;
+/// Return the number of selections; a rectangle has one per line.
+pub(super) const SCI_GETSELECTIONS: u32 = 2570;
+/// Return selection n's caret position.  WPARAM = n.
+pub(super) const SCI_GETSELECTIONNCARET: u32 = 2577;
+/// Return selection n's anchor position.  WPARAM = n.
+pub(super) const SCI_GETSELECTIONNANCHOR: u32 = 2579;
+/// Return selection n's caret virtual space.  WPARAM = n.
+pub(super) const SCI_GETSELECTIONNCARETVIRTUALSPACE: u32 = 2581;
+/// Return selection n's anchor virtual space.  WPARAM = n.
+pub(super) const SCI_GETSELECTIONNANCHORVIRTUALSPACE: u32 = 2583;
 
 // ── Undo grouping ─────────────────────────────────────────────────────────────
 
@@ -421,6 +747,10 @@ pub(super) const SCI_ENDUNDOACTION: u32 = 2079;
 pub(super) const SCI_GETLINECOUNT: u32 = 2154;
 /// Return the byte position of the start of `line` (0-based).  WPARAM = line.
 pub(super) const SCI_POSITIONFROMLINE: u32 = 2167;
+/// Return the byte length of `line` including its EOL.  WPARAM = line.
+pub(super) const SCI_LINELENGTH: u32 = 2350;
+/// Copy `line` (with EOL, no terminator).  WPARAM = line; LPARAM = buffer ptr.
+pub(super) const SCI_GETLINE: u32 = 2153;
 
 // ── Find flags (pub(crate) for use in window.rs) ──────────────────────────────
 
@@ -433,7 +763,24 @@ pub(crate) const SCFIND_WHOLEWORD: u32 = 0x0000_0002;
 
 /// Caret moved or selection changed.
 pub(crate) const SCN_UPDATEUI: u32 = 2007;
+/// A character was typed.  `SCNotification::ch` holds it.
+pub(crate) const SCN_CHARADDED: u32 = 2001;
+/// An entry of the completion list was inserted.
+pub(crate) const SCN_AUTOCCOMPLETED: u32 = 2030;
 /// Document first edited after a save point.
-pub(crate) const SCN_SAVEPOINTLEFT: u32 = 2001;
+pub(crate) const SCN_SAVEPOINTLEFT: u32 = 2003;
 /// Document returned to a save point (e.g. undo).
 pub(crate) const SCN_SAVEPOINTREACHED: u32 = 2002;
+/// Text was inserted or deleted (among other changes).
+/// `SCNotification::modificationType` says which.
+pub(crate) const SCN_MODIFIED: u32 = 2008;
+/// `SCN_MODIFIED` flag: text was inserted.
+pub(crate) const SC_MOD_INSERTTEXT: i32 = 0x01;
+/// `SCN_MODIFIED` flag: text was deleted.
+pub(crate) const SC_MOD_DELETETEXT: i32 = 0x02;
+/// `SCN_MODIFIED` flag: the change is an undo.
+pub(crate) const SC_PERFORMED_UNDO: i32 = 0x20;
+/// `SCN_MODIFIED` flag: text is about to be inserted.
+pub(crate) const SC_MOD_BEFOREINSERT: i32 = 0x400;
+/// `SCN_MODIFIED` flag: text is about to be deleted.
+pub(crate) const SC_MOD_BEFOREDELETE: i32 = 0x800;
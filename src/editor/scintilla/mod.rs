@@ -3,51 +3,77 @@
 // This is one of exactly two modules where `unsafe` is permitted.
 // Every `unsafe` block MUST carry a `// SAFETY:` comment.
 //
-// ── DLL ownership model (Phase 4) ─────────────────────────────────────────────
+// ── Backends ──────────────────────────────────────────────────────────────────
 //
-// `SciDll` owns the single `LoadLibraryW` call for `SciLexer.dll`.  It is
-// stored in `WindowState` and lives longer than all `ScintillaView` instances.
-// `ScintillaView` holds only a child `HWND`; it no longer owns the DLL.
-//
-// Drop order inside `WindowState` (Rust drops fields in declaration order):
-//   1. `app` (pure Rust, no HWNDs) — dropped first
-//   2. `sci_views` — structs with stale HWNDs (Windows already destroyed them
-//      as part of parent-window teardown before WM_DESTROY fired); no-op drop
-//   3. `sci_dll` — `FreeLibrary` called here, after all windows are gone ✓
-//
-// ── Embedded DLL (Phase 10) ───────────────────────────────────────────────────
-//
-// `SciLexer.dll` is embedded at compile time via `include_bytes!` from
-// `vendor/SciLexer.dll`.  On first use `SciDll::load()` extracts it to
-// `%TEMP%\rivet\SciLexer.dll` and loads from that absolute path with
-// `LOAD_WITH_ALTERED_SEARCH_PATH`, preventing DLL hijacking.  The extraction
-// is skipped silently if the file is already present (e.g. a second instance),
-// and the app still loads the existing copy successfully.
+// `SciDll` makes the `"Scintilla"` window class and Lexilla's `CreateLexer`
+// available.  By default it loads `Scintilla.dll` and `Lexilla.dll` at run
+// time (`dll`); built with the `static-scintilla` feature it uses libraries
+// linked into the executable instead (`linked`), and no DLL ships.
 
 #![allow(unsafe_code)]
 
 pub mod messages;
 
+#[cfg(not(feature = "static-scintilla"))]
+mod dll;
+#[cfg(feature = "static-scintilla")]
+mod linked;
+
+#[cfg(not(feature = "static-scintilla"))]
+pub(crate) use dll::SciDll;
+#[cfg(feature = "static-scintilla")]
+pub(crate) use linked::SciDll;
+
 use messages::{
-    SCI_BEGINUNDOACTION, SCI_CONVERTEOLS, SCI_ENDUNDOACTION, SCI_GETCOLUMN, SCI_GETCURRENTPOS,
-    SCI_GETEOLMODE, SCI_GETFIRSTVISIBLELINE, SCI_GETLENGTH, SCI_GETLINECOUNT, SCI_GETSELECTIONEND,
-    SCI_GETSELECTIONSTART, SCI_GETTARGETEND, SCI_GETTEXT, SCI_GETWRAPMODE, SCI_GOTOPOS,
-    SCI_LINEFROMPOSITION, SCI_POSITIONFROMLINE, SCI_REDO, SCI_REPLACETARGET, SCI_SCROLLCARET,
-    SCI_SEARCHINTARGET, SCI_SELECTALL, SCI_SETCODEPAGE, SCI_SETEOLMODE, SCI_SETFIRSTVISIBLELINE,
-    SCI_SETKEYWORDS, SCI_SETILEXER, SCI_SETSAVEPOINT, SCI_SETSEARCHFLAGS, SCI_SETSEL,
-    SCI_SETTARGETEND, SCI_SETTARGETSTART, SCI_SETTEXT, SCI_SETWRAPMODE, SCI_STYLECLEARALL,
-    SCI_STYLESETBACK, SCI_STYLESETBOLD, SCI_STYLESETFONT, SCI_STYLESETFORE, SCI_STYLESETSIZE,
-    SC_CP_UTF8, SC_EOL_CR, SC_EOL_CRLF, SC_EOL_LF, SC_WRAP_NONE, SC_WRAP_WORD,
-    WM_CLEAR, WM_COPY, WM_CUT, WM_PASTE, WM_UNDO,
+    CARETSTYLE_BLOCK, CARETSTYLE_INVISIBLE, CARETSTYLE_LINE, CARET_EVEN, CARET_SLOP, CARET_STRICT,
+    INDICATOR_AUTO_CLOSE, INDICATOR_OCCURRENCE, INDIC_HIDDEN, INDIC_ROUNDBOX, MARGIN_GIT,
+    MARKER_GIT_ADDED, MARKER_GIT_DELETED, MARKER_GIT_MODIFIED, SCI_ADDSELECTION,
+    SCI_ALLOCATELINECHARACTERINDEX, SCI_APPENDTEXT, SCI_ASSIGNCMDKEY, SCI_AUTOCACTIVE,
+    SCI_AUTOCSETIGNORECASE, SCI_AUTOCSETORDER, SCI_AUTOCSETSEPARATOR, SCI_AUTOCSHOW,
+    SCI_BEGINUNDOACTION, SCI_CANREDO, SCI_CANUNDO, SCI_CLEARCMDKEY, SCI_COLOURISE, SCI_CONVERTEOLS,
+    SCI_COUNTCHARACTERS, SCI_DELETERANGE, SCI_DOCLINEFROMVISIBLE, SCI_EDITTOGGLEOVERTYPE,
+    SCI_EMPTYUNDOBUFFER, SCI_ENDUNDOACTION, SCI_GETCHARAT, SCI_GETCURRENTPOS, SCI_GETDOCPOINTER,
+    SCI_GETEOLMODE, SCI_GETFIRSTVISIBLELINE, SCI_GETLENGTH, SCI_GETLINE, SCI_GETLINECOUNT,
+    SCI_GETMARGINMASKN, SCI_GETOVERTYPE, SCI_GETSELECTIONEND, SCI_GETSELECTIONNANCHOR,
+    SCI_GETSELECTIONNANCHORVIRTUALSPACE, SCI_GETSELECTIONNCARET,
+    SCI_GETSELECTIONNCARETVIRTUALSPACE, SCI_GETSELECTIONS, SCI_GETSELECTIONSTART, SCI_GETSELTEXT,
+    SCI_GETSTYLEDTEXTFULL, SCI_GETTARGETEND, SCI_GETTEXT, SCI_GETTEXTRANGEFULL, SCI_GETWRAPMODE,
+    SCI_GOTOPOS, SCI_HOME, SCI_HOMEEXTEND, SCI_INDEXPOSITIONFROMLINE, SCI_INDICATORCLEARRANGE,
+    SCI_INDICATORFILLRANGE, SCI_INDICATORVALUEAT, SCI_INDICSETALPHA, SCI_INDICSETFORE,
+    SCI_INDICSETOUTLINEALPHA, SCI_INDICSETSTYLE, SCI_INDICSETUNDER, SCI_INSERTTEXT,
+    SCI_LINEFROMPOSITION, SCI_LINELENGTH, SCI_LINESONSCREEN, SCI_MARKERADD, SCI_MARKERDEFINE,
+    SCI_MARKERDELETEALL, SCI_MARKERNEXT, SCI_MARKERPREVIOUS, SCI_MARKERSETBACK, SCI_MARKERSETFORE,
+    SCI_POINTXFROMPOSITION, SCI_POINTYFROMPOSITION, SCI_POSITIONFROMLINE, SCI_POSITIONFROMPOINT,
+    SCI_REDO, SCI_REPLACETARGET, SCI_SCROLLCARET, SCI_SEARCHINTARGET, SCI_SELECTALL,
+    SCI_SELECTIONISRECTANGLE, SCI_SETADDITIONALSELECTIONTYPING, SCI_SETBUFFEREDDRAW,
+    SCI_SETCARETFORE, SCI_SETCARETLINEBACK, SCI_SETCARETLINEVISIBLE, SCI_SETCARETPERIOD,
+    SCI_SETCARETSTYLE, SCI_SETCARETWIDTH, SCI_SETCHANGEHISTORY, SCI_SETCODEPAGE, SCI_SETDOCPOINTER,
+    SCI_SETELEMENTCOLOUR, SCI_SETEOLMODE, SCI_SETFIRSTVISIBLELINE, SCI_SETHSCROLLBAR,
+    SCI_SETIDLESTYLING, SCI_SETILEXER, SCI_SETINDENT, SCI_SETINDICATORCURRENT, SCI_SETKEYWORDS,
+    SCI_SETMARGINMASKN, SCI_SETMARGINWIDTHN, SCI_SETMULTIPLESELECTION, SCI_SETOVERTYPE,
+    SCI_SETPHASESDRAW, SCI_SETREADONLY, SCI_SETSAVEPOINT, SCI_SETSEARCHFLAGS, SCI_SETSEL,
+    SCI_SETSELBACK, SCI_SETSELECTION, SCI_SETSELEOLFILLED, SCI_SETSELFORE, SCI_SETTABWIDTH,
+    SCI_SETTARGETEND, SCI_SETTARGETSTART, SCI_SETTECHNOLOGY, SCI_SETTEXT, SCI_SETUNDOCOLLECTION,
+    SCI_SETUSETABS, SCI_SETVIRTUALSPACEOPTIONS, SCI_SETVSCROLLBAR, SCI_SETWRAPMODE,
+    SCI_SETYCARETPOLICY, SCI_SETZOOM, SCI_STYLECLEARALL, SCI_STYLEGETBACK, SCI_STYLEGETBOLD,
+    SCI_STYLEGETFONT, SCI_STYLEGETFORE, SCI_STYLEGETITALIC, SCI_STYLEGETSIZE, SCI_STYLESETBACK,
+    SCI_STYLESETBOLD, SCI_STYLESETFONT, SCI_STYLESETFORE, SCI_STYLESETSIZE, SCI_TEXTHEIGHT,
+    SCI_USEPOPUP, SCI_VCHOME, SCI_VCHOMEEXTEND, SCI_VISIBLEFROMDOCLINE, SCK_HOME, SCK_INSERT,
+    SCMOD_SHIFT, SCVS_NONE, SCVS_RECTANGULARSELECTION, SCVS_USERACCESSIBLE,
+    SC_CHANGE_HISTORY_ENABLED, SC_CHANGE_HISTORY_MARKERS, SC_CP_UTF8,
+    SC_ELEMENT_SELECTION_INACTIVE_BACK, SC_EOL_CR, SC_EOL_CRLF, SC_EOL_LF,
+    SC_IDLESTYLING_AFTERVISIBLE, SC_IDLESTYLING_ALL, SC_IDLESTYLING_NONE, SC_IDLESTYLING_TOVISIBLE,
+    SC_LINECHARACTERINDEX_UTF32, SC_MARKNUM_HISTORY_MODIFIED,
+    SC_MARKNUM_HISTORY_REVERTED_TO_MODIFIED, SC_MARKNUM_HISTORY_REVERTED_TO_ORIGIN,
+    SC_MARKNUM_HISTORY_SAVED, SC_MARK_FULLRECT, SC_MARK_UNDERLINE, SC_ORDER_PERFORMSORT,
+    SC_PHASES_MULTIPLE, SC_PHASES_TWO, SC_TECHNOLOGY_DEFAULT, SC_TECHNOLOGY_DIRECTWRITE,
+    SC_WRAP_NONE, SC_WRAP_WORD, WM_CLEAR, WM_COPY, WM_CUT, WM_PASTE, WM_UNDO,
 };
 
-use std::os::windows::ffi::OsStrExt as _;
-
 use windows::{
-    core::{s, PCWSTR},
+    core::PCWSTR,
     Win32::{
-        Foundation::{FreeLibrary, GetLastError, HANDLE, HINSTANCE, HMODULE, HWND, LPARAM, WPARAM},
-        System::LibraryLoader::{GetProcAddress, LoadLibraryExW, LOAD_WITH_ALTERED_SEARCH_PATH},
+        Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
         UI::WindowsAndMessaging::{
             CreateWindowExW, DestroyWindow, SendMessageW, ShowWindow, HMENU, SW_HIDE, SW_SHOW,
             WINDOW_EX_STYLE, WINDOW_STYLE, WS_CHILD, WS_CLIPSIBLINGS,
@@ -56,118 +82,17 @@ use windows::{
 };
 
 use crate::{
-    app::EolMode,
+    app::{Degraded, EolMode, Indentation},
+    config::{CaretSettings, CaretStyle, IdleStyling, PhasesDraw, Rendering},
+    editor::EditorView,
     error::{Result, RivetError},
+    git::LineChange,
 };
 
-// ── DLL identity ──────────────────────────────────────────────────────────────
+// ── Class identity ────────────────────────────────────────────────────────────
 
 const CLASS_NAME: &str = "Scintilla";
 
-/// `Scintilla.dll` bytes embedded at compile time from `vendor/Scintilla.dll`.
-static SCINTILLA_BYTES: &[u8] = include_bytes!("../../../vendor/Scintilla.dll");
-
-/// `Lexilla.dll` bytes embedded at compile time from `vendor/Lexilla.dll`.
-static LEXILLA_BYTES: &[u8] = include_bytes!("../../../vendor/Lexilla.dll");
-
-/// Signature of Lexilla's `CreateLexer` C export.
-type CreateLexerFn = unsafe extern "C" fn(*const u8) -> *mut std::ffi::c_void;
-
-// ── SciDll ────────────────────────────────────────────────────────────────────
-
-/// RAII handles to the loaded `Scintilla.dll` and `Lexilla.dll`.
-///
-/// Loading `Scintilla.dll` registers the `"Scintilla"` window class.
-/// `Lexilla.dll` provides the `CreateLexer` function for syntax highlighting.
-/// Both are freed on `Drop`, after all `ScintillaView` child windows are gone.
-pub(crate) struct SciDll {
-    scintilla: HMODULE,
-    lexilla: HMODULE,
-    create_lexer_fn: CreateLexerFn,
-}
-
-impl SciDll {
-    /// Extract and load `Scintilla.dll` + `Lexilla.dll` from embedded bytes.
-    ///
-    /// Both DLLs are written to `%TEMP%\rivet\` on first run.  A second
-    /// running instance silently skips the write (file already present) and
-    /// loads the existing copy.  Loading registers the `"Scintilla"` class and
-    /// resolves `CreateLexer`, making `ScintillaView::create` usable.
-    pub(crate) fn load() -> Result<Self> {
-        let dir = {
-            let mut d = std::env::temp_dir();
-            d.push("rivet");
-            let _ = std::fs::create_dir_all(&d);
-            d
-        };
-
-        // Extract both DLLs; ignore write errors (another instance may hold them).
-        let _ = std::fs::write(dir.join("Scintilla.dll"), SCINTILLA_BYTES);
-        let _ = std::fs::write(dir.join("Lexilla.dll"), LEXILLA_BYTES);
-
-        let scintilla = load_dll_from_dir(&dir, "Scintilla.dll")?;
-        let lexilla = load_dll_from_dir(&dir, "Lexilla.dll")?;
-
-        // Resolve CreateLexer from Lexilla.
-        // SAFETY: lexilla is a valid HMODULE; "CreateLexer\0" is a valid PCSTR.
-        let proc = unsafe { GetProcAddress(lexilla, s!("CreateLexer")) }.ok_or(
-            RivetError::Win32 {
-                function: "GetProcAddress(CreateLexer)",
-                // SAFETY: called immediately after GetProcAddress failure.
-                code: unsafe { GetLastError().0 },
-            },
-        )?;
-        // SAFETY: CreateLexer is exported from Lexilla with this exact C signature.
-        // On x64 Windows, extern "system" and extern "C" share the same ABI.
-        let create_lexer_fn: CreateLexerFn = unsafe { std::mem::transmute(proc) };
-
-        Ok(Self { scintilla, lexilla, create_lexer_fn })
-    }
-
-    /// Call Lexilla's `CreateLexer` with a null-terminated ASCII name (e.g. `b"cpp\0"`).
-    ///
-    /// Returns a null pointer if the lexer name is unrecognised; callers pass
-    /// the result straight to `ScintillaView::set_ilexer`, which treats null as
-    /// "plain text / no highlighting".
-    pub(crate) fn create_lexer(&self, name: &[u8]) -> *mut std::ffi::c_void {
-        // SAFETY: create_lexer_fn is valid; name is a null-terminated ASCII slice.
-        unsafe { (self.create_lexer_fn)(name.as_ptr()) }
-    }
-}
-
-/// Load a DLL by filename from an absolute directory path using
-/// `LOAD_WITH_ALTERED_SEARCH_PATH` to prevent DLL hijacking.
-fn load_dll_from_dir(dir: &std::path::Path, name: &str) -> Result<HMODULE> {
-    let path_wide: Vec<u16> = dir
-        .join(name)
-        .as_os_str()
-        .encode_wide()
-        .chain(std::iter::once(0))
-        .collect();
-    // SAFETY: path_wide is a valid null-terminated UTF-16 absolute path.
-    unsafe {
-        LoadLibraryExW(
-            PCWSTR(path_wide.as_ptr()),
-            HANDLE::default(),
-            LOAD_WITH_ALTERED_SEARCH_PATH,
-        )
-    }
-    .map_err(RivetError::from)
-}
-
-impl Drop for SciDll {
-    fn drop(&mut self) {
-        // SAFETY: both HMODULEs came from successful LoadLibraryExW calls and
-        // have not been freed.  All ScintillaView HWNDs are already destroyed
-        // (Windows destroys child windows before WM_DESTROY fires on the parent,
-        // and WindowState field order ensures sci_views drops before sci_dll).
-        unsafe {
-            let _ = FreeLibrary(self.lexilla);
-            let _ = FreeLibrary(self.scintilla);
-        }
-    }
-}
-
 // ── ScintillaView ─────────────────────────────────────────────────────────────
 
 /// A hosted Scintilla editor child window.
@@ -179,6 +104,34 @@ pub(crate) struct ScintillaView {
     hwnd: HWND,
 }
 
+/// The git gutter's markers, in the margin that shows only them.
+const GIT_MARKERS: [u32; 3] = [MARKER_GIT_ADDED, MARKER_GIT_MODIFIED, MARKER_GIT_DELETED];
+
+/// Width in pixels of the git gutter while it is shown.
+const GIT_MARGIN_WIDTH: isize = 4;
+
+/// The leading fields of Scintilla's `SCNotification` — enough to read the
+/// character of an `SCN_CHARADDED` and the kind of an `SCN_MODIFIED`.  Cast
+/// a `WM_NOTIFY` LPARAM from a view.
+#[repr(C)]
+pub(crate) struct NotifyHeader {
+    pub(crate) hdr: windows::Win32::UI::Controls::NMHDR,
+    pub(crate) position: isize,
+    /// The typed character (a Unicode code point) for `SCN_CHARADDED`.
+    pub(crate) ch: i32,
+    pub(crate) modifiers: i32,
+    /// `SC_MOD_*` flags for `SCN_MODIFIED`.
+    pub(crate) modification_type: i32,
+}
+
+/// Scintilla's `Sci_TextRangeFull`: a byte range and a buffer for its text.
+#[repr(C)]
+struct TextRangeFull {
+    cp_min: isize,
+    cp_max: isize,
+    text: *mut u8,
+}
+
 impl ScintillaView {
     /// Create a Scintilla child window inside `hwnd_parent`.
     ///
@@ -214,9 +167,102 @@ impl ScintillaView {
         .map_err(RivetError::from)?;
 
         // SAFETY: hwnd is a valid Scintilla window.  SCI_SETCODEPAGE with
-        // SC_CP_UTF8 is documented safe initialisation.
+        // SC_CP_UTF8 is documented safe initialisation.  The UTF-32 line
+        // index makes `caret_char_offset` O(1) per line instead of a scan
+        // from the start of the document.  Multiple selections (typed into
+        // together) back Select All Matches.  Completion lists match the
+        // typed prefix ignoring case and are sorted by Scintilla.  The
+        // auto-close indicator only tags text, so it draws nothing; the
+        // occurrence indicator is a translucent box under the text.  Change
+        // history must be switched on now, while the undo buffer is empty;
+        // it marks edited lines in the symbol margin.  The git markers get a
+        // margin of their own, hidden until a file in a repository shows it.
         unsafe {
             let _ = SendMessageW(hwnd, SCI_SETCODEPAGE, WPARAM(SC_CP_UTF8), LPARAM(0));
+            let _ = SendMessageW(
+                hwnd,
+                SCI_ALLOCATELINECHARACTERINDEX,
+                WPARAM(SC_LINECHARACTERINDEX_UTF32),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(hwnd, SCI_SETMULTIPLESELECTION, WPARAM(1), LPARAM(0));
+            let _ = SendMessageW(hwnd, SCI_SETADDITIONALSELECTIONTYPING, WPARAM(1), LPARAM(0));
+            let _ = SendMessageW(hwnd, SCI_AUTOCSETIGNORECASE, WPARAM(1), LPARAM(0));
+            // File names offered by path completion may contain spaces.
+            let _ = SendMessageW(
+                hwnd,
+                SCI_AUTOCSETSEPARATOR,
+                WPARAM(b'\n' as usize),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_AUTOCSETORDER,
+                WPARAM(SC_ORDER_PERFORMSORT),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_INDICSETSTYLE,
+                WPARAM(INDICATOR_AUTO_CLOSE),
+                LPARAM(INDIC_HIDDEN),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_INDICSETSTYLE,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(INDIC_ROUNDBOX),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_INDICSETUNDER,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(1),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_INDICSETALPHA,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(100),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_INDICSETOUTLINEALPHA,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(160),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_SETCHANGEHISTORY,
+                WPARAM(SC_CHANGE_HISTORY_ENABLED | SC_CHANGE_HISTORY_MARKERS),
+                LPARAM(0),
+            );
+            let git_mask = GIT_MARKERS.iter().fold(0isize, |m, &n| m | 1 << n);
+            let symbols = SendMessageW(hwnd, SCI_GETMARGINMASKN, WPARAM(1), LPARAM(0)).0;
+            let _ = SendMessageW(
+                hwnd,
+                SCI_SETMARGINMASKN,
+                WPARAM(1),
+                LPARAM(symbols & !git_mask),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                SCI_SETMARGINMASKN,
+                WPARAM(MARGIN_GIT),
+                LPARAM(git_mask),
+            );
+            for (marker, symbol) in [
+                (MARKER_GIT_ADDED, SC_MARK_FULLRECT),
+                (MARKER_GIT_MODIFIED, SC_MARK_FULLRECT),
+                (MARKER_GIT_DELETED, SC_MARK_UNDERLINE),
+            ] {
+                let _ = SendMessageW(
+                    hwnd,
+                    SCI_MARKERDEFINE,
+                    WPARAM(marker as usize),
+                    LPARAM(symbol as isize),
+                );
+            }
         }
 
         Ok(Self { hwnd })
@@ -296,18 +342,167 @@ impl ScintillaView {
         }
     }
 
-    /// Enable or disable Large File Mode (plain-text lexer, no word wrap).
-    pub(crate) fn set_large_file_mode(&self, enable: bool) {
-        if enable {
-            // SAFETY: hwnd valid; documented Scintilla messages.
-            unsafe {
+    /// Prepare for a chunked load with [`append_loaded`](Self::append_loaded).
+    ///
+    /// Undo recording stops (the loaded text is the starting state, not an
+    /// edit) and the document turns read-only so typing cannot interleave
+    /// with the appended chunks.  With undo off, appends also leave the save
+    /// point alone, so no `SCN_SAVEPOINTLEFT` fires mid-load.
+    pub(crate) fn begin_chunked_load(&self) {
+        // SAFETY: hwnd valid; both messages take plain integer WPARAMs.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETUNDOCOLLECTION, WPARAM(0), LPARAM(0));
+            let _ = SendMessageW(self.hwnd, SCI_SETREADONLY, WPARAM(1), LPARAM(0));
+        }
+    }
+
+    /// Append one decoded chunk during a chunked load.
+    pub(crate) fn append_loaded(&self, text: &[u8]) {
+        if text.is_empty() {
+            return;
+        }
+        // SAFETY: hwnd valid; SCI_APPENDTEXT reads exactly text.len() bytes
+        // from a buffer that outlives the call.  Read-only is lifted around
+        // the append because Scintilla ignores modifications while it is set.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETREADONLY, WPARAM(0), LPARAM(0));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_APPENDTEXT,
+                WPARAM(text.len()),
+                LPARAM(text.as_ptr() as isize),
+            );
+            let _ = SendMessageW(self.hwnd, SCI_SETREADONLY, WPARAM(1), LPARAM(0));
+        }
+    }
+
+    /// Finish (or abandon) a chunked load: restore undo recording and
+    /// editing, and make the current text the unmodified state.
+    ///
+    /// `undo` is false for a document too large to keep undo history for
+    /// (`Degraded::no_undo`); recording then stays off.
+    pub(crate) fn end_chunked_load(&self, undo: bool) {
+        self.set_undo_collection(undo);
+        self.empty_undo_buffer();
+        // SAFETY: hwnd valid; SCI_SETREADONLY takes a boolean WPARAM.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETREADONLY, WPARAM(0), LPARAM(0));
+        }
+        self.set_save_point();
+    }
+
+    /// Start or stop recording undo actions.  Stopping also discards the
+    /// history, which could no longer be undone correctly.
+    pub(crate) fn set_undo_collection(&self, on: bool) {
+        // SAFETY: hwnd valid; SCI_SETUNDOCOLLECTION takes a boolean WPARAM.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETUNDOCOLLECTION,
+                WPARAM(on as usize),
+                LPARAM(0),
+            );
+        }
+        if !on {
+            self.empty_undo_buffer();
+        }
+    }
+
+    /// Discard the undo and redo history (Edit > Clear Undo History).
+    pub(crate) fn empty_undo_buffer(&self) {
+        // SAFETY: hwnd valid; SCI_EMPTYUNDOBUFFER takes no parameters.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_EMPTYUNDOBUFFER, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    /// Whether there is an action to undo.
+    pub(crate) fn can_undo(&self) -> bool {
+        // SAFETY: hwnd valid; SCI_CANUNDO takes no parameters.
+        unsafe { SendMessageW(self.hwnd, SCI_CANUNDO, WPARAM(0), LPARAM(0)).0 != 0 }
+    }
+
+    /// Whether there is an undone action to redo.
+    pub(crate) fn can_redo(&self) -> bool {
+        // SAFETY: hwnd valid; SCI_CANREDO takes no parameters.
+        unsafe { SendMessageW(self.hwnd, SCI_CANREDO, WPARAM(0), LPARAM(0)).0 != 0 }
+    }
+
+    /// Use Scintilla's Undo / Cut / Copy / Paste right-click menu, or pass
+    /// `WM_CONTEXTMENU` on to the parent window for a menu of its own.
+    pub(crate) fn use_popup(&self, on: bool) {
+        // SAFETY: hwnd valid; SCI_USEPOPUP takes an integer mode.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_USEPOPUP, WPARAM(on as usize), LPARAM(0));
+        }
+    }
+
+    /// Make the document read-only or editable.
+    ///
+    /// Call after `set_text`: Scintilla ignores `SCI_SETTEXT` on a read-only
+    /// document.
+    pub(crate) fn set_read_only(&self, read_only: bool) {
+        // SAFETY: hwnd valid; SCI_SETREADONLY takes a boolean WPARAM.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETREADONLY,
+                WPARAM(read_only as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Turn off what a large document does without: highlighting (plain
+    /// text lexer), word wrap and undo.
+    pub(crate) fn set_degraded(&self, degraded: Degraded) {
+        // Also turns recording back on for a view reused after a large file.
+        self.set_undo_collection(!degraded.no_undo);
+        // SAFETY: hwnd valid; documented Scintilla messages.
+        unsafe {
+            if degraded.plain_text {
                 // Null ILexer5* = plain text, no highlighting.
                 let _ = SendMessageW(self.hwnd, SCI_SETILEXER, WPARAM(0), LPARAM(0));
+            }
+            if degraded.no_wrap {
                 let _ = SendMessageW(self.hwnd, SCI_SETWRAPMODE, WPARAM(SC_WRAP_NONE), LPARAM(0));
             }
         }
     }
 
+    /// Apply drawing technology, buffering, idle styling and drawing
+    /// phases.
+    pub(crate) fn set_rendering(&self, rendering: Rendering) {
+        let technology = if rendering.direct_write {
+            SC_TECHNOLOGY_DIRECTWRITE
+        } else {
+            SC_TECHNOLOGY_DEFAULT
+        };
+        let idle = match rendering.idle_styling {
+            IdleStyling::Auto | IdleStyling::None => SC_IDLESTYLING_NONE,
+            IdleStyling::ToVisible => SC_IDLESTYLING_TOVISIBLE,
+            IdleStyling::AfterVisible => SC_IDLESTYLING_AFTERVISIBLE,
+            IdleStyling::All => SC_IDLESTYLING_ALL,
+        };
+        let phases = match rendering.phases_draw {
+            PhasesDraw::Auto | PhasesDraw::Two => SC_PHASES_TWO,
+            PhasesDraw::Multiple => SC_PHASES_MULTIPLE,
+        };
+        // SAFETY: hwnd valid; all four setters take plain integers.  A
+        // technology the system lacks is refused and GDI stays.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETTECHNOLOGY, WPARAM(technology), LPARAM(0));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETBUFFEREDDRAW,
+                WPARAM(rendering.buffered_draw as usize),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(self.hwnd, SCI_SETIDLESTYLING, WPARAM(idle), LPARAM(0));
+            let _ = SendMessageW(self.hwnd, SCI_SETPHASESDRAW, WPARAM(phases), LPARAM(0));
+        }
+    }
+
     // ── Syntax highlighting ───────────────────────────────────────────────────
 
     /// Set the lexer via Lexilla's `ILexer5*` interface (Scintilla 5.x).
@@ -320,6 +515,14 @@ impl ScintillaView {
         }
     }
 
+    /// Discard existing styling and re-lex the whole document.
+    pub(crate) fn colourise_all(&self) {
+        // SAFETY: hwnd valid; SCI_COLOURISE takes plain integers.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_COLOURISE, WPARAM(0), LPARAM(-1));
+        }
+    }
+
     /// Set a keyword list for the given set index.
     ///
     /// `words` must be a null-terminated ASCII byte slice, e.g. `b"for while\0"`.
@@ -434,6 +637,30 @@ impl ScintillaView {
         }
     }
 
+    /// Client coordinates of the bottom-left corner of the caret, for
+    /// placing a popup just below it.
+    pub(crate) fn caret_point(&self) -> (i32, i32) {
+        let pos = self.caret_pos();
+        let line = self.line_from_position(pos);
+        // SAFETY: hwnd valid; read-only queries.
+        unsafe {
+            let x = SendMessageW(
+                self.hwnd,
+                SCI_POINTXFROMPOSITION,
+                WPARAM(0),
+                LPARAM(pos as isize),
+            );
+            let y = SendMessageW(
+                self.hwnd,
+                SCI_POINTYFROMPOSITION,
+                WPARAM(0),
+                LPARAM(pos as isize),
+            );
+            let h = SendMessageW(self.hwnd, SCI_TEXTHEIGHT, WPARAM(line), LPARAM(0));
+            (x.0 as i32, (y.0 + h.0) as i32)
+        }
+    }
+
     /// First visible line index (0-based, for session persistence).
     pub(crate) fn first_visible_line(&self) -> usize {
         // SAFETY: hwnd valid; read-only query.
@@ -449,17 +676,44 @@ impl ScintillaView {
     }
 
     /// 1-based (line, column) for status-bar display.
+    ///
+    /// The column counts characters, not bytes, so multibyte UTF-8 text
+    /// reports the column the user sees; a tab counts as one character.
     pub(crate) fn caret_line_col(&self) -> (usize, usize) {
-        // SAFETY: hwnd valid; all three are read-only queries.
+        // SAFETY: hwnd valid; all four are read-only queries.
         unsafe {
             let pos = SendMessageW(self.hwnd, SCI_GETCURRENTPOS, WPARAM(0), LPARAM(0)).0 as usize;
             let line =
                 SendMessageW(self.hwnd, SCI_LINEFROMPOSITION, WPARAM(pos), LPARAM(0)).0 as usize;
-            let col = SendMessageW(self.hwnd, SCI_GETCOLUMN, WPARAM(pos), LPARAM(0)).0 as usize;
+            let start =
+                SendMessageW(self.hwnd, SCI_POSITIONFROMLINE, WPARAM(line), LPARAM(0)).0 as usize;
+            let col = SendMessageW(
+                self.hwnd,
+                SCI_COUNTCHARACTERS,
+                WPARAM(start),
+                LPARAM(pos as isize),
+            )
+            .0 as usize;
             (line + 1, col + 1)
         }
     }
 
+    /// 1-based character (code point) offset of the caret in the document.
+    pub(crate) fn caret_char_offset(&self) -> usize {
+        let (line, col) = self.caret_line_col();
+        // SAFETY: hwnd valid; the UTF-32 line index was allocated in `create`.
+        let line_start = unsafe {
+            SendMessageW(
+                self.hwnd,
+                SCI_INDEXPOSITIONFROMLINE,
+                WPARAM(line - 1),
+                LPARAM(SC_LINECHARACTERINDEX_UTF32 as isize),
+            )
+            .0 as usize
+        };
+        line_start + col
+    }
+
     /// Current EOL mode.
     pub(crate) fn eol_mode(&self) -> EolMode {
         // SAFETY: hwnd valid; read-only query.
@@ -559,6 +813,188 @@ impl ScintillaView {
         }
     }
 
+    /// Apply caret shape, width, blink period and movement options.
+    pub(crate) fn set_caret(&self, caret: CaretSettings) {
+        let caret = caret.clamped();
+        let style = match caret.style {
+            CaretStyle::Line => CARETSTYLE_LINE,
+            CaretStyle::Block => CARETSTYLE_BLOCK,
+        };
+        // SAFETY: hwnd valid; all three setters take plain integers.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETCARETSTYLE, WPARAM(style), LPARAM(0));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETCARETWIDTH,
+                WPARAM(caret.width as usize),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETCARETPERIOD,
+                WPARAM(caret.blink_ms as usize),
+                LPARAM(0),
+            );
+        }
+        self.set_smart_home(caret.smart_home);
+        self.set_scroll_margin(caret.scroll_margin);
+        self.set_virtual_space(caret.virtual_space);
+        self.set_insert_key(caret.insert_key);
+    }
+
+    /// Let Insert switch between insert and overtype, or ignore it.  Turning
+    /// it off also leaves overtype, which could otherwise not be undone.
+    pub(crate) fn set_insert_key(&self, on: bool) {
+        // SAFETY: hwnd valid; SCI_ASSIGNCMDKEY / SCI_CLEARCMDKEY take a key
+        // definition, SCI_SETOVERTYPE a boolean.
+        unsafe {
+            if on {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_ASSIGNCMDKEY,
+                    WPARAM(SCK_INSERT),
+                    LPARAM(SCI_EDITTOGGLEOVERTYPE as isize),
+                );
+            } else {
+                let _ = SendMessageW(self.hwnd, SCI_CLEARCMDKEY, WPARAM(SCK_INSERT), LPARAM(0));
+                let _ = SendMessageW(self.hwnd, SCI_SETOVERTYPE, WPARAM(0), LPARAM(0));
+            }
+        }
+    }
+
+    /// Whether typing overwrites the next character (Insert toggled).
+    pub(crate) fn overtype(&self) -> bool {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe { SendMessageW(self.hwnd, SCI_GETOVERTYPE, WPARAM(0), LPARAM(0)).0 != 0 }
+    }
+
+    /// Bind Home and Shift+Home to the first non-blank character (falling
+    /// back to column 0 when already there), or plainly to column 0.
+    pub(crate) fn set_smart_home(&self, on: bool) {
+        let (home, extend) = if on {
+            (SCI_VCHOME, SCI_VCHOMEEXTEND)
+        } else {
+            (SCI_HOME, SCI_HOMEEXTEND)
+        };
+        // SAFETY: hwnd valid; SCI_ASSIGNCMDKEY takes a key definition and a
+        // command message number.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_ASSIGNCMDKEY,
+                WPARAM(SCK_HOME),
+                LPARAM(home as isize),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_ASSIGNCMDKEY,
+                WPARAM(SCK_HOME | (SCMOD_SHIFT << 16)),
+                LPARAM(extend as isize),
+            );
+        }
+    }
+
+    /// Keep `lines` lines between the caret and the top or bottom edge when
+    /// the view scrolls; 0 restores Scintilla's default policy.
+    pub(crate) fn set_scroll_margin(&self, lines: u32) {
+        let flags = if lines == 0 {
+            CARET_EVEN
+        } else {
+            CARET_SLOP | CARET_STRICT | CARET_EVEN
+        };
+        // SAFETY: hwnd valid; SCI_SETYCARETPOLICY takes flags and a line count.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETYCARETPOLICY,
+                WPARAM(flags),
+                LPARAM(lines as isize),
+            );
+        }
+    }
+
+    /// Allow the caret past the end of a line (and in rectangular
+    /// selections), or keep it within the text.
+    pub(crate) fn set_virtual_space(&self, on: bool) {
+        let options = if on {
+            SCVS_RECTANGULARSELECTION | SCVS_USERACCESSIBLE
+        } else {
+            SCVS_NONE
+        };
+        // SAFETY: hwnd valid; SCI_SETVIRTUALSPACEOPTIONS takes SCVS_* flags.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETVIRTUALSPACEOPTIONS,
+                WPARAM(options),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Set the selection and caret-line backgrounds (`COLORREF`s).  `None`
+    /// restores Scintilla's default selection colour and hides the caret line.
+    pub(crate) fn set_highlights(&self, selection: Option<u32>, caret_line: Option<u32>) {
+        // SAFETY: hwnd valid; all three setters take plain integers.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETSELBACK,
+                WPARAM(selection.is_some() as usize),
+                LPARAM(selection.unwrap_or(0) as isize),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETCARETLINEVISIBLE,
+                WPARAM(caret_line.is_some() as usize),
+                LPARAM(0),
+            );
+            if let Some(colour) = caret_line {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_SETCARETLINEBACK,
+                    WPARAM(colour as usize),
+                    LPARAM(0),
+                );
+            }
+        }
+    }
+
+    /// Set the selected text colour (`None`: keep each style's own) and the
+    /// caret colour (`None`: Scintilla's default black).
+    pub(crate) fn set_contrast_colours(&self, selection_text: Option<u32>, caret: Option<u32>) {
+        // SAFETY: hwnd valid; both setters take plain integers.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETSELFORE,
+                WPARAM(selection_text.is_some() as usize),
+                LPARAM(selection_text.unwrap_or(0) as isize),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETCARETFORE,
+                WPARAM(caret.unwrap_or(0) as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Configure what Tab inserts and the tab-stop / indent width.
+    pub(crate) fn set_indentation(&self, indent: Indentation) {
+        // SAFETY: hwnd valid; documented messages taking plain integers.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETUSETABS,
+                WPARAM(indent.use_tabs as usize),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(self.hwnd, SCI_SETTABWIDTH, WPARAM(indent.width), LPARAM(0));
+            let _ = SendMessageW(self.hwnd, SCI_SETINDENT, WPARAM(0), LPARAM(0));
+        }
+    }
+
     /// Enable or disable word wrapping for this view.
     pub(crate) fn set_word_wrap(&self, enabled: bool) {
         let mode = if enabled { SC_WRAP_WORD } else { SC_WRAP_NONE };
@@ -674,19 +1110,78 @@ impl ScintillaView {
         }
     }
 
-    /// Scroll to make the caret visible.
-    pub(crate) fn scroll_caret(&self) {
-        // SAFETY: hwnd valid; SCI_SCROLLCARET takes no parameters.
-        unsafe {
-            let _ = SendMessageW(self.hwnd, SCI_SCROLLCARET, WPARAM(0), LPARAM(0));
+    /// Select every match of `text` (`SCFIND_*` `flags`) as a multiple
+    /// selection, the last match being the main one, and scroll it into view.
+    ///
+    /// With `keep` the matches are added to the current selections; otherwise
+    /// they replace them.  Returns the number of matches; with none the
+    /// selection is left alone.
+    pub(crate) fn select_matches(&self, text: &[u8], flags: u32, keep: bool) -> usize {
+        let doc_len = self.doc_len();
+        let mut count = 0;
+        let mut pos = 0;
+        while pos < doc_len {
+            self.set_target(pos, doc_len);
+            let Some(start) = self.search_in_target(text, flags) else {
+                break;
+            };
+            let end = self.get_target_end();
+            let msg = if count == 0 && !keep {
+                SCI_SETSELECTION
+            } else {
+                SCI_ADDSELECTION
+            };
+            // SAFETY: hwnd valid; start and end come from SCI_SEARCHINTARGET
+            // so both are valid positions.
+            unsafe {
+                let _ = SendMessageW(self.hwnd, msg, WPARAM(end), LPARAM(start as isize));
+            }
+            count += 1;
+            pos = end.max(start + 1);
+        }
+        if count > 0 {
+            self.scroll_caret();
         }
+        count
     }
 
-    // ── Undo grouping ─────────────────────────────────────────────────────────
-
-    /// Begin a compound undo action (multiple edits become one Ctrl+Z step).
-    pub(crate) fn begin_undo_action(&self) {
-        // SAFETY: hwnd valid; SCI_BEGINUNDOACTION takes no parameters.
+    /// The selected text as UTF-8 bytes (empty when nothing is selected).
+    ///
+    /// For a multiple / rectangular selection Scintilla joins the ranges.
+    pub(crate) fn selected_text(&self) -> Vec<u8> {
+        // SAFETY: hwnd valid; a null LPARAM makes SCI_GETSELTEXT a length query.
+        let len =
+            unsafe { SendMessageW(self.hwnd, SCI_GETSELTEXT, WPARAM(0), LPARAM(0)).0 as usize };
+        // Older Scintilla builds count the terminator; allocate one spare byte
+        // either way and trim at the first NUL below.
+        let mut buf = vec![0u8; len + 1];
+        // SAFETY: buf holds at least len + 1 bytes, the documented requirement.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_GETSELTEXT,
+                WPARAM(0),
+                LPARAM(buf.as_mut_ptr() as isize),
+            );
+        }
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        buf.truncate(end);
+        buf
+    }
+
+    /// Scroll to make the caret visible.
+    pub(crate) fn scroll_caret(&self) {
+        // SAFETY: hwnd valid; SCI_SCROLLCARET takes no parameters.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SCROLLCARET, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    // ── Undo grouping ─────────────────────────────────────────────────────────
+
+    /// Begin a compound undo action (multiple edits become one Ctrl+Z step).
+    pub(crate) fn begin_undo_action(&self) {
+        // SAFETY: hwnd valid; SCI_BEGINUNDOACTION takes no parameters.
         unsafe {
             let _ = SendMessageW(self.hwnd, SCI_BEGINUNDOACTION, WPARAM(0), LPARAM(0));
         }
@@ -715,6 +1210,531 @@ impl ScintillaView {
         unsafe { SendMessageW(self.hwnd, SCI_POSITIONFROMLINE, WPARAM(line), LPARAM(0)).0 as usize }
     }
 
+    /// Line (0-based) containing byte position `pos`.
+    pub(crate) fn line_from_position(&self, pos: usize) -> usize {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe { SendMessageW(self.hwnd, SCI_LINEFROMPOSITION, WPARAM(pos), LPARAM(0)).0 as usize }
+    }
+
+    /// Text of `line` (0-based) including its EOL, as UTF-8 bytes.
+    pub(crate) fn line_text(&self, line: usize) -> Vec<u8> {
+        // SAFETY: hwnd valid; read-only query.
+        let len =
+            unsafe { SendMessageW(self.hwnd, SCI_LINELENGTH, WPARAM(line), LPARAM(0)).0 as usize };
+        let mut buf = vec![0u8; len];
+        if len > 0 {
+            // SAFETY: buf holds exactly SCI_LINELENGTH bytes; SCI_GETLINE
+            // writes no terminator.
+            unsafe {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_GETLINE,
+                    WPARAM(line),
+                    LPARAM(buf.as_mut_ptr() as isize),
+                );
+            }
+        }
+        buf
+    }
+
+    /// Bytes `start..end` of the document (clamped to its length).
+    pub(crate) fn text_range(&self, start: usize, end: usize) -> Vec<u8> {
+        let end = end.min(self.doc_len());
+        if start >= end {
+            return Vec::new();
+        }
+        let mut buf = vec![0u8; end - start + 1];
+        let mut range = TextRangeFull {
+            cp_min: start as isize,
+            cp_max: end as isize,
+            text: buf.as_mut_ptr(),
+        };
+        // SAFETY: buf holds the range plus the null terminator Scintilla
+        // writes; range outlives the synchronous call.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_GETTEXTRANGEFULL,
+                WPARAM(0),
+                LPARAM(&mut range as *mut TextRangeFull as isize),
+            );
+        }
+        buf.truncate(end - start);
+        buf
+    }
+
+    /// The style number of each byte in `start..end`, styling the range
+    /// first if the lexer has not reached it yet.
+    pub(crate) fn styles_in(&self, start: usize, end: usize) -> Vec<u8> {
+        let end = end.min(self.doc_len());
+        if start >= end {
+            return Vec::new();
+        }
+        let mut buf = vec![0u8; 2 * (end - start) + 2];
+        let mut range = TextRangeFull {
+            cp_min: start as isize,
+            cp_max: end as isize,
+            text: buf.as_mut_ptr(),
+        };
+        // SAFETY: buf holds two bytes per position plus the two null bytes
+        // Scintilla writes; range outlives the synchronous calls.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_COLOURISE, WPARAM(0), LPARAM(end as isize));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_GETSTYLEDTEXTFULL,
+                WPARAM(0),
+                LPARAM(&mut range as *mut TextRangeFull as isize),
+            );
+        }
+        buf.chunks_exact(2)
+            .take(end - start)
+            .map(|p| p[1])
+            .collect()
+    }
+
+    /// What text in `style` looks like.
+    pub(crate) fn text_style(&self, style: u8) -> crate::export::TextStyle {
+        let get = |msg| {
+            // SAFETY: hwnd valid; read-only query.
+            unsafe { SendMessageW(self.hwnd, msg, WPARAM(style as usize), LPARAM(0)).0 }
+        };
+        crate::export::TextStyle {
+            fore: get(SCI_STYLEGETFORE) as u32,
+            back: get(SCI_STYLEGETBACK) as u32,
+            bold: get(SCI_STYLEGETBOLD) != 0,
+            italic: get(SCI_STYLEGETITALIC) != 0,
+        }
+    }
+
+    /// The font name and point size of `style`.
+    pub(crate) fn style_font(&self, style: u8) -> (String, u32) {
+        // SAFETY: hwnd valid; a null buffer queries the name's length.
+        let len = unsafe {
+            SendMessageW(
+                self.hwnd,
+                SCI_STYLEGETFONT,
+                WPARAM(style as usize),
+                LPARAM(0),
+            )
+            .0
+        };
+        let mut buf = vec![0u8; len.max(0) as usize + 1];
+        // SAFETY: buf has room for the name and its null terminator.
+        let size = unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_STYLEGETFONT,
+                WPARAM(style as usize),
+                LPARAM(buf.as_mut_ptr() as isize),
+            );
+            SendMessageW(
+                self.hwnd,
+                SCI_STYLEGETSIZE,
+                WPARAM(style as usize),
+                LPARAM(0),
+            )
+            .0
+        };
+        buf.truncate(len.max(0) as usize);
+        (
+            String::from_utf8_lossy(&buf).into_owned(),
+            size.max(1) as u32,
+        )
+    }
+
+    // ── Column editor ─────────────────────────────────────────────────────────
+
+    /// Where each line of a rectangular selection starts, top to bottom, as
+    /// `(position, virtual space)`.  `None` unless the selection is
+    /// rectangular.
+    pub(crate) fn column_points(&self) -> Option<Vec<(usize, usize)>> {
+        let get = |msg, n| {
+            // SAFETY: hwnd valid; read-only query.
+            unsafe { SendMessageW(self.hwnd, msg, WPARAM(n), LPARAM(0)).0.max(0) as usize }
+        };
+        if get(SCI_SELECTIONISRECTANGLE, 0) == 0 {
+            return None;
+        }
+        let mut points: Vec<(usize, usize)> = (0..get(SCI_GETSELECTIONS, 0))
+            .map(|n| {
+                let anchor = (
+                    get(SCI_GETSELECTIONNANCHOR, n),
+                    get(SCI_GETSELECTIONNANCHORVIRTUALSPACE, n),
+                );
+                let caret = (
+                    get(SCI_GETSELECTIONNCARET, n),
+                    get(SCI_GETSELECTIONNCARETVIRTUALSPACE, n),
+                );
+                anchor.min(caret)
+            })
+            .collect();
+        points.sort_unstable();
+        Some(points)
+    }
+
+    /// Insert each `(position, text)` in order as one undo step, then put
+    /// the caret at the start of the last one inserted.  Positions must run
+    /// from the end of the document backwards (see `column::edits`).
+    pub(crate) fn insert_column(&self, edits: &[(usize, Vec<u8>)]) {
+        self.begin_undo_action();
+        for (pos, text) in edits {
+            let mut text = text.clone();
+            text.push(0);
+            // SAFETY: hwnd valid; text is null-terminated and outlives the call.
+            unsafe {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_INSERTTEXT,
+                    WPARAM(*pos),
+                    LPARAM(text.as_ptr() as isize),
+                );
+            }
+        }
+        self.end_undo_action();
+        if let Some((pos, _)) = edits.last() {
+            self.set_sel(*pos, *pos);
+        }
+    }
+
+    // ── Word completion ───────────────────────────────────────────────────────
+
+    /// Show `words` as a completion list for the `prefix_len` bytes before
+    /// the caret.  Does nothing if `words` is empty.
+    pub(crate) fn show_completions(&self, prefix_len: usize, words: &[&[u8]]) {
+        if words.is_empty() {
+            return;
+        }
+        let mut list = words.join(&b'\n');
+        list.push(0);
+        // SAFETY: hwnd valid; list is null-terminated and outlives the call
+        // (Scintilla copies it).
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_AUTOCSHOW,
+                WPARAM(prefix_len),
+                LPARAM(list.as_ptr() as isize),
+            );
+        }
+    }
+
+    /// Whether a completion list is currently shown.
+    pub(crate) fn is_completion_active(&self) -> bool {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe { SendMessageW(self.hwnd, SCI_AUTOCACTIVE, WPARAM(0), LPARAM(0)).0 != 0 }
+    }
+
+    // ── Auto-close brackets ───────────────────────────────────────────────────
+
+    /// The byte at `pos`, or `None` at or past the end of the document.
+    pub(crate) fn byte_at(&self, pos: usize) -> Option<u8> {
+        if pos >= self.doc_len() {
+            return None;
+        }
+        // SAFETY: hwnd valid; read-only query.
+        let b = unsafe { SendMessageW(self.hwnd, SCI_GETCHARAT, WPARAM(pos), LPARAM(0)).0 };
+        Some(b as u8)
+    }
+
+    /// Insert `closer` at the caret, leaving the caret before it, and tag it
+    /// as auto-inserted (see `is_auto_closer`).
+    pub(crate) fn insert_auto_closer(&self, closer: u8) {
+        let pos = self.caret_pos();
+        let text = [closer, 0];
+        // SAFETY: hwnd valid; text is null-terminated and outlives the call.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_INSERTTEXT,
+                WPARAM(pos),
+                LPARAM(text.as_ptr() as isize),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETINDICATORCURRENT,
+                WPARAM(INDICATOR_AUTO_CLOSE),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(self.hwnd, SCI_INDICATORFILLRANGE, WPARAM(pos), LPARAM(1));
+        }
+    }
+
+    /// Whether the byte at `pos` was inserted by `insert_auto_closer`.
+    pub(crate) fn is_auto_closer(&self, pos: usize) -> bool {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe {
+            SendMessageW(
+                self.hwnd,
+                SCI_INDICATORVALUEAT,
+                WPARAM(INDICATOR_AUTO_CLOSE),
+                LPARAM(pos as isize),
+            )
+            .0 != 0
+        }
+    }
+
+    /// Colour (COLORREF) of the occurrence highlight.
+    pub(crate) fn set_occurrence_colour(&self, colour: u32) {
+        // SAFETY: hwnd valid; plain integer arguments.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_INDICSETFORE,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(colour as isize),
+            );
+        }
+    }
+
+    /// Replace the occurrence highlight with one box per `(start, len)`.
+    /// An empty slice just clears it.
+    pub(crate) fn set_occurrences(&self, ranges: &[(usize, usize)]) {
+        let len = self.doc_len();
+        // SAFETY: hwnd valid; Scintilla clamps every range to the document.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETINDICATORCURRENT,
+                WPARAM(INDICATOR_OCCURRENCE),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_INDICATORCLEARRANGE,
+                WPARAM(0),
+                LPARAM(len as isize),
+            );
+            for &(start, n) in ranges {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_INDICATORFILLRANGE,
+                    WPARAM(start),
+                    LPARAM(n as isize),
+                );
+            }
+        }
+    }
+
+    /// Stop tracking changed lines and clear their margin markers.  Once
+    /// off, tracking cannot be turned back on for this view.
+    pub(crate) fn disable_change_history(&self) {
+        // SAFETY: hwnd valid; SCI_SETCHANGEHISTORY takes integer flags.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETCHANGEHISTORY, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    /// Colours (COLORREF) of the margin strip on lines changed since the
+    /// last save and on lines changed before it.
+    pub(crate) fn set_change_colours(&self, unsaved: u32, saved: u32) {
+        let markers = [
+            (SC_MARKNUM_HISTORY_MODIFIED, unsaved),
+            (SC_MARKNUM_HISTORY_REVERTED_TO_MODIFIED, unsaved),
+            (SC_MARKNUM_HISTORY_SAVED, saved),
+            (SC_MARKNUM_HISTORY_REVERTED_TO_ORIGIN, saved),
+        ];
+        // SAFETY: hwnd valid; plain integer arguments.
+        unsafe {
+            for (marker, colour) in markers {
+                for msg in [SCI_MARKERSETFORE, SCI_MARKERSETBACK] {
+                    let _ = SendMessageW(
+                        self.hwnd,
+                        msg,
+                        WPARAM(marker as usize),
+                        LPARAM(colour as isize),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Colours (COLORREF) of the git gutter's added, modified and deleted
+    /// marks.
+    pub(crate) fn set_git_colours(&self, added: u32, modified: u32, deleted: u32) {
+        let markers = [
+            (MARKER_GIT_ADDED, added),
+            (MARKER_GIT_MODIFIED, modified),
+            (MARKER_GIT_DELETED, deleted),
+        ];
+        // SAFETY: hwnd valid; plain integer arguments.
+        unsafe {
+            for (marker, colour) in markers {
+                for msg in [SCI_MARKERSETFORE, SCI_MARKERSETBACK] {
+                    let _ = SendMessageW(
+                        self.hwnd,
+                        msg,
+                        WPARAM(marker as usize),
+                        LPARAM(colour as isize),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Mark `changes` (0-based line, change against git HEAD) in the git
+    /// gutter, replacing the previous marks.  `None` hides the gutter.
+    pub(crate) fn set_git_marks(&self, changes: Option<&[(usize, LineChange)]>) {
+        let width = if changes.is_some() {
+            GIT_MARGIN_WIDTH
+        } else {
+            0
+        };
+        // SAFETY: hwnd valid; plain integer arguments.  Scintilla ignores
+        // lines past the end of the document.
+        unsafe {
+            for marker in GIT_MARKERS {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_MARKERDELETEALL,
+                    WPARAM(marker as usize),
+                    LPARAM(0),
+                );
+            }
+            for &(line, change) in changes.unwrap_or_default() {
+                let marker = match change {
+                    LineChange::Added => MARKER_GIT_ADDED,
+                    LineChange::Modified => MARKER_GIT_MODIFIED,
+                    LineChange::Deleted => MARKER_GIT_DELETED,
+                };
+                let _ = SendMessageW(
+                    self.hwnd,
+                    SCI_MARKERADD,
+                    WPARAM(line),
+                    LPARAM(marker as isize),
+                );
+            }
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETMARGINWIDTHN,
+                WPARAM(MARGIN_GIT),
+                LPARAM(width),
+            );
+        }
+    }
+
+    /// The nearest changed line after (`forward`) or before `line`, 0-based.
+    pub(crate) fn next_change(&self, line: usize, forward: bool) -> Option<usize> {
+        let mask = [
+            SC_MARKNUM_HISTORY_REVERTED_TO_ORIGIN,
+            SC_MARKNUM_HISTORY_SAVED,
+            SC_MARKNUM_HISTORY_MODIFIED,
+            SC_MARKNUM_HISTORY_REVERTED_TO_MODIFIED,
+        ]
+        .iter()
+        .fold(0isize, |m, &n| m | 1 << n);
+        let (msg, from) = if forward {
+            (SCI_MARKERNEXT, line + 1)
+        } else {
+            (SCI_MARKERPREVIOUS, line.checked_sub(1)?)
+        };
+        // SAFETY: hwnd valid; read-only query.
+        let found = unsafe { SendMessageW(self.hwnd, msg, WPARAM(from), LPARAM(mask)).0 };
+        usize::try_from(found).ok()
+    }
+
+    /// This view's document, for [`share_document`](Self::share_document).
+    pub(crate) fn document(&self) -> isize {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe { SendMessageW(self.hwnd, SCI_GETDOCPOINTER, WPARAM(0), LPARAM(0)).0 }
+    }
+
+    /// Show `doc` (from another view's [`document`](Self::document)) in
+    /// this view.  Edits in either view appear in both.
+    pub(crate) fn share_document(&self, doc: isize) {
+        // SAFETY: hwnd valid; `doc` came from SCI_GETDOCPOINTER on a live
+        // view, and Scintilla holds a reference while this view shows it.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETDOCPOINTER, WPARAM(0), LPARAM(doc));
+        }
+    }
+
+    /// Turn this view into a document map: text shrunk as far as it goes,
+    /// no margins, scroll bars or caret, and the selection (which marks the
+    /// editor's visible lines) filled to the right edge in `highlight`.
+    pub(crate) fn make_document_map(&self, highlight: u32) {
+        // SAFETY: hwnd valid; all documented messages taking integers.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, SCI_SETZOOM, WPARAM(-10isize as usize), LPARAM(0));
+            for margin in 0..5 {
+                let _ = SendMessageW(self.hwnd, SCI_SETMARGINWIDTHN, WPARAM(margin), LPARAM(0));
+            }
+            let _ = SendMessageW(self.hwnd, SCI_SETHSCROLLBAR, WPARAM(0), LPARAM(0));
+            let _ = SendMessageW(self.hwnd, SCI_SETVSCROLLBAR, WPARAM(0), LPARAM(0));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETCARETSTYLE,
+                WPARAM(CARETSTYLE_INVISIBLE),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(self.hwnd, SCI_SETCARETLINEVISIBLE, WPARAM(0), LPARAM(0));
+            let _ = SendMessageW(self.hwnd, SCI_SETSELEOLFILLED, WPARAM(1), LPARAM(0));
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_SETELEMENTCOLOUR,
+                WPARAM(SC_ELEMENT_SELECTION_INACTIVE_BACK),
+                LPARAM((highlight | 0xFF00_0000) as isize),
+            );
+        }
+    }
+
+    /// Whole lines that fit in the view.
+    pub(crate) fn lines_on_screen(&self) -> usize {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe { SendMessageW(self.hwnd, SCI_LINESONSCREEN, WPARAM(0), LPARAM(0)).0 as usize }
+    }
+
+    /// The document line at client y-coordinate `y`.
+    pub(crate) fn line_at_y(&self, y: i32) -> usize {
+        // SAFETY: hwnd valid; read-only query.
+        let pos = unsafe {
+            SendMessageW(
+                self.hwnd,
+                SCI_POSITIONFROMPOINT,
+                WPARAM(0),
+                LPARAM(y.max(0) as isize),
+            )
+            .0
+        };
+        self.line_from_position(pos.max(0) as usize)
+    }
+
+    /// The document line shown on display line `visible`; the two differ
+    /// when word wrap splits lines.
+    pub(crate) fn doc_line_from_visible(&self, visible: usize) -> usize {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe {
+            SendMessageW(
+                self.hwnd,
+                SCI_DOCLINEFROMVISIBLE,
+                WPARAM(visible),
+                LPARAM(0),
+            )
+            .0 as usize
+        }
+    }
+
+    /// The first display line of document line `line`.
+    pub(crate) fn visible_from_doc_line(&self, line: usize) -> usize {
+        // SAFETY: hwnd valid; read-only query.
+        unsafe {
+            SendMessageW(self.hwnd, SCI_VISIBLEFROMDOCLINE, WPARAM(line), LPARAM(0)).0 as usize
+        }
+    }
+
+    /// Delete `len` bytes starting at `pos`.
+    pub(crate) fn delete_range(&self, pos: usize, len: usize) {
+        // SAFETY: hwnd valid; Scintilla clamps the range to the document.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                SCI_DELETERANGE,
+                WPARAM(pos),
+                LPARAM(len as isize),
+            );
+        }
+    }
+
     // ── High-level search ─────────────────────────────────────────────────────
 
     /// Find `text` (UTF-8) from the current selection, wrapping around.
@@ -770,27 +1790,189 @@ impl ScintillaView {
         }
         false
     }
+}
 
-    /// Replace every occurrence of `find` with `replacement` in one undo action.
-    ///
-    /// Returns the number of replacements made.
-    pub(crate) fn replace_all(&self, find: &[u8], replacement: &[u8], flags: u32) -> usize {
-        let mut count = 0usize;
-        let mut pos = 0usize;
-        self.begin_undo_action();
-        loop {
-            let doc_len = self.doc_len(); // recalculate: doc size changes after each replacement
-            self.set_target(pos, doc_len);
-            match self.search_in_target(find, flags) {
-                None => break,
-                Some(match_start) => {
-                    let repl_len = self.replace_target(replacement);
-                    pos = match_start + repl_len;
-                    count += 1;
-                }
-            }
-        }
-        self.end_undo_action();
-        count
+// ── EditorView ────────────────────────────────────────────────────────────────
+
+/// Every method forwards to the inherent method of the same name.
+impl EditorView for ScintillaView {
+    fn set_text(&self, text: &[u8]) {
+        ScintillaView::set_text(self, text);
+    }
+
+    fn get_text(&self) -> Vec<u8> {
+        ScintillaView::get_text(self)
+    }
+
+    fn text_range(&self, start: usize, end: usize) -> Vec<u8> {
+        ScintillaView::text_range(self, start, end)
+    }
+
+    fn doc_len(&self) -> usize {
+        ScintillaView::doc_len(self)
+    }
+
+    fn set_save_point(&self) {
+        ScintillaView::set_save_point(self);
+    }
+
+    fn set_read_only(&self, read_only: bool) {
+        ScintillaView::set_read_only(self, read_only);
+    }
+
+    fn set_target(&self, start: usize, end: usize) {
+        ScintillaView::set_target(self, start, end);
+    }
+
+    fn replace_target(&self, text: &[u8]) -> usize {
+        ScintillaView::replace_target(self, text)
+    }
+
+    fn begin_undo_action(&self) {
+        ScintillaView::begin_undo_action(self);
+    }
+
+    fn end_undo_action(&self) {
+        ScintillaView::end_undo_action(self);
+    }
+
+    fn eol_mode(&self) -> EolMode {
+        ScintillaView::eol_mode(self)
+    }
+
+    fn set_eol_mode(&self, eol: EolMode) {
+        ScintillaView::set_eol_mode(self, eol);
+    }
+
+    fn convert_eols(&self, eol: EolMode) {
+        ScintillaView::convert_eols(self, eol);
+    }
+
+    fn undo(&self) {
+        ScintillaView::undo(self);
+    }
+
+    fn redo(&self) {
+        ScintillaView::redo(self);
+    }
+
+    fn can_undo(&self) -> bool {
+        ScintillaView::can_undo(self)
+    }
+
+    fn can_redo(&self) -> bool {
+        ScintillaView::can_redo(self)
+    }
+
+    fn empty_undo_buffer(&self) {
+        ScintillaView::empty_undo_buffer(self);
+    }
+
+    fn cut(&self) {
+        ScintillaView::cut(self);
+    }
+
+    fn copy_to_clipboard(&self) {
+        ScintillaView::copy_to_clipboard(self);
+    }
+
+    fn paste(&self) {
+        ScintillaView::paste(self);
+    }
+
+    fn delete_selection(&self) {
+        ScintillaView::delete_selection(self);
+    }
+
+    fn select_all(&self) {
+        ScintillaView::select_all(self);
+    }
+
+    fn caret_pos(&self) -> usize {
+        ScintillaView::caret_pos(self)
+    }
+
+    fn set_caret_pos(&self, pos: usize) {
+        ScintillaView::set_caret_pos(self, pos);
+    }
+
+    fn selection_start(&self) -> usize {
+        ScintillaView::selection_start(self)
+    }
+
+    fn selection_end(&self) -> usize {
+        ScintillaView::selection_end(self)
+    }
+
+    fn set_sel(&self, anchor: usize, caret: usize) {
+        ScintillaView::set_sel(self, anchor, caret);
+    }
+
+    fn selected_text(&self) -> Vec<u8> {
+        ScintillaView::selected_text(self)
+    }
+
+    fn scroll_caret(&self) {
+        ScintillaView::scroll_caret(self);
+    }
+
+    fn first_visible_line(&self) -> usize {
+        ScintillaView::first_visible_line(self)
+    }
+
+    fn set_first_visible_line(&self, line: usize) {
+        ScintillaView::set_first_visible_line(self, line);
+    }
+
+    fn line_count(&self) -> usize {
+        ScintillaView::line_count(self)
+    }
+
+    fn position_from_line(&self, line: usize) -> usize {
+        ScintillaView::position_from_line(self, line)
+    }
+
+    fn line_from_position(&self, pos: usize) -> usize {
+        ScintillaView::line_from_position(self, pos)
+    }
+
+    fn find_next(&self, text: &[u8], flags: u32, forward: bool) -> bool {
+        ScintillaView::find_next(self, text, flags, forward)
+    }
+
+    fn style_clear_all(&self) {
+        ScintillaView::style_clear_all(self);
+    }
+
+    fn style_set_fore(&self, style: u32, colour: u32) {
+        ScintillaView::style_set_fore(self, style, colour);
+    }
+
+    fn style_set_back(&self, style: u32, colour: u32) {
+        ScintillaView::style_set_back(self, style, colour);
+    }
+
+    fn style_set_bold(&self, style: u32, bold: bool) {
+        ScintillaView::style_set_bold(self, style, bold);
+    }
+
+    fn style_set_font(&self, style: u32, font_name: &[u8]) {
+        ScintillaView::style_set_font(self, style, font_name);
+    }
+
+    fn style_set_size(&self, style: u32, size: i32) {
+        ScintillaView::style_set_size(self, style, size);
+    }
+
+    fn set_indentation(&self, indent: Indentation) {
+        ScintillaView::set_indentation(self, indent);
+    }
+
+    fn set_word_wrap(&self, enabled: bool) {
+        ScintillaView::set_word_wrap(self, enabled);
+    }
+
+    fn is_word_wrap(&self) -> bool {
+        ScintillaView::is_word_wrap(self)
     }
 }
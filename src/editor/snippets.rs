@@ -0,0 +1,306 @@
+// ── Snippets ──────────────────────────────────────────────────────────────────
+//
+// User snippets from `%APPDATA%\Rivet\snippets\<language>.json`: a list of
+// `{ "trigger": …, "body": …, "description": … }` objects.  In a body `$1`,
+// `$2`, … (or `${1:default}`) mark tab stops, `$0` the final caret, and `$$`
+// a literal dollar.  Typing a trigger and pressing Tab, or picking it from
+// the completion list, expands it; Tab and Shift+Tab then move between the
+// stops.  The window decides when; this module loads, expands, and tracks
+// where the stops have moved to.  No `unsafe` — pure safe Rust + serde_json.
+
+use std::{fs, io, ops::Range, path::PathBuf, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::languages::Language;
+
+/// One snippet as written in the language's file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Snippet {
+    /// The word typed before Tab, e.g. `fn`.
+    pub(crate) trigger: String,
+    /// The text it expands to; lines after the first take the trigger
+    /// line's indentation, and a tab becomes one indent level.
+    pub(crate) body: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) description: String,
+}
+
+/// `%APPDATA%\Rivet\snippets\<language>.json`, or `None` if `APPDATA` is
+/// not set.
+pub(crate) fn snippets_path(lang: Language) -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("snippets");
+    p.push(format!("{}.json", lang.id()));
+    Some(p)
+}
+
+/// The snippets file for `lang`, first created with an example snippet so
+/// the format is there to copy.
+pub(crate) fn ensure_file(lang: Language) -> io::Result<PathBuf> {
+    let path = snippets_path(lang)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let example = [Snippet {
+            trigger: "todo".to_owned(),
+            body: "TODO(${1:name}): $0".to_owned(),
+            description: "Type todo and press Tab; Tab again moves to $0".to_owned(),
+        }];
+        let out = fs::File::create(&path)?;
+        serde_json::to_writer_pretty(out, &example).map_err(io::Error::other)?;
+    }
+    Ok(path)
+}
+
+/// Snippets per language, read on first use and again whenever the file
+/// changes, so edits made in Rivet apply on the next Tab.
+#[derive(Default)]
+pub(crate) struct SnippetCache {
+    loaded: Vec<(Language, Option<SystemTime>, Vec<Snippet>)>,
+}
+
+impl SnippetCache {
+    /// The snippets for `lang`; empty if its file is missing or not valid.
+    pub(crate) fn get(&mut self, lang: Language) -> &[Snippet] {
+        let path = snippets_path(lang);
+        let modified = path
+            .as_deref()
+            .and_then(|p| fs::metadata(p).ok())
+            .and_then(|m| m.modified().ok());
+        let i = match self.loaded.iter().position(|(l, _, _)| *l == lang) {
+            Some(i) if self.loaded[i].1 == modified => i,
+            found => {
+                let snippets = path
+                    .and_then(|p| fs::read(p).ok())
+                    .and_then(|data| serde_json::from_slice(&data).ok())
+                    .unwrap_or_default();
+                let entry = (lang, modified, snippets);
+                match found {
+                    Some(i) => {
+                        self.loaded[i] = entry;
+                        i
+                    }
+                    None => {
+                        self.loaded.push(entry);
+                        self.loaded.len() - 1
+                    }
+                }
+            }
+        };
+        &self.loaded[i].2
+    }
+}
+
+/// A snippet body laid out for insertion.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Expansion {
+    pub(crate) text: Vec<u8>,
+    /// Byte ranges of the tab stops in `text`, in visiting order: `$1`,
+    /// `$2`, … and `$0` (the end of the text if the body has none) last.
+    pub(crate) stops: Vec<Range<usize>>,
+}
+
+/// Lay out `body` for a line indented by `indent`: each new line starts with
+/// `eol` and `indent`, each tab becomes `indent_unit`, and the tab stops
+/// become plain text (their default, if any).  A stop number used twice
+/// keeps its first place.
+pub(crate) fn expand(body: &str, indent: &[u8], indent_unit: &[u8], eol: &[u8]) -> Expansion {
+    let mut text = Vec::with_capacity(body.len());
+    let mut numbered: Vec<(u32, Range<usize>)> = Vec::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\r' => {}
+            '\n' => {
+                text.extend_from_slice(eol);
+                text.extend_from_slice(indent);
+            }
+            '\t' => text.extend_from_slice(indent_unit),
+            '$' => match chars.peek() {
+                Some(&(_, '$')) => {
+                    chars.next();
+                    text.push(b'$');
+                }
+                Some(&(at, '{')) => {
+                    let rest = &body[at + 1..];
+                    let parsed = rest.split_once('}').and_then(|(inner, _)| {
+                        let (n, default) = inner.split_once(':').unwrap_or((inner, ""));
+                        Some((n.parse::<u32>().ok()?, default, inner.len()))
+                    });
+                    match parsed {
+                        Some((n, default, len)) => {
+                            let start = text.len();
+                            text.extend_from_slice(default.as_bytes());
+                            numbered.push((n, start..text.len()));
+                            // Skip `{`, the inside and `}`.
+                            let end = at + len + 2;
+                            while chars.next_if(|&(i, _)| i < end).is_some() {}
+                        }
+                        None => text.push(b'$'),
+                    }
+                }
+                Some(&(at, d)) if d.is_ascii_digit() => {
+                    let digits: String = body[at..]
+                        .chars()
+                        .take_while(char::is_ascii_digit)
+                        .collect();
+                    for _ in 0..digits.len() {
+                        chars.next();
+                    }
+                    let n = digits.parse::<u32>().unwrap_or(0);
+                    numbered.push((n, text.len()..text.len()));
+                }
+                _ => text.push(b'$'),
+            },
+            c => {
+                let mut buf = [0u8; 4];
+                text.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    let mut seen = Vec::new();
+    numbered.retain(|(n, _)| {
+        let first = !seen.contains(n);
+        seen.push(*n);
+        first
+    });
+    // `$0` sorts after every other stop.
+    numbered.sort_by_key(|(n, _)| n.wrapping_sub(1));
+    let mut stops: Vec<Range<usize>> = numbered.into_iter().map(|(_, r)| r).collect();
+    if !seen.contains(&0) {
+        stops.push(text.len()..text.len());
+    }
+    Expansion { text, stops }
+}
+
+/// The tab stops of an expanded snippet, followed as the user fills them in.
+///
+/// Edits are assumed to happen in the current stop: whatever the document
+/// grew or shrank by since the last move widens or narrows it and shifts
+/// every stop after it.
+#[derive(Debug)]
+pub(crate) struct TabStops {
+    stops: Vec<Range<usize>>,
+    current: usize,
+    doc_len: usize,
+}
+
+impl TabStops {
+    /// Stops of `expansion` inserted at `at`, in a document now `doc_len`
+    /// bytes long; the first stop is current.
+    pub(crate) fn new(expansion: &Expansion, at: usize, doc_len: usize) -> Self {
+        Self {
+            stops: expansion
+                .stops
+                .iter()
+                .map(|r| r.start + at..r.end + at)
+                .collect(),
+            current: 0,
+            doc_len,
+        }
+    }
+
+    /// The current stop, after the edits that made the document `doc_len`
+    /// bytes long.
+    pub(crate) fn current(&mut self, doc_len: usize) -> Range<usize> {
+        let delta = doc_len as isize - self.doc_len as isize;
+        self.doc_len = doc_len;
+        if delta != 0 {
+            let at = self.stops[self.current].clone();
+            let shift = |p: usize| p.saturating_add_signed(delta).max(at.start);
+            let current = self.current;
+            for (i, stop) in self.stops.iter_mut().enumerate() {
+                let after = stop.start > at.start
+                    || (stop.start == at.start && (stop.end > at.end || i > current));
+                if after {
+                    *stop = shift(stop.start)..shift(stop.end);
+                }
+            }
+            let cur = &mut self.stops[self.current];
+            cur.end = shift(at.end);
+        }
+        self.stops[self.current].clone()
+    }
+
+    /// Whether `current` is the last stop (`$0`), where the snippet ends.
+    pub(crate) fn at_last(&self) -> bool {
+        self.current + 1 == self.stops.len()
+    }
+
+    /// Move to the next stop (`forward`) or the previous one and return
+    /// it; `None` when there is none that way.
+    pub(crate) fn step(&mut self, forward: bool, doc_len: usize) -> Option<Range<usize>> {
+        self.current(doc_len);
+        self.current = if forward {
+            Some(self.current + 1).filter(|&i| i < self.stops.len())?
+        } else {
+            self.current.checked_sub(1)?
+        };
+        Some(self.stops[self.current].clone())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_stops_in_order_with_final_caret_last() {
+        let e = expand("fn $1(${2:args}) {\n\t$0\n}", b"  ", b"    ", b"\r\n");
+        assert_eq!(e.text, b"fn (args) {\r\n      \r\n  }");
+        assert_eq!(e.stops, [3..3, 4..8, 19..19]);
+    }
+
+    #[test]
+    fn without_final_stop_ends_at_the_end() {
+        let e = expand("cost: $$${1:5}", b"", b"\t", b"\n");
+        assert_eq!(e.text, b"cost: $5");
+        assert_eq!(e.stops, [7..8, 8..8]);
+        let e = expand("plain $x ${y}", b"", b"\t", b"\n");
+        assert_eq!(e.text, b"plain $x ${y}");
+        assert_eq!(e.stops.len(), 1);
+        assert_eq!(e.stops[0], 13..13);
+    }
+
+    #[test]
+    fn repeated_stop_keeps_its_first_place() {
+        let e = expand("$1 = $1 + ${1:x}", b"", b"\t", b"\n");
+        assert_eq!(e.text, b" =  + x");
+        assert_eq!(e.stops, [0..0, 7..7]);
+    }
+
+    #[test]
+    fn stops_follow_typing() {
+        // "if (cond) { body }" inserted at 10 in a 10-byte document.
+        let e = expand("if (${1:cond}) { $2 }$0", b"", b"\t", b"\n");
+        let mut stops = TabStops::new(&e, 10, 10 + e.text.len());
+        assert_eq!(stops.current(10 + e.text.len()), 14..18);
+        // Typing "x > 10" over "cond": two bytes longer.
+        let len = 10 + e.text.len() + 2;
+        assert_eq!(stops.step(true, len), Some(24..24));
+        // Typing "go()" at the second stop.
+        let len = len + 4;
+        assert!(!stops.at_last());
+        assert_eq!(stops.step(true, len), Some(30..30));
+        assert!(stops.at_last());
+        assert_eq!(stops.step(true, len), None);
+        assert_eq!(stops.step(false, len), Some(24..28));
+        assert_eq!(stops.step(false, len), Some(14..20));
+        assert_eq!(stops.step(false, len), None);
+    }
+
+    #[test]
+    fn snippet_file_parses() {
+        let json = r#"[{"trigger":"main","body":"fn main() {\n\t$0\n}"}]"#;
+        let snippets: Vec<Snippet> = serde_json::from_str(json).expect("parse");
+        assert_eq!(snippets[0].trigger, "main");
+        assert!(snippets[0].description.is_empty());
+    }
+}
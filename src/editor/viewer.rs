@@ -0,0 +1,347 @@
+// ── Huge-file viewer ──────────────────────────────────────────────────────────
+//
+// Files above `HUGE_FILE_THRESHOLD_BYTES` are never loaded whole.  A
+// `FileWindow` keeps the file open and hands the UI one line-aligned window
+// of at most `WINDOW_BYTES` at a time; paging and search results move the
+// window by seeking.  Search streams over the file in `CHUNK_BYTES` reads,
+// so memory use is independent of file size.
+//
+// Byte-oriented: offsets are file offsets, and window text is passed to
+// Scintilla unchanged (UTF-8 / ANSI).  No `unsafe` — pure safe Rust.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::search::Needle;
+
+/// Largest window handed to the editor at once.
+const WINDOW_BYTES: usize = 8 * 1024 * 1024;
+
+/// Read size while searching.
+const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+// ── File window ───────────────────────────────────────────────────────────────
+
+/// Read-only, windowed view of a file too large to load.
+pub(crate) struct FileWindow<R = File> {
+    src: R,
+    len: u64,
+    /// File offset of the first byte in the current window.
+    start: u64,
+    /// File offset just past the current window.
+    end: u64,
+    window_bytes: usize,
+    chunk_bytes: usize,
+}
+
+impl FileWindow<File> {
+    /// Open `path` for windowed viewing.  Nothing is read yet.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self::from_reader(file, len))
+    }
+}
+
+impl<R: Read + Seek> FileWindow<R> {
+    pub(crate) fn from_reader(src: R, len: u64) -> Self {
+        Self {
+            src,
+            len,
+            start: 0,
+            end: 0,
+            window_bytes: WINDOW_BYTES,
+            chunk_bytes: CHUNK_BYTES,
+        }
+    }
+
+    /// File size in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// File offset of the first byte in the current window.
+    pub(crate) fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// File offset just past the current window.
+    pub(crate) fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Whether the first bytes are a UTF-16 byte-order mark.  The viewer is
+    /// byte-oriented and cannot page such files.
+    pub(crate) fn is_utf16(&mut self) -> io::Result<bool> {
+        let head = self.read_at(0, 2)?;
+        Ok(head == [0xFF, 0xFE] || head == [0xFE, 0xFF])
+    }
+
+    fn read_at(&mut self, pos: u64, len: usize) -> io::Result<Vec<u8>> {
+        self.src.seek(SeekFrom::Start(pos))?;
+        let mut buf = Vec::with_capacity(len);
+        (&mut self.src).take(len as u64).read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Whether `pos` is the first byte of a line.
+    fn at_line_start(&mut self, pos: u64) -> io::Result<bool> {
+        Ok(pos == 0 || self.read_at(pos - 1, 1)? == b"\n")
+    }
+
+    /// Make `[start, start + buf.len())` the current window and return it.
+    fn set_window(&mut self, start: u64, buf: Vec<u8>) -> Vec<u8> {
+        self.start = start;
+        self.end = start + buf.len() as u64;
+        buf
+    }
+
+    /// Window beginning at `pos` (Page forward: pass the current `end`).
+    ///
+    /// A trailing partial line is cut unless it is the last line of the
+    /// file or the only line in the window.
+    pub(crate) fn show_from(&mut self, pos: u64) -> io::Result<Vec<u8>> {
+        let pos = pos.min(self.len);
+        let mut buf = self.read_at(pos, self.window_bytes)?;
+        if pos + (buf.len() as u64) < self.len {
+            if let Some(nl) = buf.iter().rposition(|&b| b == b'\n') {
+                buf.truncate(nl + 1);
+            }
+        }
+        Ok(self.set_window(pos, buf))
+    }
+
+    /// Window ending at `pos` (Page back: pass the current `start`).
+    ///
+    /// A leading partial line is cut unless it is the first line of the file
+    /// or the only line in the window.
+    pub(crate) fn show_until(&mut self, pos: u64) -> io::Result<Vec<u8>> {
+        let pos = pos.min(self.len);
+        let begin = pos.saturating_sub(self.window_bytes as u64);
+        let mut buf = self.read_at(begin, (pos - begin) as usize)?;
+        let mut start = begin;
+        if !self.at_line_start(begin)? {
+            if let Some(nl) = buf.iter().position(|&b| b == b'\n') {
+                if nl + 1 < buf.len() {
+                    buf.drain(..=nl);
+                    start += nl as u64 + 1;
+                }
+            }
+        }
+        Ok(self.set_window(start, buf))
+    }
+
+    /// Window roughly centred on `pos`, always containing the bytes
+    /// `[pos, pos + keep)` (e.g. a search match).
+    pub(crate) fn show_around(&mut self, pos: u64, keep: usize) -> io::Result<Vec<u8>> {
+        let pos = pos.min(self.len);
+        let begin = pos.saturating_sub(self.window_bytes as u64 / 2);
+        let mut buf = self.read_at(begin, self.window_bytes)?;
+        let rel = (pos - begin) as usize;
+        let mut start = begin;
+        // Trim a partial last line, but never into the kept range.
+        if begin + (buf.len() as u64) < self.len {
+            if let Some(nl) = buf.iter().rposition(|&b| b == b'\n') {
+                if nl + 1 >= rel + keep {
+                    buf.truncate(nl + 1);
+                }
+            }
+        }
+        // Trim a partial first line, but never past `pos`.
+        if !self.at_line_start(begin)? {
+            if let Some(nl) = buf[..rel].iter().position(|&b| b == b'\n') {
+                buf.drain(..=nl);
+                start += nl as u64 + 1;
+            }
+        }
+        Ok(self.set_window(start, buf))
+    }
+
+    // ── Search ────────────────────────────────────────────────────────────────
+
+    /// First match starting in `[from, to)`.
+    pub(crate) fn find_forward(
+        &mut self,
+        needle: &Needle,
+        from: u64,
+        to: u64,
+    ) -> io::Result<Option<u64>> {
+        let n = needle.len() as u64;
+        let to = to.min(self.len);
+        let mut pos = from;
+        while n > 0 && pos < to {
+            let cand_end = (pos + self.chunk_bytes as u64).min(to);
+            // One byte of context on each side for whole-word checks.
+            let read_start = pos.saturating_sub(1);
+            let read_end = (cand_end + n).min(self.len);
+            let buf = self.read_at(read_start, (read_end - read_start) as usize)?;
+            let at_end = read_end == self.len;
+            let first = (pos - read_start) as usize;
+            let last = (cand_end - read_start) as usize;
+            if let Some(i) =
+                (first..last).find(|&i| needle.matches_at(&buf, i, read_start == 0, at_end))
+            {
+                return Ok(Some(read_start + i as u64));
+            }
+            pos = cand_end;
+        }
+        Ok(None)
+    }
+
+    /// Last match starting in `[from, to)`.
+    pub(crate) fn find_backward(
+        &mut self,
+        needle: &Needle,
+        from: u64,
+        to: u64,
+    ) -> io::Result<Option<u64>> {
+        let n = needle.len() as u64;
+        let to = to.min(self.len);
+        let mut hi = to;
+        while n > 0 && hi > from {
+            let cand_start = hi.saturating_sub(self.chunk_bytes as u64).max(from);
+            let read_start = cand_start.saturating_sub(1);
+            let read_end = (hi + n).min(self.len);
+            let buf = self.read_at(read_start, (read_end - read_start) as usize)?;
+            let at_end = read_end == self.len;
+            let first = (cand_start - read_start) as usize;
+            let last = (hi - read_start) as usize;
+            if let Some(i) = (first..last)
+                .rev()
+                .find(|&i| needle.matches_at(&buf, i, read_start == 0, at_end))
+            {
+                return Ok(Some(read_start + i as u64));
+            }
+            hi = cand_start;
+        }
+        Ok(None)
+    }
+
+    /// Find Next / Previous from the selection `[sel_start, sel_end)` (file
+    /// offsets), wrapping around the file like `ScintillaView::find_next`.
+    pub(crate) fn find_wrapping(
+        &mut self,
+        needle: &Needle,
+        sel_start: u64,
+        sel_end: u64,
+        forward: bool,
+    ) -> io::Result<Option<u64>> {
+        if forward {
+            match self.find_forward(needle, sel_end, self.len)? {
+                Some(pos) => Ok(Some(pos)),
+                None => self.find_forward(needle, 0, sel_start),
+            }
+        } else {
+            match self.find_backward(needle, 0, sel_start)? {
+                Some(pos) => Ok(Some(pos)),
+                None => self.find_backward(needle, sel_end, self.len),
+            }
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// A window over `data` with tiny window / chunk sizes so the tests
+    /// cross every boundary.
+    fn small(data: &[u8], window: usize, chunk: usize) -> FileWindow<Cursor<Vec<u8>>> {
+        let mut w = FileWindow::from_reader(Cursor::new(data.to_vec()), data.len() as u64);
+        w.window_bytes = window;
+        w.chunk_bytes = chunk;
+        w
+    }
+
+    const TEXT: &[u8] = b"alpha\nbeta\ngamma\ndelta\nepsilon\n";
+
+    #[test]
+    fn show_from_cuts_partial_last_line() {
+        let mut w = small(TEXT, 14, 4);
+        assert_eq!(w.show_from(0).unwrap(), b"alpha\nbeta\n");
+        assert_eq!((w.start(), w.end()), (0, 11));
+        assert_eq!(w.show_from(w.end()).unwrap(), b"gamma\ndelta\n");
+    }
+
+    #[test]
+    fn show_until_cuts_partial_first_line() {
+        let mut w = small(TEXT, 14, 4);
+        let len = w.len();
+        // The window starts on a line: nothing is cut.
+        assert_eq!(w.show_until(len).unwrap(), b"delta\nepsilon\n");
+        assert_eq!((w.start(), w.end()), (17, len));
+        assert_eq!(w.show_until(w.start()).unwrap(), b"beta\ngamma\n");
+        assert_eq!(w.start(), 6);
+    }
+
+    #[test]
+    fn show_around_keeps_target() {
+        let mut w = small(TEXT, 12, 4);
+        // "delta" starts at 17.
+        let buf = w.show_around(17, 5).unwrap();
+        let rel = (17 - w.start()) as usize;
+        assert_eq!(&buf[rel..rel + 5], b"delta");
+        // The window began on "gamma", a whole line, which stays.
+        assert_eq!(buf, b"gamma\ndelta\n");
+    }
+
+    #[test]
+    fn single_long_line_is_not_emptied() {
+        let data = vec![b'x'; 40];
+        let mut w = small(&data, 16, 4);
+        assert_eq!(w.show_from(8).unwrap().len(), 16);
+        assert_eq!(w.show_until(30).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn find_forward_across_chunks() {
+        let mut w = small(TEXT, 64, 3);
+        let needle = Needle::new(b"DELTA", false, false);
+        assert_eq!(w.find_forward(&needle, 0, w.len()).unwrap(), Some(17));
+        assert_eq!(w.find_forward(&needle, 18, w.len()).unwrap(), None);
+        let exact = Needle::new(b"DELTA", true, false);
+        assert_eq!(w.find_forward(&exact, 0, w.len()).unwrap(), None);
+    }
+
+    #[test]
+    fn find_backward_returns_last_match() {
+        let data = b"ab ab ab";
+        let mut w = small(data, 64, 2);
+        let needle = Needle::new(b"ab", true, false);
+        assert_eq!(w.find_backward(&needle, 0, 8).unwrap(), Some(6));
+        assert_eq!(w.find_backward(&needle, 0, 6).unwrap(), Some(3));
+        assert_eq!(w.find_backward(&needle, 1, 3).unwrap(), None);
+    }
+
+    #[test]
+    fn whole_word_checks_neighbours_across_chunks() {
+        let data = b"cat concat cat_ cat";
+        let mut w = small(data, 64, 2);
+        let needle = Needle::new(b"cat", true, true);
+        assert_eq!(w.find_forward(&needle, 1, w.len()).unwrap(), Some(16));
+        assert_eq!(w.find_backward(&needle, 0, 16).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn find_wrapping_wraps_both_ways() {
+        let mut w = small(TEXT, 64, 4);
+        let needle = Needle::new(b"beta", true, false);
+        // From after the only match, forward wraps to it.
+        assert_eq!(w.find_wrapping(&needle, 20, 20, true).unwrap(), Some(6));
+        // From before it, backward wraps to it.
+        assert_eq!(w.find_wrapping(&needle, 2, 2, false).unwrap(), Some(6));
+    }
+
+    #[test]
+    fn utf16_bom_detected() {
+        assert!(small(b"\xFF\xFEa\0", 8, 4).is_utf16().unwrap());
+        assert!(!small(b"plain", 8, 4).is_utf16().unwrap());
+    }
+}
@@ -25,6 +25,16 @@ pub enum RivetError {
         detail: &'static str,
     },
 
+    /// A required DLL is missing, unreadable, or not the expected build.
+    /// A `static-scintilla` build loads none.
+    #[cfg_attr(feature = "static-scintilla", allow(dead_code))]
+    Dll {
+        /// What is wrong with it.
+        detail: &'static str,
+        /// Where the DLL was expected.
+        path: std::path::PathBuf,
+    },
+
     /// A Scintilla message returned an unexpected result.
     ///
     /// Scintilla messages do not have structured error returns; this variant
@@ -45,6 +55,9 @@ impl std::fmt::Display for RivetError {
             }
             Self::Io(e) => write!(f, "I/O error: {e}"),
             Self::Encoding { detail } => write!(f, "encoding error: {detail}"),
+            Self::Dll { detail, path } => {
+                write!(f, "{detail}\n\nExpected location:\n{}", path.display())
+            }
             Self::ScintillaMsg { message } => {
                 write!(f, "unexpected Scintilla result for message {message:#06x}")
             }
@@ -56,7 +69,10 @@ impl std::error::Error for RivetError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Io(e) => Some(e),
-            Self::Win32 { .. } | Self::Encoding { .. } | Self::ScintillaMsg { .. } => None,
+            Self::Win32 { .. }
+            | Self::Encoding { .. }
+            | Self::Dll { .. }
+            | Self::ScintillaMsg { .. } => None,
         }
     }
 }
@@ -67,6 +83,100 @@ impl From<std::io::Error> for RivetError {
     }
 }
 
+impl RivetError {
+    /// What went wrong with a file, for choosing what to offer the user.
+    pub(crate) fn file_problem(&self) -> FileProblem {
+        match self {
+            Self::Io(e) => FileProblem::of(e),
+            _ => FileProblem::Other,
+        }
+    }
+}
+
+/// Why a file could not be read or written, as far as it changes what the
+/// user can do about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileProblem {
+    /// Another process has the file open without sharing it.
+    InUse,
+    /// The file is read-only, or the user lacks permission.
+    AccessDenied,
+    /// The share or server cannot be reached.
+    NetworkUnavailable,
+    /// The file or a folder on its path does not exist.
+    NotFound,
+    Other,
+}
+
+impl FileProblem {
+    /// Classify `e` by its Win32 error code, or by its kind where it has
+    /// none.
+    pub(crate) fn of(e: &std::io::Error) -> Self {
+        match e.raw_os_error() {
+            // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+            Some(32 | 33) => Self::InUse,
+            // ERROR_ACCESS_DENIED, ERROR_WRITE_PROTECT
+            Some(5 | 19) => Self::AccessDenied,
+            // ERROR_REM_NOT_LIST, ERROR_BAD_NETPATH, ERROR_UNEXP_NET_ERR,
+            // ERROR_NETNAME_DELETED, ERROR_BAD_NET_NAME,
+            // ERROR_NO_NETWORK, ERROR_NETWORK_UNREACHABLE
+            Some(51 | 53 | 59 | 64 | 67 | 1222 | 1231) => Self::NetworkUnavailable,
+            // ERROR_FILE_NOT_FOUND, ERROR_PATH_NOT_FOUND
+            Some(2 | 3) => Self::NotFound,
+            Some(_) => Self::Other,
+            None => match e.kind() {
+                std::io::ErrorKind::PermissionDenied => Self::AccessDenied,
+                std::io::ErrorKind::NotFound => Self::NotFound,
+                _ => Self::Other,
+            },
+        }
+    }
+
+    /// Whether trying again unchanged may succeed once the user has acted
+    /// (closed the other program, reconnected).
+    pub(crate) fn retry_may_help(self) -> bool {
+        matches!(self, Self::InUse | Self::NetworkUnavailable)
+    }
+
+    /// A few words on why, to follow a file name in a list.
+    pub(crate) fn reason(self) -> &'static str {
+        match self {
+            Self::InUse => "in use by another program",
+            Self::AccessDenied => "access denied",
+            Self::NetworkUnavailable => "network location unreachable",
+            Self::NotFound => "not found",
+            Self::Other => "could not be read",
+        }
+    }
+
+    /// What to tell the user after failing to `verb` ("open", "save", …)
+    /// `path`, with the error's own text `detail` last.
+    pub(crate) fn message(self, verb: &str, path: &std::path::Path, detail: &str) -> String {
+        let advice = match self {
+            Self::InUse => "Another program is using the file. Close it there, then retry.",
+            Self::AccessDenied if verb == "open" || verb == "reload" => {
+                "You do not have permission to read it."
+            }
+            Self::AccessDenied => {
+                "The file is read-only, or you do not have permission to change it."
+            }
+            Self::NetworkUnavailable => {
+                "Its network location cannot be reached. Check the connection, then retry."
+            }
+            Self::NotFound => "The file or its folder no longer exists.",
+            Self::Other => "",
+        };
+        let mut text = format!("Could not {verb} \"{}\".", path.display());
+        if !advice.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(advice);
+        }
+        text.push_str("\n\n");
+        text.push_str(detail);
+        text
+    }
+}
+
 // Convert a windows-crate error (HRESULT) directly into a RivetError so that
 // `?` can be used on `windows::core::Result<T>` throughout the platform module.
 impl From<windows::core::Error> for RivetError {
@@ -82,3 +192,45 @@ impl From<windows::core::Error> for RivetError {
 
 /// Convenience alias used throughout the crate.
 pub type Result<T> = std::result::Result<T, RivetError>;
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn classifies_file_errors() {
+        let os = io::Error::from_raw_os_error;
+        assert_eq!(FileProblem::of(&os(32)), FileProblem::InUse);
+        assert_eq!(FileProblem::of(&os(5)), FileProblem::AccessDenied);
+        assert_eq!(FileProblem::of(&os(53)), FileProblem::NetworkUnavailable);
+        assert_eq!(FileProblem::of(&os(3)), FileProblem::NotFound);
+        assert_eq!(FileProblem::of(&os(112)), FileProblem::Other);
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(FileProblem::of(&denied), FileProblem::AccessDenied);
+        assert!(FileProblem::InUse.retry_may_help());
+        assert!(!FileProblem::AccessDenied.retry_may_help());
+        assert_eq!(
+            FileProblem::NetworkUnavailable.reason(),
+            "network location unreachable"
+        );
+    }
+
+    #[test]
+    fn message_names_the_file_and_says_what_to_do() {
+        let path = std::path::Path::new("a.txt");
+        let text = FileProblem::InUse.message("save", path, "busy");
+        assert!(text.starts_with("Could not save \"a.txt\"."));
+        assert!(text.contains("Close it there"));
+        assert!(text.ends_with("\n\nbusy"));
+        assert!(FileProblem::AccessDenied
+            .message("open", path, "")
+            .contains("permission to read"));
+        assert_eq!(
+            FileProblem::Other.message("open", path, "bad"),
+            "Could not open \"a.txt\".\n\nbad"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// ── Styled-text export ────────────────────────────────────────────────────────
+//
+// Turns Scintilla's per-byte style numbers into RTF and HTML for Edit > Copy
+// Special, so a paste into Word, Outlook or OneNote keeps the syntax colours.
+// The caller supplies the text, one style byte per text byte, and what each
+// style number looks like.  No `unsafe` — pure safe Rust.
+
+use std::fmt::Write as _;
+
+/// Scintilla's `STYLE_DEFAULT`: the look of unstyled text.
+pub(crate) const STYLE_DEFAULT: u8 = 32;
+
+/// What one style number looks like.  Colours are `COLORREF` (0x00BBGGRR).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TextStyle {
+    pub(crate) fore: u32,
+    pub(crate) back: u32,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+}
+
+/// A styled range of a document.
+pub(crate) struct Styled<'a> {
+    /// UTF-8 text.
+    pub(crate) text: &'a [u8],
+    /// The style number of each byte of `text`.
+    pub(crate) styles: &'a [u8],
+    /// Indexed by style number; missing entries look like `STYLE_DEFAULT`.
+    pub(crate) table: &'a [TextStyle],
+    pub(crate) font: &'a str,
+    /// Font size in points.
+    pub(crate) size: u32,
+}
+
+impl Styled<'_> {
+    fn style(&self, n: u8) -> TextStyle {
+        let default = self
+            .table
+            .get(STYLE_DEFAULT as usize)
+            .copied()
+            .unwrap_or_default();
+        self.table.get(n as usize).copied().unwrap_or(default)
+    }
+
+    /// Runs of text that look the same, in order.
+    fn runs(&self) -> Vec<(TextStyle, std::borrow::Cow<'_, str>)> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        while start < self.text.len() {
+            let style = self.style(self.styles.get(start).copied().unwrap_or(STYLE_DEFAULT));
+            let mut end = start + 1;
+            while end < self.text.len()
+                && self.style(self.styles.get(end).copied().unwrap_or(STYLE_DEFAULT)) == style
+            {
+                end += 1;
+            }
+            runs.push((style, String::from_utf8_lossy(&self.text[start..end])));
+            start = end;
+        }
+        runs
+    }
+}
+
+/// `#RRGGBB` for a `COLORREF`.
+fn css_colour(c: u32) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        c & 0xFF,
+        (c >> 8) & 0xFF,
+        (c >> 16) & 0xFF
+    )
+}
+
+/// The text as one `<pre>` block with a `<span>` per run that differs from
+/// the default style.
+pub(crate) fn to_html(doc: &Styled) -> String {
+    let default = doc.style(STYLE_DEFAULT);
+    let mut out = format!(
+        "<pre style=\"font-family:'{}',monospace;font-size:{}pt;color:{};background:{}\">",
+        doc.font,
+        doc.size,
+        css_colour(default.fore),
+        css_colour(default.back),
+    );
+    for (style, text) in doc.runs() {
+        let mut css = Vec::new();
+        if style.fore != default.fore {
+            css.push(format!("color:{}", css_colour(style.fore)));
+        }
+        if style.back != default.back {
+            css.push(format!("background:{}", css_colour(style.back)));
+        }
+        if style.bold {
+            css.push("font-weight:bold".to_owned());
+        }
+        if style.italic {
+            css.push("font-style:italic".to_owned());
+        }
+        if !css.is_empty() {
+            let _ = write!(out, "<span style=\"{}\">", css.join(";"));
+        }
+        for c in text.chars() {
+            match c {
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '&' => out.push_str("&amp;"),
+                '"' => out.push_str("&quot;"),
+                '\r' => {}
+                c => out.push(c),
+            }
+        }
+        if !css.is_empty() {
+            out.push_str("</span>");
+        }
+    }
+    out.push_str("</pre>");
+    out
+}
+
+/// Wrap an HTML fragment in the `CF_HTML` clipboard header, whose byte
+/// offsets tell the reader where the document and the fragment are.
+pub(crate) fn cf_html(fragment: &str) -> String {
+    const HEADER: &str = "Version:0.9\r\nStartHTML:{sh}\r\nEndHTML:{eh}\r\n\
+                          StartFragment:{sf}\r\nEndFragment:{ef}\r\n";
+    const BEFORE: &str = "<html><body>\r\n<!--StartFragment-->";
+    const AFTER: &str = "<!--EndFragment-->\r\n</body></html>";
+    // Each placeholder becomes a 10-digit number: 4 bytes longer.
+    let start_html = HEADER.len() + 4 * 6;
+    let start_fragment = start_html + BEFORE.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + AFTER.len();
+    let header = HEADER
+        .replace("{sh}", &format!("{start_html:010}"))
+        .replace("{eh}", &format!("{end_html:010}"))
+        .replace("{sf}", &format!("{start_fragment:010}"))
+        .replace("{ef}", &format!("{end_fragment:010}"));
+    format!("{header}{BEFORE}{fragment}{AFTER}")
+}
+
+/// The text as an RTF document with a colour table built from the styles
+/// it uses.
+pub(crate) fn to_rtf(doc: &Styled) -> String {
+    let runs = doc.runs();
+    let mut colours: Vec<u32> = Vec::new();
+    let mut index = |c: u32| match colours.iter().position(|&x| x == c) {
+        Some(i) => i + 1,
+        None => {
+            colours.push(c);
+            colours.len()
+        }
+    };
+    let mut body = String::new();
+    for (style, text) in &runs {
+        let _ = write!(
+            body,
+            "\\cf{}\\chcbpat{}\\b{}\\i{} ",
+            index(style.fore),
+            index(style.back),
+            if style.bold { "" } else { "0" },
+            if style.italic { "" } else { "0" },
+        );
+        for c in text.chars() {
+            match c {
+                '\\' | '{' | '}' => {
+                    body.push('\\');
+                    body.push(c);
+                }
+                '\n' => body.push_str("\\par\r\n"),
+                '\r' => {}
+                '\t' => body.push_str("\\tab "),
+                c if c.is_ascii() => body.push(c),
+                // RTF spells other characters as signed 16-bit UTF-16 units,
+                // each followed by a `?` fallback for old readers.
+                c => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        let _ = write!(body, "\\u{}?", *unit as i16);
+                    }
+                }
+            }
+        }
+    }
+    let mut out = format!(
+        "{{\\rtf1\\ansi\\deff0{{\\fonttbl{{\\f0\\fmodern {};}}}}{{\\colortbl;",
+        doc.font
+    );
+    for c in colours {
+        let _ = write!(
+            out,
+            "\\red{}\\green{}\\blue{};",
+            c & 0xFF,
+            (c >> 8) & 0xFF,
+            (c >> 16) & 0xFF
+        );
+    }
+    let _ = write!(out, "}}\\f0\\fs{} {body}}}", doc.size * 2);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Vec<TextStyle> {
+        let mut table = vec![TextStyle::default(); 33];
+        table[STYLE_DEFAULT as usize] = TextStyle {
+            fore: 0x000000,
+            back: 0xFFFFFF,
+            ..TextStyle::default()
+        };
+        table[5] = TextStyle {
+            fore: 0xFF0000, // blue in COLORREF
+            back: 0xFFFFFF,
+            bold: true,
+            italic: false,
+        };
+        table
+    }
+
+    #[test]
+    fn html_spans_only_styled_runs_and_escapes_text() {
+        let table = table();
+        let doc = Styled {
+            text: b"fn a<b>\r\n",
+            styles: &[5, 5, 32, 32, 32, 32, 32, 32, 32],
+            table: &table,
+            font: "Consolas",
+            size: 10,
+        };
+        let html = to_html(&doc);
+        assert!(html.contains("<span style=\"color:#0000ff;font-weight:bold\">fn</span>"));
+        assert!(html.ends_with(" a&lt;b&gt;\n</pre>"));
+    }
+
+    #[test]
+    fn cf_html_offsets_point_at_the_fragment() {
+        let frag = "<pre>x</pre>";
+        let clip = cf_html(frag);
+        let offset = |key: &str| -> usize {
+            let at = clip.find(key).unwrap() + key.len();
+            clip[at..at + 10].parse().unwrap()
+        };
+        assert_eq!(
+            &clip[offset("StartFragment:")..offset("EndFragment:")],
+            frag
+        );
+        assert!(clip[offset("StartHTML:")..].starts_with("<html>"));
+        assert_eq!(offset("EndHTML:"), clip.len());
+    }
+
+    #[test]
+    fn rtf_escapes_and_builds_a_colour_table() {
+        let table = table();
+        let doc = Styled {
+            text: "{é}\n".as_bytes(),
+            styles: &[5, 32, 32, 32, 32],
+            table: &table,
+            font: "Consolas",
+            size: 10,
+        };
+        let rtf = to_rtf(&doc);
+        assert!(rtf.contains("{\\colortbl;\\red0\\green0\\blue255;\\red255\\green255\\blue255;\\red0\\green0\\blue0;}"));
+        assert!(rtf.contains("\\cf1\\chcbpat2\\b\\i0 \\{"));
+        assert!(rtf.contains("\\cf3\\chcbpat2\\b0\\i0 \\u233?\\}\\par\r\n"));
+        assert!(rtf.starts_with("{\\rtf1") && rtf.ends_with('}'));
+    }
+}
@@ -0,0 +1,72 @@
+// ── File details for Document Properties ──────────────────────────────────────
+//
+// The "File Info" section of File > Document Properties…: the file's times,
+// size on disk, attributes, and the path it resolves to once links are
+// followed.  Reading them is Win32 work (`platform::win32::file_info`); this
+// module holds the result and turns attribute bits into text.
+//
+// No `unsafe` — pure safe Rust.
+
+/// What the file on disk says about itself, ready to show.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FileInfo {
+    /// Local creation time, e.g. `"2026-10-16 14:03:27"`.
+    pub(crate) created: Option<String>,
+    /// Local last-write time.
+    pub(crate) modified: Option<String>,
+    /// Size on disk in bytes, which differs from the document's while it has
+    /// unsaved changes or a different encoding.
+    pub(crate) size: u64,
+    /// Attribute names, e.g. `"Read-only, Archive"`.
+    pub(crate) attributes: String,
+    /// Target of the path when it is itself a symbolic link.
+    pub(crate) link_target: Option<String>,
+    /// The final path, with every link, junction and mapped drive followed.
+    pub(crate) resolved: Option<String>,
+}
+
+/// `FILE_ATTRIBUTE_*` bits (winnt.h) and how Explorer names them, in the
+/// order they are listed.
+const ATTRIBUTES: [(u32, &str); 11] = [
+    (0x0001, "Read-only"),
+    (0x0002, "Hidden"),
+    (0x0004, "System"),
+    (0x0020, "Archive"),
+    (0x0100, "Temporary"),
+    (0x0200, "Sparse"),
+    (0x0400, "Reparse point"),
+    (0x0800, "Compressed"),
+    (0x1000, "Offline"),
+    (0x2000, "Not content-indexed"),
+    (0x4000, "Encrypted"),
+];
+
+/// Names of the attributes set in `attributes`, comma-separated, or
+/// `"Normal"` if none of them is.
+pub(crate) fn attribute_names(attributes: u32) -> String {
+    let names: Vec<&str> = ATTRIBUTES
+        .iter()
+        .filter(|(bit, _)| attributes & bit != 0)
+        .map(|&(_, name)| name)
+        .collect();
+    if names.is_empty() {
+        "Normal".to_owned()
+    } else {
+        names.join(", ")
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_attributes_in_order() {
+        assert_eq!(attribute_names(0), "Normal");
+        assert_eq!(attribute_names(0x80), "Normal");
+        assert_eq!(attribute_names(0x21), "Read-only, Archive");
+        assert_eq!(attribute_names(0x0402), "Hidden, Reparse point");
+    }
+}
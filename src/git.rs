@@ -0,0 +1,144 @@
+// ── Git gutter ────────────────────────────────────────────────────────────────
+//
+// Which lines of a document differ from the file's last committed version:
+// the window asks git for `HEAD:<path>` on a worker thread, diffs it against
+// the buffer with `diff`, and marks the result in a thin margin.  This module
+// finds the repository and turns an edit script into per-line marks; running
+// git is left to the caller.  No `unsafe` — pure safe Rust.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::diff::{diff, split_lines, Edit};
+
+/// How a line of the buffer differs from HEAD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LineChange {
+    /// Not in HEAD.
+    Added,
+    /// Replaces lines of HEAD.
+    Modified,
+    /// Lines of HEAD were removed just above this one (or below it, for
+    /// the last line).
+    Deleted,
+}
+
+/// The working tree containing `path`: the nearest ancestor with a `.git`
+/// entry (a directory, or a file in a linked worktree).
+pub(crate) fn repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// `path` relative to `root`, with `/` separators as git spells it in
+/// `HEAD:<path>`.
+pub(crate) fn repo_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Option<Vec<&str>> = relative.iter().map(|p| p.to_str()).collect();
+    Some(parts?.join("/"))
+}
+
+/// The changed lines of `text` against `head`, as (0-based line, change)
+/// in line order.  A UTF-8 BOM on either side is ignored.
+pub(crate) fn line_changes(head: &[u8], text: &[u8]) -> Vec<(usize, LineChange)> {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+    let old = split_lines(head.strip_prefix(BOM).unwrap_or(head));
+    let new = split_lines(text.strip_prefix(BOM).unwrap_or(text));
+    let last_line = new.len().saturating_sub(1);
+
+    let mut changes = Vec::new();
+    // The current run of edits between kept lines.
+    let mut deleted = false;
+    let mut inserted: Vec<usize> = Vec::new();
+    let mut next_line = 0;
+    let mut flush = |deleted: &mut bool, inserted: &mut Vec<usize>, next_line: usize| {
+        if inserted.is_empty() {
+            if *deleted {
+                changes.push((next_line.min(last_line), LineChange::Deleted));
+            }
+        } else {
+            let kind = if *deleted {
+                LineChange::Modified
+            } else {
+                LineChange::Added
+            };
+            changes.extend(inserted.drain(..).map(|line| (line, kind)));
+        }
+        *deleted = false;
+    };
+    for edit in diff(&old, &new) {
+        match edit {
+            Edit::Keep(_, y) => {
+                flush(&mut deleted, &mut inserted, y);
+                next_line = y + 1;
+            }
+            Edit::Delete(_) => deleted = true,
+            Edit::Insert(y) => {
+                inserted.push(y);
+                next_line = y + 1;
+            }
+        }
+    }
+    flush(&mut deleted, &mut inserted, next_line);
+    changes
+}
+
+/// Fetch HEAD's copy with `show_head` and diff it against `text`: the body
+/// of a gutter refresh, run on the task pool (`crate::tasks`).
+pub(crate) fn gutter_changes(
+    show_head: impl FnOnce() -> io::Result<Vec<u8>>,
+    text: &[u8],
+) -> io::Result<Vec<(usize, LineChange)>> {
+    show_head().map(|head| line_changes(&head, text))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LineChange::{Added, Deleted, Modified};
+
+    #[test]
+    fn marks_added_modified_and_deleted_lines() {
+        let head = b"one\ntwo\nthree\nfour\nfive\n";
+        let text = b"one\nTWO\nthree\nnew\nfour\n";
+        assert_eq!(
+            line_changes(head, text),
+            [(1, Modified), (3, Added), (4, Deleted)]
+        );
+    }
+
+    #[test]
+    fn deletion_marks_the_following_line() {
+        assert_eq!(line_changes(b"a\nb\nc\n", b"a\nc\n"), [(1, Deleted)]);
+        assert_eq!(line_changes(b"a\nb\n", b"b\n"), [(0, Deleted)]);
+        assert_eq!(line_changes(b"a\nb\n", b""), [(0, Deleted)]);
+    }
+
+    #[test]
+    fn line_endings_and_bom_do_not_count() {
+        assert!(line_changes(b"a\nb\n", b"\xEF\xBB\xBFa\r\nb\r\n").is_empty());
+        assert_eq!(line_changes(b"", b"x\ny"), [(0, Added), (1, Added)]);
+    }
+
+    #[test]
+    fn repo_paths_use_forward_slashes() {
+        let root = Path::new("repo");
+        let file = root.join("src").join("main.rs");
+        assert_eq!(repo_path(root, &file).as_deref(), Some("src/main.rs"));
+        assert_eq!(repo_path(root, Path::new("elsewhere")), None);
+    }
+
+    #[test]
+    fn gutter_diffs_head_against_text() {
+        let changes = gutter_changes(|| Ok(b"a\n".to_vec()), b"a\nb\n").unwrap();
+        assert_eq!(changes, [(1, Added)]);
+        let missing = gutter_changes(|| Err(io::Error::other("untracked")), b"a\n");
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+// ── Headless flow tests ───────────────────────────────────────────────────────
+//
+// `Harness` drives `App` and one `MemoryView` per tab the way the Win32 window
+// drives `ScintillaView`s: same `App` calls, same `commands` helpers, no
+// window.  The tests below cover whole open → edit → save → close flows and
+// the session round trip, using real files under the temp directory.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::{App, Encoding, EolMode},
+    commands::{self, Command, Outcome},
+    editor::{memory::MemoryView, EditorView},
+    session::{self, restore::Source, TabEntry},
+};
+
+/// The document half of the main window: tabs and their views.
+struct Harness {
+    app: App,
+    views: Vec<MemoryView>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self {
+            app: App::new(),
+            views: vec![MemoryView::default()],
+        }
+    }
+
+    fn view(&self) -> &MemoryView {
+        &self.views[self.app.active_idx]
+    }
+
+    /// File > Open: reuse a blank active tab, otherwise open a new one.
+    fn open(&mut self, path: &Path) {
+        let bytes = fs::read(path).expect("read test file");
+        self.load(path.to_path_buf(), &bytes, None);
+    }
+
+    /// Put `bytes` read from `path` in a tab, as `open_path` and
+    /// `restore_tabs` do once the file is read.
+    fn load(&mut self, path: PathBuf, bytes: &[u8], code_page: Option<Encoding>) {
+        if !self.app.active_doc().is_blank() {
+            self.new_tab();
+        }
+        let utf8 = self.app.open_file(path, bytes, code_page);
+        commands::show_document(self.app.active_doc(), self.view(), &utf8);
+    }
+
+    fn new_tab(&mut self) {
+        self.app.active_idx = self.app.push_untitled();
+        self.views.push(MemoryView::default());
+    }
+
+    /// Type at the caret.  The window learns of the edit from
+    /// `SCN_SAVEPOINTLEFT`; here the view's save point stands in for it.
+    fn type_text(&mut self, text: &[u8]) {
+        self.view().type_text(text);
+        self.app.active_doc_mut().dirty = self.view().is_modified();
+    }
+
+    fn run(&mut self, cmd: Command) -> Outcome {
+        let idx = self.app.active_idx;
+        let out = commands::execute(cmd, &mut self.app, &self.views[idx]);
+        self.app.active_doc_mut().dirty = self.views[idx].is_modified();
+        out
+    }
+
+    /// File > Save (or Save As with `path`).
+    fn save(&mut self, path: Option<&Path>) {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| self.app.active_doc().path.clone())
+            .expect("save needs a path");
+        let text = self.view().get_text();
+        self.app.save(path, &text).expect("save");
+        self.view().set_save_point();
+    }
+
+    /// File > Close without the unsaved-changes prompt.
+    fn close(&mut self, idx: usize) {
+        if self.app.tab_count() == 1 {
+            commands::reset_to_untitled(&mut self.app.tabs[0], &self.views[0]);
+            return;
+        }
+        self.views.remove(idx);
+        self.app.remove_tab(idx);
+    }
+
+    /// A finished drag in the tab strip.
+    fn move_tab(&mut self, from: usize, to: usize) {
+        let view = self.views.remove(from);
+        self.views.insert(to, view);
+        self.app.move_tab(from, to);
+    }
+
+    fn session_entries(&self) -> Vec<TabEntry> {
+        self.app
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, doc)| TabEntry::new(doc, &self.views[i], i))
+            .collect()
+    }
+
+    /// Reopen a saved session the way `restore_tabs` does, recovering
+    /// backups from `backup_dir` if given.  Viewer tabs have no headless
+    /// counterpart and are not expected here.
+    fn restore(sf: &session::SessionFile, backup_dir: Option<&Path>) -> Self {
+        let mut h = Self::new();
+        let mut opened_any = false;
+        let mut target = 0;
+        for tab in session::restore::reopen(sf, backup_dir) {
+            let tab = tab.expect("reopen test file");
+            match tab.source {
+                Source::Untitled if opened_any => h.new_tab(),
+                Source::Untitled => {}
+                Source::Viewer(path) => panic!("{} is too large", path.display()),
+                Source::File {
+                    path,
+                    bytes,
+                    code_page,
+                } => h.load(path, &bytes, code_page),
+            }
+            if let Some(text) = &tab.backup {
+                h.view().set_text(text);
+                h.app.active_doc_mut().dirty = true;
+            }
+            h.view().set_caret_pos(tab.caret_pos);
+            h.view().set_first_visible_line(tab.scroll_line);
+            if tab.up_to_active {
+                target = h.app.active_idx;
+            }
+            opened_any = true;
+        }
+        h.app.active_idx = target;
+        h
+    }
+}
+
+/// A fresh folder under the temp directory, removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("rivet-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        Self(dir)
+    }
+
+    fn file(&self, name: &str, bytes: &[u8]) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, bytes).expect("write test file");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn open_edit_save_close() {
+    let dir = TempDir::new("flow-save");
+    let path = dir.file("notes.txt", b"one\r\ntwo\r\n");
+    let mut h = Harness::new();
+    h.open(&path);
+    assert_eq!(h.app.active_doc().eol, EolMode::Crlf);
+    assert!(!h.app.active_doc().dirty);
+
+    h.view().set_caret_pos(h.view().doc_len());
+    h.type_text(b"three\r\n");
+    assert!(h.app.active_doc().dirty);
+    assert_eq!(h.app.window_title(), "*notes.txt \u{2014} Rivet");
+
+    h.save(None);
+    assert!(!h.app.active_doc().dirty);
+    assert_eq!(fs::read(&path).unwrap(), b"one\r\ntwo\r\nthree\r\n");
+
+    h.close(0);
+    assert!(h.app.active_doc().is_blank());
+    assert_eq!(h.view().doc_len(), 0);
+}
+
+#[test]
+fn second_open_gets_its_own_tab_and_close_keeps_the_other() {
+    let dir = TempDir::new("flow-tabs");
+    let a = dir.file("a.txt", b"a");
+    let b = dir.file("b.rs", b"fn b() {}");
+    let mut h = Harness::new();
+    h.open(&a);
+    h.open(&b);
+    assert_eq!(h.app.tab_count(), 2);
+    assert_eq!(h.app.active_idx, 1);
+    assert_eq!(h.view().get_text(), b"fn b() {}");
+
+    h.close(1);
+    assert_eq!(h.app.tab_count(), 1);
+    assert_eq!(h.app.active_doc().path.as_deref(), Some(a.as_path()));
+    assert_eq!(h.view().get_text(), b"a");
+}
+
+#[test]
+fn save_as_untitled_detects_language() {
+    let dir = TempDir::new("flow-save-as");
+    let mut h = Harness::new();
+    h.type_text(b"print('hi')\n");
+    let path = dir.0.join("hello.py");
+    h.save(Some(&path));
+    assert_eq!(h.app.active_doc().path.as_deref(), Some(path.as_path()));
+    assert_eq!(
+        h.app.active_doc().language,
+        crate::languages::Language::Python
+    );
+    assert_eq!(fs::read(&path).unwrap(), b"print('hi')\n");
+}
+
+#[test]
+fn eol_conversion_is_saved_and_undoable() {
+    let dir = TempDir::new("flow-eol");
+    let path = dir.file("unix.txt", b"a\nb\n");
+    let mut h = Harness::new();
+    h.open(&path);
+    assert_eq!(h.app.active_doc().eol, EolMode::Lf);
+
+    assert_eq!(h.run(Command::ConvertEol(EolMode::Crlf)), Outcome::Updated);
+    assert!(h.app.active_doc().dirty);
+    h.save(None);
+    assert_eq!(fs::read(&path).unwrap(), b"a\r\nb\r\n");
+
+    h.run(Command::Undo);
+    assert_eq!(h.view().get_text(), b"a\nb\n");
+    assert!(h.app.active_doc().dirty);
+}
+
+#[test]
+fn utf16_files_round_trip() {
+    let dir = TempDir::new("flow-utf16");
+    let text = "h\u{e9}llo\r\n";
+    let mut le = vec![0xFF, 0xFE];
+    le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    let mut be = vec![0xFE, 0xFF];
+    be.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+
+    for (name, bytes, encoding) in [
+        ("le.txt", le, Encoding::Utf16Le),
+        ("be.txt", be, Encoding::Utf16Be),
+    ] {
+        let path = dir.file(name, &bytes);
+        let mut h = Harness::new();
+        h.open(&path);
+        assert_eq!(h.app.active_doc().encoding, encoding);
+        assert_eq!(h.view().get_text(), text.as_bytes());
+        h.save(None);
+        assert_eq!(fs::read(&path).unwrap(), bytes, "{name}");
+    }
+}
+
+#[test]
+fn non_utf8_bytes_survive_a_save() {
+    let dir = TempDir::new("flow-ansi");
+    let bytes = b"caf\xe9\r\n";
+    let path = dir.file("latin1.txt", bytes);
+    let mut h = Harness::new();
+    h.open(&path);
+    assert_eq!(h.app.active_doc().encoding, Encoding::Ansi);
+    assert_eq!(h.view().get_text(), "café\r\n".as_bytes());
+    h.save(None);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+}
+
+#[test]
+fn session_restores_tabs_order_and_caret() {
+    let dir = TempDir::new("flow-session");
+    let a = dir.file("a.txt", b"alpha\n");
+    let b = dir.file("b.txt", b"bravo\ncharlie\n");
+    let gone = dir.file("gone.txt", b"");
+    let mut h = Harness::new();
+    h.open(&a);
+    h.open(&gone);
+    h.open(&b);
+    h.view().set_caret_pos(8);
+    h.view().set_first_visible_line(1);
+    h.move_tab(2, 0);
+
+    let file = dir.0.join("session.json");
+    session::save_to(&file, &h.session_entries(), h.app.active_idx, false, 0).expect("save");
+    fs::remove_file(&gone).unwrap();
+
+    let sf = session::load_from(&file).expect("load");
+    let r = Harness::restore(&sf, None);
+    assert_eq!(r.app.tab_count(), 2);
+    assert_eq!(r.app.tabs[0].path.as_deref(), Some(b.as_path()));
+    assert_eq!(r.app.tabs[1].path.as_deref(), Some(a.as_path()));
+    assert_eq!(r.app.active_idx, 0);
+    assert_eq!(r.view().caret_pos(), 8);
+    assert_eq!(r.view().first_visible_line(), 1);
+}
+
+#[test]
+fn session_recovers_unsaved_text() {
+    let dir = TempDir::new("flow-recover");
+    let a = dir.file("a.txt", b"saved\n");
+    let backups = dir.0.join("backup");
+    let mut h = Harness::new();
+    h.open(&a);
+    h.type_text(b"edited ");
+    h.new_tab();
+    h.type_text(b"scratch");
+
+    let mut entries = h.session_entries();
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let text = h.views[i].get_text();
+        entry.backup = Some(session::write_backup(&backups, i, &text).expect("backup"));
+    }
+    let file = dir.0.join("session.json");
+    session::save_to(&file, &entries, h.app.active_idx, false, 0).expect("save");
+    let sf = session::load_from(&file).expect("load");
+
+    let r = Harness::restore(&sf, Some(&backups));
+    assert_eq!(r.app.tab_count(), 2);
+    assert_eq!(r.app.tabs[0].path.as_deref(), Some(a.as_path()));
+    assert_eq!(r.views[0].get_text(), b"edited saved\n");
+    assert!(r.app.tabs[0].dirty);
+    assert_eq!(r.app.tabs[1].path, None);
+    assert_eq!(r.views[1].get_text(), b"scratch");
+    assert_eq!(r.app.active_idx, 1);
+
+    let plain = Harness::restore(&sf, None);
+    assert_eq!(plain.app.tab_count(), 1);
+    assert_eq!(plain.view().get_text(), b"saved\n");
+}
@@ -0,0 +1,173 @@
+// ── JSON formatting ───────────────────────────────────────────────────────────
+//
+// Tools > Format JSON and Minify JSON.  serde_json checks the text and
+// locates syntax errors; the reformatting itself only changes whitespace
+// outside strings, so key order, number spelling and escapes are kept
+// exactly as written.  No `unsafe` — pure safe Rust.
+
+/// Why the text is not JSON, and where.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ParseError {
+    /// Byte offset into the text where serde_json gave up.
+    pub(crate) offset: usize,
+    /// What is wrong, e.g. "expected `,` or `}`".
+    pub(crate) message: String,
+}
+
+/// Check that `text` is one JSON value.
+fn validate(text: &[u8]) -> Result<(), ParseError> {
+    let Err(e) = serde_json::from_slice::<serde::de::IgnoredAny>(text) else {
+        return Ok(());
+    };
+    let display = e.to_string();
+    let message = match display.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_owned(),
+        None => display,
+    };
+    Err(ParseError {
+        offset: offset_of(text, e.line(), e.column()),
+        message,
+    })
+}
+
+/// Byte offset of 1-based `line` and byte `column` in `text`, clamped to the
+/// text.
+fn offset_of(text: &[u8], line: usize, column: usize) -> usize {
+    let line_start = match line.checked_sub(2) {
+        Some(newlines) => text
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'\n')
+            .nth(newlines)
+            .map_or(text.len(), |(i, _)| i + 1),
+        None => 0,
+    };
+    (line_start + column.saturating_sub(1)).min(text.len())
+}
+
+/// `text` pretty-printed with one member or element per line, each level
+/// indented by `indent`.  Empty objects and arrays stay as `{}` and `[]`.
+/// Lines end with `eol`; a final EOL is kept.
+pub(crate) fn format(text: &[u8], indent: &str, eol: &[u8]) -> Result<Vec<u8>, ParseError> {
+    validate(text)?;
+    Ok(reflow(text, Some((indent.as_bytes(), eol))))
+}
+
+/// `text` with all whitespace outside strings removed; a final EOL is kept.
+pub(crate) fn minify(text: &[u8]) -> Result<Vec<u8>, ParseError> {
+    validate(text)?;
+    Ok(reflow(text, None))
+}
+
+/// Re-space valid JSON: compact when `pretty` is `None`, otherwise with the
+/// given indent unit and EOL.
+fn reflow(text: &[u8], pretty: Option<(&[u8], &[u8])>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len() + text.len() / 2);
+    let mut depth = 0;
+    let newline = |out: &mut Vec<u8>, depth: usize| {
+        if let Some((indent, eol)) = pretty {
+            out.extend_from_slice(eol);
+            for _ in 0..depth {
+                out.extend_from_slice(indent);
+            }
+        }
+    };
+    let next_significant = |from: usize| {
+        text[from..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .map(|i| from + i)
+    };
+    let mut i = 0;
+    while i < text.len() {
+        let b = text[i];
+        match b {
+            b'"' => {
+                // Copy the string through, escapes and all.
+                let mut end = i + 1;
+                while end < text.len() && text[end] != b'"' {
+                    end += if text[end] == b'\\' { 2 } else { 1 };
+                }
+                let end = (end + 1).min(text.len());
+                out.extend_from_slice(&text[i..end]);
+                i = end;
+                continue;
+            }
+            b'{' | b'[' => {
+                out.push(b);
+                let close = if b == b'{' { b'}' } else { b']' };
+                match next_significant(i + 1) {
+                    Some(j) if text[j] == close => {
+                        out.push(close);
+                        i = j;
+                    }
+                    _ => {
+                        depth += 1;
+                        newline(&mut out, depth);
+                    }
+                }
+            }
+            b'}' | b']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(b);
+            }
+            b',' => {
+                out.push(b);
+                newline(&mut out, depth);
+            }
+            b':' => {
+                out.push(b);
+                if pretty.is_some() {
+                    out.push(b' ');
+                }
+            }
+            b if b.is_ascii_whitespace() => {}
+            _ => out.push(b),
+        }
+        i += 1;
+    }
+    if let Some(eol) = ["\r\n", "\n", "\r"]
+        .iter()
+        .find(|e| text.ends_with(e.as_bytes()))
+    {
+        out.extend_from_slice(pretty.map_or(eol.as_bytes(), |(_, eol)| eol));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str =
+        "{ \"b\": [1, 2.50, {}],\r\n  \"a\" : { \"s\": \"x, {y}: \\\"z\\\"\" }, \"e\": [ ] }\r\n";
+
+    #[test]
+    fn format_keeps_order_numbers_and_strings() {
+        let pretty = String::from_utf8(format(SRC.as_bytes(), "  ", b"\n").unwrap()).unwrap();
+        assert_eq!(
+            pretty,
+            "{\n  \"b\": [\n    1,\n    2.50,\n    {}\n  ],\n  \"a\": {\n    \
+             \"s\": \"x, {y}: \\\"z\\\"\"\n  },\n  \"e\": []\n}\n"
+        );
+    }
+
+    #[test]
+    fn minify_strips_whitespace_outside_strings() {
+        assert_eq!(
+            minify(SRC.as_bytes()).unwrap(),
+            b"{\"b\":[1,2.50,{}],\"a\":{\"s\":\"x, {y}: \\\"z\\\"\"},\"e\":[]}\r\n"
+        );
+        assert_eq!(minify(b" 42 ").unwrap(), b"42");
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let text = b"{\n  \"a\": 1\n  \"b\": 2\n}";
+        let err = minify(text).unwrap_err();
+        assert_eq!(err.message, "expected `,` or `}`");
+        assert_eq!(&text[err.offset..err.offset + 3], b"\"b\"");
+        assert!(format(b"[1, 2", "\t", b"\n").is_err());
+    }
+}
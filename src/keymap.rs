@@ -0,0 +1,410 @@
+// ── Keyboard shortcuts ────────────────────────────────────────────────────────
+//
+// Reads and writes `%APPDATA%\Rivet\keymap.json`: one key chord per command
+// name, e.g. `"file.new": "Ctrl+N"`.  The command registry itself (names,
+// labels, menu IDs, default chords) lives with the menu in the Win32 layer;
+// this module parses chords and merges the file over the defaults.
+// No `unsafe` — pure safe Rust + serde_json.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+// ── Command registry entry ────────────────────────────────────────────────────
+
+/// One bindable command.
+pub(crate) struct Command {
+    /// Menu command ID sent as `WM_COMMAND` when the chord is pressed.
+    pub(crate) id: usize,
+    /// Stable name used as the key in `keymap.json`.
+    pub(crate) name: &'static str,
+    /// Human-readable name shown in the shortcut editor.
+    pub(crate) label: &'static str,
+    /// Built-in chord, or `""` for none.
+    pub(crate) default: &'static str,
+}
+
+// ── Chords ────────────────────────────────────────────────────────────────────
+
+/// A key with modifiers.  `key` is a Win32 virtual-key code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Chord {
+    pub(crate) ctrl: bool,
+    pub(crate) shift: bool,
+    pub(crate) alt: bool,
+    pub(crate) key: u16,
+}
+
+/// Named keys: the first spelling of each code is the one displayed.
+const KEY_NAMES: &[(&str, u16)] = &[
+    ("Enter", 0x0D),
+    ("Return", 0x0D),
+    ("Tab", 0x09),
+    ("Space", 0x20),
+    ("Backspace", 0x08),
+    ("Esc", 0x1B),
+    ("Escape", 0x1B),
+    ("PgUp", 0x21),
+    ("PageUp", 0x21),
+    ("PgDn", 0x22),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Ins", 0x2D),
+    ("Insert", 0x2D),
+    ("Del", 0x2E),
+    ("Delete", 0x2E),
+    // Punctuation keys (`VK_OEM_*`), named by their US-layout character.
+    (";", 0xBA),
+    ("=", 0xBB),
+    (",", 0xBC),
+    ("-", 0xBD),
+    (".", 0xBE),
+    ("/", 0xBF),
+    ("`", 0xC0),
+    ("[", 0xDB),
+    ("\\", 0xDC),
+    ("]", 0xDD),
+    ("'", 0xDE),
+];
+
+/// `VK_F1`; F1–F24 are consecutive.
+const VK_F1: u16 = 0x70;
+/// `VK_ESCAPE`.
+const VK_ESCAPE: u16 = 0x1B;
+
+fn parse_key(name: &str) -> Option<u16> {
+    if let [c] = name.as_bytes() {
+        let c = c.to_ascii_uppercase();
+        if c.is_ascii_uppercase() || c.is_ascii_digit() {
+            return Some(u16::from(c));
+        }
+    }
+    if let Some(n) = name
+        .strip_prefix(['F', 'f'])
+        .and_then(|n| n.parse::<u16>().ok())
+    {
+        return (1..=24).contains(&n).then_some(VK_F1 + n - 1);
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, vk)| vk)
+}
+
+fn is_function_key(vk: u16) -> bool {
+    (VK_F1..VK_F1 + 24).contains(&vk)
+}
+
+/// Split `"Ctrl+Shift+F3"`-style text (case-insensitive, spaces ignored)
+/// into `[ctrl, shift, alt, win]` flags and a virtual-key code.  `None` if
+/// the text is malformed or repeats a modifier.
+fn parse_parts(text: &str) -> Option<([bool; 4], u16)> {
+    let mut mods = [false; 4];
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parts = text.split('+').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return Some((mods, parse_key(part)?));
+        }
+        let i = match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => 0,
+            "shift" => 1,
+            "alt" => 2,
+            "win" => 3,
+            _ => return None,
+        };
+        if mods[i] {
+            return None;
+        }
+        mods[i] = true;
+    }
+    None
+}
+
+impl Chord {
+    /// Parse `"Ctrl+Shift+F3"`-style text (case-insensitive, spaces ignored).
+    ///
+    /// `None` if the text is malformed or names a chord that would stop
+    /// ordinary typing: anything but a function key or Shift+Esc needs Ctrl
+    /// or Alt.  Esc alone stays with dialogs and the editor.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let ([ctrl, shift, alt, win], key) = parse_parts(text)?;
+        let chord = Chord {
+            ctrl,
+            shift,
+            alt,
+            key,
+        };
+        let shift_esc = shift && key == VK_ESCAPE;
+        (!win && (ctrl || alt || shift_esc || is_function_key(key))).then_some(chord)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+        ] {
+            if on {
+                f.write_str(name)?;
+            }
+        }
+        if is_function_key(self.key) {
+            return write!(f, "F{}", self.key - VK_F1 + 1);
+        }
+        match KEY_NAMES.iter().find(|&&(_, vk)| vk == self.key) {
+            Some((name, _)) => f.write_str(name),
+            None => write!(f, "{}", char::from(self.key as u8)),
+        }
+    }
+}
+
+// ── System-wide hot keys ──────────────────────────────────────────────────────
+
+/// A chord registered with the system (`RegisterHotKey`) rather than an
+/// accelerator table, so it may use the Windows key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct HotKey {
+    pub(crate) win: bool,
+    pub(crate) chord: Chord,
+}
+
+impl HotKey {
+    /// Parse `"Win+Shift+N"`-style text.  `None` if malformed or without
+    /// Ctrl, Alt, or Win, which would take a key away from every program.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let ([ctrl, shift, alt, win], key) = parse_parts(text)?;
+        let chord = Chord {
+            ctrl,
+            shift,
+            alt,
+            key,
+        };
+        (ctrl || alt || win).then_some(Self { win, chord })
+    }
+}
+
+impl fmt::Display for HotKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.win {
+            f.write_str("Win+")?;
+        }
+        self.chord.fmt(f)
+    }
+}
+
+// ── Bindings ──────────────────────────────────────────────────────────────────
+
+/// The chord bound to each command, parallel to the registry slice.
+pub(crate) type Bindings = Vec<Option<Chord>>;
+
+/// The built-in chord of every command.
+pub(crate) fn defaults(commands: &[Command]) -> Bindings {
+    commands.iter().map(|c| Chord::parse(c.default)).collect()
+}
+
+/// Merge a `keymap.json` map over the defaults.  An empty string unbinds the
+/// command; unknown names and unparsable chords are ignored.
+fn resolve(commands: &[Command], map: &BTreeMap<String, String>) -> Bindings {
+    commands
+        .iter()
+        .map(|c| match map.get(c.name) {
+            Some(text) if text.trim().is_empty() => None,
+            Some(text) => Chord::parse(text).or_else(|| Chord::parse(c.default)),
+            None => Chord::parse(c.default),
+        })
+        .collect()
+}
+
+/// Indices of the other commands bound to the same chord as command `i`.
+pub(crate) fn conflicts_with(bindings: &[Option<Chord>], i: usize) -> Vec<usize> {
+    let Some(chord) = bindings[i] else {
+        return Vec::new();
+    };
+    bindings
+        .iter()
+        .enumerate()
+        .filter(|&(j, b)| j != i && *b == Some(chord))
+        .map(|(j, _)| j)
+        .collect()
+}
+
+// ── On-disk format ────────────────────────────────────────────────────────────
+
+/// Root of the JSON keymap file.
+#[derive(Serialize, Deserialize)]
+struct KeymapFile {
+    version: u32,
+    /// Command name → chord text (`""` = unbound).
+    #[serde(default)]
+    bindings: BTreeMap<String, String>,
+}
+
+const KEYMAP_VERSION: u32 = 1;
+
+/// Return the path to the keymap file: `%APPDATA%\Rivet\keymap.json`.
+///
+/// Returns `None` if the `APPDATA` environment variable is not set.
+pub(crate) fn keymap_path() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("keymap.json");
+    Some(p)
+}
+
+/// Write every command's chord to `keymap.json`, so the file documents the
+/// full set and can be edited by hand.
+pub(crate) fn save(commands: &[Command], bindings: &[Option<Chord>]) -> io::Result<()> {
+    let path =
+        keymap_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file = KeymapFile {
+        version: KEYMAP_VERSION,
+        bindings: commands
+            .iter()
+            .zip(bindings)
+            .map(|(c, b)| {
+                (
+                    c.name.to_owned(),
+                    b.map(|b| b.to_string()).unwrap_or_default(),
+                )
+            })
+            .collect(),
+    };
+    let out = fs::File::create(&path)?;
+    serde_json::to_writer_pretty(out, &file).map_err(io::Error::other)
+}
+
+/// Read `keymap.json` merged over the defaults.
+///
+/// Returns the defaults on any error: file missing, JSON parse failure, or an
+/// unrecognised version number.
+pub(crate) fn load(commands: &[Command]) -> Bindings {
+    keymap_path()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice::<KeymapFile>(&data).ok())
+        .filter(|f| f.version == KEYMAP_VERSION)
+        .map(|f| resolve(commands, &f.bindings))
+        .unwrap_or_else(|| defaults(commands))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMANDS: &[Command] = &[
+        Command {
+            id: 1,
+            name: "file.new",
+            label: "File: New",
+            default: "Ctrl+N",
+        },
+        Command {
+            id: 2,
+            name: "search.next",
+            label: "Search: Find Next",
+            default: "F3",
+        },
+        Command {
+            id: 3,
+            name: "view.wrap",
+            label: "View: Word Wrap",
+            default: "",
+        },
+    ];
+
+    #[test]
+    fn parses_and_displays_canonically() {
+        let c = Chord::parse("shift + ctrl + i").unwrap();
+        assert!(c.ctrl && c.shift && !c.alt);
+        assert_eq!(c.key, u16::from(b'I'));
+        assert_eq!(c.to_string(), "Ctrl+Shift+I");
+        assert_eq!(
+            Chord::parse("Alt+PageDown").unwrap().to_string(),
+            "Alt+PgDn"
+        );
+        assert_eq!(Chord::parse("f12").unwrap().to_string(), "F12");
+        assert_eq!(
+            Chord::parse("Ctrl+Return").unwrap().to_string(),
+            "Ctrl+Enter"
+        );
+        assert_eq!(Chord::parse("ctrl+/").unwrap().key, 0xBF);
+        assert_eq!(
+            Chord::parse("Ctrl+Shift+/").unwrap().to_string(),
+            "Ctrl+Shift+/"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_and_typing_keys() {
+        for bad in [
+            "",
+            "Ctrl+",
+            "Ctrl+Ctrl+A",
+            "Hyper+A",
+            "Ctrl+F25",
+            "Ctrl+AB",
+            "A",
+            "Shift+A",
+            "Esc",
+        ] {
+            assert_eq!(Chord::parse(bad), None, "{bad:?}");
+        }
+        assert_eq!(
+            Chord::parse("shift+escape").unwrap().to_string(),
+            "Shift+Esc"
+        );
+    }
+
+    #[test]
+    fn hot_keys_allow_the_windows_key() {
+        let h = HotKey::parse("win+shift+n").unwrap();
+        assert!(h.win && h.chord.shift && !h.chord.ctrl);
+        assert_eq!(h.to_string(), "Win+Shift+N");
+        assert_eq!(Chord::parse("Win+Ctrl+N"), None);
+        assert_eq!(HotKey::parse("Shift+N"), None);
+        assert_eq!(HotKey::parse("Win+Win+N"), None);
+        assert_eq!(
+            HotKey::parse("Ctrl+Alt+Space").unwrap().to_string(),
+            "Ctrl+Alt+Space"
+        );
+    }
+
+    #[test]
+    fn file_overrides_defaults() {
+        let mut map = BTreeMap::new();
+        map.insert("file.new".to_owned(), String::new());
+        map.insert("view.wrap".to_owned(), "Alt+Z".to_owned());
+        map.insert("search.next".to_owned(), "nonsense".to_owned());
+        map.insert("no.such".to_owned(), "Ctrl+Q".to_owned());
+        let b = resolve(COMMANDS, &map);
+        assert_eq!(b[0], None);
+        assert_eq!(b[1], Chord::parse("F3"));
+        assert_eq!(b[2], Chord::parse("Alt+Z"));
+    }
+
+    #[test]
+    fn finds_conflicts() {
+        let mut b = defaults(COMMANDS);
+        assert!(conflicts_with(&b, 0).is_empty());
+        b[2] = Chord::parse("Ctrl+N");
+        assert_eq!(conflicts_with(&b, 0), [2]);
+        assert_eq!(conflicts_with(&b, 2), [0]);
+        b[2] = None;
+        assert!(conflicts_with(&b, 2).is_empty());
+    }
+}
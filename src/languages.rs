@@ -5,7 +5,6 @@
 
 use std::path::Path;
 
-
 // ── Language enum ─────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +33,31 @@ pub(crate) enum Language {
 }
 
 impl Language {
+    /// Every language, in the order File > Document Properties… lists them.
+    pub(crate) const ALL: [Language; 21] = [
+        Language::PlainText,
+        Language::C,
+        Language::Cpp,
+        Language::Python,
+        Language::Rust,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Html,
+        Language::Xml,
+        Language::Css,
+        Language::Json,
+        Language::Sql,
+        Language::Toml,
+        Language::Ini,
+        Language::Batch,
+        Language::Makefile,
+        Language::Diff,
+        Language::Shell,
+        Language::Markdown,
+        Language::Yaml,
+        Language::PowerShell,
+    ];
+
     /// Null-terminated ASCII name for Lexilla's `CreateLexer()`.
     ///
     /// `PlainText` is never passed to `CreateLexer`; callers use `null` directly.
@@ -89,6 +113,34 @@ impl Language {
             Language::PowerShell => "PowerShell",
         }
     }
+
+    /// Stable lowercase name for files kept per language, e.g.
+    /// `snippets\python.json`.
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            Language::PlainText => "text",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Html => "html",
+            Language::Xml => "xml",
+            Language::Css => "css",
+            Language::Json => "json",
+            Language::Sql => "sql",
+            Language::Toml => "toml",
+            Language::Ini => "ini",
+            Language::Batch => "batch",
+            Language::Makefile => "makefile",
+            Language::Diff => "diff",
+            Language::Shell => "shell",
+            Language::Markdown => "markdown",
+            Language::Yaml => "yaml",
+            Language::PowerShell => "powershell",
+        }
+    }
 }
 
 // ── Language detection ────────────────────────────────────────────────────────
@@ -138,6 +190,47 @@ pub(crate) fn language_from_path(path: &Path) -> Language {
     }
 }
 
+/// Detect the language from a `#!` interpreter line at the start of `text`.
+///
+/// Recognises both `#!/bin/sh` and `#!/usr/bin/env python3` forms; version
+/// suffixes on the interpreter name (`python3.12`) are ignored.  Returns
+/// `None` when `text` has no shebang or names an unknown interpreter.
+pub(crate) fn language_from_shebang(text: &[u8]) -> Option<Language> {
+    let line = text.strip_prefix(b"#!")?;
+    let line = &line[..line
+        .iter()
+        .position(|&b| b == b'\n' || b == b'\r')
+        .unwrap_or(line.len())];
+    let line = std::str::from_utf8(line).ok()?;
+
+    let mut words = line.split_whitespace();
+    let mut interp = words.next()?.rsplit(['/', '\\']).next()?;
+    if interp == "env" {
+        // Skip `env` flags such as `-S`.
+        interp = words.find(|w| !w.starts_with('-'))?;
+    }
+    let name = interp.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    match name {
+        "sh" | "bash" | "zsh" | "ksh" | "ash" | "dash" => Some(Language::Shell),
+        "python" | "pypy" => Some(Language::Python),
+        "node" | "nodejs" => Some(Language::JavaScript),
+        "deno" | "ts-node" => Some(Language::TypeScript),
+        "pwsh" | "powershell" => Some(Language::PowerShell),
+        "make" => Some(Language::Makefile),
+        _ => None,
+    }
+}
+
+/// Detect the language of a document from its path, falling back to the
+/// content (shebang) when the path is missing or says nothing.
+pub(crate) fn detect_language(path: Option<&Path>, text: &[u8]) -> Language {
+    match path.map(language_from_path) {
+        Some(lang) if lang != Language::PlainText => lang,
+        _ => language_from_shebang(text).unwrap_or(Language::PlainText),
+    }
+}
+
 // ── Keyword lists ─────────────────────────────────────────────────────────────
 
 /// Returns `(keyword-set-index, null-terminated ASCII word list)` pairs for the
@@ -157,6 +250,72 @@ pub(crate) fn keywords(lang: Language) -> &'static [(usize, &'static [u8])] {
     }
 }
 
+// ── Comment tokens ────────────────────────────────────────────────────────────
+
+/// A language's comment syntax, for Edit > Toggle Line / Block Comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CommentTokens {
+    /// Line-comment prefix, e.g. `//`.
+    pub(crate) line: Option<&'static [u8]>,
+    /// Block-comment delimiters, e.g. `/*` and `*/`.
+    pub(crate) block: Option<(&'static [u8], &'static [u8])>,
+}
+
+const C_BLOCK: Option<(&[u8], &[u8])> = Some((b"/*", b"*/"));
+const XML_BLOCK: Option<(&[u8], &[u8])> = Some((b"<!--", b"-->"));
+
+/// The comment tokens of `lang`; both `None` for languages without comments.
+pub(crate) fn comment_tokens(lang: Language) -> CommentTokens {
+    let (line, block): (Option<&'static [u8]>, _) = match lang {
+        Language::C
+        | Language::Cpp
+        | Language::Rust
+        | Language::JavaScript
+        | Language::TypeScript => (Some(b"//"), C_BLOCK),
+        Language::Css => (None, C_BLOCK),
+        Language::Sql => (Some(b"--"), C_BLOCK),
+        Language::Python
+        | Language::Shell
+        | Language::Yaml
+        | Language::Toml
+        | Language::Makefile => (Some(b"#"), None),
+        Language::PowerShell => (Some(b"#"), Some((b"<#", b"#>"))),
+        Language::Ini => (Some(b";"), None),
+        Language::Batch => (Some(b"REM"), None),
+        Language::Html | Language::Xml | Language::Markdown => (None, XML_BLOCK),
+        Language::PlainText | Language::Json | Language::Diff => (None, None),
+    };
+    CommentTokens { line, block }
+}
+
+// ── Auto-close pairs ──────────────────────────────────────────────────────────
+
+const BRACKETS_DQ: &[(u8, u8)] = &[(b'(', b')'), (b'[', b']'), (b'{', b'}'), (b'"', b'"')];
+const BRACKETS_QUOTES: &[(u8, u8)] = &[
+    (b'(', b')'),
+    (b'[', b']'),
+    (b'{', b'}'),
+    (b'"', b'"'),
+    (b'\'', b'\''),
+];
+
+/// `(opener, closer)` pairs that Edit > Auto-Close Brackets completes.
+///
+/// `'` is left out where it is mostly not a quote (apostrophes in prose and
+/// in unquoted Batch/INI/Makefile text, Rust lifetimes).  Diffs get nothing.
+pub(crate) fn auto_close_pairs(lang: Language) -> &'static [(u8, u8)] {
+    match lang {
+        Language::Diff => &[],
+        Language::PlainText
+        | Language::Markdown
+        | Language::Rust
+        | Language::Batch
+        | Language::Ini
+        | Language::Makefile => BRACKETS_DQ,
+        _ => BRACKETS_QUOTES,
+    }
+}
+
 // ── Keyword tables ────────────────────────────────────────────────────────────
 
 static C_KEYWORDS: &[(usize, &[u8])] = &[(
@@ -449,6 +608,73 @@ mod tests {
         assert_eq!(language_from_path(Path::new("index.HTML")), Language::Html);
     }
 
+    // ── Content detection ────────────────────────────────────────────────────
+
+    #[test]
+    fn shebang_direct_interpreter() {
+        assert_eq!(
+            language_from_shebang(b"#!/bin/bash\necho"),
+            Some(Language::Shell)
+        );
+        assert_eq!(language_from_shebang(b"#!/bin/sh"), Some(Language::Shell));
+    }
+
+    #[test]
+    fn shebang_via_env_with_version() {
+        assert_eq!(
+            language_from_shebang(b"#!/usr/bin/env python3.12\r\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            language_from_shebang(b"#!/usr/bin/env -S node --harmony\n"),
+            Some(Language::JavaScript)
+        );
+    }
+
+    #[test]
+    fn shebang_absent_or_unknown() {
+        assert_eq!(language_from_shebang(b"print('hi')"), None);
+        assert_eq!(language_from_shebang(b"#!/usr/bin/env ruby"), None);
+        assert_eq!(language_from_shebang(b""), None);
+    }
+
+    #[test]
+    fn path_wins_over_shebang() {
+        let text = b"#!/usr/bin/env python\n";
+        assert_eq!(
+            detect_language(Some(Path::new("run.sh")), text),
+            Language::Shell
+        );
+        assert_eq!(
+            detect_language(Some(Path::new("run")), text),
+            Language::Python
+        );
+        assert_eq!(detect_language(None, text), Language::Python);
+        assert_eq!(detect_language(None, b"plain"), Language::PlainText);
+    }
+
+    // ── lexer_name ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn lexer_names_are_single_null_terminated_ascii() {
+        // Passed straight to Lexilla's CreateLexer as a C string.
+        for lang in [
+            Language::C,
+            Language::Python,
+            Language::Html,
+            Language::Ini,
+            Language::Diff,
+            Language::PowerShell,
+        ] {
+            let name = lang.lexer_name();
+            let (last, body) = name.split_last().expect("non-empty");
+            assert_eq!(*last, 0, "{lang:?}");
+            assert!(!body.is_empty(), "{lang:?}");
+            assert!(body.iter().all(|b| b.is_ascii_lowercase()), "{lang:?}");
+        }
+        assert_eq!(Language::PlainText.lexer_name(), b"\0");
+    }
+
     // ── display_name ─────────────────────────────────────────────────────────
 
     #[test]
@@ -508,4 +734,38 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn auto_close_pairs_per_language() {
+        let has_quote = |lang| auto_close_pairs(lang).contains(&(b'\'', b'\''));
+        assert!(has_quote(Language::Python));
+        assert!(!has_quote(Language::Rust));
+        assert!(!has_quote(Language::PlainText));
+        assert!(auto_close_pairs(Language::Rust).contains(&(b'{', b'}')));
+        assert!(auto_close_pairs(Language::Diff).is_empty());
+    }
+
+    #[test]
+    fn comment_tokens_per_language() {
+        let rust = comment_tokens(Language::Rust);
+        assert_eq!(rust.line, Some(&b"//"[..]));
+        assert_eq!(rust.block, Some((&b"/*"[..], &b"*/"[..])));
+        assert_eq!(comment_tokens(Language::Python).block, None);
+        assert_eq!(comment_tokens(Language::Html).line, None);
+        assert_eq!(
+            comment_tokens(Language::Json),
+            CommentTokens {
+                line: None,
+                block: None
+            }
+        );
+    }
+
+    #[test]
+    fn all_lists_each_language_once() {
+        let names: std::collections::HashSet<_> =
+            Language::ALL.iter().map(|l| l.display_name()).collect();
+        assert_eq!(names.len(), Language::ALL.len());
+        assert_eq!(Language::ALL[0], Language::PlainText);
+    }
 }
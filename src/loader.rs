@@ -0,0 +1,432 @@
+// ── Background file loading ───────────────────────────────────────────────────
+//
+// Files above the Large File Mode threshold (`large_files.threshold` in the
+// settings) are read on a worker thread in
+// `CHUNK_BYTES` pieces so the UI stays responsive.  The worker sends each chunk
+// over a bounded channel and calls a `notify` callback, which the Win32 layer
+// turns into a posted message; the UI thread then drains the channel, decodes
+// with `StreamDecoder`, and appends to the view.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender, TryRecvError},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use crate::app::{ansi_boundary, AnsiCodec, Encoding};
+
+/// Bytes read per chunk.
+const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// Chunks the worker may read ahead of the UI before it blocks; bounds the
+/// memory held in flight.
+const LOOKAHEAD: usize = 4;
+
+// ── Worker ────────────────────────────────────────────────────────────────────
+
+/// One step of a background load, in file order.
+pub(crate) enum LoadEvent {
+    /// Raw (undecoded) bytes.
+    Chunk(Vec<u8>),
+    /// End of file reached.
+    Done,
+    /// Reading failed; no further events follow.
+    Failed(io::Error),
+}
+
+/// UI-side handle to a running background load.
+///
+/// Dropping the handle cancels the load.
+pub(crate) struct Loader {
+    rx: Receiver<LoadEvent>,
+    cancel: Arc<AtomicBool>,
+    /// The reading thread; taken by `take_worker` at shutdown.
+    worker: Option<JoinHandle<()>>,
+    total: u64,
+    read: u64,
+}
+
+impl Loader {
+    /// Open `path` and start reading it on a worker thread.
+    ///
+    /// `notify` runs on the worker after every event is queued.  Errors
+    /// opening the file are returned here rather than as an event.
+    pub(crate) fn spawn(path: &Path, notify: impl Fn() + Send + 'static) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let total = file.metadata()?.len();
+        let (tx, rx) = mpsc::sync_channel(LOOKAHEAD);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let worker = std::thread::Builder::new()
+            .name("rivet-loader".to_owned())
+            .spawn(move || read_chunks(file, CHUNK_BYTES, &tx, &worker_cancel, &notify))?;
+        Ok(Self {
+            rx,
+            cancel,
+            worker: Some(worker),
+            total,
+            read: 0,
+        })
+    }
+
+    /// Take the next queued event, if any.
+    pub(crate) fn try_next(&mut self) -> Option<LoadEvent> {
+        match self.rx.try_recv() {
+            Ok(event) => {
+                if let LoadEvent::Chunk(bytes) = &event {
+                    self.read += bytes.len() as u64;
+                }
+                Some(event)
+            }
+            Err(TryRecvError::Empty) => None,
+            // The worker always ends with Done or Failed; a vanished sender
+            // without one means it panicked.
+            Err(TryRecvError::Disconnected) => Some(LoadEvent::Failed(io::Error::other(
+                "the loader thread stopped unexpectedly",
+            ))),
+        }
+    }
+
+    /// Percentage of the file received so far (0–100).
+    pub(crate) fn percent(&self) -> u8 {
+        percent(self.read, self.total)
+    }
+
+    /// The reading thread, so shutdown can wait for it after dropping `self`.
+    pub(crate) fn take_worker(&mut self) -> Option<JoinHandle<()>> {
+        self.worker.take()
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// `done` as a percentage of `total`; an empty file counts as complete.
+fn percent(done: u64, total: u64) -> u8 {
+    (done.min(total) * 100)
+        .checked_div(total)
+        .map_or(100, |p| p as u8)
+}
+
+/// Worker body: send `reader` in `chunk`-byte pieces until EOF, an error,
+/// cancellation, or the receiver going away.
+fn read_chunks<R: Read>(
+    mut reader: R,
+    chunk: usize,
+    tx: &SyncSender<LoadEvent>,
+    cancel: &AtomicBool,
+    notify: &dyn Fn(),
+) {
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut buf = Vec::with_capacity(chunk);
+        let event = match (&mut reader).take(chunk as u64).read_to_end(&mut buf) {
+            Ok(0) => LoadEvent::Done,
+            Ok(_) => LoadEvent::Chunk(buf),
+            Err(e) => LoadEvent::Failed(e),
+        };
+        let last = !matches!(event, LoadEvent::Chunk(_));
+        if tx.send(event).is_err() {
+            return;
+        }
+        notify();
+        if last {
+            return;
+        }
+    }
+}
+
+// ── Decoding ──────────────────────────────────────────────────────────────────
+
+/// Incremental counterpart of `App::detect_and_decode`.
+///
+/// The encoding is chosen from the BOM in the first bytes.  Without a BOM the
+/// bytes pass through unchanged while they validate as UTF-8.  From the
+/// first piece that does not, the stream is ANSI and decoded with the
+/// code page; earlier pieces are left as they were, which for an ANSI file
+/// means plain ASCII, as its first non-ASCII byte almost never forms UTF-8.
+pub(crate) struct StreamDecoder {
+    /// `None` until enough bytes have arrived to look for a BOM.
+    encoding: Option<Encoding>,
+    /// Bytes not yet decoded: a partial BOM, an odd UTF-16 byte or the
+    /// unfinished end of an ANSI piece.
+    carry: Vec<u8>,
+    /// A high surrogate waiting for its pair in the next chunk.
+    high_surrogate: Option<u16>,
+    /// An incomplete UTF-8 sequence at the end of the last chunk.  Already
+    /// passed through; kept only to validate it against the next chunk.
+    utf8_tail: Vec<u8>,
+    /// `false` once the stream is known not to be valid UTF-8.
+    valid_utf8: bool,
+    /// Code page for a stream that turns out to be ANSI.
+    ansi: AnsiCodec,
+}
+
+impl StreamDecoder {
+    pub(crate) fn new(ansi: AnsiCodec) -> Self {
+        Self {
+            encoding: None,
+            carry: Vec::new(),
+            high_surrogate: None,
+            utf8_tail: Vec::new(),
+            valid_utf8: true,
+            ansi,
+        }
+    }
+
+    /// A decoder for bytes from the middle of a file already known to be in
+    /// `encoding` (no BOM expected).  A code-page `encoding` is decoded with
+    /// `ansi`, which should be the codec for that code page.
+    pub(crate) fn resume(encoding: Encoding, ansi: AnsiCodec) -> Self {
+        Self {
+            encoding: Some(encoding),
+            valid_utf8: !encoding.is_code_page(),
+            ..Self::new(ansi)
+        }
+    }
+
+    /// Decode the next piece of the file and return its UTF-8 form.
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut input = std::mem::take(&mut self.carry);
+        input.extend_from_slice(bytes);
+        let encoding = match self.encoding {
+            Some(e) => e,
+            None if input.len() < 3 => {
+                self.carry = input;
+                return Vec::new();
+            }
+            None => {
+                let (encoding, bom) = if input.starts_with(&[0xFF, 0xFE]) {
+                    (Encoding::Utf16Le, 2)
+                } else if input.starts_with(&[0xFE, 0xFF]) {
+                    (Encoding::Utf16Be, 2)
+                } else if input.starts_with(&[0xEF, 0xBB, 0xBF]) {
+                    (Encoding::Utf8Bom, 3)
+                } else {
+                    (Encoding::Ansi, 0)
+                };
+                self.encoding = Some(encoding);
+                input.drain(..bom);
+                encoding
+            }
+        };
+        match encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be => self.decode_utf16(input, encoding),
+            Encoding::Utf8 | Encoding::Utf8Bom => {
+                self.validate_utf8(&input);
+                input
+            }
+            Encoding::Ansi | Encoding::CodePage(_) => {
+                self.validate_utf8(&input);
+                // Hold back an unfinished UTF-8 sequence (validated again with
+                // the next piece), or what could be the first half of a
+                // double-byte character.
+                let keep = if self.valid_utf8 {
+                    input.len() - std::mem::take(&mut self.utf8_tail).len()
+                } else {
+                    ansi_boundary(&input)
+                };
+                self.carry = input.split_off(keep);
+                if self.valid_utf8 {
+                    input
+                } else {
+                    self.ansi.decode(&input)
+                }
+            }
+        }
+    }
+
+    /// Flush held-back bytes and return the detected encoding with the final
+    /// UTF-8 output.
+    pub(crate) fn finish(self) -> (Encoding, Vec<u8>) {
+        match self.encoding {
+            // Fewer than three bytes in the whole file.
+            None => crate::app::App::detect_and_decode(&self.carry, self.ansi),
+            Some(Encoding::Utf16Le | Encoding::Utf16Be) => {
+                // A trailing odd byte is dropped, as in `detect_and_decode`.
+                let tail = match self.high_surrogate {
+                    Some(_) => "\u{FFFD}".as_bytes().to_vec(),
+                    None => Vec::new(),
+                };
+                (self.encoding.unwrap_or(Encoding::Utf8), tail)
+            }
+            Some(Encoding::Ansi) if self.valid_utf8 && self.carry.is_empty() => {
+                (Encoding::Utf8, Vec::new())
+            }
+            Some(encoding @ (Encoding::Ansi | Encoding::CodePage(_))) => {
+                (encoding, self.ansi.decode(&self.carry))
+            }
+            Some(encoding) => (encoding, Vec::new()),
+        }
+    }
+
+    fn decode_utf16(&mut self, mut input: Vec<u8>, encoding: Encoding) -> Vec<u8> {
+        if input.len() % 2 == 1 {
+            self.carry.extend(input.pop());
+        }
+        let mut units: Vec<u16> = self.high_surrogate.take().into_iter().collect();
+        units.extend(input.chunks_exact(2).map(|c| match encoding {
+            Encoding::Utf16Be => u16::from_be_bytes([c[0], c[1]]),
+            _ => u16::from_le_bytes([c[0], c[1]]),
+        }));
+        if units.last().is_some_and(|u| (0xD800..0xDC00).contains(u)) {
+            self.high_surrogate = units.pop();
+        }
+        String::from_utf16_lossy(&units).into_bytes()
+    }
+
+    /// Track whether the stream so far is valid UTF-8.  Only the ANSI / UTF-8
+    /// label depends on this; the bytes themselves are passed through.
+    fn validate_utf8(&mut self, input: &[u8]) {
+        if !self.valid_utf8 {
+            return;
+        }
+        let mut check = std::mem::take(&mut self.utf8_tail);
+        check.extend_from_slice(input);
+        if let Err(e) = std::str::from_utf8(&check) {
+            match e.error_len() {
+                // Truncated sequence at the end: may complete next chunk.
+                None => self.utf8_tail = check[e.valid_up_to()..].to_vec(),
+                Some(_) => self.valid_utf8 = false,
+            }
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode `bytes` split into `size`-byte pieces.
+    fn decode_in_pieces(bytes: &[u8], size: usize) -> (Encoding, Vec<u8>) {
+        let mut d = StreamDecoder::new(AnsiCodec::default());
+        let mut out = Vec::new();
+        for piece in bytes.chunks(size) {
+            out.extend(d.push(piece));
+        }
+        let (encoding, tail) = d.finish();
+        out.extend(tail);
+        (encoding, out)
+    }
+
+    #[test]
+    fn utf8_split_inside_a_character() {
+        let text = "caf\u{e9} \u{1F600} end".as_bytes();
+        for size in 1..text.len() {
+            let (enc, out) = decode_in_pieces(text, size);
+            assert_eq!(enc, Encoding::Utf8, "piece size {size}");
+            assert_eq!(out, text);
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_decoded_as_ansi() {
+        let bytes = b"abc \xE9t\xE9 xyz";
+        for size in [1, 4, 64] {
+            let (enc, out) = decode_in_pieces(bytes, size);
+            assert_eq!(enc, Encoding::Ansi);
+            assert_eq!(out, "abc \u{e9}t\u{e9} xyz".as_bytes(), "piece size {size}");
+        }
+    }
+
+    #[test]
+    fn truncated_utf8_at_eof_is_ansi() {
+        let bytes = b"abcdef\xE2\x82";
+        let (enc, _) = decode_in_pieces(bytes, 3);
+        assert_eq!(enc, Encoding::Ansi);
+    }
+
+    #[test]
+    fn utf8_bom_stripped() {
+        let (enc, out) = decode_in_pieces(b"\xEF\xBB\xBFhello", 2);
+        assert_eq!(enc, Encoding::Utf8Bom);
+        assert_eq!(out, b"hello");
+    }
+
+    #[test]
+    fn utf16le_surrogate_pair_split_across_pieces() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for u in "a\u{1F600}b".encode_utf16() {
+            bytes.extend_from_slice(&u.to_le_bytes());
+        }
+        for size in 1..bytes.len() {
+            let (enc, out) = decode_in_pieces(&bytes, size);
+            assert_eq!(enc, Encoding::Utf16Le, "piece size {size}");
+            assert_eq!(out, "a\u{1F600}b".as_bytes());
+        }
+    }
+
+    #[test]
+    fn utf16be_matches_whole_buffer_decode() {
+        let mut bytes = vec![0xFE, 0xFF];
+        for u in "line1\r\nline2".encode_utf16() {
+            bytes.extend_from_slice(&u.to_be_bytes());
+        }
+        let (enc, out) = decode_in_pieces(&bytes, 5);
+        assert_eq!(
+            (enc, out),
+            crate::app::App::detect_and_decode(&bytes, AnsiCodec::default()),
+            "streamed and whole-buffer decoding must agree"
+        );
+    }
+
+    #[test]
+    fn tiny_file_uses_whole_buffer_detection() {
+        assert_eq!(decode_in_pieces(b"ab", 1), (Encoding::Utf8, b"ab".to_vec()));
+        assert_eq!(decode_in_pieces(b"", 1), (Encoding::Utf8, Vec::new()));
+    }
+
+    #[test]
+    fn read_chunks_sends_pieces_then_done() {
+        let data: Vec<u8> = (0..10u8).collect();
+        let (tx, rx) = mpsc::sync_channel(16);
+        let cancel = AtomicBool::new(false);
+        let notified = std::cell::Cell::new(0);
+        read_chunks(&data[..], 4, &tx, &cancel, &|| {
+            notified.set(notified.get() + 1)
+        });
+        let mut got = Vec::new();
+        let mut done = false;
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                LoadEvent::Chunk(bytes) => got.push(bytes),
+                LoadEvent::Done => done = true,
+                LoadEvent::Failed(e) => panic!("unexpected error: {e}"),
+            }
+        }
+        assert_eq!(got, vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]]);
+        assert!(done);
+        assert_eq!(notified.get(), 4);
+    }
+
+    #[test]
+    fn read_chunks_stops_when_cancelled() {
+        let (tx, rx) = mpsc::sync_channel(16);
+        let cancel = AtomicBool::new(true);
+        read_chunks(&b"data"[..], 2, &tx, &cancel, &|| {});
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn percent_of_total() {
+        assert_eq!(percent(0, 200), 0);
+        assert_eq!(percent(50, 200), 25);
+        assert_eq!(percent(300, 200), 100);
+        assert_eq!(percent(0, 0), 100);
+    }
+}
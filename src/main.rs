@@ -9,13 +9,36 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod associations; // per-user registry layout for Explorer integration
+mod cli; // command-line arguments
+mod commands; // Command enum + window-independent command execution
+mod config; // settings.json — user preferences
+mod crash; // crash reports under %APPDATA%\Rivet\crashes
+mod diff; // line diff for Tools > Compare With…
 mod editor;
 mod error;
+mod export; // RTF / HTML for Edit > Copy Special
+mod file_info; // File Info section of Document Properties
+mod git; // git gutter: changed lines vs HEAD
+#[cfg(test)]
+mod harness; // headless open/edit/save/close flow tests
+mod json; // Tools > Format / Minify JSON
+mod keymap; // keymap.json — keyboard shortcut bindings
 mod languages; // extension → Language + keyword lists
+mod loader; // background chunked reads for Large File Mode
+mod outline; // functions and headings for View > Outline Panel
+mod paths; // UNC, verbatim and over-long path handling
 mod platform;
+mod recent; // recent.json — most-recently-used files
+mod scratch; // scratch.txt behind the hot-key scratch tab
 mod search;
 mod session;
+mod shutdown; // joins background threads on exit
+mod tail; // View > Monitor: follow appends to a file
+mod tasks; // background task pool
 mod theme; // light / dark colour themes
+mod tools; // external tools + placeholder expansion
+mod transform; // Tools > Transform Selection codecs
 mod ui;
 
 fn main() {
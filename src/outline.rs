@@ -0,0 +1,222 @@
+// ── Document outline ──────────────────────────────────────────────────────────
+//
+// The functions, types and headings listed by View > Outline Panel.  Each
+// language has a line scanner that looks only at the start of a line — no
+// parsing — so it stays fast on large files and tolerates code that does not
+// compile.  No `unsafe` — pure safe Rust.
+
+use crate::languages::Language;
+
+/// One outline entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Item {
+    /// 0-based line the entry starts on.
+    pub(crate) line: usize,
+    /// Nesting level: indentation for code, heading level − 1 for Markdown.
+    pub(crate) depth: usize,
+    /// What the panel shows, e.g. `fn main` or `class Parser`.
+    pub(crate) label: String,
+}
+
+/// The outline of `text`, in document order.  Languages without a scanner
+/// have an empty outline.
+pub(crate) fn outline(language: Language, text: &[u8]) -> Vec<Item> {
+    let scan: fn(&str) -> Option<String> = match language {
+        Language::Rust => rust_item,
+        Language::Python => python_item,
+        Language::Markdown => return markdown(text),
+        _ => return Vec::new(),
+    };
+    lines(text)
+        .filter_map(|(line, s)| {
+            let label = scan(s.trim_start())?;
+            Some(Item {
+                line,
+                depth: indent_width(&s) / 4,
+                label,
+            })
+        })
+        .collect()
+}
+
+/// `(line number, text)` for each line, without its EOL.
+fn lines(text: &[u8]) -> impl Iterator<Item = (usize, std::borrow::Cow<'_, str>)> {
+    text.split(|&b| b == b'\n').enumerate().map(|(i, l)| {
+        let l = l.strip_suffix(b"\r").unwrap_or(l);
+        (i, String::from_utf8_lossy(l))
+    })
+}
+
+/// Columns of leading whitespace, with tabs four wide.
+fn indent_width(s: &str) -> usize {
+    s.chars()
+        .map_while(|c| match c {
+            ' ' => Some(1),
+            '\t' => Some(4),
+            _ => None,
+        })
+        .sum()
+}
+
+/// The identifier at the start of `s`, if any.
+fn ident(s: &str) -> Option<&str> {
+    let end = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    (end > 0).then(|| &s[..end])
+}
+
+/// `s` without a leading `word` and the whitespace after it.
+fn keyword<'a>(s: &'a str, word: &str) -> Option<&'a str> {
+    let rest = s.strip_prefix(word)?;
+    let trimmed = rest.trim_start();
+    (trimmed.len() < rest.len()).then_some(trimmed)
+}
+
+/// A Rust item header: `fn`, `impl`, `struct`, `enum`, `trait` or `mod`,
+/// after any visibility and qualifiers.
+fn rust_item(s: &str) -> Option<String> {
+    let mut s = s;
+    if let Some(rest) = s.strip_prefix("pub") {
+        s = match rest.strip_prefix('(') {
+            Some(inner) => inner.split_once(')')?.1,
+            None => rest,
+        }
+        .trim_start();
+    }
+    loop {
+        s = if let Some(rest) = ["const", "async", "unsafe", "default"]
+            .iter()
+            .find_map(|q| keyword(s, q))
+        {
+            rest
+        } else if let Some(rest) = keyword(s, "extern") {
+            // `extern "C" fn`: skip the ABI string.
+            match rest.strip_prefix('"') {
+                Some(abi) => abi.split_once('"')?.1.trim_start(),
+                None => rest,
+            }
+        } else {
+            break;
+        };
+    }
+    if let Some(rest) = s.strip_prefix("impl") {
+        if !rest.starts_with([' ', '<']) {
+            return None;
+        }
+        // The whole header up to the body or where clause.
+        let end = rest.find(['{', ';']).unwrap_or(rest.len());
+        let header = rest[..end].split(" where").next().unwrap_or_default();
+        return Some(format!("impl{}", header.trim_end()));
+    }
+    ["fn", "struct", "enum", "trait", "mod"]
+        .iter()
+        .find_map(|kw| Some(format!("{kw} {}", ident(keyword(s, kw)?)?)))
+}
+
+/// A Python `def`, `async def` or `class`.
+fn python_item(s: &str) -> Option<String> {
+    let s = keyword(s, "async").unwrap_or(s);
+    ["def", "class"]
+        .iter()
+        .find_map(|kw| Some(format!("{kw} {}", ident(keyword(s, kw)?)?)))
+}
+
+/// Markdown ATX headings (`#` to `######`), skipping fenced code blocks.
+fn markdown(text: &[u8]) -> Vec<Item> {
+    let mut fence: Option<&str> = None;
+    let mut items = Vec::new();
+    for (line, s) in lines(text) {
+        let t = s.trim_start();
+        if indent_width(&s) >= 4 {
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| t.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                Some(open) => Some(open),
+                None => Some(marker),
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let level = t.chars().take_while(|&c| c == '#').count();
+        let rest = &t[level..];
+        if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            continue;
+        }
+        // A closing run of `#` is not part of the title.
+        let title = rest.trim().trim_end_matches('#').trim_end();
+        if !title.is_empty() {
+            items.push(Item {
+                line,
+                depth: level - 1,
+                label: title.to_owned(),
+            });
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(language: Language, text: &str) -> Vec<(usize, usize, String)> {
+        outline(language, text.as_bytes())
+            .into_iter()
+            .map(|i| (i.line, i.depth, i.label))
+            .collect()
+    }
+
+    #[test]
+    fn rust_items_with_visibility_and_qualifiers() {
+        let src = "use std::io;\n\
+                   pub(crate) struct Buf;\n\
+                   impl<T: Clone> From<T> for Buf where T: Send {\n    \
+                       pub const unsafe fn new() -> Self {}\n    \
+                       // fn commented()\n\
+                   }\n\
+                   extern \"C\" fn callback() {}\n\
+                   let implicit = 1;\n";
+        assert_eq!(
+            labels(Language::Rust, src),
+            [
+                (1, 0, "struct Buf".to_owned()),
+                (2, 0, "impl<T: Clone> From<T> for Buf".to_owned()),
+                (3, 1, "fn new".to_owned()),
+                (6, 0, "fn callback".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_defs_nest_by_indentation() {
+        let src = "class Parser:\r\n    def parse(self):\r\n        pass\r\nasync def main():\r\n";
+        assert_eq!(
+            labels(Language::Python, src),
+            [
+                (0, 0, "class Parser".to_owned()),
+                (1, 1, "def parse".to_owned()),
+                (3, 0, "def main".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_headings_outside_code_fences() {
+        let src =
+            "# Title\n\ntext\n## Usage ##\n```sh\n# not a heading\n```\n#hashtag\n### Notes\n";
+        assert_eq!(
+            labels(Language::Markdown, src),
+            [
+                (0, 0, "Title".to_owned()),
+                (3, 1, "Usage".to_owned()),
+                (8, 2, "Notes".to_owned()),
+            ]
+        );
+        assert!(outline(Language::Json, b"{\"fn main\": 1}").is_empty());
+    }
+}
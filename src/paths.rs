@@ -0,0 +1,66 @@
+// ── Path normalisation ────────────────────────────────────────────────────────
+//
+// Paths reach the editor from the command line, drag-and-drop, the session
+// file, the folder panel and `GetFinalPathNameByHandleW`, in whatever form
+// the source used: relative, with `.` and `..` in them, on a UNC share, or
+// already verbatim (`\\?\C:\…`).  `normalize` turns each into one absolute,
+// non-verbatim form, so the same file is the same tab however it was reached
+// and titles, recent files and the session show what the user would type.
+//
+// Over-long paths need nothing here: std adds the `\\?\` prefix itself when
+// a path passed to `std::fs` is longer than `MAX_PATH`, and the application
+// manifest declares `longPathAware` for the shell's own calls.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::path::{Path, PathBuf};
+
+/// `path` without a `\\?\` prefix: `\\?\C:\a` becomes `C:\a`, and
+/// `\\?\UNC\server\share` becomes `\\server\share`.
+pub(crate) fn strip_verbatim(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{rest}")
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_owned()
+    }
+}
+
+/// `path` made absolute against the current directory, with `.` and `..`
+/// resolved and any verbatim prefix removed.  Links are not followed.  A
+/// path that cannot be made absolute (it is empty) is returned as it is.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let plain = match path.to_str() {
+        Some(s) => PathBuf::from(strip_verbatim(s)),
+        None => path.to_path_buf(),
+    };
+    std::path::absolute(&plain).unwrap_or(plain)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_verbatim_prefixes() {
+        assert_eq!(strip_verbatim(r"\\?\C:\notes\a.txt"), r"C:\notes\a.txt");
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\server\share\a.txt"),
+            r"\\server\share\a.txt"
+        );
+        assert_eq!(
+            strip_verbatim(r"\\server\share\a.txt"),
+            r"\\server\share\a.txt"
+        );
+        assert_eq!(strip_verbatim(r"C:\a.txt"), r"C:\a.txt");
+    }
+
+    #[test]
+    fn normalize_makes_paths_absolute() {
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(normalize(Path::new("a.txt")), cwd.join("a.txt"));
+        assert!(normalize(Path::new("notes/./a.txt")).is_absolute());
+        assert_eq!(normalize(Path::new("")), PathBuf::new());
+    }
+}
@@ -0,0 +1,107 @@
+// ── Screen-reader notifications ───────────────────────────────────────────────
+//
+// The status bar is a standard control, so MSAA / UI Automation already expose
+// each part as a child whose name is the part's text.  What they do not do is
+// tell anyone when that text changes.  The main window calls in here after
+// updating a part:
+//   • `EVENT_OBJECT_NAMECHANGE` on the part (child ID = part index + 1) for
+//     every change, raised by `set_child_name`, so a reader focused on the
+//     status bar stays current.
+//   • `EVENT_OBJECT_LIVEREGIONCHANGED` on the bar when something worth
+//     speaking unprompted changed: the language / EOL / encoding / INS-OVR
+//     parts, or
+//     the position text written by View > Speak Cursor Position.  Caret moves
+//     alone do not raise it, so typing is not talked over.
+//
+// Where a control's own text reads badly — a tab's `*` dirty marker, a bare
+// `UTF-8` in the status bar, the switcher's owner-drawn rows that have no
+// text at all — `set_child_name` overrides the accessible name through
+// Dynamic Annotation (`IAccPropServices`).  UI Automation picks the
+// annotation up through its MSAA proxy, so both APIs see the same name.
+
+#![allow(unsafe_code)]
+
+use std::cell::OnceCell;
+
+use windows::{
+    core::HSTRING,
+    Win32::{
+        Foundation::HWND,
+        System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+        UI::{
+            Accessibility::{CAccPropServices, IAccPropServices, NotifyWinEvent, PROPID_ACC_NAME},
+            WindowsAndMessaging::{
+                CHILDID_SELF, EVENT_OBJECT_LIVEREGIONCHANGED, EVENT_OBJECT_NAMECHANGE, OBJID_CLIENT,
+            },
+        },
+    },
+};
+
+thread_local! {
+    /// The annotation service, created on first use; `None` if COM could not
+    /// provide it, in which case controls keep their default names.
+    static SERVICES: OnceCell<Option<IAccPropServices>> = const { OnceCell::new() };
+}
+
+/// Run `f` with the UI thread's annotation service, if there is one.
+fn with_services(f: impl FnOnce(&IAccPropServices)) {
+    SERVICES.with(|cell| {
+        let services = cell.get_or_init(|| {
+            // SAFETY: COM was initialised on the UI thread by `jumplist::init`
+            // before any window exists.
+            unsafe { CoCreateInstance(&CAccPropServices, None, CLSCTX_INPROC_SERVER).ok() }
+        });
+        if let Some(services) = services {
+            f(services);
+        }
+    });
+}
+
+/// Give child `child` (1-based, as MSAA numbers the items of a tab control,
+/// status bar or list box) of `hwnd` the accessible name `name`.
+pub(crate) fn set_child_name(hwnd: HWND, child: usize, name: &str) {
+    with_services(|services| {
+        // SAFETY: hwnd is a live control on this thread; the HSTRING outlives
+        // the call, and the service copies the text.
+        let _ = unsafe {
+            services.SetHwndPropStr(
+                hwnd,
+                OBJID_CLIENT.0 as u32,
+                child as u32,
+                PROPID_ACC_NAME,
+                &HSTRING::from(name),
+            )
+        };
+    });
+    // SAFETY: NotifyWinEvent only posts to registered hooks.
+    unsafe { NotifyWinEvent(EVENT_OBJECT_NAMECHANGE, hwnd, OBJID_CLIENT.0, child as i32) };
+}
+
+/// Drop a name set by `set_child_name`, e.g. once the item it named is gone.
+pub(crate) fn clear_child_name(hwnd: HWND, child: usize) {
+    with_services(|services| {
+        // SAFETY: as in `set_child_name`.
+        let _ = unsafe {
+            services.ClearHwndProps(
+                hwnd,
+                OBJID_CLIENT.0 as u32,
+                child as u32,
+                &[PROPID_ACC_NAME],
+            )
+        };
+    });
+}
+
+/// Ask screen readers to announce the status bar's changed content now.
+pub(crate) fn announce(hwnd_status: HWND) {
+    // SAFETY: NotifyWinEvent only posts to registered hooks; hwnd_status is a
+    // valid status bar.
+    unsafe {
+        NotifyWinEvent(
+            EVENT_OBJECT_LIVEREGIONCHANGED,
+            hwnd_status,
+            OBJID_CLIENT.0,
+            CHILDID_SELF as i32,
+        );
+    }
+}
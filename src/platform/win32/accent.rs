@@ -0,0 +1,54 @@
+// ── Windows accent colour ─────────────────────────────────────────────────────
+//
+// Reads the user's accent colour and app light / dark mode (Settings >
+// Personalisation > Colours) from the registry.  Windows broadcasts
+// WM_SETTINGCHANGE when either changes.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::w,
+    Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD},
+};
+
+/// The accent colour as a `COLORREF` (0x00BBGGRR), or `None` if it is not
+/// set (older Windows, or policy-managed machines).
+pub(crate) fn accent_color() -> Option<u32> {
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    // SAFETY: value is a u32 and size matches it, as RRF_RT_REG_DWORD requires;
+    // both strings are static.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\DWM"),
+            w!("AccentColor"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    // Stored as 0xAABBGGRR; drop the alpha byte.
+    status.is_ok().then_some(value & 0x00FF_FFFF)
+}
+
+/// Whether apps are set to the dark mode, or `None` if Windows has no such
+/// setting (before Windows 10 1809).
+pub(crate) fn apps_use_dark_theme() -> Option<bool> {
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    // SAFETY: as in `accent_color`.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+            w!("AppsUseLightTheme"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    status.is_ok().then_some(value == 0)
+}
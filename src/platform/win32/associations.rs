@@ -0,0 +1,245 @@
+// ── File associations dialog ──────────────────────────────────────────────────
+//
+// Modal Tools > File Associations… dialog, and the code that writes (or
+// removes) the registry entries `crate::associations` describes.  Changes
+// are announced with `SHChangeNotify` so Explorer picks them up without a
+// restart.
+
+#![allow(unsafe_code)]
+
+use std::{io, path::Path};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::{
+        Controls::{CheckDlgButton, IsDlgButtonChecked, DLG_BUTTON_CHECK_STATE},
+        Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST},
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetWindowLongPtrW, MessageBoxW, SetWindowLongPtrW,
+            DLGTEMPLATE, GWLP_USERDATA, MB_ICONWARNING, MB_OK, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+use crate::{
+    associations::{self, RegEntry, PROG_ID},
+    platform::win32::{
+        dlg::{
+            get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_BUTTON,
+            BS_AUTOCHECKBOX, IDCANCEL, IDOK, WS_TABSTOP,
+        },
+        registry::{self, UserKey},
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_CONTEXT_MENU: u16 = 100;
+const ID_EXTENSIONS: u16 = 101;
+const ID_UNREGISTER: u16 = 102;
+
+/// `BST_CHECKED` (winuser.h).
+const BST_CHECKED: u32 = 1;
+
+/// What the user asked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AssociationChoice {
+    /// Register exactly this: the context-menu entry if `context_menu`, and
+    /// the listed extensions (anything registered before and not listed is
+    /// removed).
+    Register {
+        context_menu: bool,
+        extensions: Vec<String>,
+    },
+    /// Remove every Rivet entry.
+    Unregister,
+}
+
+/// Working state shared with `assoc_dlg_proc` through `GWLP_USERDATA`.
+struct AssocParams {
+    context_menu: bool,
+    extensions: String,
+    result: Option<AssociationChoice>,
+}
+
+/// Show the dialog seeded with the current registration: whether the
+/// context-menu entry exists and the extensions associated last time.
+pub(crate) fn show_associations_dialog(
+    hwnd_parent: HWND,
+    extensions: &[String],
+) -> Option<AssociationChoice> {
+    let mut t = DlgTemplate::new("File Associations", 245, 108);
+    t.control(
+        ATOM_BUTTON,
+        ID_CONTEXT_MENU,
+        BS_AUTOCHECKBOX | WS_TABSTOP,
+        7,
+        7,
+        231,
+        12,
+        "Add \u{201C}Edit with Rivet\u{201D} to the Explorer &context menu",
+    )
+    .label(
+        7,
+        25,
+        231,
+        9,
+        "&Open these extensions with Rivet (e.g. txt md log):",
+    )
+    .edit(ID_EXTENSIONS, 7, 36, 231, 14)
+    .label(
+        7,
+        55,
+        231,
+        18,
+        "Windows asks to confirm the default app the next time you open \
+         one of these files.",
+    )
+    .button(ID_UNREGISTER, 7, 87, 70, 14, "&Unregister All", false)
+    .button(IDOK, 133, 87, 50, 14, "Register", true)
+    .button(IDCANCEL, 188, 87, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = AssocParams {
+        context_menu: registry::user_key_exists(&associations::context_menu_key()),
+        extensions: extensions
+            .iter()
+            .map(|e| e.trim_start_matches('.'))
+            .collect::<Vec<_>>()
+            .join(" "),
+        result: None,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; assoc_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(assoc_dlg_proc),
+            LPARAM(&mut params as *mut AssocParams as isize),
+        );
+    }
+    params.result
+}
+
+/// Dialog procedure for `show_associations_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn assoc_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const AssocParams);
+            let _ = CheckDlgButton(
+                hwnd,
+                ID_CONTEXT_MENU as i32,
+                DLG_BUTTON_CHECK_STATE(params.context_menu as u32),
+            );
+            set_item_text(hwnd, ID_EXTENSIONS as i32, &params.extensions);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut AssocParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            match (wparam.0 & 0xFFFF) as u16 {
+                IDOK => {
+                    let text = get_item_text(hwnd, ID_EXTENSIONS as i32);
+                    match associations::parse_extensions(&text) {
+                        Ok(extensions) => {
+                            (*ptr).result = Some(AssociationChoice::Register {
+                                context_menu: IsDlgButtonChecked(hwnd, ID_CONTEXT_MENU as i32)
+                                    == BST_CHECKED,
+                                extensions,
+                            });
+                            let _ = EndDialog(hwnd, 1);
+                        }
+                        Err(bad) => {
+                            let msg = format!("\u{201C}{bad}\u{201D} is not a file extension.");
+                            let wide: Vec<u16> =
+                                msg.encode_utf16().chain(std::iter::once(0)).collect();
+                            let _ = MessageBoxW(
+                                hwnd,
+                                windows::core::PCWSTR(wide.as_ptr()),
+                                windows::core::w!("File Associations"),
+                                MB_OK | MB_ICONWARNING,
+                            );
+                        }
+                    }
+                }
+                ID_UNREGISTER => {
+                    (*ptr).result = Some(AssociationChoice::Unregister);
+                    let _ = EndDialog(hwnd, 1);
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
+
+// ── Registry updates ──────────────────────────────────────────────────────────
+
+/// Make the registry match `choice`.  `previous` lists the extensions
+/// registered before, so the ones no longer wanted can be removed.
+pub(crate) fn apply(exe: &Path, choice: &AssociationChoice, previous: &[String]) -> io::Result<()> {
+    let result = match choice {
+        AssociationChoice::Register {
+            context_menu,
+            extensions,
+        } => register(exe, *context_menu, extensions, previous),
+        AssociationChoice::Unregister => unregister(previous),
+    };
+    // SAFETY: SHCNE_ASSOCCHANGED takes no items.
+    unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, None, None) };
+    result
+}
+
+fn register(
+    exe: &Path,
+    context_menu: bool,
+    extensions: &[String],
+    previous: &[String],
+) -> io::Result<()> {
+    for ext in previous.iter().filter(|e| !extensions.contains(e)) {
+        registry::delete_user_value(&associations::open_with_key(ext), PROG_ID)?;
+    }
+    if context_menu {
+        write(&associations::context_menu_entries(exe))?;
+    } else {
+        registry::delete_user_tree(&associations::context_menu_key())?;
+    }
+    if extensions.is_empty() {
+        registry::delete_user_tree(&associations::prog_id_key())
+    } else {
+        write(&associations::association_entries(exe, extensions))
+    }
+}
+
+fn unregister(previous: &[String]) -> io::Result<()> {
+    for ext in previous {
+        registry::delete_user_value(&associations::open_with_key(ext), PROG_ID)?;
+    }
+    registry::delete_user_tree(&associations::context_menu_key())?;
+    registry::delete_user_tree(&associations::prog_id_key())
+}
+
+fn write(entries: &[RegEntry]) -> io::Result<()> {
+    for entry in entries {
+        UserKey::create(&entry.key)?.set_string(entry.name.as_deref(), &entry.data)?;
+    }
+    Ok(())
+}
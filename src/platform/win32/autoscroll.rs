@@ -0,0 +1,260 @@
+// ── Editor mouse scrolling ────────────────────────────────────────────────────
+//
+// Every editor view is subclassed (comctl32 `SetWindowSubclass`) for two
+// scrolling aids Scintilla lacks:
+//
+// * Middle-button autoscroll.  Pressing the middle button marks an origin;
+//   while active, a timer scrolls the view at a speed proportional to the
+//   mouse's distance from it.  A click (press and release in place) starts
+//   it until the next click or key; holding the button and dragging stops
+//   it on release.
+// * Shift+wheel scrolls horizontally, by the system's wheel-scroll chars
+//   per notch, for long unwrapped lines.
+//
+// The state lives in a heap cell passed as the subclass `dwRefData`; it is
+// freed when the view receives `WM_NCDESTROY`.
+
+#![allow(unsafe_code)]
+
+use std::cell::Cell;
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{GetCapture, ReleaseCapture, SetCapture},
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            KillTimer, LoadCursorW, SendMessageW, SetCursor, SetTimer, SystemParametersInfoW,
+            IDC_SIZEALL, SPI_GETWHEELSCROLLCHARS, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+            WM_CAPTURECHANGED, WM_KEYDOWN, WM_LBUTTONDOWN, WM_MBUTTONDOWN, WM_MBUTTONUP,
+            WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_NCDESTROY, WM_RBUTTONDOWN, WM_SETCURSOR, WM_TIMER,
+        },
+    },
+};
+
+use super::tab_drag::point_from_lparam;
+
+/// `uIdSubclass` for `SetWindowSubclass`; any value unique to this proc.
+const SUBCLASS_ID: usize = 1;
+
+/// `nIDEvent` of the autoscroll timer on the view; clear of the small IDs
+/// Scintilla uses for its own timers.
+const TIMER_ID: usize = 0x4153;
+
+/// Milliseconds between autoscroll steps.
+const TICK_MS: u32 = 15;
+
+/// Pixels around the origin in which the mouse does not scroll.
+const DEAD_ZONE: i32 = 8;
+
+/// Pixels past the dead zone per line scrolled each tick.
+const PIXELS_PER_LINE: f32 = 160.0;
+
+/// Pixels past the dead zone per column scrolled each tick.
+const PIXELS_PER_COLUMN: f32 = 40.0;
+
+/// `SCI_LINESCROLL` (Scintilla.h): `WPARAM` = columns, `LPARAM` = lines.
+const SCI_LINESCROLL: u32 = 2168;
+
+/// `MK_SHIFT` / `MK_CONTROL` / `MK_MBUTTON` (winuser.h).
+const MK_SHIFT: usize = 0x0004;
+const MK_CONTROL: usize = 0x0008;
+const MK_MBUTTON: usize = 0x0010;
+
+/// One wheel notch.
+const WHEEL_DELTA: i32 = 120;
+
+/// Per-view scrolling state.
+#[derive(Clone, Copy, Default)]
+struct ScrollState {
+    /// Where the middle button went down, in client coordinates, while
+    /// autoscroll is active.
+    origin: Option<POINT>,
+    /// Latest mouse position.
+    mouse: POINT,
+    /// The mouse left the dead zone with the button held; releasing the
+    /// button ends the scroll.
+    dragged: bool,
+    /// Fractions of a line and a column carried between ticks.
+    carry: (f32, f32),
+    /// Shift+wheel delta short of a whole notch.
+    wheel: i32,
+}
+
+/// Enable autoscroll and Shift+wheel on the editor view `hwnd_sci`.
+///
+/// # Safety
+/// `hwnd_sci` must be a valid Scintilla window owned by the calling thread.
+pub(crate) unsafe fn install(hwnd_sci: HWND) {
+    let state: Box<Cell<ScrollState>> = Box::default();
+    let _ = SetWindowSubclass(
+        hwnd_sci,
+        Some(scroll_subclass_proc),
+        SUBCLASS_ID,
+        Box::into_raw(state) as usize,
+    );
+}
+
+/// Scroll speed for a mouse offset `d` from the origin, in pixels past the
+/// dead zone.
+fn past_dead_zone(d: i32) -> f32 {
+    (d.signum() * (d.abs() - DEAD_ZONE).max(0)) as f32
+}
+
+/// Show the four-way arrow used while autoscrolling.
+///
+/// # Safety
+/// Must be called on the UI thread.
+unsafe fn set_scroll_cursor() {
+    if let Ok(cursor) = LoadCursorW(None, IDC_SIZEALL) {
+        SetCursor(cursor);
+    }
+}
+
+/// End autoscroll on `hwnd`.
+///
+/// # Safety
+/// `hwnd` must be the subclassed view.
+unsafe fn stop(hwnd: HWND, cell: &Cell<ScrollState>) {
+    let mut state = cell.get();
+    state.origin = None;
+    state.carry = (0.0, 0.0);
+    cell.set(state);
+    let _ = KillTimer(hwnd, TIMER_ID);
+    // Releasing sends WM_CAPTURECHANGED, which finds autoscroll already off.
+    if GetCapture() == hwnd {
+        let _ = ReleaseCapture();
+    }
+}
+
+/// One autoscroll step: scroll by the mouse's offset from the origin.
+///
+/// # Safety
+/// `hwnd` must be the subclassed view.
+unsafe fn tick(hwnd: HWND, cell: &Cell<ScrollState>) {
+    let mut state = cell.get();
+    let Some(origin) = state.origin else {
+        return;
+    };
+    let lines = state.carry.0 + past_dead_zone(state.mouse.y - origin.y) / PIXELS_PER_LINE;
+    let columns = state.carry.1 + past_dead_zone(state.mouse.x - origin.x) / PIXELS_PER_COLUMN;
+    state.carry = (lines.fract(), columns.fract());
+    cell.set(state);
+    let (lines, columns) = (lines.trunc() as isize, columns.trunc() as isize);
+    if lines != 0 || columns != 0 {
+        SendMessageW(
+            hwnd,
+            SCI_LINESCROLL,
+            WPARAM(columns as usize),
+            LPARAM(lines),
+        );
+    }
+}
+
+/// Scroll horizontally for a Shift+wheel message.
+///
+/// # Safety
+/// `hwnd` must be the subclassed view.
+unsafe fn shift_wheel(hwnd: HWND, cell: &Cell<ScrollState>, wparam: WPARAM) {
+    let mut state = cell.get();
+    state.wheel += ((wparam.0 >> 16) & 0xFFFF) as i16 as i32;
+    let notches = state.wheel / WHEEL_DELTA;
+    state.wheel %= WHEEL_DELTA;
+    cell.set(state);
+    if notches == 0 {
+        return;
+    }
+    let mut chars: u32 = 3;
+    let _ = SystemParametersInfoW(
+        SPI_GETWHEELSCROLLCHARS,
+        0,
+        Some(&mut chars as *mut u32 as *mut _),
+        SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    );
+    // Wheel away from the user (positive delta) scrolls left.
+    let columns = -(notches as isize) * chars as isize;
+    SendMessageW(hwnd, SCI_LINESCROLL, WPARAM(columns as usize), LPARAM(0));
+}
+
+/// Subclass procedure installed by [`install`].
+///
+/// # Safety
+/// Called by comctl32 with `ref_data` set to the pointer passed in `install`.
+unsafe extern "system" fn scroll_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    let cell = &*(ref_data as *const Cell<ScrollState>);
+    let active = cell.get().origin.is_some();
+    match msg {
+        WM_MBUTTONDOWN if active => {
+            stop(hwnd, cell);
+            LRESULT(0)
+        }
+        WM_MBUTTONDOWN => {
+            let pt = point_from_lparam(lparam);
+            cell.set(ScrollState {
+                origin: Some(pt),
+                mouse: pt,
+                ..cell.get()
+            });
+            SetCapture(hwnd);
+            SetTimer(hwnd, TIMER_ID, TICK_MS, None);
+            set_scroll_cursor();
+            LRESULT(0)
+        }
+        WM_MOUSEMOVE if active => {
+            let mut state = cell.get();
+            state.mouse = point_from_lparam(lparam);
+            if let Some(origin) = state.origin {
+                let away = (state.mouse.x - origin.x).abs() > DEAD_ZONE
+                    || (state.mouse.y - origin.y).abs() > DEAD_ZONE;
+                state.dragged |= away && wparam.0 & MK_MBUTTON != 0;
+            }
+            cell.set(state);
+            LRESULT(0)
+        }
+        WM_MBUTTONUP if active => {
+            if cell.get().dragged {
+                stop(hwnd, cell);
+            }
+            LRESULT(0)
+        }
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_KEYDOWN if active => {
+            stop(hwnd, cell);
+            LRESULT(0)
+        }
+        WM_TIMER if wparam.0 == TIMER_ID => {
+            tick(hwnd, cell);
+            LRESULT(0)
+        }
+        WM_SETCURSOR if active => {
+            set_scroll_cursor();
+            LRESULT(1)
+        }
+        WM_CAPTURECHANGED => {
+            // Capture taken by another window (Alt+Tab, a dialog) — stop.
+            if active && HWND(lparam.0 as *mut _) != hwnd {
+                stop(hwnd, cell);
+            }
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        WM_MOUSEWHEEL if wparam.0 & (MK_SHIFT | MK_CONTROL) == MK_SHIFT => {
+            shift_wheel(hwnd, cell, wparam);
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let _ = KillTimer(hwnd, TIMER_ID);
+            let _ = RemoveWindowSubclass(hwnd, Some(scroll_subclass_proc), SUBCLASS_ID);
+            // SAFETY: ref_data came from Box::into_raw in `install`; the
+            // subclass is removed so it is never dereferenced again.
+            drop(Box::from_raw(ref_data as *mut Cell<ScrollState>));
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
@@ -0,0 +1,114 @@
+// ── Worker → UI message bridge ────────────────────────────────────────────────
+//
+// A background job hands its result (or a progress update) to the UI thread
+// as a closure: `UiSender::send` boxes it and posts `WM_TASK` to the window
+// with the box in LPARAM, and the window procedure runs it with the window's
+// state (`receive`).  Feature code only ever sees the closure; the pointer
+// lives in this file.
+//
+// Ownership of the box:
+//   • `send` leaks it into the LPARAM of a successful `PostMessageW`; if the
+//     post fails (the window is gone, or its queue is full) `send` takes it
+//     back and drops it on the worker.
+//   • Each posted `WM_TASK` is taken back exactly once: by `receive`, which
+//     the window procedure calls for every `WM_TASK` whether or not it still
+//     has state to run it with, or by `discard_pending` while the window is
+//     being destroyed, which drops what is still queued unrun.
+//   • Only a post that lands between `discard_pending` and the window's
+//     final destruction is lost, and then leaked rather than freed twice or
+//     run against freed state.
+//
+// The closure must be `Send` (it crosses to the UI thread) but runs there
+// alone, so it may freely use the window's state.  Workers should post
+// progress only when it changes, as every message is a queue entry.
+
+#![allow(unsafe_code)]
+
+use std::marker::PhantomData;
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::WindowsAndMessaging::{PeekMessageW, PostMessageW, MSG, PM_REMOVE, WM_APP},
+};
+
+/// Posted to the window with a boxed [`UiMessage`] in LPARAM.
+pub(crate) const WM_TASK: u32 = WM_APP + 11;
+
+/// Work for the UI thread: runs with the window handle and its state `S`.
+pub(crate) type UiMessage<S> = Box<dyn FnOnce(HWND, &mut S) + Send>;
+
+/// Posts [`UiMessage`]s to one window from any thread.  Cheap to clone; a
+/// job may send any number of messages, which run in order.
+pub(crate) struct UiSender<S> {
+    /// The window handle as an integer: `HWND` is not `Send`.
+    hwnd: isize,
+    _state: PhantomData<fn(&mut S)>,
+}
+
+impl<S> Clone for UiSender<S> {
+    fn clone(&self) -> Self {
+        Self {
+            hwnd: self.hwnd,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<S: 'static> UiSender<S> {
+    /// A sender to `hwnd`, whose window procedure passes every `WM_TASK` to
+    /// [`receive`] with state of type `S`.
+    pub(crate) fn new(hwnd: HWND) -> Self {
+        Self {
+            hwnd: hwnd.0 as isize,
+            _state: PhantomData,
+        }
+    }
+
+    /// Run `f` on the UI thread.  Returns `false`, dropping `f` unrun, if it
+    /// could not be posted (the window has been destroyed).
+    pub(crate) fn send(&self, f: impl FnOnce(HWND, &mut S) + Send + 'static) -> bool {
+        // Box twice: the outer box is a thin pointer that fits in LPARAM.
+        let message: Box<UiMessage<S>> = Box::new(Box::new(f));
+        let raw = Box::into_raw(message);
+        // SAFETY: PostMessageW may be called from any thread.  On success
+        // the message owns `raw` until `receive` or `discard_pending` takes
+        // it back; on failure nothing else has seen it, so it is reclaimed
+        // here.
+        unsafe {
+            let posted = PostMessageW(
+                HWND(self.hwnd as *mut _),
+                WM_TASK,
+                WPARAM(0),
+                LPARAM(raw as isize),
+            );
+            if posted.is_err() {
+                drop(Box::from_raw(raw));
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Take back the message posted with a `WM_TASK`.
+///
+/// # Safety
+/// `lparam` must be that of a `WM_TASK` posted by a `UiSender<S>` with the
+/// same `S`, and each such message must be passed here (or drained by
+/// `discard_pending`) exactly once.
+pub(crate) unsafe fn receive<S: 'static>(lparam: LPARAM) -> UiMessage<S> {
+    *Box::from_raw(lparam.0 as *mut UiMessage<S>)
+}
+
+/// Drop every `WM_TASK` still queued for `hwnd` without running it.  Call
+/// while the window is being destroyed, before its state is freed.
+///
+/// # Safety
+/// Every `WM_TASK` queued for `hwnd` must come from a `UiSender<S>`; see
+/// [`receive`].
+pub(crate) unsafe fn discard_pending<S: 'static>(hwnd: HWND) {
+    let mut msg = MSG::default();
+    while PeekMessageW(&mut msg, hwnd, WM_TASK, WM_TASK, PM_REMOVE).as_bool() {
+        drop(receive::<S>(msg.lParam));
+    }
+}
@@ -0,0 +1,239 @@
+// ── Caret settings dialog ─────────────────────────────────────────────────────
+//
+// Modal "Caret" dialog: Line / Block shape, width and blink-period edits,
+// plus the movement options (smart Home, scroll margin, virtual space) and
+// whether Insert switches to overtype.
+// Works on a copy of the settings; the caller only sees the result if the user
+// presses OK with valid numbers.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::{
+        Controls::{CheckDlgButton, CheckRadioButton, IsDlgButtonChecked, DLG_BUTTON_CHECK_STATE},
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetWindowLongPtrW, SetWindowLongPtrW, DLGTEMPLATE,
+            GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+use crate::{
+    config::{CaretSettings, CaretStyle},
+    platform::win32::dlg::{
+        get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_BUTTON, BS_AUTOCHECKBOX,
+        BS_AUTORADIOBUTTON, IDCANCEL, IDOK, WS_GROUP, WS_TABSTOP,
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_LINE: u16 = 100;
+const ID_BLOCK: u16 = 101;
+const ID_WIDTH: u16 = 102;
+const ID_BLINK: u16 = 103;
+const ID_MARGIN: u16 = 104;
+const ID_SMART_HOME: u16 = 105;
+const ID_VIRTUAL_SPACE: u16 = 106;
+const ID_INSERT_KEY: u16 = 107;
+
+/// `BST_CHECKED` (winuser.h).
+const BST_CHECKED: u32 = 1;
+
+/// Working state shared with `caret_dlg_proc` through `GWLP_USERDATA`.
+struct CaretParams {
+    caret: CaretSettings,
+    confirmed: bool,
+}
+
+/// Show the modal caret dialog seeded with `caret`.
+///
+/// Returns the new settings if the user pressed OK, `None` on Cancel.
+pub(crate) fn show_caret_dialog(hwnd_parent: HWND, caret: CaretSettings) -> Option<CaretSettings> {
+    let width_label = format!("&Width (1\u{2013}{} px):", CaretSettings::MAX_WIDTH);
+    let margin_label = format!(
+        "Scroll &margin (0\u{2013}{} lines):",
+        CaretSettings::MAX_SCROLL_MARGIN
+    );
+    let mut t = DlgTemplate::new("Caret", 201, 147);
+    t.label(7, 9, 50, 9, "Shape:")
+        .control(
+            ATOM_BUTTON,
+            ID_LINE,
+            BS_AUTORADIOBUTTON | WS_GROUP | WS_TABSTOP,
+            60,
+            7,
+            50,
+            12,
+            "&Line",
+        )
+        .control(
+            ATOM_BUTTON,
+            ID_BLOCK,
+            BS_AUTORADIOBUTTON,
+            115,
+            7,
+            50,
+            12,
+            "&Block",
+        )
+        .label(7, 27, 110, 9, &width_label)
+        .edit(ID_WIDTH, 120, 25, 40, 14)
+        .label(7, 45, 110, 9, "Blink &period (ms, 0 = steady):")
+        .edit(ID_BLINK, 120, 43, 40, 14)
+        .label(7, 63, 110, 9, &margin_label)
+        .edit(ID_MARGIN, 120, 61, 40, 14)
+        .control(
+            ATOM_BUTTON,
+            ID_SMART_HOME,
+            BS_AUTOCHECKBOX | WS_TABSTOP,
+            7,
+            80,
+            187,
+            12,
+            "Smart &Home (first non-blank character, then column 0)",
+        )
+        .control(
+            ATOM_BUTTON,
+            ID_VIRTUAL_SPACE,
+            BS_AUTOCHECKBOX | WS_TABSTOP,
+            7,
+            94,
+            187,
+            12,
+            "&Virtual space (caret may go past the end of a line)",
+        )
+        .control(
+            ATOM_BUTTON,
+            ID_INSERT_KEY,
+            BS_AUTOCHECKBOX | WS_TABSTOP,
+            7,
+            108,
+            187,
+            12,
+            "&Insert key switches between insert and overtype",
+        )
+        .button(IDOK, 89, 126, 50, 14, "OK", true)
+        .button(IDCANCEL, 144, 126, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = CaretParams {
+        caret,
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; caret_dlg_proc is a valid
+    // DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(caret_dlg_proc),
+            LPARAM(&mut params as *mut CaretParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.caret)
+}
+
+/// Read the controls; `None` (with a beep) if a number is missing or out of
+/// range.
+///
+/// # Safety
+/// `hwnd` must be the live caret dialog.
+unsafe fn read_fields(hwnd: HWND) -> Option<CaretSettings> {
+    let style = if IsDlgButtonChecked(hwnd, ID_BLOCK as i32) == BST_CHECKED {
+        CaretStyle::Block
+    } else {
+        CaretStyle::Line
+    };
+    let width = get_item_text(hwnd, ID_WIDTH as i32).trim().parse::<u32>();
+    let blink_ms = get_item_text(hwnd, ID_BLINK as i32).trim().parse::<u32>();
+    let margin = get_item_text(hwnd, ID_MARGIN as i32).trim().parse::<u32>();
+    match (width, blink_ms, margin) {
+        (Ok(width), Ok(blink_ms), Ok(scroll_margin))
+            if (1..=CaretSettings::MAX_WIDTH).contains(&width)
+                && blink_ms <= CaretSettings::MAX_BLINK_MS
+                && scroll_margin <= CaretSettings::MAX_SCROLL_MARGIN =>
+        {
+            Some(CaretSettings {
+                style,
+                width,
+                blink_ms,
+                smart_home: IsDlgButtonChecked(hwnd, ID_SMART_HOME as i32) == BST_CHECKED,
+                scroll_margin,
+                virtual_space: IsDlgButtonChecked(hwnd, ID_VIRTUAL_SPACE as i32) == BST_CHECKED,
+                insert_key: IsDlgButtonChecked(hwnd, ID_INSERT_KEY as i32) == BST_CHECKED,
+            })
+        }
+        _ => {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+            None
+        }
+    }
+}
+
+/// Dialog procedure for `show_caret_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn caret_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let caret = (*(lparam.0 as *const CaretParams)).caret.clamped();
+            let checked = match caret.style {
+                CaretStyle::Line => ID_LINE,
+                CaretStyle::Block => ID_BLOCK,
+            };
+            let _ = CheckRadioButton(hwnd, ID_LINE as i32, ID_BLOCK as i32, checked as i32);
+            set_item_text(hwnd, ID_WIDTH as i32, &caret.width.to_string());
+            set_item_text(hwnd, ID_BLINK as i32, &caret.blink_ms.to_string());
+            set_item_text(hwnd, ID_MARGIN as i32, &caret.scroll_margin.to_string());
+            let _ = CheckDlgButton(
+                hwnd,
+                ID_SMART_HOME as i32,
+                DLG_BUTTON_CHECK_STATE(caret.smart_home as u32),
+            );
+            let _ = CheckDlgButton(
+                hwnd,
+                ID_VIRTUAL_SPACE as i32,
+                DLG_BUTTON_CHECK_STATE(caret.virtual_space as u32),
+            );
+            let _ = CheckDlgButton(
+                hwnd,
+                ID_INSERT_KEY as i32,
+                DLG_BUTTON_CHECK_STATE(caret.insert_key as u32),
+            );
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut CaretParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            match (wparam.0 & 0xFFFF) as u16 {
+                IDOK => {
+                    if let Some(caret) = read_fields(hwnd) {
+                        (*ptr).caret = caret;
+                        (*ptr).confirmed = true;
+                        let _ = EndDialog(hwnd, 1);
+                    }
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
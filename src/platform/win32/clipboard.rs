@@ -0,0 +1,213 @@
+// ── System clipboard ──────────────────────────────────────────────────────────
+//
+// Plain-text (`CF_UNICODETEXT`) access to the Windows clipboard for features
+// that produce or consume text outside a Scintilla view (Copy Full Path,
+// search results, paste transforms), optionally alongside rich formats
+// (Copy Special).  Scintilla's own Cut/Copy/Paste keep
+// using SCI_CUT / SCI_COPY / SCI_PASTE.
+//
+// The main window also listens for clipboard changes (`WM_CLIPBOARDUPDATE`)
+// so Edit > Paste and its toolbar button gray out while there is no text.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{GlobalFree, HANDLE, HGLOBAL, HWND},
+        System::{
+            DataExchange::{
+                AddClipboardFormatListener, CloseClipboard, EmptyClipboard, GetClipboardData,
+                IsClipboardFormatAvailable, OpenClipboard, RegisterClipboardFormatW,
+                RemoveClipboardFormatListener, SetClipboardData,
+            },
+            Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+        },
+    },
+};
+
+use crate::error::{Result, RivetError};
+
+/// `CF_UNICODETEXT` (winuser.h): null-terminated UTF-16 text.
+const CF_UNICODETEXT: u32 = 13;
+
+/// How many times to retry `OpenClipboard` while another process holds it.
+const OPEN_ATTEMPTS: u32 = 5;
+/// Pause between `OpenClipboard` attempts.
+const OPEN_RETRY_MS: u64 = 10;
+
+/// Open the clipboard, retrying briefly if another process has it open.
+fn open(owner: HWND) -> Result<()> {
+    let mut last = None;
+    for _ in 0..OPEN_ATTEMPTS {
+        // SAFETY: owner is a window handle (or null); OpenClipboard has no
+        // other preconditions.
+        match unsafe { OpenClipboard(owner) } {
+            Ok(()) => return Ok(()),
+            Err(e) => last = Some(e),
+        }
+        std::thread::sleep(std::time::Duration::from_millis(OPEN_RETRY_MS));
+    }
+    Err(RivetError::Win32 {
+        function: "OpenClipboard",
+        code: last.map_or(0, |e| e.code().0 as u32),
+    })
+}
+
+/// Closes the clipboard when dropped so every early return releases it.
+struct OpenGuard;
+
+impl Drop for OpenGuard {
+    fn drop(&mut self) {
+        // SAFETY: only constructed after a successful `open`.
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// Whether the clipboard holds text.
+pub(crate) fn has_text() -> bool {
+    // SAFETY: IsClipboardFormatAvailable only queries; no open required.
+    unsafe { IsClipboardFormatAvailable(CF_UNICODETEXT) }.is_ok()
+}
+
+/// Start (`on`) or stop sending `hwnd` a `WM_CLIPBOARDUPDATE` whenever the
+/// clipboard's content changes.
+pub(crate) fn listen(hwnd: HWND, on: bool) {
+    // SAFETY: hwnd is a live top-level window of this thread; removing a
+    // window that is not listening fails harmlessly.
+    unsafe {
+        let _ = if on {
+            AddClipboardFormatListener(hwnd)
+        } else {
+            RemoveClipboardFormatListener(hwnd)
+        };
+    }
+}
+
+/// Return the clipboard's text, or `None` if it holds no text or cannot be
+/// opened.
+#[allow(dead_code)]
+pub(crate) fn get_text(owner: HWND) -> Option<String> {
+    if !has_text() {
+        return None;
+    }
+    open(owner).ok()?;
+    let _guard = OpenGuard;
+
+    // SAFETY: the clipboard is open; the returned handle is owned by the
+    // clipboard and stays valid until CloseClipboard.
+    let handle = unsafe { GetClipboardData(CF_UNICODETEXT) }.ok()?;
+    let hglobal = HGLOBAL(handle.0);
+    // SAFETY: hglobal is a valid global memory handle from the clipboard.
+    let ptr = unsafe { GlobalLock(hglobal) } as *const u16;
+    if ptr.is_null() {
+        return None;
+    }
+    // The terminator is normally present, but never read past the block.
+    // SAFETY: the locked block is GlobalSize bytes long.
+    let max_units = unsafe { GlobalSize(hglobal) } / 2;
+    // SAFETY: ptr points to max_units readable u16 values while locked.
+    let units = unsafe { std::slice::from_raw_parts(ptr, max_units) };
+    let len = units.iter().position(|&u| u == 0).unwrap_or(max_units);
+    let text = String::from_utf16_lossy(&units[..len]);
+    // SAFETY: balanced with the GlobalLock above.
+    unsafe {
+        let _ = GlobalUnlock(hglobal);
+    }
+    Some(text)
+}
+
+/// Copy `bytes` into a new moveable global block, the form the clipboard
+/// takes ownership of.
+fn global_copy(bytes: &[u8]) -> Result<HGLOBAL> {
+    // SAFETY: GMEM_MOVEABLE is the allocation mode the clipboard requires.
+    let hglobal =
+        unsafe { GlobalAlloc(GMEM_MOVEABLE, bytes.len()) }.map_err(|e| RivetError::Win32 {
+            function: "GlobalAlloc",
+            code: e.code().0 as u32,
+        })?;
+    // SAFETY: hglobal was just allocated with room for `bytes.len()` bytes.
+    unsafe {
+        let dst = GlobalLock(hglobal) as *mut u8;
+        if dst.is_null() {
+            let _ = GlobalFree(hglobal);
+            return Err(RivetError::Win32 {
+                function: "GlobalLock",
+                code: 0,
+            });
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+        let _ = GlobalUnlock(hglobal);
+    }
+    Ok(hglobal)
+}
+
+/// Replace the clipboard contents with `text`.
+///
+/// `owner` becomes the clipboard owner; it must be a real window, because
+/// `SetClipboardData` fails after `EmptyClipboard` with a null owner.
+pub(crate) fn set_text(owner: HWND, text: &str) -> Result<()> {
+    set_text_with(owner, text, &[])
+}
+
+/// Replace the clipboard contents with `text` plus the same content in
+/// registered formats such as `"Rich Text Format"` or `"HTML Format"`.
+/// Each `(format name, data)` is stored with a null terminator added.
+pub(crate) fn set_text_with(owner: HWND, text: &str, formats: &[(&str, &[u8])]) -> Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let bytes: Vec<u8> = wide.iter().flat_map(|u| u.to_le_bytes()).collect();
+    let mut blocks = vec![(CF_UNICODETEXT, global_copy(&bytes)?)];
+    for (name, data) in formats {
+        let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        // SAFETY: name is null-terminated UTF-16 that outlives the call.
+        let format = unsafe { RegisterClipboardFormatW(PCWSTR(name.as_ptr())) };
+        let data: Vec<u8> = data.iter().copied().chain(std::iter::once(0)).collect();
+        match global_copy(&data) {
+            Ok(block) if format != 0 => blocks.push((format, block)),
+            Ok(block) => {
+                // SAFETY: never handed to the clipboard; we still own it.
+                unsafe {
+                    let _ = GlobalFree(block);
+                }
+            }
+            Err(e) => {
+                free_all(&blocks);
+                return Err(e);
+            }
+        }
+    }
+
+    if let Err(e) = open(owner) {
+        free_all(&blocks);
+        return Err(e);
+    }
+    let _guard = OpenGuard;
+
+    // SAFETY: the clipboard is open by us.  On success the system takes
+    // ownership of each block; on failure we still own it and free it.
+    unsafe {
+        let _ = EmptyClipboard();
+        for (i, &(format, block)) in blocks.iter().enumerate() {
+            if let Err(e) = SetClipboardData(format, HANDLE(block.0)) {
+                free_all(&blocks[i..]);
+                return Err(RivetError::Win32 {
+                    function: "SetClipboardData",
+                    code: e.code().0 as u32,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Free blocks the clipboard never took ownership of.
+fn free_all(blocks: &[(u32, HGLOBAL)]) {
+    for &(_, block) in blocks {
+        // SAFETY: each block came from `global_copy` and is still ours.
+        unsafe {
+            let _ = GlobalFree(block);
+        }
+    }
+}
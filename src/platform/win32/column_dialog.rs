@@ -0,0 +1,266 @@
+// ── Column editor dialog ──────────────────────────────────────────────────────
+//
+// Modal "Column Editor" dialog for Edit > Column Editor…: insert either a
+// fixed text or a number sequence (initial number, step, leading zeros,
+// decimal / hex) into each line of a rectangular selection.  Returns what to
+// insert; `editor::column` works out the edits.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::{
+        Controls::{CheckDlgButton, CheckRadioButton, IsDlgButtonChecked, DLG_BUTTON_CHECK_STATE},
+        Input::KeyboardAndMouse::EnableWindow,
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SetWindowLongPtrW,
+            DLGTEMPLATE, GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+use crate::{
+    editor::column::{Fill, Sequence},
+    platform::win32::dlg::{
+        get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_BUTTON, ATOM_EDIT,
+        BS_AUTOCHECKBOX, BS_AUTORADIOBUTTON, BS_DEFPUSHBUTTON, ES_AUTOHSCROLL, IDCANCEL, IDOK,
+        WS_BORDER, WS_GROUP, WS_TABSTOP,
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_TEXT_MODE: u16 = 100;
+const ID_NUMBER_MODE: u16 = 101;
+const ID_TEXT: u16 = 102;
+const ID_START: u16 = 103;
+const ID_STEP: u16 = 104;
+const ID_LEADING_ZEROS: u16 = 105;
+const ID_DEC: u16 = 106;
+const ID_HEX: u16 = 107;
+
+/// The controls that only apply to a number sequence.
+const NUMBER_FIELDS: [u16; 5] = [ID_START, ID_STEP, ID_LEADING_ZEROS, ID_DEC, ID_HEX];
+
+/// `BST_CHECKED` (winuser.h).
+const BST_CHECKED: u32 = 1;
+
+/// Working state shared with `column_dlg_proc` through `GWLP_USERDATA`.
+struct ColumnParams {
+    fill: Fill,
+    confirmed: bool,
+}
+
+/// Show the modal column editor seeded with `fill` (the last one used).
+///
+/// Returns what to insert if the user pressed OK, `None` on Cancel.
+pub(crate) fn show_column_dialog(hwnd_parent: HWND, fill: Fill) -> Option<Fill> {
+    let mut t = DlgTemplate::new("Column Editor", 201, 149);
+    t.control(
+        ATOM_BUTTON,
+        ID_TEXT_MODE,
+        BS_AUTORADIOBUTTON | WS_GROUP | WS_TABSTOP,
+        7,
+        8,
+        50,
+        12,
+        "&Text:",
+    )
+    .control(
+        ATOM_BUTTON,
+        ID_NUMBER_MODE,
+        BS_AUTORADIOBUTTON,
+        7,
+        28,
+        100,
+        12,
+        "&Number sequence:",
+    )
+    // WS_GROUP ends the mode radio group.
+    .control(
+        ATOM_EDIT,
+        ID_TEXT,
+        WS_BORDER | WS_TABSTOP | ES_AUTOHSCROLL | WS_GROUP,
+        60,
+        7,
+        134,
+        14,
+        "",
+    )
+    .label(19, 47, 90, 9, "&Initial number:")
+    .edit(ID_START, 110, 45, 60, 14)
+    .label(19, 65, 90, 9, "Increase &by:")
+    .edit(ID_STEP, 110, 63, 60, 14)
+    .control(
+        ATOM_BUTTON,
+        ID_LEADING_ZEROS,
+        BS_AUTOCHECKBOX | WS_TABSTOP,
+        19,
+        81,
+        150,
+        12,
+        "&Leading zeros",
+    )
+    .label(19, 99, 90, 9, "Format:")
+    .control(
+        ATOM_BUTTON,
+        ID_DEC,
+        BS_AUTORADIOBUTTON | WS_GROUP | WS_TABSTOP,
+        110,
+        97,
+        40,
+        12,
+        "&Dec",
+    )
+    .control(
+        ATOM_BUTTON,
+        ID_HEX,
+        BS_AUTORADIOBUTTON,
+        152,
+        97,
+        40,
+        12,
+        "He&x",
+    )
+    // WS_GROUP ends the format radio group.
+    .control(
+        ATOM_BUTTON,
+        IDOK,
+        BS_DEFPUSHBUTTON | WS_GROUP | WS_TABSTOP,
+        89,
+        128,
+        50,
+        14,
+        "OK",
+    )
+    .button(IDCANCEL, 144, 128, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = ColumnParams {
+        fill,
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; column_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(column_dlg_proc),
+            LPARAM(&mut params as *mut ColumnParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.fill)
+}
+
+/// Enable the number fields when the number mode is chosen, the text box
+/// otherwise.
+///
+/// # Safety
+/// `hwnd` must be the live column dialog.
+unsafe fn enable_fields(hwnd: HWND) {
+    let numbers = IsDlgButtonChecked(hwnd, ID_NUMBER_MODE as i32) == BST_CHECKED;
+    if let Ok(edit) = GetDlgItem(hwnd, ID_TEXT as i32) {
+        let _ = EnableWindow(edit, !numbers);
+    }
+    for id in NUMBER_FIELDS {
+        if let Ok(control) = GetDlgItem(hwnd, id as i32) {
+            let _ = EnableWindow(control, numbers);
+        }
+    }
+}
+
+/// Read the controls; `None` (with a beep) if the text is empty or a number
+/// does not parse.
+///
+/// # Safety
+/// `hwnd` must be the live column dialog.
+unsafe fn read_fields(hwnd: HWND) -> Option<Fill> {
+    let fill = if IsDlgButtonChecked(hwnd, ID_NUMBER_MODE as i32) == BST_CHECKED {
+        let start = get_item_text(hwnd, ID_START as i32).trim().parse::<i64>();
+        let step = get_item_text(hwnd, ID_STEP as i32).trim().parse::<i64>();
+        match (start, step) {
+            (Ok(start), Ok(step)) => Some(Fill::Numbers(Sequence {
+                start,
+                step,
+                leading_zeros: IsDlgButtonChecked(hwnd, ID_LEADING_ZEROS as i32) == BST_CHECKED,
+                hex: IsDlgButtonChecked(hwnd, ID_HEX as i32) == BST_CHECKED,
+            })),
+            _ => None,
+        }
+    } else {
+        let text = get_item_text(hwnd, ID_TEXT as i32);
+        (!text.is_empty()).then_some(Fill::Text(text))
+    };
+    if fill.is_none() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+    }
+    fill
+}
+
+/// Dialog procedure for `show_column_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn column_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let fill = &(*(lparam.0 as *const ColumnParams)).fill;
+            let (mode, seq) = match fill {
+                Fill::Text(text) => {
+                    set_item_text(hwnd, ID_TEXT as i32, text);
+                    (ID_TEXT_MODE, Sequence::default())
+                }
+                Fill::Numbers(seq) => (ID_NUMBER_MODE, *seq),
+            };
+            let _ = CheckRadioButton(
+                hwnd,
+                ID_TEXT_MODE as i32,
+                ID_NUMBER_MODE as i32,
+                mode as i32,
+            );
+            set_item_text(hwnd, ID_START as i32, &seq.start.to_string());
+            set_item_text(hwnd, ID_STEP as i32, &seq.step.to_string());
+            let _ = CheckDlgButton(
+                hwnd,
+                ID_LEADING_ZEROS as i32,
+                DLG_BUTTON_CHECK_STATE(seq.leading_zeros as u32),
+            );
+            let base = if seq.hex { ID_HEX } else { ID_DEC };
+            let _ = CheckRadioButton(hwnd, ID_DEC as i32, ID_HEX as i32, base as i32);
+            enable_fields(hwnd);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ColumnParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            match (wparam.0 & 0xFFFF) as u16 {
+                ID_TEXT_MODE | ID_NUMBER_MODE => enable_fields(hwnd),
+                IDOK => {
+                    if let Some(fill) = read_fields(hwnd) {
+                        (*ptr).fill = fill;
+                        (*ptr).confirmed = true;
+                        let _ = EndDialog(hwnd, 1);
+                    }
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
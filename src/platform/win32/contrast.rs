@@ -0,0 +1,57 @@
+// ── High Contrast mode ────────────────────────────────────────────────────────
+//
+// Whether Windows is in High Contrast mode, and the system colours to draw
+// with while it is.  Windows broadcasts WM_SETTINGCHANGE when the mode is
+// switched and WM_SYSCOLORCHANGE when the colours change.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Graphics::Gdi::{
+        GetSysColor, COLOR_BTNFACE, COLOR_BTNTEXT, COLOR_GRAYTEXT, COLOR_HIGHLIGHT,
+        COLOR_HIGHLIGHTTEXT, COLOR_WINDOW, COLOR_WINDOWTEXT, SYS_COLOR_INDEX,
+    },
+    UI::{
+        Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW},
+        WindowsAndMessaging::{
+            SystemParametersInfoW, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        },
+    },
+};
+
+use crate::theme::SystemColours;
+
+/// Whether High Contrast mode is on.
+pub(crate) fn is_on() -> bool {
+    let mut hc = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: hc is a HIGHCONTRASTW with cbSize set, as SPI_GETHIGHCONTRAST
+    // requires; uiParam must be its size.
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            hc.cbSize,
+            Some(&mut hc as *mut HIGHCONTRASTW as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    };
+    ok.is_ok() && hc.dwFlags.0 & HCF_HIGHCONTRASTON.0 != 0
+}
+
+/// The current system colours.
+pub(crate) fn system_colours() -> SystemColours {
+    // SAFETY: GetSysColor only reads a colour; every index is a valid
+    // COLOR_* constant.
+    let get = |index: SYS_COLOR_INDEX| unsafe { GetSysColor(index) };
+    SystemColours {
+        window: get(COLOR_WINDOW),
+        window_text: get(COLOR_WINDOWTEXT),
+        highlight: get(COLOR_HIGHLIGHT),
+        highlight_text: get(COLOR_HIGHLIGHTTEXT),
+        gray_text: get(COLOR_GRAYTEXT),
+        button_face: get(COLOR_BTNFACE),
+        button_text: get(COLOR_BTNTEXT),
+    }
+}
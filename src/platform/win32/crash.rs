@@ -0,0 +1,108 @@
+// ── Crash handler ─────────────────────────────────────────────────────────────
+//
+// A panic hook and an unhandled-exception filter that write a report and a
+// minidump to `%APPDATA%\Rivet\crashes\` (see `crate::crash`).  Both run in
+// whatever state the crash left the process in, so they only touch the file
+// system: no window, no `WindowState`.  The next start finds the pending
+// report and offers to restore the last checkpoint's unsaved text.
+
+#![allow(unsafe_code)]
+
+use std::{fs::File, os::windows::io::AsRawHandle, path::Path, time::SystemTime};
+
+use windows::Win32::{
+    Foundation::{HANDLE, NTSTATUS},
+    System::{
+        Diagnostics::Debug::{
+            MiniDumpWithThreadInfo, MiniDumpWriteDump, SetUnhandledExceptionFilter,
+            EXCEPTION_POINTERS, MINIDUMP_EXCEPTION_INFORMATION,
+        },
+        Threading::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId},
+    },
+};
+
+/// `EXCEPTION_CONTINUE_SEARCH`: let Windows Error Reporting run as usual.
+const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+/// Install the panic hook and the unhandled-exception filter.  Call once,
+/// first thing in `run()`.
+pub(crate) fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("(non-string payload)");
+        let location = info.location().map(ToString::to_string);
+        write_panic_report(message, location.as_deref());
+        previous(info);
+    }));
+    // SAFETY: `exception_filter` matches LPTOP_LEVEL_EXCEPTION_FILTER and
+    // lives for the whole process.
+    unsafe {
+        SetUnhandledExceptionFilter(Some(exception_filter));
+    }
+}
+
+fn write_panic_report(message: &str, location: Option<&str>) {
+    let Some(dir) = crate::crash::crashes_dir() else {
+        return;
+    };
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let stem = crate::crash::report_stem(SystemTime::now());
+    let _ = std::fs::create_dir_all(&dir);
+    let _ = write_minidump(&dir.join(format!("{stem}.dmp")), None);
+    let text = crate::crash::panic_report(message, location, &backtrace);
+    let _ = crate::crash::write_report(&dir, &stem, &text);
+}
+
+/// Write a minidump of this process to `path`, with the faulting thread's
+/// context when `exception` is given.  Returns whether it was written.
+fn write_minidump(path: &Path, exception: Option<*const EXCEPTION_POINTERS>) -> bool {
+    let Ok(file) = File::create(path) else {
+        return false;
+    };
+    let info = exception.map(|e| MINIDUMP_EXCEPTION_INFORMATION {
+        // SAFETY: GetCurrentThreadId has no preconditions.
+        ThreadId: unsafe { GetCurrentThreadId() },
+        ExceptionPointers: e as *mut _,
+        ClientPointers: false.into(),
+    });
+    // SAFETY: the process pseudo-handle is always valid; `file` is open for
+    // writing until the call returns; `info` outlives the call.
+    unsafe {
+        MiniDumpWriteDump(
+            GetCurrentProcess(),
+            GetCurrentProcessId(),
+            HANDLE(file.as_raw_handle()),
+            MiniDumpWithThreadInfo,
+            info.as_ref().map(|i| i as *const _),
+            None,
+            None,
+        )
+        .is_ok()
+    }
+}
+
+/// Top-level filter for structured exceptions nothing else handled (access
+/// violations, stack overflows in FFI, …).
+unsafe extern "system" fn exception_filter(pointers: *const EXCEPTION_POINTERS) -> i32 {
+    let Some(dir) = crate::crash::crashes_dir() else {
+        return EXCEPTION_CONTINUE_SEARCH;
+    };
+    // SAFETY: Windows passes a valid EXCEPTION_POINTERS whose record pointer
+    // is valid for the duration of the filter.
+    let (code, address) = match pointers.as_ref().and_then(|p| p.ExceptionRecord.as_ref()) {
+        Some(r) => (r.ExceptionCode, r.ExceptionAddress as usize),
+        None => (NTSTATUS(0), 0),
+    };
+    let stem = crate::crash::report_stem(SystemTime::now());
+    let dump = dir.join(format!("{stem}.dmp"));
+    let _ = std::fs::create_dir_all(&dir);
+    let written = write_minidump(&dump, Some(pointers));
+    let text = crate::crash::exception_report(code.0 as u32, address, written.then_some(&*dump));
+    let _ = crate::crash::write_report(&dir, &stem, &text);
+    EXCEPTION_CONTINUE_SEARCH
+}
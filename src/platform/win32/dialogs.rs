@@ -10,16 +10,37 @@
 use std::path::PathBuf;
 
 use windows::{
-    core::PCWSTR,
+    core::{w, PCWSTR},
     Win32::{
-        Foundation::HWND,
-        UI::Controls::Dialogs::{
-            GetOpenFileNameW, GetSaveFileNameW, OFN_FILEMUSTEXIST, OFN_HIDEREADONLY,
-            OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+        Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM},
+        System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_INPROC_SERVER},
+        UI::{
+            Controls::{
+                Dialogs::{
+                    GetOpenFileNameW, GetSaveFileNameW, FINDREPLACEW, FR_MATCHCASE, FR_WHOLEWORD,
+                    OFN_FILEMUSTEXIST, OFN_HIDEREADONLY, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST,
+                    OPENFILENAMEW,
+                },
+                IsDlgButtonChecked,
+            },
+            Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_DOWN, VK_F4, VK_RETURN, VK_UP},
+            Shell::{
+                DefSubclassProc, FileOpenDialog, IFileOpenDialog, RemoveWindowSubclass,
+                SetWindowSubclass, FOS_FORCEFILESYSTEM, FOS_PICKFOLDERS, SIGDN_FILESYSPATH,
+            },
+            WindowsAndMessaging::{
+                AppendMenuW, CreatePopupMenu, DestroyMenu, GetDlgCtrlID, GetDlgItem,
+                GetDlgItemTextW, GetParent, GetWindowLongPtrW, GetWindowRect, PostMessageW,
+                SendMessageW, SetDlgItemTextW, SetWindowLongPtrW, TrackPopupMenu, DLGC_WANTMESSAGE,
+                GWLP_USERDATA, MF_STRING, MSG, TPM_RETURNCMD, WM_APP, WM_CHAR, WM_COMMAND,
+                WM_GETDLGCODE, WM_INITDIALOG, WM_KEYDOWN, WM_NCDESTROY, WM_SYSCHAR, WM_SYSKEYDOWN,
+            },
         },
     },
 };
 
+use crate::{platform::win32::dlg, search::history::History};
+
 // ── Buffer size ───────────────────────────────────────────────────────────────
 
 /// Maximum path length in `WCHAR`s, including the null terminator.
@@ -27,6 +48,35 @@ use windows::{
 /// is the documented maximum for `\\?\` extended paths.
 const PATH_BUF_LEN: usize = 32_768;
 
+// ── Folder dialog ─────────────────────────────────────────────────────────────
+
+/// Show the shell's folder picker.
+///
+/// Returns the chosen folder, or `None` if the user cancelled.
+pub(crate) fn show_folder_dialog(hwnd_owner: HWND) -> Option<PathBuf> {
+    // SAFETY: COM was initialised on the UI thread by `jumplist::init`;
+    // every interface is released when its wrapper drops, and the display
+    // name is freed with CoTaskMemFree as documented.
+    unsafe {
+        let dialog: IFileOpenDialog =
+            CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER).ok()?;
+        let options = dialog.GetOptions().ok()?;
+        dialog
+            .SetOptions(options | FOS_PICKFOLDERS | FOS_FORCEFILESYSTEM)
+            .ok()?;
+        // Cancelling reports an error too.
+        dialog.Show(hwnd_owner).ok()?;
+        let name = dialog
+            .GetResult()
+            .ok()?
+            .GetDisplayName(SIGDN_FILESYSPATH)
+            .ok()?;
+        let path = name.to_string().ok().map(PathBuf::from);
+        CoTaskMemFree(Some(name.0 as *const _));
+        path
+    }
+}
+
 // ── Open dialog ───────────────────────────────────────────────────────────────
 
 /// Show the standard "Open File" dialog.
@@ -101,6 +151,256 @@ pub(crate) fn show_save_dialog(hwnd_owner: HWND, default_name: &str) -> Option<P
     }
 }
 
+// ── Find / Replace dialog hook ────────────────────────────────────────────────
+
+/// Posted to the owner of the Find dialog when its Find All button is
+/// clicked.  The dialog's `FINDREPLACEW` already holds the text and the
+/// Match Case / Whole Word flags.
+pub(crate) const WM_FIND_ALL: u32 = WM_APP + 6;
+
+/// Posted to the owner of the Replace dialog when its Replace in Open
+/// Documents button is clicked; the `FINDREPLACEW` also holds the
+/// replacement.
+pub(crate) const WM_REPLACE_ALL_OPEN: u32 = WM_APP + 7;
+
+/// Posted to the owner of the Find / Replace dialog for Ctrl+Enter (`WPARAM`
+/// 1, add all matches to the selection) or Alt+Enter (0, select all
+/// matches) in the Find field, after its text and options are stored in the
+/// `FINDREPLACEW` as for `WM_FIND_ALL`.
+pub(crate) const WM_SELECT_MATCHES: u32 = WM_APP + 10;
+
+/// Control IDs of the common Find / Replace dialog (dlgs.h).
+const PSH_HELP: u16 = 0x040E;
+const CHK_WHOLE_WORD: i32 = 0x0410;
+const CHK_MATCH_CASE: i32 = 0x0411;
+const EDT_FIND: i32 = 0x0480;
+const EDT_REPLACE: i32 = 0x0481;
+
+/// `BST_CHECKED` (winuser.h).
+const BST_CHECKED: u32 = 1;
+
+/// Hook for `FindTextW` / `ReplaceTextW` opened with `FR_ENABLEHOOK |
+/// FR_SHOWHELP`: the Help button becomes Find All (Find dialog) or Replace
+/// in Open Documents (Replace dialog).  Clicking it copies the dialog's text
+/// and options into the `FINDREPLACEW` (as Find Next would) and posts
+/// `WM_FIND_ALL` or `WM_REPLACE_ALL_OPEN` to the owner instead of the help
+/// message.
+///
+/// A non-zero `lCustData` is a `*const SearchHistory`; the text fields then
+/// step through it (see `history_edit_proc`).
+///
+/// # Safety
+/// Called by the dialog with the `FINDREPLACEW` it was opened with as the
+/// `WM_INITDIALOG` parameter; that struct, and the history it points to,
+/// outlive the dialog.
+pub(crate) unsafe extern "system" fn find_hook(
+    hdlg: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> usize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hdlg, GWLP_USERDATA, lparam.0);
+            let label = if GetDlgItem(hdlg, EDT_REPLACE).is_ok() {
+                w!("In &Open Docs")
+            } else {
+                w!("Find &All")
+            };
+            let _ = SetDlgItemTextW(hdlg, PSH_HELP as i32, label);
+            let fr = &*(lparam.0 as *const FINDREPLACEW);
+            let history = fr.lCustData.0 as *const crate::search::history::SearchHistory;
+            if !history.is_null() {
+                for (id, list) in [
+                    (EDT_FIND, &(*history).find),
+                    (EDT_REPLACE, &(*history).replace),
+                ] {
+                    if let Ok(edit) = GetDlgItem(hdlg, id) {
+                        let _ = SetWindowSubclass(
+                            edit,
+                            Some(history_edit_proc),
+                            HISTORY_SUBCLASS_ID,
+                            list as *const History as usize,
+                        );
+                    }
+                }
+            }
+            // Let the dialog set the initial focus.
+            1
+        }
+        WM_COMMAND if (wparam.0 & 0xFFFF) as u16 == PSH_HELP => {
+            let fr = GetWindowLongPtrW(hdlg, GWLP_USERDATA) as *mut FINDREPLACEW;
+            if fr.is_null() {
+                return 0;
+            }
+            let fr = &mut *fr;
+            let replacing = store_fields(hdlg, fr);
+            let msg = if replacing {
+                WM_REPLACE_ALL_OPEN
+            } else {
+                WM_FIND_ALL
+            };
+            let _ = PostMessageW(fr.hwndOwner, msg, WPARAM(0), LPARAM(0));
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Copy the dialog's text and Match Case / Whole Word options into `fr`, as
+/// Find Next would.  Returns whether it is the Replace dialog.
+///
+/// # Safety
+/// `fr` must be the `FINDREPLACEW` the dialog `hdlg` was opened with.
+unsafe fn store_fields(hdlg: HWND, fr: &mut FINDREPLACEW) -> bool {
+    // SAFETY: lpstrFindWhat points to wFindWhatLen writable units owned by
+    // the caller of FindTextW / ReplaceTextW.
+    let buf = std::slice::from_raw_parts_mut(fr.lpstrFindWhat.0, usize::from(fr.wFindWhatLen));
+    GetDlgItemTextW(hdlg, EDT_FIND, buf);
+    let replacing = GetDlgItem(hdlg, EDT_REPLACE).is_ok();
+    if replacing {
+        // SAFETY: as above, for lpstrReplaceWith / wReplaceWithLen.
+        let buf =
+            std::slice::from_raw_parts_mut(fr.lpstrReplaceWith.0, usize::from(fr.wReplaceWithLen));
+        GetDlgItemTextW(hdlg, EDT_REPLACE, buf);
+    }
+    for (id, flag) in [
+        (CHK_WHOLE_WORD, FR_WHOLEWORD),
+        (CHK_MATCH_CASE, FR_MATCHCASE),
+    ] {
+        if IsDlgButtonChecked(hdlg, id) == BST_CHECKED {
+            fr.Flags |= flag;
+        } else {
+            fr.Flags &= !flag;
+        }
+    }
+    replacing
+}
+
+/// Whether `msg` is Ctrl+Enter (`Some(true)`) or Alt+Enter (`Some(false)`),
+/// including the characters they produce, so the dialog lets the Find
+/// field have them instead of pressing its default button.
+///
+/// # Safety
+/// Called on the thread that owns the keyboard state being read.
+unsafe fn match_selection_key(msg: u32, wparam: WPARAM) -> Option<bool> {
+    const CTRL_ENTER_CHAR: usize = 0x0A;
+    const ENTER_CHAR: usize = 0x0D;
+    match msg {
+        WM_KEYDOWN if wparam.0 as u16 == VK_RETURN.0 && GetKeyState(VK_CONTROL.0.into()) < 0 => {
+            Some(true)
+        }
+        WM_CHAR if wparam.0 == CTRL_ENTER_CHAR => Some(true),
+        WM_SYSKEYDOWN if wparam.0 as u16 == VK_RETURN.0 => Some(false),
+        WM_SYSCHAR if wparam.0 == ENTER_CHAR => Some(false),
+        _ => None,
+    }
+}
+
+/// `uIdSubclass` for the history subclass of the dialog's text fields.
+const HISTORY_SUBCLASS_ID: usize = 1;
+
+/// Subclass of the Find / Replace text fields: Up and Down put the older or
+/// newer history entry in the field; Alt+Down or F4 lists them all in a
+/// menu under it.  `ref_data` is the field's `History`.  In the Find field,
+/// Ctrl+Enter and Alt+Enter post `WM_SELECT_MATCHES` to the dialog's owner.
+///
+/// # Safety
+/// Installed by `find_hook` with a `History` that outlives the dialog.
+unsafe extern "system" fn history_edit_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    let history = &*(ref_data as *const History);
+    let key = wparam.0 as u16;
+    if GetDlgCtrlID(hwnd) == EDT_FIND {
+        if msg == WM_GETDLGCODE {
+            // SAFETY: for WM_GETDLGCODE, a non-zero lParam is the MSG being
+            // checked by IsDialogMessage.
+            let checked = (lparam.0 as *const MSG).as_ref();
+            let code = DefSubclassProc(hwnd, msg, wparam, lparam);
+            return match checked.and_then(|m| match_selection_key(m.message, m.wParam)) {
+                Some(_) => LRESULT(code.0 | DLGC_WANTMESSAGE as isize),
+                None => code,
+            };
+        }
+        match (msg, match_selection_key(msg, wparam)) {
+            (WM_KEYDOWN | WM_SYSKEYDOWN, Some(keep)) => {
+                let hdlg = GetParent(hwnd).unwrap_or_default();
+                let fr = GetWindowLongPtrW(hdlg, GWLP_USERDATA) as *mut FINDREPLACEW;
+                if let Some(fr) = fr.as_mut() {
+                    store_fields(hdlg, fr);
+                    let _ = PostMessageW(
+                        fr.hwndOwner,
+                        WM_SELECT_MATCHES,
+                        WPARAM(usize::from(keep)),
+                        LPARAM(0),
+                    );
+                }
+                return LRESULT(0);
+            }
+            // The characters the keys produce would only beep.
+            (_, Some(_)) => return LRESULT(0),
+            _ => {}
+        }
+    }
+    let pick = match msg {
+        WM_KEYDOWN if key == VK_UP.0 || key == VK_DOWN.0 => {
+            let current =
+                dlg::get_item_text(GetParent(hwnd).unwrap_or_default(), GetDlgCtrlID(hwnd));
+            history.step(&current, key == VK_UP.0).map(str::to_owned)
+        }
+        WM_KEYDOWN if key == VK_F4.0 => history_menu(hwnd, history),
+        WM_SYSKEYDOWN if key == VK_DOWN.0 => history_menu(hwnd, history),
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(history_edit_proc), HISTORY_SUBCLASS_ID);
+            return DefSubclassProc(hwnd, msg, wparam, lparam);
+        }
+        _ => return DefSubclassProc(hwnd, msg, wparam, lparam),
+    };
+    if let Some(text) = pick {
+        dlg::set_item_text(
+            GetParent(hwnd).unwrap_or_default(),
+            GetDlgCtrlID(hwnd),
+            &text,
+        );
+        // Select it all, so typing replaces the recalled entry.
+        const EM_SETSEL: u32 = 0x00B1;
+        let _ = SendMessageW(hwnd, EM_SETSEL, WPARAM(0), LPARAM(-1isize));
+    }
+    LRESULT(0)
+}
+
+/// Show `history` as a menu under the field `edit`; the entry picked.
+///
+/// # Safety
+/// `edit` must be a live window owned by the calling thread.
+unsafe fn history_menu(edit: HWND, history: &History) -> Option<String> {
+    if history.entries().is_empty() {
+        return None;
+    }
+    let menu = CreatePopupMenu().ok()?;
+    for (i, entry) in history.entries().iter().enumerate() {
+        // A lone `&` would become a mnemonic.
+        let label: Vec<u16> = entry
+            .replace('&', "&&")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = AppendMenuW(menu, MF_STRING, i + 1, PCWSTR(label.as_ptr()));
+    }
+    let mut rc = RECT::default();
+    let _ = GetWindowRect(edit, &mut rc);
+    let cmd = TrackPopupMenu(menu, TPM_RETURNCMD, rc.left, rc.bottom, 0, edit, None);
+    let _ = DestroyMenu(menu);
+    let picked = (cmd.0 as usize).checked_sub(1)?;
+    history.entries().get(picked).cloned()
+}
+
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
 /// Convert a null-terminated UTF-16 buffer to a `PathBuf`.
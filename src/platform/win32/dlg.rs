@@ -0,0 +1,334 @@
+// ── In-memory dialog templates ────────────────────────────────────────────────
+//
+// Rivet ships no `.rc` resources, so every modal dialog is described by a
+// `DLGTEMPLATE` byte blob assembled at runtime and passed to
+// `DialogBoxIndirectParamW`.  `DlgTemplate` hides the layout rules (UTF-16
+// strings, DWORD alignment between items, the item count in the header).
+//
+// Also hosts `show_prompt_dialog`, the generic single-line text prompt used by
+// commands that need one value from the user.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+        System::{Diagnostics::Debug::MessageBeep, LibraryLoader::GetModuleHandleW},
+        UI::WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetDlgItemTextW, GetWindowLongPtrW,
+            GetWindowTextLengthW, SendMessageW, SetDlgItemTextW, SetWindowLongPtrW, DLGTEMPLATE,
+            GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+// ── Style bits (u32 to avoid the WINDOW_STYLE newtype in templates) ───────────
+
+pub(crate) const WS_POPUP: u32 = 0x8000_0000;
+pub(crate) const WS_CAPTION: u32 = 0x00C0_0000; // WS_BORDER | WS_DLGFRAME
+pub(crate) const WS_SYSMENU: u32 = 0x0008_0000;
+pub(crate) const WS_CHILD: u32 = 0x4000_0000;
+pub(crate) const WS_VISIBLE: u32 = 0x1000_0000;
+pub(crate) const WS_BORDER: u32 = 0x0080_0000;
+pub(crate) const WS_VSCROLL: u32 = 0x0020_0000;
+pub(crate) const WS_TABSTOP: u32 = 0x0001_0000;
+pub(crate) const WS_GROUP: u32 = 0x0002_0000;
+pub(crate) const DS_MODALFRAME: u32 = 0x0080;
+pub(crate) const DS_CENTER: u32 = 0x0800;
+pub(crate) const ES_AUTOHSCROLL: u32 = 0x0080;
+pub(crate) const BS_DEFPUSHBUTTON: u32 = 0x0001;
+pub(crate) const BS_AUTOCHECKBOX: u32 = 0x0003;
+pub(crate) const BS_AUTORADIOBUTTON: u32 = 0x0009;
+pub(crate) const LBS_NOTIFY: u32 = 0x0001;
+
+/// Style shared by every top-level Rivet dialog.
+pub(crate) const DIALOG_STYLE: u32 = WS_POPUP | WS_CAPTION | WS_SYSMENU | DS_MODALFRAME | DS_CENTER;
+
+// ── Predefined control class atoms ────────────────────────────────────────────
+
+pub(crate) const ATOM_BUTTON: u16 = 0x0080;
+pub(crate) const ATOM_EDIT: u16 = 0x0081;
+pub(crate) const ATOM_STATIC: u16 = 0x0082;
+pub(crate) const ATOM_LISTBOX: u16 = 0x0083;
+
+/// Dialog-manager ID of the default push button.
+pub(crate) const IDOK: u16 = 1;
+/// Dialog-manager ID sent when the user presses Escape or Cancel.
+pub(crate) const IDCANCEL: u16 = 2;
+/// Control ID for items that are never addressed (static labels).
+pub(crate) const ID_UNUSED: u16 = 0xFFFF;
+
+// ── Template builder ──────────────────────────────────────────────────────────
+
+/// Incrementally assembles a `DLGTEMPLATE` followed by `DLGITEMTEMPLATE`s.
+///
+/// All coordinates are in dialog units.  The dialog is centred on its owner
+/// (`DS_CENTER`) so the header position is always `(0, 0)`.
+pub(crate) struct DlgTemplate {
+    buf: Vec<u8>,
+    /// Byte offset of the `cdit` field, patched on every `control` call.
+    count_offset: usize,
+    count: u16,
+}
+
+impl DlgTemplate {
+    /// Start a template for a dialog of `cx × cy` dialog units.
+    pub(crate) fn new(title: &str, cx: u16, cy: u16) -> Self {
+        let mut buf = Vec::with_capacity(512);
+        push_u32(&mut buf, DIALOG_STYLE);
+        push_u32(&mut buf, 0); // dwExtendedStyle
+        let count_offset = buf.len();
+        push_u16(&mut buf, 0); // cdit — patched as controls are added
+        push_u16(&mut buf, 0); // x (DS_CENTER ignores these)
+        push_u16(&mut buf, 0); // y
+        push_u16(&mut buf, cx);
+        push_u16(&mut buf, cy);
+        push_u16(&mut buf, 0); // menu: none
+        push_u16(&mut buf, 0); // window class: default dialog
+        push_wstr(&mut buf, title);
+        Self {
+            buf,
+            count_offset,
+            count: 0,
+        }
+    }
+
+    /// Replace the dialog's own window style (default [`DIALOG_STYLE`]).
+    ///
+    /// Without `DS_CENTER` the dialog opens at `(0, 0)`; position it in
+    /// `WM_INITDIALOG`.
+    pub(crate) fn set_style(&mut self, style: u32) -> &mut Self {
+        self.buf[..4].copy_from_slice(&style.to_le_bytes());
+        self
+    }
+
+    /// Append one control.  `style` is OR-ed with `WS_CHILD | WS_VISIBLE`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn control(
+        &mut self,
+        atom: u16,
+        id: u16,
+        style: u32,
+        x: u16,
+        y: u16,
+        cx: u16,
+        cy: u16,
+        text: &str,
+    ) -> &mut Self {
+        align4(&mut self.buf);
+        push_u32(&mut self.buf, WS_CHILD | WS_VISIBLE | style);
+        push_u32(&mut self.buf, 0); // dwExtendedStyle
+        push_u16(&mut self.buf, x);
+        push_u16(&mut self.buf, y);
+        push_u16(&mut self.buf, cx);
+        push_u16(&mut self.buf, cy);
+        push_u16(&mut self.buf, id);
+        push_u16(&mut self.buf, 0xFFFF); // class is an atom
+        push_u16(&mut self.buf, atom);
+        push_wstr(&mut self.buf, text);
+        push_u16(&mut self.buf, 0); // cbWndExtra
+        self.count += 1;
+        let off = self.count_offset;
+        self.buf[off..off + 2].copy_from_slice(&self.count.to_le_bytes());
+        self
+    }
+
+    /// Static text label.
+    pub(crate) fn label(&mut self, x: u16, y: u16, cx: u16, cy: u16, text: &str) -> &mut Self {
+        self.control(ATOM_STATIC, ID_UNUSED, 0, x, y, cx, cy, text)
+    }
+
+    /// Single-line bordered edit box.
+    pub(crate) fn edit(&mut self, id: u16, x: u16, y: u16, cx: u16, cy: u16) -> &mut Self {
+        self.control(
+            ATOM_EDIT,
+            id,
+            WS_BORDER | WS_TABSTOP | ES_AUTOHSCROLL,
+            x,
+            y,
+            cx,
+            cy,
+            "",
+        )
+    }
+
+    /// Push button; pass `default = true` for the Enter-key button.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn button(
+        &mut self,
+        id: u16,
+        x: u16,
+        y: u16,
+        cx: u16,
+        cy: u16,
+        text: &str,
+        default: bool,
+    ) -> &mut Self {
+        let style = WS_TABSTOP | if default { BS_DEFPUSHBUTTON } else { 0 };
+        self.control(ATOM_BUTTON, id, style, x, y, cx, cy, text)
+    }
+
+    /// Finish the template.  The returned bytes are 4-byte aligned at the
+    /// start because `Vec<u8>` allocations are at least word aligned.
+    pub(crate) fn build(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+// ── Byte helpers ──────────────────────────────────────────────────────────────
+
+#[inline]
+fn push_u16(v: &mut Vec<u8>, n: u16) {
+    v.extend_from_slice(&n.to_le_bytes());
+}
+
+#[inline]
+fn push_u32(v: &mut Vec<u8>, n: u32) {
+    v.extend_from_slice(&n.to_le_bytes());
+}
+
+/// Append a null-terminated UTF-16 string.
+fn push_wstr(v: &mut Vec<u8>, s: &str) {
+    for cu in s.encode_utf16() {
+        push_u16(v, cu);
+    }
+    push_u16(v, 0); // null terminator
+}
+
+/// Pad to the next 4-byte boundary (required between DLGITEMTEMPLATE entries).
+fn align4(v: &mut Vec<u8>) {
+    while v.len() % 4 != 0 {
+        v.push(0);
+    }
+}
+
+// ── Control text helpers ──────────────────────────────────────────────────────
+
+/// Read the full text of dialog control `id`.
+///
+/// # Safety
+/// `hwnd` must be a live dialog window.
+pub(crate) unsafe fn get_item_text(hwnd: HWND, id: i32) -> String {
+    let len = GetDlgItem(hwnd, id)
+        .map(|h| GetWindowTextLengthW(h))
+        .unwrap_or(0)
+        .max(0) as usize;
+    let mut buf = vec![0u16; len + 1];
+    let n = GetDlgItemTextW(hwnd, id, &mut buf) as usize;
+    String::from_utf16_lossy(&buf[..n.min(len)])
+}
+
+/// Replace the text of dialog control `id`.
+///
+/// # Safety
+/// `hwnd` must be a live dialog window.
+pub(crate) unsafe fn set_item_text(hwnd: HWND, id: i32, text: &str) {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let _ = SetDlgItemTextW(hwnd, id, PCWSTR(wide.as_ptr()));
+}
+
+/// The module handle used as `hInstance` for every indirect dialog.
+pub(crate) fn module_instance() -> HINSTANCE {
+    // SAFETY: GetModuleHandleW(None) returns the exe's own module handle.
+    let hmodule = unsafe { GetModuleHandleW(None) }.unwrap_or_default();
+    HINSTANCE(hmodule.0)
+}
+
+// ── Generic prompt dialog ─────────────────────────────────────────────────────
+
+/// Data shared with `prompt_dlg_proc` through `GWLP_USERDATA`.
+struct PromptParams {
+    initial: String,
+    /// Written by the dialog procedure when the user presses OK.
+    result: Option<String>,
+    /// When `true`, OK is refused (with a beep) while the edit box is empty.
+    require_text: bool,
+}
+
+const PROMPT_EDIT_ID: u16 = 100;
+
+/// Show a modal dialog with a label, a single-line edit, and OK / Cancel.
+///
+/// Returns the entered text, or `None` if the user cancelled.  When
+/// `require_text` is set, an empty entry cannot be confirmed.
+pub(crate) fn show_prompt_dialog(
+    hwnd_parent: HWND,
+    title: &str,
+    label: &str,
+    initial: &str,
+    require_text: bool,
+) -> Option<String> {
+    let mut t = DlgTemplate::new(title, 245, 55);
+    t.label(7, 7, 231, 9, label)
+        .edit(PROMPT_EDIT_ID, 7, 18, 231, 14)
+        .button(IDOK, 133, 36, 50, 14, "OK", true)
+        .button(IDCANCEL, 188, 36, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = PromptParams {
+        initial: initial.to_owned(),
+        result: None,
+        require_text,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; prompt_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop (DialogBoxIndirectParamW
+    // blocks until EndDialog).
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(prompt_dlg_proc),
+            LPARAM(&mut params as *mut PromptParams as isize),
+        );
+    }
+    params.result
+}
+
+/// Dialog procedure for `show_prompt_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn prompt_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    const EM_SETSEL: u32 = 0x00B1;
+
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const PromptParams);
+            set_item_text(hwnd, PROMPT_EDIT_ID as i32, &params.initial);
+            if let Ok(edit) = GetDlgItem(hwnd, PROMPT_EDIT_ID as i32) {
+                let _ = SendMessageW(edit, EM_SETSEL, WPARAM(0), LPARAM(-1isize));
+            }
+            1 // TRUE: let Windows focus the first tab stop (the edit)
+        }
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let params = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PromptParams;
+            match id {
+                IDOK if !params.is_null() => {
+                    let text = get_item_text(hwnd, PROMPT_EDIT_ID as i32);
+                    if (*params).require_text && text.trim().is_empty() {
+                        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+                    } else {
+                        (*params).result = Some(text);
+                        let _ = EndDialog(hwnd, 1);
+                    }
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,102 @@
+// ── Document map input ────────────────────────────────────────────────────────
+//
+// The document map is a Scintilla view showing the active document at the
+// smallest zoom.  It must never take focus or edit the (shared) document, so
+// its mouse input is subclassed (comctl32 `SetWindowSubclass`): a left-button
+// press captures the mouse and every press or drag sends
+// `WM_DOCUMENT_MAP_SCROLL` to the parent, which scrolls the editor.  Other
+// clicks and the wheel are swallowed; the map follows the editor instead.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{GetCapture, ReleaseCapture, SetCapture},
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            GetParent, LoadCursorW, SendMessageW, SetCursor, IDC_ARROW, WM_APP, WM_CONTEXTMENU,
+            WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP,
+            WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_NCDESTROY, WM_RBUTTONDOWN,
+            WM_RBUTTONUP, WM_SETCURSOR,
+        },
+    },
+};
+
+use super::tab_drag::point_from_lparam;
+
+/// Sent to the map's parent when the user clicks or drags in the map.
+/// `LPARAM` = the mouse's client y-coordinate in the map.
+pub(crate) const WM_DOCUMENT_MAP_SCROLL: u32 = WM_APP + 8;
+
+/// `uIdSubclass` for `SetWindowSubclass`; any value unique to this proc.
+const SUBCLASS_ID: usize = 1;
+
+/// Route `hwnd_map`'s mouse input to its parent as scroll requests.
+///
+/// # Safety
+/// `hwnd_map` must be a valid Scintilla window owned by the calling thread.
+pub(crate) unsafe fn install(hwnd_map: HWND) {
+    let _ = SetWindowSubclass(hwnd_map, Some(map_subclass_proc), SUBCLASS_ID, 0);
+}
+
+/// Subclass procedure installed by [`install`].
+///
+/// # Safety
+/// Called by comctl32 on the UI thread.
+unsafe extern "system" fn map_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    _ref_data: usize,
+) -> LRESULT {
+    match msg {
+        WM_LBUTTONDOWN | WM_LBUTTONDBLCLK => {
+            SetCapture(hwnd);
+            scroll_to(hwnd, lparam);
+            LRESULT(0)
+        }
+        WM_MOUSEMOVE if GetCapture() == hwnd => {
+            scroll_to(hwnd, lparam);
+            LRESULT(0)
+        }
+        WM_LBUTTONUP => {
+            if GetCapture() == hwnd {
+                let _ = ReleaseCapture();
+            }
+            LRESULT(0)
+        }
+        WM_RBUTTONDOWN | WM_RBUTTONUP | WM_MBUTTONDOWN | WM_MBUTTONUP | WM_CONTEXTMENU
+        | WM_MOUSEWHEEL | WM_MOUSEHWHEEL => LRESULT(0),
+        WM_SETCURSOR => {
+            // Scintilla would show the text I-beam.
+            if let Ok(cursor) = LoadCursorW(None, IDC_ARROW) {
+                SetCursor(cursor);
+            }
+            LRESULT(1)
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(map_subclass_proc), SUBCLASS_ID);
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Ask the parent to scroll the editor to the map line under the mouse.
+///
+/// # Safety
+/// `hwnd` must be the subclassed map window.
+unsafe fn scroll_to(hwnd: HWND, lparam: LPARAM) {
+    if let Ok(parent) = GetParent(hwnd) {
+        let y = point_from_lparam(lparam).y;
+        let _ = SendMessageW(
+            parent,
+            WM_DOCUMENT_MAP_SCROLL,
+            WPARAM(0),
+            LPARAM(y as isize),
+        );
+    }
+}
@@ -2,9 +2,13 @@
 
 use windows::Win32::{
     Foundation::HWND,
-    UI::HiDpi::{
-        GetDpiForSystem, GetDpiForWindow, SetProcessDpiAwarenessContext,
-        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    Graphics::Gdi::{CreateFontIndirectW, HFONT},
+    UI::{
+        HiDpi::{
+            GetDpiForSystem, GetDpiForWindow, SetProcessDpiAwarenessContext,
+            SystemParametersInfoForDpi, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+        },
+        WindowsAndMessaging::{NONCLIENTMETRICSW, SPI_GETNONCLIENTMETRICS},
     },
 };
 
@@ -45,3 +49,27 @@ pub(crate) fn get_system_dpi() -> u32 {
         v
     }
 }
+
+/// Create the system message font (the one dialogs use) at `dpi`, for the
+/// tab strip and status bar.  The caller owns the font and must
+/// `DeleteObject` it.  `None` if the metrics cannot be read.
+pub(crate) fn create_ui_font(dpi: u32) -> Option<HFONT> {
+    let mut ncm = NONCLIENTMETRICSW {
+        cbSize: std::mem::size_of::<NONCLIENTMETRICSW>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: ncm is a correctly sized NONCLIENTMETRICSW that outlives both
+    // calls; CreateFontIndirectW only reads the LOGFONTW.
+    unsafe {
+        SystemParametersInfoForDpi(
+            SPI_GETNONCLIENTMETRICS.0,
+            ncm.cbSize,
+            Some(&mut ncm as *mut NONCLIENTMETRICSW as *mut core::ffi::c_void),
+            0,
+            dpi,
+        )
+        .ok()?;
+        let font = CreateFontIndirectW(&ncm.lfMessageFont);
+        (!font.is_invalid()).then_some(font)
+    }
+}
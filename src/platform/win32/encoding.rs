@@ -0,0 +1,111 @@
+// ── ANSI code pages ───────────────────────────────────────────────────────────
+//
+// The `AnsiCodec` the app uses for files without a BOM that are not valid
+// UTF-8: `MultiByteToWideChar` / `WideCharToMultiByte` in the configured
+// code page (the system ANSI code page by default), so a Windows-1252 or
+// Shift-JIS file reads as the right characters and saves back byte for byte.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::PCSTR,
+    Win32::Globalization::{
+        GetACP, MultiByteToWideChar, WideCharToMultiByte, MULTI_BYTE_TO_WIDE_CHAR_FLAGS,
+    },
+};
+
+use crate::app::{ansi_boundary, AnsiCodec};
+
+/// Bytes converted per Win32 call; the APIs take `i32` lengths.
+const PIECE: usize = 16 * 1024 * 1024;
+
+/// The codec for `code_page`, or for the system ANSI code page when 0.
+pub(crate) fn codec(code_page: u32) -> AnsiCodec {
+    let code_page = match code_page {
+        // SAFETY: GetACP has no preconditions.
+        0 => unsafe { GetACP() },
+        cp => cp,
+    };
+    AnsiCodec {
+        code_page,
+        to_utf8,
+        from_utf8,
+    }
+}
+
+/// Split `bytes` into pieces of at most about `PIECE` bytes, each ending
+/// where `boundary` allows.
+fn pieces(mut bytes: &[u8], boundary: impl Fn(&[u8]) -> usize) -> Vec<&[u8]> {
+    let mut out = Vec::new();
+    while bytes.len() > PIECE {
+        let cut = match boundary(&bytes[..PIECE]) {
+            0 => PIECE,
+            n => n,
+        };
+        let (head, rest) = bytes.split_at(cut);
+        out.push(head);
+        bytes = rest;
+    }
+    if !bytes.is_empty() {
+        out.push(bytes);
+    }
+    out
+}
+
+/// `bytes` in `code_page` as UTF-8.
+fn to_utf8(code_page: u32, bytes: &[u8]) -> Vec<u8> {
+    let mut wide: Vec<u16> = Vec::new();
+    for piece in pieces(bytes, ansi_boundary) {
+        // SAFETY: `piece` is a valid slice under i32::MAX bytes; the first
+        // call only measures, the second fills a buffer of that length.
+        unsafe {
+            let flags = MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0);
+            let len = MultiByteToWideChar(code_page, flags, piece, None);
+            let Ok(len) = usize::try_from(len) else {
+                continue;
+            };
+            let start = wide.len();
+            wide.resize(start + len, 0);
+            let written = MultiByteToWideChar(code_page, flags, piece, Some(&mut wide[start..]));
+            wide.truncate(start + usize::try_from(written).unwrap_or(0));
+        }
+    }
+    String::from_utf16_lossy(&wide).into_bytes()
+}
+
+/// UTF-8 `utf8` in `code_page`; characters it lacks become its default
+/// character.
+fn from_utf8(code_page: u32, utf8: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(utf8);
+    let mut out = Vec::new();
+    let char_boundary = |b: &[u8]| {
+        // Back up over continuation bytes to the start of a character.
+        (1..b.len())
+            .rev()
+            .find(|&i| b[i] & 0xC0 != 0x80)
+            .unwrap_or(0)
+    };
+    for piece in pieces(text.as_bytes(), char_boundary) {
+        let wide: Vec<u16> = String::from_utf8_lossy(piece).encode_utf16().collect();
+        // SAFETY: `wide` is a valid slice; the first call only measures, the
+        // second fills a buffer of that length.
+        unsafe {
+            let len = WideCharToMultiByte(code_page, 0, &wide, None, PCSTR::null(), None);
+            let Ok(len) = usize::try_from(len) else {
+                continue;
+            };
+            let start = out.len();
+            out.resize(start + len, 0);
+            let written = WideCharToMultiByte(
+                code_page,
+                0,
+                &wide,
+                Some(&mut out[start..]),
+                PCSTR::null(),
+                None,
+            );
+            out.truncate(start + usize::try_from(written).unwrap_or(0));
+        }
+    }
+    out
+}
@@ -0,0 +1,95 @@
+// ── File details for Document Properties ──────────────────────────────────────
+//
+// Reads what `crate::file_info::FileInfo` shows: times, size and attributes
+// from the file's metadata (links followed), the link's own target if the
+// path is a symbolic link, and the final path from
+// `GetFinalPathNameByHandleW` on a handle to the file.  Times are shown in
+// local time as of their own date, so summer-time changes apply.
+
+#![allow(unsafe_code)]
+
+use std::{
+    fs::File,
+    os::windows::{fs::MetadataExt, io::AsRawHandle},
+    path::Path,
+};
+
+use windows::Win32::{
+    Foundation::{FILETIME, HANDLE, MAX_PATH, SYSTEMTIME},
+    Storage::FileSystem::{
+        GetFinalPathNameByHandleW, FILE_NAME_NORMALIZED, GETFINALPATHNAMEBYHANDLE_FLAGS,
+        VOLUME_NAME_DOS,
+    },
+    System::Time::{FileTimeToSystemTime, SystemTimeToTzSpecificLocalTime},
+};
+
+use crate::{
+    file_info::{attribute_names, FileInfo},
+    paths::strip_verbatim,
+};
+
+/// Details of the file at `path`, or `None` if it cannot be read (it was
+/// deleted or moved since it was opened).
+pub(crate) fn read(path: &Path) -> Option<FileInfo> {
+    let meta = std::fs::metadata(path).ok()?;
+    let link_target = std::fs::symlink_metadata(path)
+        .is_ok_and(|m| m.file_type().is_symlink())
+        .then(|| std::fs::read_link(path).ok())
+        .flatten()
+        .map(|target| target.display().to_string());
+    Some(FileInfo {
+        created: local_time(meta.creation_time()),
+        modified: local_time(meta.last_write_time()),
+        size: meta.file_size(),
+        attributes: attribute_names(meta.file_attributes()),
+        link_target,
+        resolved: final_path(path),
+    })
+}
+
+/// A `FILETIME` (100 ns ticks since 1601, UTC) as local
+/// `"YYYY-MM-DD hh:mm:ss"`, or `None` if the file system keeps none.
+fn local_time(ticks: u64) -> Option<String> {
+    if ticks == 0 {
+        return None;
+    }
+    let file_time = FILETIME {
+        dwLowDateTime: ticks as u32,
+        dwHighDateTime: (ticks >> 32) as u32,
+    };
+    let mut utc = SYSTEMTIME::default();
+    let mut local = SYSTEMTIME::default();
+    // SAFETY: both take pointers to live structs of the declared types; no
+    // time-zone argument means the current one.
+    unsafe {
+        FileTimeToSystemTime(&file_time, &mut utc).ok()?;
+        SystemTimeToTzSpecificLocalTime(None, &utc, &mut local).ok()?;
+    }
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        local.wYear, local.wMonth, local.wDay, local.wHour, local.wMinute, local.wSecond
+    ))
+}
+
+/// The path `path` finally resolves to, or `None` if the file cannot be
+/// opened.
+fn final_path(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let handle = HANDLE(file.as_raw_handle());
+    let flags = GETFINALPATHNAMEBYHANDLE_FLAGS(FILE_NAME_NORMALIZED.0 | VOLUME_NAME_DOS.0);
+    let mut buf = vec![0u16; MAX_PATH as usize];
+    loop {
+        // SAFETY: handle is an open file for the duration of the call; buf
+        // is writable for its whole length.  The result is the length
+        // written (without the NUL), or the size needed (with it) if buf is
+        // too small, or 0 on failure.
+        let len = unsafe { GetFinalPathNameByHandleW(handle, &mut buf, flags) } as usize;
+        if len == 0 {
+            return None;
+        }
+        if len < buf.len() {
+            return Some(strip_verbatim(&String::from_utf16_lossy(&buf[..len])));
+        }
+        buf.resize(len, 0);
+    }
+}
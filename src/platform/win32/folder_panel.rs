@@ -0,0 +1,436 @@
+// ── Folder panel ──────────────────────────────────────────────────────────────
+//
+// View > Folder Panel: a `SysTreeView32` docked on the left of the main
+// window listing one folder.  Folders are filled in when first expanded, so
+// opening a large tree costs one directory read per click.  Each item's
+// `lParam` indexes `FolderPanel::nodes`, which holds its path.
+//
+// The tree is a direct child of the main window, so its notifications arrive
+// there as `WM_NOTIFY` and are handed to `FolderPanel::notify`.  That
+// returns what the user asked for (open, new file, rename, …) and the window
+// carries it out, since most of it touches the open tabs.
+
+#![allow(unsafe_code)]
+
+use std::{
+    os::windows::ffi::OsStrExt as _,
+    path::{Path, PathBuf},
+};
+
+use windows::{
+    core::{w, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{COLORREF, HWND, LPARAM, POINT, WPARAM},
+        Graphics::Gdi::ScreenToClient,
+        UI::{
+            Controls::{
+                HTREEITEM, I_ONE_OR_MORE, NMHDR, NMTREEVIEWW, NMTVKEYDOWN, NM_DBLCLK, NM_RCLICK,
+                NM_RETURN, TVE_EXPAND, TVGN_CARET, TVGN_CHILD, TVHITTESTINFO, TVHT_ONITEM,
+                TVIF_CHILDREN, TVIF_PARAM, TVIF_TEXT, TVINSERTSTRUCTW, TVINSERTSTRUCTW_0,
+                TVITEMEXW_CHILDREN, TVITEMW, TVI_LAST, TVI_ROOT, TVM_DELETEITEM, TVM_EXPAND,
+                TVM_GETITEMW, TVM_GETNEXTITEM, TVM_HITTEST, TVM_INSERTITEMW, TVM_SELECTITEM,
+                TVM_SETBKCOLOR, TVM_SETITEMW, TVM_SETTEXTCOLOR, TVN_ITEMEXPANDINGW, TVN_KEYDOWN,
+                TVS_HASBUTTONS, TVS_HASLINES, TVS_LINESATROOT, TVS_SHOWSELALWAYS, WC_TREEVIEWW,
+            },
+            Input::KeyboardAndMouse::{VK_DELETE, VK_F2},
+            Shell::{SHFileOperationW, FOF_ALLOWUNDO, FO_DELETE, SHFILEOPSTRUCTW},
+            WindowsAndMessaging::{
+                AppendMenuW, CreatePopupMenu, CreateWindowExW, DestroyMenu, DestroyWindow,
+                GetCursorPos, SendMessageW, TrackPopupMenu, HMENU, MF_GRAYED, MF_SEPARATOR,
+                MF_STRING, TPM_RETURNCMD, TPM_RIGHTBUTTON, WINDOW_EX_STYLE, WINDOW_STYLE, WS_CHILD,
+                WS_CLIPSIBLINGS, WS_TABSTOP, WS_VISIBLE,
+            },
+        },
+    },
+};
+
+use crate::ui::folder_tree;
+
+/// What the user asked the panel for.  Paths are absolute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PanelCommand {
+    /// Open a file in a tab.
+    Open(PathBuf),
+    /// Create a file in this folder.
+    NewFile(PathBuf),
+    Rename(PathBuf),
+    /// Move to the Recycle Bin.
+    Delete(PathBuf),
+    /// Show in an Explorer window.
+    Reveal(PathBuf),
+}
+
+const ID_NEW_FILE: usize = 1;
+const ID_RENAME: usize = 2;
+const ID_DELETE: usize = 3;
+const ID_REVEAL: usize = 4;
+
+/// One tree item.
+struct Node {
+    path: PathBuf,
+    is_dir: bool,
+    item: HTREEITEM,
+}
+
+/// The tree control and the paths of its items.
+pub(crate) struct FolderPanel {
+    hwnd: HWND,
+    /// Indexed by each item's `lParam`.  Entries of deleted items stay
+    /// behind; they are dropped when the whole tree is refilled.
+    nodes: Vec<Node>,
+}
+
+impl FolderPanel {
+    /// Create the tree inside `parent` listing `root`.
+    pub(crate) fn create(parent: HWND, root: &Path) -> Option<Self> {
+        let style = TVS_HASBUTTONS | TVS_HASLINES | TVS_LINESATROOT | TVS_SHOWSELALWAYS;
+        // SAFETY: WC_TREEVIEWW is registered by InitCommonControlsEx at
+        // startup; parent is the live main window.
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                WC_TREEVIEWW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WS_TABSTOP | WINDOW_STYLE(style),
+                0,
+                0,
+                0,
+                0,
+                parent,
+                HMENU::default(),
+                None,
+                None,
+            )
+        }
+        .ok()?;
+        let mut panel = Self {
+            hwnd,
+            nodes: Vec::new(),
+        };
+        panel.set_root(root);
+        Some(panel)
+    }
+
+    /// The tree control's window handle.
+    pub(crate) fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// The folder listed, if the tree has been filled.
+    pub(crate) fn root(&self) -> Option<&Path> {
+        self.nodes.first().map(|n| n.path.as_path())
+    }
+
+    /// Replace the tree with `root`, expanded one level.
+    pub(crate) fn set_root(&mut self, root: &Path) {
+        // SAFETY: hwnd is the live tree; TVI_ROOT deletes every item.
+        unsafe {
+            let _ = SendMessageW(self.hwnd, TVM_DELETEITEM, WPARAM(0), LPARAM(TVI_ROOT.0));
+        }
+        self.nodes.clear();
+        let name = root
+            .file_name()
+            .map_or_else(|| root.to_string_lossy(), |n| n.to_string_lossy())
+            .into_owned();
+        // Filled here rather than on TVN_ITEMEXPANDINGW: while `create` runs
+        // the window does not yet route the tree's notifications here.
+        let item = self.insert(TVI_ROOT, &name, root, true);
+        self.fill(item, root);
+        self.expand(item);
+    }
+
+    /// Colours for the light or dark theme (`COLORREF` layout).
+    pub(crate) fn set_colours(&self, background: u32, text: u32) {
+        // SAFETY: hwnd is the live tree; both messages take a COLORREF.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                TVM_SETBKCOLOR,
+                WPARAM(0),
+                LPARAM(COLORREF(background).0 as isize),
+            );
+            let _ = SendMessageW(
+                self.hwnd,
+                TVM_SETTEXTCOLOR,
+                WPARAM(0),
+                LPARAM(COLORREF(text).0 as isize),
+            );
+        }
+    }
+
+    /// List `dir` again if it is shown, keeping it expanded.  Used after
+    /// creating, renaming or deleting something inside it.
+    pub(crate) fn refresh(&mut self, dir: &Path) {
+        // Newest first: a folder listed again has a newer node.
+        let Some(item) = self
+            .nodes
+            .iter()
+            .rev()
+            .find(|n| n.is_dir && n.path == dir)
+            .map(|n| n.item)
+        else {
+            return;
+        };
+        // SAFETY: hwnd is the live tree and item one of its items; children
+        // are deleted one at a time until none is left.
+        unsafe {
+            while let Some(child) = self.first_child(item) {
+                let _ = SendMessageW(self.hwnd, TVM_DELETEITEM, WPARAM(0), LPARAM(child.0));
+            }
+        }
+        self.fill(item, dir);
+        self.expand(item);
+    }
+
+    /// Handle a `WM_NOTIFY` from the tree.  Expanding a folder fills it in
+    /// here; anything the window has to do is returned.
+    ///
+    /// # Safety
+    /// `hdr` must point to the notification the tree sent, so structures
+    /// that start with an `NMHDR` can be read through it.
+    pub(crate) unsafe fn notify(&mut self, owner: HWND, hdr: *const NMHDR) -> Option<PanelCommand> {
+        match (*hdr).code {
+            TVN_ITEMEXPANDINGW => {
+                let tv = &*(hdr as *const NMTREEVIEWW);
+                if tv.action == TVE_EXPAND && self.first_child(tv.itemNew.hItem).is_none() {
+                    let node = self.nodes.get(tv.itemNew.lParam.0 as usize)?;
+                    let path = node.path.clone();
+                    self.fill(tv.itemNew.hItem, &path);
+                }
+                None
+            }
+            NM_DBLCLK | NM_RETURN => {
+                let node = self.node(self.selected()?)?;
+                (!node.is_dir).then(|| PanelCommand::Open(node.path.clone()))
+            }
+            TVN_KEYDOWN => {
+                let key = (*(hdr as *const NMTVKEYDOWN)).wVKey;
+                let node = self.node(self.selected()?)?;
+                let path = node.path.clone();
+                match key {
+                    k if k == VK_F2.0 => Some(PanelCommand::Rename(path)),
+                    k if k == VK_DELETE.0 && self.nodes[0].path != path => {
+                        Some(PanelCommand::Delete(path))
+                    }
+                    _ => None,
+                }
+            }
+            NM_RCLICK => self.context_menu(owner),
+            _ => None,
+        }
+    }
+
+    /// Show the right-click menu for the item under the cursor (or the
+    /// root, off the items) and return the choice.
+    unsafe fn context_menu(&self, owner: HWND) -> Option<PanelCommand> {
+        let mut pt = POINT::default();
+        let _ = GetCursorPos(&mut pt);
+        let mut hit = TVHITTESTINFO {
+            pt,
+            ..Default::default()
+        };
+        let _ = ScreenToClient(self.hwnd, &mut hit.pt);
+        // SAFETY: hit outlives the call; TVM_HITTEST fills hItem and flags.
+        let _ = SendMessageW(
+            self.hwnd,
+            TVM_HITTEST,
+            WPARAM(0),
+            LPARAM(&mut hit as *mut TVHITTESTINFO as isize),
+        );
+        let item = if hit.flags.0 & TVHT_ONITEM.0 != 0 && hit.hItem.0 != 0 {
+            let _ = SendMessageW(
+                self.hwnd,
+                TVM_SELECTITEM,
+                WPARAM(TVGN_CARET as usize),
+                LPARAM(hit.hItem.0),
+            );
+            hit.hItem
+        } else {
+            self.nodes.first()?.item
+        };
+        let node = self.node(item)?;
+        let is_root = item == self.nodes[0].item;
+        let folder = if node.is_dir {
+            node.path.clone()
+        } else {
+            node.path.parent()?.to_path_buf()
+        };
+
+        let menu = CreatePopupMenu().ok()?;
+        let fixed = if is_root { MF_GRAYED } else { MF_STRING };
+        let _ = AppendMenuW(menu, MF_STRING, ID_NEW_FILE, w!("&New File\u{2026}"));
+        let _ = AppendMenuW(menu, fixed, ID_RENAME, w!("&Rename\u{2026}\tF2"));
+        let _ = AppendMenuW(menu, fixed, ID_DELETE, w!("&Delete\tDel"));
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+        let _ = AppendMenuW(menu, MF_STRING, ID_REVEAL, w!("Reveal in &Explorer"));
+        let cmd = TrackPopupMenu(
+            menu,
+            TPM_RETURNCMD | TPM_RIGHTBUTTON,
+            pt.x,
+            pt.y,
+            0,
+            owner,
+            None,
+        );
+        let _ = DestroyMenu(menu);
+        match cmd.0 as usize {
+            ID_NEW_FILE => Some(PanelCommand::NewFile(folder)),
+            ID_RENAME => Some(PanelCommand::Rename(node.path.clone())),
+            ID_DELETE => Some(PanelCommand::Delete(node.path.clone())),
+            ID_REVEAL => Some(PanelCommand::Reveal(node.path.clone())),
+            _ => None,
+        }
+    }
+
+    /// Add an item for `path` under `parent`.  Folders get an expand button
+    /// before they are listed.
+    fn insert(&mut self, parent: HTREEITEM, name: &str, path: &Path, is_dir: bool) -> HTREEITEM {
+        let mut text: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        let insert = TVINSERTSTRUCTW {
+            hParent: parent,
+            hInsertAfter: TVI_LAST,
+            Anonymous: TVINSERTSTRUCTW_0 {
+                item: TVITEMW {
+                    mask: TVIF_TEXT | TVIF_PARAM | TVIF_CHILDREN,
+                    pszText: PWSTR(text.as_mut_ptr()),
+                    cChildren: if is_dir {
+                        I_ONE_OR_MORE
+                    } else {
+                        TVITEMEXW_CHILDREN(0)
+                    },
+                    lParam: LPARAM(self.nodes.len() as isize),
+                    ..Default::default()
+                },
+            },
+        };
+        // SAFETY: insert and text outlive the call; the tree copies the text.
+        let item = unsafe {
+            SendMessageW(
+                self.hwnd,
+                TVM_INSERTITEMW,
+                WPARAM(0),
+                LPARAM(&insert as *const TVINSERTSTRUCTW as isize),
+            )
+        };
+        let item = HTREEITEM(item.0);
+        self.nodes.push(Node {
+            path: path.to_path_buf(),
+            is_dir,
+            item,
+        });
+        item
+    }
+
+    /// Add the contents of `dir` under `item`.  An empty or unreadable
+    /// folder loses its expand button.
+    fn fill(&mut self, item: HTREEITEM, dir: &Path) {
+        let entries = folder_tree::list_dir(dir).unwrap_or_default();
+        for e in &entries {
+            self.insert(item, &e.name, &e.path, e.is_dir);
+        }
+        if entries.is_empty() {
+            let update = TVITEMW {
+                mask: TVIF_CHILDREN,
+                hItem: item,
+                ..Default::default()
+            };
+            // SAFETY: update outlives the call; item is one of the tree's items.
+            unsafe {
+                let _ = SendMessageW(
+                    self.hwnd,
+                    TVM_SETITEMW,
+                    WPARAM(0),
+                    LPARAM(&update as *const TVITEMW as isize),
+                );
+            }
+        }
+    }
+
+    fn expand(&self, item: HTREEITEM) {
+        // SAFETY: hwnd is the live tree and item one of its items.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                TVM_EXPAND,
+                WPARAM(TVE_EXPAND.0 as usize),
+                LPARAM(item.0),
+            );
+        }
+    }
+
+    fn first_child(&self, item: HTREEITEM) -> Option<HTREEITEM> {
+        // SAFETY: hwnd is the live tree; read-only query.
+        let child = unsafe {
+            SendMessageW(
+                self.hwnd,
+                TVM_GETNEXTITEM,
+                WPARAM(TVGN_CHILD as usize),
+                LPARAM(item.0),
+            )
+        };
+        (child.0 != 0).then_some(HTREEITEM(child.0))
+    }
+
+    fn selected(&self) -> Option<HTREEITEM> {
+        // SAFETY: hwnd is the live tree; read-only query.
+        let item = unsafe {
+            SendMessageW(
+                self.hwnd,
+                TVM_GETNEXTITEM,
+                WPARAM(TVGN_CARET as usize),
+                LPARAM(0),
+            )
+        };
+        (item.0 != 0).then_some(HTREEITEM(item.0))
+    }
+
+    /// The node behind `item`, read from its `lParam`.
+    fn node(&self, item: HTREEITEM) -> Option<&Node> {
+        let mut query = TVITEMW {
+            mask: TVIF_PARAM,
+            hItem: item,
+            ..Default::default()
+        };
+        // SAFETY: query outlives the call; TVM_GETITEMW fills lParam.
+        let ok = unsafe {
+            SendMessageW(
+                self.hwnd,
+                TVM_GETITEMW,
+                WPARAM(0),
+                LPARAM(&mut query as *mut TVITEMW as isize),
+            )
+        };
+        if ok.0 == 0 {
+            return None;
+        }
+        self.nodes.get(query.lParam.0 as usize)
+    }
+}
+
+impl Drop for FolderPanel {
+    fn drop(&mut self) {
+        // SAFETY: hwnd is the tree created in `create`; destroying a window
+        // that is already gone fails harmlessly.
+        unsafe {
+            let _ = DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// Move `path` (a file or a folder) to the Recycle Bin.  The shell asks
+/// for confirmation and shows its own errors; returns whether it was
+/// removed.
+pub(crate) fn recycle(owner: HWND, path: &Path) -> bool {
+    // SHFileOperationW takes a list of paths ending with an empty one.
+    let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+    let mut op = SHFILEOPSTRUCTW {
+        hwnd: owner,
+        wFunc: FO_DELETE,
+        pFrom: PCWSTR(from.as_ptr()),
+        fFlags: FOF_ALLOWUNDO.0 as u16,
+        ..Default::default()
+    };
+    // SAFETY: op and from outlive the call; pFrom is double-null-terminated.
+    let result = unsafe { SHFileOperationW(&mut op) };
+    result == 0 && !op.fAnyOperationsAborted.as_bool() && !path.exists()
+}
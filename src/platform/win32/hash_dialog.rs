@@ -0,0 +1,124 @@
+// ── Hash dialog ───────────────────────────────────────────────────────────────
+//
+// Modal "Generate Hash" dialog for Tools > Generate Hash…: shows the MD5,
+// SHA-1 and SHA-256 of the file or selection in read-only boxes, each with a
+// Copy button.  The digests are computed by `tools::hash` before it opens.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::WindowsAndMessaging::{
+        DialogBoxIndirectParamW, EndDialog, GetWindowLongPtrW, SetWindowLongPtrW, DLGTEMPLATE,
+        GWLP_USERDATA, WM_COMMAND, WM_INITDIALOG,
+    },
+};
+
+use crate::{
+    platform::win32::{
+        clipboard,
+        dlg::{
+            module_instance, set_item_text, DlgTemplate, ATOM_EDIT, ES_AUTOHSCROLL, IDCANCEL,
+            WS_BORDER, WS_TABSTOP,
+        },
+    },
+    tools::hash::Digests,
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_MD5: u16 = 100;
+const ID_SHA1: u16 = 101;
+const ID_SHA256: u16 = 102;
+const ID_COPY_MD5: u16 = 110;
+const ID_COPY_SHA1: u16 = 111;
+const ID_COPY_SHA256: u16 = 112;
+
+/// `ES_READONLY` (winuser.h).
+const ES_READONLY: u32 = 0x0800;
+
+/// Show the modal hash dialog for `digests`; `source` says what was hashed,
+/// e.g. the file name or "Selection (12 bytes)".
+pub(crate) fn show_hash_dialog(hwnd_parent: HWND, source: &str, digests: &Digests) {
+    let mut t = DlgTemplate::new("Generate Hash", 330, 113);
+    t.label(7, 7, 316, 9, source);
+    for (row, (label, id, copy)) in [
+        ("MD5:", ID_MD5, ID_COPY_MD5),
+        ("SHA-1:", ID_SHA1, ID_COPY_SHA1),
+        ("SHA-256:", ID_SHA256, ID_COPY_SHA256),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let y = 24 + 20 * row as u16;
+        t.label(7, y + 2, 40, 9, label).control(
+            ATOM_EDIT,
+            id,
+            WS_BORDER | WS_TABSTOP | ES_AUTOHSCROLL | ES_READONLY,
+            50,
+            y,
+            220,
+            14,
+            "",
+        );
+        t.button(copy, 273, y, 50, 14, "&Copy", false);
+    }
+    t.button(IDCANCEL, 273, 92, 50, 14, "Close", true);
+    let template = t.build();
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; hash_dlg_proc is a
+    // valid DLGPROC; digests outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(hash_dlg_proc),
+            LPARAM(digests as *const Digests as isize),
+        );
+    }
+}
+
+/// Dialog procedure for `show_hash_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn hash_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let digests = &*(lparam.0 as *const Digests);
+            set_item_text(hwnd, ID_MD5 as i32, &digests.md5);
+            set_item_text(hwnd, ID_SHA1 as i32, &digests.sha1);
+            set_item_text(hwnd, ID_SHA256 as i32, &digests.sha256);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const Digests;
+            if ptr.is_null() {
+                return 0;
+            }
+            let digests = &*ptr;
+            let copy = match (wparam.0 & 0xFFFF) as u16 {
+                ID_COPY_MD5 => Some(&digests.md5),
+                ID_COPY_SHA1 => Some(&digests.sha1),
+                ID_COPY_SHA256 => Some(&digests.sha256),
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                    None
+                }
+                _ => None,
+            };
+            if let Some(text) = copy {
+                let _ = clipboard::set_text(hwnd, text);
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,116 @@
+// ── Icons ─────────────────────────────────────────────────────────────────────
+//
+// The application icon is resource 1 in `res/rivet.rc`, linked in by
+// build.rs.  A build made without a resource compiler has no such resource;
+// `app_icon` then falls back to the stock `IDI_APPLICATION`.
+//
+// File-type icons for the tab strip (View > File Icons in Tabs) come from the
+// shell's small system image list.  `SHGFI_USEFILEATTRIBUTES` asks by name
+// only, so the files need not exist (unsaved documents) and the shell never
+// touches the disk; the index depends only on the extension and is cached.
+
+#![allow(unsafe_code)]
+
+use std::{cell::RefCell, collections::HashMap, path::Path};
+
+use windows::{
+    core::{HSTRING, PCWSTR},
+    Win32::{
+        Storage::FileSystem::FILE_ATTRIBUTE_NORMAL,
+        System::LibraryLoader::GetModuleHandleW,
+        UI::{
+            Controls::HIMAGELIST,
+            Shell::{
+                SHGetFileInfoW, SHFILEINFOW, SHGFI_SMALLICON, SHGFI_SYSICONINDEX,
+                SHGFI_USEFILEATTRIBUTES,
+            },
+            WindowsAndMessaging::{
+                GetSystemMetrics, LoadIconW, LoadImageW, HICON, IDI_APPLICATION, IMAGE_ICON,
+                LR_SHARED, SM_CXICON, SM_CXSMICON,
+            },
+        },
+    },
+};
+
+/// Resource ID of the application icon in `res/rivet.rc`.
+const APP_ICON_ID: u16 = 1;
+
+thread_local! {
+    /// System image list index by lower-case extension (`""` for none).
+    static FILE_ICONS: RefCell<HashMap<String, i32>> = RefCell::new(HashMap::new());
+}
+
+/// The application icon at the system's large (`small == false`) or small
+/// icon size, or the stock application icon if the resource is missing.
+/// Shared: the caller must not destroy it.
+pub(crate) fn app_icon(small: bool) -> HICON {
+    let metric = if small { SM_CXSMICON } else { SM_CXICON };
+    // SAFETY: GetSystemMetrics takes a plain index; GetModuleHandleW(None)
+    // is the exe's own module.  The resource "name" is an integer ID in a
+    // PCWSTR (MAKEINTRESOURCE), which LoadImageW accepts; LR_SHARED leaves
+    // the icon owned by the system.
+    let loaded = unsafe {
+        let size = GetSystemMetrics(metric);
+        GetModuleHandleW(None).and_then(|module| {
+            LoadImageW(
+                module,
+                PCWSTR(APP_ICON_ID as usize as *const u16),
+                IMAGE_ICON,
+                size,
+                size,
+                LR_SHARED,
+            )
+        })
+    };
+    match loaded {
+        Ok(handle) => HICON(handle.0),
+        // SAFETY: IDI_APPLICATION is a stock icon; a failed load leaves the
+        // icon blank rather than missing.
+        Err(_) => unsafe { LoadIconW(None, IDI_APPLICATION) }.unwrap_or_default(),
+    }
+}
+
+/// The shell's small system image list, for `TCM_SETIMAGELIST`.  It is
+/// shared by the whole process and must never be destroyed.
+pub(crate) fn small_image_list() -> HIMAGELIST {
+    // SAFETY: a null path with SHGFI_SYSICONINDEX only asks for the list.
+    let list = unsafe {
+        SHGetFileInfoW(
+            PCWSTR::null(),
+            FILE_ATTRIBUTE_NORMAL,
+            None,
+            0,
+            SHGFI_SYSICONINDEX | SHGFI_SMALLICON,
+        )
+    };
+    HIMAGELIST(list as isize)
+}
+
+/// The index in `small_image_list` of the icon for a file named `name`, or
+/// -1 (no image) if the shell has none.
+pub(crate) fn file_icon(name: &str) -> i32 {
+    let extension = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if let Some(index) = FILE_ICONS.with(|cache| cache.borrow().get(&extension).copied()) {
+        return index;
+    }
+    let mut info = SHFILEINFOW::default();
+    let path = HSTRING::from(name);
+    // SAFETY: path is a NUL-terminated name; info is a writable SHFILEINFOW
+    // of the size passed.  With SHGFI_USEFILEATTRIBUTES the file need not
+    // exist.
+    let list = unsafe {
+        SHGetFileInfoW(
+            &path,
+            FILE_ATTRIBUTE_NORMAL,
+            Some(&mut info),
+            std::mem::size_of::<SHFILEINFOW>() as u32,
+            SHGFI_SYSICONINDEX | SHGFI_SMALLICON | SHGFI_USEFILEATTRIBUTES,
+        )
+    };
+    let index = if list == 0 { -1 } else { info.iIcon };
+    FILE_ICONS.with(|cache| cache.borrow_mut().insert(extension, index));
+    index
+}
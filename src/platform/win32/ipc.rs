@@ -0,0 +1,110 @@
+// ── Cross-window coordination ─────────────────────────────────────────────────
+//
+// Every Rivet instance runs in its own process with its own top-level
+// `RivetMainWindow`.  Instances find each other by window class and exchange
+// `WM_COPYDATA` messages, which Windows marshals across process boundaries.
+// The exchange is synchronous: the receiver's `LRESULT` is the reply.
+//
+// Requests carry a file path as UTF-16 (no terminator) and one of the
+// `COPYDATA_*` codes in `dwData`.
+
+#![allow(unsafe_code)]
+
+use std::path::{Path, PathBuf};
+
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{HWND, LPARAM, WPARAM},
+        UI::WindowsAndMessaging::{FindWindowExW, SendMessageTimeoutW, SMTO_ABORTIFHUNG},
+    },
+};
+
+/// `WM_COPYDATA` (winuser.h).
+pub(crate) const WM_COPYDATA: u32 = 0x004A;
+
+/// "Do you have this path open with unsaved changes?"  Reply 1 = yes.
+pub(crate) const COPYDATA_QUERY_DIRTY: usize = 0x5256_0001;
+/// "Bring the tab holding this path to the front."  Reply 1 = done.
+pub(crate) const COPYDATA_ACTIVATE: usize = 0x5256_0002;
+
+/// How long to wait for a peer before treating it as unresponsive.
+const PEER_TIMEOUT_MS: u32 = 1000;
+
+/// Portable Rust representation of the Win32 `COPYDATASTRUCT` struct.
+#[repr(C)]
+#[allow(clippy::upper_case_acronyms)]
+struct COPYDATASTRUCT {
+    dw_data: usize,
+    cb_data: u32,
+    lp_data: *const std::ffi::c_void,
+}
+
+/// `true` if `a` and `b` name the same file.
+///
+/// NTFS paths are case-insensitive, so compare case-folded strings.
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+/// Send request `code` about `path` to `peer` and return its reply.
+///
+/// A peer that is hung or times out answers `false`.
+fn send_request(own: HWND, peer: HWND, code: usize, path: &Path) -> bool {
+    let wide: Vec<u16> = path.as_os_str().to_string_lossy().encode_utf16().collect();
+    let cds = COPYDATASTRUCT {
+        dw_data: code,
+        cb_data: (wide.len() * 2) as u32,
+        lp_data: wide.as_ptr() as *const std::ffi::c_void,
+    };
+    let mut reply = 0usize;
+    // SAFETY: cds and the buffer it points to outlive the synchronous call;
+    // Windows copies the payload into the receiving process.
+    let ok = unsafe {
+        SendMessageTimeoutW(
+            peer,
+            WM_COPYDATA,
+            WPARAM(own.0 as usize),
+            LPARAM(&cds as *const COPYDATASTRUCT as isize),
+            SMTO_ABORTIFHUNG,
+            PEER_TIMEOUT_MS,
+            Some(&mut reply),
+        )
+    };
+    ok.0 != 0 && reply == 1
+}
+
+/// Find another Rivet window that has `path` open with unsaved changes.
+///
+/// `own` is excluded from the search.
+pub(crate) fn find_dirty_peer(own: HWND, class_name: PCWSTR, path: &Path) -> Option<HWND> {
+    let mut after = HWND::default();
+    loop {
+        // SAFETY: class_name is a valid null-terminated wide string; a null
+        // parent enumerates top-level windows.
+        let peer = unsafe { FindWindowExW(None, after, class_name, PCWSTR::null()) }.ok()?;
+        if peer != own && send_request(own, peer, COPYDATA_QUERY_DIRTY, path) {
+            return Some(peer);
+        }
+        after = peer;
+    }
+}
+
+/// Ask `peer` to bring its tab for `path` to the front.
+pub(crate) fn activate_peer(own: HWND, peer: HWND, path: &Path) -> bool {
+    send_request(own, peer, COPYDATA_ACTIVATE, path)
+}
+
+/// Decode a received `WM_COPYDATA` into its request code and path.
+///
+/// # Safety
+/// `lparam` must be the `LPARAM` of a `WM_COPYDATA` message currently being
+/// handled; the data it points to is only valid until the handler returns.
+pub(crate) unsafe fn decode_request(lparam: LPARAM) -> Option<(usize, PathBuf)> {
+    let cds = (lparam.0 as *const COPYDATASTRUCT).as_ref()?;
+    if cds.lp_data.is_null() || cds.cb_data % 2 != 0 {
+        return None;
+    }
+    let units = std::slice::from_raw_parts(cds.lp_data as *const u16, cds.cb_data as usize / 2);
+    Some((cds.dw_data, PathBuf::from(String::from_utf16_lossy(units))))
+}
@@ -0,0 +1,126 @@
+// ── Taskbar jump list ─────────────────────────────────────────────────────────
+//
+// Fills the menu shown when Rivet's taskbar button is right-clicked: a
+// "Recent" category from `crate::recent` and a "New Window" task.  Every item
+// is a shell link back to `rivet.exe`; recent files pass their path as the
+// only argument, which `run()` opens on startup.
+//
+// The list is rebuilt from scratch each time (`BeginList` … `CommitList`).
+// Files the user removed from the jump list must not be added back, so
+// `update` drops them and reports them to the caller.
+
+#![allow(unsafe_code)]
+
+use std::path::{Path, PathBuf};
+
+use windows::{
+    core::{w, Interface, HSTRING, PROPVARIANT},
+    Win32::{
+        System::Com::{
+            CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+        },
+        UI::Shell::{
+            Common::{IObjectArray, IObjectCollection},
+            DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+            PropertiesSystem::{IPropertyStore, PROPERTYKEY},
+            ShellLink,
+        },
+    },
+};
+
+/// `PKEY_Title` (propkey.h): the text the jump list shows for a link.
+const PKEY_TITLE: PROPERTYKEY = PROPERTYKEY {
+    fmtid: windows::core::GUID::from_u128(0xf29f85e0_4ff9_1068_ab91_08002b27b3d9),
+    pid: 2,
+};
+
+/// Initialise COM for the UI thread.  Call once, before the first `update`.
+pub(crate) fn init() {
+    // SAFETY: called once on the UI thread before any COM object is created;
+    // S_FALSE (already initialised) is harmless.
+    let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+}
+
+/// Rebuild the jump list from `recent` (newest first).  Returns the entries
+/// the user has removed from the jump list since the last update; they are
+/// left out and the caller should forget them.
+pub(crate) fn update(recent: &[PathBuf]) -> Vec<PathBuf> {
+    let Ok(exe) = std::env::current_exe() else {
+        return Vec::new();
+    };
+    // SAFETY: COM was initialised by `init` on this thread; every interface
+    // is released when its wrapper drops.
+    unsafe { build(&exe, recent) }.unwrap_or_default()
+}
+
+unsafe fn build(exe: &Path, recent: &[PathBuf]) -> windows::core::Result<Vec<PathBuf>> {
+    let list: ICustomDestinationList =
+        CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+    let mut min_slots = 0u32;
+    let removed: IObjectArray = list.BeginList(&mut min_slots)?;
+    let removed = removed_paths(&removed);
+
+    let files: IObjectCollection =
+        CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+    for path in recent.iter().filter(|p| !removed.contains(p)) {
+        let title = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let link = shell_link(exe, &quote(path), &title, &path.display().to_string())?;
+        files.AddObject(&link)?;
+    }
+    if files.GetCount()? > 0 {
+        list.AppendCategory(w!("Recent"), &files.cast::<IObjectArray>()?)?;
+    }
+
+    let tasks: IObjectCollection =
+        CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+    let new_window = shell_link(exe, "", "New Window", "Open another Rivet window")?;
+    tasks.AddObject(&new_window)?;
+    list.AddUserTasks(&tasks.cast::<IObjectArray>()?)?;
+
+    list.CommitList()?;
+    Ok(removed)
+}
+
+/// A link that starts `exe` with `args`, shown as `title`.
+unsafe fn shell_link(
+    exe: &Path,
+    args: &str,
+    title: &str,
+    tooltip: &str,
+) -> windows::core::Result<IShellLinkW> {
+    let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+    link.SetPath(&HSTRING::from(exe))?;
+    link.SetArguments(&HSTRING::from(args))?;
+    link.SetIconLocation(&HSTRING::from(exe), 0)?;
+    link.SetDescription(&HSTRING::from(tooltip))?;
+    let props: IPropertyStore = link.cast()?;
+    props.SetValue(&PKEY_TITLE, &PROPVARIANT::from(title))?;
+    props.Commit()?;
+    Ok(link)
+}
+
+/// Paths of the removed links in `removed` (tasks have no argument and are
+/// skipped).
+unsafe fn removed_paths(removed: &IObjectArray) -> Vec<PathBuf> {
+    let count = removed.GetCount().unwrap_or(0);
+    (0..count)
+        .filter_map(|i| removed.GetAt::<IShellLinkW>(i).ok())
+        .filter_map(|link| {
+            let mut buf = [0u16; 1024];
+            link.GetArguments(&mut buf).ok()?;
+            let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+            let args = String::from_utf16_lossy(&buf[..len]);
+            let path = args.trim().trim_matches('"');
+            (!path.is_empty()).then(|| PathBuf::from(path))
+        })
+        .collect()
+}
+
+/// `path` as a single command-line argument.  Windows paths cannot contain
+/// `"`, so wrapping in quotes is enough.
+fn quote(path: &Path) -> String {
+    format!("\"{}\"", path.display())
+}
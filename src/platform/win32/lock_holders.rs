@@ -0,0 +1,108 @@
+// ── Processes holding a file ──────────────────────────────────────────────────
+//
+// When a save fails with a sharing violation, the prompt names the programs
+// that have the file open.  The Restart Manager answers that without any
+// privileges: a session registers the one file, `RmGetList` lists the
+// processes using it, and the session ends.  Nothing is shut down or
+// restarted.
+//
+// The answer is best effort: a process in another session, or a file on a
+// network share held from another machine, is not listed.
+
+#![allow(unsafe_code)]
+
+use std::path::Path;
+
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS},
+        System::RestartManager::{
+            RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+            RM_PROCESS_INFO,
+        },
+    },
+};
+
+/// A process with the file open.
+pub(crate) struct Holder {
+    /// The program's display name, e.g. `"Microsoft Excel"`.
+    pub(crate) name: String,
+    pub(crate) pid: u32,
+}
+
+/// Times to ask again when the list grows between the sizing call and the
+/// one that fills it.
+const ATTEMPTS: usize = 3;
+
+/// The processes that have `path` open, as far as the Restart Manager can
+/// tell; empty if it cannot.
+pub(crate) fn holders(path: &Path) -> Vec<Holder> {
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    let mut session = 0u32;
+    // SAFETY: key has room for CCH_RM_SESSION_KEY characters and the NUL.
+    if unsafe { RmStartSession(&mut session, 0, PWSTR(key.as_mut_ptr())) } != ERROR_SUCCESS {
+        return Vec::new();
+    }
+    let found = list(session, path);
+    // SAFETY: session was started above and is ended exactly once.
+    unsafe {
+        let _ = RmEndSession(session);
+    }
+    found
+}
+
+/// Register `path` in `session` and read back who holds it.
+fn list(session: u32, path: &Path) -> Vec<Holder> {
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let files = [PCWSTR(wide.as_ptr())];
+    // SAFETY: files holds one NUL-terminated path that outlives the call;
+    // the session copies it.
+    if unsafe { RmRegisterResources(session, Some(&files), None, None) } != ERROR_SUCCESS {
+        return Vec::new();
+    }
+    let mut infos: Vec<RM_PROCESS_INFO> = Vec::new();
+    for _ in 0..ATTEMPTS {
+        let mut needed = 0u32;
+        let mut count = infos.len() as u32;
+        let mut reasons = 0u32;
+        // SAFETY: infos is writable for `count` entries (none on the sizing
+        // call, which passes a null buffer); the counts and reasons are live
+        // locals.
+        let status = unsafe {
+            RmGetList(
+                session,
+                &mut needed,
+                &mut count,
+                (!infos.is_empty()).then_some(infos.as_mut_ptr()),
+                &mut reasons,
+            )
+        };
+        if status == ERROR_SUCCESS {
+            infos.truncate(count as usize);
+            return infos
+                .iter()
+                .map(|info| Holder {
+                    name: wide_to_string(&info.strAppName),
+                    pid: info.Process.dwProcessId,
+                })
+                .collect();
+        }
+        if status != ERROR_MORE_DATA {
+            break;
+        }
+        infos.resize(needed as usize, RM_PROCESS_INFO::default());
+    }
+    Vec::new()
+}
+
+/// A fixed NUL-padded UTF-16 buffer as a `String`.
+fn wide_to_string(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
@@ -16,4 +16,38 @@
 pub mod dialogs; // Phase 3: common open/save/find dialogs
 pub mod window; // Phase 2: main window, WndProc, message loop
 
+pub(crate) mod a11y; // screen-reader notifications + accessible names
+pub(crate) mod accent; // Windows accent colour from the registry
+pub(crate) mod associations; // Tools > File Associations… + registry writes
+pub(crate) mod autoscroll; // middle-click autoscroll + Shift+wheel in editor views
+pub(crate) mod bridge; // typed worker → UI messages (WM_TASK)
+pub(crate) mod caret_dialog; // View > Caret… settings dialog
+pub(crate) mod clipboard; // CF_UNICODETEXT get/set outside Scintilla
+pub(crate) mod column_dialog; // Edit > Column Editor… dialog
+pub(crate) mod contrast; // High Contrast mode + system colours
+pub(crate) mod crash; // panic hook + minidump on unhandled exceptions
+pub(crate) mod dlg; // in-memory DLGTEMPLATE builder + generic prompt dialog
+pub(crate) mod document_map; // View > Document Map mouse input
 pub(crate) mod dpi; // Phase 8: per-monitor DPI v2 helpers
+pub(crate) mod encoding; // ANSI code page <-> UTF-8 conversion
+pub(crate) mod file_info; // file times, attributes + final path for Document Properties
+pub(crate) mod folder_panel; // View > Folder Panel directory tree
+pub(crate) mod hash_dialog; // Tools > Generate Hash… results
+pub(crate) mod icons; // application icon + file-type icons for tabs
+pub(crate) mod ipc; // WM_COPYDATA requests between Rivet windows
+pub(crate) mod jumplist; // taskbar jump list: recent files + tasks
+pub(crate) mod lock_holders; // Restart Manager: which processes hold a file
+pub(crate) mod pipeline_dialog; // Tools > Text Pipeline… recipe and preview
+pub(crate) mod process; // CreateProcessW for the Tools menu
+pub(crate) mod progress; // status-bar progress bar + Cancel for long operations
+pub(crate) mod properties_dialog; // File > Document Properties… (Alt+Enter)
+pub(crate) mod registry; // safe HKCU key/value wrapper
+pub(crate) mod save_prompt; // "Save before closing?" with Show Changes
+pub(crate) mod shortcuts_dialog; // Tools > Keyboard Shortcuts… editor
+pub(crate) mod switcher_dialog; // View > Switch to Tab… quick switcher
+pub(crate) mod tab_drag; // drag-reorder subclass for the tab strip
+pub(crate) mod titlebar; // DWM extended frame for tabs in the title bar
+pub(crate) mod toolbar; // View > Toolbar button strip
+pub(crate) mod tools_dialog; // Tools > Configure Tools… editor
+pub(crate) mod tray; // notification-area icon for View > Minimize to Tray
+pub(crate) mod workspace_dialog; // File > Open / Save Workspace… picker
@@ -0,0 +1,195 @@
+// ── Text pipeline dialog ──────────────────────────────────────────────────────
+//
+// Modal "Text Pipeline" dialog for Tools > Text Pipeline…: the user types a
+// recipe of steps (see `editor::pipeline`) and sees the result on the
+// selected lines update as they type.  Returns the recipe on Apply; the
+// caller runs it and replaces the text.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::{
+        Input::KeyboardAndMouse::EnableWindow,
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SetWindowLongPtrW,
+            DLGTEMPLATE, EN_CHANGE, GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+use crate::{
+    editor::pipeline,
+    platform::win32::dlg::{
+        get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_EDIT, ATOM_STATIC,
+        ES_AUTOHSCROLL, IDCANCEL, IDOK, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_RECIPE: u16 = 100;
+const ID_PREVIEW: u16 = 101;
+const ID_SUMMARY: u16 = 102;
+
+// Edit styles not in `dlg` (winuser.h).
+const ES_MULTILINE: u32 = 0x0004;
+const ES_AUTOVSCROLL: u32 = 0x0040;
+const ES_READONLY: u32 = 0x0800;
+const ES_WANTRETURN: u32 = 0x1000;
+const WS_HSCROLL: u32 = 0x0010_0000;
+
+/// Lines of the result shown in the preview; the rest are counted only.
+const PREVIEW_LINES: usize = 500;
+
+/// Working state shared with `pipeline_dlg_proc` through `GWLP_USERDATA`.
+struct PipelineParams<'a> {
+    /// The selected lines the recipe runs over.
+    text: &'a [u8],
+    /// The document's EOL, for joining the result.
+    eol: &'a [u8],
+    recipe: String,
+    confirmed: bool,
+}
+
+/// Show the modal pipeline dialog over `text`, seeded with `recipe` (the
+/// last one used).
+///
+/// Returns the recipe if the user pressed Apply, `None` on Cancel.  The
+/// returned recipe always parses.
+pub(crate) fn show_pipeline_dialog(
+    hwnd_parent: HWND,
+    text: &[u8],
+    eol: &[u8],
+    recipe: &str,
+) -> Option<String> {
+    let multiline = WS_BORDER | WS_VSCROLL | ES_MULTILINE | ES_AUTOVSCROLL;
+    let mut t = DlgTemplate::new("Text Pipeline", 320, 216);
+    t.label(7, 7, 130, 9, "&Steps, one per line:")
+        .control(
+            ATOM_EDIT,
+            ID_RECIPE,
+            multiline | WS_TABSTOP | ES_WANTRETURN,
+            7,
+            18,
+            130,
+            150,
+            "",
+        )
+        .label(144, 7, 169, 9, "&Preview:")
+        .control(
+            ATOM_EDIT,
+            ID_PREVIEW,
+            multiline | WS_TABSTOP | WS_HSCROLL | ES_AUTOHSCROLL | ES_READONLY,
+            144,
+            18,
+            169,
+            150,
+            "",
+        )
+        .label(7, 172, 306, 18, &format!("Steps: {}", pipeline::SYNTAX))
+        .control(ATOM_STATIC, ID_SUMMARY, 0, 7, 197, 195, 9, "")
+        .button(IDOK, 209, 195, 50, 14, "&Apply", true)
+        .button(IDCANCEL, 263, 195, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = PipelineParams {
+        text,
+        eol,
+        recipe: recipe.to_owned(),
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; pipeline_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(pipeline_dlg_proc),
+            LPARAM(&mut params as *mut PipelineParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.recipe)
+}
+
+/// Re-run the typed recipe and show the result, or where it fails to parse.
+/// Apply is enabled only for a recipe that parses.
+///
+/// # Safety
+/// `hwnd` must be the live pipeline dialog.
+unsafe fn update_preview(hwnd: HWND, params: &PipelineParams) {
+    let recipe = get_item_text(hwnd, ID_RECIPE as i32);
+    let (preview, summary, valid) = match pipeline::parse(&recipe) {
+        Ok(steps) => {
+            let (out, stats) = pipeline::run(&steps, params.text, params.eol);
+            let text = String::from_utf8_lossy(&out);
+            let mut preview: Vec<&str> = text.lines().take(PREVIEW_LINES).collect();
+            if stats.lines_out > PREVIEW_LINES {
+                preview.push("\u{2026}");
+            }
+            let summary = format!("{} lines in, {} out", stats.lines_in, stats.lines_out);
+            (preview.join("\r\n"), summary, true)
+        }
+        Err(e) => (
+            String::new(),
+            format!("Line {}: {}", e.line, e.message),
+            false,
+        ),
+    };
+    set_item_text(hwnd, ID_PREVIEW as i32, &preview);
+    set_item_text(hwnd, ID_SUMMARY as i32, &summary);
+    if let Ok(apply) = GetDlgItem(hwnd, IDOK as i32) {
+        let _ = EnableWindow(apply, valid);
+    }
+}
+
+/// Dialog procedure for `show_pipeline_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn pipeline_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const PipelineParams);
+            set_item_text(hwnd, ID_RECIPE as i32, &params.recipe);
+            update_preview(hwnd, params);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PipelineParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let code = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            match id {
+                ID_RECIPE if code == EN_CHANGE => update_preview(hwnd, &*ptr),
+                IDOK => {
+                    let recipe = get_item_text(hwnd, ID_RECIPE as i32);
+                    if pipeline::parse(&recipe).is_ok() {
+                        (*ptr).recipe = recipe;
+                        (*ptr).confirmed = true;
+                        let _ = EndDialog(hwnd, 1);
+                    } else {
+                        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+                    }
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,106 @@
+// ── Child-process launching ───────────────────────────────────────────────────
+//
+// Thin wrapper around `CreateProcessW` for the Tools menu.  The child runs
+// fully detached: Rivet closes both returned handles immediately and never
+// waits on the process.  `capture` is the other way round, for short helper
+// runs such as `git show`: it waits and returns what the child printed.
+
+#![allow(unsafe_code)]
+
+use std::{io, os::windows::process::CommandExt, path::Path, process::Command};
+
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{
+            CreateProcessW, CREATE_NEW_PROCESS_GROUP, CREATE_NO_WINDOW, PROCESS_INFORMATION,
+            STARTUPINFOW,
+        },
+    },
+};
+
+use crate::error::{Result, RivetError};
+
+/// Launch `command_line` as a detached child process.
+///
+/// The command line is passed to `CreateProcessW` verbatim, so the first
+/// token is resolved with the usual Win32 rules (application directory,
+/// system directories, `PATH`).  `working_dir`, if given, becomes the child's
+/// current directory.
+pub(crate) fn spawn_detached(command_line: &str, working_dir: Option<&Path>) -> Result<()> {
+    // CreateProcessW may write into the command-line buffer, so it must be a
+    // mutable, null-terminated copy.
+    let mut cmd: Vec<u16> = command_line
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let dir: Option<Vec<u16>> = working_dir.map(|d| {
+        d.as_os_str()
+            .to_string_lossy()
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect()
+    });
+    let dir_ptr = dir.as_ref().map_or(PCWSTR::null(), |d| PCWSTR(d.as_ptr()));
+
+    let startup = STARTUPINFOW {
+        cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+        ..Default::default()
+    };
+    let mut info = PROCESS_INFORMATION::default();
+
+    // SAFETY: `cmd` is a writable null-terminated UTF-16 buffer and `dir_ptr`
+    // is null or points into `dir`; both outlive the call.  `startup` and
+    // `info` are correctly sized and initialised structs.
+    unsafe {
+        CreateProcessW(
+            PCWSTR::null(),
+            PWSTR(cmd.as_mut_ptr()),
+            None,
+            None,
+            false,
+            CREATE_NEW_PROCESS_GROUP,
+            None,
+            dir_ptr,
+            &startup,
+            &mut info,
+        )
+    }
+    .map_err(|e| RivetError::Win32 {
+        function: "CreateProcessW",
+        code: e.code().0 as u32,
+    })?;
+
+    // SAFETY: both handles were just returned by a successful CreateProcessW
+    // and are owned by us; closing them does not affect the child.
+    unsafe {
+        let _ = CloseHandle(info.hThread);
+        let _ = CloseHandle(info.hProcess);
+    }
+    Ok(())
+}
+
+/// Run `program` with `args` in `working_dir`, wait for it, and return its
+/// standard output.
+///
+/// The child gets no console window.  A non-zero exit status is an error
+/// carrying the first line of its standard error.  Blocks, so call it from
+/// a worker thread.
+pub(crate) fn capture(program: &str, args: &[&str], working_dir: &Path) -> io::Result<Vec<u8>> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(working_dir)
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let first = stderr.lines().next().unwrap_or_default();
+        Err(io::Error::other(format!(
+            "{program} exited with {}: {first}",
+            output.status
+        )))
+    }
+}
@@ -0,0 +1,185 @@
+// ── Status-bar progress ───────────────────────────────────────────────────────
+//
+// While a long operation runs in the active tab the status bar shows a
+// `msctls_progress32` bar and a Cancel button over the right end of its
+// position part (see `ui::status::Task`).  Both are children of the main
+// window rather than of the status bar, which would swallow the button's
+// `WM_COMMAND`; they are raised above it whenever they are placed, and the
+// status bar's `WS_CLIPSIBLINGS` keeps it from painting over them.
+//
+// The workers themselves only post messages; the window re-reads the task's
+// percentage and calls `show` or `hide` each time it refreshes the status
+// bar.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{HWND, LPARAM, POINT, RECT, WPARAM},
+        Graphics::Gdi::{MapWindowPoints, HFONT},
+        UI::{
+            Controls::{PBM_SETPOS, PBS_SMOOTH, PROGRESS_CLASSW, SB_GETRECT, WC_BUTTONW},
+            WindowsAndMessaging::{
+                CreateWindowExW, SendMessageW, SetWindowPos, ShowWindow, BS_PUSHBUTTON, HMENU,
+                HWND_TOP, SWP_NOACTIVATE, SWP_SHOWWINDOW, SW_HIDE, WINDOW_EX_STYLE, WINDOW_STYLE,
+                WM_SETFONT, WS_CHILD, WS_CLIPSIBLINGS,
+            },
+        },
+    },
+};
+
+use crate::platform::win32::dpi;
+
+/// Cancel button width at 96 DPI.
+const CANCEL_W_BASE: i32 = 64;
+/// Progress bar width at 96 DPI; narrower if the part is.
+const BAR_W_BASE: i32 = 120;
+/// Gap around and between the two controls at 96 DPI.
+const GAP_BASE: i32 = 2;
+
+/// The progress bar and Cancel button, hidden until a task runs.
+pub(crate) struct StatusProgress {
+    parent: HWND,
+    bar: HWND,
+    cancel: HWND,
+}
+
+impl StatusProgress {
+    /// Create both controls, hidden, in `parent`; the button sends
+    /// `WM_COMMAND` with `cancel_id`.
+    pub(crate) fn create(parent: HWND, cancel_id: usize) -> Option<Self> {
+        // SAFETY: comctl32 registers the progress class when it loads and
+        // user32 the button class; parent is the live main window.
+        unsafe {
+            let bar = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                PROGRESS_CLASSW,
+                None,
+                WS_CHILD | WS_CLIPSIBLINGS | WINDOW_STYLE(PBS_SMOOTH),
+                0,
+                0,
+                0,
+                0,
+                parent,
+                HMENU::default(),
+                None,
+                None,
+            )
+            .ok()?;
+            let cancel = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                WC_BUTTONW,
+                w!("Cancel"),
+                WS_CHILD | WS_CLIPSIBLINGS | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+                0,
+                0,
+                0,
+                0,
+                parent,
+                HMENU(cancel_id as *mut _),
+                None,
+                None,
+            )
+            .ok()?;
+            Some(Self {
+                parent,
+                bar,
+                cancel,
+            })
+        }
+    }
+
+    /// Both control handles, for theming.
+    pub(crate) fn hwnds(&self) -> [HWND; 2] {
+        [self.bar, self.cancel]
+    }
+
+    /// Draw the button's caption in `font`.
+    pub(crate) fn set_font(&self, font: HFONT) {
+        // SAFETY: cancel is a live button; WM_SETFONT takes an HFONT and a
+        // redraw flag.
+        unsafe {
+            let _ = SendMessageW(self.cancel, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        }
+    }
+
+    /// Show the controls at `percent` over the right end of part `part` of
+    /// the status bar `status`, sized for `dpi`.
+    pub(crate) fn show(&self, status: HWND, part: usize, percent: u8, dpi: u32) {
+        let gap = dpi::scale(GAP_BASE, dpi);
+        let mut rc = RECT::default();
+        // SAFETY: status is the live status bar; SB_GETRECT fills rc with
+        // the part's rectangle in its client coordinates, which
+        // MapWindowPoints converts to the parent's.
+        unsafe {
+            let _ = SendMessageW(
+                status,
+                SB_GETRECT,
+                WPARAM(part),
+                LPARAM(&mut rc as *mut RECT as isize),
+            );
+            let mut corners = [
+                POINT {
+                    x: rc.left,
+                    y: rc.top,
+                },
+                POINT {
+                    x: rc.right,
+                    y: rc.bottom,
+                },
+            ];
+            MapWindowPoints(status, self.parent, &mut corners);
+            rc = RECT {
+                left: corners[0].x,
+                top: corners[0].y,
+                right: corners[1].x,
+                bottom: corners[1].y,
+            };
+        }
+        let h = (rc.bottom - rc.top - 2 * gap).max(1);
+        let y = rc.top + gap;
+        let cancel_w = dpi::scale(CANCEL_W_BASE, dpi);
+        let cancel_x = rc.right - gap - cancel_w;
+        // Leave at least half the part for its text.
+        let bar_w = dpi::scale(BAR_W_BASE, dpi).min((cancel_x - rc.left) / 2 - gap);
+        let bar_x = cancel_x - gap - bar_w;
+        // SAFETY: both controls are live children of parent; PBM_SETPOS
+        // takes the position in the default 0–100 range.
+        unsafe {
+            let _ = SendMessageW(self.bar, PBM_SETPOS, WPARAM(percent as usize), LPARAM(0));
+            if bar_w > 0 {
+                let _ = SetWindowPos(
+                    self.bar,
+                    HWND_TOP,
+                    bar_x,
+                    y,
+                    bar_w,
+                    h,
+                    SWP_SHOWWINDOW | SWP_NOACTIVATE,
+                );
+            } else {
+                let _ = ShowWindow(self.bar, SW_HIDE);
+            }
+            let _ = SetWindowPos(
+                self.cancel,
+                HWND_TOP,
+                cancel_x,
+                y,
+                cancel_w,
+                h,
+                SWP_SHOWWINDOW | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    /// Hide both controls.
+    pub(crate) fn hide(&self) {
+        // SAFETY: both are live child windows; hiding a hidden window is a
+        // no-op.
+        unsafe {
+            let _ = ShowWindow(self.bar, SW_HIDE);
+            let _ = ShowWindow(self.cancel, SW_HIDE);
+        }
+    }
+}
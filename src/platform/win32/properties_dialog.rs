@@ -0,0 +1,281 @@
+// ── Document properties dialog ────────────────────────────────────────────────
+//
+// Modal "Document Properties" dialog for File > Document Properties…
+// (Alt+Enter): the active document's path, size, line count and read-only
+// state, a read-only "File Info" section on the file behind it
+// (`crate::file_info`), and drop-downs for the encoding, EOL and language
+// the status bar shows — so all of them can be read and changed from the
+// keyboard.  Returns the chosen settings; the caller applies what changed.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::EnableWindow,
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SendMessageW,
+            SetWindowLongPtrW, DLGTEMPLATE, GWLP_USERDATA, WM_COMMAND, WM_INITDIALOG,
+        },
+    },
+};
+
+use crate::{
+    app::{Encoding, EolMode},
+    file_info::FileInfo,
+    languages::Language,
+    platform::win32::dlg::{
+        module_instance, set_item_text, DlgTemplate, ATOM_BUTTON, ATOM_EDIT, ES_AUTOHSCROLL,
+        IDCANCEL, IDOK, ID_UNUSED, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_PATH: u16 = 100;
+const ID_ENCODING: u16 = 101;
+const ID_EOL: u16 = 102;
+const ID_LANGUAGE: u16 = 103;
+const ID_LINK_TARGET: u16 = 104;
+const ID_RESOLVED: u16 = 105;
+
+/// Combo box class atom (DLGITEMTEMPLATE predefined classes).
+const ATOM_COMBOBOX: u16 = 0x0085;
+
+// winuser.h
+const ES_READONLY: u32 = 0x0800;
+const BS_GROUPBOX: u32 = 0x0007;
+const CBS_DROPDOWNLIST: u32 = 0x0003;
+const CB_ADDSTRING: u32 = 0x0143;
+const CB_GETCURSEL: u32 = 0x0147;
+const CB_SETCURSEL: u32 = 0x014E;
+
+/// What the dialog shows but does not change.
+pub(crate) struct DocumentInfo {
+    /// Full path, or the tab name of an unsaved document.
+    pub(crate) path: String,
+    /// Size for display, e.g. "1.2 KB (1,234 bytes)".
+    pub(crate) size: String,
+    /// Line count; `None` when not known (a huge file in the viewer).
+    pub(crate) lines: Option<usize>,
+    /// The tab cannot be edited; encoding and EOL are shown but locked.
+    pub(crate) read_only: bool,
+    /// The file on disk; `None` for an unsaved document or a file that is
+    /// gone.
+    pub(crate) file: Option<FileInfo>,
+}
+
+/// The settings the dialog can change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DocumentProperties {
+    pub(crate) encoding: Encoding,
+    pub(crate) eol: EolMode,
+    pub(crate) language: Language,
+}
+
+/// Working state shared with `properties_dlg_proc` through `GWLP_USERDATA`.
+struct PropertiesParams<'a> {
+    info: &'a DocumentInfo,
+    properties: DocumentProperties,
+    confirmed: bool,
+}
+
+/// Show the modal properties dialog for a document.
+///
+/// Returns the settings if the user pressed OK, `None` on Cancel.
+pub(crate) fn show_properties_dialog(
+    hwnd_parent: HWND,
+    info: &DocumentInfo,
+    properties: DocumentProperties,
+) -> Option<DocumentProperties> {
+    let lines = info
+        .lines
+        .map_or_else(|| "\u{2014}".to_owned(), |n| n.to_string());
+    let read_only = if info.read_only { "Yes" } else { "No" };
+    let none = || "\u{2014}".to_owned();
+    let file = info.file.as_ref();
+    let created = file.and_then(|f| f.created.clone()).unwrap_or_else(none);
+    let modified = file.and_then(|f| f.modified.clone()).unwrap_or_else(none);
+    let disk_size = file.map_or_else(none, |f| {
+        format!(
+            "{} ({} bytes)",
+            crate::ui::status::human_size(f.size),
+            f.size
+        )
+    });
+    let attributes = file.map_or_else(none, |f| f.attributes.clone());
+    let path_box = WS_BORDER | WS_TABSTOP | ES_AUTOHSCROLL | ES_READONLY;
+    let combo = CBS_DROPDOWNLIST | WS_VSCROLL | WS_TABSTOP;
+    let mut t = DlgTemplate::new("Document Properties", 260, 251);
+    t.label(7, 9, 60, 9, "&Path:")
+        .control(ATOM_EDIT, ID_PATH, path_box, 70, 7, 183, 14, "")
+        .label(7, 27, 60, 9, "Size:")
+        .label(70, 27, 183, 9, &info.size)
+        .label(7, 40, 60, 9, "Lines:")
+        .label(70, 40, 183, 9, &lines)
+        .label(7, 53, 60, 9, "Read-only:")
+        .label(70, 53, 183, 9, read_only)
+        .control(
+            ATOM_BUTTON,
+            ID_UNUSED,
+            BS_GROUPBOX,
+            7,
+            66,
+            246,
+            95,
+            "File Info",
+        )
+        .label(13, 78, 54, 9, "Created:")
+        .label(70, 78, 177, 9, &created)
+        .label(13, 91, 54, 9, "Modified:")
+        .label(70, 91, 177, 9, &modified)
+        .label(13, 104, 54, 9, "Size on disk:")
+        .label(70, 104, 177, 9, &disk_size)
+        .label(13, 117, 54, 9, "Attributes:")
+        .label(70, 117, 177, 9, &attributes)
+        .label(13, 131, 54, 9, "Lin&k target:")
+        .control(ATOM_EDIT, ID_LINK_TARGET, path_box, 70, 129, 177, 12, "")
+        .label(13, 145, 54, 9, "Resol&ved:")
+        .control(ATOM_EDIT, ID_RESOLVED, path_box, 70, 143, 177, 12, "")
+        .label(7, 170, 60, 9, "&Encoding:")
+        .control(ATOM_COMBOBOX, ID_ENCODING, combo, 70, 168, 183, 80, "")
+        .label(7, 188, 60, 9, "E&OL:")
+        .control(ATOM_COMBOBOX, ID_EOL, combo, 70, 186, 183, 80, "")
+        .label(7, 206, 60, 9, "&Language:")
+        .control(ATOM_COMBOBOX, ID_LANGUAGE, combo, 70, 204, 183, 150, "")
+        .button(IDOK, 149, 230, 50, 14, "OK", true)
+        .button(IDCANCEL, 203, 230, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = PropertiesParams {
+        info,
+        properties,
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; properties_dlg_proc is
+    // a valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(properties_dlg_proc),
+            LPARAM(&mut params as *mut PropertiesParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.properties)
+}
+
+/// Position of `value` in `all` (0 if missing).
+fn index_of<T: PartialEq>(all: &[T], value: T) -> usize {
+    all.iter().position(|v| *v == value).unwrap_or(0)
+}
+
+/// Fill combo box `id` with `names` and select `selected`.
+///
+/// # Safety
+/// `hwnd` must be the live properties dialog.
+unsafe fn fill_combo(hwnd: HWND, id: u16, names: &[&str], selected: usize) {
+    let Ok(combo) = GetDlgItem(hwnd, id as i32) else {
+        return;
+    };
+    for name in names {
+        let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(
+            combo,
+            CB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    SendMessageW(combo, CB_SETCURSEL, WPARAM(selected), LPARAM(0));
+}
+
+/// Index of the entry selected in combo box `id`.
+///
+/// # Safety
+/// `hwnd` must be the live properties dialog.
+unsafe fn combo_selection(hwnd: HWND, id: u16) -> Option<usize> {
+    let combo = GetDlgItem(hwnd, id as i32).ok()?;
+    let sel = SendMessageW(combo, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    usize::try_from(sel).ok()
+}
+
+/// Dialog procedure for `show_properties_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn properties_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const PropertiesParams);
+            let p = params.properties;
+            set_item_text(hwnd, ID_PATH as i32, &params.info.path);
+            // Paths go in read-only edits: a label would take `&` for a
+            // mnemonic, and a long path can be scrolled and copied.
+            let file = params.info.file.as_ref();
+            let none = "\u{2014}";
+            let target = file.and_then(|f| f.link_target.as_deref());
+            set_item_text(hwnd, ID_LINK_TARGET as i32, target.unwrap_or(none));
+            let resolved = file.and_then(|f| f.resolved.as_deref());
+            set_item_text(hwnd, ID_RESOLVED as i32, resolved.unwrap_or(none));
+            let encodings: Vec<&str> = Encoding::ALL.iter().map(|e| e.display_name()).collect();
+            let index = index_of(&Encoding::ALL, p.encoding);
+            fill_combo(hwnd, ID_ENCODING, &encodings, index);
+            let eols: Vec<&str> = EolMode::ALL.iter().map(|e| e.as_str()).collect();
+            fill_combo(hwnd, ID_EOL, &eols, index_of(&EolMode::ALL, p.eol));
+            let languages: Vec<&str> = Language::ALL.iter().map(|l| l.display_name()).collect();
+            let index = index_of(&Language::ALL, p.language);
+            fill_combo(hwnd, ID_LANGUAGE, &languages, index);
+            if params.info.read_only {
+                for id in [ID_ENCODING, ID_EOL] {
+                    if let Ok(combo) = GetDlgItem(hwnd, id as i32) {
+                        let _ = EnableWindow(combo, false);
+                    }
+                }
+            }
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PropertiesParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            match (wparam.0 & 0xFFFF) as u16 {
+                IDOK => {
+                    let p = &mut (*ptr).properties;
+                    if let Some(&v) =
+                        combo_selection(hwnd, ID_ENCODING).and_then(|i| Encoding::ALL.get(i))
+                    {
+                        p.encoding = v;
+                    }
+                    if let Some(&v) =
+                        combo_selection(hwnd, ID_EOL).and_then(|i| EolMode::ALL.get(i))
+                    {
+                        p.eol = v;
+                    }
+                    if let Some(&v) =
+                        combo_selection(hwnd, ID_LANGUAGE).and_then(|i| Language::ALL.get(i))
+                    {
+                        p.language = v;
+                    }
+                    (*ptr).confirmed = true;
+                    let _ = EndDialog(hwnd, 1);
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,127 @@
+// ── Per-user registry access ──────────────────────────────────────────────────
+//
+// A small safe wrapper over the `Reg*` functions for keys under
+// `HKEY_CURRENT_USER`: create a key, write string values, and delete values
+// or whole subtrees.  Keys close when `UserKey` drops.  Win32 status codes
+// surface as `io::Error`s carrying the same code.
+
+#![allow(unsafe_code)]
+
+use std::io;
+
+use windows::{
+    core::HSTRING,
+    Win32::{
+        Foundation::{ERROR_FILE_NOT_FOUND, WIN32_ERROR},
+        System::Registry::{
+            RegCloseKey, RegCreateKeyExW, RegDeleteKeyValueW, RegDeleteTreeW, RegOpenKeyExW,
+            RegSetValueExW, HKEY, HKEY_CURRENT_USER, KEY_READ, KEY_WRITE, REG_OPTION_NON_VOLATILE,
+            REG_SZ,
+        },
+    },
+};
+
+/// An open key under `HKEY_CURRENT_USER`.
+pub(crate) struct UserKey(HKEY);
+
+impl UserKey {
+    /// Open `path`, creating it (and missing parents) if needed.
+    pub(crate) fn create(path: &str) -> io::Result<Self> {
+        let mut hkey = HKEY::default();
+        // SAFETY: the path is a valid HSTRING for the duration of the call;
+        // hkey receives the opened key, which `Drop` closes.
+        let status = unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                &HSTRING::from(path),
+                0,
+                None,
+                REG_OPTION_NON_VOLATILE,
+                KEY_WRITE,
+                None,
+                &mut hkey,
+                None,
+            )
+        };
+        check(status)?;
+        Ok(Self(hkey))
+    }
+
+    /// Set string value `name` (`None` = the default value) to `data`.
+    pub(crate) fn set_string(&self, name: Option<&str>, data: &str) -> io::Result<()> {
+        let bytes: Vec<u8> = data
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let name = name.map(HSTRING::from).unwrap_or_default();
+        // SAFETY: self.0 is an open key; REG_SZ data is null-terminated UTF-16
+        // and `bytes` outlives the call.  An empty name is the default value.
+        check(unsafe { RegSetValueExW(self.0, &name, 0, REG_SZ, Some(&bytes)) })
+    }
+}
+
+impl Drop for UserKey {
+    fn drop(&mut self) {
+        // SAFETY: self.0 was opened by RegCreateKeyExW and is closed once.
+        let _ = unsafe { RegCloseKey(self.0) };
+    }
+}
+
+/// Whether `path` exists under `HKEY_CURRENT_USER`.
+pub(crate) fn user_key_exists(path: &str) -> bool {
+    let mut hkey = HKEY::default();
+    // SAFETY: the path is a valid HSTRING for the call; a key that opens is
+    // closed straight away.
+    unsafe {
+        let status = RegOpenKeyExW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(path),
+            0,
+            KEY_READ,
+            &mut hkey,
+        );
+        if status.is_err() {
+            return false;
+        }
+        let _ = RegCloseKey(hkey);
+    }
+    true
+}
+
+/// Delete `path` and everything below it.  A missing key is not an error.
+pub(crate) fn delete_user_tree(path: &str) -> io::Result<()> {
+    // SAFETY: the path is a valid HSTRING for the call.
+    let status = unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, &HSTRING::from(path)) };
+    check(ignore_missing(status))
+}
+
+/// Delete value `name` of key `path`.  A missing key or value is not an
+/// error.
+pub(crate) fn delete_user_value(path: &str, name: &str) -> io::Result<()> {
+    // SAFETY: both strings are valid HSTRINGs for the call.
+    let status = unsafe {
+        RegDeleteKeyValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(path),
+            &HSTRING::from(name),
+        )
+    };
+    check(ignore_missing(status))
+}
+
+fn ignore_missing(status: WIN32_ERROR) -> WIN32_ERROR {
+    if status == ERROR_FILE_NOT_FOUND {
+        WIN32_ERROR(0)
+    } else {
+        status
+    }
+}
+
+fn check(status: WIN32_ERROR) -> io::Result<()> {
+    if status.is_ok() {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(status.0 as i32))
+    }
+}
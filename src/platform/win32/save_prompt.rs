@@ -0,0 +1,97 @@
+// ── "Save before closing?" prompt ─────────────────────────────────────────────
+//
+// Replaces the Yes / No / Cancel message box for closing a dirty tab so the
+// user can also ask to see the unsaved changes first.  A message box cannot
+// carry a fourth, custom button, hence the small template dialog.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::WindowsAndMessaging::{
+        DialogBoxIndirectParamW, EndDialog, DLGTEMPLATE, WM_COMMAND, WM_INITDIALOG,
+    },
+};
+
+use crate::platform::win32::dlg::{
+    module_instance, DlgTemplate, ATOM_STATIC, IDCANCEL, IDOK, ID_UNUSED,
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_DISCARD: u16 = 100;
+const ID_SHOW_CHANGES: u16 = 101;
+
+/// `SS_NOPREFIX`: show `&` in file names literally.
+const SS_NOPREFIX: u32 = 0x0080;
+
+/// The user's answer to [`show_save_prompt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SaveChoice {
+    Save,
+    Discard,
+    ShowChanges,
+    Cancel,
+}
+
+/// Ask whether to save `name` before closing it.  The "Show Changes" button
+/// is offered only when `can_show_changes` (the document has a file on disk
+/// to compare with).
+pub(crate) fn show_save_prompt(
+    hwnd_parent: HWND,
+    name: &str,
+    can_show_changes: bool,
+) -> SaveChoice {
+    let text = format!("\"{name}\" has unsaved changes.\n\nSave before closing?");
+    let mut t = DlgTemplate::new("Rivet", 262, 62);
+    t.control(ATOM_STATIC, ID_UNUSED, SS_NOPREFIX, 7, 7, 248, 26, &text)
+        .button(IDOK, 36, 41, 50, 14, "&Save", true)
+        .button(ID_DISCARD, 91, 41, 50, 14, "Do&n't Save", false);
+    if can_show_changes {
+        t.button(ID_SHOW_CHANGES, 146, 41, 54, 14, "Show &Changes", false);
+    }
+    t.button(IDCANCEL, 205, 41, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; save_dlg_proc is a valid
+    // DLGPROC and keeps no state beyond the modal loop.
+    let result = unsafe {
+        DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(save_dlg_proc),
+            LPARAM(0),
+        )
+    };
+    match u16::try_from(result) {
+        Ok(IDOK) => SaveChoice::Save,
+        Ok(ID_DISCARD) => SaveChoice::Discard,
+        Ok(ID_SHOW_CHANGES) => SaveChoice::ShowChanges,
+        _ => SaveChoice::Cancel,
+    }
+}
+
+/// Dialog procedure for `show_save_prompt`: every button ends the dialog
+/// with its own ID.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn save_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    _lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => 1,
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as u16;
+            if matches!(id, IDOK | ID_DISCARD | ID_SHOW_CHANGES | IDCANCEL) {
+                let _ = EndDialog(hwnd, id as isize);
+            }
+            0
+        }
+        _ => 0,
+    }
+}
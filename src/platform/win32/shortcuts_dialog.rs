@@ -0,0 +1,247 @@
+// ── Keyboard shortcuts dialog ─────────────────────────────────────────────────
+//
+// Modal "Keyboard Shortcuts" editor: every bindable command with its chord,
+// a Shortcut edit, and a line naming the commands that share the selected
+// command's chord.  Works on a private copy of the bindings; the caller only
+// sees the edited set if the user presses OK.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::WindowsAndMessaging::{
+        DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SendMessageW,
+        SetWindowLongPtrW, DLGTEMPLATE, GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+    },
+};
+
+use crate::{
+    keymap::{self, Bindings, Chord, Command},
+    platform::win32::dlg::{
+        get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_LISTBOX, ATOM_STATIC,
+        IDCANCEL, IDOK, LBS_NOTIFY, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+    },
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_LIST: u16 = 100;
+const ID_CHORD: u16 = 101;
+const ID_CONFLICTS: u16 = 102;
+const ID_ASSIGN: u16 = 103;
+const ID_CLEAR: u16 = 104;
+const ID_DEFAULT: u16 = 105;
+
+// ── List-box messages and styles (winuser.h) ──────────────────────────────────
+
+const LB_ADDSTRING: u32 = 0x0180;
+const LB_RESETCONTENT: u32 = 0x0184;
+const LB_SETCURSEL: u32 = 0x0186;
+const LB_GETCURSEL: u32 = 0x0188;
+const LB_SETTABSTOPS: u32 = 0x0192;
+/// Expand `\t` in items to the tab stops set with `LB_SETTABSTOPS`.
+const LBS_USETABSTOPS: u32 = 0x0080;
+/// `HIWORD(wParam)` of `WM_COMMAND` when the list-box selection changes.
+const LBN_SELCHANGE: u32 = 1;
+
+/// Working state shared with `shortcuts_dlg_proc` through `GWLP_USERDATA`.
+struct ShortcutsParams<'a> {
+    commands: &'a [Command],
+    bindings: Bindings,
+    confirmed: bool,
+}
+
+/// Show the modal shortcut editor for `commands`, seeded with `bindings`.
+///
+/// Returns the edited bindings if the user pressed OK, `None` on Cancel.
+pub(crate) fn show_shortcuts_dialog(
+    hwnd_parent: HWND,
+    commands: &[Command],
+    bindings: &[Option<Chord>],
+) -> Option<Bindings> {
+    let mut t = DlgTemplate::new("Keyboard Shortcuts", 280, 200);
+    t.control(
+        ATOM_LISTBOX,
+        ID_LIST,
+        LBS_NOTIFY | LBS_USETABSTOPS | WS_BORDER | WS_VSCROLL | WS_TABSTOP,
+        7,
+        7,
+        266,
+        120,
+        "",
+    )
+    .label(7, 136, 40, 9, "&Shortcut:")
+    .edit(ID_CHORD, 50, 134, 113, 14)
+    .button(ID_ASSIGN, 168, 134, 50, 14, "&Assign", false)
+    .button(ID_CLEAR, 223, 134, 50, 14, "C&lear", false)
+    .control(ATOM_STATIC, ID_CONFLICTS, 0, 7, 154, 210, 18, "")
+    .button(ID_DEFAULT, 223, 154, 50, 14, "&Default", false)
+    .button(IDOK, 168, 179, 50, 14, "OK", true)
+    .button(IDCANCEL, 223, 179, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = ShortcutsParams {
+        commands,
+        bindings: bindings.to_vec(),
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; shortcuts_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(shortcuts_dlg_proc),
+            LPARAM(&mut params as *mut ShortcutsParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.bindings)
+}
+
+/// Repopulate the list (conflicting entries marked) and select `sel`.
+///
+/// # Safety
+/// `hwnd` must be the live shortcuts dialog.
+unsafe fn refill_list(hwnd: HWND, params: &ShortcutsParams, sel: usize) {
+    let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) else {
+        return;
+    };
+    let _ = SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for (i, command) in params.commands.iter().enumerate() {
+        let chord = params.bindings[i]
+            .map(|c| c.to_string())
+            .unwrap_or_default();
+        let mark = if keymap::conflicts_with(&params.bindings, i).is_empty() {
+            ""
+        } else {
+            "  (!)"
+        };
+        let text = format!("{}\t{chord}{mark}", command.label);
+        let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = SendMessageW(
+            list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    let _ = SendMessageW(list, LB_SETCURSEL, WPARAM(sel), LPARAM(0));
+    load_fields(hwnd, params, Some(sel));
+}
+
+/// Show command `sel`'s chord and the commands it clashes with.
+///
+/// # Safety
+/// `hwnd` must be the live shortcuts dialog.
+unsafe fn load_fields(hwnd: HWND, params: &ShortcutsParams, sel: Option<usize>) {
+    let Some(i) = sel.filter(|&i| i < params.commands.len()) else {
+        set_item_text(hwnd, ID_CHORD as i32, "");
+        set_item_text(hwnd, ID_CONFLICTS as i32, "");
+        return;
+    };
+    let chord = params.bindings[i]
+        .map(|c| c.to_string())
+        .unwrap_or_default();
+    set_item_text(hwnd, ID_CHORD as i32, &chord);
+    let others: Vec<&str> = keymap::conflicts_with(&params.bindings, i)
+        .into_iter()
+        .map(|j| params.commands[j].label)
+        .collect();
+    let note = if others.is_empty() {
+        String::new()
+    } else {
+        format!("Also used by: {}", others.join(", "))
+    };
+    set_item_text(hwnd, ID_CONFLICTS as i32, &note);
+}
+
+/// Index of the selected command, if any.
+///
+/// # Safety
+/// `hwnd` must be the live shortcuts dialog.
+unsafe fn current_sel(hwnd: HWND) -> Option<usize> {
+    let list = GetDlgItem(hwnd, ID_LIST as i32).ok()?;
+    let r = SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    (r >= 0).then_some(r as usize)
+}
+
+/// Dialog procedure for `show_shortcuts_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn shortcuts_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const ShortcutsParams);
+            if let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) {
+                // One tab stop (dialog units) separating label and chord.
+                let stops = [170u32];
+                let _ = SendMessageW(
+                    list,
+                    LB_SETTABSTOPS,
+                    WPARAM(stops.len()),
+                    LPARAM(stops.as_ptr() as isize),
+                );
+            }
+            refill_list(hwnd, params, 0);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ShortcutsParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            let params = &mut *ptr;
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let code = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            match id {
+                ID_LIST if code == LBN_SELCHANGE => {
+                    load_fields(hwnd, params, current_sel(hwnd));
+                }
+                ID_ASSIGN => {
+                    let text = get_item_text(hwnd, ID_CHORD as i32);
+                    match (current_sel(hwnd), Chord::parse(&text)) {
+                        (Some(i), Some(chord)) => {
+                            params.bindings[i] = Some(chord);
+                            refill_list(hwnd, params, i);
+                        }
+                        _ => {
+                            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+                        }
+                    }
+                }
+                ID_CLEAR => {
+                    if let Some(i) = current_sel(hwnd) {
+                        params.bindings[i] = None;
+                        refill_list(hwnd, params, i);
+                    }
+                }
+                ID_DEFAULT => {
+                    if let Some(i) = current_sel(hwnd) {
+                        params.bindings[i] = Chord::parse(params.commands[i].default);
+                        refill_list(hwnd, params, i);
+                    }
+                }
+                IDOK => {
+                    params.confirmed = true;
+                    let _ = EndDialog(hwnd, 1);
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
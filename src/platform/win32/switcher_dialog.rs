@@ -0,0 +1,331 @@
+// ── Quick tab switcher ────────────────────────────────────────────────────────
+//
+// View > Switch to Tab… (Ctrl+Shift+O): a captionless popup over the top of
+// the editor with a filter box and an owner-drawn list of open tabs (file
+// name, then the dimmed path).  Typing re-filters with `ui::switcher::filter`;
+// Up/Down/PgUp/PgDn in the filter box move the list selection, Enter switches,
+// Esc closes.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{COLORREF, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Graphics::Gdi::{
+        ClientToScreen, DrawTextW, FillRect, GetSysColor, GetSysColorBrush, SetBkMode,
+        SetTextColor, COLOR_GRAYTEXT, COLOR_HIGHLIGHT, COLOR_HIGHLIGHTTEXT, COLOR_WINDOW,
+        COLOR_WINDOWTEXT, DT_END_ELLIPSIS, DT_NOPREFIX, DT_PATH_ELLIPSIS, DT_SINGLELINE,
+        DT_VCENTER, TRANSPARENT,
+    },
+    UI::{
+        Controls::{DRAWITEMSTRUCT, ODS_SELECTED},
+        Input::KeyboardAndMouse::{VK_DOWN, VK_NEXT, VK_PRIOR, VK_UP},
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            DialogBoxIndirectParamW, EndDialog, GetClientRect, GetDlgItem, GetWindowLongPtrW,
+            GetWindowRect, SendMessageW, SetWindowLongPtrW, SetWindowPos, DLGTEMPLATE, EN_CHANGE,
+            GWLP_USERDATA, SWP_NOSIZE, SWP_NOZORDER, WM_COMMAND, WM_DRAWITEM, WM_INITDIALOG,
+            WM_KEYDOWN, WM_NCDESTROY,
+        },
+    },
+};
+
+use crate::{
+    platform::win32::dlg::{
+        get_item_text, module_instance, DlgTemplate, ATOM_LISTBOX, IDCANCEL, IDOK, LBS_NOTIFY,
+        WS_BORDER, WS_POPUP, WS_VSCROLL,
+    },
+    ui::switcher::{self, SwitcherEntry},
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_FILTER: u16 = 100;
+const ID_LIST: u16 = 101;
+
+// ── List-box messages and styles (winuser.h) ──────────────────────────────────
+
+const LB_ADDSTRING: u32 = 0x0180;
+const LB_RESETCONTENT: u32 = 0x0184;
+const LB_SETCURSEL: u32 = 0x0186;
+const LB_GETCURSEL: u32 = 0x0188;
+/// Items are drawn by the owner (`WM_DRAWITEM`) and share one height.
+const LBS_OWNERDRAWFIXED: u32 = 0x0010;
+/// Size the list exactly as given instead of to whole items.
+const LBS_NOINTEGRALHEIGHT: u32 = 0x0100;
+/// `HIWORD(wParam)` of `WM_COMMAND` when a list-box item is double-clicked.
+const LBN_DBLCLK: u32 = 2;
+
+/// `uIdSubclass` for the filter box's `SetWindowSubclass`.
+const SUBCLASS_ID: usize = 1;
+
+/// Gap in pixels between the top of the owner's client area and the popup.
+const TOP_MARGIN: i32 = 8;
+
+/// Data shared with `switcher_dlg_proc` through `GWLP_USERDATA`.
+struct SwitcherParams<'a> {
+    owner: HWND,
+    entries: &'a [SwitcherEntry],
+    /// Indices into `entries` currently listed, best match first.
+    matches: Vec<usize>,
+    /// Row selected when the dialog opens (the active tab).
+    initial: usize,
+    /// Written by the dialog procedure when the user confirms.
+    result: Option<usize>,
+}
+
+/// Show the switcher over `hwnd_parent` listing `entries`, with entry
+/// `active` selected.
+///
+/// Returns the index of the chosen entry, or `None` if the user cancelled.
+pub(crate) fn show_switcher_dialog(
+    hwnd_parent: HWND,
+    entries: &[SwitcherEntry],
+    active: usize,
+) -> Option<usize> {
+    let mut t = DlgTemplate::new("Switch to Tab", 260, 140);
+    t.set_style(WS_POPUP | WS_BORDER)
+        .edit(ID_FILTER, 4, 4, 252, 14)
+        .control(
+            ATOM_LISTBOX,
+            ID_LIST,
+            LBS_NOTIFY | LBS_OWNERDRAWFIXED | LBS_NOINTEGRALHEIGHT | WS_BORDER | WS_VSCROLL,
+            4,
+            22,
+            252,
+            114,
+            "",
+        );
+    let template = t.build();
+
+    let mut params = SwitcherParams {
+        owner: hwnd_parent,
+        entries,
+        matches: (0..entries.len()).collect(),
+        initial: active,
+        result: None,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; switcher_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(switcher_dlg_proc),
+            LPARAM(&mut params as *mut SwitcherParams as isize),
+        );
+    }
+    params.result
+}
+
+/// Place the popup centred horizontally at the top of the owner's client area.
+///
+/// # Safety
+/// `hwnd` must be the live switcher; `owner` a live window.
+unsafe fn position_over_owner(hwnd: HWND, owner: HWND) {
+    let mut client = RECT::default();
+    let mut own = RECT::default();
+    if GetClientRect(owner, &mut client).is_err() || GetWindowRect(hwnd, &mut own).is_err() {
+        return;
+    }
+    let mut origin = POINT::default();
+    let _ = ClientToScreen(owner, &mut origin);
+    let width = own.right - own.left;
+    let x = origin.x + (client.right - width).max(0) / 2;
+    let y = origin.y + TOP_MARGIN;
+    let _ = SetWindowPos(hwnd, None, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER);
+}
+
+/// Repopulate the list with `params.matches` and select row `sel`.
+///
+/// # Safety
+/// `hwnd` must be the live switcher.
+unsafe fn refill_list(hwnd: HWND, params: &SwitcherParams, sel: usize) {
+    let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) else {
+        return;
+    };
+    let _ = SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    // Owner-drawn without LBS_HASSTRINGS: the row number is all we need.
+    // Screen readers get the text `draw_row` paints as the row's name.
+    for (row, &i) in params.matches.iter().enumerate() {
+        let _ = SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(row as isize));
+        let entry = &params.entries[i];
+        let name = if entry.path.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}, {}", entry.name, entry.path)
+        };
+        crate::platform::win32::a11y::set_child_name(list, row + 1, &name);
+    }
+    if !params.matches.is_empty() {
+        let _ = SendMessageW(list, LB_SETCURSEL, WPARAM(sel), LPARAM(0));
+    }
+}
+
+/// Accept the selected row; does nothing if the filter matched no tab.
+///
+/// # Safety
+/// `hwnd` must be the live switcher.
+unsafe fn confirm(hwnd: HWND, params: &mut SwitcherParams) {
+    let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) else {
+        return;
+    };
+    let row = SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    if let Some(&i) = usize::try_from(row)
+        .ok()
+        .and_then(|r| params.matches.get(r))
+    {
+        params.result = Some(i);
+        let _ = EndDialog(hwnd, 1);
+    }
+}
+
+/// Draw one row: the file name, then its path in grey.
+///
+/// # Safety
+/// `dis` must come from a `WM_DRAWITEM` for the switcher's list.
+unsafe fn draw_row(dis: &DRAWITEMSTRUCT, params: &SwitcherParams) {
+    let Some(entry) = params
+        .matches
+        .get(dis.itemID as usize)
+        .map(|&i| &params.entries[i])
+    else {
+        return;
+    };
+    let selected = dis.itemState.0 & ODS_SELECTED.0 != 0;
+    let hdc = dis.hDC;
+    let back = if selected {
+        COLOR_HIGHLIGHT
+    } else {
+        COLOR_WINDOW
+    };
+    FillRect(hdc, &dis.rcItem, GetSysColorBrush(back));
+    SetBkMode(hdc, TRANSPARENT);
+
+    let flags = DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX;
+    let mut rc = dis.rcItem;
+    rc.left += 4;
+    rc.right -= 4;
+    let split = rc.left + (rc.right - rc.left) * 2 / 5;
+
+    let fore = if selected {
+        COLOR_HIGHLIGHTTEXT
+    } else {
+        COLOR_WINDOWTEXT
+    };
+    SetTextColor(hdc, COLORREF(GetSysColor(fore)));
+    let mut name: Vec<u16> = entry.name.encode_utf16().collect();
+    let mut name_rc = RECT {
+        right: split - 4,
+        ..rc
+    };
+    DrawTextW(hdc, &mut name, &mut name_rc, flags | DT_END_ELLIPSIS);
+
+    let dim = if selected {
+        COLOR_HIGHLIGHTTEXT
+    } else {
+        COLOR_GRAYTEXT
+    };
+    SetTextColor(hdc, COLORREF(GetSysColor(dim)));
+    let mut path: Vec<u16> = entry.path.encode_utf16().collect();
+    let mut path_rc = RECT { left: split, ..rc };
+    DrawTextW(hdc, &mut path, &mut path_rc, flags | DT_PATH_ELLIPSIS);
+}
+
+/// Subclass procedure for the filter box: list-navigation keys go to the
+/// list so the caret can stay in the filter.  `ref_data` is the list's HWND.
+///
+/// # Safety
+/// Installed by `switcher_dlg_proc`; called by Windows.
+unsafe extern "system" fn filter_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    match msg {
+        WM_KEYDOWN
+            if [VK_UP, VK_DOWN, VK_PRIOR, VK_NEXT]
+                .iter()
+                .any(|vk| usize::from(vk.0) == wparam.0) =>
+        {
+            let list = HWND(ref_data as *mut _);
+            let _ = SendMessageW(list, WM_KEYDOWN, wparam, lparam);
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(filter_subclass_proc), SUBCLASS_ID);
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Dialog procedure for `show_switcher_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn switcher_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const SwitcherParams);
+            position_over_owner(hwnd, params.owner);
+            if let (Ok(filter), Ok(list)) = (
+                GetDlgItem(hwnd, ID_FILTER as i32),
+                GetDlgItem(hwnd, ID_LIST as i32),
+            ) {
+                let _ = SetWindowSubclass(
+                    filter,
+                    Some(filter_subclass_proc),
+                    SUBCLASS_ID,
+                    list.0 as usize,
+                );
+            }
+            refill_list(hwnd, params, params.initial);
+            1 // TRUE: focus the first tab stop (the filter box)
+        }
+        WM_DRAWITEM => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const SwitcherParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            // SAFETY: WM_DRAWITEM's lparam is a DRAWITEMSTRUCT*.
+            let dis = &*(lparam.0 as *const DRAWITEMSTRUCT);
+            draw_row(dis, &*ptr);
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SwitcherParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            let params = &mut *ptr;
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let code = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            match id {
+                ID_FILTER if code == EN_CHANGE => {
+                    let query = get_item_text(hwnd, ID_FILTER as i32);
+                    params.matches = switcher::filter(&query, params.entries);
+                    refill_list(hwnd, params, 0);
+                }
+                ID_LIST if code == LBN_DBLCLK => confirm(hwnd, params),
+                IDOK => confirm(hwnd, params),
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,183 @@
+// ── Tab drag-reorder ──────────────────────────────────────────────────────────
+//
+// `SysTabControl32` has no built-in reordering, so the tab strip is subclassed
+// (comctl32 `SetWindowSubclass`).  A left-button press on a tab records it as
+// the drag source and captures the mouse; releasing over a different tab posts
+// `WM_TAB_MOVE` to the parent, which moves the document and its view.
+// Releasing outside the main window posts `WM_TAB_DETACH` instead, which
+// moves the tab into the Rivet window under the cursor or a new one.
+//
+// With tabs in the title bar, the empty strip beside the tabs is reported as
+// `HTTRANSPARENT` so the parent's `HTCAPTION` applies there (drag, Snap).
+//
+// The drag source lives in a heap cell passed as the subclass `dwRefData`;
+// it is freed when the tab control receives `WM_NCDESTROY`.
+
+#![allow(unsafe_code)]
+
+use std::cell::Cell;
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Graphics::Gdi::{ClientToScreen, PtInRect, ScreenToClient},
+    UI::{
+        Input::KeyboardAndMouse::{GetCapture, ReleaseCapture, SetCapture},
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            GetAncestor, GetParent, GetWindowRect, PostMessageW, SendMessageW, GA_ROOT, HTCAPTION,
+            HTTRANSPARENT, WM_APP, WM_CAPTURECHANGED, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_NCDESTROY,
+            WM_NCHITTEST,
+        },
+    },
+};
+
+/// Posted to the tab control's parent when a drag ends on a different tab.
+/// `WPARAM` = source index, `LPARAM` = destination index.
+pub(crate) const WM_TAB_MOVE: u32 = WM_APP + 1;
+
+/// Posted to the tab control's parent when a drag ends outside the main
+/// window.  `WPARAM` = source index, `LPARAM` = drop point in screen
+/// coordinates (x low word, y high word, both signed).
+pub(crate) const WM_TAB_DETACH: u32 = WM_APP + 4;
+
+/// `uIdSubclass` for `SetWindowSubclass`; any value unique to this proc.
+const SUBCLASS_ID: usize = 1;
+
+/// `TCM_HITTEST` (commctrl.h): `LPARAM` = `TCHITTESTINFO*`, returns the tab
+/// index under the point or -1.
+const TCM_HITTEST: u32 = 0x1300 + 13;
+
+/// Portable Rust representation of the Win32 `TCHITTESTINFO` struct.
+#[repr(C)]
+#[allow(clippy::upper_case_acronyms)]
+struct TCHITTESTINFO {
+    pt: POINT,
+    flags: u32,
+}
+
+/// Enable drag-reordering on `hwnd_tab`.
+///
+/// # Safety
+/// `hwnd_tab` must be a valid `SysTabControl32` owned by the calling thread.
+pub(crate) unsafe fn install(hwnd_tab: HWND) {
+    let source: Box<Cell<Option<usize>>> = Box::new(Cell::new(None));
+    let _ = SetWindowSubclass(
+        hwnd_tab,
+        Some(tab_subclass_proc),
+        SUBCLASS_ID,
+        Box::into_raw(source) as usize,
+    );
+}
+
+/// Unpack the signed x/y coordinates of a mouse message's `lparam`.
+pub(crate) fn point_from_lparam(lparam: LPARAM) -> POINT {
+    POINT {
+        x: (lparam.0 & 0xFFFF) as i16 as i32,
+        y: ((lparam.0 >> 16) & 0xFFFF) as i16 as i32,
+    }
+}
+
+/// Pack a point into an `LPARAM` the way mouse messages do; the inverse of
+/// [`point_from_lparam`].
+pub(crate) fn lparam_from_point(pt: POINT) -> LPARAM {
+    LPARAM(((pt.x as u16 as u32) | ((pt.y as u16 as u32) << 16)) as isize)
+}
+
+/// Index of the tab under the client-area point `pt`.
+///
+/// # Safety
+/// `hwnd` must be a valid tab control.
+unsafe fn hit_test(hwnd: HWND, pt: POINT) -> Option<usize> {
+    let mut info = TCHITTESTINFO { pt, flags: 0 };
+    let r = SendMessageW(
+        hwnd,
+        TCM_HITTEST,
+        WPARAM(0),
+        LPARAM(&mut info as *mut TCHITTESTINFO as isize),
+    );
+    (r.0 >= 0).then_some(r.0 as usize)
+}
+
+/// Subclass procedure installed by [`install`].
+///
+/// # Safety
+/// Called by comctl32 with `ref_data` set to the pointer passed in `install`.
+unsafe extern "system" fn tab_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    let source = &*(ref_data as *const Cell<Option<usize>>);
+    match msg {
+        WM_LBUTTONDOWN => {
+            // Let the control select the tab first, then start tracking.
+            let r = DefSubclassProc(hwnd, msg, wparam, lparam);
+            if let Some(idx) = hit_test(hwnd, point_from_lparam(lparam)) {
+                source.set(Some(idx));
+                SetCapture(hwnd);
+            }
+            r
+        }
+        WM_LBUTTONUP => {
+            if let Some(from) = source.take() {
+                if GetCapture() == hwnd {
+                    let _ = ReleaseCapture();
+                }
+                let pt = point_from_lparam(lparam);
+                let to = hit_test(hwnd, pt);
+                if let Some(to) = to.filter(|&to| to != from) {
+                    if let Ok(parent) = GetParent(hwnd) {
+                        let _ =
+                            PostMessageW(parent, WM_TAB_MOVE, WPARAM(from), LPARAM(to as isize));
+                    }
+                } else if to.is_none() {
+                    let mut screen = pt;
+                    let _ = ClientToScreen(hwnd, &mut screen);
+                    let mut frame = RECT::default();
+                    let _ = GetWindowRect(GetAncestor(hwnd, GA_ROOT), &mut frame);
+                    if !PtInRect(&frame, screen).as_bool() {
+                        if let Ok(parent) = GetParent(hwnd) {
+                            let _ = PostMessageW(
+                                parent,
+                                WM_TAB_DETACH,
+                                WPARAM(from),
+                                lparam_from_point(screen),
+                            );
+                        }
+                    }
+                }
+            }
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        WM_CAPTURECHANGED => {
+            // Capture lost (Alt+Tab, Esc, another window) — abandon the drag.
+            source.set(None);
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        WM_NCHITTEST => {
+            // Off the tabs, defer to the parent when it calls this spot caption.
+            let mut pt = point_from_lparam(lparam);
+            let _ = ScreenToClient(hwnd, &mut pt);
+            if hit_test(hwnd, pt).is_none() {
+                if let Ok(parent) = GetParent(hwnd) {
+                    let parent_hit = SendMessageW(parent, msg, wparam, lparam);
+                    if parent_hit.0 as u32 == HTCAPTION {
+                        return LRESULT(HTTRANSPARENT as isize);
+                    }
+                }
+            }
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(tab_subclass_proc), SUBCLASS_ID);
+            // SAFETY: ref_data came from Box::into_raw in `install`; the
+            // subclass is removed so it is never dereferenced again.
+            drop(Box::from_raw(ref_data as *mut Cell<Option<usize>>));
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
@@ -0,0 +1,165 @@
+// ── Integrated title bar ──────────────────────────────────────────────────────
+//
+// Optional chrome that places the tab strip in the caption band:
+//   • WM_NCCALCSIZE folds the caption into the client area.
+//   • DwmExtendFrameIntoClientArea keeps DWM drawing the frame and the
+//     minimise / maximise / close buttons inside that band.
+//   • WM_NCHITTEST maps the empty parts of the band back to HTCAPTION (drag,
+//     double-click to maximise, Snap) and its top edge to HTTOP (resize).
+//
+// Anything painted in the band must be black: GDI black has zero alpha, so
+// DWM composites its frame there.  The tab strip itself paints opaquely and is
+// kept clear of the caption buttons.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Graphics::{
+        Dwm::{
+            DwmDefWindowProc, DwmExtendFrameIntoClientArea, DwmGetWindowAttribute,
+            DWMWA_CAPTION_BUTTON_BOUNDS,
+        },
+        Gdi::{FillRect, GetStockObject, ScreenToClient, BLACK_BRUSH, HBRUSH, HDC},
+    },
+    UI::{
+        Controls::MARGINS,
+        HiDpi::GetSystemMetricsForDpi,
+        WindowsAndMessaging::{
+            DefWindowProcW, GetClientRect, IsZoomed, HTCAPTION, HTCLIENT, HTTOP, NCCALCSIZE_PARAMS,
+            SM_CXPADDEDBORDER, SM_CXSIZE, SM_CYCAPTION, SM_CYFRAME,
+        },
+    },
+};
+
+/// Height of the resize border above the caption at `dpi`.
+fn frame_thickness(dpi: u32) -> i32 {
+    // SAFETY: GetSystemMetricsForDpi only reads system metrics.
+    unsafe {
+        GetSystemMetricsForDpi(SM_CYFRAME, dpi) + GetSystemMetricsForDpi(SM_CXPADDEDBORDER, dpi)
+    }
+}
+
+/// Height, in client pixels, of the band that replaces the caption.
+///
+/// A maximised window has no visible top resize border, so the band is just
+/// the caption.
+pub(crate) fn band_height(hwnd: HWND, dpi: u32) -> i32 {
+    // SAFETY: GetSystemMetricsForDpi only reads system metrics; hwnd is valid.
+    let caption = unsafe { GetSystemMetricsForDpi(SM_CYCAPTION, dpi) };
+    if unsafe { IsZoomed(hwnd) }.as_bool() {
+        caption
+    } else {
+        caption + frame_thickness(dpi)
+    }
+}
+
+/// Width of the DWM caption buttons at the right of the band.
+pub(crate) fn caption_buttons_width(hwnd: HWND, dpi: u32) -> i32 {
+    let mut rc = RECT::default();
+    // SAFETY: rc is a RECT, the documented type for DWMWA_CAPTION_BUTTON_BOUNDS.
+    let ok = unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_CAPTION_BUTTON_BOUNDS,
+            &mut rc as *mut RECT as *mut _,
+            std::mem::size_of::<RECT>() as u32,
+        )
+    };
+    match ok {
+        Ok(()) if rc.right > rc.left => rc.right - rc.left,
+        // Not composited yet: assume three standard buttons.
+        // SAFETY: GetSystemMetricsForDpi only reads system metrics.
+        _ => 3 * unsafe { GetSystemMetricsForDpi(SM_CXSIZE, dpi) },
+    }
+}
+
+/// Extend the DWM frame `top` pixels into the client area (0 = standard).
+pub(crate) fn extend_frame(hwnd: HWND, top: i32) {
+    let margins = MARGINS {
+        cyTopHeight: top,
+        ..Default::default()
+    };
+    // SAFETY: hwnd is a valid top-level window; margins outlives the call.
+    unsafe {
+        let _ = DwmExtendFrameIntoClientArea(hwnd, &margins);
+    }
+}
+
+/// Erase the caption band to black so DWM composites the frame behind it.
+///
+/// # Safety
+/// `hdc` must be the device context passed with `WM_ERASEBKGND` for `hwnd`.
+pub(crate) unsafe fn paint_band(hwnd: HWND, hdc: HDC, dpi: u32) {
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    rc.bottom = rc.bottom.min(band_height(hwnd, dpi));
+    FillRect(hdc, &rc, HBRUSH(GetStockObject(BLACK_BRUSH).0));
+}
+
+/// `WM_NCCALCSIZE` while the integrated title bar is active.
+///
+/// Lets `DefWindowProcW` compute the standard frame, then moves the client
+/// top back up to the window top so the caption becomes client area.
+///
+/// # Safety
+/// Must be called from the window procedure with the message's own
+/// `wparam` / `lparam`.
+pub(crate) unsafe fn nc_calc_size(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    dpi: u32,
+) -> LRESULT {
+    if wparam.0 == 0 {
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+    // SAFETY: with wparam TRUE, lparam points to NCCALCSIZE_PARAMS.
+    let params = &mut *(lparam.0 as *mut NCCALCSIZE_PARAMS);
+    let top = params.rgrc[0].top;
+    let r = DefWindowProcW(hwnd, msg, wparam, lparam);
+    // A maximised window overhangs the monitor by the frame thickness; keep
+    // that strip off-screen rather than in the client area.
+    params.rgrc[0].top = if IsZoomed(hwnd).as_bool() {
+        top + frame_thickness(dpi)
+    } else {
+        top
+    };
+    r
+}
+
+/// `WM_NCHITTEST` while the integrated title bar is active.
+///
+/// # Safety
+/// Must be called from the window procedure with the message's own
+/// `wparam` / `lparam`.
+pub(crate) unsafe fn nc_hit_test(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    dpi: u32,
+) -> LRESULT {
+    // Caption buttons are owned by DWM.
+    let mut dwm = LRESULT(0);
+    if DwmDefWindowProc(hwnd, msg, wparam, lparam, &mut dwm).as_bool() {
+        return dwm;
+    }
+    let hit = DefWindowProcW(hwnd, msg, wparam, lparam);
+    if hit.0 as u32 != HTCLIENT {
+        return hit;
+    }
+    let mut pt = POINT {
+        x: (lparam.0 & 0xFFFF) as i16 as i32,
+        y: ((lparam.0 >> 16) & 0xFFFF) as i16 as i32,
+    };
+    let _ = ScreenToClient(hwnd, &mut pt);
+    if !IsZoomed(hwnd).as_bool() && pt.y < frame_thickness(dpi) {
+        LRESULT(HTTOP as isize)
+    } else if pt.y < band_height(hwnd, dpi) {
+        LRESULT(HTCAPTION as isize)
+    } else {
+        hit
+    }
+}
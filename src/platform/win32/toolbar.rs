@@ -0,0 +1,289 @@
+// ── Toolbar ───────────────────────────────────────────────────────────────────
+//
+// View > Toolbar: a flat `ToolbarWindow32` across the top of the main window,
+// below the caption band when tabs are in the title bar.  Each button carries
+// its menu command ID, so a click arrives as an ordinary `WM_COMMAND`.
+//
+// Icons come from comctl32's standard bitmap: the 16 px set, or the 24 px
+// set from 150% scaling up.  The window rebuilds the bar when its DPI
+// changes.  Buttons without an icon show their text; the others show it as a
+// tooltip.  In dark mode the bar is custom-drawn in the editor's colours
+// (`custom_draw`); the light theme and High Contrast use the system look.
+
+#![allow(unsafe_code)]
+
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{COLORREF, HWND, LPARAM, LRESULT, WPARAM},
+        Graphics::Gdi::{CreateSolidBrush, DeleteObject, FillRect, HFONT},
+        UI::{
+            Controls::{
+                BTNS_AUTOSIZE, BTNS_BUTTON, BTNS_CHECK, BTNS_SEP, BTNS_SHOWTEXT, CCS_NODIVIDER,
+                CCS_NOPARENTALIGN, CCS_NORESIZE, CDDS_ITEMPREPAINT, CDDS_PREPAINT, CDRF_DODEFAULT,
+                CDRF_NOTIFYITEMDRAW, IDB_STD_LARGE_COLOR, IDB_STD_SMALL_COLOR, I_IMAGENONE, NMHDR,
+                NMTBCUSTOMDRAW, NM_CUSTOMDRAW, STD_COPY, STD_CUT, STD_FILENEW, STD_FILEOPEN,
+                STD_FILESAVE, STD_FIND, STD_PASTE, STD_PROPERTIES, STD_REDOW, STD_REPLACE,
+                STD_UNDO, TBBUTTON, TBCDRF_HILITEHOTTRACK, TBCDRF_USECDCOLORS, TBSTATE_ENABLED,
+                TBSTYLE_EX_MIXEDBUTTONS, TBSTYLE_FLAT, TBSTYLE_LIST, TBSTYLE_TOOLTIPS,
+                TB_ADDBUTTONSW, TB_ADDSTRINGW, TB_BUTTONSTRUCTSIZE, TB_CHECKBUTTON,
+                TB_ENABLEBUTTON, TB_GETBUTTONSIZE, TB_LOADIMAGES, TB_SETEXTENDEDSTYLE,
+                TOOLBARCLASSNAMEW,
+            },
+            WindowsAndMessaging::{
+                CreateWindowExW, DestroyWindow, SendMessageW, HMENU, WINDOW_EX_STYLE, WINDOW_STYLE,
+                WM_SETFONT, WS_CHILD, WS_CLIPSIBLINGS, WS_VISIBLE,
+            },
+        },
+    },
+};
+
+use crate::ui::toolbar::Icon;
+
+/// `HINST_COMMCTRL` (commctrl.h): load a standard bitmap from comctl32.
+const HINST_COMMCTRL: isize = -1;
+
+/// The DPI from which the 24 px icons are used.
+const LARGE_ICON_DPI: u32 = 144;
+
+/// One button: a menu command and how it looks.
+pub(crate) struct ToolbarButton {
+    pub(crate) id: usize,
+    pub(crate) icon: Option<Icon>,
+    /// Shown on the button without an icon, as the tooltip with one.
+    pub(crate) text: String,
+    /// Stays pressed while the command is on (View > Word Wrap).
+    pub(crate) check: bool,
+}
+
+/// Colours (`COLORREF` layout) for a custom-drawn bar.
+#[derive(Clone, Copy)]
+pub(crate) struct BarColours {
+    pub(crate) background: u32,
+    pub(crate) text: u32,
+    /// Behind a hovered or checked button.
+    pub(crate) hot: u32,
+}
+
+/// The toolbar control.
+pub(crate) struct Toolbar {
+    hwnd: HWND,
+}
+
+impl Toolbar {
+    /// Create the bar inside `parent` with `buttons` (`None` = separator),
+    /// its text in `font` and icons sized for `dpi`.
+    pub(crate) fn create(
+        parent: HWND,
+        buttons: &[Option<ToolbarButton>],
+        font: HFONT,
+        dpi: u32,
+    ) -> Option<Self> {
+        let style = TBSTYLE_FLAT
+            | TBSTYLE_LIST
+            | TBSTYLE_TOOLTIPS
+            | (CCS_NORESIZE | CCS_NOPARENTALIGN | CCS_NODIVIDER) as u32;
+        // SAFETY: comctl32 registers the toolbar class when it loads; parent
+        // is the live main window.
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                TOOLBARCLASSNAMEW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WINDOW_STYLE(style),
+                0,
+                0,
+                0,
+                0,
+                parent,
+                HMENU::default(),
+                None,
+                None,
+            )
+        }
+        .ok()?;
+        let bitmap = if dpi >= LARGE_ICON_DPI {
+            IDB_STD_LARGE_COLOR
+        } else {
+            IDB_STD_SMALL_COLOR
+        };
+        // SAFETY: hwnd is the toolbar just created; these messages take
+        // plain integers or handles.
+        unsafe {
+            let _ = SendMessageW(
+                hwnd,
+                TB_BUTTONSTRUCTSIZE,
+                WPARAM(std::mem::size_of::<TBBUTTON>()),
+                LPARAM(0),
+            );
+            let _ = SendMessageW(
+                hwnd,
+                TB_SETEXTENDEDSTYLE,
+                WPARAM(0),
+                LPARAM(TBSTYLE_EX_MIXEDBUTTONS as isize),
+            );
+            let _ = SendMessageW(hwnd, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(0));
+            let _ = SendMessageW(
+                hwnd,
+                TB_LOADIMAGES,
+                WPARAM(bitmap as usize),
+                LPARAM(HINST_COMMCTRL),
+            );
+        }
+        let items: Vec<TBBUTTON> = buttons
+            .iter()
+            .map(|button| match button {
+                Some(b) => Self::item(hwnd, b),
+                None => TBBUTTON {
+                    fsStyle: BTNS_SEP as u8,
+                    ..Default::default()
+                },
+            })
+            .collect();
+        // SAFETY: items holds items.len() initialised TBBUTTONs, which the
+        // control copies.
+        unsafe {
+            let _ = SendMessageW(
+                hwnd,
+                TB_ADDBUTTONSW,
+                WPARAM(items.len()),
+                LPARAM(items.as_ptr() as isize),
+            );
+        }
+        Some(Self { hwnd })
+    }
+
+    /// The `TBBUTTON` for `b`, adding its text to the bar's string pool.
+    fn item(hwnd: HWND, b: &ToolbarButton) -> TBBUTTON {
+        // The pool takes a list ended by an empty string: two NULs.
+        let text: Vec<u16> = b.text.encode_utf16().chain([0, 0]).collect();
+        // SAFETY: text is a double-NUL-terminated UTF-16 list that the
+        // control copies; the result is the new string's index or -1.
+        let string = unsafe {
+            SendMessageW(
+                hwnd,
+                TB_ADDSTRINGW,
+                WPARAM(0),
+                LPARAM(text.as_ptr() as isize),
+            )
+        };
+        let mut style = BTNS_BUTTON | BTNS_AUTOSIZE;
+        if b.icon.is_none() {
+            style |= BTNS_SHOWTEXT;
+        }
+        if b.check {
+            style |= BTNS_CHECK;
+        }
+        TBBUTTON {
+            iBitmap: b
+                .icon
+                .map_or(I_IMAGENONE, |icon| std_bitmap_index(icon) as i32),
+            idCommand: b.id as i32,
+            fsState: TBSTATE_ENABLED as u8,
+            fsStyle: style as u8,
+            iString: string.0,
+            ..Default::default()
+        }
+    }
+
+    /// The toolbar's window handle.
+    pub(crate) fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// Height to give the bar: its buttons plus a small margin.
+    pub(crate) fn height(&self, dpi: u32) -> i32 {
+        // SAFETY: hwnd is the live toolbar; the result packs width and height.
+        let size = unsafe { SendMessageW(self.hwnd, TB_GETBUTTONSIZE, WPARAM(0), LPARAM(0)) };
+        ((size.0 >> 16) & 0xFFFF) as i32 + crate::platform::win32::dpi::scale(2, dpi)
+    }
+
+    /// Press or release the check button for command `id`, if there is one.
+    pub(crate) fn set_checked(&self, id: usize, checked: bool) {
+        // SAFETY: hwnd is the live toolbar; an unknown ID is ignored.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                TB_CHECKBUTTON,
+                WPARAM(id),
+                LPARAM(checked as isize),
+            );
+        }
+    }
+
+    /// Enable or gray the button for command `id`, if there is one.
+    pub(crate) fn set_enabled(&self, id: usize, enabled: bool) {
+        // SAFETY: hwnd is the live toolbar; an unknown ID is ignored.
+        unsafe {
+            let _ = SendMessageW(
+                self.hwnd,
+                TB_ENABLEBUTTON,
+                WPARAM(id),
+                LPARAM(enabled as isize),
+            );
+        }
+    }
+
+    /// Handle a `WM_NOTIFY` from the bar: with `colours`, paint it in them;
+    /// without, leave the drawing to the control.
+    ///
+    /// # Safety
+    /// `lparam` must be the `WM_NOTIFY` LPARAM of a notification from this
+    /// bar.
+    pub(crate) unsafe fn custom_draw(
+        &self,
+        lparam: LPARAM,
+        colours: Option<BarColours>,
+    ) -> LRESULT {
+        let hdr = &*(lparam.0 as *const NMHDR);
+        let Some(colours) = colours.filter(|_| hdr.code == NM_CUSTOMDRAW) else {
+            return LRESULT(CDRF_DODEFAULT as isize);
+        };
+        // SAFETY: NM_CUSTOMDRAW from a toolbar carries an NMTBCUSTOMDRAW.
+        let draw = &mut *(lparam.0 as *mut NMTBCUSTOMDRAW);
+        match draw.nmcd.dwDrawStage {
+            CDDS_PREPAINT => {
+                let brush = CreateSolidBrush(COLORREF(colours.background));
+                FillRect(draw.nmcd.hdc, &draw.nmcd.rc, brush);
+                let _ = DeleteObject(brush);
+                LRESULT(CDRF_NOTIFYITEMDRAW as isize)
+            }
+            CDDS_ITEMPREPAINT => {
+                draw.clrText = COLORREF(colours.text);
+                draw.clrTextHighlight = COLORREF(colours.text);
+                draw.clrBtnFace = COLORREF(colours.background);
+                draw.clrBtnHighlight = COLORREF(colours.hot);
+                draw.clrHighlightHotTrack = COLORREF(colours.hot);
+                LRESULT((TBCDRF_USECDCOLORS | TBCDRF_HILITEHOTTRACK) as isize)
+            }
+            _ => LRESULT(CDRF_DODEFAULT as isize),
+        }
+    }
+}
+
+impl Drop for Toolbar {
+    fn drop(&mut self) {
+        // SAFETY: hwnd is the toolbar created in `create`; destroying a
+        // window that is already gone fails harmlessly.
+        unsafe {
+            let _ = DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// `icon`'s position in the standard bitmap.
+fn std_bitmap_index(icon: Icon) -> u32 {
+    match icon {
+        Icon::New => STD_FILENEW,
+        Icon::Open => STD_FILEOPEN,
+        Icon::Save => STD_FILESAVE,
+        Icon::Undo => STD_UNDO,
+        Icon::Redo => STD_REDOW,
+        Icon::Cut => STD_CUT,
+        Icon::Copy => STD_COPY,
+        Icon::Paste => STD_PASTE,
+        Icon::Find => STD_FIND,
+        Icon::Replace => STD_REPLACE,
+        Icon::Properties => STD_PROPERTIES,
+    }
+}
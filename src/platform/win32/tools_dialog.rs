@@ -0,0 +1,230 @@
+// ── Tools editor dialog ───────────────────────────────────────────────────────
+//
+// Modal "Configure Tools" dialog: a list of external tools plus Name / Command
+// edits.  Works on a private copy of the tool list; the caller only sees the
+// edited list if the user presses OK.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::WindowsAndMessaging::{
+        DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SendMessageW,
+        SetWindowLongPtrW, DLGTEMPLATE, GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+    },
+};
+
+use crate::{
+    platform::win32::dlg::{
+        get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_LISTBOX, IDCANCEL, IDOK,
+        LBS_NOTIFY, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+    },
+    tools::{ExternalTool, PLACEHOLDERS},
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_LIST: u16 = 100;
+const ID_NAME: u16 = 101;
+const ID_COMMAND: u16 = 102;
+const ID_ADD: u16 = 103;
+const ID_UPDATE: u16 = 104;
+const ID_REMOVE: u16 = 105;
+
+// ── List-box messages (winuser.h) ─────────────────────────────────────────────
+
+const LB_ADDSTRING: u32 = 0x0180;
+const LB_RESETCONTENT: u32 = 0x0184;
+const LB_SETCURSEL: u32 = 0x0186;
+const LB_GETCURSEL: u32 = 0x0188;
+/// `HIWORD(wParam)` of `WM_COMMAND` when the list-box selection changes.
+const LBN_SELCHANGE: u32 = 1;
+
+/// Working state shared with `tools_dlg_proc` through `GWLP_USERDATA`.
+struct ToolsParams {
+    tools: Vec<ExternalTool>,
+    confirmed: bool,
+}
+
+/// Show the modal tools editor seeded with `tools`.
+///
+/// Returns the edited list if the user pressed OK, `None` on Cancel.
+pub(crate) fn show_tools_dialog(
+    hwnd_parent: HWND,
+    tools: &[ExternalTool],
+) -> Option<Vec<ExternalTool>> {
+    let placeholders = format!("Placeholders: {}", PLACEHOLDERS.join("  "));
+    let mut t = DlgTemplate::new("Configure Tools", 280, 167);
+    t.control(
+        ATOM_LISTBOX,
+        ID_LIST,
+        LBS_NOTIFY | WS_BORDER | WS_VSCROLL | WS_TABSTOP,
+        7,
+        7,
+        266,
+        70,
+        "",
+    )
+    .label(7, 84, 40, 9, "&Name:")
+    .edit(ID_NAME, 50, 82, 223, 14)
+    .label(7, 102, 40, 9, "&Command:")
+    .edit(ID_COMMAND, 50, 100, 223, 14)
+    .label(7, 120, 266, 18, &placeholders)
+    .button(ID_ADD, 7, 146, 50, 14, "&Add", false)
+    .button(ID_UPDATE, 62, 146, 50, 14, "&Update", false)
+    .button(ID_REMOVE, 117, 146, 50, 14, "&Remove", false)
+    .button(IDOK, 168, 146, 50, 14, "OK", true)
+    .button(IDCANCEL, 223, 146, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = ToolsParams {
+        tools: tools.to_vec(),
+        confirmed: false,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; tools_dlg_proc is a valid
+    // DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(tools_dlg_proc),
+            LPARAM(&mut params as *mut ToolsParams as isize),
+        );
+    }
+    params.confirmed.then_some(params.tools)
+}
+
+/// Repopulate the list box from `tools` and select `sel` (if in range).
+///
+/// # Safety
+/// `hwnd` must be the live tools dialog.
+unsafe fn refill_list(hwnd: HWND, tools: &[ExternalTool], sel: Option<usize>) {
+    let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) else {
+        return;
+    };
+    let _ = SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for tool in tools {
+        let wide: Vec<u16> = tool.name.encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = SendMessageW(
+            list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    let sel = sel.filter(|&i| i < tools.len());
+    // WPARAM(usize::MAX) is LB_SETCURSEL's "-1 = clear selection".
+    let _ = SendMessageW(
+        list,
+        LB_SETCURSEL,
+        WPARAM(sel.unwrap_or(usize::MAX)),
+        LPARAM(0),
+    );
+    load_fields(hwnd, tools, sel);
+}
+
+/// Copy the selected tool (or blanks) into the Name / Command edits.
+///
+/// # Safety
+/// `hwnd` must be the live tools dialog.
+unsafe fn load_fields(hwnd: HWND, tools: &[ExternalTool], sel: Option<usize>) {
+    let (name, command) = sel
+        .and_then(|i| tools.get(i))
+        .map(|t| (t.name.as_str(), t.command.as_str()))
+        .unwrap_or(("", ""));
+    set_item_text(hwnd, ID_NAME as i32, name);
+    set_item_text(hwnd, ID_COMMAND as i32, command);
+}
+
+/// Index of the selected list item, if any.
+///
+/// # Safety
+/// `hwnd` must be the live tools dialog.
+unsafe fn current_sel(hwnd: HWND) -> Option<usize> {
+    let list = GetDlgItem(hwnd, ID_LIST as i32).ok()?;
+    let r = SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    (r >= 0).then_some(r as usize)
+}
+
+/// Read the Name / Command edits; `None` (with a beep) if either is blank.
+///
+/// # Safety
+/// `hwnd` must be the live tools dialog.
+unsafe fn read_fields(hwnd: HWND) -> Option<ExternalTool> {
+    let name = get_item_text(hwnd, ID_NAME as i32).trim().to_owned();
+    let command = get_item_text(hwnd, ID_COMMAND as i32).trim().to_owned();
+    if name.is_empty() || command.is_empty() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return None;
+    }
+    Some(ExternalTool { name, command })
+}
+
+/// Dialog procedure for `show_tools_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn tools_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const ToolsParams);
+            refill_list(hwnd, &params.tools, Some(0));
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ToolsParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            let params = &mut *ptr;
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let code = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            match id {
+                ID_LIST if code == LBN_SELCHANGE => {
+                    load_fields(hwnd, &params.tools, current_sel(hwnd));
+                }
+                ID_ADD => {
+                    if let Some(tool) = read_fields(hwnd) {
+                        params.tools.push(tool);
+                        refill_list(hwnd, &params.tools, Some(params.tools.len() - 1));
+                    }
+                }
+                ID_UPDATE => {
+                    if let (Some(i), Some(tool)) = (current_sel(hwnd), read_fields(hwnd)) {
+                        params.tools[i] = tool;
+                        refill_list(hwnd, &params.tools, Some(i));
+                    }
+                }
+                ID_REMOVE => {
+                    if let Some(i) = current_sel(hwnd) {
+                        params.tools.remove(i);
+                        refill_list(
+                            hwnd,
+                            &params.tools,
+                            Some(i.min(params.tools.len().saturating_sub(1))),
+                        );
+                    }
+                }
+                IDOK => {
+                    params.confirmed = true;
+                    let _ = EndDialog(hwnd, 1);
+                }
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,129 @@
+// ── Notification-area icon ────────────────────────────────────────────────────
+//
+// With View > Minimize to Tray on, each main window keeps an icon in the
+// notification area (`Shell_NotifyIconW`) and minimizing or closing hides the
+// window there instead.  Mouse events on the icon arrive at the window as
+// `WM_TRAY_ICON`; a click restores the window, a right-click opens
+// `show_menu`.
+//
+// Explorer drops every icon when it restarts and then broadcasts the
+// registered "TaskbarCreated" message; the window adds its icon again.
+
+#![allow(unsafe_code)]
+
+use std::sync::OnceLock;
+
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{HWND, LPARAM, POINT, WPARAM},
+        UI::{
+            Shell::{
+                Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE,
+                NOTIFYICONDATAW,
+            },
+            WindowsAndMessaging::{
+                AppendMenuW, CreatePopupMenu, DestroyMenu, GetCursorPos, PostMessageW,
+                RegisterWindowMessageW, SetForegroundWindow, SetMenuDefaultItem, TrackPopupMenu,
+                MF_SEPARATOR, MF_STRING, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_APP, WM_NULL,
+            },
+        },
+    },
+};
+
+/// Sent to the window for mouse events on its icon.  `LPARAM` = the mouse
+/// message (`WM_LBUTTONUP`, `WM_RBUTTONUP`, …).
+pub(crate) const WM_TRAY_ICON: u32 = WM_APP + 5;
+
+/// `uID` of the icon; one icon per window, so the window handle tells them
+/// apart.
+const ICON_ID: u32 = 1;
+
+/// What the user picked from the icon's context menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrayChoice {
+    Restore,
+    NewFile,
+    Exit,
+}
+
+const ID_RESTORE: usize = 1;
+const ID_NEW_FILE: usize = 2;
+const ID_EXIT: usize = 3;
+
+/// The "TaskbarCreated" message Explorer broadcasts after it restarts; 0 if
+/// registration failed.
+pub(crate) fn taskbar_created_msg() -> u32 {
+    static MSG: OnceLock<u32> = OnceLock::new();
+    // SAFETY: RegisterWindowMessageW is always safe; the literal is valid UTF-16.
+    *MSG.get_or_init(|| unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) })
+}
+
+fn icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: hwnd,
+        uID: ICON_ID,
+        ..Default::default()
+    }
+}
+
+/// Add `hwnd`'s icon.  Adding an icon that is already there fails
+/// harmlessly.
+pub(crate) fn add(hwnd: HWND) {
+    let mut data = icon_data(hwnd);
+    data.uFlags = NIF_MESSAGE | NIF_ICON | NIF_TIP;
+    data.uCallbackMessage = WM_TRAY_ICON;
+    data.hIcon = crate::platform::win32::icons::app_icon(true);
+    for (dst, src) in data.szTip.iter_mut().zip("Rivet".encode_utf16()) {
+        *dst = src;
+    }
+    // SAFETY: data is fully initialised and hwnd is a live window.
+    let _ = unsafe { Shell_NotifyIconW(NIM_ADD, &data) };
+}
+
+/// Remove `hwnd`'s icon, if it has one.
+pub(crate) fn remove(hwnd: HWND) {
+    let data = icon_data(hwnd);
+    // SAFETY: data identifies the icon by window and ID; a missing icon
+    // makes the call fail harmlessly.
+    let _ = unsafe { Shell_NotifyIconW(NIM_DELETE, &data) };
+}
+
+/// Show the icon's context menu at the cursor and return the choice.
+pub(crate) fn show_menu(hwnd: HWND) -> Option<TrayChoice> {
+    // SAFETY: the menu is created, tracked, and destroyed here; hwnd is a
+    // live window that owns the tracking.
+    unsafe {
+        let menu = CreatePopupMenu().ok()?;
+        let _ = AppendMenuW(menu, MF_STRING, ID_RESTORE, w!("&Restore"));
+        let _ = AppendMenuW(menu, MF_STRING, ID_NEW_FILE, w!("&New File"));
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+        let _ = AppendMenuW(menu, MF_STRING, ID_EXIT, w!("E&xit"));
+        let _ = SetMenuDefaultItem(menu, ID_RESTORE as u32, 0);
+
+        let mut pt = POINT::default();
+        let _ = GetCursorPos(&mut pt);
+        // The window must be in the foreground or the menu will not close
+        // when the user clicks elsewhere; the WM_NULL afterwards is the
+        // documented companion to that.
+        let _ = SetForegroundWindow(hwnd);
+        let cmd = TrackPopupMenu(
+            menu,
+            TPM_RETURNCMD | TPM_RIGHTBUTTON,
+            pt.x,
+            pt.y,
+            0,
+            hwnd,
+            None,
+        );
+        let _ = PostMessageW(hwnd, WM_NULL, WPARAM(0), LPARAM(0));
+        let _ = DestroyMenu(menu);
+        match cmd.0 as usize {
+            ID_RESTORE => Some(TrayChoice::Restore),
+            ID_NEW_FILE => Some(TrayChoice::NewFile),
+            ID_EXIT => Some(TrayChoice::Exit),
+            _ => None,
+        }
+    }
+}
@@ -6,42 +6,84 @@
 //   • WM_CREATE  → load SciLexer.dll + create Scintilla + tab bar + status bar.
 //   • WM_SIZE    → resize children to fill the client area (three-zone layout).
 //   • WM_DESTROY → drop WindowState (SciDll::drop calls FreeLibrary).
-//   • WM_COMMAND → File > New/Open/Save/Save As/Exit, Help > About.
+//   • WM_COMMAND → translated to a `commands::Command` and run (see `run_command`).
 //   • WM_NOTIFY  → Scintilla notifications + TCN_SELCHANGE (tab switch).
 //   • WM_TIMER   → periodic 30-second session checkpoint.
+//   • WM_COPYDATA → requests from other Rivet windows (see `ipc`).
+//   • WM_TAB_MOVE → a finished drag in the tab strip (see `tab_drag`).
+//   • WM_TAB_DETACH → a tab dropped outside the window moves to another one.
+//   • WM_TRAY_ICON → clicks on the notification-area icon (see `tray`).
+//   • WM_HOTKEY  → the global scratchpad hot key (see `crate::scratch`).
+//   • WM_LOAD_PROGRESS → chunks from a Large File Mode load (see `loader`).
+//   • WM_REPLACE_PROGRESS → a background Replace All (see `search::replace`).
+//   • WM_TASK    → a result or progress update from a background job on the
+//     task pool (see `crate::tasks` and `bridge`).
+//   • WM_FIND_ALL / WM_REPLACE_ALL_OPEN → the extra Find / Replace dialog
+//     button (see `dialogs`).
+//   • WM_NCCALCSIZE / WM_NCHITTEST → tabs in the title bar (see `titlebar`).
 //   • Expose a safe error-dialog helper for main().
 //
 // State threading: a `Box<WindowState>` is stored in GWLP_USERDATA.
 // It is set in WM_CREATE, read in WM_SIZE/NOTIFY/COMMAND, freed in WM_DESTROY.
-// All accesses happen on the single UI thread.
+// All accesses happen on the single UI thread.  Several main windows (File >
+// New Window) each have their own state and share one `SciDll` load and one
+// message loop; the oldest open window owns the session file.
 
 #![allow(unsafe_code)]
 #![allow(dangerous_implicit_autorefs)]
 
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
 use windows::{
     core::{w, PCWSTR, PWSTR},
     Win32::{
-        Foundation::{GetLastError, HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM},
-        Graphics::Gdi::{GetStockObject, UpdateWindow, HBRUSH, WHITE_BRUSH},
+        Foundation::{
+            GetLastError, COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM,
+        },
+        Graphics::Gdi::{
+            ClientToScreen, CreateSolidBrush, DeleteObject, FillRect, GetStockObject,
+            InvalidateRect, UpdateWindow, HBRUSH, HDC, HFONT, WHITE_BRUSH,
+        },
         System::{Diagnostics::Debug::MessageBeep, LibraryLoader::GetModuleHandleW},
         UI::{
-            Controls::Dialogs::{FindTextW, ReplaceTextW, FINDREPLACEW, FINDREPLACE_FLAGS},
+            Controls::{
+                Dialogs::{FindTextW, ReplaceTextW, FINDREPLACEW, FINDREPLACE_FLAGS},
+                SetWindowTheme,
+            },
+            Input::KeyboardAndMouse::{
+                GetFocus, GetKeyState, RegisterHotKey, SetFocus, UnregisterHotKey, MOD_ALT,
+                MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, VK_CONTROL, VK_ESCAPE, VK_MENU,
+                VK_SHIFT, VK_TAB,
+            },
             WindowsAndMessaging::{
-                AppendMenuW, CheckMenuItem, CreateAcceleratorTableW, CreateMenu, CreateWindowExW,
-                DefWindowProcW, DestroyWindow, DialogBoxIndirectParamW, DispatchMessageW,
-                EndDialog, GetClientRect, GetDlgItem, GetDlgItemTextW, GetMenu, GetMessageW,
-                GetWindowLongPtrW, IsDialogMessageW, KillTimer, LoadCursorW, LoadIconW,
-                MessageBoxW, PostQuitMessage, RegisterClassExW, RegisterWindowMessageW,
-                SendMessageW, SetDlgItemTextW, SetForegroundWindow, SetMenu, SetTimer,
-                SetWindowLongPtrW, SetWindowPos, SetWindowTextW, ShowWindow, TranslateAcceleratorW,
-                TranslateMessage, ACCEL, ACCEL_VIRT_FLAGS, CW_USEDEFAULT, DLGTEMPLATE, FCONTROL,
-                FSHIFT, FVIRTKEY, GWL_STYLE, GWLP_USERDATA, HACCEL, HMENU, IDC_ARROW,
-                IDI_APPLICATION, IDNO, IDYES, MB_ICONERROR, MB_ICONWARNING, MB_OK, MB_YESNO,
-                MB_YESNOCANCEL, MESSAGEBOX_STYLE, MF_BYCOMMAND, MF_CHECKED, MF_POPUP,
-                MF_SEPARATOR, MF_STRING, MF_UNCHECKED, MSG, SWP_FRAMECHANGED, SWP_NOACTIVATE,
-                SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SW_SHOW, WINDOW_EX_STYLE, WINDOW_STYLE,
-                WM_CLOSE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_INITDIALOG, WM_NOTIFY, WM_SIZE,
-                WM_TIMER, WNDCLASSEXW, WNDCLASS_STYLES, WS_CHILD, WS_CLIPSIBLINGS,
+                AppendMenuW, CheckMenuItem, CreateAcceleratorTableW, CreateMenu, CreatePopupMenu,
+                CreateWindowExW, DefWindowProcW, DeleteMenu, DestroyAcceleratorTable, DestroyMenu,
+                DestroyWindow, DialogBoxIndirectParamW, DispatchMessageW, EnableMenuItem,
+                EndDialog, FlashWindowEx, GetAncestor, GetClientRect, GetDlgItem, GetDlgItemTextW,
+                GetMenu, GetMenuItemCount, GetMenuStringW, GetMessageW, GetParent,
+                GetWindowLongPtrW, IsDialogMessageW, IsIconic, IsWindowVisible, KillTimer,
+                LoadCursorW, MessageBoxW, PostMessageW, PostQuitMessage, RegisterClassExW,
+                RegisterWindowMessageW, SendMessageW, SetDlgItemTextW, SetForegroundWindow,
+                SetMenu, SetMenuItemInfoW, SetParent, SetTimer, SetWindowLongPtrW, SetWindowPos,
+                SetWindowTextW, ShowWindow, TrackPopupMenu, TranslateAcceleratorW,
+                TranslateMessage, WindowFromPoint, ACCEL, CW_USEDEFAULT, DLGTEMPLATE, FALT,
+                FCONTROL, FLASHWINFO, FLASHW_ALL, FSHIFT, FVIRTKEY, GA_ROOT, GWLP_USERDATA,
+                GWL_STYLE, HACCEL, HMENU, IDC_ARROW, IDNO, IDRETRY, IDYES, MB_ICONERROR,
+                MB_ICONINFORMATION, MB_ICONQUESTION, MB_ICONWARNING, MB_OK, MB_RETRYCANCEL,
+                MB_YESNO, MB_YESNOCANCEL, MENUITEMINFOW, MESSAGEBOX_STYLE, MF_BYCOMMAND,
+                MF_BYPOSITION, MF_CHECKED, MF_ENABLED, MF_GRAYED, MF_POPUP, MF_SEPARATOR,
+                MF_STRING, MF_UNCHECKED, MIIM_STRING, MSG, SC_KEYMENU, SC_MINIMIZE,
+                SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SW_HIDE,
+                SW_RESTORE, SW_SHOW, TPM_RETURNCMD, TPM_RIGHTBUTTON, WINDOW_EX_STYLE, WINDOW_STYLE,
+                WM_APP, WM_CLIPBOARDUPDATE, WM_CLOSE, WM_COMMAND, WM_CONTEXTMENU, WM_CREATE,
+                WM_DESTROY, WM_DWMCOLORIZATIONCOLORCHANGED, WM_ENDSESSION, WM_ERASEBKGND,
+                WM_EXITMENULOOP, WM_HOTKEY, WM_INITDIALOG, WM_INITMENUPOPUP, WM_KEYDOWN,
+                WM_LBUTTONUP, WM_NCCALCSIZE, WM_NCHITTEST, WM_NOTIFY, WM_RBUTTONUP, WM_SETFONT,
+                WM_SETTINGCHANGE, WM_SIZE, WM_SYSCOLORCHANGE, WM_SYSCOMMAND, WM_TIMER, WNDCLASSEXW,
+                WNDCLASS_STYLES, WS_CHILD, WS_CLIPCHILDREN, WS_CLIPSIBLINGS, WS_EX_COMPOSITED,
                 WS_OVERLAPPEDWINDOW, WS_VISIBLE,
             },
         },
@@ -49,16 +91,37 @@ use windows::{
 };
 
 use crate::{
-    app::{App, EolMode},
+    app::{App, Degraded, Encoding, EolMode},
+    commands::{self, Command as Cmd, Outcome},
+    config::{NotFoundFeedback, RestoreSession},
     editor::scintilla::{
         messages::{
-            SCFIND_MATCHCASE, SCFIND_WHOLEWORD, SCN_SAVEPOINTLEFT, SCN_SAVEPOINTREACHED,
-            SCN_UPDATEUI,
+            SCFIND_MATCHCASE, SCFIND_WHOLEWORD, SCN_AUTOCCOMPLETED, SCN_CHARADDED, SCN_MODIFIED,
+            SCN_SAVEPOINTLEFT, SCN_SAVEPOINTREACHED, SCN_UPDATEUI, SC_MOD_BEFOREDELETE,
+            SC_MOD_BEFOREINSERT, SC_MOD_DELETETEXT, SC_MOD_INSERTTEXT, SC_PERFORMED_UNDO,
         },
-        SciDll, ScintillaView,
+        NotifyHeader, SciDll, ScintillaView,
     },
-    error::{Result, RivetError},
-    platform::win32::dialogs::{show_open_dialog, show_save_dialog},
+    editor::{line_ops::LineOp, viewer::FileWindow, EditorView, HUGE_FILE_THRESHOLD_BYTES},
+    error::{FileProblem, Result, RivetError},
+    keymap::{Bindings, Chord, Command},
+    loader::{LoadEvent, Loader, StreamDecoder},
+    platform::win32::{
+        autoscroll,
+        bridge::{self, UiSender, WM_TASK},
+        dialogs::{self, show_open_dialog, show_save_dialog},
+        document_map,
+        folder_panel::{FolderPanel, PanelCommand},
+        ipc,
+        progress::StatusProgress,
+        save_prompt::{self, SaveChoice},
+        tab_drag, titlebar,
+        toolbar::{BarColours, Toolbar, ToolbarButton},
+        tray,
+    },
+    search::{replace::ReplaceJob, Needle},
+    tail::{Tail, TailEvent},
+    transform::Transform,
 };
 
 // ── Window identity ───────────────────────────────────────────────────────────
@@ -78,6 +141,17 @@ const IDM_FILE_OPEN: usize = 1001;
 const IDM_FILE_SAVE: usize = 1002;
 const IDM_FILE_SAVE_AS: usize = 1003;
 const IDM_FILE_CLOSE: usize = 1004;
+const IDM_FILE_RELOAD: usize = 1005;
+const IDM_FILE_SAVE_WORKSPACE: usize = 1006;
+const IDM_FILE_OPEN_WORKSPACE: usize = 1007;
+const IDM_FILE_RESTORE_ALWAYS: usize = 1008;
+const IDM_FILE_RESTORE_NEVER: usize = 1009;
+const IDM_FILE_RESTORE_ASK: usize = 1010;
+const IDM_FILE_NEW_WINDOW: usize = 1011;
+const IDM_FILE_KEEP_UNSAVED: usize = 1012;
+const IDM_FILE_OPEN_FOLDER: usize = 1013;
+const IDM_FILE_PROPERTIES: usize = 1014;
+const IDM_FILE_GOTO: usize = 1015;
 const IDM_FILE_EXIT: usize = 1099;
 
 const IDM_EDIT_UNDO: usize = 2000;
@@ -87,29 +161,662 @@ const IDM_EDIT_COPY: usize = 2003;
 const IDM_EDIT_PASTE: usize = 2004;
 const IDM_EDIT_DELETE: usize = 2005;
 const IDM_EDIT_SELECT_ALL: usize = 2006;
+const IDM_EDIT_COPY_PATH: usize = 2007;
+const IDM_EDIT_COMPLETE_WORD: usize = 2008;
+const IDM_EDIT_AUTO_CLOSE: usize = 2009;
+const IDM_EDIT_LINE_COMMENT: usize = 2010;
+const IDM_EDIT_BLOCK_COMMENT: usize = 2011;
+const IDM_EDIT_COPY_RTF: usize = 2012;
+const IDM_EDIT_COPY_HTML: usize = 2013;
+const IDM_EDIT_PASTE_HISTORY: usize = 2014;
+const IDM_EDIT_COLUMN_EDITOR: usize = 2015;
+const IDM_EDIT_JOIN_LINES: usize = 2016;
+const IDM_EDIT_SPLIT_LINES: usize = 2017;
+const IDM_EDIT_REMOVE_DUPLICATES: usize = 2018;
+const IDM_EDIT_REMOVE_CONSECUTIVE_DUPLICATES: usize = 2019;
+const IDM_EDIT_REMOVE_EMPTY: usize = 2020;
+const IDM_EDIT_REMOVE_BLANK: usize = 2021;
+const IDM_EDIT_SNIPPETS: usize = 2022;
+const IDM_EDIT_CLEAR_UNDO: usize = 2023;
 
 const IDM_FORMAT_EOL_CRLF: usize = 3000;
 const IDM_FORMAT_EOL_LF: usize = 3001;
 const IDM_FORMAT_EOL_CR: usize = 3002;
+const IDM_FORMAT_ADD_BOM: usize = 3003;
+const IDM_FORMAT_REMOVE_BOM: usize = 3004;
+/// Format > Encoding: encoding `i` of `Encoding::ALL` is
+/// `IDM_FORMAT_ENCODING_FIRST + i`, and its Reopen With item (code pages
+/// only) `IDM_FORMAT_REOPEN_FIRST + i`.
+const IDM_FORMAT_ENCODING_FIRST: usize = 3100;
+const IDM_FORMAT_REOPEN_FIRST: usize = 3200;
 
 const IDM_VIEW_WORD_WRAP: usize = 4000;
 const IDM_VIEW_DARK_MODE: usize = 4001;
 const IDM_VIEW_TAB_TOP: usize = 4002;
 const IDM_VIEW_TAB_LEFT: usize = 4003;
 const IDM_VIEW_TAB_RIGHT: usize = 4004;
+const IDM_VIEW_AUTOHIDE_MENU: usize = 4005;
+const IDM_VIEW_TITLEBAR_TABS: usize = 4006;
+const IDM_VIEW_CARET: usize = 4007;
+const IDM_VIEW_NEXT_CHUNK: usize = 4008;
+const IDM_VIEW_PREV_CHUNK: usize = 4009;
+const IDM_VIEW_ACCENT: usize = 4010;
+const IDM_VIEW_SPEAK_POSITION: usize = 4011;
+const IDM_VIEW_MONITOR: usize = 4012;
+const IDM_VIEW_RELATIVE_PATHS: usize = 4013;
+const IDM_VIEW_REFRESH_HIGHLIGHTING: usize = 4014;
+const IDM_VIEW_SWITCH_TAB: usize = 4015;
+const IDM_VIEW_TRAY: usize = 4016;
+const IDM_VIEW_OCCURRENCES: usize = 4017;
+const IDM_VIEW_MULTILINE_TABS: usize = 4018;
+const IDM_VIEW_FOLDER_PANEL: usize = 4019;
+const IDM_VIEW_OUTLINE: usize = 4020;
+const IDM_VIEW_DOCUMENT_MAP: usize = 4021;
+const IDM_VIEW_NEXT_PANE: usize = 4022;
+const IDM_VIEW_PREV_PANE: usize = 4023;
+const IDM_VIEW_FOLLOW_THEME: usize = 4024;
+const IDM_VIEW_TOOLBAR: usize = 4025;
+const IDM_VIEW_TAB_ICONS: usize = 4026;
+const IDM_VIEW_LONG_LINES: usize = 4027;
 
 const IDM_SEARCH_FIND: usize = 5000;
 const IDM_SEARCH_REPLACE: usize = 5001;
 const IDM_SEARCH_FIND_NEXT: usize = 5002;
 const IDM_SEARCH_FIND_PREV: usize = 5003;
 const IDM_SEARCH_GOTO_LINE: usize = 5004;
+const IDM_SEARCH_FEEDBACK_BEEP: usize = 5005;
+const IDM_SEARCH_FEEDBACK_STATUS: usize = 5006;
+const IDM_SEARCH_FEEDBACK_TASKBAR: usize = 5007;
+const IDM_SEARCH_FEEDBACK_SILENT: usize = 5008;
+const IDM_SEARCH_SELECT_MATCHES: usize = 5009;
+const IDM_SEARCH_ADD_MATCHES: usize = 5010;
+const IDM_SEARCH_FIND_SELECTED: usize = 5011;
+const IDM_SEARCH_FIND_ALL: usize = 5012;
+const IDM_SEARCH_CLOSE_RESULTS: usize = 5013;
+const IDM_SEARCH_REPLACE_ALL_OPEN: usize = 5014;
+const IDM_SEARCH_EXTENDED: usize = 5015;
+const IDM_SEARCH_NEXT_CHANGE: usize = 5016;
+const IDM_SEARCH_PREV_CHANGE: usize = 5017;
+
+const IDM_TOOLS_RUN: usize = 6000;
+const IDM_TOOLS_CONFIGURE: usize = 6001;
+const IDM_TOOLS_SHORTCUTS: usize = 6002;
+const IDM_TOOLS_COMPARE_FILE: usize = 6003;
+const IDM_TOOLS_COMPARE_TAB: usize = 6004;
+const IDM_TOOLS_ASSOCIATIONS: usize = 6005;
+const IDM_TOOLS_BASE64_ENCODE: usize = 6006;
+const IDM_TOOLS_BASE64_DECODE: usize = 6007;
+const IDM_TOOLS_URL_ENCODE: usize = 6008;
+const IDM_TOOLS_URL_DECODE: usize = 6009;
+const IDM_TOOLS_HTML_ESCAPE: usize = 6010;
+const IDM_TOOLS_HTML_UNESCAPE: usize = 6011;
+const IDM_TOOLS_FORMAT_JSON: usize = 6012;
+const IDM_TOOLS_MINIFY_JSON: usize = 6013;
+const IDM_TOOLS_GENERATE_HASH: usize = 6014;
+const IDM_TOOLS_TEXT_PIPELINE: usize = 6015;
+/// First command ID of the user-defined tool entries; tool `i` is
+/// `IDM_TOOLS_FIRST + i`.
+const IDM_TOOLS_FIRST: usize = 6100;
+/// Upper bound on the number of tool entries placed in the menu.
+const MAX_TOOLS: usize = 100;
 
 const IDM_HELP_ABOUT: usize = 9001;
 
+/// The status bar's Cancel button (not a menu item).
+const IDC_CANCEL_TASK: usize = 9100;
+
+// ── Command registry ──────────────────────────────────────────────────────────
+
+/// Every command that can carry a keyboard shortcut, in the order the
+/// Keyboard Shortcuts dialog lists them.  `name` is the key in
+/// `keymap.json`; menu items show the bound chord after a tab.
+const COMMANDS: &[Command] = &[
+    Command {
+        id: IDM_FILE_NEW,
+        name: "file.new",
+        label: "File: New",
+        default: "Ctrl+N",
+    },
+    Command {
+        id: IDM_FILE_NEW_WINDOW,
+        name: "file.new_window",
+        label: "File: New Window",
+        default: "Ctrl+Shift+N",
+    },
+    Command {
+        id: IDM_FILE_OPEN,
+        name: "file.open",
+        label: "File: Open",
+        default: "Ctrl+O",
+    },
+    Command {
+        id: IDM_FILE_RELOAD,
+        name: "file.reload",
+        label: "File: Reload from Disk",
+        default: "Ctrl+R",
+    },
+    Command {
+        id: IDM_FILE_SAVE,
+        name: "file.save",
+        label: "File: Save",
+        default: "Ctrl+S",
+    },
+    Command {
+        id: IDM_FILE_SAVE_AS,
+        name: "file.save_as",
+        label: "File: Save As",
+        default: "",
+    },
+    Command {
+        id: IDM_FILE_OPEN_FOLDER,
+        name: "file.open_folder",
+        label: "File: Open Folder",
+        default: "",
+    },
+    Command {
+        id: IDM_FILE_GOTO,
+        name: "file.goto",
+        label: "File: Go to File",
+        default: "Ctrl+P",
+    },
+    Command {
+        id: IDM_FILE_OPEN_WORKSPACE,
+        name: "file.open_workspace",
+        label: "File: Open Workspace",
+        default: "",
+    },
+    Command {
+        id: IDM_FILE_SAVE_WORKSPACE,
+        name: "file.save_workspace",
+        label: "File: Save Workspace",
+        default: "",
+    },
+    Command {
+        id: IDM_FILE_CLOSE,
+        name: "file.close",
+        label: "File: Close Tab",
+        default: "Ctrl+W",
+    },
+    Command {
+        id: IDM_FILE_PROPERTIES,
+        name: "file.properties",
+        label: "File: Document Properties",
+        default: "Alt+Enter",
+    },
+    Command {
+        id: IDM_EDIT_UNDO,
+        name: "edit.undo",
+        label: "Edit: Undo",
+        default: "Ctrl+Z",
+    },
+    Command {
+        id: IDM_EDIT_REDO,
+        name: "edit.redo",
+        label: "Edit: Redo",
+        default: "Ctrl+Y",
+    },
+    Command {
+        id: IDM_EDIT_CLEAR_UNDO,
+        name: "edit.clear_undo_history",
+        label: "Edit: Clear Undo History",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_CUT,
+        name: "edit.cut",
+        label: "Edit: Cut",
+        default: "Ctrl+X",
+    },
+    Command {
+        id: IDM_EDIT_COPY,
+        name: "edit.copy",
+        label: "Edit: Copy",
+        default: "Ctrl+C",
+    },
+    Command {
+        id: IDM_EDIT_PASTE,
+        name: "edit.paste",
+        label: "Edit: Paste",
+        default: "Ctrl+V",
+    },
+    Command {
+        id: IDM_EDIT_PASTE_HISTORY,
+        name: "edit.paste_history",
+        label: "Edit: Paste from History",
+        default: "Ctrl+Shift+V",
+    },
+    Command {
+        id: IDM_EDIT_SELECT_ALL,
+        name: "edit.select_all",
+        label: "Edit: Select All",
+        default: "Ctrl+A",
+    },
+    Command {
+        id: IDM_EDIT_COLUMN_EDITOR,
+        name: "edit.column_editor",
+        label: "Edit: Column Editor",
+        default: "Alt+C",
+    },
+    Command {
+        id: IDM_EDIT_COMPLETE_WORD,
+        name: "edit.complete_word",
+        label: "Edit: Complete Word",
+        default: "Ctrl+Space",
+    },
+    Command {
+        id: IDM_EDIT_SNIPPETS,
+        name: "edit.snippets",
+        label: "Edit: Edit Snippets",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_LINE_COMMENT,
+        name: "edit.line_comment",
+        label: "Edit: Toggle Line Comment",
+        default: "Ctrl+/",
+    },
+    Command {
+        id: IDM_EDIT_BLOCK_COMMENT,
+        name: "edit.block_comment",
+        label: "Edit: Toggle Block Comment",
+        default: "Ctrl+Shift+/",
+    },
+    Command {
+        id: IDM_EDIT_JOIN_LINES,
+        name: "edit.join_lines",
+        label: "Edit: Join Lines",
+        default: "Ctrl+J",
+    },
+    Command {
+        id: IDM_EDIT_SPLIT_LINES,
+        name: "edit.split_lines",
+        label: "Edit: Split Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_REMOVE_DUPLICATES,
+        name: "edit.remove_duplicate_lines",
+        label: "Edit: Remove Duplicate Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_REMOVE_CONSECUTIVE_DUPLICATES,
+        name: "edit.remove_consecutive_duplicate_lines",
+        label: "Edit: Remove Consecutive Duplicate Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_REMOVE_EMPTY,
+        name: "edit.remove_empty_lines",
+        label: "Edit: Remove Empty Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_REMOVE_BLANK,
+        name: "edit.remove_blank_lines",
+        label: "Edit: Remove Empty and Blank Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_AUTO_CLOSE,
+        name: "edit.auto_close",
+        label: "Edit: Auto-Close Brackets",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_COPY_PATH,
+        name: "edit.copy_path",
+        label: "Edit: Copy Full Path",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_COPY_RTF,
+        name: "edit.copy_rtf",
+        label: "Edit: Copy as RTF",
+        default: "",
+    },
+    Command {
+        id: IDM_EDIT_COPY_HTML,
+        name: "edit.copy_html",
+        label: "Edit: Copy as HTML",
+        default: "",
+    },
+    Command {
+        id: IDM_FORMAT_EOL_CRLF,
+        name: "format.eol_crlf",
+        label: "Format: Convert to Windows (CRLF)",
+        default: "",
+    },
+    Command {
+        id: IDM_FORMAT_EOL_LF,
+        name: "format.eol_lf",
+        label: "Format: Convert to Unix (LF)",
+        default: "",
+    },
+    Command {
+        id: IDM_FORMAT_EOL_CR,
+        name: "format.eol_cr",
+        label: "Format: Convert to Classic Mac (CR)",
+        default: "",
+    },
+    Command {
+        id: IDM_FORMAT_ADD_BOM,
+        name: "format.add_bom",
+        label: "Format: Add BOM",
+        default: "",
+    },
+    Command {
+        id: IDM_FORMAT_REMOVE_BOM,
+        name: "format.remove_bom",
+        label: "Format: Remove BOM",
+        default: "",
+    },
+    Command {
+        id: IDM_SEARCH_FIND,
+        name: "search.find",
+        label: "Search: Find",
+        default: "Ctrl+F",
+    },
+    Command {
+        id: IDM_SEARCH_REPLACE,
+        name: "search.replace",
+        label: "Search: Replace",
+        default: "Ctrl+H",
+    },
+    Command {
+        id: IDM_SEARCH_REPLACE_ALL_OPEN,
+        name: "search.replace_all_open",
+        label: "Search: Replace All in All Opened Documents",
+        default: "",
+    },
+    Command {
+        id: IDM_SEARCH_FIND_NEXT,
+        name: "search.find_next",
+        label: "Search: Find Next",
+        default: "F3",
+    },
+    Command {
+        id: IDM_SEARCH_FIND_PREV,
+        name: "search.find_prev",
+        label: "Search: Find Previous",
+        default: "Shift+F3",
+    },
+    Command {
+        id: IDM_SEARCH_FIND_SELECTED,
+        name: "search.find_selected",
+        label: "Search: Find Selected Word",
+        default: "Ctrl+F3",
+    },
+    Command {
+        id: IDM_SEARCH_FIND_ALL,
+        name: "search.find_all",
+        label: "Search: Find All in Document",
+        default: "Ctrl+Alt+F",
+    },
+    Command {
+        id: IDM_SEARCH_CLOSE_RESULTS,
+        name: "search.close_results",
+        label: "Search: Close Results",
+        default: "Shift+Esc",
+    },
+    Command {
+        id: IDM_SEARCH_SELECT_MATCHES,
+        name: "search.select_matches",
+        label: "Search: Select All Matches",
+        default: "Ctrl+Shift+L",
+    },
+    Command {
+        id: IDM_SEARCH_ADD_MATCHES,
+        name: "search.add_matches",
+        label: "Search: Add All Matches to Selection",
+        default: "Ctrl+Enter",
+    },
+    Command {
+        id: IDM_SEARCH_GOTO_LINE,
+        name: "search.goto_line",
+        label: "Search: Go to Line",
+        default: "Ctrl+G",
+    },
+    Command {
+        id: IDM_SEARCH_NEXT_CHANGE,
+        name: "search.next_change",
+        label: "Search: Next Change",
+        default: "Ctrl+Alt+Down",
+    },
+    Command {
+        id: IDM_SEARCH_PREV_CHANGE,
+        name: "search.prev_change",
+        label: "Search: Previous Change",
+        default: "Ctrl+Alt+Up",
+    },
+    Command {
+        id: IDM_VIEW_WORD_WRAP,
+        name: "view.word_wrap",
+        label: "View: Word Wrap",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_MONITOR,
+        name: "view.monitor",
+        label: "View: Monitor",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_TOOLBAR,
+        name: "view.toolbar",
+        label: "View: Toolbar",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_TAB_ICONS,
+        name: "view.tab_icons",
+        label: "View: File Icons in Tabs",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_FOLDER_PANEL,
+        name: "view.folder_panel",
+        label: "View: Folder Panel",
+        default: "Ctrl+Shift+E",
+    },
+    Command {
+        id: IDM_VIEW_OUTLINE,
+        name: "view.outline",
+        label: "View: Outline Panel",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_DOCUMENT_MAP,
+        name: "view.document_map",
+        label: "View: Document Map",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_NEXT_PANE,
+        name: "view.next_pane",
+        label: "View: Focus Next Pane",
+        default: "F6",
+    },
+    Command {
+        id: IDM_VIEW_PREV_PANE,
+        name: "view.prev_pane",
+        label: "View: Focus Previous Pane",
+        default: "Shift+F6",
+    },
+    Command {
+        id: IDM_VIEW_SWITCH_TAB,
+        name: "view.switch_tab",
+        label: "View: Switch to Tab",
+        default: "Ctrl+Shift+O",
+    },
+    Command {
+        id: IDM_VIEW_REFRESH_HIGHLIGHTING,
+        name: "view.refresh_highlighting",
+        label: "View: Refresh Highlighting",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_LONG_LINES,
+        name: "view.long_lines",
+        label: "View: Highlight and Wrap Long Lines",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_RELATIVE_PATHS,
+        name: "view.relative_paths",
+        label: "View: Workspace-Relative Paths",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_DARK_MODE,
+        name: "view.dark_mode",
+        label: "View: Dark Mode",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_FOLLOW_THEME,
+        name: "view.follow_system_theme",
+        label: "View: Follow System Theme",
+        default: "",
+    },
+    Command {
+        id: IDM_VIEW_SPEAK_POSITION,
+        name: "view.speak_position",
+        label: "View: Speak Cursor Position",
+        default: "Ctrl+Shift+I",
+    },
+    Command {
+        id: IDM_VIEW_NEXT_CHUNK,
+        name: "view.next_chunk",
+        label: "View: Next Chunk",
+        default: "Alt+PgDn",
+    },
+    Command {
+        id: IDM_VIEW_PREV_CHUNK,
+        name: "view.prev_chunk",
+        label: "View: Previous Chunk",
+        default: "Alt+PgUp",
+    },
+    Command {
+        id: IDM_TOOLS_RUN,
+        name: "tools.run",
+        label: "Tools: Run",
+        default: "F5",
+    },
+    Command {
+        id: IDM_TOOLS_CONFIGURE,
+        name: "tools.configure",
+        label: "Tools: Configure Tools",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_SHORTCUTS,
+        name: "tools.shortcuts",
+        label: "Tools: Keyboard Shortcuts",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_COMPARE_FILE,
+        name: "tools.compare_file",
+        label: "Tools: Compare With File",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_COMPARE_TAB,
+        name: "tools.compare_tab",
+        label: "Tools: Compare With Tab",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_ASSOCIATIONS,
+        name: "tools.associations",
+        label: "Tools: File Associations",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_BASE64_ENCODE,
+        name: "tools.base64_encode",
+        label: "Tools: Base64 Encode",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_BASE64_DECODE,
+        name: "tools.base64_decode",
+        label: "Tools: Base64 Decode",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_URL_ENCODE,
+        name: "tools.url_encode",
+        label: "Tools: URL Encode",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_URL_DECODE,
+        name: "tools.url_decode",
+        label: "Tools: URL Decode",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_HTML_ESCAPE,
+        name: "tools.html_escape",
+        label: "Tools: HTML Escape",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_HTML_UNESCAPE,
+        name: "tools.html_unescape",
+        label: "Tools: HTML Unescape",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_FORMAT_JSON,
+        name: "tools.format_json",
+        label: "Tools: Format JSON",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_MINIFY_JSON,
+        name: "tools.minify_json",
+        label: "Tools: Minify JSON",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_GENERATE_HASH,
+        name: "tools.generate_hash",
+        label: "Tools: Generate Hash",
+        default: "",
+    },
+    Command {
+        id: IDM_TOOLS_TEXT_PIPELINE,
+        name: "tools.text_pipeline",
+        label: "Tools: Text Pipeline",
+        default: "",
+    },
+];
+
 // ── Auto-save timer ───────────────────────────────────────────────────────────
 
 /// `nIDEvent` passed to `SetTimer` for the periodic session checkpoint.
 const AUTOSAVE_TIMER_ID: usize = 1;
+
+/// `RegisterHotKey` ID of the global scratchpad hot key.
+const SCRATCH_HOTKEY_ID: i32 = 1;
+/// `nIDEvent` for polling monitored (tail -f) files; runs only while at least
+/// one tab is monitored.
+const MONITOR_TIMER_ID: usize = 2;
+const MONITOR_INTERVAL_MS: u32 = 1000;
+/// `nIDEvent` that ends a "Not found" status-bar flash.
+const STATUS_FLASH_TIMER_ID: usize = 3;
+const STATUS_FLASH_MS: u32 = 1500;
+/// `nIDEvent` that refreshes the occurrence highlight once the caret has
+/// rested for `OCCURRENCE_DELAY_MS`.
+const OCCURRENCE_TIMER_ID: usize = 4;
+const OCCURRENCE_DELAY_MS: u32 = 250;
+/// `nIDEvent` that refreshes the active tab's git gutter once editing has
+/// paused for `GIT_DELAY_MS`.
+const GIT_TIMER_ID: usize = 5;
+const GIT_DELAY_MS: u32 = 1000;
 /// Auto-save interval in milliseconds (30 seconds).
 const AUTOSAVE_INTERVAL_MS: u32 = 30_000;
 
@@ -122,17 +829,57 @@ const FR_FINDNEXT: u32 = 0x0008;
 const FR_REPLACE: u32 = 0x0010;
 const FR_REPLACEALL: u32 = 0x0020;
 const FR_DIALOGTERM: u32 = 0x0040;
-
-/// Virtual key code for the F3 key (used in accelerator table).
-const VK_F3: u16 = 0x72;
+const FR_SHOWHELP: u32 = 0x0080; // repurposed as Find All, see `dialogs::find_hook`
+const FR_ENABLEHOOK: u32 = 0x0100;
 
 // ── Registered message ID for the modeless Find/Replace dialog ────────────────
 
-/// Populated once in `run()` via `RegisterWindowMessageW("commdlg_FindReplace")`.
+/// Populated on first use via `RegisterWindowMessageW("commdlg_FindReplace")`.
 /// Every WM_* value dispatched through the message loop is compared against this
 /// before the standard `match msg { … }` to intercept Find/Replace notifications.
 static FIND_MSG_ID: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
 
+/// The Find/Replace notification message, registered on first use.  The ID
+/// is the same for every window in the process; 0 if registration failed.
+fn find_msg_id() -> u32 {
+    // SAFETY: RegisterWindowMessageW is always safe; the literal is valid UTF-16.
+    *FIND_MSG_ID.get_or_init(|| unsafe { RegisterWindowMessageW(w!("commdlg_FindReplace")) })
+}
+
+// ── Top-level windows ─────────────────────────────────────────────────────────
+
+thread_local! {
+    /// Every open main window, oldest first.  Added in WM_CREATE, removed in
+    /// WM_DESTROY; the message loop ends when the last one goes.
+    static WINDOWS: RefCell<Vec<HWND>> = const { RefCell::new(Vec::new()) };
+    /// The `SciLexer.dll` load shared by every window.  Each `WindowState`
+    /// holds a strong reference, so the DLL is freed after the last window.
+    static SCI_DLL: RefCell<Weak<SciDll>> = const { RefCell::new(Weak::new()) };
+}
+
+/// The shared `SciLexer.dll` load, loading it if no window holds it.
+fn shared_sci_dll() -> Result<Rc<SciDll>> {
+    SCI_DLL.with(|cell| {
+        if let Some(dll) = cell.borrow().upgrade() {
+            return Ok(dll);
+        }
+        let dll = Rc::new(SciDll::load()?);
+        *cell.borrow_mut() = Rc::downgrade(&dll);
+        Ok(dll)
+    })
+}
+
+/// Whether `hwnd` is one of this process's main windows.
+fn is_main_window(hwnd: HWND) -> bool {
+    WINDOWS.with(|w| w.borrow().contains(&hwnd))
+}
+
+/// Whether `hwnd` writes the session file: only the oldest open window does,
+/// on behalf of every window (see `save_session`).
+fn owns_session(hwnd: HWND) -> bool {
+    WINDOWS.with(|w| w.borrow().first() == Some(&hwnd))
+}
+
 // ── Tab bar ───────────────────────────────────────────────────────────────────
 
 /// Win32 window class for the common-controls tab control.
@@ -141,9 +888,36 @@ const TAB_CLASS: PCWSTR = w!("SysTabControl32");
 /// Baseline height of the tab strip at 96 DPI; scaled by actual DPI at runtime.
 const TAB_BAR_BASE_H: i32 = 25;
 
+/// Height of the Find All results panel at 96 DPI; at most half the window.
+const RESULTS_PANEL_BASE_H: i32 = 160;
+
+/// Width of the folder panel at 96 DPI; at most half the window.
+const FOLDER_PANEL_BASE_W: i32 = 220;
+
+/// Width of the outline panel at 96 DPI; at most a third of the window.
+const OUTLINE_PANEL_BASE_W: i32 = 220;
+
+/// Width of the document map at 96 DPI; at most a third of the editor.
+const DOCUMENT_MAP_BASE_W: i32 = 110;
+
 /// `WM_DPICHANGED` — sent when the window moves to a monitor with a different DPI.
 const WM_DPICHANGED: u32 = 0x02E0;
 
+/// Posted by a loader thread each time it queues an event (see `loader`).
+/// `WM_APP + 1` is `tab_drag::WM_TAB_MOVE`, `+ 4` `tab_drag::WM_TAB_DETACH`
+/// `+ 5` `tray::WM_TRAY_ICON`, `+ 6` `dialogs::WM_FIND_ALL`, `+ 7`
+/// `dialogs::WM_REPLACE_ALL_OPEN`, `+ 8`
+/// `document_map::WM_DOCUMENT_MAP_SCROLL`, `+ 10` `dialogs::WM_SELECT_MATCHES`
+/// and `+ 11` `bridge::WM_TASK`.
+const WM_LOAD_PROGRESS: u32 = WM_APP + 2;
+
+/// Posted by the Replace All worker as it progresses and when it finishes.
+const WM_REPLACE_PROGRESS: u32 = WM_APP + 3;
+
+/// Sent by the message loop for Tab / Shift+Tab (`wparam` 1 / 0) in an
+/// editor view (`lparam`); returns non-zero if a snippet used the key.
+const WM_SNIPPET_TAB: u32 = WM_APP + 9;
+
 /// `DWMWA_USE_IMMERSIVE_DARK_MODE` attribute ID for `DwmSetWindowAttribute`.
 const DWMWA_DARK_MODE: i32 = 20;
 
@@ -154,9 +928,21 @@ const TCM_DELETEITEM: u32 = TCM_FIRST + 8; // 0x1308  (used in Phase 4d)
 const TCM_GETCURSEL: u32 = TCM_FIRST + 11; // 0x130B
 const TCM_SETCURSEL: u32 = TCM_FIRST + 12; // 0x130C
 const TCM_SETITEMW: u32 = TCM_FIRST + 61; // 0x133D
+const TCM_GETTOOLTIPS: u32 = TCM_FIRST + 45; // 0x132D
+const TCM_GETITEMRECT: u32 = TCM_FIRST + 10; // 0x130A
+const TCM_GETROWCOUNT: u32 = TCM_FIRST + 44; // 0x132C
+const TCM_SETIMAGELIST: u32 = TCM_FIRST + 3; // 0x1303
 
 // Tab-control notifications.
 const TCN_SELCHANGE: u32 = 0xFFFF_FDD9; // (-551i32 as u32)
+/// Sent by the tab strip's tooltip for the text of tab `idFrom`.
+const TTN_GETDISPINFOW: u32 = 0xFFFF_FDEE; // (-530i32 as u32)
+
+/// Tab-control style: show a tooltip per tab (answered via `TTN_GETDISPINFOW`).
+const TCS_TOOLTIPS: u32 = 0x4000;
+/// Tab-control style: wrap tabs onto extra rows instead of showing scroll
+/// arrows.
+const TCS_MULTILINE: u32 = 0x0200;
 
 // Tab-control styles for side-positioned tab bars.
 /// Draws tabs vertically along the left edge of the tab control.
@@ -169,6 +955,7 @@ const TAB_BAR_SIDE_W_BASE: i32 = 160;
 
 // Tab-control item flags / styles.
 const TCIF_TEXT: u32 = 0x0001;
+const TCIF_IMAGE: u32 = 0x0002;
 
 /// Portable Rust representation of the Win32 `TCITEMW` struct.
 ///
@@ -198,6 +985,11 @@ const SBARS_SIZEGRIP: u32 = 0x0100;
 /// 0x0401 is the ANSI version; 0x040B is the Unicode version (WM_USER+11).
 const SB_SETTEXT: u32 = 0x040B;
 
+/// `SB_GETTEXTLENGTHW` / `SB_GETTEXTW` — read back a part's text (Unicode).
+/// LOWORD of the length result is the character count.
+const SB_GETTEXTLENGTH: u32 = 0x040C;
+const SB_GETTEXT: u32 = 0x040D;
+
 /// `SB_SETPARTS` message — sets the number of parts and their right-edge pixel
 /// positions.  WPARAM = part count; LPARAM = pointer to i32 array of edges.
 /// A right-edge of -1 means "extend to the end of the bar".
@@ -209,6 +1001,10 @@ const SB_PART_ENCODING_W_BASE: i32 = 120;
 const SB_PART_EOL_W_BASE: i32 = 60;
 /// Width of the language part at 96 DPI baseline (e.g. "JavaScript").
 const SB_PART_LANG_W_BASE: i32 = 130;
+/// Width of the document-size part at 96 DPI baseline (e.g. "123.4 MB").
+const SB_PART_SIZE_W_BASE: i32 = 90;
+/// Width of the insert / overtype part at 96 DPI baseline ("INS" / "OVR").
+const SB_PART_MODE_W_BASE: i32 = 45;
 
 // ── Tab position ──────────────────────────────────────────────────────────────
 
@@ -251,15 +1047,17 @@ impl TabPosition {
 /// Rust drops struct fields in declaration order:
 ///   1. `app`       — pure Rust, no handles
 ///   2. `sci_views` — child HWNDs already destroyed by Windows before WM_DESTROY
-///   3. `sci_dll`   — `FreeLibrary` fires here, safely after all views are gone
+///   3. `sci_dll`   — the last window's reference fires `FreeLibrary`, safely
+///      after all views are gone
 ///   4. `hwnd_tab`, `hwnd_status` — HWND values only, no cleanup needed
 struct WindowState {
     /// Top-level application state (documents, active tab index, …).
     app: App,
     /// One Scintilla child window per open tab; parallel to `app.tabs`.
     sci_views: Vec<ScintillaView>,
-    /// RAII owner of `SciLexer.dll`; must outlive every `ScintillaView`.
-    sci_dll: SciDll,
+    /// Shared owner of `SciLexer.dll` (see `shared_sci_dll`); must outlive
+    /// every `ScintillaView`.
+    sci_dll: Rc<SciDll>,
     /// The Win32 `SysTabControl32` tab strip child window.
     hwnd_tab: HWND,
     /// The Win32 `msctls_statusbar32` status bar child window.
@@ -268,15 +1066,20 @@ struct WindowState {
     /// Current display DPI; initialised to 96, updated in `post_create_init`
     /// and `WM_DPICHANGED`.
     dpi: u32,
+    /// Tab strip and status bar font, created for `dpi`; owned (deleted when
+    /// replaced and in `WM_DESTROY`).
+    ui_font: HFONT,
     /// Whether dark mode is currently active; persisted in `session.json`.
     dark_mode: bool,
+    /// The Windows system colours while they replace the light / dark
+    /// palettes (High Contrast mode, or `settings.high_contrast`).
+    system_colours: Option<crate::theme::SystemColours>,
     /// Where the tab bar is rendered; persisted in `session.json`.
     tab_position: TabPosition,
     // ── Phase 6: Find / Replace state ─────────────────────────────────────────
     /// Heap-stable UTF-16 buffer for the Find text (pointed to by `findreplace`).
     find_buf: Box<[u16; 512]>,
     /// Heap-stable UTF-16 buffer for the Replace text.
-    #[allow(dead_code)]
     replace_buf: Box<[u16; 512]>,
     /// Shared `FINDREPLACEW` struct — passed to `FindTextW` / `ReplaceTextW`.
     /// Its `lpstrFindWhat` and `lpstrReplaceWith` pointers into the boxes above
@@ -284,6 +1087,168 @@ struct WindowState {
     findreplace: FINDREPLACEW,
     /// HWND of the open modeless Find (or Replace) dialog, or `HWND::default()`.
     hwnd_find_dlg: HWND,
+    /// The pane that had the focus when Find or Replace was last invoked, so
+    /// the dialog keeps searching it while the focus is in the dialog.
+    find_target: HWND,
+    // ── User configuration ────────────────────────────────────────────────────
+    /// Preferences loaded from `settings.json` at startup.
+    settings: crate::config::Settings,
+    /// The Tools popup menu; rebuilt whenever the tool list changes.
+    tools_menu: HMENU,
+    /// The menu bar.  Kept here because it is detached from the window
+    /// (`GetMenu` returns null) while auto-hidden.
+    menu_bar: HMENU,
+    /// Large File Mode loads still streaming in.
+    loads: Vec<PendingLoad>,
+    /// Tabs showing a window of a file too large to load.
+    viewers: Vec<ViewerTab>,
+    /// The Replace All running in the background, if any.
+    replace: Option<PendingReplace>,
+    /// Runs short background jobs (`spawn_task`); started by the first.
+    tasks: Option<crate::tasks::Pool>,
+    /// Git gutter refreshes still running, oldest first.
+    git_jobs: Vec<PendingGitGutter>,
+    /// ID for the next git gutter refresh.
+    next_git_job: u64,
+    /// Writes session checkpoints off the UI thread; started by the first.
+    session_writer: Option<crate::session::writer::SessionWriter>,
+    /// Snippets of the languages used so far.
+    snippets: crate::editor::snippets::SnippetCache,
+    /// The view and tab stops of the snippet being filled in, if any.
+    tab_stops: Option<(HWND, crate::editor::snippets::TabStops)>,
+    /// Tabs following appends to their file (View > Monitor).
+    monitors: Vec<MonitoredTab>,
+    /// Chord bound to each entry of `COMMANDS`, from `keymap.json`.
+    bindings: Bindings,
+    /// Accelerator table built from `bindings`; rebuilt when they change.
+    accel: HACCEL,
+    /// Folder of the named workspace last opened or saved, if any.
+    workspace_root: Option<std::path::PathBuf>,
+    /// Text handed to the tab tooltip by `TTN_GETDISPINFOW`.
+    tooltip_buf: Vec<u16>,
+    /// Recently opened and saved files, shown in the taskbar jump list.
+    recent: crate::recent::RecentFiles,
+    /// Set by the tray menu's Exit so the `WM_CLOSE` it sends exits instead
+    /// of hiding to the tray.
+    quitting: bool,
+    /// What the occurrence highlight was last computed for, so an unchanged
+    /// caret word is not searched for again.
+    occurrences: Option<OccurrenceKey>,
+    /// The Find All results panel, created on first use.
+    results: Option<ResultsPanel>,
+    /// The toolbar while it is shown.
+    toolbar: Option<Toolbar>,
+    /// The status bar's progress bar and Cancel button for a running task.
+    progress: Option<StatusProgress>,
+    /// The folder panel while it is shown.
+    folder: Option<FolderPanel>,
+    /// The outline panel while it is shown.
+    outline: Option<OutlinePanel>,
+    /// The document map while it is shown.
+    map: Option<DocumentMap>,
+    /// What Edit > Column Editor… last inserted, to seed the dialog.
+    column_fill: crate::editor::column::Fill,
+    /// Recipe last applied with Tools > Text Pipeline….
+    pipeline_recipe: String,
+    /// Whether the last text change a view reported was an undo, so
+    /// `SCN_SAVEPOINTLEFT` can tell undoing past the save from editing.
+    last_change_undone: bool,
+}
+
+/// The docked Find All panel: a read-only Scintilla view listing the hits,
+/// one row each after a summary row.
+struct ResultsPanel {
+    view: ScintillaView,
+    visible: bool,
+    /// The view searched (tracked by handle, like `PendingLoad`).
+    source: HWND,
+    /// The searched document's path (or title), for copied results.
+    label: String,
+    hits: Vec<crate::search::find_all::Hit>,
+    /// Length of each match, for selecting it.
+    match_len: usize,
+    /// The row last jumped to, so re-reporting the same caret does nothing.
+    row: usize,
+}
+
+/// The docked outline panel: a read-only Scintilla view listing the active
+/// document's functions or headings, one row each.
+struct OutlinePanel {
+    view: ScintillaView,
+    /// The view outlined (tracked by handle, like `ResultsPanel`).
+    source: HWND,
+    /// The line each row jumps to.
+    lines: Vec<usize>,
+    /// The row last jumped to, so re-reporting the same caret does nothing.
+    row: usize,
+}
+
+/// The document map: a Scintilla view sharing the active tab's document at
+/// the smallest zoom, with the editor's visible lines selected.
+struct DocumentMap {
+    view: ScintillaView,
+    /// The view whose document is shown (tracked by handle, like
+    /// `ResultsPanel`), and the language it was coloured for.
+    source: HWND,
+    language: crate::languages::Language,
+}
+
+/// The view, word, word position and document length behind the current
+/// occurrence highlight.
+#[derive(PartialEq, Eq)]
+struct OccurrenceKey {
+    view: HWND,
+    word: Vec<u8>,
+    start: usize,
+    doc_len: usize,
+}
+
+/// A tab in View > Monitor mode.  The view stays in the chunked-load state
+/// (read-only, no undo) so appends never mark it dirty.
+struct MonitoredTab {
+    /// The view being followed (tracked by handle, like `PendingLoad`).
+    view: HWND,
+    tail: Tail,
+}
+
+/// A Replace All, or a line-ending conversion in Large File Mode, computing
+/// on a worker thread.  The view is read-only until the result is applied,
+/// so it cannot go stale.
+struct PendingReplace {
+    /// The view being replaced in (tracked by handle, like `PendingLoad`).
+    view: HWND,
+    job: ReplaceJob,
+    /// The target line ending of a conversion; `None` for Replace All.
+    eol: Option<EolMode>,
+}
+
+/// A git gutter refresh diffing a view's text against HEAD on the task pool.
+/// Only the newest one for a view is applied.
+struct PendingGitGutter {
+    /// The view being diffed (tracked by handle, like `PendingLoad`).
+    view: HWND,
+    /// Matches the refresh to its result.
+    id: u64,
+}
+
+/// A read-only tab showing one window of a huge file.
+struct ViewerTab {
+    /// The view showing the window (tracked by handle, like `PendingLoad`).
+    view: HWND,
+    window: FileWindow,
+}
+
+/// A Large File Mode load streaming into one Scintilla view.
+struct PendingLoad {
+    /// The receiving view.  Tabs can move or close while the load runs, so
+    /// the view is tracked by handle rather than index.
+    view: HWND,
+    loader: Loader,
+    decoder: StreamDecoder,
+    /// Whether EOL / indentation / language were detected yet.
+    sampled: bool,
+    /// Switch the tab to read-only when done (see `handle_file_open`).
+    read_only: bool,
 }
 
 // ── Public entry points ───────────────────────────────────────────────────────
@@ -296,6 +1261,12 @@ pub(crate) fn run() -> Result<()> {
     #[cfg(debug_assertions)]
     let t0 = std::time::Instant::now();
 
+    // Report panics and unhandled exceptions from here on.
+    crate::platform::win32::crash::install();
+
+    // COM, for the taskbar jump list.
+    crate::platform::win32::jumplist::init();
+
     // Per-Monitor v2 DPI awareness — must be set before any window is created.
     crate::platform::win32::dpi::init();
 
@@ -310,7 +1281,6 @@ pub(crate) fn run() -> Result<()> {
 
     register_class(hinstance)?;
     let hwnd = create_window(hinstance)?;
-    let haccel = create_accelerators()?;
 
     // SAFETY: hwnd was returned by CreateWindowExW and is valid.
     // ShowWindow / UpdateWindow return values are intentionally unused.
@@ -325,14 +1295,7 @@ pub(crate) fn run() -> Result<()> {
         t0.elapsed().as_secs_f64() * 1000.0
     );
 
-    // Register the custom message that FindTextW / ReplaceTextW send to the
-    // owner window.  The ID is process-unique and must be checked in wnd_proc
-    // before the standard match on msg.
-    // SAFETY: RegisterWindowMessageW is always safe; the literal is valid UTF-16.
-    let find_msg = unsafe { RegisterWindowMessageW(w!("commdlg_FindReplace")) };
-    if find_msg != 0 {
-        let _ = FIND_MSG_ID.set(find_msg);
-    }
+    let args = crate::cli::Args::from_env();
 
     // Restore the previous session.
     // SAFETY: WM_CREATE (fired synchronously inside create_window) already
@@ -340,11 +1303,18 @@ pub(crate) fn run() -> Result<()> {
     unsafe {
         let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
         if !ptr.is_null() {
-            restore_session(hwnd, &mut *ptr);
+            let state = &mut *ptr;
+            restore_session(hwnd, state, args.clean);
+            // Files named on the command line (e.g. picked from the jump
+            // list) open on top of the restored session.
+            for file in args.files {
+                open_file_at(hwnd, state, file);
+            }
+            refresh_jump_list(state);
         }
     }
 
-    message_loop(hwnd, haccel)
+    message_loop()
 }
 
 /// Show a modal "Fatal Error" dialog.  Safe to call from `main()`.
@@ -367,10 +1337,92 @@ pub(crate) fn show_error_dialog(message: &str) {
     }
 }
 
+/// What the user chose after a file could not be read or written.
+enum FileErrorChoice {
+    /// Try the same operation again.
+    Retry,
+    /// Pick another path to save to.
+    SaveAs,
+    Cancel,
+}
+
+/// Tell the user that `verb`ing ("open", "save", …) `path` failed, and why
+/// in terms they can act on.  Offers Retry when the file is in use or its
+/// share unreachable, and a Save As when a save was refused access; a save
+/// blocked by another program offers both, naming the program.  Any other
+/// failure is only reported.
+///
+/// # Safety
+/// `hwnd` must be the main window.
+unsafe fn file_error_prompt(
+    hwnd: HWND,
+    verb: &str,
+    path: &std::path::Path,
+    problem: FileProblem,
+    detail: &str,
+) -> FileErrorChoice {
+    let saving = verb == "save";
+    let mut text = problem.message(verb, path, detail);
+    // The box's style, and what its Yes and No buttons mean.
+    let (style, yes, no) = if saving && problem == FileProblem::InUse {
+        let holders = crate::platform::win32::lock_holders::holders(path);
+        if !holders.is_empty() {
+            text.push_str("\n\nIt is open in:");
+            for holder in &holders {
+                text.push_str(&format!("\n    {} (process {})", holder.name, holder.pid));
+            }
+        }
+        text.push_str(
+            "\n\nYes \u{2014} try again\n\
+             No \u{2014} save it somewhere else\n\
+             Cancel \u{2014} do nothing",
+        );
+        let style = MB_YESNOCANCEL | MB_ICONWARNING;
+        (style, FileErrorChoice::Retry, FileErrorChoice::SaveAs)
+    } else if saving && problem == FileProblem::AccessDenied {
+        text.push_str("\n\nSave it somewhere else?");
+        let style = MB_YESNO | MB_ICONWARNING;
+        (style, FileErrorChoice::SaveAs, FileErrorChoice::Cancel)
+    } else if problem.retry_may_help() {
+        let style = MB_RETRYCANCEL | MB_ICONWARNING;
+        (style, FileErrorChoice::Cancel, FileErrorChoice::Cancel)
+    } else {
+        show_error_dialog(&text);
+        return FileErrorChoice::Cancel;
+    };
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: wide is valid null-terminated UTF-16 that outlives the call.
+    match MessageBoxW(hwnd, PCWSTR(wide.as_ptr()), w!("Rivet"), style) {
+        r if r == IDRETRY => FileErrorChoice::Retry,
+        r if r == IDYES => yes,
+        r if r == IDNO => no,
+        _ => FileErrorChoice::Cancel,
+    }
+}
+
+/// Read all of `path` for `verb` ("open", "reload"), offering Retry while it
+/// is in use or unreachable.  `None` once the user gives up; they have been
+/// told why.
+///
+/// # Safety
+/// `hwnd` must be the main window.
+unsafe fn read_file_prompting(hwnd: HWND, verb: &str, path: &std::path::Path) -> Option<Vec<u8>> {
+    loop {
+        match std::fs::read(path) {
+            Ok(bytes) => return Some(bytes),
+            Err(e) => {
+                match file_error_prompt(hwnd, verb, path, FileProblem::of(&e), &e.to_string()) {
+                    FileErrorChoice::Retry => {}
+                    FileErrorChoice::SaveAs | FileErrorChoice::Cancel => return None,
+                }
+            }
+        }
+    }
+}
+
 // ── Window class + creation ───────────────────────────────────────────────────
 
 fn register_class(hinstance: HINSTANCE) -> Result<()> {
-    let icon = unsafe { LoadIconW(None, IDI_APPLICATION) }.map_err(RivetError::from)?;
     let cursor = unsafe { LoadCursorW(None, IDC_ARROW) }.map_err(RivetError::from)?;
 
     // SAFETY: GetStockObject(WHITE_BRUSH) always returns a valid HGDIOBJ.
@@ -383,12 +1435,12 @@ fn register_class(hinstance: HINSTANCE) -> Result<()> {
         cbClsExtra: 0,
         cbWndExtra: 0,
         hInstance: hinstance,
-        hIcon: icon,
+        hIcon: crate::platform::win32::icons::app_icon(false),
         hCursor: cursor,
         hbrBackground: bg_brush,
         lpszMenuName: PCWSTR::null(),
         lpszClassName: CLASS_NAME,
-        hIconSm: icon,
+        hIconSm: crate::platform::win32::icons::app_icon(true),
     };
 
     // SAFETY: wndclass is fully initialised with valid handles.
@@ -411,7 +1463,8 @@ fn create_window(hinstance: HINSTANCE) -> Result<HWND> {
             WINDOW_EX_STYLE(0),
             CLASS_NAME,
             APP_TITLE,
-            WS_OVERLAPPEDWINDOW,
+            // Clip children so erasing the background never paints over them.
+            WS_OVERLAPPEDWINDOW | WS_CLIPCHILDREN,
             CW_USEDEFAULT,
             CW_USEDEFAULT,
             init_w,
@@ -423,10 +1476,6 @@ fn create_window(hinstance: HINSTANCE) -> Result<HWND> {
         )
     }
     .map_err(RivetError::from)?;
-
-    let menu = build_menu()?;
-    // SAFETY: hwnd and menu are valid handles.
-    unsafe { SetMenu(hwnd, menu) }.map_err(RivetError::from)?;
     Ok(hwnd)
 }
 
@@ -438,18 +1487,21 @@ fn create_window(hinstance: HINSTANCE) -> Result<HWND> {
 /// abort window creation.
 fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<WindowState> {
     // ── Scintilla DLL ─────────────────────────────────────────────────────────
-    // Loading the DLL registers the "Scintilla" window class.
-    let sci_dll = SciDll::load()?;
+    // Loading the DLL registers the "Scintilla" window class.  Later windows
+    // share the first window's load.
+    let sci_dll = shared_sci_dll()?;
 
     // ── Tab bar ───────────────────────────────────────────────────────────────
     // Initial geometry (0,0,0,0); WM_SIZE will resize it correctly.
     // SAFETY: TAB_CLASS is a valid PCWSTR literal; hwnd_parent is valid.
+    // WS_EX_COMPOSITED double-buffers the strip so repaints during resizing
+    // and tab switches don't flicker.
     let hwnd_tab = unsafe {
         CreateWindowExW(
-            WINDOW_EX_STYLE(0),
+            WS_EX_COMPOSITED,
             TAB_CLASS,
             PCWSTR::null(),
-            WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS,
+            WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WINDOW_STYLE(TCS_TOOLTIPS),
             0,
             0,
             0,
@@ -464,14 +1516,17 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
 
     // ── Scintilla view (initial tab) ──────────────────────────────────────────
     let sci = ScintillaView::create(hwnd_parent, hinstance, &sci_dll)?;
+    // SAFETY: sci.hwnd() is the Scintilla window just created on this thread.
+    unsafe { autoscroll::install(sci.hwnd()) };
     sci.show(true);
     let sci_views = vec![sci];
 
     // ── Status bar ────────────────────────────────────────────────────────────
     // SAFETY: STATUS_CLASS is valid; hwnd_parent and hinstance are valid.
+    // Double-buffered like the tab strip.
     let hwnd_status = unsafe {
         CreateWindowExW(
-            WINDOW_EX_STYLE(0),
+            WS_EX_COMPOSITED,
             STATUS_CLASS,
             PCWSTR::null(),
             WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WINDOW_STYLE(SBARS_SIZEGRIP),
@@ -487,13 +1542,29 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
     }
     .map_err(RivetError::from)?;
 
-    let app = App::new();
+    let settings = crate::config::load();
+    let mut app = App::new();
+    app.ansi = crate::platform::win32::encoding::codec(settings.ansi_code_page);
+    app.large_files = settings.large_files;
+
+    // ── Menu bar ──────────────────────────────────────────────────────────────
+    // Attached here (rather than after CreateWindowExW returns) so that
+    // `post_create_init` can already set checkmarks and fill the Tools menu.
+    let (menu, tools_menu) = build_menu()?;
+    // SAFETY: hwnd_parent and menu are valid handles.
+    unsafe { SetMenu(hwnd_parent, menu) }.map_err(RivetError::from)?;
 
     // Split the status bar at 96 DPI baseline; `post_create_init` rescales if needed.
-    let parts: [i32; 4] = [
+    let parts: [i32; 6] = [
         SB_PART_ENCODING_W_BASE,
         SB_PART_ENCODING_W_BASE + SB_PART_EOL_W_BASE,
         SB_PART_ENCODING_W_BASE + SB_PART_EOL_W_BASE + SB_PART_LANG_W_BASE,
+        SB_PART_ENCODING_W_BASE + SB_PART_EOL_W_BASE + SB_PART_LANG_W_BASE + SB_PART_SIZE_W_BASE,
+        SB_PART_ENCODING_W_BASE
+            + SB_PART_EOL_W_BASE
+            + SB_PART_LANG_W_BASE
+            + SB_PART_SIZE_W_BASE
+            + SB_PART_MODE_W_BASE,
         -1, // language: extends to fill remaining width
     ];
     // SAFETY: hwnd_status is valid; parts is a non-null i32 array of right-edge pixels.
@@ -509,6 +1580,8 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
     // Insert the initial "Untitled" tab.
     // SAFETY: hwnd_tab is valid; "Untitled" is a valid string.
     unsafe { tab_insert(hwnd_tab, 0, "Untitled") };
+    // SAFETY: hwnd_tab is a valid tab control created on this thread.
+    unsafe { tab_drag::install(hwnd_tab) };
 
     // ── Phase 6: Find/Replace buffers ─────────────────────────────────────────
     // The buffers are heap-allocated so their addresses are stable even after
@@ -525,10 +1598,14 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
         wFindWhatLen: 512,
         lpstrReplaceWith: PWSTR(repl_ptr),
         wReplaceWithLen: 512,
-        Flags: FINDREPLACE_FLAGS(FR_DOWN),
+        Flags: FINDREPLACE_FLAGS(FR_DOWN | FR_SHOWHELP | FR_ENABLEHOOK),
+        lpfnHook: Some(dialogs::find_hook),
         ..Default::default()
     };
 
+    let bindings = crate::keymap::load(COMMANDS);
+    let accel = create_accelerators(&bindings)?;
+
     let state = WindowState {
         app,
         sci_views,
@@ -536,12 +1613,44 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
         hwnd_tab,
         hwnd_status,
         dpi: crate::platform::win32::dpi::BASE_DPI,
-        dark_mode: true,
+        ui_font: HFONT::default(),
+        dark_mode: system_dark_mode(&settings).unwrap_or(true),
+        system_colours: system_colours_for(&settings),
         tab_position: TabPosition::Top,
         find_buf,
         replace_buf,
         findreplace,
         hwnd_find_dlg: HWND::default(),
+        find_target: HWND::default(),
+        settings,
+        tools_menu,
+        menu_bar: menu,
+        loads: Vec::new(),
+        viewers: Vec::new(),
+        replace: None,
+        session_writer: None,
+        snippets: Default::default(),
+        tab_stops: None,
+        tasks: None,
+        git_jobs: Vec::new(),
+        next_git_job: 0,
+        monitors: Vec::new(),
+        bindings,
+        accel,
+        workspace_root: None,
+        tooltip_buf: Vec::new(),
+        recent: crate::recent::load(),
+        quitting: false,
+        occurrences: None,
+        results: None,
+        toolbar: None,
+        progress: None,
+        folder: None,
+        outline: None,
+        map: None,
+        column_fill: Default::default(),
+        pipeline_recipe: "trim\r\nsort\r\nunique".to_owned(),
+        last_change_undone: false,
     };
 
     // SAFETY: all child HWNDs are valid; app has one initialised tab.
@@ -556,10 +1665,16 @@ fn create_child_controls(hwnd_parent: HWND, hinstance: HINSTANCE) -> Result<Wind
 /// The status bar always self-measures at the bottom.  The tab strip and editor
 /// area are laid out in one of three configurations:
 ///
-/// - **Top** (default): tab strip across the top, editor fills the rest.
+/// - **Top** (default): tab strip across the top, editor fills the rest.  With
+///   tabs in the title bar the strip sits at the bottom of the caption band,
+///   clear of the caption buttons.
 /// - **Left**: tab strip as a vertical strip on the left, editor to its right.
 /// - **Right**: tab strip as a vertical strip on the right, editor to its left.
 ///
+/// The folder and outline panels, when shown, take columns on the left and
+/// right; the tab strip and editor are laid out between them.  The document
+/// map takes a strip off the editor's right edge.
+///
 /// # Safety
 /// `state` must point to a live `WindowState` whose child HWNDs are valid.
 unsafe fn layout_children(state: &WindowState, client_width: i32, client_height: i32) {
@@ -568,88 +1683,186 @@ unsafe fn layout_children(state: &WindowState, client_width: i32, client_height:
     // Status bar always occupies the bottom; it self-positions from WM_SIZE.
     let _ = SendMessageW(state.hwnd_status, WM_SIZE, WPARAM(0), LPARAM(0));
     update_statusbar_parts(state);
+    sync_progress(state);
     let mut sr = RECT::default();
     let _ = GetClientRect(state.hwnd_status, &mut sr);
-    let status_h = sr.bottom;
+    let mut status_h = sr.bottom;
+
+    // Find All results: full width, just above the status bar.
+    if let Some(panel) = state.results.as_ref().filter(|p| p.visible) {
+        let panel_h = dpi::scale(RESULTS_PANEL_BASE_H, state.dpi).min(client_height / 2);
+        let _ = SetWindowPos(
+            panel.view.hwnd(),
+            HWND::default(),
+            0,
+            (client_height - status_h - panel_h).max(0),
+            client_width,
+            panel_h,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+        status_h += panel_h;
+    }
 
     let sci_hwnd = state.sci_views[state.app.active_idx].hwnd();
 
-    match state.tab_position {
-        TabPosition::Top => {
-            let tab_h = dpi::scale(TAB_BAR_BASE_H, state.dpi);
-            // Tab strip: full width across the top.
+    let hwnd = GetParent(state.hwnd_tab).unwrap_or_default();
+    let band_h = title_bar_tabs_active(hwnd, state).then(|| titlebar::band_height(hwnd, state.dpi));
+    titlebar::extend_frame(hwnd, band_h.unwrap_or(0));
+
+    // Toolbar: full width, below the caption band (which holds the tab
+    // strip, so is at least as tall).
+    let band_bottom = band_h.map_or(0, |h| h.max(dpi::scale(TAB_BAR_BASE_H, state.dpi)));
+    let bar_h = match state.toolbar.as_ref() {
+        Some(bar) => {
+            let bar_h = bar.height(state.dpi);
             let _ = SetWindowPos(
-                state.hwnd_tab,
+                bar.hwnd(),
                 HWND::default(),
                 0,
-                0,
+                band_bottom,
                 client_width,
-                tab_h,
+                bar_h,
                 SWP_NOZORDER | SWP_NOACTIVATE,
             );
-            // Editor: below tab strip, above status bar.
-            let sci_h = (client_height - tab_h - status_h).max(0);
+            bar_h
+        }
+        None => 0,
+    };
+    let top = band_bottom + bar_h;
+
+    // Folder panel: a column on the left from below the caption band and
+    // toolbar down to the results panel; everything else moves right of it.
+    let mut left = 0;
+    if let Some(folder) = state.folder.as_ref() {
+        let panel_w = dpi::scale(FOLDER_PANEL_BASE_W, state.dpi).min(client_width / 2);
+        let _ = SetWindowPos(
+            folder.hwnd(),
+            HWND::default(),
+            0,
+            top,
+            panel_w,
+            (client_height - top - status_h).max(0),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+        left = panel_w;
+    }
+    // Outline panel: the same column on the right.
+    let mut right = client_width;
+    if let Some(panel) = state.outline.as_ref() {
+        let panel_w = dpi::scale(OUTLINE_PANEL_BASE_W, state.dpi).min(client_width / 3);
+        right = (client_width - panel_w).max(left);
+        let _ = SetWindowPos(
+            panel.view.hwnd(),
+            HWND::default(),
+            right,
+            top,
+            panel_w,
+            (client_height - top - status_h).max(0),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+    let content_w = (right - left).max(0);
+
+    // Each arm places the tab strip and yields the editor's area.
+    let (sci_x, sci_y, mut sci_w, sci_h) = match state.tab_position {
+        TabPosition::Top => {
+            // The caption band has room for one row only.
+            let multiline = state.settings.multiline_tabs && band_h.is_none();
+            set_tab_multiline(state.hwnd_tab, multiline);
+            let tab_h = dpi::scale(TAB_BAR_BASE_H, state.dpi);
+            let (tab_y, tab_w, tab_bottom) = match band_h {
+                // Tab strip: bottom of the caption band, left of the buttons.
+                Some(band_h) => {
+                    let band_h = band_h.max(tab_h);
+                    let buttons_w = titlebar::caption_buttons_width(hwnd, state.dpi);
+                    (band_h - tab_h, (client_width - buttons_w).max(0), band_h)
+                }
+                // Tab strip: full width across the top, one row per line
+                // of wrapped tabs.
+                None if multiline => {
+                    let rows_h = tab_rows_height(state.hwnd_tab, content_w, tab_h);
+                    (top, content_w, top + rows_h)
+                }
+                None => (top, content_w, top + tab_h),
+            };
+            let tab_h = tab_bottom - tab_y;
+            // Under the caption band the toolbar comes before the editor.
+            let sci_y = tab_bottom.max(top);
+            // The caption band spans the whole window; otherwise the strip
+            // sits right of the folder panel.
+            let tab_x = if band_h.is_some() { 0 } else { left };
             let _ = SetWindowPos(
-                sci_hwnd,
+                state.hwnd_tab,
                 HWND::default(),
-                0,
+                tab_x,
+                tab_y,
+                tab_w,
                 tab_h,
-                client_width,
-                sci_h,
                 SWP_NOZORDER | SWP_NOACTIVATE,
             );
+            // Editor: below tab strip, above status bar.
+            let sci_h = (client_height - sci_y - status_h).max(0);
+            (left, sci_y, content_w, sci_h)
         }
         TabPosition::Left => {
             let tab_w = dpi::scale(TAB_BAR_SIDE_W_BASE, state.dpi);
-            let content_h = (client_height - status_h).max(0);
+            let content_h = (client_height - top - status_h).max(0);
             // Tab strip: vertical strip on the left.
             let _ = SetWindowPos(
                 state.hwnd_tab,
                 HWND::default(),
-                0,
-                0,
+                left,
+                top,
                 tab_w,
                 content_h,
                 SWP_NOZORDER | SWP_NOACTIVATE,
             );
             // Editor: to the right of the tab strip.
-            let sci_w = (client_width - tab_w).max(0);
-            let _ = SetWindowPos(
-                sci_hwnd,
-                HWND::default(),
-                tab_w,
-                0,
-                sci_w,
-                content_h,
-                SWP_NOZORDER | SWP_NOACTIVATE,
-            );
+            let sci_w = (content_w - tab_w).max(0);
+            (left + tab_w, top, sci_w, content_h)
         }
         TabPosition::Right => {
             let tab_w = dpi::scale(TAB_BAR_SIDE_W_BASE, state.dpi);
-            let content_h = (client_height - status_h).max(0);
-            let tab_x = (client_width - tab_w).max(0);
+            let content_h = (client_height - top - status_h).max(0);
+            let tab_x = (right - tab_w).max(left);
             // Tab strip: vertical strip on the right.
             let _ = SetWindowPos(
                 state.hwnd_tab,
                 HWND::default(),
                 tab_x,
-                0,
+                top,
                 tab_w,
                 content_h,
                 SWP_NOZORDER | SWP_NOACTIVATE,
             );
             // Editor: to the left of the tab strip.
-            let _ = SetWindowPos(
-                sci_hwnd,
-                HWND::default(),
-                0,
-                0,
-                tab_x,
-                content_h,
-                SWP_NOZORDER | SWP_NOACTIVATE,
-            );
+            (left, top, tab_x - left, content_h)
         }
+    };
+
+    // Document map: a strip along the editor's right edge.
+    if let Some(map) = state.map.as_ref() {
+        let map_w = dpi::scale(DOCUMENT_MAP_BASE_W, state.dpi).min(sci_w / 3);
+        sci_w -= map_w;
+        let _ = SetWindowPos(
+            map.view.hwnd(),
+            HWND::default(),
+            sci_x + sci_w,
+            sci_y,
+            map_w,
+            sci_h,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
     }
+    let _ = SetWindowPos(
+        sci_hwnd,
+        HWND::default(),
+        sci_x,
+        sci_y,
+        sci_w,
+        sci_h,
+        SWP_NOZORDER | SWP_NOACTIVATE,
+    );
 }
 
 // ── Tab helpers ───────────────────────────────────────────────────────────────
@@ -679,19 +1892,20 @@ unsafe fn tab_insert(hwnd_tab: HWND, idx: usize, label: &str) {
     );
 }
 
-/// Update the text of an existing tab at `idx`.
+/// Update the text and image (index in the strip's image list, -1 for none)
+/// of an existing tab at `idx`.
 ///
 /// # Safety
 /// `hwnd_tab` must be a valid `SysTabControl32` HWND.
-unsafe fn tab_set_label(hwnd_tab: HWND, idx: usize, label: &str) {
+unsafe fn tab_set_label(hwnd_tab: HWND, idx: usize, label: &str, image: i32) {
     let mut wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
     let mut item = TCITEMW {
-        mask: TCIF_TEXT,
+        mask: TCIF_TEXT | TCIF_IMAGE,
         dw_state: 0,
         dw_state_mask: 0,
         psz_text: wide.as_mut_ptr(),
         cch_text_max: wide.len() as i32,
-        i_image: -1,
+        i_image: image,
         l_param: 0,
     };
     // SAFETY: see tab_insert.
@@ -708,55 +1922,249 @@ unsafe fn tab_set_label(hwnd_tab: HWND, idx: usize, label: &str) {
 /// # Safety
 /// `state.hwnd_tab` must be a valid `SysTabControl32` HWND.
 unsafe fn sync_tab_label(state: &WindowState, idx: usize) {
-    let label = crate::ui::tabs::tab_label(&state.app.tabs[idx]);
-    tab_set_label(state.hwnd_tab, idx, &label);
+    let doc = &state.app.tabs[idx];
+    let image = if state.settings.tab_icons {
+        crate::platform::win32::icons::file_icon(&crate::ui::tabs::tab_icon_name(doc))
+    } else {
+        -1
+    };
+    tab_set_label(state.hwnd_tab, idx, &crate::ui::tabs::tab_label(doc), image);
+    crate::platform::win32::a11y::set_child_name(
+        state.hwnd_tab,
+        idx + 1,
+        &crate::ui::tabs::tab_accessible_name(doc),
+    );
+    // The label shows whether the document is dirty, which Save follows.
+    if idx == state.app.active_idx {
+        update_edit_state(state);
+    }
+}
+
+/// Re-name every tab for screen readers after tabs were removed or moved:
+/// the names are keyed by position, so each shifted tab needs its own again
+/// and the slot past the last tab must not keep a closed tab's name.
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn sync_tab_names(state: &WindowState) {
+    for (idx, doc) in state.app.tabs.iter().enumerate() {
+        crate::platform::win32::a11y::set_child_name(
+            state.hwnd_tab,
+            idx + 1,
+            &crate::ui::tabs::tab_accessible_name(doc),
+        );
+    }
+    crate::platform::win32::a11y::clear_child_name(state.hwnd_tab, state.app.tabs.len() + 1);
 }
 
 // ── Menu ──────────────────────────────────────────────────────────────────────
 
-fn build_menu() -> Result<HMENU> {
+/// Build the menu bar.  Returns the bar and the (still empty) Tools popup,
+/// which `rebuild_tools_menu` fills from the user's settings.
+fn build_menu() -> Result<(HMENU, HMENU)> {
     // SAFETY: CreateMenu / AppendMenuW are always safe on Win32 threads.
     unsafe {
         let bar = CreateMenu().map_err(RivetError::from)?;
 
         // ── File ──────────────────────────────────────────────────────────────
         let file = CreateMenu().map_err(RivetError::from)?;
-        AppendMenuW(file, MF_STRING, IDM_FILE_NEW, w!("&New\tCtrl+N")).map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_NEW, w!("&New")).map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_NEW_WINDOW, w!("New &Window"))
+            .map_err(RivetError::from)?;
         AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
-        AppendMenuW(file, MF_STRING, IDM_FILE_OPEN, w!("&Open\u{2026}\tCtrl+O"))
+        AppendMenuW(file, MF_STRING, IDM_FILE_OPEN, w!("&Open\u{2026}"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(
+            file,
+            MF_STRING,
+            IDM_FILE_OPEN_FOLDER,
+            w!("Open &Folder\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_GOTO, w!("&Go to File\u{2026}"))
             .map_err(RivetError::from)?;
-        AppendMenuW(file, MF_STRING, IDM_FILE_SAVE, w!("&Save\tCtrl+S"))
+        AppendMenuW(file, MF_STRING, IDM_FILE_RELOAD, w!("&Reload from Disk"))
             .map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_SAVE, w!("&Save")).map_err(RivetError::from)?;
         AppendMenuW(file, MF_STRING, IDM_FILE_SAVE_AS, w!("Save &As\u{2026}"))
             .map_err(RivetError::from)?;
+        AppendMenuW(
+            file,
+            MF_STRING,
+            IDM_FILE_PROPERTIES,
+            w!("Document Propert&ies\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
         AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
-        AppendMenuW(file, MF_STRING, IDM_FILE_CLOSE, w!("&Close Tab\tCtrl+W"))
-            .map_err(RivetError::from)?;
-        AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
-        AppendMenuW(file, MF_STRING, IDM_FILE_EXIT, w!("E&xit\tAlt+F4"))
+        AppendMenuW(
+            file,
+            MF_STRING,
+            IDM_FILE_OPEN_WORKSPACE,
+            w!("Open &Workspace\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            file,
+            MF_STRING,
+            IDM_FILE_SAVE_WORKSPACE,
+            w!("Save Wor&kspace\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_CLOSE, w!("&Close Tab")).map_err(RivetError::from)?;
+        AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        let startup = CreateMenu().map_err(RivetError::from)?;
+        for (id, label) in [
+            (IDM_FILE_RESTORE_ALWAYS, w!("&Reopen Previous Files")),
+            (IDM_FILE_RESTORE_NEVER, w!("Start &Empty")),
+            (IDM_FILE_RESTORE_ASK, w!("&Ask")),
+        ] {
+            AppendMenuW(startup, MF_STRING, id, label).map_err(RivetError::from)?;
+        }
+        AppendMenuW(startup, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(
+            startup,
+            MF_STRING,
+            IDM_FILE_KEEP_UNSAVED,
+            w!("&Keep Unsaved Changes"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(file, MF_POPUP, startup.0 as usize, w!("On S&tartup"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(file, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(file, MF_STRING, IDM_FILE_EXIT, w!("E&xit\tAlt+F4"))
             .map_err(RivetError::from)?;
 
         // ── Edit ──────────────────────────────────────────────────────────────
         let edit = CreateMenu().map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_UNDO, w!("&Undo\tCtrl+Z"))
-            .map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_REDO, w!("&Redo\tCtrl+Y"))
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_UNDO, w!("&Undo")).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_REDO, w!("&Redo")).map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_CLEAR_UNDO,
+            w!("Clear Undo Histor&y"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_CUT, w!("Cu&t")).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_COPY, w!("&Copy")).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_PASTE, w!("&Paste")).map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_PASTE_HISTORY,
+            w!("Paste from &History\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_DELETE, w!("&Delete")).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_SELECT_ALL, w!("Select &All"))
             .map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_COMPLETE_WORD,
+            w!("Complete &Word"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_SNIPPETS,
+            w!("Edit S&nippets\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_AUTO_CLOSE,
+            w!("Auto-Close &Brackets"),
+        )
+        .map_err(RivetError::from)?;
         AppendMenuW(edit, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_CUT, w!("Cu&t\tCtrl+X")).map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_COPY, w!("&Copy\tCtrl+C"))
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_LINE_COMMENT,
+            w!("Toggle &Line Comment"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_BLOCK_COMMENT,
+            w!("Toggle Bloc&k Comment"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            edit,
+            MF_STRING,
+            IDM_EDIT_COLUMN_EDITOR,
+            w!("Colu&mn Editor\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        let line_ops = CreateMenu().map_err(RivetError::from)?;
+        AppendMenuW(line_ops, MF_STRING, IDM_EDIT_JOIN_LINES, w!("&Join Lines"))
             .map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_PASTE, w!("&Paste\tCtrl+V"))
+        AppendMenuW(
+            line_ops,
+            MF_STRING,
+            IDM_EDIT_SPLIT_LINES,
+            w!("&Split Lines\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(line_ops, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(
+            line_ops,
+            MF_STRING,
+            IDM_EDIT_REMOVE_DUPLICATES,
+            w!("Remove &Duplicate Lines"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            line_ops,
+            MF_STRING,
+            IDM_EDIT_REMOVE_CONSECUTIVE_DUPLICATES,
+            w!("Remove &Consecutive Duplicate Lines"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            line_ops,
+            MF_STRING,
+            IDM_EDIT_REMOVE_EMPTY,
+            w!("Remove &Empty Lines"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            line_ops,
+            MF_STRING,
+            IDM_EDIT_REMOVE_BLANK,
+            w!("Remove Empty and &Blank Lines"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_POPUP, line_ops.0 as usize, w!("Line &Operations"))
             .map_err(RivetError::from)?;
-        AppendMenuW(edit, MF_STRING, IDM_EDIT_DELETE, w!("&Delete")).map_err(RivetError::from)?;
         AppendMenuW(edit, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_STRING, IDM_EDIT_COPY_PATH, w!("Copy Full &Path"))
+            .map_err(RivetError::from)?;
+        let copy_special = CreateMenu().map_err(RivetError::from)?;
         AppendMenuW(
-            edit,
+            copy_special,
+            MF_STRING,
+            IDM_EDIT_COPY_RTF,
+            w!("Copy as &RTF"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            copy_special,
             MF_STRING,
-            IDM_EDIT_SELECT_ALL,
-            w!("Select &All\tCtrl+A"),
+            IDM_EDIT_COPY_HTML,
+            w!("Copy as &HTML"),
         )
         .map_err(RivetError::from)?;
+        AppendMenuW(edit, MF_POPUP, copy_special.0 as usize, w!("Copy &Special"))
+            .map_err(RivetError::from)?;
 
         // ── Format ────────────────────────────────────────────────────────────
         let format = CreateMenu().map_err(RivetError::from)?;
@@ -781,35 +2189,88 @@ fn build_menu() -> Result<HMENU> {
             w!("Convert to &Classic Mac (CR)"),
         )
         .map_err(RivetError::from)?;
+        AppendMenuW(format, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(format, MF_STRING, IDM_FORMAT_ADD_BOM, w!("Add &BOM"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(format, MF_STRING, IDM_FORMAT_REMOVE_BOM, w!("&Remove BOM"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(format, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        let encoding = CreateMenu().map_err(RivetError::from)?;
+        let reopen = CreateMenu().map_err(RivetError::from)?;
+        for (i, enc) in Encoding::ALL.iter().enumerate() {
+            let label: Vec<u16> = enc
+                .display_name()
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            if *enc == Encoding::Ansi {
+                AppendMenuW(encoding, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+            }
+            let id = IDM_FORMAT_ENCODING_FIRST + i;
+            AppendMenuW(encoding, MF_STRING, id, PCWSTR(label.as_ptr()))
+                .map_err(RivetError::from)?;
+            if enc.is_code_page() {
+                let id = IDM_FORMAT_REOPEN_FIRST + i;
+                AppendMenuW(reopen, MF_STRING, id, PCWSTR(label.as_ptr()))
+                    .map_err(RivetError::from)?;
+            }
+        }
+        AppendMenuW(encoding, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(encoding, MF_POPUP, reopen.0 as usize, w!("&Reopen With"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(format, MF_POPUP, encoding.0 as usize, w!("&Encoding"))
+            .map_err(RivetError::from)?;
 
         // ── Search ────────────────────────────────────────────────────────────
         let search = CreateMenu().map_err(RivetError::from)?;
+        AppendMenuW(search, MF_STRING, IDM_SEARCH_FIND, w!("&Find\u{2026}"))
+            .map_err(RivetError::from)?;
         AppendMenuW(
             search,
             MF_STRING,
-            IDM_SEARCH_FIND,
-            w!("&Find\u{2026}\tCtrl+F"),
+            IDM_SEARCH_REPLACE,
+            w!("&Replace\u{2026}"),
         )
         .map_err(RivetError::from)?;
         AppendMenuW(
             search,
             MF_STRING,
-            IDM_SEARCH_REPLACE,
-            w!("&Replace\u{2026}\tCtrl+H"),
+            IDM_SEARCH_REPLACE_ALL_OPEN,
+            w!("Replace All in All &Opened Documents"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(search, MF_STRING, IDM_SEARCH_FIND_NEXT, w!("Find &Next"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(search, MF_STRING, IDM_SEARCH_FIND_PREV, w!("Find &Prev"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(
+            search,
+            MF_STRING,
+            IDM_SEARCH_FIND_SELECTED,
+            w!("Find Selecte&d Word"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(search, MF_STRING, IDM_SEARCH_FIND_ALL, w!("Find &All"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(
+            search,
+            MF_STRING,
+            IDM_SEARCH_CLOSE_RESULTS,
+            w!("Close Resul&ts"),
         )
         .map_err(RivetError::from)?;
         AppendMenuW(
             search,
             MF_STRING,
-            IDM_SEARCH_FIND_NEXT,
-            w!("Find &Next\tF3"),
+            IDM_SEARCH_SELECT_MATCHES,
+            w!("Select All &Matches"),
         )
         .map_err(RivetError::from)?;
         AppendMenuW(
             search,
             MF_STRING,
-            IDM_SEARCH_FIND_PREV,
-            w!("Find &Prev\tShift+F3"),
+            IDM_SEARCH_ADD_MATCHES,
+            w!("A&dd All Matches to Selection"),
         )
         .map_err(RivetError::from)?;
         AppendMenuW(search, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
@@ -817,127 +2278,226 @@ fn build_menu() -> Result<HMENU> {
             search,
             MF_STRING,
             IDM_SEARCH_GOTO_LINE,
-            w!("&Go to Line\u{2026}\tCtrl+G"),
+            w!("&Go to Line\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            search,
+            MF_STRING,
+            IDM_SEARCH_NEXT_CHANGE,
+            w!("Next &Change"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            search,
+            MF_STRING,
+            IDM_SEARCH_PREV_CHANGE,
+            w!("Pre&vious Change"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(search, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(
+            search,
+            MF_STRING,
+            IDM_SEARCH_EXTENDED,
+            w!("E&xtended Mode (\\n, \\t, \\xNN)"),
         )
         .map_err(RivetError::from)?;
+        let feedback = CreateMenu().map_err(RivetError::from)?;
+        for (id, label) in [
+            (IDM_SEARCH_FEEDBACK_BEEP, w!("&Beep")),
+            (IDM_SEARCH_FEEDBACK_STATUS, w!("&Status Bar Message")),
+            (IDM_SEARCH_FEEDBACK_TASKBAR, w!("&Flash Window")),
+            (IDM_SEARCH_FEEDBACK_SILENT, w!("&None")),
+        ] {
+            AppendMenuW(feedback, MF_STRING, id, label).map_err(RivetError::from)?;
+        }
+        AppendMenuW(search, MF_POPUP, feedback.0 as usize, w!("When Not F&ound"))
+            .map_err(RivetError::from)?;
 
         // ── View ──────────────────────────────────────────────────────────────
         let view = CreateMenu().map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_SWITCH_TAB,
+            w!("&Switch to Tab\u{2026}"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_TOOLBAR, w!("Tool&bar")).map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_FOLDER_PANEL, w!("&Folder Panel"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_OUTLINE, w!("Out&line Panel"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_DOCUMENT_MAP, w!("&Document Map"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_NEXT_PANE, w!("Ne&xt Pane"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_PREV_PANE, w!("Pre&vious Pane"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
         AppendMenuW(view, MF_STRING, IDM_VIEW_WORD_WRAP, w!("Word &Wrap"))
             .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_MONITOR, w!("M&onitor (tail -f)"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_REFRESH_HIGHLIGHTING,
+            w!("&Refresh Highlighting"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_LONG_LINES,
+            w!("Highlight and Wrap Lon&g Lines"),
+        )
+        .map_err(RivetError::from)?;
         AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
         AppendMenuW(view, MF_STRING, IDM_VIEW_DARK_MODE, w!("&Dark Mode"))
             .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_FOLLOW_THEME,
+            w!("Follow System T&heme"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_ACCENT,
+            w!("Accent &Colour Highlights"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_OCCURRENCES,
+            w!("Highlight &Occurrences"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_RELATIVE_PATHS,
+            w!("Workspace-Relative &Paths"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        let tab_position = CreateMenu().map_err(RivetError::from)?;
+        for (id, label) in [
+            (IDM_VIEW_TAB_TOP, w!("&Top")),
+            (IDM_VIEW_TAB_LEFT, w!("&Left")),
+            (IDM_VIEW_TAB_RIGHT, w!("&Right")),
+        ] {
+            AppendMenuW(tab_position, MF_STRING, id, label).map_err(RivetError::from)?;
+        }
+        AppendMenuW(view, MF_POPUP, tab_position.0 as usize, w!("Tab &Position"))
+            .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_MULTILINE_TABS,
+            w!("M&ultiline Tabs"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_TAB_ICONS,
+            w!("File &Icons in Tabs"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_AUTOHIDE_MENU,
+            w!("Auto-hide &Menu Bar"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_TITLEBAR_TABS,
+            w!("Tabs in &Title Bar"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_TRAY, w!("Minimize to Tra&y"))
+            .map_err(RivetError::from)?;
         AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
-        AppendMenuW(view, MF_STRING, IDM_VIEW_TAB_TOP, w!("Tabs at &Top"))
+        AppendMenuW(view, MF_STRING, IDM_VIEW_CARET, w!("C&aret\u{2026}"))
             .map_err(RivetError::from)?;
-        AppendMenuW(view, MF_STRING, IDM_VIEW_TAB_LEFT, w!("Tabs at &Left"))
+        AppendMenuW(
+            view,
+            MF_STRING,
+            IDM_VIEW_SPEAK_POSITION,
+            w!("&Speak Cursor Position"),
+        )
+        .map_err(RivetError::from)?;
+        AppendMenuW(view, MF_SEPARATOR, 0, PCWSTR::null()).map_err(RivetError::from)?;
+        AppendMenuW(view, MF_STRING, IDM_VIEW_NEXT_CHUNK, w!("&Next Chunk"))
             .map_err(RivetError::from)?;
-        AppendMenuW(view, MF_STRING, IDM_VIEW_TAB_RIGHT, w!("Tabs at &Right"))
+        AppendMenuW(view, MF_STRING, IDM_VIEW_PREV_CHUNK, w!("&Previous Chunk"))
             .map_err(RivetError::from)?;
 
+        // ── Tools (populated by rebuild_tools_menu) ───────────────────────────
+        let tools = CreateMenu().map_err(RivetError::from)?;
+
         // ── Help ──────────────────────────────────────────────────────────────
         let help = CreateMenu().map_err(RivetError::from)?;
         AppendMenuW(help, MF_STRING, IDM_HELP_ABOUT, w!("&About Rivet\u{2026}"))
             .map_err(RivetError::from)?;
 
-        // ── Bar: File | Edit | Format | Search | View | Help ─────────────────
+        // ── Bar: File | Edit | Format | Search | View | Tools | Help ─────────
         AppendMenuW(bar, MF_POPUP, file.0 as usize, w!("&File")).map_err(RivetError::from)?;
         AppendMenuW(bar, MF_POPUP, edit.0 as usize, w!("&Edit")).map_err(RivetError::from)?;
         AppendMenuW(bar, MF_POPUP, format.0 as usize, w!("F&ormat")).map_err(RivetError::from)?;
         AppendMenuW(bar, MF_POPUP, search.0 as usize, w!("&Search")).map_err(RivetError::from)?;
         AppendMenuW(bar, MF_POPUP, view.0 as usize, w!("&View")).map_err(RivetError::from)?;
+        AppendMenuW(bar, MF_POPUP, tools.0 as usize, w!("&Tools")).map_err(RivetError::from)?;
         AppendMenuW(bar, MF_POPUP, help.0 as usize, w!("&Help")).map_err(RivetError::from)?;
 
-        Ok(bar)
+        Ok((bar, tools))
     }
 }
 
 // ── Accelerator table ─────────────────────────────────────────────────────────
 
-fn create_accelerators() -> Result<HACCEL> {
-    let ctrl_virt: ACCEL_VIRT_FLAGS = FCONTROL | FVIRTKEY;
-    let virt_only: ACCEL_VIRT_FLAGS = FVIRTKEY;
-    let shift_virt: ACCEL_VIRT_FLAGS = FVIRTKEY | FSHIFT;
-    let accels = [
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'N' as u16,
-            cmd: IDM_FILE_NEW as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'O' as u16,
-            cmd: IDM_FILE_OPEN as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'S' as u16,
-            cmd: IDM_FILE_SAVE as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'W' as u16,
-            cmd: IDM_FILE_CLOSE as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'Z' as u16,
-            cmd: IDM_EDIT_UNDO as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'Y' as u16,
-            cmd: IDM_EDIT_REDO as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'X' as u16,
-            cmd: IDM_EDIT_CUT as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'C' as u16,
-            cmd: IDM_EDIT_COPY as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'V' as u16,
-            cmd: IDM_EDIT_PASTE as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'A' as u16,
-            cmd: IDM_EDIT_SELECT_ALL as u16,
-        },
-        // Search
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'F' as u16,
-            cmd: IDM_SEARCH_FIND as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'H' as u16,
-            cmd: IDM_SEARCH_REPLACE as u16,
-        },
-        ACCEL {
-            fVirt: ctrl_virt,
-            key: b'G' as u16,
-            cmd: IDM_SEARCH_GOTO_LINE as u16,
-        },
-        ACCEL {
-            fVirt: virt_only,
-            key: VK_F3,
-            cmd: IDM_SEARCH_FIND_NEXT as u16,
-        },
-        ACCEL {
-            fVirt: shift_virt,
-            key: VK_F3,
-            cmd: IDM_SEARCH_FIND_PREV as u16,
-        },
-    ];
+/// Build the accelerator table from the resolved `bindings` (parallel to
+/// `COMMANDS`).  With conflicting chords the first command in registry
+/// order wins.
+fn create_accelerators(bindings: &[Option<Chord>]) -> Result<HACCEL> {
+    let accels: Vec<ACCEL> = COMMANDS
+        .iter()
+        .zip(bindings)
+        .filter_map(|(command, chord)| {
+            let chord = (*chord)?;
+            let mut virt = FVIRTKEY;
+            if chord.ctrl {
+                virt |= FCONTROL;
+            }
+            if chord.shift {
+                virt |= FSHIFT;
+            }
+            if chord.alt {
+                virt |= FALT;
+            }
+            Some(ACCEL {
+                fVirt: virt,
+                key: chord.key,
+                cmd: command.id as u16,
+            })
+        })
+        .collect();
 
+    // CreateAcceleratorTableW rejects an empty table; a null HACCEL makes
+    // TranslateAcceleratorW a no-op.
+    if accels.is_empty() {
+        return Ok(HACCEL::default());
+    }
     // SAFETY: accels is a valid, non-empty slice of ACCEL entries.
     let haccel = unsafe { CreateAcceleratorTableW(&accels) }.map_err(RivetError::from)?;
     Ok(haccel)
@@ -945,7 +2505,11 @@ fn create_accelerators() -> Result<HACCEL> {
 
 // ── Message loop ──────────────────────────────────────────────────────────────
 
-fn message_loop(hwnd: HWND, haccel: HACCEL) -> Result<()> {
+/// Pump messages for every main window until the last one is destroyed.
+///
+/// Each message is routed to the window it belongs to: that window's Find
+/// dialog, Esc handling, and accelerator table.
+fn message_loop() -> Result<()> {
     let mut msg = MSG::default();
     loop {
         let ret = unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) };
@@ -953,18 +2517,74 @@ fn message_loop(hwnd: HWND, haccel: HACCEL) -> Result<()> {
             -1 => return Err(last_error("GetMessageW")),
             0 => break,
             _ => unsafe {
-                // Give the modeless Find/Replace dialog first crack at keyboard
+                // Give the modeless Find/Replace dialogs first crack at keyboard
                 // messages (Tab, Enter, Escape, arrow keys, etc.).
-                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const WindowState;
-                let dlg = if !ptr.is_null() {
-                    (*ptr).hwnd_find_dlg
+                let windows = WINDOWS.with(|w| w.borrow().clone());
+                let in_dialog = windows.iter().any(|&hwnd| {
+                    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const WindowState;
+                    !ptr.is_null()
+                        && (*ptr).hwnd_find_dlg != HWND::default()
+                        && IsDialogMessageW((*ptr).hwnd_find_dlg, &msg).as_bool()
+                });
+                if in_dialog {
+                    continue;
+                }
+                // The main window the message is headed for, if any.
+                let hwnd = GetAncestor(msg.hwnd, GA_ROOT);
+                let ptr = if windows.contains(&hwnd) {
+                    GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const WindowState
                 } else {
-                    HWND::default()
+                    std::ptr::null()
                 };
-                if dlg != HWND::default() && IsDialogMessageW(dlg, &msg).as_bool() {
+                // Esc in a view that is still loading, or has a Replace All
+                // or EOL conversion running, cancels it.  The keyboard focus
+                // is in the active tab, which is the one the task belongs to.
+                if msg.message == WM_KEYDOWN
+                    && msg.wParam.0 == VK_ESCAPE.0 as usize
+                    && !ptr.is_null()
+                    && (*ptr).sci_views[(*ptr).app.active_idx].hwnd() == msg.hwnd
+                    && current_task(&*ptr).is_some()
+                {
+                    let _ = PostMessageW(hwnd, WM_COMMAND, WPARAM(IDC_CANCEL_TASK), LPARAM(0));
                     continue;
                 }
-                if TranslateAcceleratorW(hwnd, haccel, &msg) == 0 {
+                // Esc in a panel or the tab strip hands the focus back to
+                // the editor.
+                if msg.message == WM_KEYDOWN
+                    && msg.wParam.0 == VK_ESCAPE.0 as usize
+                    && !ptr.is_null()
+                {
+                    let editor = (*ptr).sci_views[(*ptr).app.active_idx].hwnd();
+                    if msg.hwnd != editor && focus_panes(&*ptr).contains(&msg.hwnd) {
+                        let _ = SetFocus(editor);
+                        continue;
+                    }
+                }
+                // Tab in a view expands a snippet or moves between its tab
+                // stops; Ctrl+Tab and Alt+Tab are left alone.
+                if msg.message == WM_KEYDOWN
+                    && msg.wParam.0 == VK_TAB.0 as usize
+                    && !ptr.is_null()
+                    && GetKeyState(VK_CONTROL.0 as i32) >= 0
+                    && GetKeyState(VK_MENU.0 as i32) >= 0
+                {
+                    let forward = GetKeyState(VK_SHIFT.0 as i32) >= 0;
+                    let used = SendMessageW(
+                        hwnd,
+                        WM_SNIPPET_TAB,
+                        WPARAM(forward as usize),
+                        LPARAM(msg.hwnd.0 as isize),
+                    );
+                    if used.0 != 0 {
+                        continue;
+                    }
+                }
+                let haccel = if ptr.is_null() {
+                    HACCEL::default()
+                } else {
+                    (*ptr).accel
+                };
+                if haccel.is_invalid() || TranslateAcceleratorW(hwnd, haccel, &msg) == 0 {
                     let _ = TranslateMessage(&msg);
                     let _ = DispatchMessageW(&msg);
                 }
@@ -985,14 +2605,22 @@ unsafe extern "system" fn wnd_proc(
 ) -> LRESULT {
     // Check for the registered "commdlg_FindReplace" message from the modeless
     // Find / Replace dialog before the standard match so it never falls through.
-    if let Some(&find_msg) = FIND_MSG_ID.get() {
-        if msg == find_msg {
-            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
-            if !ptr.is_null() {
-                handle_findreplace_msg(hwnd, lparam, &mut *ptr);
-            }
-            return LRESULT(0);
+    let find_msg = find_msg_id();
+    if find_msg != 0 && msg == find_msg {
+        let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+        if !ptr.is_null() {
+            handle_findreplace_msg(hwnd, lparam, &mut *ptr);
+        }
+        return LRESULT(0);
+    }
+    // Explorer restarted and dropped the notification-area icons.
+    let taskbar_msg = tray::taskbar_created_msg();
+    if taskbar_msg != 0 && msg == taskbar_msg {
+        let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+        if !ptr.is_null() && (*ptr).settings.minimize_to_tray {
+            tray::add(hwnd);
         }
+        return LRESULT(0);
     }
 
     match msg {
@@ -1008,6 +2636,7 @@ unsafe extern "system" fn wnd_proc(
                 Ok(state) => {
                     let ptr = Box::into_raw(Box::new(state));
                     SetWindowLongPtrW(hwnd, GWLP_USERDATA, ptr as isize);
+                    WINDOWS.with(|w| w.borrow_mut().push(hwnd));
                     post_create_init(hwnd, &mut *ptr);
                     LRESULT(0)
                 }
@@ -1035,6 +2664,17 @@ unsafe extern "system" fn wnd_proc(
         WM_CLOSE => {
             let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
             if !ptr.is_null() {
+                // With a tray icon, closing hides; the tray menu's Exit quits.
+                let quitting = std::mem::take(&mut (*ptr).quitting);
+                if (*ptr).settings.minimize_to_tray && !quitting {
+                    let _ = ShowWindow(hwnd, SW_HIDE);
+                    return LRESULT(0);
+                }
+                // The scratch tab saves itself rather than asking.
+                save_scratch_tabs(&mut *ptr);
+                // Unsaved changes can be kept only by the last window: it
+                // writes the session that carries them.
+                let keep = keeps_unsaved(hwnd, &*ptr);
                 // Collect the display names of every dirty tab.
                 let dirty: Vec<String> = (*ptr)
                     .app
@@ -1044,219 +2684,286 @@ unsafe extern "system" fn wnd_proc(
                     .map(|doc| doc.display_name())
                     .collect();
 
-                if !dirty.is_empty() && !confirm_discard_all(hwnd, &dirty) {
+                if !keep
+                    && !dirty.is_empty()
+                    && !confirm_discard_all(hwnd, &dirty, "Discard all and exit?")
+                {
                     return LRESULT(0);
                 }
 
                 // Save session while all Scintilla views are still alive.
-                save_session(&*ptr);
+                shutdown(hwnd, &mut *ptr, false);
             }
             let _ = DestroyWindow(hwnd);
             LRESULT(0)
         }
 
+        // Logoff / restart / shutdown: no WM_CLOSE follows, and the process
+        // ends as soon as this returns.
+        WM_ENDSESSION if wparam.0 != 0 => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                shutdown(hwnd, &mut *ptr, true);
+            }
+            LRESULT(0)
+        }
+
         WM_DESTROY => {
-            // Drop order: app → sci_views → sci_dll (FreeLibrary) → hwnd_*.
+            // Drop order: app → sci_views → sci_dll (FreeLibrary after the
+            // last window) → hwnd_*.
             let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
             if !ptr.is_null() {
                 // Stop the auto-save timer before freeing state.
                 // SAFETY: hwnd is valid; timer ID matches the one set in post_create_init.
                 let _ = KillTimer(hwnd, AUTOSAVE_TIMER_ID);
+                // An attached menu is destroyed with the window; a detached
+                // (auto-hidden) one is not.
+                if GetMenu(hwnd).is_invalid() {
+                    let _ = DestroyMenu((*ptr).menu_bar);
+                }
+                tray::remove(hwnd);
+                crate::platform::win32::clipboard::listen(hwnd, false);
+                if !(*ptr).ui_font.is_invalid() {
+                    let _ = DeleteObject((*ptr).ui_font);
+                }
+                // Results still queued would otherwise leak with the queue.
+                bridge::discard_pending::<WindowState>(hwnd);
                 SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
                 drop(Box::from_raw(ptr));
             }
-            PostQuitMessage(0);
+            // The process ends with its last window.  The oldest window holds
+            // the scratchpad hot key; it passes to the next one.
+            let had_hotkey = owns_session(hwnd);
+            let _ = UnregisterHotKey(hwnd, SCRATCH_HOTKEY_ID);
+            let next = WINDOWS.with(|w| {
+                let mut w = w.borrow_mut();
+                w.retain(|&h| h != hwnd);
+                w.first().copied()
+            });
+            match next {
+                None => PostQuitMessage(0),
+                Some(next) if had_hotkey => {
+                    let ptr = GetWindowLongPtrW(next, GWLP_USERDATA) as *const WindowState;
+                    if !ptr.is_null() {
+                        register_scratch_hotkey(next, &(*ptr).settings);
+                    }
+                }
+                Some(_) => {}
+            }
             LRESULT(0)
         }
 
-        // ── Commands ──────────────────────────────────────────────────────────
-        WM_COMMAND => {
-            let cmd = wparam.0 & 0xFFFF;
+        // ── Minimize to tray ──────────────────────────────────────────────────
+        WM_SYSCOMMAND if (wparam.0 & 0xFFF0) as u32 == SC_MINIMIZE => {
             let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
-            match cmd {
-                IDM_FILE_NEW => {
-                    if !ptr.is_null() {
-                        handle_new_file(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FILE_OPEN => {
-                    if !ptr.is_null() {
-                        handle_file_open(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FILE_SAVE => {
-                    if !ptr.is_null() {
-                        handle_file_save(hwnd, &mut *ptr, false);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FILE_SAVE_AS => {
-                    if !ptr.is_null() {
-                        handle_file_save(hwnd, &mut *ptr, true);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FILE_CLOSE => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        handle_close_tab(hwnd, &mut *ptr, idx);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FILE_EXIT => {
-                    let _ = DestroyWindow(hwnd);
-                    LRESULT(0)
-                }
+            if !ptr.is_null() && (*ptr).settings.minimize_to_tray {
+                let _ = ShowWindow(hwnd, SW_HIDE);
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_HOTKEY if wparam.0 == SCRATCH_HOTKEY_ID as usize => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_scratch_hotkey(hwnd, &mut *ptr);
+            }
+            LRESULT(0)
+        }
+        dialogs::WM_REPLACE_ALL_OPEN => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_replace_all_open(hwnd, &mut *ptr);
+            }
+            LRESULT(0)
+        }
 
-                // ── Edit commands ─────────────────────────────────────────────
-                IDM_EDIT_UNDO => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].undo();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_REDO => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].redo();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_CUT => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].cut();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_COPY => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].copy_to_clipboard();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_PASTE => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].paste();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_DELETE => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].delete_selection();
-                    }
-                    LRESULT(0)
-                }
-                IDM_EDIT_SELECT_ALL => {
-                    if !ptr.is_null() {
-                        let idx = (*ptr).app.active_idx;
-                        (*ptr).sci_views[idx].select_all();
-                    }
-                    LRESULT(0)
-                }
+        document_map::WM_DOCUMENT_MAP_SCROLL => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_document_map_scroll(&mut *ptr, lparam.0 as i32);
+            }
+            LRESULT(0)
+        }
 
-                // ── Format — EOL conversion ───────────────────────────────────
-                IDM_FORMAT_EOL_CRLF => {
-                    if !ptr.is_null() {
-                        handle_eol_convert(hwnd, &mut *ptr, EolMode::Crlf);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FORMAT_EOL_LF => {
-                    if !ptr.is_null() {
-                        handle_eol_convert(hwnd, &mut *ptr, EolMode::Lf);
-                    }
-                    LRESULT(0)
-                }
-                IDM_FORMAT_EOL_CR => {
-                    if !ptr.is_null() {
-                        handle_eol_convert(hwnd, &mut *ptr, EolMode::Cr);
-                    }
-                    LRESULT(0)
-                }
+        dialogs::WM_FIND_ALL => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_find_all(hwnd, &mut *ptr);
+            }
+            LRESULT(0)
+        }
 
-                // ── View — Word Wrap ──────────────────────────────────────────
-                IDM_VIEW_WORD_WRAP => {
-                    if !ptr.is_null() {
-                        handle_word_wrap_toggle(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
+        dialogs::WM_SELECT_MATCHES => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                remember_search(&mut *ptr, false);
+                handle_select_matches(hwnd, &mut *ptr, wparam.0 != 0);
+            }
+            LRESULT(0)
+        }
 
-                // ── View — Dark Mode ──────────────────────────────────────────
-                IDM_VIEW_DARK_MODE => {
-                    if !ptr.is_null() {
-                        handle_dark_mode_toggle(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
+        tray::WM_TRAY_ICON => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_tray_icon(hwnd, &mut *ptr, lparam.0 as u32);
+            }
+            LRESULT(0)
+        }
 
-                // ── View — Tab position ───────────────────────────────────────
-                IDM_VIEW_TAB_TOP => {
-                    if !ptr.is_null() {
-                        handle_tab_position(hwnd, &mut *ptr, TabPosition::Top);
-                    }
-                    LRESULT(0)
-                }
-                IDM_VIEW_TAB_LEFT => {
-                    if !ptr.is_null() {
-                        handle_tab_position(hwnd, &mut *ptr, TabPosition::Left);
-                    }
-                    LRESULT(0)
-                }
-                IDM_VIEW_TAB_RIGHT => {
-                    if !ptr.is_null() {
-                        handle_tab_position(hwnd, &mut *ptr, TabPosition::Right);
-                    }
-                    LRESULT(0)
-                }
+        // ── Menu bar auto-hide ────────────────────────────────────────────────
+        WM_SYSCOMMAND if (wparam.0 & 0xFFF0) as u32 == SC_KEYMENU => {
+            // Alt or an Alt+letter mnemonic: re-attach a hidden menu bar so the
+            // default handling below has a menu to activate.
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() && menu_autohides(&(*ptr).settings) && GetMenu(hwnd).is_invalid() {
+                let _ = SetMenu(hwnd, (*ptr).menu_bar);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_EXITMENULOOP => {
+            // wparam is TRUE for context (TrackPopupMenu) menus; only the
+            // menu bar's own loop should hide it again.
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() && wparam.0 == 0 && menu_autohides(&(*ptr).settings) {
+                let _ = SetMenu(hwnd, HMENU::default());
+            }
+            LRESULT(0)
+        }
+        // A menu is about to open, or a shortcut to run (see
+        // `update_edit_state`): gray what cannot run right now.  The
+        // clipboard changing may also change whether Paste can.
+        WM_INITMENUPOPUP | WM_CLIPBOARDUPDATE => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                update_edit_state(&*ptr);
+            }
+            LRESULT(0)
+        }
 
-                // ── Search commands ───────────────────────────────────────────
-                IDM_SEARCH_FIND => {
-                    if !ptr.is_null() {
-                        handle_find_open(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
-                IDM_SEARCH_REPLACE => {
-                    if !ptr.is_null() {
-                        handle_replace_open(hwnd, &mut *ptr);
-                    }
-                    LRESULT(0)
-                }
-                IDM_SEARCH_FIND_NEXT => {
-                    if !ptr.is_null() {
-                        handle_find_next(hwnd, &mut *ptr, true);
-                    }
-                    LRESULT(0)
-                }
-                IDM_SEARCH_FIND_PREV => {
-                    if !ptr.is_null() {
-                        handle_find_next(hwnd, &mut *ptr, false);
-                    }
-                    LRESULT(0)
-                }
-                IDM_SEARCH_GOTO_LINE => {
-                    if !ptr.is_null() {
-                        let hmodule = GetModuleHandleW(None).unwrap_or_default();
-                        let hinstance = HINSTANCE(hmodule.0);
-                        handle_goto_line(hwnd, &mut *ptr, hinstance);
-                    }
-                    LRESULT(0)
+        // ── Tabs in the title bar ─────────────────────────────────────────────
+        WM_NCCALCSIZE => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() && title_bar_tabs_active(hwnd, &*ptr) {
+                titlebar::nc_calc_size(hwnd, msg, wparam, lparam, (*ptr).dpi)
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_NCHITTEST => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() && title_bar_tabs_active(hwnd, &*ptr) {
+                titlebar::nc_hit_test(hwnd, msg, wparam, lparam, (*ptr).dpi)
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_ERASEBKGND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if ptr.is_null() {
+                return DefWindowProcW(hwnd, msg, wparam, lparam);
+            }
+            erase_background(hwnd, &*ptr, HDC(wparam.0 as *mut _));
+            LRESULT(1)
+        }
+
+        // ── Large File Mode loading ───────────────────────────────────────────
+        WM_LOAD_PROGRESS => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_load_progress(hwnd, &mut *ptr);
+            }
+            LRESULT(0)
+        }
+
+        // ── Background Replace All ────────────────────────────────────────────
+        WM_REPLACE_PROGRESS => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_replace_progress(hwnd, &mut *ptr, false);
+            }
+            LRESULT(0)
+        }
+
+        // The results panel passes right-clicks on (its Scintilla menu is off).
+        WM_CONTEXTMENU => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            let from = HWND(wparam.0 as *mut _);
+            if ptr.is_null() || (*ptr).results.as_ref().map(|p| p.view.hwnd()) != Some(from) {
+                return DefWindowProcW(hwnd, msg, wparam, lparam);
+            }
+            let pt = POINT {
+                x: (lparam.0 & 0xFFFF) as i16 as i32,
+                y: ((lparam.0 >> 16) & 0xFFFF) as i16 as i32,
+            };
+            handle_results_menu(hwnd, &mut *ptr, pt);
+            LRESULT(0)
+        }
+
+        // ── Background task results ───────────────────────────────────────────
+        WM_TASK => {
+            // SAFETY: every WM_TASK for this window comes from a
+            // `UiSender<WindowState>` (`spawn_task`) and is received once,
+            // here; without state it is dropped unrun.
+            let message = bridge::receive::<WindowState>(lparam);
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                message(hwnd, &mut *ptr);
+            }
+            LRESULT(0)
+        }
+
+        WM_SNIPPET_TAB => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            let view = HWND(lparam.0 as *mut _);
+            let used = !ptr.is_null() && handle_snippet_tab(&mut *ptr, view, wparam.0 != 0);
+            LRESULT(used as isize)
+        }
+
+        // ── Tab drag-reorder ──────────────────────────────────────────────────
+        tab_drag::WM_TAB_MOVE => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                handle_tab_move(&mut *ptr, wparam.0, lparam.0 as usize);
+            }
+            LRESULT(0)
+        }
+
+        // ── Tab dragged out of the strip ──────────────────────────────────────
+        tab_drag::WM_TAB_DETACH => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                let pt = tab_drag::point_from_lparam(lparam);
+                handle_tab_detach(hwnd, &mut *ptr, wparam.0, pt);
+            }
+            LRESULT(0)
+        }
+
+        // ── Other Rivet windows ───────────────────────────────────────────────
+        ipc::WM_COPYDATA => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            match ipc::decode_request(lparam) {
+                Some((code, path)) if !ptr.is_null() => {
+                    LRESULT(handle_peer_request(hwnd, &mut *ptr, code, &path) as isize)
                 }
+                _ => LRESULT(0),
+            }
+        }
 
-                IDM_HELP_ABOUT => {
-                    about_dialog(hwnd);
-                    LRESULT(0)
+        // ── Commands ──────────────────────────────────────────────────────────
+        WM_COMMAND => {
+            let Some(cmd) = command_for_id(wparam.0 & 0xFFFF) else {
+                return DefWindowProcW(hwnd, msg, wparam, lparam);
+            };
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            match cmd {
+                Cmd::FileExit => {
+                    let _ = DestroyWindow(hwnd);
                 }
-                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+                Cmd::About => about_dialog(hwnd),
+                _ if !ptr.is_null() => run_command(hwnd, &mut *ptr, cmd),
+                _ => {}
             }
+            LRESULT(0)
         }
 
         // ── Scintilla + tab notifications ─────────────────────────────────────
@@ -1269,6 +2976,58 @@ unsafe extern "system" fn wnd_proc(
                 return LRESULT(0);
             }
 
+            // The toolbar only asks to be custom-drawn (in dark mode).
+            if let Some(bar) = (*ptr).toolbar.as_ref() {
+                if hdr.hwndFrom == bar.hwnd() {
+                    return bar.custom_draw(lparam, toolbar_colours(&*ptr));
+                }
+            }
+
+            // The folder panel handles its own tree notifications and says
+            // what, if anything, the window should do.
+            if (*ptr)
+                .folder
+                .as_ref()
+                .is_some_and(|f| f.hwnd() == hdr.hwndFrom)
+            {
+                let cmd = (*ptr).folder.as_mut().and_then(|f| f.notify(hwnd, hdr));
+                if let Some(cmd) = cmd {
+                    handle_folder_command(hwnd, &mut *ptr, cmd);
+                }
+                return LRESULT(0);
+            }
+
+            // The results panel is a Scintilla view too; none of the editor
+            // handling below applies to it.
+            if let Some(panel) = (*ptr).results.as_ref() {
+                if hdr.hwndFrom == panel.view.hwnd() {
+                    if hdr.code == SCN_UPDATEUI {
+                        handle_results_click(hwnd, &mut *ptr);
+                    }
+                    return LRESULT(0);
+                }
+            }
+
+            // The document map shares the editor's document, so it reports
+            // the same edits; it follows the editor instead.
+            if (*ptr)
+                .map
+                .as_ref()
+                .is_some_and(|m| m.view.hwnd() == hdr.hwndFrom)
+            {
+                return LRESULT(0);
+            }
+
+            // So is the outline panel.
+            if let Some(panel) = (*ptr).outline.as_ref() {
+                if hdr.hwndFrom == panel.view.hwnd() {
+                    if hdr.code == SCN_UPDATEUI {
+                        handle_outline_click(hwnd, &mut *ptr);
+                    }
+                    return LRESULT(0);
+                }
+            }
+
             match hdr.code {
                 // ── Tab-control ───────────────────────────────────────────────
                 TCN_SELCHANGE => {
@@ -1280,49 +3039,109 @@ unsafe extern "system" fn wnd_proc(
                     let new_idx = sel.0 as usize;
 
                     if new_idx != (*ptr).app.active_idx {
-                        // Hide the outgoing view, switch, show the incoming view.
-                        (*ptr).sci_views[(*ptr).app.active_idx].show(false);
-                        (*ptr).app.active_idx = new_idx;
-                        (*ptr).sci_views[new_idx].show(true);
+                        swap_visible_view(hwnd, &mut *ptr, new_idx);
 
                         // Sync EOL from the newly-visible view.
                         let eol = (*ptr).sci_views[new_idx].eol_mode();
                         (*ptr).app.active_doc_mut().eol = eol;
 
-                        // Resize the newly-visible Scintilla to fill its zone.
-                        let mut rc = RECT::default();
-                        let _ = GetClientRect(hwnd, &mut rc);
-                        layout_children(&*ptr, rc.right, rc.bottom);
-
                         // Reflect the new tab's word-wrap state in the View menu.
                         let wrap = (*ptr).app.active_doc().word_wrap;
-                        update_wrap_checkmark(hwnd, wrap);
+                        update_wrap_checkmark(&*ptr, wrap);
+                        update_monitor_checkmark(&*ptr);
 
                         update_window_title(hwnd, &(*ptr).app);
                         update_status_bar(&*ptr);
                     }
                 }
 
+                TTN_GETDISPINFOW => {
+                    let idx = hdr.idFrom;
+                    if idx < (*ptr).app.tab_count() {
+                        // SAFETY: TTN_GETDISPINFOW carries an NMTTDISPINFOW.
+                        let info =
+                            &mut *(lparam.0 as *mut windows::Win32::UI::Controls::NMTTDISPINFOW);
+                        let state = &mut *ptr;
+                        let text = tab_tooltip(state, idx);
+                        state.tooltip_buf = text.encode_utf16().chain(std::iter::once(0)).collect();
+                        // The tooltip copies the text before the buffer changes.
+                        info.lpszText = PWSTR(state.tooltip_buf.as_mut_ptr());
+                    }
+                }
+
                 // ── Scintilla — dirty tracking ─────────────────────────────────
                 SCN_SAVEPOINTLEFT => {
-                    (*ptr).app.active_doc_mut().dirty = true;
-                    let idx = (*ptr).app.active_idx;
+                    let idx = notifying_tab(&*ptr, hdr.hwndFrom);
+                    (*ptr).app.tabs[idx].dirty = true;
+                    (*ptr).app.tabs[idx].undone_past_save = (*ptr).last_change_undone;
                     sync_tab_label(&*ptr, idx);
                     update_window_title(hwnd, &(*ptr).app);
                 }
                 SCN_SAVEPOINTREACHED => {
-                    (*ptr).app.active_doc_mut().dirty = false;
-                    let idx = (*ptr).app.active_idx;
+                    let idx = notifying_tab(&*ptr, hdr.hwndFrom);
+                    (*ptr).app.tabs[idx].dirty = false;
+                    (*ptr).app.tabs[idx].undone_past_save = false;
                     sync_tab_label(&*ptr, idx);
                     update_window_title(hwnd, &(*ptr).app);
                 }
 
-                // ── Scintilla — caret moved ────────────────────────────────────
+                // ── Scintilla — typing ─────────────────────────────────────────
+                SCN_CHARADDED => {
+                    let state = &mut *ptr;
+                    if hdr.hwndFrom == state.sci_views[state.app.active_idx].hwnd() {
+                        // SAFETY: Scintilla notifications carry an SCNotification.
+                        let ch = (*(lparam.0 as *const NotifyHeader)).ch;
+                        if let (true, Ok(typed)) =
+                            (state.settings.auto_close_brackets, u8::try_from(ch))
+                        {
+                            handle_auto_close(state, typed);
+                        }
+                        if state.settings.autocomplete_after > 0 {
+                            handle_complete_word(state, false);
+                        }
+                    }
+                }
+                // The entry picked from the completion list may be a snippet
+                // trigger.
+                SCN_AUTOCCOMPLETED => {
+                    let state = &mut *ptr;
+                    if hdr.hwndFrom == state.sci_views[state.app.active_idx].hwnd() {
+                        expand_snippet(state);
+                    }
+                }
+
+                // ── Scintilla — caret moved or text changed ────────────────────
                 SCN_UPDATEUI => {
                     let idx = (*ptr).app.active_idx;
                     let eol = (*ptr).sci_views[idx].eol_mode();
                     (*ptr).app.active_doc_mut().eol = eol;
                     update_status_bar(&*ptr);
+                    update_edit_state(&*ptr);
+                    sync_document_map(&mut *ptr);
+                    if (*ptr).settings.highlight_occurrences {
+                        let _ = SetTimer(hwnd, OCCURRENCE_TIMER_ID, OCCURRENCE_DELAY_MS, None);
+                    }
+                }
+                // Text typed, pasted or replaced: the git gutter follows once
+                // editing pauses.  An undo's first notice precedes the
+                // `SCN_SAVEPOINTLEFT` it may cause, and so does a user edit's
+                // "before" notice.
+                SCN_MODIFIED => {
+                    // SAFETY: Scintilla notifications carry an SCNotification.
+                    let kind = (*(lparam.0 as *const NotifyHeader)).modification_type;
+                    let state = &mut *ptr;
+                    let change = SC_MOD_INSERTTEXT
+                        | SC_MOD_DELETETEXT
+                        | SC_MOD_BEFOREINSERT
+                        | SC_MOD_BEFOREDELETE;
+                    if kind & change != 0 {
+                        state.last_change_undone = kind & SC_PERFORMED_UNDO != 0;
+                    }
+                    if kind & (SC_MOD_INSERTTEXT | SC_MOD_DELETETEXT) != 0
+                        && hdr.hwndFrom == state.sci_views[state.app.active_idx].hwnd()
+                    {
+                        let _ = SetTimer(hwnd, GIT_TIMER_ID, GIT_DELAY_MS, None);
+                    }
                 }
 
                 _ => {}
@@ -1332,22 +3151,82 @@ unsafe extern "system" fn wnd_proc(
 
         // ── Periodic session checkpoint ───────────────────────────────────────
         WM_TIMER => {
-            if wparam.0 == AUTOSAVE_TIMER_ID {
-                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const WindowState;
-                if !ptr.is_null() {
-                    save_session(&*ptr);
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                match wparam.0 {
+                    AUTOSAVE_TIMER_ID => {
+                        save_scratch_tabs(&mut *ptr);
+                        if owns_session(hwnd) {
+                            save_session(hwnd, &mut *ptr, SessionSave::Checkpoint);
+                        }
+                    }
+                    MONITOR_TIMER_ID => handle_monitor_tick(hwnd, &mut *ptr),
+                    STATUS_FLASH_TIMER_ID => {
+                        let _ = KillTimer(hwnd, STATUS_FLASH_TIMER_ID);
+                        update_status_bar(&*ptr);
+                    }
+                    OCCURRENCE_TIMER_ID => {
+                        let _ = KillTimer(hwnd, OCCURRENCE_TIMER_ID);
+                        update_occurrences(&mut *ptr);
+                    }
+                    GIT_TIMER_ID => {
+                        let _ = KillTimer(hwnd, GIT_TIMER_ID);
+                        refresh_git_gutter(hwnd, &mut *ptr);
+                    }
+                    _ => {}
                 }
             }
             LRESULT(0)
         }
 
-        // ── DPI change ────────────────────────────────────────────────────────
-        WM_DPICHANGED => {
-            let new_dpi = (wparam.0 & 0xFFFF) as u32;
+        // ── System colours ────────────────────────────────────────────────────
+        WM_SETTINGCHANGE => {
+            // Sent (among much else) when the accent colour or app mode
+            // changes, or High Contrast is switched on or off.
             let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
             if !ptr.is_null() {
-                let state = &mut *ptr;
-                state.dpi = new_dpi;
+                follow_system_theme(hwnd, &mut *ptr);
+                refresh_system_colours(hwnd, &mut *ptr);
+                if (*ptr).settings.accent_highlight {
+                    apply_accent_highlights(&*ptr);
+                }
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DWMCOLORIZATIONCOLORCHANGED => {
+            // Sent with the accent colour, and by some builds instead of
+            // WM_SETTINGCHANGE when the app mode flips.
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                follow_system_theme(hwnd, &mut *ptr);
+                if (*ptr).settings.accent_highlight {
+                    apply_accent_highlights(&*ptr);
+                }
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_SYSCOLORCHANGE => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                // Common controls only hear of it from their parent.
+                let _ = SendMessageW((*ptr).hwnd_tab, msg, wparam, lparam);
+                let _ = SendMessageW((*ptr).hwnd_status, msg, wparam, lparam);
+                refresh_system_colours(hwnd, &mut *ptr);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+
+        // ── DPI change ────────────────────────────────────────────────────────
+        WM_DPICHANGED => {
+            let new_dpi = (wparam.0 & 0xFFFF) as u32;
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+            if !ptr.is_null() {
+                let state = &mut *ptr;
+                state.dpi = new_dpi;
+                apply_ui_font(state);
+                if state.toolbar.is_some() {
+                    apply_toolbar(hwnd, state);
+                }
                 // Windows provides the optimal new window bounds in LPARAM.
                 // SAFETY: Windows guarantees LPARAM is a valid *const RECT for WM_DPICHANGED.
                 let r = &*(lparam.0 as *const RECT);
@@ -1361,6 +3240,10 @@ unsafe extern "system" fn wnd_proc(
                     SWP_NOZORDER | SWP_NOACTIVATE,
                 );
                 update_statusbar_parts(state);
+                // Scintilla follows its own window's DPI but only re-measures
+                // fonts when styles change, so restyle every view to keep the
+                // text the same physical size on the new monitor.
+                reapply_all_themes(state);
             }
             LRESULT(0)
         }
@@ -1369,6 +3252,289 @@ unsafe extern "system" fn wnd_proc(
     }
 }
 
+// ── Command dispatch ──────────────────────────────────────────────────────────
+
+/// Translate a menu / accelerator ID from `WM_COMMAND` into a `Command`.
+fn command_for_id(id: usize) -> Option<Cmd> {
+    Some(match id {
+        IDM_FILE_NEW => Cmd::FileNew,
+        IDM_FILE_NEW_WINDOW => Cmd::FileNewWindow,
+        IDM_FILE_OPEN => Cmd::FileOpen,
+        IDM_FILE_RELOAD => Cmd::FileReload,
+        IDM_FILE_SAVE => Cmd::FileSave,
+        IDM_FILE_SAVE_AS => Cmd::FileSaveAs,
+        IDM_FILE_SAVE_WORKSPACE => Cmd::FileSaveWorkspace,
+        IDM_FILE_OPEN_WORKSPACE => Cmd::FileOpenWorkspace,
+        IDM_FILE_OPEN_FOLDER => Cmd::FileOpenFolder,
+        IDM_FILE_PROPERTIES => Cmd::FileProperties,
+        IDM_FILE_GOTO => Cmd::FileGoto,
+        IDM_FILE_CLOSE => Cmd::FileClose,
+        IDM_FILE_EXIT => Cmd::FileExit,
+        IDM_EDIT_UNDO => Cmd::Undo,
+        IDM_EDIT_REDO => Cmd::Redo,
+        IDM_EDIT_CLEAR_UNDO => Cmd::ClearUndoHistory,
+        IDM_EDIT_CUT => Cmd::Cut,
+        IDM_EDIT_COPY => Cmd::Copy,
+        IDM_EDIT_PASTE => Cmd::Paste,
+        IDM_EDIT_DELETE => Cmd::Delete,
+        IDM_EDIT_SELECT_ALL => Cmd::SelectAll,
+        IDM_EDIT_COPY_PATH => Cmd::CopyPath,
+        IDM_EDIT_PASTE_HISTORY => Cmd::PasteFromHistory,
+        IDM_EDIT_COPY_RTF => Cmd::CopyAsRtf,
+        IDM_EDIT_COPY_HTML => Cmd::CopyAsHtml,
+        IDM_EDIT_COLUMN_EDITOR => Cmd::ColumnEditor,
+        IDM_EDIT_JOIN_LINES => Cmd::LineOperation(LineOp::Join),
+        IDM_EDIT_SPLIT_LINES => Cmd::LineOperation(LineOp::Split),
+        IDM_EDIT_REMOVE_DUPLICATES => Cmd::LineOperation(LineOp::RemoveDuplicates),
+        IDM_EDIT_REMOVE_CONSECUTIVE_DUPLICATES => {
+            Cmd::LineOperation(LineOp::RemoveConsecutiveDuplicates)
+        }
+        IDM_EDIT_REMOVE_EMPTY => Cmd::LineOperation(LineOp::RemoveEmpty),
+        IDM_EDIT_REMOVE_BLANK => Cmd::LineOperation(LineOp::RemoveBlank),
+        IDM_EDIT_COMPLETE_WORD => Cmd::CompleteWord,
+        IDM_EDIT_SNIPPETS => Cmd::EditSnippets,
+        IDM_EDIT_AUTO_CLOSE => Cmd::ToggleAutoClose,
+        IDM_EDIT_LINE_COMMENT => Cmd::LineComment,
+        IDM_EDIT_BLOCK_COMMENT => Cmd::BlockComment,
+        IDM_FORMAT_EOL_CRLF => Cmd::ConvertEol(EolMode::Crlf),
+        IDM_FORMAT_EOL_LF => Cmd::ConvertEol(EolMode::Lf),
+        IDM_FORMAT_EOL_CR => Cmd::ConvertEol(EolMode::Cr),
+        IDM_FORMAT_ADD_BOM => Cmd::SetBom(true),
+        IDM_FORMAT_REMOVE_BOM => Cmd::SetBom(false),
+        id if (IDM_FORMAT_ENCODING_FIRST..IDM_FORMAT_ENCODING_FIRST + Encoding::ALL.len())
+            .contains(&id) =>
+        {
+            Cmd::SetEncoding(Encoding::ALL[id - IDM_FORMAT_ENCODING_FIRST])
+        }
+        id if (IDM_FORMAT_REOPEN_FIRST..IDM_FORMAT_REOPEN_FIRST + Encoding::ALL.len())
+            .contains(&id) =>
+        {
+            Cmd::ReopenWithEncoding(Encoding::ALL[id - IDM_FORMAT_REOPEN_FIRST])
+        }
+        IDM_VIEW_WORD_WRAP => Cmd::ToggleWordWrap,
+        IDM_VIEW_DARK_MODE => Cmd::ToggleDarkMode,
+        IDM_VIEW_FOLLOW_THEME => Cmd::ToggleFollowTheme,
+        IDM_VIEW_TAB_TOP => Cmd::TabsTop,
+        IDM_VIEW_TAB_LEFT => Cmd::TabsLeft,
+        IDM_VIEW_TAB_RIGHT => Cmd::TabsRight,
+        IDM_VIEW_AUTOHIDE_MENU => Cmd::ToggleAutoHideMenu,
+        IDM_VIEW_TITLEBAR_TABS => Cmd::ToggleTitleBarTabs,
+        IDM_VIEW_MULTILINE_TABS => Cmd::ToggleMultilineTabs,
+        IDM_VIEW_TRAY => Cmd::ToggleTray,
+        IDM_VIEW_CARET => Cmd::CaretSettings,
+        IDM_VIEW_NEXT_CHUNK => Cmd::NextChunk,
+        IDM_VIEW_PREV_CHUNK => Cmd::PrevChunk,
+        IDM_VIEW_ACCENT => Cmd::ToggleAccent,
+        IDM_VIEW_OCCURRENCES => Cmd::ToggleOccurrences,
+        IDM_VIEW_SPEAK_POSITION => Cmd::SpeakPosition,
+        IDM_VIEW_MONITOR => Cmd::ToggleMonitor,
+        IDM_VIEW_RELATIVE_PATHS => Cmd::ToggleRelativePaths,
+        IDM_VIEW_REFRESH_HIGHLIGHTING => Cmd::RefreshHighlighting,
+        IDM_VIEW_LONG_LINES => Cmd::AllowLongLines,
+        IDM_VIEW_SWITCH_TAB => Cmd::SwitchTab,
+        IDM_VIEW_TOOLBAR => Cmd::ToggleToolbar,
+        IDM_VIEW_TAB_ICONS => Cmd::ToggleTabIcons,
+        IDM_VIEW_FOLDER_PANEL => Cmd::ToggleFolderPanel,
+        IDM_VIEW_OUTLINE => Cmd::ToggleOutline,
+        IDM_VIEW_DOCUMENT_MAP => Cmd::ToggleDocumentMap,
+        IDM_VIEW_NEXT_PANE => Cmd::FocusPane { forward: true },
+        IDM_VIEW_PREV_PANE => Cmd::FocusPane { forward: false },
+        IDM_SEARCH_FIND => Cmd::Find,
+        IDM_SEARCH_REPLACE => Cmd::Replace,
+        IDM_SEARCH_FIND_NEXT => Cmd::FindNext,
+        IDM_SEARCH_FIND_PREV => Cmd::FindPrev,
+        IDM_SEARCH_FIND_SELECTED => Cmd::FindSelected,
+        IDM_SEARCH_FIND_ALL => Cmd::FindAll,
+        IDM_SEARCH_REPLACE_ALL_OPEN => Cmd::ReplaceAllOpen,
+        IDM_SEARCH_EXTENDED => Cmd::ToggleExtended,
+        IDM_SEARCH_CLOSE_RESULTS => Cmd::CloseResults,
+        IDC_CANCEL_TASK => Cmd::CancelTask,
+        IDM_SEARCH_GOTO_LINE => Cmd::GotoLine,
+        IDM_SEARCH_NEXT_CHANGE => Cmd::NextChange,
+        IDM_SEARCH_PREV_CHANGE => Cmd::PrevChange,
+        IDM_FILE_RESTORE_ALWAYS => Cmd::RestoreSession(RestoreSession::Always),
+        IDM_FILE_RESTORE_NEVER => Cmd::RestoreSession(RestoreSession::Never),
+        IDM_FILE_RESTORE_ASK => Cmd::RestoreSession(RestoreSession::Ask),
+        IDM_FILE_KEEP_UNSAVED => Cmd::ToggleKeepUnsaved,
+        IDM_SEARCH_FEEDBACK_BEEP => Cmd::NotFoundFeedback(NotFoundFeedback::Beep),
+        IDM_SEARCH_FEEDBACK_STATUS => Cmd::NotFoundFeedback(NotFoundFeedback::Status),
+        IDM_SEARCH_FEEDBACK_TASKBAR => Cmd::NotFoundFeedback(NotFoundFeedback::Taskbar),
+        IDM_SEARCH_FEEDBACK_SILENT => Cmd::NotFoundFeedback(NotFoundFeedback::Silent),
+        IDM_SEARCH_SELECT_MATCHES => Cmd::SelectMatches,
+        IDM_SEARCH_ADD_MATCHES => Cmd::AddMatches,
+        IDM_TOOLS_RUN => Cmd::Run,
+        IDM_TOOLS_CONFIGURE => Cmd::ConfigureTools,
+        IDM_TOOLS_SHORTCUTS => Cmd::Shortcuts,
+        IDM_TOOLS_COMPARE_FILE => Cmd::CompareFile,
+        IDM_TOOLS_COMPARE_TAB => Cmd::CompareTab,
+        IDM_TOOLS_ASSOCIATIONS => Cmd::FileAssociations,
+        IDM_TOOLS_BASE64_ENCODE => Cmd::Transform(Transform::Base64Encode),
+        IDM_TOOLS_BASE64_DECODE => Cmd::Transform(Transform::Base64Decode),
+        IDM_TOOLS_URL_ENCODE => Cmd::Transform(Transform::UrlEncode),
+        IDM_TOOLS_URL_DECODE => Cmd::Transform(Transform::UrlDecode),
+        IDM_TOOLS_HTML_ESCAPE => Cmd::Transform(Transform::HtmlEscape),
+        IDM_TOOLS_HTML_UNESCAPE => Cmd::Transform(Transform::HtmlUnescape),
+        IDM_TOOLS_FORMAT_JSON => Cmd::FormatJson,
+        IDM_TOOLS_MINIFY_JSON => Cmd::MinifyJson,
+        IDM_TOOLS_GENERATE_HASH => Cmd::GenerateHash,
+        IDM_TOOLS_TEXT_PIPELINE => Cmd::TextPipeline,
+        id if (IDM_TOOLS_FIRST..IDM_TOOLS_FIRST + MAX_TOOLS).contains(&id) => {
+            Cmd::Tool(id - IDM_TOOLS_FIRST)
+        }
+        IDM_HELP_ABOUT => Cmd::About,
+        _ => return None,
+    })
+}
+
+/// Run `cmd`: document-level commands go through `commands::execute`; the
+/// rest need dialogs or window chrome and are handled here.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn run_command(hwnd: HWND, state: &mut WindowState, cmd: Cmd) {
+    let idx = state.app.active_idx;
+    if let Cmd::ConvertEol(eol) = cmd {
+        if state.app.active_doc().degraded.large_file {
+            start_eol_conversion(hwnd, state, eol);
+            return;
+        }
+    }
+    match commands::execute(cmd, &mut state.app, &state.sci_views[idx]) {
+        Outcome::Done => return,
+        Outcome::Updated => {
+            update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+            update_edit_state(state);
+            update_status_bar(state);
+            return;
+        }
+        Outcome::Redetect => {
+            redetect_language(state, idx);
+            return;
+        }
+        Outcome::Host => {}
+    }
+    match cmd {
+        Cmd::FileNew => handle_new_file(hwnd, state),
+        Cmd::FileNewWindow => {
+            let _ = open_window(state);
+        }
+        Cmd::FileOpen => handle_file_open(hwnd, state),
+        Cmd::FileReload => {
+            handle_file_reload(hwnd, state, None);
+        }
+        Cmd::ReopenWithEncoding(encoding) => {
+            handle_file_reload(hwnd, state, Some(encoding));
+        }
+        Cmd::FileSave => handle_file_save(hwnd, state, false),
+        Cmd::FileSaveAs => handle_file_save(hwnd, state, true),
+        Cmd::FileSaveWorkspace => handle_workspace_save(hwnd, state),
+        Cmd::FileOpenWorkspace => handle_workspace_open(hwnd, state),
+        Cmd::FileOpenFolder => handle_open_folder(hwnd, state),
+        Cmd::FileProperties => handle_document_properties(hwnd, state),
+        Cmd::FileGoto => handle_goto_file(hwnd, state),
+        Cmd::SetBom(bom) => handle_set_bom(hwnd, state, bom),
+        Cmd::SetEncoding(encoding) => handle_set_encoding(hwnd, state, encoding),
+        Cmd::FileClose => handle_close_tab(hwnd, state, idx),
+        Cmd::CopyPath => handle_copy_full_path(hwnd, state),
+        Cmd::PasteFromHistory => handle_paste_from_history(hwnd, state),
+        Cmd::CopyAsRtf => handle_copy_special(hwnd, state, false),
+        Cmd::CopyAsHtml => handle_copy_special(hwnd, state, true),
+        Cmd::ColumnEditor => handle_column_editor(hwnd, state),
+        Cmd::CompleteWord => handle_complete_word(state, true),
+        Cmd::EditSnippets => handle_edit_snippets(hwnd, state),
+        Cmd::ToggleAutoClose => handle_auto_close_toggle(state),
+        Cmd::LineComment => handle_toggle_comment(state, false),
+        Cmd::BlockComment => handle_toggle_comment(state, true),
+        Cmd::LineOperation(op) => handle_line_operation(hwnd, state, op),
+        Cmd::ToggleDarkMode => handle_dark_mode_toggle(hwnd, state),
+        Cmd::ToggleFollowTheme => handle_follow_theme_toggle(hwnd, state),
+        Cmd::TabsTop => handle_tab_position(hwnd, state, TabPosition::Top),
+        Cmd::TabsLeft => handle_tab_position(hwnd, state, TabPosition::Left),
+        Cmd::TabsRight => handle_tab_position(hwnd, state, TabPosition::Right),
+        Cmd::ToggleAutoHideMenu => handle_autohide_menu_toggle(hwnd, state),
+        Cmd::ToggleTitleBarTabs => handle_title_bar_tabs_toggle(hwnd, state),
+        Cmd::ToggleMultilineTabs => handle_multiline_tabs_toggle(hwnd, state),
+        Cmd::ToggleTray => handle_tray_toggle(hwnd, state),
+        Cmd::CaretSettings => handle_caret_settings(hwnd, state),
+        Cmd::NextChunk => handle_viewer_page(state, true),
+        Cmd::PrevChunk => handle_viewer_page(state, false),
+        Cmd::ToggleAccent => handle_accent_toggle(state),
+        Cmd::ToggleOccurrences => handle_occurrences_toggle(state),
+        Cmd::SpeakPosition => handle_speak_position(state),
+        Cmd::ToggleMonitor => handle_monitor_toggle(hwnd, state),
+        Cmd::ToggleRelativePaths => handle_relative_paths_toggle(state),
+        Cmd::RefreshHighlighting => handle_refresh_highlighting(state),
+        Cmd::AllowLongLines => handle_allow_long_lines(state),
+        Cmd::SwitchTab => handle_switch_tab(hwnd, state),
+        Cmd::ToggleToolbar => handle_toolbar_toggle(hwnd, state),
+        Cmd::ToggleTabIcons => handle_tab_icons_toggle(hwnd, state),
+        Cmd::ToggleFolderPanel => handle_folder_panel_toggle(hwnd, state),
+        Cmd::ToggleOutline => handle_outline_toggle(hwnd, state),
+        Cmd::ToggleDocumentMap => handle_document_map_toggle(hwnd, state),
+        Cmd::FocusPane { forward } => handle_focus_pane(state, forward),
+        Cmd::Find => handle_find_open(hwnd, state),
+        Cmd::Replace => handle_replace_open(hwnd, state),
+        Cmd::FindNext => handle_find_next(hwnd, state, true),
+        Cmd::FindPrev => handle_find_next(hwnd, state, false),
+        Cmd::FindSelected => handle_find_selected(hwnd, state),
+        Cmd::FindAll => {
+            if last_search(state).is_none() && !seed_find_text(state) {
+                handle_find_open(hwnd, state);
+            } else {
+                handle_find_all(hwnd, state);
+            }
+        }
+        Cmd::CloseResults => handle_close_results(hwnd, state),
+        Cmd::CancelTask => handle_cancel_task(hwnd, state),
+        Cmd::ToggleExtended => handle_extended_toggle(state),
+        Cmd::ReplaceAllOpen => {
+            if last_search(state).is_none() {
+                handle_replace_open(hwnd, state);
+            } else {
+                handle_replace_all_open(hwnd, state);
+            }
+        }
+        Cmd::GotoLine => {
+            let hmodule = GetModuleHandleW(None).unwrap_or_default();
+            handle_goto_line(hwnd, state, HINSTANCE(hmodule.0));
+        }
+        Cmd::NextChange => handle_goto_change(hwnd, state, true),
+        Cmd::PrevChange => handle_goto_change(hwnd, state, false),
+        Cmd::NotFoundFeedback(choice) => handle_feedback_choice(state, choice),
+        Cmd::RestoreSession(choice) => handle_restore_choice(state, choice),
+        Cmd::ToggleKeepUnsaved => handle_keep_unsaved_toggle(state),
+        Cmd::SelectMatches => handle_select_matches(hwnd, state, false),
+        Cmd::AddMatches => handle_select_matches(hwnd, state, true),
+        Cmd::Run => handle_tools_run(hwnd, state),
+        Cmd::ConfigureTools => handle_tools_configure(hwnd, state),
+        Cmd::FileAssociations => handle_file_associations(hwnd, state),
+        Cmd::Transform(t) => handle_transform(state, t),
+        Cmd::FormatJson => handle_json(hwnd, state, false),
+        Cmd::MinifyJson => handle_json(hwnd, state, true),
+        Cmd::GenerateHash => handle_generate_hash(hwnd, state),
+        Cmd::TextPipeline => handle_text_pipeline(hwnd, state),
+        Cmd::Shortcuts => handle_tools_shortcuts(hwnd, state),
+        Cmd::CompareFile => handle_compare_file(hwnd, state),
+        Cmd::CompareTab => handle_compare_tab(hwnd, state),
+        Cmd::Tool(i) => handle_tool_launch(state, i),
+        Cmd::FileExit => {
+            let _ = DestroyWindow(hwnd);
+        }
+        Cmd::About => about_dialog(hwnd),
+        // Run by `commands::execute` above.
+        Cmd::Undo
+        | Cmd::Redo
+        | Cmd::ClearUndoHistory
+        | Cmd::Cut
+        | Cmd::Copy
+        | Cmd::Paste
+        | Cmd::Delete
+        | Cmd::SelectAll
+        | Cmd::ConvertEol(_)
+        | Cmd::ToggleWordWrap => {}
+    }
+}
+
 // ── New file ──────────────────────────────────────────────────────────────────
 
 /// Handle File > New: open a fresh untitled tab.
@@ -1380,7 +3546,7 @@ unsafe extern "system" fn wnd_proc(
 /// Called only from WM_COMMAND on the UI thread with a valid `state`.
 unsafe fn handle_new_file(hwnd: HWND, state: &mut WindowState) {
     // Already a clean untitled tab — nothing to do.
-    if state.app.active_doc().path.is_none() && !state.app.active_doc().dirty {
+    if state.app.active_doc().is_blank() {
         return;
     }
     open_untitled_tab(hwnd, state);
@@ -1400,7 +3566,18 @@ unsafe fn handle_file_open(hwnd: HWND, state: &mut WindowState) {
     let Some(path) = show_open_dialog(hwnd) else {
         return;
     };
+    open_path(hwnd, state, path);
+}
 
+/// Open `path` as File > Open does once the dialog has returned; also used
+/// for files named on the command line (jump list entries).
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn open_path(hwnd: HWND, state: &mut WindowState, path: std::path::PathBuf) {
+    // One spelling per file, so a file reached by a relative, verbatim or
+    // `..` path still finds its tab.
+    let path = crate::paths::normalize(&path);
     // Activate the existing tab if this file is already open.
     if let Some(dup_idx) = state
         .app
@@ -1408,451 +3585,3107 @@ unsafe fn handle_file_open(hwnd: HWND, state: &mut WindowState) {
         .iter()
         .position(|t| t.path.as_deref() == Some(path.as_path()))
     {
-        if dup_idx != state.app.active_idx {
-            state.sci_views[state.app.active_idx].show(false);
-            state.app.active_idx = dup_idx;
-            state.sci_views[dup_idx].show(true);
-            let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(dup_idx), LPARAM(0));
-            let eol = state.sci_views[dup_idx].eol_mode();
-            state.app.active_doc_mut().eol = eol;
-            let mut rc = RECT::default();
-            let _ = GetClientRect(hwnd, &mut rc);
-            layout_children(state, rc.right, rc.bottom);
-            update_window_title(hwnd, &state.app);
-            update_status_bar(state);
-        }
+        activate_tab(hwnd, state, dup_idx);
+        note_recent(state, &path);
         return;
     }
 
-    let bytes = match std::fs::read(&path) {
-        Ok(b) => b,
-        Err(e) => {
-            show_error_dialog(&format!("Could not open file:\n{e}"));
-            return;
+    // Another Rivet window may hold unsaved edits to the same file; opening
+    // it writable here would let whichever window saves last win.
+    let mut read_only = false;
+    if let Some(peer) = ipc::find_dirty_peer(hwnd, CLASS_NAME, &path) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let msg = format!(
+            "\"{name}\" has unsaved changes in another Rivet window.\n\n\
+             Yes \u{2014} switch to that window\n\
+             No \u{2014} open a read-only copy here\n\
+             Cancel \u{2014} do nothing"
+        );
+        let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+        // SAFETY: wide is valid null-terminated UTF-16 that outlives the call.
+        let result = MessageBoxW(
+            hwnd,
+            PCWSTR(wide.as_ptr()),
+            w!("Rivet"),
+            MB_YESNOCANCEL | MB_ICONWARNING,
+        );
+        match result {
+            r if r == IDYES => {
+                if ipc::activate_peer(hwnd, peer, &path) {
+                    let _ = SetForegroundWindow(peer);
+                }
+                return;
+            }
+            r if r == IDNO => read_only = true,
+            _ => return,
+        }
+    }
+
+    let meta = std::fs::metadata(&path);
+    // A file marked read-only opens as a read-only tab, so Save offers Save
+    // As instead of failing.
+    read_only |= meta.as_ref().is_ok_and(|m| m.permissions().readonly());
+    let size = meta.map_or(0, |m| m.len());
+    if size > HUGE_FILE_THRESHOLD_BYTES {
+        match open_viewer(hwnd, state, path.clone()) {
+            Ok(()) => note_recent(state, &path),
+            Err(e) => {
+                show_error_dialog(&FileProblem::of(&e).message("open", &path, &e.to_string()))
+            }
         }
+        return;
+    }
+    if size > state.app.large_files.threshold {
+        note_recent(state, &path);
+        start_chunked_load(hwnd, state, path, size, read_only);
+        return;
+    }
+
+    let Some(bytes) = read_file_prompting(hwnd, "open", &path) else {
+        return;
     };
 
+    note_recent(state, &path);
     // Reuse the current tab if it is a clean untitled document.
-    if state.app.active_doc().path.is_none() && !state.app.active_doc().dirty {
-        load_file_into_active_tab(hwnd, state, path, &bytes);
+    if state.app.active_doc().is_blank() {
+        load_file_into_active_tab(hwnd, state, path, &bytes, None);
     } else {
-        open_file_in_new_tab(hwnd, state, path, &bytes);
+        open_file_in_new_tab(hwnd, state, path, &bytes, None);
     }
+    if read_only {
+        set_tab_read_only(hwnd, state, state.app.active_idx, true);
+    }
+    refresh_outline(state);
 }
 
-/// Load `path` / `bytes` into the currently active tab (which must be untitled
-/// and clean before this call).
+/// Open `file.path` like [`open_path`], then put the caret on its line and
+/// column, if given.  A file opened in Large File Mode or the viewer stays
+/// at the top.
 ///
 /// # Safety
-/// `state` must be valid; the active tab must be untitled and clean.
-unsafe fn load_file_into_active_tab(
-    hwnd: HWND,
-    state: &mut WindowState,
-    path: std::path::PathBuf,
-    bytes: &[u8],
-) {
-    let utf8 = state.app.open_file(path, bytes);
+/// Called only from the UI thread with a valid `state`.
+unsafe fn open_file_at(hwnd: HWND, state: &mut WindowState, file: crate::cli::FileArg) {
+    let crate::cli::FileArg { path, line, column } = file;
+    let path = crate::paths::normalize(&path);
+    open_path(hwnd, state, path.clone());
     let idx = state.app.active_idx;
-    let (large_file, eol) = {
-        let doc = state.app.active_doc();
-        (doc.large_file, doc.eol)
-    };
-    state.sci_views[idx].set_large_file_mode(large_file);
-    apply_highlighting(
-        &state.sci_views[idx],
-        state.app.active_doc(),
-        state.dark_mode,
-        &state.sci_dll,
-    );
-    state.sci_views[idx].set_eol_mode(eol);
-    state.sci_views[idx].set_word_wrap(true);
-    state.sci_views[idx].set_text(&utf8);
-    state.sci_views[idx].set_save_point();
-    sync_tab_label(state, idx);
-    update_window_title(hwnd, &state.app);
-    update_status_bar(state);
+    let opened = state.app.tabs[idx].path.as_deref() == Some(path.as_path())
+        && !is_loading(state, idx)
+        && viewer_index(state, idx).is_none();
+    if let (true, Some(line)) = (opened, line) {
+        goto_line_column(&state.sci_views[idx], line, column.unwrap_or(1));
+    }
+}
+
+/// Put the caret on 1-based `line` at 1-based character `column`, each
+/// clamped to the document, and scroll it into view.
+fn goto_line_column(view: &ScintillaView, line: usize, column: usize) {
+    let line = line.clamp(1, view.line_count()) - 1;
+    let text = view.line_text(line);
+    let text = text.strip_suffix(b"\n").unwrap_or(&text);
+    let text = text.strip_suffix(b"\r").unwrap_or(text);
+    let offset = text
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b & 0xC0 != 0x80)
+        .nth(column.max(1) - 1)
+        .map_or(text.len(), |(i, _)| i);
+    view.set_caret_pos(view.position_from_line(line) + offset);
+    view.scroll_caret();
 }
 
-/// Create a new tab and open `path` / `bytes` in it.
+/// Handle File > Go to File…: open a file typed or pasted as
+/// `path:line:col` (or `path(line,col)`) and jump there.  A relative path
+/// is looked up next to the active document, then in the workspace
+/// folder.  The clipboard is offered first when it holds such a location.
 ///
 /// # Safety
-/// `state` must be valid; `hwnd` is the parent window handle.
-unsafe fn open_file_in_new_tab(
-    hwnd: HWND,
-    state: &mut WindowState,
-    path: std::path::PathBuf,
-    bytes: &[u8],
-) {
-    let sci = match new_scintilla_view(hwnd, state) {
-        Some(s) => s,
-        None => return,
-    };
-
-    // Hide current view, push the new tab.
-    state.sci_views[state.app.active_idx].show(false);
-    let new_idx = state.app.push_untitled();
-    state.sci_views.push(sci);
-    state.app.active_idx = new_idx;
-
-    // Insert a placeholder tab label (updated below by sync_tab_label).
-    tab_insert(state.hwnd_tab, new_idx, "Untitled");
-    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_idx), LPARAM(0));
-
-    // Load the file and configure the new Scintilla view.
-    let utf8 = state.app.open_file(path, bytes);
-    let (large_file, eol) = {
-        let doc = state.app.active_doc();
-        (doc.large_file, doc.eol)
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn handle_goto_file(hwnd: HWND, state: &mut WindowState) {
+    let initial = crate::platform::win32::clipboard::get_text(hwnd)
+        .filter(|t| !t.trim().contains('\n') && crate::cli::FileArg::parse(t).line.is_some())
+        .unwrap_or_default();
+    let Some(text) = crate::platform::win32::dlg::show_prompt_dialog(
+        hwnd,
+        "Go to File",
+        "File, optionally with :line or :line:column:",
+        initial.trim(),
+        true,
+    ) else {
+        return;
     };
-    state.sci_views[new_idx].set_large_file_mode(large_file);
-    apply_highlighting(
-        &state.sci_views[new_idx],
-        state.app.active_doc(),
-        state.dark_mode,
-        &state.sci_dll,
-    );
-    state.sci_views[new_idx].set_eol_mode(eol);
-    state.sci_views[new_idx].set_word_wrap(true);
-    state.sci_views[new_idx].set_text(&utf8);
-    state.sci_views[new_idx].set_save_point();
+    let mut file = crate::cli::FileArg::parse(&text);
+    if file.path.is_relative() {
+        let bases = [
+            state
+                .app
+                .active_doc()
+                .path
+                .as_deref()
+                .and_then(|p| p.parent()),
+            state.workspace_root.as_deref(),
+        ];
+        if let Some(found) = bases
+            .into_iter()
+            .flatten()
+            .map(|base| base.join(&file.path))
+            .find(|p| p.is_file())
+        {
+            file.path = found;
+        }
+    }
+    open_file_at(hwnd, state, file);
+}
 
-    sync_tab_label(state, new_idx);
-    state.sci_views[new_idx].show(true);
+// ── Recent files ──────────────────────────────────────────────────────────────
 
-    let mut rc = RECT::default();
-    let _ = GetClientRect(hwnd, &mut rc);
-    layout_children(state, rc.right, rc.bottom);
+/// Put `path` at the top of the recent-files list and refresh the jump list.
+fn note_recent(state: &mut WindowState, path: &std::path::Path) {
+    state.recent.push(path);
+    refresh_jump_list(state);
+    let _ = crate::recent::save(&state.recent);
+}
 
-    update_window_title(hwnd, &state.app);
-    update_status_bar(state);
+/// Rebuild the taskbar jump list, forgetting files the user removed from it.
+fn refresh_jump_list(state: &mut WindowState) {
+    let mut changed = false;
+    for path in crate::platform::win32::jumplist::update(state.recent.paths()) {
+        changed |= state.recent.remove(&path);
+    }
+    if changed {
+        let _ = crate::recent::save(&state.recent);
+    }
 }
 
-/// Create a fresh untitled tab and make it active.
+// ── Reload from disk ──────────────────────────────────────────────────────────
+
+/// Handle File > Reload from Disk: re-read the active tab's file, keeping the
+/// caret and scroll position where the new text allows.  With `code_page`
+/// (Format > Encoding > Reopen With) the file is decoded in that code page.
+///
+/// Asks first if the buffer has unsaved changes.  The reload is one undoable
+/// step.  Returns the number of bytes read, or `None` if nothing was
+/// reloaded.
 ///
 /// # Safety
-/// `state` must be valid; `hwnd` is the parent window handle.
-unsafe fn open_untitled_tab(hwnd: HWND, state: &mut WindowState) {
-    let sci = match new_scintilla_view(hwnd, state) {
-        Some(s) => s,
-        None => return,
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_file_reload(
+    hwnd: HWND,
+    state: &mut WindowState,
+    code_page: Option<Encoding>,
+) -> Option<u64> {
+    let idx = state.app.active_idx;
+    let view = state.sci_views[idx].hwnd();
+    let busy = is_loading(state, idx)
+        || viewer_index(state, idx).is_some()
+        || state.replace.as_ref().is_some_and(|r| r.view == view)
+        || monitor_index(state, idx).is_some();
+    let Some(path) = state.app.active_doc().path.clone().filter(|_| !busy) else {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return None;
     };
 
-    state.sci_views[state.app.active_idx].show(false);
-    let new_idx = state.app.push_untitled();
-    state.sci_views.push(sci);
-    state.app.active_idx = new_idx;
+    if state.app.active_doc().dirty {
+        let name = state.app.active_doc().display_name();
+        let msg = format!("\"{name}\" has unsaved changes.\n\nDiscard them and reload from disk?");
+        let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+        // SAFETY: wide is valid null-terminated UTF-16 that outlives the call.
+        let result = MessageBoxW(
+            hwnd,
+            PCWSTR(wide.as_ptr()),
+            w!("Rivet"),
+            MB_YESNO | MB_ICONWARNING,
+        );
+        if result != IDYES {
+            return None;
+        }
+    }
 
-    tab_insert(state.hwnd_tab, new_idx, "Untitled");
-    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_idx), LPARAM(0));
+    // Files that have grown past the viewer threshold must be reopened.
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > HUGE_FILE_THRESHOLD_BYTES) {
+        show_error_dialog("The file is now too large to reload.\nClose and reopen it.");
+        return None;
+    }
+    let bytes = read_file_prompting(hwnd, "reload", &path)?;
 
-    // Apply Consolas font + current palette so all tabs are visually consistent.
+    let sci = &state.sci_views[idx];
+    let caret = sci.caret_pos();
+    let first_line = sci.first_visible_line();
+    let utf8 = state.app.reload_file(&bytes, code_page)?;
+    let doc = state.app.active_doc();
+    sci.set_degraded(doc.degraded);
+    sci.set_rendering(state.settings.rendering.resolve(utf8.len() as u64));
     apply_highlighting(
-        &state.sci_views[new_idx],
-        state.app.active_doc(),
+        sci,
+        doc,
         state.dark_mode,
+        state.system_colours.as_ref(),
         &state.sci_dll,
     );
-    state.sci_views[new_idx].set_word_wrap(true);
-    state.app.active_doc_mut().word_wrap = true;
-
-    state.sci_views[new_idx].show(true);
-
-    let mut rc = RECT::default();
-    let _ = GetClientRect(hwnd, &mut rc);
-    layout_children(state, rc.right, rc.bottom);
-
+    sci.set_eol_mode(doc.eol);
+    sci.set_indentation(doc.indent);
+    // Scintilla ignores SCI_SETTEXT while read-only.
+    sci.set_read_only(false);
+    sci.set_text(&utf8);
+    sci.set_read_only(doc.read_only);
+    sci.set_save_point();
+    // SCI_GOTOPOS clamps past-the-end positions.
+    sci.set_caret_pos(caret);
+    sci.set_first_visible_line(first_line);
+    sync_tab_label(state, idx);
     update_window_title(hwnd, &state.app);
     update_status_bar(state);
+    Some(bytes.len() as u64)
 }
 
-/// Create a new `ScintillaView` parented to `hwnd`.
+// ── Monitor (tail -f) ─────────────────────────────────────────────────────────
+
+/// Index into `state.monitors` for tab `idx`, if it is being monitored.
+fn monitor_index(state: &WindowState, idx: usize) -> Option<usize> {
+    let view = state.sci_views[idx].hwnd();
+    state.monitors.iter().position(|m| m.view == view)
+}
+
+/// Handle View > Monitor: start or stop following the active tab's file.
 ///
-/// Returns `None` and shows an error dialog on failure.
+/// Starting reloads the file first (asking about unsaved changes), so the
+/// view and the followed offset agree.
 ///
 /// # Safety
-/// `state.sci_dll` must be live; `hwnd` must be the main window.
-unsafe fn new_scintilla_view(hwnd: HWND, state: &WindowState) -> Option<ScintillaView> {
-    let hmodule = match GetModuleHandleW(None) {
-        Ok(h) => h,
-        Err(_) => return None,
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_monitor_toggle(hwnd: HWND, state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    if let Some(m) = monitor_index(state, idx) {
+        stop_monitoring(hwnd, state, m);
+        update_monitor_checkmark(state);
+        return;
+    }
+    let Some(offset) = handle_file_reload(hwnd, state, None) else {
+        return;
     };
-    let hinstance = HINSTANCE(hmodule.0);
-    match ScintillaView::create(hwnd, hinstance, &state.sci_dll) {
-        Ok(s) => Some(s),
-        Err(e) => {
-            show_error_dialog(&format!("Could not create editor view:\n{e}"));
-            None
-        }
+    let encoding = state.app.active_doc().encoding;
+    let view = &state.sci_views[idx];
+    view.begin_chunked_load();
+    view.set_caret_pos(view.doc_len());
+    state.monitors.push(MonitoredTab {
+        view: view.hwnd(),
+        tail: Tail::new(offset, encoding, state.app.codec(encoding)),
+    });
+    if state.monitors.len() == 1 {
+        let _ = SetTimer(hwnd, MONITOR_TIMER_ID, MONITOR_INTERVAL_MS, None);
     }
+    update_monitor_checkmark(state);
 }
 
-// ── File save ─────────────────────────────────────────────────────────────────
-
-/// Handle File > Save / Save As.
+/// Stop monitoring `state.monitors[m]` and make its view editable again.
 ///
 /// # Safety
-/// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_file_save(hwnd: HWND, state: &mut WindowState, force_dialog: bool) {
-    let path = if force_dialog || state.app.active_doc().path.is_none() {
-        let default = state
-            .app
-            .active_doc()
-            .path
-            .as_deref()
-            .and_then(|p| p.file_name())
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_default();
-        match show_save_dialog(hwnd, &default) {
-            Some(p) => p,
-            None => return,
-        }
-    } else {
-        state.app.active_doc().path.clone().unwrap()
-    };
-
-    let idx = state.app.active_idx;
-    let utf8 = state.sci_views[idx].get_text();
-    match state.app.save(path, &utf8) {
-        Ok(()) => {
-            state.sci_views[idx].set_save_point();
-            sync_tab_label(state, idx);
-            update_window_title(hwnd, &state.app);
-            // Refresh language in status bar (extension may have changed via Save As).
-            update_status_bar(state);
-        }
-        Err(e) => show_error_dialog(&format!("Could not save file:\n{e}")),
+/// `state` must be valid; `m` must be in range.
+unsafe fn stop_monitoring(hwnd: HWND, state: &mut WindowState, m: usize) {
+    let view = state.monitors.remove(m).view;
+    if let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == view) {
+        state.sci_views[idx].end_chunked_load(!state.app.tabs[idx].degraded.no_undo);
+        state.sci_views[idx].set_read_only(state.app.tabs[idx].read_only);
+    }
+    if state.monitors.is_empty() {
+        let _ = KillTimer(hwnd, MONITOR_TIMER_ID);
     }
 }
 
-// ── EOL conversion ────────────────────────────────────────────────────────────
-
-/// Handle Format > Convert to … : convert all existing EOL sequences and set
-/// the new default EOL mode.  Scintilla fires `SCN_SAVEPOINTLEFT` automatically
-/// after the conversion, so `doc.dirty` will be updated via the notification path.
+/// `MONITOR_TIMER_ID`: append whatever each monitored file gained and keep
+/// its view scrolled to the end.  A file that can no longer be read stops
+/// being monitored.
 ///
 /// # Safety
-/// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_eol_convert(hwnd: HWND, state: &mut WindowState, eol: EolMode) {
-    let idx = state.app.active_idx;
-    // Convert all existing line endings and set the mode for new keystrokes.
-    state.sci_views[idx].convert_eols(eol);
-    state.sci_views[idx].set_eol_mode(eol);
-    state.app.active_doc_mut().eol = eol;
+/// Called only from WM_TIMER with a valid `state`.
+unsafe fn handle_monitor_tick(hwnd: HWND, state: &mut WindowState) {
+    let mut m = 0;
+    while m < state.monitors.len() {
+        let view = state.monitors[m].view;
+        let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == view) else {
+            state.monitors.remove(m);
+            continue;
+        };
+        let Some(path) = state.app.tabs[idx].path.clone() else {
+            stop_monitoring(hwnd, state, m);
+            continue;
+        };
+        let sci = &state.sci_views[idx];
+        match state.monitors[m].tail.poll(&path) {
+            Ok(TailEvent::Unchanged) => {}
+            Ok(TailEvent::Appended(text)) => {
+                sci.append_loaded(&text);
+                sci.set_caret_pos(sci.doc_len());
+            }
+            Ok(TailEvent::Truncated) => {
+                sci.set_read_only(false);
+                sci.set_text(b"");
+                sci.set_read_only(true);
+            }
+            Err(_) => {
+                stop_monitoring(hwnd, state, m);
+                update_monitor_checkmark(state);
+                continue;
+            }
+        }
+        m += 1;
+    }
+    if state.monitors.is_empty() {
+        let _ = KillTimer(hwnd, MONITOR_TIMER_ID);
+    }
     update_status_bar(state);
-    let _ = hwnd; // hwnd available for future use (e.g. title update)
 }
 
-// ── Word wrap toggle ──────────────────────────────────────────────────────────
+/// Sync the View > Monitor checkmark with the active tab.
+fn update_monitor_checkmark(state: &WindowState) {
+    let checked = monitor_index(state, state.app.active_idx).is_some();
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_MONITOR as u32, flag);
+    }
+}
+
+// ── Large File Mode loading ───────────────────────────────────────────────────
 
-/// Handle View > Word Wrap: toggle word wrap for the active document.
+/// Open `path` in Large File Mode, streaming it in on a worker thread.
+///
+/// The tab appears at once, empty and read-only; `WM_LOAD_PROGRESS` fills it
+/// chunk by chunk while the status bar shows progress.  Esc in the tab, the
+/// status bar's Cancel button, or closing the tab cancels the load.
 ///
 /// # Safety
 /// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_word_wrap_toggle(hwnd: HWND, state: &mut WindowState) {
-    let wrap = !state.app.active_doc().word_wrap;
-    state.app.active_doc_mut().word_wrap = wrap;
-    let idx = state.app.active_idx;
-    state.sci_views[idx].set_word_wrap(wrap);
-    update_wrap_checkmark(hwnd, wrap);
+unsafe fn start_chunked_load(
+    hwnd: HWND,
+    state: &mut WindowState,
+    path: std::path::PathBuf,
+    size: u64,
+    read_only: bool,
+) {
+    let raw_hwnd = hwnd.0 as isize;
+    let notify = move || {
+        // SAFETY: PostMessageW may be called from any thread; if the window
+        // has been destroyed the post simply fails.
+        unsafe {
+            let _ = PostMessageW(
+                HWND(raw_hwnd as *mut _),
+                WM_LOAD_PROGRESS,
+                WPARAM(0),
+                LPARAM(0),
+            );
+        }
+    };
+    let loader = match Loader::spawn(&path, notify) {
+        Ok(l) => l,
+        Err(e) => {
+            show_error_dialog(&FileProblem::of(&e).message("open", &path, &e.to_string()));
+            return;
+        }
+    };
+
+    let Some(idx) = claim_tab_for_open(hwnd, state) else {
+        return;
+    };
+    state.app.begin_load(path, size);
+    state.app.active_doc_mut().word_wrap = false;
+    let view = &state.sci_views[idx];
+    view.set_text(b"");
+    view.set_degraded(state.app.active_doc().degraded);
+    view.set_rendering(state.settings.rendering.resolve(size));
+    update_wrap_checkmark(state, false);
+    view.begin_chunked_load();
+    state.loads.push(PendingLoad {
+        view: view.hwnd(),
+        loader,
+        decoder: StreamDecoder::new(state.app.ansi),
+        sampled: false,
+        read_only,
+    });
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
 }
 
-/// Update the View > Word Wrap checkmark to reflect `wrap`.
-///
-/// Uses `MF_BYCOMMAND` so the correct item is found regardless of the menu
-/// position of the View submenu (which shifted when Format was inserted).
+/// The tab a streamed or windowed open should fill: the current tab if it is
+/// a clean untitled document, otherwise a new one.  `None` if no tab could be
+/// created.
 ///
 /// # Safety
-/// `hwnd` must be the valid main-window handle.
-unsafe fn update_wrap_checkmark(hwnd: HWND, wrap: bool) {
-    let menu = GetMenu(hwnd);
-    // MF_BYCOMMAND | MF_{UN}CHECKED gives MENU_ITEM_FLAGS; CheckMenuItem wants u32.
-    let flag = (MF_BYCOMMAND | if wrap { MF_CHECKED } else { MF_UNCHECKED }).0;
-    // SAFETY: menu is the main window's menu bar (valid while the window exists).
-    // CheckMenuItem with MF_BYCOMMAND searches all submenus.
-    let _ = CheckMenuItem(menu, IDM_VIEW_WORD_WRAP as u32, flag);
+/// `state` must be valid.
+unsafe fn claim_tab_for_open(hwnd: HWND, state: &mut WindowState) -> Option<usize> {
+    if !state.app.active_doc().is_blank() {
+        let before = state.app.tab_count();
+        open_untitled_tab(hwnd, state);
+        if state.app.tab_count() == before {
+            return None;
+        }
+    }
+    Some(state.app.active_idx)
 }
 
-// ── DPI + status bar helpers ─────────────────────────────────────────────────
-
-/// Initialise DPI tracking and apply initial highlighting to the first tab.
-///
-/// Called from WM_CREATE after the `WindowState` is stored in GWLP_USERDATA.
+/// Handle `WM_LOAD_PROGRESS`: append every queued chunk to its view and
+/// finish loads that reached the end of the file.
 ///
 /// # Safety
-/// `hwnd` must be the valid main-window handle; `state` must be live.
-unsafe fn post_create_init(hwnd: HWND, state: &mut WindowState) {
-    state.dpi = crate::platform::win32::dpi::get_for_window(hwnd);
-    update_statusbar_parts(state);
-    // Apply initial dark mode chrome and menu checkmarks.
-    apply_title_bar_dark(hwnd, state.dark_mode);
-    update_dark_mode_checkmark(hwnd, state.dark_mode);
-    // Set the initial tab position checkmark (Top by default).
-    update_tab_position_checkmarks(hwnd, state.tab_position);
-    // Apply Consolas font + initial palette to the first untitled tab.
-    apply_highlighting(&state.sci_views[0], state.app.active_doc(), state.dark_mode, &state.sci_dll);
-    state.sci_views[0].set_word_wrap(true);
-    state.app.active_doc_mut().word_wrap = true;
-    // Start the periodic session checkpoint timer.
-    // SAFETY: hwnd is valid; no callback (None) — the timer fires as WM_TIMER.
-    let _ = SetTimer(hwnd, AUTOSAVE_TIMER_ID, AUTOSAVE_INTERVAL_MS, None);
+/// Called only from the window procedure with a valid `state`.
+unsafe fn handle_load_progress(hwnd: HWND, state: &mut WindowState) {
+    let mut i = 0;
+    while i < state.loads.len() {
+        let view = state.loads[i].view;
+        let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == view) else {
+            state.loads.remove(i);
+            continue;
+        };
+        let mut finished = None;
+        while let Some(event) = state.loads[i].loader.try_next() {
+            match event {
+                LoadEvent::Chunk(bytes) => {
+                    let utf8 = state.loads[i].decoder.push(&bytes);
+                    if !state.loads[i].sampled && !utf8.is_empty() {
+                        state.loads[i].sampled = true;
+                        apply_load_sample(state, idx, &utf8);
+                    }
+                    state.sci_views[idx].append_loaded(&utf8);
+                }
+                LoadEvent::Done => {
+                    finished = Some(Ok(()));
+                    break;
+                }
+                LoadEvent::Failed(e) => {
+                    finished = Some(Err(e));
+                    break;
+                }
+            }
+        }
+        match finished {
+            None => i += 1,
+            Some(result) => {
+                let load = state.loads.remove(i);
+                finish_chunked_load(hwnd, state, idx, load, result);
+            }
+        }
+    }
+    update_status_bar(state);
 }
 
-/// Recompute and apply status-bar part widths.
+/// Detect EOL, indentation, and language for tab `idx` from its first
+/// decoded chunk and configure the view to match.
 ///
-/// Fixed-width panels (language, EOL, encoding) are right-anchored by computing
-/// their right edges from the actual status-bar client width.  The Ln/Col panel
-/// fills whatever space remains on the left.  Call this after every resize and
-/// DPI change so the layout is always pixel-perfect regardless of window size.
-fn update_statusbar_parts(state: &WindowState) {
-    use crate::platform::win32::dpi;
-    let enc_w = dpi::scale(SB_PART_ENCODING_W_BASE, state.dpi);
-    let eol_w = dpi::scale(SB_PART_EOL_W_BASE, state.dpi);
-    let lang_w = dpi::scale(SB_PART_LANG_W_BASE, state.dpi);
-
-    // Query the current status-bar width so right edges are always accurate.
-    let total = {
-        let mut rc = RECT::default();
-        // SAFETY: hwnd_status is a valid window handle for the life of WindowState.
-        unsafe { let _ = GetClientRect(state.hwnd_status, &mut rc); }
-        rc.right
-    };
-
-    // Layout (left → right): [Ln/Col] | [Language] | [EOL] | [Encoding]
-    // The last part uses -1 so Windows extends it to the right edge, accounting
-    // for the sizing grip.
-    let eol_right  = (total - enc_w).max(1);
-    let lang_right = (total - enc_w - eol_w).max(1);
-    let col_right  = (total - enc_w - eol_w - lang_w).max(1);
-    let parts: [i32; 4] = [col_right, lang_right, eol_right, -1];
+/// # Safety
+/// `state` must be valid; `idx` must be in range.
+unsafe fn apply_load_sample(state: &mut WindowState, idx: usize, sample: &[u8]) {
+    state.app.apply_load_sample(idx, sample);
+    let doc = &state.app.tabs[idx];
+    state.sci_views[idx].set_eol_mode(doc.eol);
+    state.sci_views[idx].set_indentation(doc.indent);
+    // Only if the highlighting limit is above the Large File Mode one.
+    apply_highlighting(
+        &state.sci_views[idx],
+        doc,
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+}
 
-    // SAFETY: hwnd_status is a valid status-bar HWND for the lifetime of WindowState.
-    unsafe {
-        let _ = SendMessageW(
-            state.hwnd_status,
-            SB_SETPARTS,
-            WPARAM(parts.len()),
-            LPARAM(parts.as_ptr() as isize),
-        );
+/// Complete a load that has ended, successfully or not.
+///
+/// # Safety
+/// `state` must be valid; `idx` must be the index of `load.view`.
+unsafe fn finish_chunked_load(
+    hwnd: HWND,
+    state: &mut WindowState,
+    idx: usize,
+    load: PendingLoad,
+    result: std::io::Result<()>,
+) {
+    match result {
+        Ok(()) => {
+            let (encoding, tail) = load.decoder.finish();
+            if !load.sampled {
+                apply_load_sample(state, idx, &tail);
+            }
+            state.sci_views[idx].append_loaded(&tail);
+            state.sci_views[idx].end_chunked_load(!state.app.tabs[idx].degraded.no_undo);
+            state.app.tabs[idx].encoding = encoding;
+            if load.read_only {
+                set_tab_read_only(hwnd, state, idx, true);
+            }
+        }
+        Err(e) => {
+            state.sci_views[idx].end_chunked_load(!state.app.tabs[idx].degraded.no_undo);
+            show_error_dialog(&format!("Could not open file:\n{e}"));
+            handle_close_tab(hwnd, state, idx);
+        }
     }
 }
 
-// ── Dark mode helpers ─────────────────────────────────────────────────────────
+// ── Huge-file viewer ──────────────────────────────────────────────────────────
 
-/// Toggle dark mode: flip flag, update chrome + checkmark, re-theme all views.
+/// Open `path` in the read-only viewer: the tab holds one window of the file
+/// at a time, paged with View > Next / Previous Chunk, and Find searches the
+/// file on disk.
 ///
 /// # Safety
-/// `hwnd` must be the valid main-window handle; `state` must be live.
-unsafe fn handle_dark_mode_toggle(hwnd: HWND, state: &mut WindowState) {
-    state.dark_mode = !state.dark_mode;
-    apply_title_bar_dark(hwnd, state.dark_mode);
-    update_dark_mode_checkmark(hwnd, state.dark_mode);
-    reapply_all_themes(state);
+/// `state` must be valid.
+unsafe fn open_viewer(
+    hwnd: HWND,
+    state: &mut WindowState,
+    path: std::path::PathBuf,
+) -> std::io::Result<()> {
+    let mut window = FileWindow::open(&path)?;
+    if window.is_utf16()? {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "UTF-16 files this large are not supported.",
+        ));
+    }
+    let text = window.show_from(0)?;
+    let Some(idx) = claim_tab_for_open(hwnd, state) else {
+        return Ok(());
+    };
+    state.app.begin_load(path, window.len());
+    state.app.apply_load_sample(idx, &text);
+    let doc = state.app.active_doc_mut();
+    doc.degraded = Degraded::ALL;
+    doc.word_wrap = false;
+    doc.read_only = true;
+    let view = &state.sci_views[idx];
+    view.set_degraded(Degraded::ALL);
+    view.set_rendering(state.settings.rendering.resolve(window.len()));
+    view.set_eol_mode(state.app.tabs[idx].eol);
+    view.set_indentation(state.app.tabs[idx].indent);
+    show_viewer_text(view, &text);
+    update_wrap_checkmark(state, false);
+    state.viewers.push(ViewerTab {
+        view: view.hwnd(),
+        window,
+    });
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+    Ok(())
 }
 
-/// Set or clear the View > Dark Mode checkmark.
-///
-/// # Safety
-/// `hwnd` must be the valid main-window handle.
-unsafe fn update_dark_mode_checkmark(hwnd: HWND, dark: bool) {
-    let flag = (MF_BYCOMMAND | if dark { MF_CHECKED } else { MF_UNCHECKED }).0;
-    let _ = CheckMenuItem(GetMenu(hwnd), IDM_VIEW_DARK_MODE as u32, flag);
+/// Replace a viewer tab's text with a new window of the file.
+fn show_viewer_text(view: &dyn EditorView, text: &[u8]) {
+    // Scintilla ignores SCI_SETTEXT while read-only.
+    view.set_read_only(false);
+    view.set_text(text);
+    view.set_read_only(true);
+    view.set_save_point();
 }
 
-/// Apply or remove dark DWM window chrome (title bar).
+/// Index into `state.viewers` for tab `idx`, if it is a viewer tab.
+fn viewer_index(state: &WindowState, idx: usize) -> Option<usize> {
+    let view = state.sci_views[idx].hwnd();
+    state.viewers.iter().position(|v| v.view == view)
+}
+
+/// Handle View > Next / Previous Chunk in a viewer tab.
 ///
-/// Silently ignored on unsupported Windows versions.
-fn apply_title_bar_dark(hwnd: HWND, dark: bool) {
-    use windows::Win32::Graphics::Dwm::{DwmSetWindowAttribute, DWMWINDOWATTRIBUTE};
-    let value: u32 = dark as u32;
-    // SAFETY: hwnd is a valid window handle; pvAttribute points to a u32 whose
-    // size matches cbAttribute.
-    unsafe {
-        let _ = DwmSetWindowAttribute(
-            hwnd,
-            DWMWINDOWATTRIBUTE(DWMWA_DARK_MODE),
-            &value as *const u32 as *const _,
-            std::mem::size_of::<u32>() as u32,
-        );
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_viewer_page(state: &mut WindowState, forward: bool) {
+    let idx = state.app.active_idx;
+    let Some(v) = viewer_index(state, idx) else {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    };
+    let window = &mut state.viewers[v].window;
+    let at_edge = if forward {
+        window.end() >= window.len()
+    } else {
+        window.start() == 0
+    };
+    if at_edge {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
     }
+    let text = if forward {
+        window.show_from(window.end())
+    } else {
+        window.show_until(window.start())
+    };
+    match text {
+        Ok(text) => {
+            let view = &state.sci_views[idx];
+            show_viewer_text(view, &text);
+            // Paging back lands at the end of the previous window.
+            if !forward {
+                view.set_caret_pos(text.len());
+            }
+        }
+        Err(e) => show_error_dialog(&format!("Could not read file:\n{e}")),
+    }
+    update_status_bar(state);
 }
 
-/// Re-apply highlighting (with the current `dark_mode` flag) to every open tab.
-fn reapply_all_themes(state: &mut WindowState) {
-    for i in 0..state.app.tabs.len() {
-        apply_highlighting(&state.sci_views[i], &state.app.tabs[i], state.dark_mode, &state.sci_dll);
+/// Find Next / Previous in a viewer tab, searching the whole file on disk.
+///
+/// Returns `None` if the active tab is not a viewer (the caller searches the
+/// Scintilla buffer instead), otherwise whether a match was selected.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn viewer_find_next(
+    state: &mut WindowState,
+    find: &[u8],
+    sci_flags: u32,
+    forward: bool,
+) -> Option<bool> {
+    let idx = state.app.active_idx;
+    let v = viewer_index(state, idx)?;
+    let needle = Needle::new(
+        find,
+        sci_flags & SCFIND_MATCHCASE != 0,
+        sci_flags & SCFIND_WHOLEWORD != 0,
+    );
+    let view = &state.sci_views[idx];
+    let window = &mut state.viewers[v].window;
+    let sel_start = window.start() + view.selection_start() as u64;
+    let sel_end = window.start() + view.selection_end() as u64;
+    let hit = match window.find_wrapping(&needle, sel_start, sel_end, forward) {
+        Ok(Some(pos)) => pos,
+        Ok(None) => return Some(false),
+        Err(e) => {
+            show_error_dialog(&format!("Could not read file:\n{e}"));
+            return Some(false);
+        }
+    };
+    if hit < window.start() || hit + needle.len() as u64 > window.end() {
+        match window.show_around(hit, needle.len()) {
+            Ok(text) => show_viewer_text(view, &text),
+            Err(e) => {
+                show_error_dialog(&format!("Could not read file:\n{e}"));
+                return Some(false);
+            }
+        }
     }
+    let rel = (hit - window.start()) as usize;
+    view.set_sel(rel, rel + needle.len());
+    view.scroll_caret();
+    update_status_bar(state);
+    Some(true)
 }
 
-// ── Tab position helpers ──────────────────────────────────────────────────────
+/// `true` while tab `idx` is still receiving a Large File Mode load.
+fn is_loading(state: &WindowState, idx: usize) -> bool {
+    let view = state.sci_views[idx].hwnd();
+    state.loads.iter().any(|l| l.view == view)
+}
 
-/// Apply the Win32 style bits for `pos` to the tab control and force a repaint.
+/// Mark the tab at `idx` read-only (or writable) in both the document state
+/// and its Scintilla view, and refresh the tab label and title.
 ///
 /// # Safety
-/// `hwnd_tab` must be a valid `SysTabControl32` HWND.
-unsafe fn set_tab_style(hwnd_tab: HWND, pos: TabPosition) {
-    let cur = GetWindowLongPtrW(hwnd_tab, GWL_STYLE) as u32;
-    let new_style = match pos {
-        TabPosition::Top => cur & !(TCS_VERTICAL | TCS_RIGHT),
-        TabPosition::Left => (cur & !TCS_RIGHT) | TCS_VERTICAL,
-        TabPosition::Right => cur | TCS_VERTICAL | TCS_RIGHT,
-    };
-    SetWindowLongPtrW(hwnd_tab, GWL_STYLE, new_style as isize);
-    // Force the tab control to re-measure and repaint with the new style.
-    let _ = SetWindowPos(
-        hwnd_tab,
-        HWND::default(),
-        0,
-        0,
-        0,
-        0,
-        SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED,
-    );
+/// `state` must be valid; `idx` must be in range.
+unsafe fn set_tab_read_only(hwnd: HWND, state: &mut WindowState, idx: usize, read_only: bool) {
+    state.app.tabs[idx].read_only = read_only;
+    state.sci_views[idx].set_read_only(read_only);
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
 }
 
-/// Change the tab bar position, update the Win32 style, reposition all children.
+/// Make the tab at `idx` the visible one and sync the tab strip, menus,
+/// title, and status bar.  No-op if it is already active.
 ///
 /// # Safety
-/// `hwnd` and `state` must be valid.
-unsafe fn handle_tab_position(hwnd: HWND, state: &mut WindowState, pos: TabPosition) {
-    if state.tab_position == pos {
+/// `state` must be valid; `idx` must be in range.
+unsafe fn activate_tab(hwnd: HWND, state: &mut WindowState, idx: usize) {
+    if idx == state.app.active_idx {
         return;
     }
-    state.tab_position = pos;
-    set_tab_style(state.hwnd_tab, pos);
-    update_tab_position_checkmarks(hwnd, pos);
+    swap_visible_view(hwnd, state, idx);
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(idx), LPARAM(0));
+    let eol = state.sci_views[idx].eol_mode();
+    state.app.active_doc_mut().eol = eol;
+    update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+    update_monitor_checkmark(state);
+    update_edit_state(state);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+/// The tab whose view sent a notification.  Usually the active tab, but a
+/// background Replace All can finish in a tab that is no longer active.
+fn notifying_tab(state: &WindowState, from: HWND) -> usize {
+    state
+        .sci_views
+        .iter()
+        .position(|v| v.hwnd() == from)
+        .unwrap_or(state.app.active_idx)
+}
+
+/// Make `idx` the active tab's view without flashing the parent background.
+///
+/// The incoming view is sized while still hidden and shown before the
+/// outgoing one is hidden, so no frame ever exposes the area between them.
+///
+/// # Safety
+/// `state` must be valid; `idx` must be in range.
+unsafe fn swap_visible_view(hwnd: HWND, state: &mut WindowState, idx: usize) {
+    let old = state.app.active_idx;
+    state.app.active_idx = idx;
     let mut rc = RECT::default();
     let _ = GetClientRect(hwnd, &mut rc);
     layout_children(state, rc.right, rc.bottom);
+    state.sci_views[idx].show(true);
+    state.sci_views[old].show(false);
+    refresh_outline(state);
+    sync_document_map(state);
+    let _ = SetTimer(hwnd, GIT_TIMER_ID, GIT_DELAY_MS, None);
 }
 
-/// Sync the View > Tabs at … checkmarks to reflect the current `pos`.
+/// Move the tab at `from` to index `to` (a finished drag in the tab strip).
+///
+/// The document, its Scintilla view, and the strip item all move together;
+/// the active document stays active.
 ///
 /// # Safety
-/// `hwnd` must be the valid main-window handle.
-unsafe fn update_tab_position_checkmarks(hwnd: HWND, pos: TabPosition) {
-    let menu = GetMenu(hwnd);
-    let set = |id: usize, checked: bool| {
-        let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
-        let _ = CheckMenuItem(menu, id as u32, flag);
-    };
-    set(IDM_VIEW_TAB_TOP, pos == TabPosition::Top);
-    set(IDM_VIEW_TAB_LEFT, pos == TabPosition::Left);
-    set(IDM_VIEW_TAB_RIGHT, pos == TabPosition::Right);
+/// Called only from `WM_TAB_MOVE` on the UI thread with a valid `state`.
+unsafe fn handle_tab_move(state: &mut WindowState, from: usize, to: usize) {
+    let n = state.app.tab_count();
+    if from >= n || to >= n || from == to {
+        return;
+    }
+    state.app.move_tab(from, to);
+    let view = state.sci_views.remove(from);
+    state.sci_views.insert(to, view);
+
+    let _ = SendMessageW(state.hwnd_tab, TCM_DELETEITEM, WPARAM(from), LPARAM(0));
+    tab_insert(state.hwnd_tab, to, "");
+    sync_tab_label(state, to);
+    sync_tab_names(state);
+    let _ = SendMessageW(
+        state.hwnd_tab,
+        TCM_SETCURSEL,
+        WPARAM(state.app.active_idx),
+        LPARAM(0),
+    );
 }
 
-// ── Find / Replace helpers ────────────────────────────────────────────────────
+// ── Scratch tab ───────────────────────────────────────────────────────────────
 
-/// Open (or focus) the modeless Find dialog.
+/// Register `settings.scratch_hotkey` for `hwnd`.  An empty or unparsable
+/// setting registers nothing; a chord another program holds fails quietly.
 ///
 /// # Safety
-/// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_find_open(hwnd: HWND, state: &mut WindowState) {
-    if state.hwnd_find_dlg != HWND::default() {
-        // Dialog already open — bring it to the front.
-        let _ = SetForegroundWindow(state.hwnd_find_dlg);
+/// `hwnd` must be a valid main-window handle.
+unsafe fn register_scratch_hotkey(hwnd: HWND, settings: &crate::config::Settings) {
+    let Some(hotkey) = crate::keymap::HotKey::parse(&settings.scratch_hotkey) else {
         return;
+    };
+    let mut mods = MOD_NOREPEAT;
+    for (on, flag) in [
+        (hotkey.win, MOD_WIN),
+        (hotkey.chord.ctrl, MOD_CONTROL),
+        (hotkey.chord.shift, MOD_SHIFT),
+        (hotkey.chord.alt, MOD_ALT),
+    ] {
+        if on {
+            mods |= flag;
+        }
     }
-    state.findreplace.hwndOwner = hwnd;
+    let _ = RegisterHotKey(hwnd, SCRATCH_HOTKEY_ID, mods, u32::from(hotkey.chord.key));
+}
+
+/// Handle the scratchpad hot key: bring Rivet forward on the scratch tab,
+/// opening it (and creating the scratch file) if no window has it.
+///
+/// # Safety
+/// Called only from `WM_HOTKEY` on the UI thread with a valid `state`.
+unsafe fn handle_scratch_hotkey(hwnd: HWND, state: &mut WindowState) {
+    // A window that already shows the scratch tab wins.
+    let windows = WINDOWS.with(|w| w.borrow().clone());
+    for other in windows {
+        let ptr = GetWindowLongPtrW(other, GWLP_USERDATA) as *mut WindowState;
+        if ptr.is_null() {
+            continue;
+        }
+        if let Some(idx) = (*ptr).app.tabs.iter().position(|d| d.is_scratch()) {
+            bring_to_front(other);
+            activate_tab(other, &mut *ptr, idx);
+            let _ = SetFocus((*ptr).sci_views[idx].hwnd());
+            return;
+        }
+    }
+
+    bring_to_front(hwnd);
+    match crate::scratch::ensure_file() {
+        Ok(path) => open_path(hwnd, state, path),
+        Err(e) => {
+            show_error_dialog(&format!("Could not create the scratch file:\n{e}"));
+            return;
+        }
+    }
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].is_scratch() {
+        sync_tab_label(state, idx);
+        update_window_title(hwnd, &state.app);
+        let _ = SetFocus(state.sci_views[idx].hwnd());
+    }
+}
+
+/// Write every dirty scratch tab to the scratch file.  Errors are ignored;
+/// the next checkpoint tries again.
+///
+/// # Safety
+/// `state` must be valid and its views alive.
+unsafe fn save_scratch_tabs(state: &mut WindowState) {
+    let prev_active = state.app.active_idx;
+    for idx in 0..state.app.tab_count() {
+        let doc = &state.app.tabs[idx];
+        if !doc.is_scratch() || !doc.dirty {
+            continue;
+        }
+        let Some(path) = doc.path.clone() else {
+            continue;
+        };
+        let utf8 = state.sci_views[idx].get_text();
+        // App::save writes the active document; point it at this tab.
+        state.app.active_idx = idx;
+        if state.app.save(path, &utf8).is_ok() {
+            state.sci_views[idx].set_save_point();
+            sync_tab_label(state, idx);
+        }
+    }
+    state.app.active_idx = prev_active;
+}
+
+// ── More windows ──────────────────────────────────────────────────────────────
+
+/// Create and show another main window with one untitled tab, taking dark
+/// mode and the tab position from `from` (File > New Window, or a tab
+/// dragged out of the strip).  Returns the window and its state.
+///
+/// # Safety
+/// Called on the UI thread with a valid `from`.
+unsafe fn open_window(from: &WindowState) -> Option<(HWND, *mut WindowState)> {
+    let hinstance = HINSTANCE(GetModuleHandleW(None).ok()?.0);
+    let hwnd = match create_window(hinstance) {
+        Ok(hwnd) => hwnd,
+        Err(e) => {
+            let text = format!("Could not open a new window.\n\n{e}");
+            let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = MessageBoxW(
+                HWND::default(),
+                PCWSTR(wide.as_ptr()),
+                w!("Rivet"),
+                MB_OK | MB_ICONWARNING,
+            );
+            return None;
+        }
+    };
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+    if ptr.is_null() {
+        return None;
+    }
+    apply_window_prefs(hwnd, &mut *ptr, from.dark_mode, from.tab_position);
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = UpdateWindow(hwnd);
+    Some((hwnd, ptr))
+}
+
+/// Move the tab at `idx` to the Rivet window under the screen point `pt`,
+/// or to a new window placed there (a tab dropped outside the window).
+///
+/// A tab that is still loading, paging a viewer, running Replace All, or
+/// being monitored stays put, as does a window's only tab.
+///
+/// # Safety
+/// Called only from `WM_TAB_DETACH` on the UI thread with a valid `state`.
+unsafe fn handle_tab_detach(hwnd: HWND, state: &mut WindowState, idx: usize, pt: POINT) {
+    if idx >= state.app.tab_count() {
+        return;
+    }
+    let view = state.sci_views[idx].hwnd();
+    let busy = is_loading(state, idx)
+        || viewer_index(state, idx).is_some()
+        || monitor_index(state, idx).is_some()
+        || state.replace.as_ref().is_some_and(|r| r.view == view);
+    if busy || state.app.tab_count() == 1 {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+
+    let target = GetAncestor(WindowFromPoint(pt), GA_ROOT);
+    let (dst_hwnd, dst) = if target != hwnd && is_main_window(target) {
+        let ptr = GetWindowLongPtrW(target, GWLP_USERDATA) as *mut WindowState;
+        if ptr.is_null() {
+            return;
+        }
+        (target, ptr)
+    } else {
+        let Some((new_hwnd, ptr)) = open_window(state) else {
+            return;
+        };
+        let _ = SetWindowPos(
+            new_hwnd,
+            HWND::default(),
+            pt.x,
+            pt.y,
+            0,
+            0,
+            SWP_NOSIZE | SWP_NOZORDER,
+        );
+        (new_hwnd, ptr)
+    };
+
+    let (doc, view) = detach_tab(hwnd, state, idx);
+    adopt_tab(dst_hwnd, &mut *dst, doc, view);
+    let _ = SetForegroundWindow(dst_hwnd);
+}
+
+/// Remove the tab at `idx` without destroying its view, returning the
+/// document and the (hidden) view.  `idx` must not be the only tab.
+///
+/// # Safety
+/// `state` must be valid; `idx` must be in range.
+unsafe fn detach_tab(
+    hwnd: HWND,
+    state: &mut WindowState,
+    idx: usize,
+) -> (crate::app::DocumentState, ScintillaView) {
+    let was_active = idx == state.app.active_idx;
+    let view = state.sci_views.remove(idx);
+    view.show(false);
+    let _ = SendMessageW(state.hwnd_tab, TCM_DELETEITEM, WPARAM(idx), LPARAM(0));
+    let doc = state.app.take_tab(idx);
+    sync_tab_names(state);
+
+    let new_active = state.app.active_idx;
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_active), LPARAM(0));
+    if was_active {
+        state.sci_views[new_active].show(true);
+    }
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+    update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+    update_monitor_checkmark(state);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+    (doc, view)
+}
+
+/// Add a tab taken from another window with `detach_tab` and make it
+/// active.  Its view is re-parented and restyled for this window; a blank
+/// only tab is replaced rather than kept.
+///
+/// # Safety
+/// `hwnd` must be the main window owning `state`.
+unsafe fn adopt_tab(
+    hwnd: HWND,
+    state: &mut WindowState,
+    doc: crate::app::DocumentState,
+    view: ScintillaView,
+) {
+    let _ = SetParent(view.hwnd(), hwnd);
+    apply_control_theme(view.hwnd(), dark_chrome(state));
+    apply_highlighting(
+        &view,
+        &doc,
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    let (selection, caret_line) = accent_colours(state);
+    view.set_highlights(selection, caret_line);
+    view.set_caret(state.settings.caret);
+
+    let old = state.app.active_idx;
+    state.sci_views[old].show(false);
+    let idx = if state.app.tab_count() == 1 && state.app.active_doc().is_blank() {
+        std::mem::replace(&mut state.sci_views[0], view).destroy();
+        state.app.tabs[0] = doc;
+        0
+    } else {
+        let idx = state.app.push_tab(doc);
+        state.sci_views.push(view);
+        tab_insert(state.hwnd_tab, idx, "");
+        idx
+    };
+    sync_tab_label(state, idx);
+    state.app.active_idx = idx;
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(idx), LPARAM(0));
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+    state.sci_views[idx].show(true);
+    update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+    update_monitor_checkmark(state);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+/// Answer a `WM_COPYDATA` request from another Rivet window.
+///
+/// Returns the reply: `true` if this window has the requested path open and
+/// (for `COPYDATA_QUERY_DIRTY`) dirty, or (for `COPYDATA_ACTIVATE`) brought
+/// it to the front.
+///
+/// # Safety
+/// Called only from WM_COPYDATA on the UI thread with a valid `state`.
+unsafe fn handle_peer_request(
+    hwnd: HWND,
+    state: &mut WindowState,
+    code: usize,
+    path: &std::path::Path,
+) -> bool {
+    let Some(idx) = state
+        .app
+        .tabs
+        .iter()
+        .position(|t| t.path.as_deref().is_some_and(|p| ipc::same_path(p, path)))
+    else {
+        return false;
+    };
+    match code {
+        ipc::COPYDATA_QUERY_DIRTY => state.app.tabs[idx].dirty,
+        ipc::COPYDATA_ACTIVATE => {
+            if !IsWindowVisible(hwnd).as_bool() {
+                let _ = ShowWindow(hwnd, SW_SHOW);
+            }
+            if IsIconic(hwnd).as_bool() {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+            }
+            activate_tab(hwnd, state, idx);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Load `path` / `bytes` into the currently active tab (which must be untitled
+/// and clean before this call), decoded as `code_page` if given (see
+/// [`App::open_file`]).
+///
+/// # Safety
+/// `state` must be valid; the active tab must be untitled and clean.
+unsafe fn load_file_into_active_tab(
+    hwnd: HWND,
+    state: &mut WindowState,
+    path: std::path::PathBuf,
+    bytes: &[u8],
+    code_page: Option<Encoding>,
+) {
+    let utf8 = state.app.open_file(path, bytes, code_page);
+    let idx = state.app.active_idx;
+    let degraded = state.app.active_doc().degraded;
+    state.sci_views[idx].set_degraded(degraded);
+    state.sci_views[idx].set_rendering(state.settings.rendering.resolve(utf8.len() as u64));
+    apply_highlighting(
+        &state.sci_views[idx],
+        state.app.active_doc(),
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    commands::show_document(state.app.active_doc(), &state.sci_views[idx], &utf8);
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+/// Create a new tab and open `path` / `bytes` in it, decoded as `code_page`
+/// if given.
+///
+/// # Safety
+/// `state` must be valid; `hwnd` is the parent window handle.
+unsafe fn open_file_in_new_tab(
+    hwnd: HWND,
+    state: &mut WindowState,
+    path: std::path::PathBuf,
+    bytes: &[u8],
+    code_page: Option<Encoding>,
+) {
+    let sci = match new_scintilla_view(hwnd, state) {
+        Some(s) => s,
+        None => return,
+    };
+
+    // Hide current view, push the new tab.
+    state.sci_views[state.app.active_idx].show(false);
+    let new_idx = state.app.push_untitled();
+    state.sci_views.push(sci);
+    state.app.active_idx = new_idx;
+
+    // Insert a placeholder tab label (updated below by sync_tab_label).
+    tab_insert(state.hwnd_tab, new_idx, "Untitled");
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_idx), LPARAM(0));
+
+    // Load the file and configure the new Scintilla view.
+    let utf8 = state.app.open_file(path, bytes, code_page);
+    let degraded = state.app.active_doc().degraded;
+    state.sci_views[new_idx].set_degraded(degraded);
+    state.sci_views[new_idx].set_rendering(state.settings.rendering.resolve(utf8.len() as u64));
+    apply_highlighting(
+        &state.sci_views[new_idx],
+        state.app.active_doc(),
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    commands::show_document(state.app.active_doc(), &state.sci_views[new_idx], &utf8);
+
+    sync_tab_label(state, new_idx);
+    state.sci_views[new_idx].show(true);
+
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+/// Create a fresh untitled tab and make it active.
+///
+/// # Safety
+/// `state` must be valid; `hwnd` is the parent window handle.
+unsafe fn open_untitled_tab(hwnd: HWND, state: &mut WindowState) {
+    let sci = match new_scintilla_view(hwnd, state) {
+        Some(s) => s,
+        None => return,
+    };
+
+    state.sci_views[state.app.active_idx].show(false);
+    let new_idx = state.app.push_untitled();
+    state.sci_views.push(sci);
+    state.app.active_idx = new_idx;
+
+    tab_insert(state.hwnd_tab, new_idx, "Untitled");
+    sync_tab_label(state, new_idx);
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_idx), LPARAM(0));
+
+    // Apply Consolas font + current palette so all tabs are visually consistent.
+    apply_highlighting(
+        &state.sci_views[new_idx],
+        state.app.active_doc(),
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    state.sci_views[new_idx].set_indentation(state.app.active_doc().indent);
+    state.sci_views[new_idx].set_word_wrap(true);
+    state.app.active_doc_mut().word_wrap = true;
+
+    state.sci_views[new_idx].show(true);
+
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+/// Create a new `ScintillaView` parented to `hwnd`.
+///
+/// Returns `None` and shows an error dialog on failure.
+///
+/// # Safety
+/// `state.sci_dll` must be live; `hwnd` must be the main window.
+unsafe fn new_scintilla_view(hwnd: HWND, state: &WindowState) -> Option<ScintillaView> {
+    let hmodule = match GetModuleHandleW(None) {
+        Ok(h) => h,
+        Err(_) => return None,
+    };
+    let hinstance = HINSTANCE(hmodule.0);
+    match ScintillaView::create(hwnd, hinstance, &state.sci_dll) {
+        Ok(s) => {
+            autoscroll::install(s.hwnd());
+            s.set_caret(state.settings.caret);
+            apply_control_theme(s.hwnd(), dark_chrome(state));
+            let (selection, caret_line) = accent_colours(state);
+            s.set_highlights(selection, caret_line);
+            Some(s)
+        }
+        Err(e) => {
+            show_error_dialog(&format!("Could not create editor view:\n{e}"));
+            None
+        }
+    }
+}
+
+// ── File save ─────────────────────────────────────────────────────────────────
+
+/// Handle File > Save / Save As.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_file_save(hwnd: HWND, state: &mut WindowState, force_dialog: bool) {
+    // Saving now would write a truncated file (or, for a viewer tab, only the
+    // window currently shown).
+    let idx = state.app.active_idx;
+    if is_loading(state, idx) || viewer_index(state, idx).is_some() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    // A read-only copy must not overwrite the file another window is editing.
+    let force_dialog = force_dialog || state.app.active_doc().read_only;
+    let path = if force_dialog || state.app.active_doc().path.is_none() {
+        let default = state
+            .app
+            .active_doc()
+            .path
+            .as_deref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match show_save_dialog(hwnd, &default) {
+            Some(p) => p,
+            None => return,
+        }
+    } else {
+        state.app.active_doc().path.clone().unwrap()
+    };
+
+    let idx = state.app.active_idx;
+    let utf8 = state.sci_views[idx].get_text();
+    let old_lang = state.app.active_doc().language;
+    while let Err(e) = state.app.save(path.clone(), &utf8) {
+        match file_error_prompt(hwnd, "save", &path, e.file_problem(), &e.to_string()) {
+            FileErrorChoice::Retry => {}
+            FileErrorChoice::SaveAs => return handle_file_save(hwnd, state, true),
+            FileErrorChoice::Cancel => return,
+        }
+    }
+    state.sci_views[idx].set_save_point();
+    note_recent(state, &path);
+    // First save of an untitled buffer (or Save As to a new extension) can
+    // change the language.
+    if state.app.active_doc().language != old_lang {
+        apply_highlighting(
+            &state.sci_views[idx],
+            state.app.active_doc(),
+            state.dark_mode,
+            state.system_colours.as_ref(),
+            &state.sci_dll,
+        );
+    }
+    if state.app.tabs[idx].read_only {
+        set_tab_read_only(hwnd, state, idx, false);
+    }
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+    refresh_outline(state);
+    refresh_git_gutter(hwnd, state);
+}
+
+// ── Copy Full Path ────────────────────────────────────────────────────────────
+
+/// Handle Edit > Copy Full Path: put the active document's path on the
+/// clipboard.  Beeps for an untitled buffer.
+fn handle_copy_full_path(hwnd: HWND, state: &WindowState) {
+    let Some(path) = state.app.active_doc().path.as_deref() else {
+        // SAFETY: MessageBeep has no preconditions.
+        unsafe {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+        return;
+    };
+    if let Err(e) = crate::platform::win32::clipboard::set_text(hwnd, &path.to_string_lossy()) {
+        show_error_dialog(&format!("Could not copy to the clipboard:\n{e}"));
+    }
+}
+
+// ── Paste from History ────────────────────────────────────────────────────────
+
+/// Handle Edit > Paste from History…: list recent cuts and copies in a
+/// popup menu at the caret and paste the one picked.  Beeps if there are
+/// none yet.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_paste_from_history(hwnd: HWND, state: &mut WindowState) {
+    let entries = state.app.clip_ring.entries();
+    if entries.is_empty() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let Ok(menu) = CreatePopupMenu() else {
+        return;
+    };
+    for (i, entry) in entries.iter().enumerate() {
+        // A lone `&` would become a mnemonic; the first nine get digits.
+        let text = crate::editor::clip_ring::label(entry).replace('&', "&&");
+        let label = if i < 9 {
+            format!("&{} {text}", i + 1)
+        } else {
+            format!("   {text}")
+        };
+        let wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = AppendMenuW(menu, MF_STRING, i + 1, PCWSTR(wide.as_ptr()));
+    }
+    let idx = state.app.active_idx;
+    let view = &state.sci_views[idx];
+    let (x, y) = view.caret_point();
+    let mut pt = POINT { x, y };
+    let _ = ClientToScreen(view.hwnd(), &mut pt);
+    let cmd = TrackPopupMenu(menu, TPM_RETURNCMD, pt.x, pt.y, 0, hwnd, None);
+    let _ = DestroyMenu(menu);
+    let Some(picked) = (cmd.0 as usize).checked_sub(1) else {
+        return;
+    };
+    let out = commands::paste_from_history(&mut state.app, &state.sci_views[idx], picked);
+    if out == Outcome::Redetect {
+        redetect_language(state, idx);
+    }
+}
+
+// ── Column Editor ─────────────────────────────────────────────────────────────
+
+/// Handle Edit > Column Editor…: ask what to insert, then put it at the left
+/// edge of every line of the rectangular selection as one undo step.
+/// Without a rectangular selection, explains how to make one; beeps in a
+/// read-only tab.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_column_editor(hwnd: HWND, state: &mut WindowState) {
+    use crate::editor::column;
+
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let Some(points) = state.sci_views[idx].column_points() else {
+        let _ = MessageBoxW(
+            hwnd,
+            w!("Make a column selection first: drag with Alt held down, or press Alt+Shift with the arrow keys."),
+            w!("Column Editor"),
+            MB_OK | MB_ICONINFORMATION,
+        );
+        return;
+    };
+    let Some(fill) =
+        crate::platform::win32::column_dialog::show_column_dialog(hwnd, state.column_fill.clone())
+    else {
+        return;
+    };
+    let values = column::values(&fill, points.len());
+    state.sci_views[idx].insert_column(&column::edits(&points, &values));
+    state.column_fill = fill;
+}
+
+// ── Copy Special ──────────────────────────────────────────────────────────────
+
+/// Handle Edit > Copy Special > Copy as RTF / Copy as HTML: put the
+/// selection (or the whole document if nothing is selected) on the
+/// clipboard with its syntax colours, alongside the plain text.
+fn handle_copy_special(hwnd: HWND, state: &WindowState, html: bool) {
+    use crate::export::{self, Styled, STYLE_DEFAULT};
+
+    let view = &state.sci_views[state.app.active_idx];
+    let (start, end) = match (view.selection_start(), view.selection_end()) {
+        (start, end) if start < end => (start, end),
+        _ => (0, view.doc_len()),
+    };
+    let text = view.text_range(start, end);
+    let styles = view.styles_in(start, end);
+    let table: Vec<_> = (0..=u8::MAX).map(|style| view.text_style(style)).collect();
+    let (font, size) = view.style_font(STYLE_DEFAULT);
+    let doc = Styled {
+        text: &text,
+        styles: &styles,
+        table: &table,
+        font: &font,
+        size,
+    };
+    let (format, data) = if html {
+        ("HTML Format", export::cf_html(&export::to_html(&doc)))
+    } else {
+        ("Rich Text Format", export::to_rtf(&doc))
+    };
+    let plain = String::from_utf8_lossy(&text);
+    let result = crate::platform::win32::clipboard::set_text_with(
+        hwnd,
+        &plain,
+        &[(format, data.as_bytes())],
+    );
+    if let Err(e) = result {
+        show_error_dialog(&format!("Could not copy to the clipboard:\n{e}"));
+    }
+}
+
+// ── Word wrap ─────────────────────────────────────────────────────────────────
+
+/// Update the View > Word Wrap checkmark to reflect `wrap`.
+///
+/// Uses `MF_BYCOMMAND` so the correct item is found regardless of the menu
+/// position of the View submenu (which shifted when Format was inserted).
+///
+/// # Safety
+/// `menu` must be the main window's menu bar.
+unsafe fn update_wrap_checkmark(state: &WindowState, wrap: bool) {
+    // MF_BYCOMMAND | MF_{UN}CHECKED gives MENU_ITEM_FLAGS; CheckMenuItem wants u32.
+    let flag = (MF_BYCOMMAND | if wrap { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the main window's menu bar (valid while the window
+    // exists).  CheckMenuItem with MF_BYCOMMAND searches all submenus.
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_WORD_WRAP as u32, flag);
+    if let Some(bar) = &state.toolbar {
+        bar.set_checked(IDM_VIEW_WORD_WRAP, wrap);
+    }
+}
+
+/// Menu items grayed when they have nothing to act on
+/// (`commands::is_enabled`).
+const CONTEXT_ITEMS: [usize; 8] = [
+    IDM_FILE_SAVE,
+    IDM_EDIT_UNDO,
+    IDM_EDIT_REDO,
+    IDM_EDIT_CUT,
+    IDM_EDIT_COPY,
+    IDM_EDIT_PASTE,
+    IDM_EDIT_DELETE,
+    IDM_VIEW_LONG_LINES,
+];
+
+/// Gray Save, Undo, Redo, Cut, Copy, Paste, Delete and Highlight and Wrap
+/// Long Lines, in the menu and on the toolbar, when the active document
+/// gives them nothing to act on.
+///
+/// Also runs on `WM_INITMENUPOPUP`, which `TranslateAcceleratorW` sends
+/// before it checks an item, so shortcuts see the same state as the menu.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn update_edit_state(state: &WindowState) {
+    let doc = state.app.active_doc();
+    // A tab being opened may not have its view yet.
+    let Some(view) = state.sci_views.get(state.app.active_idx) else {
+        return;
+    };
+    let clipboard_text = crate::platform::win32::clipboard::has_text();
+    for id in CONTEXT_ITEMS {
+        let on = command_for_id(id)
+            .is_some_and(|cmd| commands::is_enabled(cmd, doc, view, clipboard_text));
+        let flag = MF_BYCOMMAND | if on { MF_ENABLED } else { MF_GRAYED };
+        let _ = EnableMenuItem(state.menu_bar, id as u32, flag);
+        if let Some(bar) = &state.toolbar {
+            bar.set_enabled(id, on);
+        }
+    }
+}
+
+// ── DPI + status bar helpers ─────────────────────────────────────────────────
+
+/// Give the tab strip and status bar the message font at `state.dpi`,
+/// replacing (and deleting) the previous one.  Their default font is fixed at
+/// the DPI the process started with.
+fn apply_ui_font(state: &mut WindowState) {
+    let Some(font) = crate::platform::win32::dpi::create_ui_font(state.dpi) else {
+        return;
+    };
+    // SAFETY: both children are live; WM_SETFONT takes an HFONT and a redraw
+    // flag.  The old font is no longer selected by either control once the
+    // new one is set, so it can be deleted.
+    unsafe {
+        for child in [state.hwnd_tab, state.hwnd_status] {
+            let _ = SendMessageW(child, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        }
+        if let Some(progress) = &state.progress {
+            progress.set_font(font);
+        }
+        if !state.ui_font.is_invalid() {
+            let _ = DeleteObject(state.ui_font);
+        }
+    }
+    state.ui_font = font;
+}
+
+/// Initialise DPI tracking and apply initial highlighting to the first tab.
+///
+/// Called from WM_CREATE after the `WindowState` is stored in GWLP_USERDATA.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be live.
+unsafe fn post_create_init(hwnd: HWND, state: &mut WindowState) {
+    state.dpi = crate::platform::win32::dpi::get_for_window(hwnd);
+    state.progress = StatusProgress::create(hwnd, IDC_CANCEL_TASK);
+    crate::platform::win32::clipboard::listen(hwnd, true);
+    apply_ui_font(state);
+    update_statusbar_parts(state);
+    // Apply initial dark mode chrome and menu checkmarks.
+    apply_title_bar_dark(hwnd, dark_chrome(state));
+    apply_dark_controls(state);
+    update_dark_mode_checkmark(state.menu_bar, state.dark_mode);
+    update_follow_theme_checkmark(state);
+    update_accent_checkmark(state);
+    apply_accent_highlights(state);
+    update_occurrences_checkmark(state);
+    update_feedback_checkmarks(state);
+    update_extended_checkmark(state);
+    update_multiline_tabs_checkmark(state);
+    apply_tab_icons(state);
+    update_restore_checkmarks(state);
+    update_keep_unsaved_checkmark(state);
+    update_relative_paths_checkmark(state);
+    update_auto_close_checkmark(state);
+    // Set the initial tab position checkmark (Top by default).
+    update_tab_position_checkmarks(state.menu_bar, state.tab_position);
+    apply_toolbar(hwnd, state);
+    apply_folder_panel(hwnd, state);
+    apply_outline_panel(hwnd, state);
+    apply_document_map(hwnd, state);
+    apply_menu_autohide(hwnd, state);
+    apply_title_bar_tabs(hwnd, state);
+    apply_tray(hwnd, state);
+    // Only one window can hold the hot key: the first (see WM_DESTROY).
+    if owns_session(hwnd) {
+        register_scratch_hotkey(hwnd, &state.settings);
+    }
+    // Apply Consolas font + initial palette to the first untitled tab.
+    apply_highlighting(
+        &state.sci_views[0],
+        state.app.active_doc(),
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    state.sci_views[0].set_indentation(state.app.active_doc().indent);
+    state.sci_views[0].set_caret(state.settings.caret);
+    state.sci_views[0].set_word_wrap(true);
+    state.app.active_doc_mut().word_wrap = true;
+    rebuild_tools_menu(state);
+    // Start the periodic session checkpoint timer.
+    // SAFETY: hwnd is valid; no callback (None) — the timer fires as WM_TIMER.
+    let _ = SetTimer(hwnd, AUTOSAVE_TIMER_ID, AUTOSAVE_INTERVAL_MS, None);
+}
+
+/// Recompute and apply status-bar part widths.
+///
+/// Fixed-width panels (size, language, EOL, encoding, INS/OVR) are right-anchored by computing
+/// their right edges from the actual status-bar client width.  The Ln/Col panel
+/// fills whatever space remains on the left.  Call this after every resize and
+/// DPI change so the layout is always pixel-perfect regardless of window size.
+fn update_statusbar_parts(state: &WindowState) {
+    use crate::platform::win32::dpi;
+    let enc_w = dpi::scale(SB_PART_ENCODING_W_BASE, state.dpi);
+    let eol_w = dpi::scale(SB_PART_EOL_W_BASE, state.dpi);
+    let lang_w = dpi::scale(SB_PART_LANG_W_BASE, state.dpi);
+    let size_w = dpi::scale(SB_PART_SIZE_W_BASE, state.dpi);
+    let mode_w = dpi::scale(SB_PART_MODE_W_BASE, state.dpi);
+
+    // Query the current status-bar width so right edges are always accurate.
+    let total = {
+        let mut rc = RECT::default();
+        // SAFETY: hwnd_status is a valid window handle for the life of WindowState.
+        unsafe {
+            let _ = GetClientRect(state.hwnd_status, &mut rc);
+        }
+        rc.right
+    };
+
+    // Layout (left → right):
+    //   [Ln/Col] | [Size] | [Language] | [EOL] | [Encoding] | [INS/OVR]
+    // The last part uses -1 so Windows extends it to the right edge, accounting
+    // for the sizing grip.
+    let enc_right = (total - mode_w).max(1);
+    let eol_right = (total - mode_w - enc_w).max(1);
+    let lang_right = (total - mode_w - enc_w - eol_w).max(1);
+    let size_right = (total - mode_w - enc_w - eol_w - lang_w).max(1);
+    let col_right = (total - mode_w - enc_w - eol_w - lang_w - size_w).max(1);
+    let parts: [i32; 6] = [col_right, size_right, lang_right, eol_right, enc_right, -1];
+
+    // SAFETY: hwnd_status is a valid status-bar HWND for the lifetime of WindowState.
+    unsafe {
+        let _ = SendMessageW(
+            state.hwnd_status,
+            SB_SETPARTS,
+            WPARAM(parts.len()),
+            LPARAM(parts.as_ptr() as isize),
+        );
+    }
+}
+
+// ── Dark mode helpers ─────────────────────────────────────────────────────────
+
+/// Toggle dark mode: flip flag, update chrome + checkmark, re-theme all views.
+/// A manual choice stops View > Follow System Theme.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be live.
+unsafe fn handle_dark_mode_toggle(hwnd: HWND, state: &mut WindowState) {
+    if state.settings.follow_system_theme {
+        state.settings.follow_system_theme = false;
+        let _ = crate::config::save(&state.settings);
+        update_follow_theme_checkmark(state);
+    }
+    set_dark_mode(hwnd, state, !state.dark_mode);
+}
+
+/// Switch dark mode on or off: chrome, checkmark and every view.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be live.
+unsafe fn set_dark_mode(hwnd: HWND, state: &mut WindowState, dark: bool) {
+    state.dark_mode = dark;
+    apply_title_bar_dark(hwnd, dark_chrome(state));
+    update_dark_mode_checkmark(state.menu_bar, state.dark_mode);
+    reapply_all_themes(state);
+}
+
+/// Handle View > Follow System Theme.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be live.
+unsafe fn handle_follow_theme_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.follow_system_theme = !state.settings.follow_system_theme;
+    let _ = crate::config::save(&state.settings);
+    update_follow_theme_checkmark(state);
+    follow_system_theme(hwnd, state);
+}
+
+/// The Windows app mode, when dark mode follows it; `None` when it does not
+/// (or Windows has no app mode).
+fn system_dark_mode(settings: &crate::config::Settings) -> Option<bool> {
+    settings
+        .follow_system_theme
+        .then(crate::platform::win32::accent::apps_use_dark_theme)
+        .flatten()
+}
+
+/// Bring dark mode in line with the Windows app mode, if it follows it.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be live.
+unsafe fn follow_system_theme(hwnd: HWND, state: &mut WindowState) {
+    if let Some(dark) = system_dark_mode(&state.settings) {
+        if dark != state.dark_mode {
+            set_dark_mode(hwnd, state, dark);
+        }
+    }
+}
+
+/// Set or clear the View > Follow System Theme checkmark.
+fn update_follow_theme_checkmark(state: &WindowState) {
+    let checked = state.settings.follow_system_theme;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_FOLLOW_THEME as u32, flag);
+    }
+}
+
+/// `WM_ERASEBKGND` for the main window: fill the client area (visible only
+/// around the children, which are clipped out) with the theme background,
+/// so live resizing never flashes the white class brush in dark mode.
+///
+/// # Safety
+/// `hdc` must be the device context passed with `WM_ERASEBKGND` for `hwnd`.
+unsafe fn erase_background(hwnd: HWND, state: &WindowState, hdc: HDC) {
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    if title_bar_tabs_active(hwnd, state) {
+        titlebar::paint_band(hwnd, hdc, state.dpi);
+        rc.top = titlebar::band_height(hwnd, state.dpi);
+    }
+    let colour = match state.system_colours {
+        Some(colours) => colours.window,
+        None => crate::theme::background(state.dark_mode),
+    };
+    let brush = CreateSolidBrush(COLORREF(colour));
+    FillRect(hdc, &rc, brush);
+    let _ = DeleteObject(brush);
+}
+
+/// Set or clear the View > Dark Mode checkmark.
+///
+/// # Safety
+/// `menu` must be the main window's menu bar.
+unsafe fn update_dark_mode_checkmark(menu: HMENU, dark: bool) {
+    let flag = (MF_BYCOMMAND | if dark { MF_CHECKED } else { MF_UNCHECKED }).0;
+    let _ = CheckMenuItem(menu, IDM_VIEW_DARK_MODE as u32, flag);
+}
+
+/// Whether the window chrome and controls use their dark styles: dark mode
+/// is on and the system colours are not in use.
+fn dark_chrome(state: &WindowState) -> bool {
+    state.dark_mode && state.system_colours.is_none()
+}
+
+/// The system colours to draw with under `settings.high_contrast`, or
+/// `None` to use the light / dark palette.
+fn system_colours_for(settings: &crate::config::Settings) -> Option<crate::theme::SystemColours> {
+    use crate::platform::win32::contrast;
+    settings
+        .high_contrast
+        .applies(contrast::is_on())
+        .then(contrast::system_colours)
+}
+
+/// High Contrast mode or the system colours may have changed: switch
+/// palettes and re-theme everything if what is in use differs.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be its state.
+unsafe fn refresh_system_colours(hwnd: HWND, state: &mut WindowState) {
+    let colours = system_colours_for(&state.settings);
+    if colours == state.system_colours {
+        return;
+    }
+    state.system_colours = colours;
+    apply_title_bar_dark(hwnd, dark_chrome(state));
+    reapply_all_themes(state);
+    let _ = InvalidateRect(hwnd, None, true);
+}
+
+/// Apply or remove dark DWM window chrome (title bar).
+///
+/// Silently ignored on unsupported Windows versions.
+fn apply_title_bar_dark(hwnd: HWND, dark: bool) {
+    use windows::Win32::Graphics::Dwm::{DwmSetWindowAttribute, DWMWINDOWATTRIBUTE};
+    let value: u32 = dark as u32;
+    // SAFETY: hwnd is a valid window handle; pvAttribute points to a u32 whose
+    // size matches cbAttribute.
+    unsafe {
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWINDOWATTRIBUTE(DWMWA_DARK_MODE),
+            &value as *const u32 as *const _,
+            std::mem::size_of::<u32>() as u32,
+        );
+    }
+}
+
+/// Re-apply highlighting (with the current `dark_mode` flag) to every open tab.
+fn reapply_all_themes(state: &mut WindowState) {
+    for i in 0..state.app.tabs.len() {
+        apply_highlighting(
+            &state.sci_views[i],
+            &state.app.tabs[i],
+            state.dark_mode,
+            state.system_colours.as_ref(),
+            &state.sci_dll,
+        );
+    }
+    apply_dark_controls(state);
+    apply_accent_highlights(state);
+    let system = state.system_colours.as_ref();
+    if let Some(panel) = &state.results {
+        theme_results_panel(&panel.view, state.dark_mode, system);
+    }
+    if let Some(panel) = &state.outline {
+        theme_results_panel(&panel.view, state.dark_mode, system);
+    }
+    if let Some(map) = &state.map {
+        theme_document_map(map, state.dark_mode, system);
+    }
+}
+
+// ── Accent-colour highlights ──────────────────────────────────────────────────
+
+/// Handle View > Accent Colour Highlights.
+fn handle_accent_toggle(state: &mut WindowState) {
+    state.settings.accent_highlight = !state.settings.accent_highlight;
+    let _ = crate::config::save(&state.settings);
+    update_accent_checkmark(state);
+    apply_accent_highlights(state);
+}
+
+// ── Occurrence highlighting ───────────────────────────────────────────────────
+
+/// Documents longer than this (bytes) are not searched for occurrences.
+const OCCURRENCE_MAX_DOC: usize = 8 << 20;
+/// At most this many occurrences are highlighted.
+const OCCURRENCE_MAX_MATCHES: usize = 2000;
+
+/// Handle View > Highlight Occurrences.
+fn handle_occurrences_toggle(state: &mut WindowState) {
+    state.settings.highlight_occurrences = !state.settings.highlight_occurrences;
+    let _ = crate::config::save(&state.settings);
+    update_occurrences_checkmark(state);
+    update_occurrences(state);
+}
+
+/// Sync the View > Highlight Occurrences checkmark with the setting.
+fn update_occurrences_checkmark(state: &WindowState) {
+    let checked = state.settings.highlight_occurrences;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_OCCURRENCES as u32, flag);
+    }
+}
+
+/// The word to highlight in the active view and where it starts: the
+/// selection if it is exactly one word, else the word at the caret when
+/// nothing is selected.
+fn occurrence_word(view: &ScintillaView) -> Option<(Vec<u8>, usize)> {
+    let (sel_start, sel_end) = (view.selection_start(), view.selection_end());
+    if sel_start != sel_end {
+        let text = view.selected_text();
+        return (crate::search::word_around(&text, 0) == Some(0..text.len()))
+            .then_some((text, sel_start));
+    }
+    let line = view.line_from_position(sel_start);
+    let line_start = view.position_from_line(line);
+    let text = view.line_text(line);
+    let word = crate::search::word_around(&text, sel_start - line_start)?;
+    Some((text[word.clone()].to_vec(), line_start + word.start))
+}
+
+/// Box every other whole-word, case-sensitive occurrence of the word at the
+/// caret in the active view.  Runs off `OCCURRENCE_TIMER_ID`, so a burst of
+/// caret moves searches once; large documents are skipped.
+fn update_occurrences(state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    let view = &state.sci_views[idx];
+    let doc_len = view.doc_len();
+    let key = (state.settings.highlight_occurrences
+        && !state.app.tabs[idx].degraded.no_scan
+        && doc_len <= OCCURRENCE_MAX_DOC)
+        .then(|| occurrence_word(view))
+        .flatten()
+        .map(|(word, start)| OccurrenceKey {
+            view: view.hwnd(),
+            word,
+            start,
+            doc_len,
+        });
+    if key == state.occurrences {
+        return;
+    }
+    // The previous highlight may be in another tab's view.
+    if let Some(old) = &state.occurrences {
+        if let Some(prev) = state.sci_views.iter().find(|v| v.hwnd() == old.view) {
+            prev.set_occurrences(&[]);
+        }
+    }
+    if let Some(key) = &key {
+        let mut ranges = Vec::new();
+        let mut pos = 0;
+        while ranges.len() < OCCURRENCE_MAX_MATCHES {
+            view.set_target(pos, doc_len);
+            let Some(found) = view.search_in_target(&key.word, SCFIND_MATCHCASE | SCFIND_WHOLEWORD)
+            else {
+                break;
+            };
+            if found != key.start {
+                ranges.push((found, key.word.len()));
+            }
+            pos = found + key.word.len();
+        }
+        view.set_occurrences(&ranges);
+    }
+    state.occurrences = key;
+}
+
+// ── Git gutter ────────────────────────────────────────────────────────────────
+
+/// Start diffing the active tab against its file's git HEAD on the task
+/// pool; `apply_git_gutter` marks the result.  Runs off `GIT_TIMER_ID`
+/// and after each save.  Tabs without a file in a repository, in an
+/// encoding other than UTF-8, or too large to scan get no gutter.
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn refresh_git_gutter(hwnd: HWND, state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let scannable = matches!(doc.encoding, Encoding::Utf8 | Encoding::Utf8Bom)
+        && !doc.degraded.no_scan
+        && !is_loading(state, idx);
+    let target = doc.path.as_deref().filter(|_| scannable).and_then(|path| {
+        let root = crate::git::repo_root(path)?;
+        let spec = format!("HEAD:{}", crate::git::repo_path(&root, path)?);
+        Some((root, spec))
+    });
+    let view = &state.sci_views[idx];
+    let Some((root, spec)) = target else {
+        view.set_git_marks(None);
+        return;
+    };
+    let show_head = move || {
+        crate::platform::win32::process::capture("git", &["show", "--no-textconv", &spec], &root)
+    };
+    let text = view.get_text();
+    let view = view.hwnd();
+    // HWND is not Send; the handle travels as an integer.
+    let raw_view = view.0 as isize;
+    let id = state.next_git_job;
+    // Failing to queue the job only costs this refresh.
+    let queued = spawn_task(hwnd, state, move |ui| {
+        let result = crate::git::gutter_changes(show_head, &text);
+        ui.send(move |_, state| {
+            apply_git_gutter(state, HWND(raw_view as *mut _), id, result);
+        });
+    });
+    if queued {
+        state.next_git_job += 1;
+        state.git_jobs.push(PendingGitGutter { view, id });
+    }
+}
+
+/// Mark the result of git gutter refresh `id` in `view`.  A result already
+/// superseded by a newer refresh of the same view is dropped, and so is one
+/// for a view closed since.  A file git does not know (untracked, or git
+/// missing) shows no gutter.
+fn apply_git_gutter(
+    state: &mut WindowState,
+    view: HWND,
+    id: u64,
+    result: std::io::Result<Vec<(usize, crate::git::LineChange)>>,
+) {
+    let Some(at) = state.git_jobs.iter().position(|g| g.id == id) else {
+        return;
+    };
+    state.git_jobs.remove(at);
+    if state.git_jobs[at..].iter().any(|g| g.view == view) {
+        return;
+    }
+    if let Some(sci) = state.sci_views.iter().find(|v| v.hwnd() == view) {
+        sci.set_git_marks(result.ok().as_deref());
+    }
+}
+
+// ── Background tasks ──────────────────────────────────────────────────────────
+
+/// Queue `job` on the window's task pool, starting the pool on first use.
+/// The job gets a sender for posting its results back to this window, where
+/// they run with its state.  Returns `false` if the job could not be queued.
+fn spawn_task(
+    hwnd: HWND,
+    state: &mut WindowState,
+    job: impl FnOnce(UiSender<WindowState>) + Send + 'static,
+) -> bool {
+    if state.tasks.is_none() {
+        state.tasks = crate::tasks::Pool::new().ok();
+    }
+    let ui = UiSender::new(hwnd);
+    state
+        .tasks
+        .as_ref()
+        .is_some_and(|pool| pool.spawn(move || job(ui)))
+}
+
+// ── Workspace-relative paths ──────────────────────────────────────────────────
+
+/// Tooltip for tab `idx`: the document's path, relative to the workspace
+/// folder when one is open and View > Workspace-Relative Paths is on.
+fn tab_tooltip(state: &WindowState, idx: usize) -> String {
+    state.app.tabs[idx].display_path(display_root(state))
+}
+
+/// The folder paths are shown relative to, if any.
+fn display_root(state: &WindowState) -> Option<&std::path::Path> {
+    state
+        .workspace_root
+        .as_deref()
+        .filter(|_| state.settings.workspace_relative_paths)
+}
+
+/// Handle View > Switch to Tab…: pick an open tab from a filtered list.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn handle_switch_tab(hwnd: HWND, state: &mut WindowState) {
+    let root = display_root(state);
+    let entries: Vec<crate::ui::switcher::SwitcherEntry> = state
+        .app
+        .tabs
+        .iter()
+        .map(|doc| crate::ui::switcher::SwitcherEntry::new(doc, root))
+        .collect();
+    let active = state.app.active_idx;
+    if let Some(idx) = super::switcher_dialog::show_switcher_dialog(hwnd, &entries, active) {
+        activate_tab(hwnd, state, idx);
+    }
+}
+
+/// Handle View > Workspace-Relative Paths.
+fn handle_relative_paths_toggle(state: &mut WindowState) {
+    state.settings.workspace_relative_paths = !state.settings.workspace_relative_paths;
+    let _ = crate::config::save(&state.settings);
+    update_relative_paths_checkmark(state);
+}
+
+/// Sync the View > Workspace-Relative Paths checkmark with the setting.
+fn update_relative_paths_checkmark(state: &WindowState) {
+    let checked = state.settings.workspace_relative_paths;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_RELATIVE_PATHS as u32, flag);
+    }
+}
+
+/// Sync the View > Accent Colour Highlights checkmark with the setting.
+fn update_accent_checkmark(state: &WindowState) {
+    let checked = state.settings.accent_highlight;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_ACCENT as u32, flag);
+    }
+}
+
+/// Selection and caret-line colours for the current settings: the system
+/// highlight under High Contrast, derived from the accent colour when
+/// enabled and available, Scintilla's defaults (`None`) otherwise.
+fn accent_colours(state: &WindowState) -> (Option<u32>, Option<u32>) {
+    if let Some(colours) = state.system_colours {
+        return (Some(colours.highlight), None);
+    }
+    let accent = state
+        .settings
+        .accent_highlight
+        .then(crate::platform::win32::accent::accent_color)
+        .flatten();
+    match accent {
+        Some(accent) => {
+            let h = crate::theme::accent_highlights(accent, state.dark_mode);
+            (Some(h.selection), Some(h.caret_line))
+        }
+        None => (None, None),
+    }
+}
+
+/// Apply [`accent_colours`] to every editor view.
+fn apply_accent_highlights(state: &WindowState) {
+    let (selection, caret_line) = accent_colours(state);
+    for view in &state.sci_views {
+        view.set_highlights(selection, caret_line);
+    }
+}
+
+/// Give `hwnd` the dark Explorer visual style (dark scrollbars), or restore
+/// the default style.
+fn apply_control_theme(hwnd: HWND, dark: bool) {
+    let class = if dark {
+        w!("DarkMode_Explorer")
+    } else {
+        PCWSTR::null()
+    };
+    // SAFETY: hwnd is a valid window; both strings are static or null.
+    unsafe {
+        let _ = SetWindowTheme(hwnd, class, PCWSTR::null());
+    }
+}
+
+/// Apply the current `dark_mode` to the child controls' visual styles: every
+/// Scintilla view, the tab strip and its tooltip, the folder panel, the
+/// toolbar, and the status bar with its progress controls.
+fn apply_dark_controls(state: &WindowState) {
+    if let Some(folder) = state.folder.as_ref() {
+        theme_folder_panel(folder, state.dark_mode, state.system_colours.as_ref());
+    }
+    for view in &state.sci_views {
+        apply_control_theme(view.hwnd(), dark_chrome(state));
+    }
+    apply_control_theme(state.hwnd_tab, dark_chrome(state));
+    apply_control_theme(state.hwnd_status, dark_chrome(state));
+    for hwnd in state.progress.iter().flat_map(StatusProgress::hwnds) {
+        apply_control_theme(hwnd, dark_chrome(state));
+    }
+    // SAFETY: hwnd_tab is a valid tab control; TCM_GETTOOLTIPS takes no
+    // parameters and returns the tooltip HWND or null.
+    let tooltip = unsafe { SendMessageW(state.hwnd_tab, TCM_GETTOOLTIPS, WPARAM(0), LPARAM(0)) };
+    if tooltip.0 != 0 {
+        apply_control_theme(HWND(tooltip.0 as *mut _), dark_chrome(state));
+    }
+    if let Some(bar) = &state.toolbar {
+        // SAFETY: the toolbar is a live child; repainting it picks up
+        // `toolbar_colours`.
+        unsafe {
+            let _ = InvalidateRect(bar.hwnd(), None, true);
+        }
+    }
+}
+
+// ── Tab position helpers ──────────────────────────────────────────────────────
+
+/// Apply the Win32 style bits for `pos` to the tab control and force a repaint.
+///
+/// # Safety
+/// `hwnd_tab` must be a valid `SysTabControl32` HWND.
+unsafe fn set_tab_style(hwnd_tab: HWND, pos: TabPosition) {
+    let cur = GetWindowLongPtrW(hwnd_tab, GWL_STYLE) as u32;
+    let new_style = match pos {
+        TabPosition::Top => cur & !(TCS_VERTICAL | TCS_RIGHT),
+        TabPosition::Left => (cur & !TCS_RIGHT) | TCS_VERTICAL,
+        TabPosition::Right => cur | TCS_VERTICAL | TCS_RIGHT,
+    };
+    SetWindowLongPtrW(hwnd_tab, GWL_STYLE, new_style as isize);
+    // Force the tab control to re-measure and repaint with the new style.
+    let _ = SetWindowPos(
+        hwnd_tab,
+        HWND::default(),
+        0,
+        0,
+        0,
+        0,
+        SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED,
+    );
+}
+
+/// Set or clear `TCS_MULTILINE` on the tab control.
+///
+/// # Safety
+/// `hwnd_tab` must be a valid `SysTabControl32` HWND.
+unsafe fn set_tab_multiline(hwnd_tab: HWND, on: bool) {
+    let cur = GetWindowLongPtrW(hwnd_tab, GWL_STYLE) as u32;
+    let new_style = if on {
+        cur | TCS_MULTILINE
+    } else {
+        cur & !TCS_MULTILINE
+    };
+    if new_style != cur {
+        SetWindowLongPtrW(hwnd_tab, GWL_STYLE, new_style as isize);
+    }
+}
+
+/// Height of a multiline tab strip `width` pixels wide: `row_h` for the
+/// first row plus one tab height per extra row.  The strip is resized to
+/// `width` first so the control wraps its tabs at the final width.
+///
+/// # Safety
+/// `hwnd_tab` must be a valid `SysTabControl32` HWND with `TCS_MULTILINE`.
+unsafe fn tab_rows_height(hwnd_tab: HWND, width: i32, row_h: i32) -> i32 {
+    let _ = SetWindowPos(
+        hwnd_tab,
+        HWND::default(),
+        0,
+        0,
+        width,
+        row_h,
+        SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+    let rows = SendMessageW(hwnd_tab, TCM_GETROWCOUNT, WPARAM(0), LPARAM(0)).0 as i32;
+    let mut rc = RECT::default();
+    // SAFETY: rc outlives the call; TCM_GETITEMRECT fills it for item 0.
+    let _ = SendMessageW(
+        hwnd_tab,
+        TCM_GETITEMRECT,
+        WPARAM(0),
+        LPARAM(&mut rc as *mut RECT as isize),
+    );
+    row_h + (rows - 1).max(0) * (rc.bottom - rc.top)
+}
+
+/// Handle View > Multiline Tabs: flip the setting, persist it, and re-lay
+/// out the window.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_multiline_tabs_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.multiline_tabs = !state.settings.multiline_tabs;
+    let _ = crate::config::save(&state.settings);
+    update_multiline_tabs_checkmark(state);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Sync the View > Multiline Tabs checkmark with the setting.
+fn update_multiline_tabs_checkmark(state: &WindowState) {
+    let checked = state.settings.multiline_tabs;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_MULTILINE_TABS as u32, flag);
+    }
+}
+
+/// Handle View > File Icons in Tabs: flip the setting, persist it, and
+/// re-lay out the window (the icons change the strip's height).
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_tab_icons_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.tab_icons = !state.settings.tab_icons;
+    let _ = crate::config::save(&state.settings);
+    apply_tab_icons(state);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Give the tab strip the shell's small image list (or none) to match the
+/// setting, refresh every tab's icon, and sync the View menu checkmark.
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn apply_tab_icons(state: &WindowState) {
+    let on = state.settings.tab_icons;
+    let list = if on {
+        crate::platform::win32::icons::small_image_list().0
+    } else {
+        0
+    };
+    // SAFETY: hwnd_tab is the live tab strip.  The system image list is
+    // shared and outlives the control, which never destroys its list.
+    let _ = SendMessageW(state.hwnd_tab, TCM_SETIMAGELIST, WPARAM(0), LPARAM(list));
+    for idx in 0..state.app.tabs.len() {
+        sync_tab_label(state, idx);
+    }
+    let flag = (MF_BYCOMMAND | if on { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_TAB_ICONS as u32, flag);
+}
+
+/// Change the tab bar position, update the Win32 style, reposition all children.
+///
+/// # Safety
+/// `hwnd` and `state` must be valid.
+unsafe fn handle_tab_position(hwnd: HWND, state: &mut WindowState, pos: TabPosition) {
+    if state.tab_position == pos {
+        return;
+    }
+    state.tab_position = pos;
+    set_tab_style(state.hwnd_tab, pos);
+    update_tab_position_checkmarks(state.menu_bar, pos);
+    // Tabs in the title bar only applies at the top.
+    refresh_frame(hwnd);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Sync the View > Tab Position checkmarks to reflect the current `pos`.
+///
+/// # Safety
+/// `menu` must be the main window's menu bar.
+unsafe fn update_tab_position_checkmarks(menu: HMENU, pos: TabPosition) {
+    let set = |id: usize, checked: bool| {
+        let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+        let _ = CheckMenuItem(menu, id as u32, flag);
+    };
+    set(IDM_VIEW_TAB_TOP, pos == TabPosition::Top);
+    set(IDM_VIEW_TAB_LEFT, pos == TabPosition::Left);
+    set(IDM_VIEW_TAB_RIGHT, pos == TabPosition::Right);
+}
+
+// ── Menu bar auto-hide ────────────────────────────────────────────────────────
+
+/// Handle View > Auto-hide Menu Bar: flip the setting, persist it, and
+/// attach or detach the menu bar to match.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_autohide_menu_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.auto_hide_menu = !state.settings.auto_hide_menu;
+    let _ = crate::config::save(&state.settings);
+    apply_menu_autohide(hwnd, state);
+}
+
+/// Attach or detach the menu bar according to [`menu_autohides`] and sync the
+/// View menu checkmark.
+///
+/// While hidden, `WM_SYSCOMMAND`/`SC_KEYMENU` re-attaches the bar and
+/// `WM_EXITMENULOOP` detaches it again.  Accelerators keep working either way.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn apply_menu_autohide(hwnd: HWND, state: &WindowState) {
+    let checked = state.settings.auto_hide_menu;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_AUTOHIDE_MENU as u32, flag);
+    let target = if menu_autohides(&state.settings) {
+        HMENU::default()
+    } else {
+        state.menu_bar
+    };
+    // SetMenu resizes the client area, so WM_SIZE re-lays out the children.
+    let _ = SetMenu(hwnd, target);
+}
+
+/// Handle View > Minimize to Tray.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn handle_tray_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.minimize_to_tray = !state.settings.minimize_to_tray;
+    let _ = crate::config::save(&state.settings);
+    apply_tray(hwnd, state);
+}
+
+/// Show or remove the notification-area icon according to the setting and
+/// sync the View menu checkmark.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn apply_tray(hwnd: HWND, state: &WindowState) {
+    let checked = state.settings.minimize_to_tray;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_TRAY as u32, flag);
+    if checked {
+        tray::add(hwnd);
+    } else {
+        tray::remove(hwnd);
+    }
+}
+
+/// Handle a mouse event (`event`, e.g. `WM_LBUTTONUP`) on the tray icon: a
+/// click brings the window back, a right-click opens the icon's menu.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn handle_tray_icon(hwnd: HWND, state: &mut WindowState, event: u32) {
+    let choice = match event {
+        WM_LBUTTONUP => tray::TrayChoice::Restore,
+        WM_RBUTTONUP => match tray::show_menu(hwnd) {
+            Some(choice) => choice,
+            None => return,
+        },
+        _ => return,
+    };
+    if choice == tray::TrayChoice::Exit {
+        state.quitting = true;
+        let _ = PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0));
+        return;
+    }
+    bring_to_front(hwnd);
+    if choice == tray::TrayChoice::NewFile {
+        handle_new_file(hwnd, state);
+    }
+}
+
+/// Show `hwnd` if hidden in the tray, restore it if minimized, and make it
+/// the foreground window.
+///
+/// # Safety
+/// `hwnd` must be a valid main-window handle.
+unsafe fn bring_to_front(hwnd: HWND) {
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    if IsIconic(hwnd).as_bool() {
+        let _ = ShowWindow(hwnd, SW_RESTORE);
+    }
+    let _ = SetForegroundWindow(hwnd);
+}
+
+/// `true` when the menu bar stays detached until Alt is pressed.
+///
+/// Tabs in the title bar need the menu out of the caption band, so that
+/// option implies auto-hide.
+fn menu_autohides(settings: &crate::config::Settings) -> bool {
+    settings.auto_hide_menu || settings.tabs_in_title_bar
+}
+
+// ── Caret ─────────────────────────────────────────────────────────────────────
+
+/// Handle View > Caret…: edit the caret settings, persist them, and apply
+/// them to every open view.
+fn handle_caret_settings(hwnd: HWND, state: &mut WindowState) {
+    let Some(caret) =
+        crate::platform::win32::caret_dialog::show_caret_dialog(hwnd, state.settings.caret)
+    else {
+        return;
+    };
+    state.settings.caret = caret;
+    if let Err(e) = crate::config::save(&state.settings) {
+        show_error_dialog(&format!("Could not save settings:\n{e}"));
+    }
+    for view in &state.sci_views {
+        view.set_caret(caret);
+    }
+}
+
+// ── Tabs in the title bar ─────────────────────────────────────────────────────
+
+/// `true` while the tab strip is drawn in the caption band.
+///
+/// Needs the option on, tabs at the top, and the menu bar detached: while Alt
+/// has the menu attached the window falls back to its standard frame.  The
+/// band shows the DWM frame, so it follows dark mode with the rest of the
+/// caption (see `apply_title_bar_dark`).
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn title_bar_tabs_active(hwnd: HWND, state: &WindowState) -> bool {
+    state.settings.tabs_in_title_bar
+        && state.tab_position == TabPosition::Top
+        && GetMenu(hwnd).is_invalid()
+}
+
+/// Handle View > Tabs in Title Bar: flip the setting, persist it, and rebuild
+/// the frame.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_title_bar_tabs_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.tabs_in_title_bar = !state.settings.tabs_in_title_bar;
+    let _ = crate::config::save(&state.settings);
+    apply_menu_autohide(hwnd, state);
+    apply_title_bar_tabs(hwnd, state);
+}
+
+/// Sync the View menu checkmark and re-lay out the window for the current
+/// title-bar mode.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn apply_title_bar_tabs(hwnd: HWND, state: &WindowState) {
+    let on = state.settings.tabs_in_title_bar;
+    let flag = (MF_BYCOMMAND | if on { MF_CHECKED } else { MF_UNCHECKED }).0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_TITLEBAR_TABS as u32, flag);
+    refresh_frame(hwnd);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Make Windows re-send `WM_NCCALCSIZE` so the frame matches the current
+/// title-bar mode.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn refresh_frame(hwnd: HWND) {
+    let _ = SetWindowPos(
+        hwnd,
+        HWND::default(),
+        0,
+        0,
+        0,
+        0,
+        SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+    );
+}
+
+// ── Tools menu ────────────────────────────────────────────────────────────────
+
+/// Clear and refill the Tools popup from `state.settings.tools`.
+///
+/// Layout: Run… | Configure Tools… | Keyboard Shortcuts… | separator |
+/// Compare With File… | Compare With Tab… | separator | Transform Selection |
+/// Format JSON | Minify JSON | File Associations… | separator | one item per
+/// tool.
+fn rebuild_tools_menu(state: &WindowState) {
+    let menu = state.tools_menu;
+    // SAFETY: tools_menu is the live Tools popup owned by the menu bar; item
+    // strings are null-terminated UTF-16 that outlive each AppendMenuW call.
+    unsafe {
+        while GetMenuItemCount(menu) > 0 {
+            if DeleteMenu(menu, 0, MF_BYPOSITION).is_err() {
+                break;
+            }
+        }
+        let _ = AppendMenuW(menu, MF_STRING, IDM_TOOLS_RUN, w!("&Run\u{2026}"));
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_CONFIGURE,
+            w!("&Configure Tools\u{2026}"),
+        );
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_SHORTCUTS,
+            w!("&Keyboard Shortcuts\u{2026}"),
+        );
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_COMPARE_FILE,
+            w!("Compare With &File\u{2026}"),
+        );
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_COMPARE_TAB,
+            w!("Compare With &Tab\u{2026}"),
+        );
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+        // DeleteMenu above destroyed the previous submenu.
+        if let Ok(transform) = CreatePopupMenu() {
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_BASE64_ENCODE,
+                w!("&Base64 Encode"),
+            );
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_BASE64_DECODE,
+                w!("Base64 &Decode"),
+            );
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_URL_ENCODE,
+                w!("&URL Encode"),
+            );
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_URL_DECODE,
+                w!("U&RL Decode"),
+            );
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_HTML_ESCAPE,
+                w!("&HTML Escape"),
+            );
+            let _ = AppendMenuW(
+                transform,
+                MF_STRING,
+                IDM_TOOLS_HTML_UNESCAPE,
+                w!("HTML U&nescape"),
+            );
+            let _ = AppendMenuW(
+                menu,
+                MF_POPUP,
+                transform.0 as usize,
+                w!("Transform &Selection"),
+            );
+        }
+        let _ = AppendMenuW(menu, MF_STRING, IDM_TOOLS_FORMAT_JSON, w!("Format &JSON"));
+        let _ = AppendMenuW(menu, MF_STRING, IDM_TOOLS_MINIFY_JSON, w!("&Minify JSON"));
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_GENERATE_HASH,
+            w!("Generate &Hash\u{2026}"),
+        );
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_TEXT_PIPELINE,
+            w!("Text &Pipeline\u{2026}"),
+        );
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            IDM_TOOLS_ASSOCIATIONS,
+            w!("File &Associations\u{2026}"),
+        );
+        if !state.settings.tools.is_empty() {
+            let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+        }
+        for (i, tool) in state.settings.tools.iter().take(MAX_TOOLS).enumerate() {
+            let label: Vec<u16> = tool.name.encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = AppendMenuW(menu, MF_STRING, IDM_TOOLS_FIRST + i, PCWSTR(label.as_ptr()));
+        }
+    }
+    apply_menu_shortcuts(state);
+}
+
+// ── Transform Selection ───────────────────────────────────────────────────────
+
+/// Handle Tools > Transform Selection: replace the selection with its
+/// encoded or decoded form as one undo step and select the result.  Beeps
+/// with no selection, in a read-only tab, or if the text does not decode.
+fn handle_transform(state: &WindowState, transform: Transform) {
+    let idx = state.app.active_idx;
+    let sci = &state.sci_views[idx];
+    let (start, end) = (sci.selection_start(), sci.selection_end());
+    let result =
+        if start == end || state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+            None
+        } else {
+            crate::transform::apply(transform, &sci.text_range(start, end))
+        };
+    let Some(new) = result else {
+        // SAFETY: MessageBeep has no preconditions.
+        unsafe {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+        return;
+    };
+    sci.begin_undo_action();
+    sci.set_target(start, end);
+    sci.replace_target(&new);
+    sci.end_undo_action();
+    sci.set_sel(start, start + new.len());
+}
+
+// ── JSON ──────────────────────────────────────────────────────────────────────
+
+/// Handle Tools > Format JSON, or Minify JSON (`minify`): reformat the
+/// selection, or the whole document if nothing is selected, as one undo
+/// step.  Format indents with the document's indentation.  Text that is not
+/// JSON is left alone; the caret moves to the error and a message gives its
+/// line and column.
+fn handle_json(hwnd: HWND, state: &WindowState, minify: bool) {
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    if doc.read_only || viewer_index(state, idx).is_some() {
+        // SAFETY: MessageBeep has no preconditions.
+        unsafe {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+        return;
+    }
+    let (start, end) = (sci.selection_start(), sci.selection_end());
+    let whole_document = start == end;
+    let (start, end) = if whole_document {
+        (0, sci.doc_len())
+    } else {
+        (start, end)
+    };
+    let text = sci.text_range(start, end);
+    let result = if minify {
+        crate::json::minify(&text)
+    } else {
+        let indent = if doc.indent.use_tabs {
+            "\t".to_owned()
+        } else {
+            " ".repeat(doc.indent.width)
+        };
+        crate::json::format(&text, &indent, doc.eol.bytes())
+    };
+    match result {
+        Ok(new) if new == text => {}
+        Ok(new) => {
+            sci.begin_undo_action();
+            sci.set_target(start, end);
+            sci.replace_target(&new);
+            sci.end_undo_action();
+            if whole_document {
+                sci.set_sel(0, 0);
+            } else {
+                sci.set_sel(start, start + new.len());
+            }
+        }
+        Err(e) => {
+            let pos = start + e.offset;
+            sci.set_sel(pos, pos);
+            let line = sci.line_from_position(pos);
+            let column = pos - sci.position_from_line(line) + 1;
+            let title = if minify { "Minify JSON" } else { "Format JSON" };
+            let msg = format!(
+                "Not valid JSON: {} (line {}, column {column}).",
+                e.message,
+                line + 1
+            );
+            let wide_msg: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+            let wide_title: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
+            // SAFETY: both strings are null-terminated and outlive the call.
+            unsafe {
+                let _ = MessageBoxW(
+                    hwnd,
+                    PCWSTR(wide_msg.as_ptr()),
+                    PCWSTR(wide_title.as_ptr()),
+                    MB_OK | MB_ICONERROR,
+                );
+            }
+        }
+    }
+}
+
+// ── Generate Hash ─────────────────────────────────────────────────────────────
+
+/// Handle Tools > Generate Hash…: show the MD5, SHA-1 and SHA-256 of the
+/// selected text, or with no selection of the file as saved on disk (an
+/// untitled buffer hashes its UTF-8 text).  The file is read in chunks so a
+/// large download is never held in memory twice.
+fn handle_generate_hash(hwnd: HWND, state: &WindowState) {
+    use std::io::Read;
+
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    let (start, end) = (sci.selection_start(), sci.selection_end());
+    let (source, digests) = if start != end {
+        let text = sci.text_range(start, end);
+        let source = format!("Selection ({} bytes, UTF-8)", text.len());
+        (source, crate::tools::hash::digests(&text))
+    } else if let Some(path) = &doc.path {
+        let mut file = match std::fs::File::open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                show_error_dialog(&format!("Could not open file:\n{e}"));
+                return;
+            }
+        };
+        let mut hasher = crate::tools::hash::Hasher::default();
+        let mut buf = vec![0; 1 << 16];
+        let mut total = 0u64;
+        loop {
+            match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    hasher.update(&buf[..n]);
+                    total += n as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    show_error_dialog(&format!("Could not read file:\n{e}"));
+                    return;
+                }
+            }
+        }
+        let mut source = format!("{} ({total} bytes on disk)", path.display());
+        if doc.dirty {
+            source.push_str(" \u{2014} unsaved changes are not included");
+        }
+        (source, hasher.finish())
+    } else {
+        let text = sci.get_text();
+        let source = format!("{} ({} bytes, UTF-8)", doc.display_name(), text.len());
+        (source, crate::tools::hash::digests(&text))
+    };
+    crate::platform::win32::hash_dialog::show_hash_dialog(hwnd, &source, &digests);
+}
+
+// ── Text Pipeline ─────────────────────────────────────────────────────────────
+
+/// Handle Tools > Text Pipeline…: run the recipe the user composes in the
+/// dialog over the selected lines, or the whole document if nothing is
+/// selected, and replace them as one undo step.  Beeps in a read-only tab.
+fn handle_text_pipeline(hwnd: HWND, state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+        // SAFETY: MessageBeep has no preconditions.
+        unsafe {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+        return;
+    }
+    let sci = &state.sci_views[idx];
+    let whole_document = sci.selection_start() == sci.selection_end();
+    let (start, end) = if whole_document {
+        (0, sci.doc_len())
+    } else {
+        let (first, last) = selected_lines(sci);
+        lines_range(sci, first, last)
+    };
+    let text = sci.text_range(start, end);
+    let eol = state.app.tabs[idx].eol.bytes();
+    let Some(recipe) = crate::platform::win32::pipeline_dialog::show_pipeline_dialog(
+        hwnd,
+        &text,
+        eol,
+        &state.pipeline_recipe,
+    ) else {
+        return;
+    };
+    let steps = crate::editor::pipeline::parse(&recipe).unwrap_or_default();
+    state.pipeline_recipe = recipe;
+    let (new, _) = crate::editor::pipeline::run(&steps, &text, eol);
+    if new == text {
+        return;
+    }
+    let sci = &state.sci_views[idx];
+    sci.begin_undo_action();
+    sci.set_target(start, end);
+    sci.replace_target(&new);
+    sci.end_undo_action();
+    if whole_document {
+        sci.set_sel(0, 0);
+    } else {
+        sci.set_sel(start, start + new.len());
+    }
+}
+
+// ── Compare ───────────────────────────────────────────────────────────────────
+
+/// Handle Tools > Compare With File…: diff the active document against a
+/// file chosen on disk.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn handle_compare_file(hwnd: HWND, state: &mut WindowState) {
+    let Some(path) = show_open_dialog(hwnd) else {
+        return;
+    };
+    let Some(bytes) = read_file_prompting(hwnd, "open", &path) else {
+        return;
+    };
+    let (_, other) = App::detect_and_decode(&bytes, state.app.ansi);
+    let label = path.to_string_lossy().into_owned();
+    compare_tab_with(hwnd, state, state.app.active_idx, &label, &other);
+}
+
+/// Handle Tools > Compare With Tab…: diff the active document against
+/// another open tab picked from the tab switcher.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn handle_compare_tab(hwnd: HWND, state: &mut WindowState) {
+    let active = state.app.active_idx;
+    let root = display_root(state);
+    let others: Vec<usize> = (0..state.app.tab_count())
+        .filter(|&i| i != active)
+        .collect();
+    if others.is_empty() {
+        show_error_dialog("Open another tab to compare with.");
+        return;
+    }
+    let entries: Vec<crate::ui::switcher::SwitcherEntry> = others
+        .iter()
+        .map(|&i| crate::ui::switcher::SwitcherEntry::new(&state.app.tabs[i], root))
+        .collect();
+    let Some(pick) = super::switcher_dialog::show_switcher_dialog(hwnd, &entries, 0) else {
+        return;
+    };
+    let idx = others[pick];
+    let label = state.app.tabs[idx].display_path(root);
+    let other = state.sci_views[idx].get_text();
+    compare_tab_with(hwnd, state, active, &label, &other);
+}
+
+/// Diff `other` (old, labelled `label`) against the text of tab `idx` (new)
+/// and show the result in a new tab, or say that they match.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid; `idx` must be in
+/// range.
+unsafe fn compare_tab_with(
+    hwnd: HWND,
+    state: &mut WindowState,
+    idx: usize,
+    label: &str,
+    other: &[u8],
+) {
+    let doc = &state.app.tabs[idx];
+    let name = doc.display_name();
+    let new_label = doc.display_path(display_root(state));
+    let text = state.sci_views[idx].get_text();
+    match crate::diff::unified(label, &new_label, other, &text) {
+        Some(diff) => open_diff_tab(hwnd, state, &format!("{name}.diff"), &diff),
+        None => {
+            let _ = MessageBoxW(
+                hwnd,
+                w!("The documents have the same lines."),
+                w!("Rivet"),
+                MB_OK | MB_ICONINFORMATION,
+            );
+        }
+    }
+}
+
+/// Diff the file at `path` against the unsaved text of tab `idx` and show
+/// the result in a read-only tab.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid; `idx` must be in
+/// range.
+unsafe fn show_unsaved_changes(
+    hwnd: HWND,
+    state: &mut WindowState,
+    idx: usize,
+    path: &std::path::Path,
+) {
+    let bytes = match std::fs::read(path) {
+        Ok(b) => b,
+        Err(e) => {
+            show_error_dialog(&format!("Could not read the saved file:\n{e}"));
+            return;
+        }
+    };
+    let (_, saved) = App::detect_and_decode(&bytes, state.app.ansi);
+    let label = format!("{} (saved)", path.to_string_lossy());
+    compare_tab_with(hwnd, state, idx, &label, &saved);
+}
+
+/// Open `diff` in a new read-only tab titled `title`, highlighted by the
+/// Diff lexer.
+///
+/// # Safety
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn open_diff_tab(hwnd: HWND, state: &mut WindowState, title: &str, diff: &[u8]) {
+    let before = state.app.tab_count();
+    open_untitled_tab(hwnd, state);
+    if state.app.tab_count() == before {
+        return;
+    }
+    let idx = state.app.active_idx;
+    {
+        let doc = state.app.active_doc_mut();
+        doc.title = Some(title.to_owned());
+        doc.language = crate::languages::Language::Diff;
+        doc.eol = EolMode::Lf;
+    }
+    let view = &state.sci_views[idx];
+    apply_highlighting(
+        view,
+        state.app.active_doc(),
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    view.set_eol_mode(EolMode::Lf);
+    view.set_text(diff);
+    view.set_save_point();
+    set_tab_read_only(hwnd, state, idx, true);
+    update_status_bar(state);
+}
+
+// ── Keyboard shortcuts ────────────────────────────────────────────────────────
+
+/// Handle Tools > Keyboard Shortcuts…: edit the bindings, save them to
+/// `keymap.json`, and swap in a rebuilt accelerator table.
+fn handle_tools_shortcuts(hwnd: HWND, state: &mut WindowState) {
+    let Some(bindings) = crate::platform::win32::shortcuts_dialog::show_shortcuts_dialog(
+        hwnd,
+        COMMANDS,
+        &state.bindings,
+    ) else {
+        return;
+    };
+    let accel = match create_accelerators(&bindings) {
+        Ok(accel) => accel,
+        Err(e) => {
+            show_error_dialog(&format!("Could not apply the shortcuts:\n{e}"));
+            return;
+        }
+    };
+    if !state.accel.is_invalid() {
+        // SAFETY: the old table is ours and no longer referenced once replaced.
+        unsafe {
+            let _ = DestroyAcceleratorTable(state.accel);
+        }
+    }
+    state.accel = accel;
+    state.bindings = bindings;
+    if let Err(e) = crate::keymap::save(COMMANDS, &state.bindings) {
+        show_error_dialog(&format!("Could not save keymap.json:\n{e}"));
+    }
+    apply_menu_shortcuts(state);
+}
+
+/// Show each command's bound chord after a tab in its menu item text.
+fn apply_menu_shortcuts(state: &WindowState) {
+    for (command, chord) in COMMANDS.iter().zip(&state.bindings) {
+        let mut buf = [0u16; 128];
+        // SAFETY: menu_bar is the live main menu; MF_BYCOMMAND searches its
+        // popups.  buf outlives both calls.
+        unsafe {
+            let len = GetMenuStringW(
+                state.menu_bar,
+                command.id as u32,
+                Some(&mut buf),
+                MF_BYCOMMAND,
+            );
+            if len <= 0 {
+                continue;
+            }
+            let text = String::from_utf16_lossy(&buf[..len as usize]);
+            let base = text.split('\t').next().unwrap_or_default();
+            let label = match chord {
+                Some(chord) => format!("{base}\t{chord}"),
+                None => base.to_owned(),
+            };
+            let mut wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+            let info = MENUITEMINFOW {
+                cbSize: std::mem::size_of::<MENUITEMINFOW>() as u32,
+                fMask: MIIM_STRING,
+                dwTypeData: PWSTR(wide.as_mut_ptr()),
+                ..Default::default()
+            };
+            let _ = SetMenuItemInfoW(state.menu_bar, command.id as u32, false, &info);
+        }
+    }
+}
+
+/// Expand `template` against the active document and launch it.
+///
+/// The child's working directory is the document's folder, or Rivet's own
+/// current directory for untitled buffers.  Failures are reported in a dialog.
+fn launch_command(state: &WindowState, template: &str) {
+    let idx = state.app.active_idx;
+    let sci = &state.sci_views[idx];
+    let (line, column) = sci.caret_line_col();
+    let selection = String::from_utf8_lossy(&sci.selected_text()).into_owned();
+    let path = state.app.active_doc().path.as_deref();
+    let ctx = crate::tools::ToolContext {
+        path,
+        line,
+        column,
+        selection: &selection,
+    };
+    let command = crate::tools::expand(template, &ctx);
+    let dir = path.and_then(std::path::Path::parent);
+    if let Err(e) = crate::platform::win32::process::spawn_detached(&command, dir) {
+        show_error_dialog(&format!("Could not run:\n{command}\n\n{e}"));
+    }
+}
+
+/// Handle Tools > Run…: prompt for a command line and launch it once.
+///
+/// The entered text is remembered in `settings.json` for next time.
+fn handle_tools_run(hwnd: HWND, state: &mut WindowState) {
+    let label = "Command line (placeholders such as $(FULL_PATH) and $(LINE) are expanded):";
+    let Some(cmd) = crate::platform::win32::dlg::show_prompt_dialog(
+        hwnd,
+        "Run",
+        label,
+        &state.settings.last_run_command,
+        true,
+    ) else {
+        return;
+    };
+    let cmd = cmd.trim().to_owned();
+    launch_command(state, &cmd);
+    state.settings.last_run_command = cmd;
+    let _ = crate::config::save(&state.settings);
+}
+
+/// Handle Tools > Configure Tools…: edit the tool list and rebuild the menu.
+fn handle_tools_configure(hwnd: HWND, state: &mut WindowState) {
+    let Some(tools) =
+        crate::platform::win32::tools_dialog::show_tools_dialog(hwnd, &state.settings.tools)
+    else {
+        return;
+    };
+    state.settings.tools = tools;
+    if let Err(e) = crate::config::save(&state.settings) {
+        show_error_dialog(&format!("Could not save settings:\n{e}"));
+    }
+    rebuild_tools_menu(state);
+}
+
+/// Handle Tools > File Associations…: register or remove the per-user
+/// "Edit with Rivet" menu entry and extension associations.
+fn handle_file_associations(hwnd: HWND, state: &mut WindowState) {
+    use crate::platform::win32::associations::{self, AssociationChoice};
+
+    let previous = state.settings.associated_extensions.clone();
+    let Some(choice) = associations::show_associations_dialog(hwnd, &previous) else {
+        return;
+    };
+    let result =
+        std::env::current_exe().and_then(|exe| associations::apply(&exe, &choice, &previous));
+    if let Err(e) = result {
+        show_error_dialog(&format!("Could not update file associations:\n{e}"));
+        return;
+    }
+    state.settings.associated_extensions = match choice {
+        AssociationChoice::Register { extensions, .. } => extensions,
+        AssociationChoice::Unregister => Vec::new(),
+    };
+    if let Err(e) = crate::config::save(&state.settings) {
+        show_error_dialog(&format!("Could not save settings:\n{e}"));
+    }
+}
+
+/// Launch the user-defined tool at `idx` in `settings.tools`.
+fn handle_tool_launch(state: &WindowState, idx: usize) {
+    if let Some(tool) = state.settings.tools.get(idx) {
+        launch_command(state, &tool.command);
+    }
+}
+
+// ── Find / Replace helpers ────────────────────────────────────────────────────
+
+/// Open (or focus) the modeless Find dialog.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_find_open(hwnd: HWND, state: &mut WindowState) {
+    note_find_target(state);
+    if state.hwnd_find_dlg != HWND::default() {
+        // Dialog already open — bring it to the front.
+        let _ = SetForegroundWindow(state.hwnd_find_dlg);
+        return;
+    }
+    state.findreplace.hwndOwner = hwnd;
+    state.findreplace.lCustData = history_param(state);
+    seed_find_text(state);
     // Clear the replace-only flag so FindTextW shows the Find dialog.
     state.findreplace.Flags =
         FINDREPLACE_FLAGS((state.findreplace.Flags.0 & !(FR_REPLACE | FR_REPLACEALL)) | FR_DOWN);
@@ -1861,136 +6694,1792 @@ unsafe fn handle_find_open(hwnd: HWND, state: &mut WindowState) {
     state.hwnd_find_dlg = FindTextW(&mut state.findreplace);
 }
 
-/// Open (or focus) the modeless Replace dialog.
+/// Open (or focus) the modeless Replace dialog.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_replace_open(hwnd: HWND, state: &mut WindowState) {
+    note_find_target(state);
+    if state.hwnd_find_dlg != HWND::default() {
+        let _ = SetForegroundWindow(state.hwnd_find_dlg);
+        return;
+    }
+    state.findreplace.hwndOwner = hwnd;
+    state.findreplace.lCustData = history_param(state);
+    seed_find_text(state);
+    state.findreplace.Flags = FINDREPLACE_FLAGS(state.findreplace.Flags.0 | FR_DOWN);
+    // SAFETY: findreplace is stable in heap memory; hwndOwner is valid.
+    state.hwnd_find_dlg = ReplaceTextW(&mut state.findreplace);
+}
+
+/// `lCustData` for the Find / Replace dialog: the search history its fields
+/// step through (see `dialogs::find_hook`).  `WindowState` never moves, so
+/// the pointer stays valid while the dialog is open.
+fn history_param(state: &WindowState) -> LPARAM {
+    LPARAM(&state.settings.search_history as *const _ as isize)
+}
+
+/// Add the current find (and, if `replacing`, replace) string to the search
+/// history, saving the settings if that changed it.
+fn remember_search(state: &mut WindowState, replacing: bool) {
+    let typed = |buf: &[u16]| {
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..len])
+    };
+    let history = &mut state.settings.search_history;
+    let mut changed = history.find.push(&typed(&state.find_buf[..]));
+    if replacing {
+        changed |= history.replace.push(&typed(&state.replace_buf[..]));
+    }
+    if changed {
+        let _ = crate::config::save(&state.settings);
+    }
+}
+
+/// Put the selection into the Find field, if one line or less of text is
+/// selected; otherwise the last search stays.  Returns whether it did.
+fn seed_find_text(state: &mut WindowState) -> bool {
+    let selected = find_view(state).selected_text();
+    // One unit of the buffer is the terminating NUL.
+    let Some(units) = crate::search::seed_text(&selected, state.find_buf.len() - 1) else {
+        return false;
+    };
+    state.find_buf.fill(0);
+    state.find_buf[..units.len()].copy_from_slice(&units);
+    true
+}
+
+/// Handle Search > Find Selected Word (Ctrl+F3): search forward for the
+/// selection, or for the word at the caret when nothing is selected, without
+/// opening the dialog.  The text becomes the last search, so F3 repeats it.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_find_selected(hwnd: HWND, state: &mut WindowState) {
+    let view = find_view(state);
+    if view.selection_start() == view.selection_end() {
+        let caret = view.caret_pos();
+        let line = view.line_from_position(caret);
+        let start = view.position_from_line(line);
+        let text = view.line_text(line);
+        match crate::search::word_around(&text, caret - start) {
+            Some(word) => view.set_sel(start + word.start, start + word.end),
+            None => {
+                let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+                return;
+            }
+        }
+    }
+    if !seed_find_text(state) {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    handle_find_next(hwnd, state, true);
+}
+
+/// The Scintilla view that Find / Replace / F3 act on: the pane holding the
+/// focus, or, while the focus is in the Find dialog, the pane that had it
+/// when the dialog was invoked; the active tab otherwise.
+///
+/// Search never indexes `sci_views` directly: every Scintilla-backed surface
+/// is resolved here, so adding a searchable pane only means teaching
+/// `search_pane` about it.
+fn find_view(state: &WindowState) -> &ScintillaView {
+    // SAFETY: GetFocus only reads the calling thread's focus.
+    let focus = unsafe { GetFocus() };
+    search_pane(state, focus)
+        .or_else(|| {
+            (state.hwnd_find_dlg != HWND::default())
+                .then(|| search_pane(state, state.find_target))
+                .flatten()
+        })
+        .unwrap_or(&state.sci_views[state.app.active_idx])
+}
+
+/// The searchable view whose window is `hwnd`: the active editor or a shown
+/// results or outline panel.
+fn search_pane(state: &WindowState, hwnd: HWND) -> Option<&ScintillaView> {
+    if hwnd == HWND::default() {
+        return None;
+    }
+    let editor = &state.sci_views[state.app.active_idx];
+    let results = state.results.as_ref().filter(|p| p.visible);
+    let outline = state.outline.as_ref();
+    [
+        Some(editor),
+        results.map(|p| &p.view),
+        outline.map(|p| &p.view),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|view| view.hwnd() == hwnd)
+}
+
+/// Remember which pane Find or Replace was invoked from (see `find_view`).
+fn note_find_target(state: &mut WindowState) {
+    state.find_target = find_view(state).hwnd();
+}
+
+/// Search the `find_view` for the next match: through the viewer window for
+/// a viewer tab, with Scintilla otherwise.  Returns whether it found one.
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn find_next_in_target(
+    state: &mut WindowState,
+    find: &[u8],
+    sci_flags: u32,
+    forward: bool,
+) -> bool {
+    let editor = state.sci_views[state.app.active_idx].hwnd();
+    if find_view(state).hwnd() == editor {
+        if let Some(found) = viewer_find_next(state, find, sci_flags, forward) {
+            return found;
+        }
+    }
+    find_view(state).find_next(find, sci_flags, forward)
+}
+
+/// Handle the registered "commdlg_FindReplace" message sent by FindTextW /
+/// ReplaceTextW whenever the user clicks Find Next, Replace, Replace All, or
+/// closes the dialog.
+///
+/// # Safety
+/// `lparam` is a valid `*const FINDREPLACEW` provided by the OS.
+unsafe fn handle_findreplace_msg(hwnd: HWND, lparam: LPARAM, state: &mut WindowState) {
+    // SAFETY: the OS guarantees lparam is a *const FINDREPLACEW pointing to
+    // the same struct we passed to FindTextW / ReplaceTextW.
+    let fr = &*(lparam.0 as *const FINDREPLACEW);
+    let flags = fr.Flags.0;
+
+    if flags & FR_DIALOGTERM != 0 {
+        // Dialog is closing — clear the stored HWND.
+        state.hwnd_find_dlg = HWND::default();
+        return;
+    }
+
+    let find_bytes = search_bytes(state, pwstr_to_utf8(fr.lpstrFindWhat));
+    if find_bytes.is_empty() {
+        return;
+    }
+    remember_search(state, flags & (FR_REPLACE | FR_REPLACEALL) != 0);
+
+    let sci_flags = (if flags & FR_MATCHCASE != 0 {
+        SCFIND_MATCHCASE
+    } else {
+        0
+    }) | (if flags & FR_WHOLEWORD != 0 {
+        SCFIND_WHOLEWORD
+    } else {
+        0
+    });
+    let forward = flags & FR_DOWN != 0;
+
+    if flags & FR_FINDNEXT != 0 {
+        if !find_next_in_target(state, &find_bytes, sci_flags, forward) {
+            notify_not_found(hwnd, state);
+        }
+        return;
+    }
+
+    if flags & FR_REPLACE != 0 {
+        let repl_bytes = search_bytes(state, pwstr_to_utf8(fr.lpstrReplaceWith));
+        let found = handle_replace_once(
+            find_view(state),
+            &find_bytes,
+            &repl_bytes,
+            sci_flags,
+            forward,
+        );
+        if !found {
+            notify_not_found(hwnd, state);
+        }
+    } else if flags & FR_REPLACEALL != 0 {
+        let repl_bytes = search_bytes(state, pwstr_to_utf8(fr.lpstrReplaceWith));
+        start_replace_all(hwnd, state, &find_bytes, repl_bytes, sci_flags);
+    }
+}
+
+/// Start a Replace All over the whole of the active document on a worker
+/// thread.  The view stays read-only, with progress in the status bar, until
+/// `handle_replace_progress` applies the result; Esc or the status bar's
+/// Cancel button cancels.
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn start_replace_all(
+    hwnd: HWND,
+    state: &mut WindowState,
+    find: &[u8],
+    repl: Vec<u8>,
+    sci_flags: u32,
+) {
+    // One job at a time; read-only and monitored documents cannot be
+    // changed at all, and one still loading is not all there yet.
+    if state.replace.is_some()
+        || state.app.active_doc().read_only
+        || monitor_index(state, state.app.active_idx).is_some()
+        || is_loading(state, state.app.active_idx)
+    {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let needle = Needle::new(
+        find,
+        sci_flags & SCFIND_MATCHCASE != 0,
+        sci_flags & SCFIND_WHOLEWORD != 0,
+    );
+    // Panels are read-only: Replace All always edits the document.
+    let sci = &state.sci_views[state.app.active_idx];
+    let raw_hwnd = hwnd.0 as isize;
+    let notify = move || {
+        // SAFETY: PostMessageW may be called from any thread; if the window
+        // has been destroyed the post simply fails.
+        unsafe {
+            let _ = PostMessageW(
+                HWND(raw_hwnd as *mut _),
+                WM_REPLACE_PROGRESS,
+                WPARAM(0),
+                LPARAM(0),
+            );
+        }
+    };
+    match ReplaceJob::spawn(sci.get_text(), needle, repl, notify) {
+        Ok(job) => {
+            sci.set_read_only(true);
+            state.replace = Some(PendingReplace {
+                view: sci.hwnd(),
+                job,
+                eol: None,
+            });
+            update_status_bar(state);
+        }
+        Err(e) => show_error_dialog(&format!("Could not start Replace All:\n{e}")),
+    }
+}
+
+/// Start Format > Convert line endings for a document in Large File Mode on
+/// a worker thread, like [`start_replace_all`]: Scintilla's own conversion
+/// would block the UI for the whole document.
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn start_eol_conversion(hwnd: HWND, state: &mut WindowState, eol: EolMode) {
+    let idx = state.app.active_idx;
+    if state.replace.is_some()
+        || state.app.tabs[idx].read_only
+        || is_loading(state, idx)
+        || viewer_index(state, idx).is_some()
+        || monitor_index(state, idx).is_some()
+    {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let sci = &state.sci_views[idx];
+    let raw_hwnd = hwnd.0 as isize;
+    let notify = move || {
+        // SAFETY: as in `start_replace_all`.
+        unsafe {
+            let _ = PostMessageW(
+                HWND(raw_hwnd as *mut _),
+                WM_REPLACE_PROGRESS,
+                WPARAM(0),
+                LPARAM(0),
+            );
+        }
+    };
+    match ReplaceJob::spawn_eol(sci.get_text(), eol.bytes(), notify) {
+        Ok(job) => {
+            sci.set_read_only(true);
+            state.replace = Some(PendingReplace {
+                view: sci.hwnd(),
+                job,
+                eol: Some(eol),
+            });
+            update_status_bar(state);
+        }
+        Err(e) => show_error_dialog(&format!("Could not convert line endings:\n{e}")),
+    }
+}
+
+/// Handle `WM_REPLACE_PROGRESS`: refresh the progress display, or apply the
+/// finished result as a single undo action, or abandon the job on `cancel`.
+/// A finished line-ending conversion also sets the document's EOL mode.
+///
+/// # Safety
+/// Called only from the window procedure with a valid `state`.
+unsafe fn handle_replace_progress(hwnd: HWND, state: &mut WindowState, cancel: bool) {
+    let Some(pending) = &state.replace else {
+        return;
+    };
+    let result = if cancel {
+        None
+    } else {
+        match pending.job.try_result() {
+            Some(done) => Some(done),
+            None => {
+                update_status_bar(state);
+                return;
+            }
+        }
+    };
+    let (view, eol) = (pending.view, pending.eol);
+    state.replace = None;
+    let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == view) else {
+        return;
+    };
+    let sci = &state.sci_views[idx];
+    sci.set_read_only(state.app.tabs[idx].read_only);
+    update_status_bar(state);
+    let Some(done) = result else {
+        return;
+    };
+    if done.count > 0 {
+        let caret = sci.caret_pos();
+        sci.begin_undo_action();
+        sci.set_target(0, sci.doc_len());
+        sci.replace_target(&done.text);
+        sci.end_undo_action();
+        sci.set_caret_pos(caret);
+    }
+    if let Some(eol) = eol {
+        sci.set_eol_mode(eol);
+        state.app.tabs[idx].eol = eol;
+        update_status_bar(state);
+        return;
+    }
+    let n = done.count;
+    let msg = format!("{n} replacement{} made.", if n == 1 { "" } else { "s" });
+    let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+    let _ = MessageBoxW(hwnd, PCWSTR(wide.as_ptr()), w!("Rivet"), MB_OK);
+}
+
+/// Replace every match of the last search with the last replacement in every
+/// tab that can be edited, then report the count per document.  Each tab
+/// gets one undo action; its save-point notification marks it dirty.
+///
+/// Runs on the UI thread: unlike a single Replace All, the work is spread
+/// over documents that are mostly small.
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn handle_replace_all_open(hwnd: HWND, state: &mut WindowState) {
+    let Some((find_bytes, sci_flags)) = last_search(state) else {
+        return;
+    };
+    remember_search(state, true);
+    let len = state.replace_buf.iter().position(|&c| c == 0).unwrap_or(0);
+    let repl = search_bytes(
+        state,
+        String::from_utf16_lossy(&state.replace_buf[..len]).into_bytes(),
+    );
+    let needle = Needle::new(
+        &find_bytes,
+        sci_flags & SCFIND_MATCHCASE != 0,
+        sci_flags & SCFIND_WHOLEWORD != 0,
+    );
+
+    let mut counts = Vec::new();
+    let mut skipped = Vec::new();
+    for idx in 0..state.app.tab_count() {
+        let view = state.sci_views[idx].hwnd();
+        let name = state.app.tabs[idx].display_name();
+        if state.app.tabs[idx].read_only
+            || is_loading(state, idx)
+            || viewer_index(state, idx).is_some()
+            || monitor_index(state, idx).is_some()
+            || state.replace.as_ref().is_some_and(|r| r.view == view)
+        {
+            skipped.push(name);
+            continue;
+        }
+        let sci = &state.sci_views[idx];
+        let done = crate::search::replace::replace_now(&sci.get_text(), &needle, &repl);
+        if done.count > 0 {
+            let caret = sci.caret_pos();
+            sci.begin_undo_action();
+            sci.set_target(0, sci.doc_len());
+            sci.replace_target(&done.text);
+            sci.end_undo_action();
+            sci.set_caret_pos(caret.min(sci.doc_len()));
+        }
+        counts.push((name, done.count));
+    }
+
+    let msg = crate::search::replace::open_documents_summary(&counts, &skipped);
+    let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+    let _ = MessageBoxW(
+        hwnd,
+        PCWSTR(wide.as_ptr()),
+        w!("Replace in Opened Documents"),
+        MB_OK,
+    );
+}
+
+/// Replace the current selection (if it matches `find`) then move to the next
+/// occurrence.  Returns `false` if there is no next occurrence.
+///
+/// # Safety
+/// `sci` must be a valid `ScintillaView` whose HWND is alive.
+unsafe fn handle_replace_once(
+    sci: &ScintillaView,
+    find: &[u8],
+    repl: &[u8],
+    flags: u32,
+    forward: bool,
+) -> bool {
+    let sel_start = sci.selection_start();
+    let sel_end = sci.selection_end();
+
+    // If the current selection exactly matches the search term, replace it.
+    if sel_end > sel_start {
+        sci.set_target(sel_start, sel_end);
+        if sci.search_in_target(find, flags).is_some() {
+            sci.replace_target(repl);
+        }
+    }
+
+    // Advance to the next match.
+    sci.find_next(find, flags, forward)
+}
+
+/// Handle F3 / Shift+F3: repeat the last search from the Find dialog.
+///
+/// If no previous search text exists in the buffer the Find dialog is opened.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_find_next(hwnd: HWND, state: &mut WindowState, forward: bool) {
+    // If the find buffer is empty (no previous search), open the Find dialog.
+    let Some((find_bytes, sci_flags)) = last_search(state) else {
+        handle_find_open(hwnd, state);
+        return;
+    };
+
+    if !find_next_in_target(state, &find_bytes, sci_flags, forward) {
+        notify_not_found(hwnd, state);
+    }
+}
+
+/// The last Find dialog query as UTF-8 with its Scintilla flags, or `None`
+/// if nothing has been searched for yet.
+fn last_search(state: &WindowState) -> Option<(Vec<u8>, u32)> {
+    if state.find_buf[0] == 0 {
+        return None;
+    }
+
+    // Derive Scintilla flags from the last dialog flag state.
+    let fr_flags = state.findreplace.Flags.0;
+    let sci_flags = (if fr_flags & FR_MATCHCASE != 0 {
+        SCFIND_MATCHCASE
+    } else {
+        0
+    }) | (if fr_flags & FR_WHOLEWORD != 0 {
+        SCFIND_WHOLEWORD
+    } else {
+        0
+    });
+
+    // Decode the UTF-16 find buffer to UTF-8.
+    let len = state.find_buf.iter().position(|&c| c == 0).unwrap_or(0);
+    let s = String::from_utf16_lossy(&state.find_buf[..len]);
+    Some((search_bytes(state, s.into_bytes()), sci_flags))
+}
+
+/// A find or replace string as typed, with Extended mode's escapes expanded
+/// when it is on.
+fn search_bytes(state: &WindowState, typed: Vec<u8>) -> Vec<u8> {
+    if state.settings.search_extended {
+        crate::search::unescape(&typed)
+    } else {
+        typed
+    }
+}
+
+/// Handle Search > Extended Mode.
+fn handle_extended_toggle(state: &mut WindowState) {
+    state.settings.search_extended = !state.settings.search_extended;
+    let _ = crate::config::save(&state.settings);
+    update_extended_checkmark(state);
+}
+
+/// Sync the Search > Extended Mode checkmark with the setting.
+fn update_extended_checkmark(state: &WindowState) {
+    let checked = state.settings.search_extended;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_SEARCH_EXTENDED as u32, flag);
+    }
+}
+
+/// Handle Search > Select All Matches (Ctrl+Shift+L) and Add All Matches to
+/// Selection (Ctrl+Enter): select every match of the last search as a
+/// multiple selection, typed into together.  `keep` adds the matches to the
+/// current selections instead of replacing them.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_select_matches(hwnd: HWND, state: &mut WindowState, keep: bool) {
+    let Some((find_bytes, sci_flags)) = last_search(state) else {
+        handle_find_open(hwnd, state);
+        return;
+    };
+    // The viewer holds only a window of the file; a selection there means
+    // nothing outside it.
+    if viewer_index(state, state.app.active_idx).is_some() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let idx = state.app.active_idx;
+    if state.sci_views[idx].select_matches(&find_bytes, sci_flags, keep) == 0 {
+        notify_not_found(hwnd, state);
+    }
+}
+
+// ── Find All ──────────────────────────────────────────────────────────────────
+
+/// Most hits listed by Find All.
+const FIND_ALL_LIMIT: usize = 10_000;
+
+/// List every match of the last search in the active document in the
+/// results panel (Search > Find All, or the Find dialog's Find All button).
+///
+/// # Safety
+/// Called only from the UI thread with a valid `state`.
+unsafe fn handle_find_all(hwnd: HWND, state: &mut WindowState) {
+    let Some((find_bytes, sci_flags)) = last_search(state) else {
+        return;
+    };
+    remember_search(state, false);
+    let idx = state.app.active_idx;
+    // A viewer holds only a window of the file, a load only part of it.
+    if viewer_index(state, idx).is_some() || is_loading(state, idx) {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    let needle = Needle::new(
+        &find_bytes,
+        sci_flags & SCFIND_MATCHCASE != 0,
+        sci_flags & SCFIND_WHOLEWORD != 0,
+    );
+    let text = state.sci_views[idx].get_text();
+    let mut hits = crate::search::find_all::find_all(&text, &needle, FIND_ALL_LIMIT + 1);
+    let truncated = hits.len() > FIND_ALL_LIMIT;
+    hits.truncate(FIND_ALL_LIMIT);
+    let term = String::from_utf8_lossy(&find_bytes);
+    let listing = crate::search::find_all::listing(&text, &term, &hits, truncated);
+
+    if state.results.is_none() {
+        let Some(view) = new_scintilla_view(hwnd, state) else {
+            return;
+        };
+        view.disable_change_history();
+        view.use_popup(false);
+        theme_results_panel(&view, state.dark_mode, state.system_colours.as_ref());
+        state.results = Some(ResultsPanel {
+            view,
+            visible: false,
+            source: HWND::default(),
+            label: String::new(),
+            hits: Vec::new(),
+            match_len: 0,
+            row: 0,
+        });
+    }
+    let doc = state.app.active_doc();
+    let label = doc
+        .path
+        .as_deref()
+        .map_or_else(|| doc.display_name(), |p| p.to_string_lossy().into_owned());
+    let Some(panel) = state.results.as_mut() else {
+        return;
+    };
+    panel.label = label;
+    panel.view.set_read_only(false);
+    panel.view.set_text(&listing);
+    panel.view.set_save_point();
+    panel.view.set_read_only(true);
+    panel.source = state.sci_views[idx].hwnd();
+    panel.hits = hits;
+    panel.match_len = needle.len();
+    panel.row = 0;
+    if !panel.visible {
+        panel.visible = true;
+        panel.view.show(true);
+        let mut rc = RECT::default();
+        let _ = GetClientRect(hwnd, &mut rc);
+        layout_children(state, rc.right, rc.bottom);
+    }
+    if state.results.as_ref().is_some_and(|p| p.hits.is_empty()) {
+        notify_not_found(hwnd, state);
+    }
+}
+
+/// Plain-text colours for the results panel (and the outline panel), with
+/// the current row marked in the occurrence-highlight colour.
+fn theme_results_panel(
+    view: &ScintillaView,
+    dark: bool,
+    system: Option<&crate::theme::SystemColours>,
+) {
+    apply_control_theme(view.hwnd(), dark && system.is_none());
+    let Some(colours) = system else {
+        crate::theme::apply_theme(view, crate::languages::Language::PlainText, dark);
+        view.set_highlights(None, Some(crate::theme::occurrence_highlight(dark)));
+        view.set_contrast_colours(None, None);
+        return;
+    };
+    // The current row is selected rather than shaded, in the system colours.
+    crate::theme::apply_system_theme(view, colours);
+    view.set_highlights(Some(colours.highlight), None);
+    view.set_contrast_colours(Some(colours.highlight_text), Some(colours.window_text));
+}
+
+/// The caret moved in the results panel: if the user put it on a new hit
+/// row, select that match in its tab.  Focus stays in the panel so the
+/// arrow keys step through the hits.
+///
+/// # Safety
+/// Called only from `WM_NOTIFY` with a valid `state`.
+unsafe fn handle_results_click(hwnd: HWND, state: &mut WindowState) {
+    let Some(panel) = state.results.as_mut() else {
+        return;
+    };
+    // Setting the text moves the caret too; only the user's moves count.
+    if GetFocus() != panel.view.hwnd() {
+        return;
+    }
+    let row = panel.view.line_from_position(panel.view.caret_pos());
+    if row == panel.row {
+        return;
+    }
+    panel.row = row;
+    let Some(hit) = row.checked_sub(1).and_then(|i| panel.hits.get(i)).copied() else {
+        return;
+    };
+    let (source, len) = (panel.source, panel.match_len);
+    let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == source) else {
+        // The tab searched has been closed.
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    };
+    activate_tab(hwnd, state, idx);
+    let view = &state.sci_views[idx];
+    view.set_sel(hit.pos, hit.pos + len);
+    view.scroll_caret();
+}
+
+/// Right-click in the results panel at screen point `pt` ((-1, -1) from the
+/// keyboard: at the caret).  Copies the hits on the selected rows, or all
+/// of them, in `grep -n` form (`path:line: text`), or writes them all to a
+/// new tab or a file.
+///
+/// # Safety
+/// Called only from `WM_CONTEXTMENU` with a valid `state`.
+unsafe fn handle_results_menu(hwnd: HWND, state: &mut WindowState, mut pt: POINT) {
+    const ID_COPY_SELECTED: usize = 1;
+    const ID_COPY_ALL: usize = 2;
+    const ID_NEW_TAB: usize = 3;
+    const ID_SAVE: usize = 4;
+
+    let Some(panel) = state.results.as_ref() else {
+        return;
+    };
+    let view = &panel.view;
+    // Rows 1.. are hits; a selection ending at the start of a row leaves it out.
+    let (start, end) = (view.selection_start(), view.selection_end());
+    let first = view.line_from_position(start);
+    let mut last = view.line_from_position(end);
+    if end > start && view.position_from_line(last) == end {
+        last -= 1;
+    }
+    let selected = first.max(1) - 1..last.min(panel.hits.len());
+
+    let Ok(menu) = CreatePopupMenu() else {
+        return;
+    };
+    let some = |on: bool| if on { MF_STRING } else { MF_GRAYED };
+    let any = !panel.hits.is_empty();
+    let _ = AppendMenuW(
+        menu,
+        some(!selected.is_empty()),
+        ID_COPY_SELECTED,
+        w!("&Copy Selected Results"),
+    );
+    let _ = AppendMenuW(menu, some(any), ID_COPY_ALL, w!("Copy &All Results"));
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+    let _ = AppendMenuW(menu, some(any), ID_NEW_TAB, w!("Open Results in &New Tab"));
+    let _ = AppendMenuW(menu, some(any), ID_SAVE, w!("&Save Results As\u{2026}"));
+    if pt.x == -1 && pt.y == -1 {
+        let (x, y) = view.caret_point();
+        pt = POINT { x, y };
+        let _ = ClientToScreen(view.hwnd(), &mut pt);
+    }
+    let cmd = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON,
+        pt.x,
+        pt.y,
+        0,
+        hwnd,
+        None,
+    );
+    let _ = DestroyMenu(menu);
+    let cmd = cmd.0 as usize;
+    if cmd == 0 {
+        return;
+    }
+
+    let Some(source) = state.sci_views.iter().find(|v| v.hwnd() == panel.source) else {
+        // The tab searched has been closed; its lines are gone.
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    };
+    let hits = if cmd == ID_COPY_SELECTED {
+        &panel.hits[selected]
+    } else {
+        &panel.hits[..]
+    };
+    let text = source.get_text();
+    let grep = |eol: &[u8]| crate::search::find_all::grep_lines(&panel.label, &text, hits, eol);
+    match cmd {
+        ID_COPY_SELECTED | ID_COPY_ALL => {
+            let out = String::from_utf8_lossy(&grep(b"\r\n")).into_owned();
+            if let Err(e) = crate::platform::win32::clipboard::set_text(hwnd, &out) {
+                show_error_dialog(&format!("Could not copy to the clipboard:\n{e}"));
+            }
+        }
+        ID_SAVE => {
+            let Some(path) = dialogs::show_save_dialog(hwnd, "results.txt") else {
+                return;
+            };
+            if let Err(e) = std::fs::write(&path, grep(b"\r\n")) {
+                show_error_dialog(&format!("Could not save the results:\n{e}"));
+            }
+        }
+        ID_NEW_TAB => {
+            let out = grep(b"\n");
+            let before = state.app.tab_count();
+            open_untitled_tab(hwnd, state);
+            if state.app.tab_count() == before {
+                return;
+            }
+            let idx = state.app.active_idx;
+            state.app.active_doc_mut().title = Some("Find Results".to_owned());
+            let view = &state.sci_views[idx];
+            view.set_eol_mode(EolMode::Lf);
+            state.app.active_doc_mut().eol = EolMode::Lf;
+            view.set_text(&out);
+            view.set_save_point();
+            sync_tab_label(state, idx);
+            update_window_title(hwnd, &state.app);
+            update_status_bar(state);
+        }
+        _ => {}
+    }
+}
+
+// ── Pane focus ────────────────────────────────────────────────────────────────
+
+/// The panes F6 steps through, in screen order: the folder panel, the tab
+/// strip, the editor, the outline panel and the Find All results, each
+/// while shown.
+fn focus_panes(state: &WindowState) -> Vec<HWND> {
+    let mut panes = Vec::new();
+    panes.extend(state.folder.as_ref().map(|f| f.hwnd()));
+    panes.push(state.hwnd_tab);
+    panes.push(state.sci_views[state.app.active_idx].hwnd());
+    panes.extend(state.outline.as_ref().map(|p| p.view.hwnd()));
+    panes.extend(
+        state
+            .results
+            .as_ref()
+            .filter(|p| p.visible)
+            .map(|p| p.view.hwnd()),
+    );
+    panes
+}
+
+/// Handle View > Next / Previous Pane: move the keyboard focus to the pane
+/// after (or before) the one holding it, wrapping around.  From anywhere
+/// else, such as a rename box in the folder tree, it goes to the editor.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_focus_pane(state: &WindowState, forward: bool) {
+    let panes = focus_panes(state);
+    let editor = state.sci_views[state.app.active_idx].hwnd();
+    let focus = GetFocus();
+    let next = match panes.iter().position(|&p| p == focus) {
+        Some(i) if forward => panes[(i + 1) % panes.len()],
+        Some(i) => panes[(i + panes.len() - 1) % panes.len()],
+        None => editor,
+    };
+    let _ = SetFocus(next);
+}
+
+/// Handle Search > Close Results: hide the panel and return to the editor.
+///
+/// # Safety
+/// Called only from WM_COMMAND with a valid `state`.
+unsafe fn handle_close_results(hwnd: HWND, state: &mut WindowState) {
+    let Some(panel) = state.results.as_mut().filter(|p| p.visible) else {
+        return;
+    };
+    panel.visible = false;
+    panel.view.show(false);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+    let _ = SetFocus(state.sci_views[state.app.active_idx].hwnd());
+}
+
+// ── Folder panel ──────────────────────────────────────────────────────────────
+
+/// Handle File > Open Folder…: pick a folder and list it in the folder
+/// panel, showing the panel if it was hidden.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_open_folder(hwnd: HWND, state: &mut WindowState) {
+    let Some(root) = dialogs::show_folder_dialog(hwnd) else {
+        return;
+    };
+    state.settings.folder_panel_root = Some(root);
+    state.settings.show_folder_panel = true;
+    let _ = crate::config::save(&state.settings);
+    apply_folder_panel(hwnd, state);
+}
+
+/// Handle View > Toolbar.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_toolbar_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.toolbar.visible = !state.settings.toolbar.visible;
+    let _ = crate::config::save(&state.settings);
+    apply_toolbar(hwnd, state);
+}
+
+/// Build the toolbar from the settings (or remove it), then sync the View
+/// menu checkmark and re-lay out the window.  Also called after a DPI
+/// change, which needs the other icon size.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn apply_toolbar(hwnd: HWND, state: &mut WindowState) {
+    state.toolbar = None;
+    let visible = state.settings.toolbar.visible;
+    if visible {
+        let buttons: Vec<Option<ToolbarButton>> = state
+            .settings
+            .toolbar
+            .buttons
+            .iter()
+            .filter_map(|name| {
+                if name == crate::ui::toolbar::SEPARATOR {
+                    return Some(None);
+                }
+                let command = COMMANDS.iter().find(|c| c.name == name)?;
+                Some(Some(ToolbarButton {
+                    id: command.id,
+                    icon: crate::ui::toolbar::icon(name),
+                    text: crate::ui::toolbar::button_text(command.label).to_owned(),
+                    check: command.id == IDM_VIEW_WORD_WRAP,
+                }))
+            })
+            .collect();
+        state.toolbar = Toolbar::create(hwnd, &buttons, state.ui_font, state.dpi);
+        update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+        update_edit_state(state);
+    }
+    let flag = (MF_BYCOMMAND | if visible { MF_CHECKED } else { MF_UNCHECKED }).0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_TOOLBAR as u32, flag);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Colours for custom-drawing the toolbar: the editor's in dark mode, none
+/// (the system look) in the light theme and under High Contrast.
+fn toolbar_colours(state: &WindowState) -> Option<BarColours> {
+    dark_chrome(state).then(|| BarColours {
+        background: crate::theme::background(true),
+        text: crate::theme::foreground(true),
+        hot: crate::theme::occurrence_highlight(true),
+    })
+}
+
+/// Handle View > Folder Panel: show or hide the panel.  With no folder
+/// chosen yet (or the last one gone), showing it asks for one first.
 ///
 /// # Safety
 /// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_replace_open(hwnd: HWND, state: &mut WindowState) {
-    if state.hwnd_find_dlg != HWND::default() {
-        let _ = SetForegroundWindow(state.hwnd_find_dlg);
+unsafe fn handle_folder_panel_toggle(hwnd: HWND, state: &mut WindowState) {
+    let has_root = matches!(&state.settings.folder_panel_root, Some(p) if p.is_dir());
+    if state.folder.is_none() && !has_root {
+        handle_open_folder(hwnd, state);
         return;
     }
-    state.findreplace.hwndOwner = hwnd;
-    state.findreplace.Flags = FINDREPLACE_FLAGS(state.findreplace.Flags.0 | FR_DOWN);
-    // SAFETY: findreplace is stable in heap memory; hwndOwner is valid.
-    state.hwnd_find_dlg = ReplaceTextW(&mut state.findreplace);
+    state.settings.show_folder_panel = state.folder.is_none();
+    let _ = crate::config::save(&state.settings);
+    apply_folder_panel(hwnd, state);
+}
+
+/// Create, re-root or remove the folder panel to match the settings, then
+/// sync the View menu checkmark and re-lay out the window.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn apply_folder_panel(hwnd: HWND, state: &mut WindowState) {
+    let root = state
+        .settings
+        .folder_panel_root
+        .clone()
+        .filter(|p| state.settings.show_folder_panel && p.is_dir());
+    match (root, state.folder.as_mut()) {
+        (None, Some(_)) => {
+            state.folder = None;
+            let _ = SetFocus(state.sci_views[state.app.active_idx].hwnd());
+        }
+        (None, None) => {}
+        (Some(root), Some(panel)) => {
+            if panel.root() != Some(root.as_path()) {
+                panel.set_root(&root);
+            }
+        }
+        (Some(root), None) => {
+            state.folder = FolderPanel::create(hwnd, &root);
+            if let Some(panel) = state.folder.as_ref() {
+                theme_folder_panel(panel, state.dark_mode, state.system_colours.as_ref());
+            }
+        }
+    }
+    let flag = (MF_BYCOMMAND
+        | if state.folder.is_some() {
+            MF_CHECKED
+        } else {
+            MF_UNCHECKED
+        })
+    .0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_FOLDER_PANEL as u32, flag);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Give the folder panel the editor's colours.
+fn theme_folder_panel(
+    panel: &FolderPanel,
+    dark: bool,
+    system: Option<&crate::theme::SystemColours>,
+) {
+    apply_control_theme(panel.hwnd(), dark && system.is_none());
+    match system {
+        Some(colours) => panel.set_colours(colours.window, colours.window_text),
+        None => panel.set_colours(
+            crate::theme::background(dark),
+            crate::theme::foreground(dark),
+        ),
+    }
+}
+
+/// Carry out what the user picked in the folder panel.
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn handle_folder_command(hwnd: HWND, state: &mut WindowState, cmd: PanelCommand) {
+    match cmd {
+        PanelCommand::Open(path) => open_path(hwnd, state, path),
+        PanelCommand::NewFile(dir) => folder_new_file(hwnd, state, &dir),
+        PanelCommand::Rename(path) => folder_rename(hwnd, state, &path),
+        PanelCommand::Delete(path) => {
+            if crate::platform::win32::folder_panel::recycle(hwnd, &path) {
+                if let (Some(panel), Some(dir)) = (state.folder.as_mut(), path.parent()) {
+                    panel.refresh(dir);
+                }
+            }
+        }
+        PanelCommand::Reveal(path) => {
+            let command = format!(r#"explorer.exe /select,"{}""#, path.display());
+            if let Err(e) = crate::platform::win32::process::spawn_detached(&command, None) {
+                show_error_dialog(&format!("Could not start Explorer:\n{e}"));
+            }
+        }
+    }
+}
+
+/// Folder panel > New File…: ask for a name, create an empty file in `dir`
+/// and open it.
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn folder_new_file(hwnd: HWND, state: &mut WindowState, dir: &std::path::Path) {
+    let Some(name) = crate::platform::win32::dlg::show_prompt_dialog(
+        hwnd,
+        "New File",
+        &format!("New file in {}:", dir.display()),
+        &crate::ui::folder_tree::unused_name(dir),
+        true,
+    ) else {
+        return;
+    };
+    if !crate::ui::folder_tree::valid_name(&name) {
+        show_error_dialog(&format!("\"{name}\" is not a valid file name."));
+        return;
+    }
+    let path = dir.join(&name);
+    let created = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path);
+    if let Err(e) = created {
+        show_error_dialog(&format!("Could not create \"{name}\":\n{e}"));
+        return;
+    }
+    if let Some(panel) = state.folder.as_mut() {
+        panel.refresh(dir);
+    }
+    open_path(hwnd, state, path);
+}
+
+/// Folder panel > Rename…: ask for a new name and rename `path`.  Open
+/// tabs follow the file, or every file under a renamed folder.
+///
+/// # Safety
+/// Called on the UI thread with a valid `state`.
+unsafe fn folder_rename(hwnd: HWND, state: &mut WindowState, path: &std::path::Path) {
+    let Some(old_name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+        return;
+    };
+    let Some(name) = crate::platform::win32::dlg::show_prompt_dialog(
+        hwnd,
+        "Rename",
+        &format!("New name for \"{old_name}\":"),
+        &old_name,
+        true,
+    ) else {
+        return;
+    };
+    if name == old_name {
+        return;
+    }
+    if !crate::ui::folder_tree::valid_name(&name) {
+        show_error_dialog(&format!("\"{name}\" is not a valid file name."));
+        return;
+    }
+    let new_path = path.with_file_name(&name);
+    // Changing only the case renames the same file.
+    if new_path.exists() && !name.eq_ignore_ascii_case(&old_name) {
+        show_error_dialog(&format!("\"{name}\" already exists."));
+        return;
+    }
+    if let Err(e) = std::fs::rename(path, &new_path) {
+        show_error_dialog(&format!("Could not rename \"{old_name}\":\n{e}"));
+        return;
+    }
+    for idx in 0..state.app.tab_count() {
+        let doc = &mut state.app.tabs[idx];
+        let Some(rest) = doc.path.as_deref().and_then(|p| p.strip_prefix(path).ok()) else {
+            continue;
+        };
+        doc.path = Some(if rest.as_os_str().is_empty() {
+            new_path.clone()
+        } else {
+            new_path.join(rest)
+        });
+        sync_tab_label(state, idx);
+    }
+    update_window_title(hwnd, &state.app);
+    if let (Some(panel), Some(dir)) = (state.folder.as_mut(), path.parent()) {
+        panel.refresh(dir);
+    }
+}
+
+// ── Outline panel ─────────────────────────────────────────────────────────────
+
+/// Handle View > Outline Panel: show or hide the panel.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_outline_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.show_outline = state.outline.is_none();
+    let _ = crate::config::save(&state.settings);
+    apply_outline_panel(hwnd, state);
+}
+
+/// Create or remove the outline panel to match the settings, then sync the
+/// View menu checkmark and re-lay out the window.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn apply_outline_panel(hwnd: HWND, state: &mut WindowState) {
+    match (state.settings.show_outline, state.outline.is_some()) {
+        (false, true) => {
+            if let Some(panel) = state.outline.take() {
+                panel.view.destroy();
+            }
+            let _ = SetFocus(state.sci_views[state.app.active_idx].hwnd());
+        }
+        (true, false) => {
+            if let Some(view) = new_scintilla_view(hwnd, state) {
+                view.disable_change_history();
+                theme_results_panel(&view, state.dark_mode, state.system_colours.as_ref());
+                view.show(true);
+                state.outline = Some(OutlinePanel {
+                    view,
+                    source: HWND::default(),
+                    lines: Vec::new(),
+                    row: usize::MAX,
+                });
+                refresh_outline(state);
+            }
+        }
+        _ => {}
+    }
+    let flag = (MF_BYCOMMAND
+        | if state.outline.is_some() {
+            MF_CHECKED
+        } else {
+            MF_UNCHECKED
+        })
+    .0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_OUTLINE as u32, flag);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+}
+
+/// Re-list the active document in the outline panel, if it is shown.
+/// Called when the panel opens, the active tab changes, and on save.
+fn refresh_outline(state: &mut WindowState) {
+    if state.outline.is_none() {
+        return;
+    }
+    let idx = state.app.active_idx;
+    // A viewer holds only a window of the file, a load only part of it.
+    let items = if viewer_index(state, idx).is_some() || is_loading(state, idx) {
+        Vec::new()
+    } else {
+        let text = state.sci_views[idx].get_text();
+        crate::outline::outline(state.app.tabs[idx].language, &text)
+    };
+    let listing = if items.is_empty() {
+        "No outline for this document.".to_owned()
+    } else {
+        items
+            .iter()
+            .map(|item| format!("{}{}", "    ".repeat(item.depth), item.label))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let source = state.sci_views[idx].hwnd();
+    let Some(panel) = state.outline.as_mut() else {
+        return;
+    };
+    panel.view.set_read_only(false);
+    panel.view.set_text(listing.as_bytes());
+    panel.view.set_save_point();
+    panel.view.set_read_only(true);
+    panel.source = source;
+    panel.lines = items.iter().map(|item| item.line).collect();
+    panel.row = usize::MAX;
+}
+
+/// The caret moved in the outline panel: if the user put it on a new row,
+/// move the editor to that line.  Focus stays in the panel so the arrow
+/// keys step through the entries.
+///
+/// # Safety
+/// Called only from `WM_NOTIFY` with a valid `state`.
+unsafe fn handle_outline_click(hwnd: HWND, state: &mut WindowState) {
+    let Some(panel) = state.outline.as_mut() else {
+        return;
+    };
+    // Setting the text moves the caret too; only the user's moves count.
+    if GetFocus() != panel.view.hwnd() {
+        return;
+    }
+    let row = panel.view.line_from_position(panel.view.caret_pos());
+    if row == panel.row {
+        return;
+    }
+    panel.row = row;
+    let Some(line) = panel.lines.get(row).copied() else {
+        return;
+    };
+    let source = panel.source;
+    let Some(idx) = state.sci_views.iter().position(|v| v.hwnd() == source) else {
+        return;
+    };
+    activate_tab(hwnd, state, idx);
+    let view = &state.sci_views[idx];
+    let pos = view.position_from_line(line);
+    view.set_sel(pos, pos);
+    view.scroll_caret();
+}
+
+// ── Document map ──────────────────────────────────────────────────────────────
+
+/// Handle View > Document Map: show or hide the map.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_document_map_toggle(hwnd: HWND, state: &mut WindowState) {
+    state.settings.show_document_map = state.map.is_none();
+    let _ = crate::config::save(&state.settings);
+    apply_document_map(hwnd, state);
+}
+
+/// Create or remove the document map to match the settings, then sync the
+/// View menu checkmark and re-lay out the window.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn apply_document_map(hwnd: HWND, state: &mut WindowState) {
+    match (state.settings.show_document_map, state.map.is_some()) {
+        (false, true) => {
+            if let Some(map) = state.map.take() {
+                map.view.destroy();
+            }
+        }
+        (true, false) => {
+            if let Some(view) = new_scintilla_view(hwnd, state) {
+                document_map::install(view.hwnd());
+                view.show(true);
+                state.map = Some(DocumentMap {
+                    view,
+                    source: HWND::default(),
+                    language: crate::languages::Language::PlainText,
+                });
+            }
+        }
+        _ => {}
+    }
+    let flag = (MF_BYCOMMAND
+        | if state.map.is_some() {
+            MF_CHECKED
+        } else {
+            MF_UNCHECKED
+        })
+    .0;
+    let _ = CheckMenuItem(state.menu_bar, IDM_VIEW_DOCUMENT_MAP as u32, flag);
+    let mut rc = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rc);
+    layout_children(state, rc.right, rc.bottom);
+    sync_document_map(state);
+}
+
+/// Colours for the map: the document's syntax theme, with the editor's
+/// visible lines marked in the occurrence-highlight colour.
+fn theme_document_map(map: &DocumentMap, dark: bool, system: Option<&crate::theme::SystemColours>) {
+    apply_control_theme(map.view.hwnd(), dark && system.is_none());
+    match system {
+        Some(colours) => {
+            crate::theme::apply_system_theme(&map.view, colours);
+            map.view.make_document_map(colours.highlight);
+        }
+        None => {
+            crate::theme::apply_theme(&map.view, map.language, dark);
+            map.view
+                .make_document_map(crate::theme::occurrence_highlight(dark));
+        }
+    }
+}
+
+/// Point the map at the active tab's document and mark the editor's visible
+/// lines, scrolling the map so the marked block sits as far down the map as
+/// the editor is down the document.  Called whenever the editor scrolls,
+/// changes, or another tab becomes active.
+fn sync_document_map(state: &mut WindowState) {
+    let dark = state.dark_mode;
+    let system = state.system_colours;
+    let idx = state.app.active_idx;
+    let language = state.app.tabs[idx].language;
+    let editor = &state.sci_views[idx];
+    let Some(map) = state.map.as_mut() else {
+        return;
+    };
+    if map.source != editor.hwnd() || map.language != language {
+        if map.source != editor.hwnd() {
+            map.view.share_document(editor.document());
+            map.source = editor.hwnd();
+        }
+        map.language = language;
+        theme_document_map(map, dark, system.as_ref());
+    }
+
+    let first_visible = editor.first_visible_line();
+    let rows = editor.lines_on_screen().max(1);
+    let first = editor.doc_line_from_visible(first_visible);
+    let last = editor.doc_line_from_visible(first_visible + rows - 1);
+    let total = map.view.line_count();
+    let end = if last + 1 < total {
+        map.view.position_from_line(last + 1)
+    } else {
+        map.view.doc_len()
+    };
+    // Selecting scrolls the map to the caret; set the real scroll after.
+    map.view.set_sel(map.view.position_from_line(first), end);
+    let map_rows = map.view.lines_on_screen();
+    let shown = last + 1 - first;
+    let top = match total.checked_sub(map_rows) {
+        Some(spare) if spare > 0 && total > shown => first * spare / (total - shown),
+        _ => 0,
+    };
+    map.view.set_first_visible_line(top);
+}
+
+/// A click or drag in the map at client y-coordinate `y`: centre the editor
+/// on the line there.
+fn handle_document_map_scroll(state: &mut WindowState, y: i32) {
+    let Some(map) = state.map.as_ref() else {
+        return;
+    };
+    let line = map.view.line_at_y(y);
+    let editor = &state.sci_views[state.app.active_idx];
+    let rows = editor.lines_on_screen();
+    editor.set_first_visible_line(editor.visible_from_doc_line(line).saturating_sub(rows / 2));
+    sync_document_map(state);
+}
+
+// ── Auto-close brackets ───────────────────────────────────────────────────────
+
+/// Handle Edit > Auto-Close Brackets.
+fn handle_auto_close_toggle(state: &mut WindowState) {
+    state.settings.auto_close_brackets = !state.settings.auto_close_brackets;
+    let _ = crate::config::save(&state.settings);
+    update_auto_close_checkmark(state);
+}
+
+/// Sync the Edit > Auto-Close Brackets checkmark with the setting.
+fn update_auto_close_checkmark(state: &WindowState) {
+    let checked = state.settings.auto_close_brackets;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_EDIT_AUTO_CLOSE as u32, flag);
+    }
+}
+
+/// After `typed` was inserted in the active view, add the matching closer or
+/// step over an auto-inserted one, using the document language's pairs.
+fn handle_auto_close(state: &WindowState, typed: u8) {
+    use crate::editor::autoclose::{action, Action};
+
+    let idx = state.app.active_idx;
+    let pairs = crate::languages::auto_close_pairs(state.app.tabs[idx].language);
+    if pairs.is_empty() {
+        return;
+    }
+    let sci = &state.sci_views[idx];
+    let caret = sci.caret_pos();
+    let prev = caret.checked_sub(2).and_then(|p| sci.byte_at(p));
+    let next = sci.byte_at(caret);
+    let next_auto = next.is_some() && sci.is_auto_closer(caret);
+    match action(pairs, typed, prev, next, next_auto) {
+        Action::Close(closer) => sci.insert_auto_closer(closer),
+        Action::Overtype => sci.delete_range(caret, 1),
+        Action::Nothing => {}
+    }
+}
+
+// ── Comment / uncomment ───────────────────────────────────────────────────────
+
+/// Handle Edit > Toggle Line Comment (`block == false`) and Toggle Block
+/// Comment, using the active document's language tokens.
+///
+/// Line comments cover every line the selection touches; a language with
+/// only block comments gets the lines wrapped in one block instead.  Block
+/// comments wrap the selection; a language with only line comments falls back
+/// to line comments.  Beeps for read-only documents, languages without
+/// comments, and a block comment with nothing selected.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn handle_toggle_comment(state: &WindowState, block: bool) {
+    use crate::editor::comment::{toggle_block_comment, toggle_line_comment};
+
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    let beep = || {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+    };
+    if doc.read_only || viewer_index(state, idx).is_some() {
+        beep();
+        return;
+    }
+    let tokens = crate::languages::comment_tokens(doc.language);
+    let (sel_start, sel_end) = (sci.selection_start(), sci.selection_end());
+    let use_block = match (block, tokens.line, tokens.block) {
+        (_, None, None) => {
+            beep();
+            return;
+        }
+        (true, _, Some(_)) if sel_start == sel_end => {
+            beep();
+            return;
+        }
+        (true, _, Some(_)) | (false, None, Some(_)) => true,
+        _ => false,
+    };
+
+    let (start, end) = if use_block && block {
+        (sel_start, sel_end)
+    } else {
+        // Whole lines; a selection ending at a line start leaves that line out.
+        let first = sci.line_from_position(sel_start);
+        let mut last = sci.line_from_position(sel_end);
+        if last > first && sel_end == sci.position_from_line(last) {
+            last -= 1;
+        }
+        let end = if last + 1 < sci.line_count() {
+            sci.position_from_line(last + 1)
+        } else {
+            sci.doc_len()
+        };
+        (sci.position_from_line(first), end)
+    };
+    let text = sci.text_range(start, end);
+    let new = match (use_block, tokens.line, tokens.block) {
+        (true, _, Some((open, close))) if block => toggle_block_comment(&text, open, close),
+        (true, _, Some((open, close))) => {
+            // Keep the final EOL outside the block.
+            let body = text.trim_ascii_end().len();
+            let mut out = toggle_block_comment(&text[..body], open, close);
+            out.extend_from_slice(&text[body..]);
+            out
+        }
+        (_, Some(token), _) => toggle_line_comment(&text, token),
+        _ => return,
+    };
+
+    sci.begin_undo_action();
+    sci.set_target(start, end);
+    sci.replace_target(&new);
+    sci.end_undo_action();
+    if sel_start == sel_end {
+        // Keep the caret on its text as the line shifts.
+        let caret =
+            (sel_start as isize + new.len() as isize - text.len() as isize).max(start as isize);
+        sci.set_sel(caret as usize, caret as usize);
+    } else {
+        sci.set_sel(start, start + new.len());
+    }
+}
+
+// ── Line operations ───────────────────────────────────────────────────────────
+
+/// First and last line the selection touches.  A selection ending at a line
+/// start leaves that line out.
+fn selected_lines(sci: &ScintillaView) -> (usize, usize) {
+    let (sel_start, sel_end) = (sci.selection_start(), sci.selection_end());
+    let first = sci.line_from_position(sel_start);
+    let mut last = sci.line_from_position(sel_end);
+    if last > first && sel_end == sci.position_from_line(last) {
+        last -= 1;
+    }
+    (first, last)
+}
+
+/// Byte range of lines `first..=last`, including the last line's EOL.
+fn lines_range(sci: &ScintillaView, first: usize, last: usize) -> (usize, usize) {
+    let end = if last + 1 < sci.line_count() {
+        sci.position_from_line(last + 1)
+    } else {
+        sci.doc_len()
+    };
+    (sci.position_from_line(first), end)
+}
+
+/// Handle an Edit > Line Operations command.
+///
+/// Join Lines and Split Lines… work on the whole lines the selection
+/// touches; Join Lines with no multi-line selection joins the caret line
+/// with the next one, and Split Lines first asks for the width.  The
+/// Remove commands work on the selected lines, or on the whole document
+/// when nothing is selected.  The result replaces the lines as one undo
+/// step.  Beeps in a read-only tab or when there is nothing to join.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_line_operation(hwnd: HWND, state: &mut WindowState, op: LineOp) {
+    use crate::editor::line_ops::{
+        join_lines, remove_duplicate_lines, remove_empty_lines, split_lines,
+    };
+
+    let idx = state.app.active_idx;
+    let beep = || {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+    };
+    if state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+        beep();
+        return;
+    }
+    let sci = &state.sci_views[idx];
+    let (sel_start, sel_end) = (sci.selection_start(), sci.selection_end());
+    let whole_document = sel_start == sel_end && op.whole_document_default();
+    let (start, end) = if whole_document {
+        (0, sci.doc_len())
+    } else {
+        let (first, mut last) = selected_lines(sci);
+        if op == LineOp::Join && last == first {
+            if last + 1 >= sci.line_count() {
+                beep();
+                return;
+            }
+            last += 1;
+        }
+        lines_range(sci, first, last)
+    };
+
+    let text = sci.text_range(start, end);
+    let new = match op {
+        LineOp::Join => join_lines(&text),
+        LineOp::Split => {
+            let Some(width) = crate::platform::win32::dlg::show_prompt_dialog(
+                hwnd,
+                "Split Lines",
+                "Split the selected lines to at most this many characters:",
+                &state.settings.split_width.to_string(),
+                true,
+            ) else {
+                return;
+            };
+            let Ok(width @ 1..) = width.trim().parse::<u32>() else {
+                beep();
+                return;
+            };
+            if width != state.settings.split_width {
+                state.settings.split_width = width;
+                let _ = crate::config::save(&state.settings);
+            }
+            split_lines(&text, width as usize, state.app.tabs[idx].eol.bytes())
+        }
+        LineOp::RemoveDuplicates => remove_duplicate_lines(&text, false),
+        LineOp::RemoveConsecutiveDuplicates => remove_duplicate_lines(&text, true),
+        LineOp::RemoveEmpty => remove_empty_lines(&text, false),
+        LineOp::RemoveBlank => remove_empty_lines(&text, true),
+    };
+    if new == text {
+        return;
+    }
+
+    let sci = &state.sci_views[idx];
+    sci.begin_undo_action();
+    sci.set_target(start, end);
+    sci.replace_target(&new);
+    sci.end_undo_action();
+    if whole_document {
+        let caret = sel_start.min(sci.doc_len());
+        sci.set_sel(caret, caret);
+    } else {
+        sci.set_sel(start, start + new.len());
+    }
 }
 
-/// Handle the registered "commdlg_FindReplace" message sent by FindTextW /
-/// ReplaceTextW whenever the user clicks Find Next, Replace, Replace All, or
-/// closes the dialog.
+// ── Word completion ───────────────────────────────────────────────────────────
+
+/// Handle Edit > Complete Word (`explicit`), or the automatic popup after a
+/// typed character.
+///
+/// When the text before the caret is a path (`C:\Us`, `./src/ma`) whose
+/// folder exists, offers that folder's matching entries; otherwise words from
+/// the text around the caret and the language's keywords that extend the
+/// word before the caret, and the triggers of the language's snippets.  The
+/// automatic popup needs at least
+/// `settings.autocomplete_after` typed word characters (any path will do)
+/// and stays quiet when nothing matches; the menu command beeps instead.
 ///
 /// # Safety
-/// `lparam` is a valid `*const FINDREPLACEW` provided by the OS.
-unsafe fn handle_findreplace_msg(hwnd: HWND, lparam: LPARAM, state: &mut WindowState) {
-    // SAFETY: the OS guarantees lparam is a *const FINDREPLACEW pointing to
-    // the same struct we passed to FindTextW / ReplaceTextW.
-    let fr = &*(lparam.0 as *const FINDREPLACEW);
-    let flags = fr.Flags.0;
+/// `state` must be valid.
+unsafe fn handle_complete_word(state: &mut WindowState, explicit: bool) {
+    use crate::editor::completion::{candidates, prefix_len, HARVEST_RADIUS};
 
-    if flags & FR_DIALOGTERM != 0 {
-        // Dialog is closing — clear the stored HWND.
-        state.hwnd_find_dlg = HWND::default();
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    let beep = || {
+        if explicit {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+    };
+    if doc.degraded.no_scan || doc.read_only || viewer_index(state, idx).is_some() {
+        beep();
         return;
     }
-
-    let find_bytes = pwstr_to_utf8(fr.lpstrFindWhat);
-    if find_bytes.is_empty() {
+    let caret = sci.caret_pos();
+    let start = caret.saturating_sub(HARVEST_RADIUS);
+    let text = sci.text_range(start, caret + HARVEST_RADIUS);
+    let before = &text[..(caret - start).min(text.len())];
+    // A typed separator opens the next folder even over the current list.
+    let separator_typed = matches!(before.last(), Some(b'\\' | b'/'));
+    if !explicit && sci.is_completion_active() && !separator_typed {
+        return;
+    }
+    match complete_path(state, before) {
+        Some(0) => {
+            beep();
+            return;
+        }
+        Some(_) => return,
+        None => {}
+    }
+    let len = prefix_len(before);
+    if !explicit && len < state.settings.autocomplete_after as usize {
+        return;
+    }
+    let prefix = &before[before.len() - len..];
+    let triggers = state
+        .snippets
+        .get(doc.language)
+        .iter()
+        .map(|s| s.trigger.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let keywords: Vec<&[u8]> = crate::languages::keywords(doc.language)
+        .iter()
+        .map(|&(_, words)| words)
+        .chain([triggers.as_bytes()])
+        .collect();
+    let words = candidates(prefix, &text, &keywords);
+    if words.is_empty() {
+        beep();
         return;
     }
+    sci.show_completions(len, &words);
+}
 
-    let sci_flags = (if flags & FR_MATCHCASE != 0 {
-        SCFIND_MATCHCASE
-    } else {
-        0
-    }) | (if flags & FR_WHOLEWORD != 0 {
-        SCFIND_WHOLEWORD
-    } else {
-        0
-    });
-    let forward = flags & FR_DOWN != 0;
+/// Offer the entries matching a path typed at the end of `before`; relative
+/// paths are taken from the document's folder, or the workspace's for an
+/// untitled document.  Returns how many were offered, or `None` if no path
+/// with a readable folder was typed.
+fn complete_path(state: &WindowState, before: &[u8]) -> Option<usize> {
+    use crate::editor::path_completion::{entries, resolve, typed_paths};
 
     let idx = state.app.active_idx;
-    let sci = &state.sci_views[idx];
-
-    if flags & FR_FINDNEXT != 0 {
-        if !sci.find_next(&find_bytes, sci_flags, forward) {
-            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+    let base = state.app.tabs[idx]
+        .path
+        .as_deref()
+        .and_then(std::path::Path::parent)
+        .or(state.workspace_root.as_deref());
+    for typed in typed_paths(before) {
+        let Some(dir) = resolve(typed.dir, base) else {
+            continue;
+        };
+        if let Some(names) = entries(&dir, typed.name, typed.separator) {
+            let names: Vec<&[u8]> = names.iter().map(|n| n.as_bytes()).collect();
+            state.sci_views[idx].show_completions(typed.name.len(), &names);
+            return Some(names.len());
         }
-    } else if flags & FR_REPLACE != 0 {
-        let repl_bytes = pwstr_to_utf8(fr.lpstrReplaceWith);
-        handle_replace_once(sci, &find_bytes, &repl_bytes, sci_flags, forward);
-    } else if flags & FR_REPLACEALL != 0 {
-        let repl_bytes = pwstr_to_utf8(fr.lpstrReplaceWith);
-        let n = sci.replace_all(&find_bytes, &repl_bytes, sci_flags);
-        let msg = format!("{n} replacement{} made.", if n == 1 { "" } else { "s" });
-        let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
-        let _ = MessageBoxW(hwnd, PCWSTR(wide.as_ptr()), w!("Rivet"), MB_OK);
     }
+    None
 }
 
-/// Replace the current selection (if it matches `find`) then move to the next
-/// occurrence.
+/// Signal a search that found nothing, as chosen under Search > When Not
+/// Found.  Every failed Find Next / Find Previous / Replace goes through here.
 ///
 /// # Safety
-/// `sci` must be a valid `ScintillaView` whose HWND is alive.
-unsafe fn handle_replace_once(
-    sci: &ScintillaView,
-    find: &[u8],
-    repl: &[u8],
-    flags: u32,
-    forward: bool,
-) {
-    let sel_start = sci.selection_start();
-    let sel_end = sci.selection_end();
-
-    // If the current selection exactly matches the search term, replace it.
-    if sel_end > sel_start {
-        sci.set_target(sel_start, sel_end);
-        if sci.search_in_target(find, flags).is_some() {
-            sci.replace_target(repl);
+/// `hwnd` must be the main window; `state` must be valid.
+unsafe fn notify_not_found(hwnd: HWND, state: &WindowState) {
+    match state.settings.not_found_feedback {
+        NotFoundFeedback::Beep => {
+            let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        }
+        NotFoundFeedback::Status => {
+            set_status_part(state.hwnd_status, 0, " Not found");
+            crate::platform::win32::a11y::announce(state.hwnd_status);
+            let _ = SetTimer(hwnd, STATUS_FLASH_TIMER_ID, STATUS_FLASH_MS, None);
+        }
+        NotFoundFeedback::Taskbar => {
+            let info = FLASHWINFO {
+                cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+                hwnd,
+                dwFlags: FLASHW_ALL,
+                uCount: 2,
+                dwTimeout: 0,
+            };
+            let _ = FlashWindowEx(&info);
         }
+        NotFoundFeedback::Silent => {}
     }
+}
 
-    // Advance to the next match.
-    if !sci.find_next(find, flags, forward) {
-        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
-    }
+/// Handle a Search > When Not Found choice: store it and move the check.
+fn handle_feedback_choice(state: &mut WindowState, choice: NotFoundFeedback) {
+    state.settings.not_found_feedback = choice;
+    let _ = crate::config::save(&state.settings);
+    update_feedback_checkmarks(state);
 }
 
-/// Handle F3 / Shift+F3: repeat the last search from the Find dialog.
-///
-/// If no previous search text exists in the buffer the Find dialog is opened.
-///
-/// # Safety
-/// Called only from WM_COMMAND on the UI thread with a valid `state`.
-unsafe fn handle_find_next(hwnd: HWND, state: &mut WindowState, forward: bool) {
-    // If the find buffer is empty (no previous search), open the Find dialog.
-    if state.find_buf[0] == 0 {
-        handle_find_open(hwnd, state);
-        return;
+/// Handle a File > On Startup choice: persist it and move the checkmark.
+fn handle_restore_choice(state: &mut WindowState, choice: RestoreSession) {
+    state.settings.restore_session = choice;
+    let _ = crate::config::save(&state.settings);
+    update_restore_checkmarks(state);
+}
+
+/// Check the File > On Startup item matching the setting.
+fn update_restore_checkmarks(state: &WindowState) {
+    let current = state.settings.restore_session;
+    for (id, choice) in [
+        (IDM_FILE_RESTORE_ALWAYS, RestoreSession::Always),
+        (IDM_FILE_RESTORE_NEVER, RestoreSession::Never),
+        (IDM_FILE_RESTORE_ASK, RestoreSession::Ask),
+    ] {
+        let flag = (MF_BYCOMMAND
+            | if choice == current {
+                MF_CHECKED
+            } else {
+                MF_UNCHECKED
+            })
+        .0;
+        // SAFETY: menu_bar is the live main menu.
+        unsafe {
+            let _ = CheckMenuItem(state.menu_bar, id as u32, flag);
+        }
     }
+}
 
-    // Derive Scintilla flags from the last dialog flag state.
-    let fr_flags = state.findreplace.Flags.0;
-    let sci_flags = (if fr_flags & FR_MATCHCASE != 0 {
-        SCFIND_MATCHCASE
-    } else {
-        0
-    }) | (if fr_flags & FR_WHOLEWORD != 0 {
-        SCFIND_WHOLEWORD
-    } else {
-        0
-    });
+/// Handle File > On Startup > Keep Unsaved Changes.
+fn handle_keep_unsaved_toggle(state: &mut WindowState) {
+    state.settings.keep_unsaved_on_exit = !state.settings.keep_unsaved_on_exit;
+    let _ = crate::config::save(&state.settings);
+    update_keep_unsaved_checkmark(state);
+}
 
-    // Decode the UTF-16 find buffer to UTF-8.
-    let len = state.find_buf.iter().position(|&c| c == 0).unwrap_or(0);
-    let s = String::from_utf16_lossy(&state.find_buf[..len]);
-    let find_bytes = s.into_bytes();
+/// Sync the Keep Unsaved Changes checkmark with the setting.
+fn update_keep_unsaved_checkmark(state: &WindowState) {
+    let checked = state.settings.keep_unsaved_on_exit;
+    let flag = (MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED }).0;
+    // SAFETY: menu_bar is the live main menu.
+    unsafe {
+        let _ = CheckMenuItem(state.menu_bar, IDM_FILE_KEEP_UNSAVED as u32, flag);
+    }
+}
 
-    let idx = state.app.active_idx;
-    if !state.sci_views[idx].find_next(&find_bytes, sci_flags, forward) {
-        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+/// Check the Search > When Not Found item matching the setting.
+fn update_feedback_checkmarks(state: &WindowState) {
+    let current = state.settings.not_found_feedback;
+    for (id, choice) in [
+        (IDM_SEARCH_FEEDBACK_BEEP, NotFoundFeedback::Beep),
+        (IDM_SEARCH_FEEDBACK_STATUS, NotFoundFeedback::Status),
+        (IDM_SEARCH_FEEDBACK_TASKBAR, NotFoundFeedback::Taskbar),
+        (IDM_SEARCH_FEEDBACK_SILENT, NotFoundFeedback::Silent),
+    ] {
+        let flag = (MF_BYCOMMAND
+            | if choice == current {
+                MF_CHECKED
+            } else {
+                MF_UNCHECKED
+            })
+        .0;
+        // SAFETY: menu_bar is the live main menu.
+        unsafe {
+            let _ = CheckMenuItem(state.menu_bar, id as u32, flag);
+        }
     }
 }
 
@@ -2012,6 +8501,154 @@ unsafe fn handle_goto_line(hwnd: HWND, state: &mut WindowState, hinstance: HINST
     }
 }
 
+/// Search > Next / Previous Change: move the caret to the first line of
+/// the next (or previous) run of changed lines, stepping over the run the
+/// caret is in.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_goto_change(hwnd: HWND, state: &WindowState, forward: bool) {
+    let view = &state.sci_views[state.app.active_idx];
+    let changed = |line: usize| match line.checked_sub(1) {
+        Some(before) => view.next_change(before, true) == Some(line),
+        None => view.next_change(1, false) == Some(0),
+    };
+    let (line, _) = view.caret_line_col();
+    let mut line = line - 1;
+    let target = if forward {
+        while changed(line) && changed(line + 1) {
+            line += 1;
+        }
+        view.next_change(line, true)
+    } else {
+        while line > 0 && changed(line) && changed(line - 1) {
+            line -= 1;
+        }
+        view.next_change(line, false).map(|mut start| {
+            while start > 0 && changed(start - 1) {
+                start -= 1;
+            }
+            start
+        })
+    };
+    match target {
+        Some(line) => {
+            view.set_caret_pos(view.position_from_line(line));
+            view.scroll_caret();
+        }
+        None => notify_not_found(hwnd, state),
+    }
+}
+
+// ── Snippets ──────────────────────────────────────────────────────────────────
+
+/// Handle Tab (`forward`) or Shift+Tab pressed in `view`: move to the next or
+/// previous tab stop of the snippet being filled in, or expand the snippet
+/// whose trigger is before the caret.  Returns whether the key was used;
+/// otherwise Scintilla indents as usual.
+///
+/// The snippet is left once the caret is outside its current stop or has
+/// reached the last one.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn handle_snippet_tab(state: &mut WindowState, view: HWND, forward: bool) -> bool {
+    let sci = &state.sci_views[state.app.active_idx];
+    if view != sci.hwnd() || sci.is_completion_active() {
+        return false;
+    }
+    if let Some((stops_view, stops)) = &mut state.tab_stops {
+        let len = sci.doc_len();
+        let current = stops.current(len);
+        let caret = sci.caret_pos();
+        if *stops_view == view && (current.start..=current.end).contains(&caret) {
+            match stops.step(forward, len) {
+                Some(next) => {
+                    sci.set_sel(next.start, next.end);
+                    if stops.at_last() {
+                        state.tab_stops = None;
+                    }
+                }
+                // Shift+Tab at the first stop stays there.
+                None => sci.set_sel(current.start, current.end),
+            }
+            return true;
+        }
+        state.tab_stops = None;
+    }
+    forward && expand_snippet(state)
+}
+
+/// Replace the snippet trigger before the caret with the snippet and select
+/// its first tab stop.  Returns whether there was a trigger to expand.
+///
+/// A trigger right after `.`, `/` or `\` (a member or a path) is left alone.
+fn expand_snippet(state: &mut WindowState) -> bool {
+    use crate::editor::{
+        completion::prefix_len,
+        snippets::{expand, TabStops},
+    };
+
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+        return false;
+    }
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    let caret = sci.caret_pos();
+    if sci.selection_start() != sci.selection_end() {
+        return false;
+    }
+    let line_start = sci.position_from_line(sci.line_from_position(caret));
+    let before = sci.text_range(line_start, caret);
+    let len = prefix_len(&before);
+    let (head, word) = before.split_at(before.len() - len);
+    if word.is_empty() || matches!(head.last(), Some(b'.' | b'/' | b'\\')) {
+        return false;
+    }
+    let Some(snippet) = state
+        .snippets
+        .get(doc.language)
+        .iter()
+        .find(|s| s.trigger.as_bytes() == word)
+    else {
+        return false;
+    };
+    let indent: Vec<u8> = before
+        .iter()
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .copied()
+        .collect();
+    let unit = if doc.indent.use_tabs {
+        b"\t".to_vec()
+    } else {
+        vec![b' '; doc.indent.width]
+    };
+    let expansion = expand(&snippet.body, &indent, &unit, doc.eol.bytes());
+    let start = caret - len;
+    sci.begin_undo_action();
+    sci.set_target(start, caret);
+    sci.replace_target(&expansion.text);
+    sci.end_undo_action();
+    let mut stops = TabStops::new(&expansion, start, sci.doc_len());
+    let first = stops.current(sci.doc_len());
+    sci.set_sel(first.start, first.end);
+    state.tab_stops = (!stops.at_last()).then(|| (sci.hwnd(), stops));
+    true
+}
+
+/// Handle Edit > Edit Snippets…: open the active document's language's
+/// snippets file, created with an example on first use.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_edit_snippets(hwnd: HWND, state: &mut WindowState) {
+    match crate::editor::snippets::ensure_file(state.app.active_doc().language) {
+        Ok(path) => open_path(hwnd, state, path),
+        Err(e) => show_error_dialog(&format!("Could not create the snippets file:\n{e}")),
+    }
+}
+
 // ── Go To Line dialog ─────────────────────────────────────────────────────────
 
 /// Data passed to `goto_dlg_proc` via the `lParam` of `WM_INITDIALOG`.
@@ -2139,128 +8776,15 @@ unsafe extern "system" fn goto_dlg_proc(
 ///   OK     (IDOK=1)             at (73, 36) 50×14 DU
 ///   Cancel (IDCANCEL=2)         at (128, 36) 50×14 DU
 fn build_goto_line_template(total_lines: usize) -> Vec<u8> {
-    // ── Local bit constants (u32 to avoid conflict with WINDOW_STYLE newtypes) ──
-    const WS_POPUP_V: u32 = 0x8000_0000;
-    const WS_CAPTION_V: u32 = 0x00C0_0000; // WS_BORDER | WS_DLGFRAME
-    const WS_SYSMENU_V: u32 = 0x0008_0000;
-    const DS_MODALFRAME: u32 = 0x0080;
-    const DS_CENTER: u32 = 0x0800;
-    const WS_CHILD_V: u32 = 0x4000_0000;
-    const WS_VISIBLE_V: u32 = 0x1000_0000;
-    const WS_BORDER_V: u32 = 0x0080_0000;
-    const WS_TABSTOP_V: u32 = 0x0001_0000;
-    const ES_AUTOHSCROLL: u32 = 0x0080;
-    const BS_DEFPB: u32 = 0x0001; // BS_DEFPUSHBUTTON
-                                  // Predefined class atoms for controls in a dialog template.
-    const ATOM_BUTTON: u16 = 0x0080;
-    const ATOM_EDIT: u16 = 0x0081;
-    const ATOM_STATIC: u16 = 0x0082;
-
-    let dlg_style: u32 = WS_POPUP_V | WS_CAPTION_V | WS_SYSMENU_V | DS_MODALFRAME | DS_CENTER;
+    use crate::platform::win32::dlg::{DlgTemplate, IDCANCEL, IDOK};
 
     let label = format!("Go to line (1\u{2013}{total_lines}):");
-
-    let mut v: Vec<u8> = Vec::with_capacity(512);
-
-    // ── DLGTEMPLATE header ────────────────────────────────────────────────────
-    push_u32(&mut v, dlg_style);
-    push_u32(&mut v, 0); // dwExtendedStyle
-    push_u16(&mut v, 4); // cdit — number of controls
-    push_u16(&mut v, 0); // x (DS_CENTER ignores these)
-    push_u16(&mut v, 0); // y
-    push_u16(&mut v, 185); // cx (dialog units)
-    push_u16(&mut v, 55); // cy
-    push_u16(&mut v, 0); // menu: none
-    push_u16(&mut v, 0); // window class: default dialog
-    push_wstr(&mut v, "Go to Line"); // title
-
-    // ── Control 1: Static label ───────────────────────────────────────────────
-    align4(&mut v);
-    push_u32(&mut v, WS_CHILD_V | WS_VISIBLE_V); // SS_LEFT = 0
-    push_u32(&mut v, 0);
-    push_u16(&mut v, 7);
-    push_u16(&mut v, 7);
-    push_u16(&mut v, 170);
-    push_u16(&mut v, 9);
-    push_u16(&mut v, 0xFFFF); // id (unused for statics)
-    push_u16(&mut v, 0xFFFF);
-    push_u16(&mut v, ATOM_STATIC);
-    push_wstr(&mut v, &label);
-    push_u16(&mut v, 0); // cbWndExtra
-
-    // ── Control 2: Edit (ID=100) ──────────────────────────────────────────────
-    align4(&mut v);
-    push_u32(
-        &mut v,
-        WS_CHILD_V | WS_VISIBLE_V | WS_BORDER_V | WS_TABSTOP_V | ES_AUTOHSCROLL,
-    );
-    push_u32(&mut v, 0);
-    push_u16(&mut v, 7);
-    push_u16(&mut v, 18);
-    push_u16(&mut v, 170);
-    push_u16(&mut v, 14);
-    push_u16(&mut v, 100); // id=100
-    push_u16(&mut v, 0xFFFF);
-    push_u16(&mut v, ATOM_EDIT);
-    push_wstr(&mut v, "");
-    push_u16(&mut v, 0);
-
-    // ── Control 3: OK button (IDOK=1) ─────────────────────────────────────────
-    align4(&mut v);
-    push_u32(&mut v, WS_CHILD_V | WS_VISIBLE_V | WS_TABSTOP_V | BS_DEFPB);
-    push_u32(&mut v, 0);
-    push_u16(&mut v, 73);
-    push_u16(&mut v, 36);
-    push_u16(&mut v, 50);
-    push_u16(&mut v, 14);
-    push_u16(&mut v, 1); // IDOK
-    push_u16(&mut v, 0xFFFF);
-    push_u16(&mut v, ATOM_BUTTON);
-    push_wstr(&mut v, "OK");
-    push_u16(&mut v, 0);
-
-    // ── Control 4: Cancel button (IDCANCEL=2) ─────────────────────────────────
-    align4(&mut v);
-    push_u32(&mut v, WS_CHILD_V | WS_VISIBLE_V | WS_TABSTOP_V);
-    push_u32(&mut v, 0);
-    push_u16(&mut v, 128);
-    push_u16(&mut v, 36);
-    push_u16(&mut v, 50);
-    push_u16(&mut v, 14);
-    push_u16(&mut v, 2); // IDCANCEL
-    push_u16(&mut v, 0xFFFF);
-    push_u16(&mut v, ATOM_BUTTON);
-    push_wstr(&mut v, "Cancel");
-    push_u16(&mut v, 0);
-
-    v
-}
-
-// ── DLGTEMPLATE builder helpers ───────────────────────────────────────────────
-
-#[inline]
-fn push_u16(v: &mut Vec<u8>, n: u16) {
-    v.extend_from_slice(&n.to_le_bytes());
-}
-
-#[inline]
-fn push_u32(v: &mut Vec<u8>, n: u32) {
-    v.extend_from_slice(&n.to_le_bytes());
-}
-
-/// Append a null-terminated UTF-16 string.
-fn push_wstr(v: &mut Vec<u8>, s: &str) {
-    for cu in s.encode_utf16() {
-        push_u16(v, cu);
-    }
-    push_u16(v, 0); // null terminator
-}
-
-/// Pad to the next 4-byte boundary (required between DLGITEMTEMPLATE entries).
-fn align4(v: &mut Vec<u8>) {
-    while v.len() % 4 != 0 {
-        v.push(0);
-    }
+    let mut t = DlgTemplate::new("Go to Line", 185, 55);
+    t.label(7, 7, 170, 9, &label)
+        .edit(100, 7, 18, 170, 14)
+        .button(IDOK, 73, 36, 50, 14, "OK", true)
+        .button(IDCANCEL, 128, 36, 50, 14, "Cancel", false);
+    t.build()
 }
 
 // ── PWSTR → UTF-8 helper ──────────────────────────────────────────────────────
@@ -2283,6 +8807,117 @@ unsafe fn pwstr_to_utf8(pwstr: PWSTR) -> Vec<u8> {
         .unwrap_or_default()
 }
 
+// ── Byte-order mark ───────────────────────────────────────────────────────────
+
+/// Handle Format > Add BOM (`bom`) / Remove BOM: choose whether the active
+/// UTF-8 document is saved with a byte-order mark.  The tab turns dirty so
+/// the change is saved; beeps for a read-only tab, a non-UTF-8 document, or
+/// one already as asked.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_set_bom(hwnd: HWND, state: &mut WindowState, bom: bool) {
+    let idx = state.app.active_idx;
+    let editable = !state.app.tabs[idx].read_only && viewer_index(state, idx).is_none();
+    if !editable || !state.app.tabs[idx].set_bom(bom) {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+// ── Encoding ──────────────────────────────────────────────────────────────────
+
+/// Handle Format > Encoding: save the active document in `encoding` from
+/// now on.  The text is unchanged; the tab turns dirty so the change is
+/// saved.  Characters a code page lacks are written as its default
+/// character.  Beeps for a read-only tab.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_set_encoding(hwnd: HWND, state: &mut WindowState, encoding: Encoding) {
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].read_only || viewer_index(state, idx).is_some() {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+        return;
+    }
+    if state.app.tabs[idx].encoding == encoding {
+        return;
+    }
+    state.app.tabs[idx].encoding = encoding;
+    state.app.tabs[idx].dirty = true;
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+}
+
+// ── Document properties ───────────────────────────────────────────────────────
+
+/// Handle File > Document Properties… (Alt+Enter): show the active
+/// document's details and apply any encoding, EOL or language chosen.
+///
+/// A new EOL converts the text like Format > Convert; a new encoding marks
+/// the tab dirty so the next save writes it; a new language re-applies
+/// highlighting until the next save or refresh detects it again.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_document_properties(hwnd: HWND, state: &mut WindowState) {
+    use crate::platform::win32::properties_dialog::{
+        show_properties_dialog, DocumentInfo, DocumentProperties,
+    };
+
+    let idx = state.app.active_idx;
+    let doc = &state.app.tabs[idx];
+    let sci = &state.sci_views[idx];
+    let viewer = state.viewers.iter().find(|v| v.view == sci.hwnd());
+    let size = viewer.map_or(sci.doc_len() as u64, |v| v.window.len());
+    let info = DocumentInfo {
+        path: doc.path.as_deref().map_or_else(
+            || format!("{} (not saved)", doc.display_name()),
+            |p| p.display().to_string(),
+        ),
+        size: format!("{} ({size} bytes)", crate::ui::status::human_size(size)),
+        lines: viewer.is_none().then(|| sci.line_count()),
+        read_only: doc.read_only || viewer.is_some(),
+        file: doc
+            .path
+            .as_deref()
+            .and_then(crate::platform::win32::file_info::read),
+    };
+    let current = DocumentProperties {
+        encoding: doc.encoding,
+        eol: doc.eol,
+        language: doc.language,
+    };
+    let Some(chosen) = show_properties_dialog(hwnd, &info, current) else {
+        return;
+    };
+    if chosen.eol != current.eol && !info.read_only {
+        run_command(hwnd, state, Cmd::ConvertEol(chosen.eol));
+    }
+    if chosen.encoding != current.encoding && !info.read_only {
+        handle_set_encoding(hwnd, state, chosen.encoding);
+    }
+    if chosen.language != current.language {
+        state.app.tabs[idx].language = chosen.language;
+        let sci = &state.sci_views[idx];
+        apply_highlighting(
+            sci,
+            &state.app.tabs[idx],
+            state.dark_mode,
+            state.system_colours.as_ref(),
+            &state.sci_dll,
+        );
+        let (selection, caret_line) = accent_colours(state);
+        sci.set_highlights(selection, caret_line);
+        sci.colourise_all();
+    }
+    update_status_bar(state);
+}
+
 // ── Status bar / title ────────────────────────────────────────────────────────
 
 // Refresh all three status-bar parts from the current `WindowState`.
@@ -2290,71 +8925,323 @@ unsafe fn pwstr_to_utf8(pwstr: PWSTR) -> Vec<u8> {
 // Safety: `state.hwnd_status` and the active sci_view must be valid.
 // ── Syntax highlighting ────────────────────────────────────────────────────────
 
+/// Re-run content-based language detection for the tab at `idx` and, if the
+/// language changed, re-apply highlighting and refresh the status bar.
+///
+/// Only documents whose path says nothing (untitled, or no recognised
+/// extension) are affected; a path-detected language is never overridden.
+///
+/// # Safety
+/// `state` must be valid; `idx` must be in range.
+unsafe fn redetect_language(state: &mut WindowState, idx: usize) {
+    let doc = &state.app.tabs[idx];
+    if doc.degraded.plain_text {
+        return;
+    }
+    let first_line = state.sci_views[idx].line_text(0);
+    let lang = crate::languages::detect_language(doc.path.as_deref(), &first_line);
+    if lang == doc.language {
+        return;
+    }
+    state.app.tabs[idx].language = lang;
+    apply_highlighting(
+        &state.sci_views[idx],
+        &state.app.tabs[idx],
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    if idx == state.app.active_idx {
+        update_status_bar(state);
+    }
+}
+
+/// Handle View > Refresh Highlighting: detect the active document's language
+/// afresh, re-apply lexer, keywords and theme, and re-style the whole text.
+///
+/// Unlike [`redetect_language`] this always re-applies, so it also repairs
+/// styling that has fallen out of sync with the text.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn handle_refresh_highlighting(state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    if state.app.tabs[idx].degraded.plain_text {
+        return;
+    }
+    let first_line = state.sci_views[idx].line_text(0);
+    let doc = &mut state.app.tabs[idx];
+    doc.language = crate::languages::detect_language(doc.path.as_deref(), &first_line);
+    let sci = &state.sci_views[idx];
+    apply_highlighting(
+        sci,
+        &state.app.tabs[idx],
+        state.dark_mode,
+        state.system_colours.as_ref(),
+        &state.sci_dll,
+    );
+    let (selection, caret_line) = accent_colours(state);
+    sci.set_highlights(selection, caret_line);
+    sci.colourise_all();
+    update_status_bar(state);
+}
+
+/// Handle View > Highlight and Wrap Long Lines: turn word wrap (if the
+/// document had it on) and highlighting back on for a document opened
+/// without them because of a long line.  Its size limits still apply.
+///
+/// # Safety
+/// `state` must be valid.
+unsafe fn handle_allow_long_lines(state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    let sci = &state.sci_views[idx];
+    if !state.app.allow_long_lines(idx, sci.doc_len() as u64) {
+        return;
+    }
+    let doc = &state.app.tabs[idx];
+    sci.set_degraded(doc.degraded);
+    if !doc.degraded.no_wrap {
+        sci.set_word_wrap(doc.word_wrap);
+    }
+    if !doc.degraded.plain_text {
+        apply_highlighting(
+            sci,
+            doc,
+            state.dark_mode,
+            state.system_colours.as_ref(),
+            &state.sci_dll,
+        );
+        let (selection, caret_line) = accent_colours(state);
+        sci.set_highlights(selection, caret_line);
+        sci.colourise_all();
+    }
+    update_wrap_checkmark(state, state.app.active_doc().word_wrap);
+    update_edit_state(state);
+    update_status_bar(state);
+}
+
 /// Apply the language lexer and colour theme to `sci` based on `doc`.
 ///
-/// Skipped for large files (`doc.large_file == true`) — they stay with
-/// `SCLEX_NULL` (plain text) which is already set by `set_large_file_mode`.
+/// Skipped for documents too large to highlight (`doc.degraded.plain_text`)
+/// — they stay with `SCLEX_NULL` (plain text) which is already set by
+/// `set_degraded`.
 fn apply_highlighting(
     sci: &ScintillaView,
     doc: &crate::app::DocumentState,
     dark: bool,
+    system: Option<&crate::theme::SystemColours>,
     sci_dll: &crate::editor::scintilla::SciDll,
 ) {
-    if doc.large_file {
+    if doc.degraded.plain_text {
         return;
     }
-    let lang = match &doc.path {
-        Some(p) => crate::languages::language_from_path(p),
-        None => crate::languages::Language::PlainText,
-    };
+    let lang = doc.language;
     let lexer_ptr = match lang {
         crate::languages::Language::PlainText => std::ptr::null_mut(),
         _ => sci_dll.create_lexer(lang.lexer_name()),
     };
+    if lexer_ptr.is_null() && lang != crate::languages::Language::PlainText {
+        // The bundled Lexilla lacks this lexer; the text stays unstyled.
+        eprintln!(
+            "[rivet] Lexilla has no lexer named {:?}",
+            String::from_utf8_lossy(lang.lexer_name().strip_suffix(b"\0").unwrap_or_default())
+        );
+    }
     sci.set_ilexer(lexer_ptr);
     for (set_idx, words) in crate::languages::keywords(lang) {
         sci.set_keywords(*set_idx, words);
     }
-    crate::theme::apply_theme(sci, lang, dark);
+    let Some(colours) = system else {
+        crate::theme::apply_theme(sci, lang, dark);
+        sci.set_occurrence_colour(crate::theme::occurrence_highlight(dark));
+        let (unsaved, saved) = crate::theme::change_markers(dark);
+        sci.set_change_colours(unsaved, saved);
+        let (added, modified, deleted) = crate::theme::git_markers(dark);
+        sci.set_git_colours(added, modified, deleted);
+        sci.set_contrast_colours(None, None);
+        return;
+    };
+    crate::theme::apply_system_theme(sci, colours);
+    sci.set_occurrence_colour(colours.highlight);
+    let (unsaved, saved) = colours.change_markers();
+    sci.set_change_colours(unsaved, saved);
+    let (added, modified, deleted) = colours.git_markers();
+    sci.set_git_colours(added, modified, deleted);
+    sci.set_contrast_colours(Some(colours.highlight_text), Some(colours.window_text));
 }
 
 unsafe fn update_status_bar(state: &WindowState) {
     let idx = state.app.active_idx;
     let (line, col) = state.sci_views[idx].caret_line_col();
-    let (enc, eol, large_file, path) = {
+    let pos = state.sci_views[idx].caret_char_offset();
+    let (enc, eol, degraded, lang) = {
         let doc = state.app.active_doc();
         (
             doc.encoding.as_str().to_owned(),
             doc.eol.as_str().to_owned(),
-            doc.large_file,
-            doc.path.clone(),
+            doc.degraded,
+            doc.language,
         )
     };
-    let lang = match &path {
-        Some(p) => crate::languages::language_from_path(p),
-        None => crate::languages::Language::PlainText,
+    let mut lang_text = lang.display_name().to_owned();
+    if degraded.large_file {
+        lang_text.push_str(" [Large]");
+    }
+    // Long lines: wrap and highlighting are off until View > Highlight and
+    // Wrap Long Lines.
+    if degraded.long_lines {
+        lang_text.push_str(" [Long Lines]");
+    }
+    let sci = &state.sci_views[idx];
+    let view = sci.hwnd();
+    // A viewer holds only a window of the file; report the whole file.
+    let size = match state.viewers.iter().find(|v| v.view == view) {
+        Some(v) => v.window.len(),
+        None => sci.doc_len() as u64,
     };
-    let lang_text = if large_file {
-        format!("{} [Large]", lang.display_name())
+    let position = if let Some(task) = current_task(state) {
+        crate::ui::status::task_text(&task)
+    } else if let Some(v) = state.viewers.iter().find(|v| v.view == view) {
+        const MB: u64 = 1024 * 1024;
+        let w = &v.window;
+        format!(
+            " Ln {line}, Col {col}  [{}\u{2013}{} of {} MB]",
+            w.start() / MB,
+            w.end().div_ceil(MB),
+            w.len().div_ceil(MB)
+        )
     } else {
-        lang.display_name().to_owned()
+        format!(" Ln {line}/{}, Col {col}, Pos {pos}", sci.line_count())
     };
-    // Parts (left → right): 0=Ln/Col, 1=language, 2=EOL, 3=encoding
-    let texts: [String; 4] = [
-        format!(" Ln {line}, Col {col}"),
+    let mode = if sci.overtype() { "OVR" } else { "INS" };
+    // Parts (left → right): 0=Ln/Col, 1=size, 2=language, 3=EOL, 4=encoding,
+    // 5=insert / overtype
+    let texts: [String; 6] = [
+        position,
+        format!(" {}", crate::ui::status::human_size(size)),
         format!(" {lang_text}"),
         format!(" {eol}"),
         format!(" {enc}"),
+        format!(" {mode}"),
     ];
+    let mut announce = false;
     for (i, text) in texts.iter().enumerate() {
-        let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        if set_status_part(state.hwnd_status, i, text) && i > 1 {
+            announce = true;
+        }
+    }
+    // Language / EOL / encoding / overtype changes are spoken; caret moves
+    // and edits are not.
+    if announce {
+        crate::platform::win32::a11y::announce(state.hwnd_status);
+    }
+    sync_progress(state);
+}
+
+/// The long operation running in the active tab, if any.
+fn current_task(state: &WindowState) -> Option<crate::ui::status::Task> {
+    use crate::ui::status::Task;
+    let view = state.sci_views[state.app.active_idx].hwnd();
+    if let Some(load) = state.loads.iter().find(|l| l.view == view) {
+        return Some(Task {
+            label: "Loading",
+            percent: load.loader.percent(),
+        });
+    }
+    let r = state.replace.as_ref().filter(|r| r.view == view)?;
+    Some(Task {
+        label: match r.eol {
+            Some(_) => "Converting line endings",
+            None => "Replacing",
+        },
+        percent: r.job.percent(),
+    })
+}
+
+/// Show the status bar's progress bar and Cancel button for the active
+/// tab's task, or hide them if it has none.
+fn sync_progress(state: &WindowState) {
+    let Some(progress) = &state.progress else {
+        return;
+    };
+    match current_task(state) {
+        Some(task) => progress.show(state.hwnd_status, 0, task.percent, state.dpi),
+        None => progress.hide(),
+    }
+}
+
+/// Handle the status bar's Cancel button or Esc: stop the active tab's
+/// task.  A cancelled load closes its tab, as the document is incomplete;
+/// a cancelled Replace All or conversion leaves the text as it was.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_cancel_task(hwnd: HWND, state: &mut WindowState) {
+    let idx = state.app.active_idx;
+    let view = state.sci_views[idx].hwnd();
+    if is_loading(state, idx) {
+        handle_close_tab(hwnd, state, idx);
+    } else if state.replace.as_ref().is_some_and(|r| r.view == view) {
+        handle_replace_progress(hwnd, state, true);
+    }
+    // The Cancel button took the focus when clicked.
+    let _ = SetFocus(state.sci_views[state.app.active_idx].hwnd());
+}
+
+/// Set status-bar part `part` to `text` if it differs, and report the change
+/// to accessibility clients.  Returns whether the text changed.
+///
+/// # Safety
+/// `hwnd_status` must be the valid status bar.
+unsafe fn set_status_part(hwnd_status: HWND, part: usize, text: &str) -> bool {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let len = SendMessageW(hwnd_status, SB_GETTEXTLENGTH, WPARAM(part), LPARAM(0)).0 & 0xFFFF;
+    if len as usize == wide.len() - 1 {
+        let mut current = vec![0u16; wide.len()];
         let _ = SendMessageW(
-            state.hwnd_status,
-            SB_SETTEXT,
-            WPARAM(i),
-            LPARAM(wide.as_ptr() as isize),
+            hwnd_status,
+            SB_GETTEXT,
+            WPARAM(part),
+            LPARAM(current.as_mut_ptr() as isize),
         );
+        if current == wide {
+            return false;
+        }
     }
+    let _ = SendMessageW(
+        hwnd_status,
+        SB_SETTEXT,
+        WPARAM(part),
+        LPARAM(wide.as_ptr() as isize),
+    );
+    crate::platform::win32::a11y::set_child_name(
+        hwnd_status,
+        part + 1,
+        &crate::ui::status::part_accessible_name(part, text),
+    );
+    true
+}
+
+/// Handle View > Speak Cursor Position: write the full position into the
+/// status bar and have screen readers announce it.  The next caret move
+/// restores the short form.
+///
+/// # Safety
+/// Called only from WM_COMMAND on the UI thread with a valid `state`.
+unsafe fn handle_speak_position(state: &WindowState) {
+    let sci = &state.sci_views[state.app.active_idx];
+    let (line, col) = sci.caret_line_col();
+    let doc = state.app.active_doc();
+    let text = format!(
+        "Line {line} of {}, column {col}. {}, {}, {}.",
+        sci.line_count(),
+        doc.language.display_name(),
+        doc.eol.as_str(),
+        doc.encoding.as_str()
+    );
+    // Always announce, even if the text is unchanged from last time.
+    set_status_part(state.hwnd_status, 0, &text);
+    crate::platform::win32::a11y::announce(state.hwnd_status);
 }
 
 /// Update the main window title from the current `App` state.
@@ -2381,43 +9268,64 @@ unsafe fn update_window_title(hwnd: HWND, app: &App) {
 /// Called only from WM_COMMAND / accelerator on the UI thread.
 unsafe fn handle_close_tab(hwnd: HWND, state: &mut WindowState, idx: usize) {
     // ── Dirty check ───────────────────────────────────────────────────────────
+    // The scratch tab saves itself rather than asking.
+    if state.app.tabs[idx].is_scratch() {
+        save_scratch_tabs(state);
+    }
     if state.app.tabs[idx].dirty {
         let name = state.app.tabs[idx].display_name();
-        let msg = format!("\"{name}\" has unsaved changes.\n\nSave before closing?");
-        let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
-        // SAFETY: wide is valid null-terminated UTF-16 that outlives the call.
-        let result = MessageBoxW(
-            hwnd,
-            PCWSTR(wide.as_ptr()),
-            w!("Rivet"),
-            MB_YESNOCANCEL | MB_ICONWARNING,
-        );
-        match result {
-            r if r == IDYES => {
+        let on_disk = state.app.tabs[idx].path.clone().filter(|p| p.is_file());
+        match save_prompt::show_save_prompt(hwnd, &name, on_disk.is_some()) {
+            SaveChoice::Save => {
                 // Try to save; if it fails or the user cancels the dialog, abort.
                 if !save_tab_for_close(hwnd, state, idx) {
                     return;
                 }
             }
-            r if r == IDNO => { /* discard — fall through to close */ }
-            _ => return, // Cancel
+            SaveChoice::Discard => { /* fall through to close */ }
+            SaveChoice::ShowChanges => {
+                // Leave the tab open; the diff tab shows what saving would
+                // write, and the user closes again once decided.
+                if let Some(path) = on_disk {
+                    show_unsaved_changes(hwnd, state, idx, &path);
+                }
+                return;
+            }
+            SaveChoice::Cancel => return,
         }
     }
 
+    // ── Abandon an unfinished load ────────────────────────────────────────────
+    let view = state.sci_views[idx].hwnd();
+    if let Some(pos) = state.loads.iter().position(|l| l.view == view) {
+        // Dropping the Loader stops the worker thread.
+        state.loads.remove(pos);
+        state.sci_views[idx].end_chunked_load(!state.app.tabs[idx].degraded.no_undo);
+    }
+    if let Some(v) = viewer_index(state, idx) {
+        state.viewers.remove(v);
+    }
+    if state.replace.as_ref().is_some_and(|r| r.view == view) {
+        // Dropping the ReplaceJob stops the worker thread.
+        state.replace = None;
+    }
+    if let Some(m) = monitor_index(state, idx) {
+        stop_monitoring(hwnd, state, m);
+    }
+
     // ── Last tab: reset to untitled instead of removing ───────────────────────
     if state.app.tab_count() == 1 {
-        let doc = &mut state.app.tabs[0];
-        doc.path = None;
-        doc.dirty = false;
-        doc.large_file = false;
-        doc.encoding = crate::app::Encoding::Utf8;
-        doc.eol = crate::app::EolMode::Crlf;
-        doc.word_wrap = true;
-        state.sci_views[0].set_eol_mode(crate::app::EolMode::Crlf);
-        state.sci_views[0].set_word_wrap(true);
-        state.sci_views[0].set_text(b"");
-        state.sci_views[0].set_save_point();
-        update_wrap_checkmark(hwnd, false);
+        commands::reset_to_untitled(&mut state.app.tabs[0], &state.sci_views[0]);
+        state.sci_views[0].set_degraded(Degraded::default());
+        state.sci_views[0].set_rendering(state.settings.rendering.resolve(0));
+        apply_highlighting(
+            &state.sci_views[0],
+            &state.app.tabs[0],
+            state.dark_mode,
+            state.system_colours.as_ref(),
+            &state.sci_dll,
+        );
+        update_wrap_checkmark(state, false);
         sync_tab_label(state, 0);
         update_window_title(hwnd, &state.app);
         update_status_bar(state);
@@ -2436,6 +9344,7 @@ unsafe fn handle_close_tab(hwnd: HWND, state: &mut WindowState, idx: usize) {
 
     // Update App state; remove_tab returns the new active_idx.
     let new_active = state.app.remove_tab(idx);
+    sync_tab_names(state);
 
     // Sync the tab strip selection.
     let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(new_active), LPARAM(0));
@@ -2466,7 +9375,7 @@ unsafe fn handle_close_tab(hwnd: HWND, state: &mut WindowState, idx: usize) {
 /// # Safety
 /// Called only from `handle_close_tab` on the UI thread with a valid `state`.
 unsafe fn save_tab_for_close(hwnd: HWND, state: &mut WindowState, idx: usize) -> bool {
-    let path = if let Some(p) = state.app.tabs[idx].path.clone() {
+    let mut path = if let Some(p) = state.app.tabs[idx].path.clone() {
         p
     } else {
         match show_save_dialog(hwnd, "") {
@@ -2482,36 +9391,44 @@ unsafe fn save_tab_for_close(hwnd: HWND, state: &mut WindowState, idx: usize) ->
     let prev_active = state.app.active_idx;
     state.app.active_idx = idx;
 
-    match state.app.save(path, &utf8) {
-        Ok(()) => {
-            state.sci_views[idx].set_save_point();
-            sync_tab_label(state, idx);
-            // Leave active_idx at idx — handle_close_tab removes it next.
-            true
-        }
-        Err(e) => {
+    while let Err(e) = state.app.save(path.clone(), &utf8) {
+        let retry = match file_error_prompt(hwnd, "save", &path, e.file_problem(), &e.to_string()) {
+            FileErrorChoice::Retry => Some(path),
+            FileErrorChoice::SaveAs => {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+                show_save_dialog(hwnd, &name.unwrap_or_default())
+            }
+            FileErrorChoice::Cancel => None,
+        };
+        let Some(next) = retry else {
             state.app.active_idx = prev_active;
-            show_error_dialog(&format!("Could not save file:\n{e}"));
-            false
-        }
+            return false;
+        };
+        path = next;
     }
+    state.sci_views[idx].set_save_point();
+    sync_tab_label(state, idx);
+    // Leave active_idx at idx — handle_close_tab removes it next.
+    true
 }
 
-/// Combined exit guard: show a single dialog listing every dirty tab.
+/// Combined guard for exit and Open Workspace: show a single dialog listing
+/// every dirty tab, ending with `question`.
 ///
-/// Returns `true` if the user chose to discard all changes and exit.
+/// Returns `true` if the user chose to discard all changes.
 ///
 /// # Safety
 /// `hwnd` must be a valid window handle.
-unsafe fn confirm_discard_all(hwnd: HWND, names: &[String]) -> bool {
+unsafe fn confirm_discard_all(hwnd: HWND, names: &[String], question: &str) -> bool {
     let mut text = String::from("The following files have unsaved changes:\n");
     for name in names {
         text.push_str(&format!("  \u{2022} {name}\n"));
     }
-    text.push_str("\nDiscard all and exit?");
+    text.push('\n');
+    text.push_str(question);
 
     let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
-    // MB_YESNO: "Yes" = discard and exit, "No" = stay open.
+    // MB_YESNO: "Yes" = discard, "No" = keep the tabs.
     let result = MessageBoxW(
         hwnd,
         PCWSTR(wide.as_ptr()),
@@ -2535,116 +9452,532 @@ fn about_dialog(hwnd: HWND) {
 
 // ── Session ───────────────────────────────────────────────────────────────────
 
-/// Serialize the current session to `%APPDATA%\Rivet\session.json`.
+/// Stop everything that could still write or post, then save the session as
+/// the final write.
+///
+/// Timers are killed first so no checkpoint or Monitor tick can follow.
+/// Background loads and Replace All are cancelled and their threads joined
+/// within `SHUTDOWN_TIMEOUT`; a thread still running then is abandoned.  The
+/// session writer is the exception: it is always waited for, so a checkpoint
+/// cannot be renamed over the exit save.  `ending_session` is a logoff or
+/// restart, where nobody was asked about unsaved changes: they are backed up
+/// and offered back at the next start.
+///
+/// # Safety
+/// `hwnd` must be the main window; all Scintilla views must still be alive.
+unsafe fn shutdown(hwnd: HWND, state: &mut WindowState, ending_session: bool) {
+    for id in [
+        AUTOSAVE_TIMER_ID,
+        MONITOR_TIMER_ID,
+        STATUS_FLASH_TIMER_ID,
+        OCCURRENCE_TIMER_ID,
+        GIT_TIMER_ID,
+    ] {
+        let _ = KillTimer(hwnd, id);
+    }
+    let mut threads: Vec<_> = state
+        .loads
+        .iter_mut()
+        .filter_map(|l| l.loader.take_worker())
+        .collect();
+    threads.extend(state.replace.as_mut().and_then(|r| r.job.take_worker()));
+    // Queued jobs are dropped; running ones finish.
+    threads.extend(
+        state
+            .tasks
+            .take()
+            .map_or_else(Vec::new, crate::tasks::Pool::close),
+    );
+    // Dropping the jobs cancels them and unblocks any pending channel send.
+    state.loads.clear();
+    state.replace = None;
+    state.git_jobs.clear();
+    crate::shutdown::join_all(threads, crate::shutdown::SHUTDOWN_TIMEOUT);
+    save_scratch_tabs(state);
+    // A clean exit: unsaved changes were saved, discarded, or kept on purpose.
+    if owns_session(hwnd) {
+        let save = if keeps_unsaved(hwnd, state) {
+            SessionSave::ExitKeepingUnsaved
+        } else if ending_session {
+            SessionSave::EndSession
+        } else {
+            SessionSave::Exit
+        };
+        save_session(hwnd, state, save);
+    }
+    // Whatever checkpoint is still queued or being written finishes before
+    // the window goes; the exit save, if any, went through the writer last.
+    if let Some(writer) = state.session_writer.take() {
+        writer.close();
+    }
+}
+
+/// Whether closing `hwnd` keeps its unsaved changes for the next start
+/// instead of asking: the setting is on and no other window is open.
+fn keeps_unsaved(hwnd: HWND, state: &WindowState) -> bool {
+    state.settings.keep_unsaved_on_exit && WINDOWS.with(|w| *w.borrow() == [hwnd])
+}
+
+/// Why the session file is being written.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SessionSave {
+    /// The periodic checkpoint: unsaved text is backed up in case Rivet
+    /// stops unexpectedly.
+    Checkpoint,
+    /// A clean exit; unsaved changes were saved or discarded.
+    Exit,
+    /// A clean exit with Keep Unsaved Changes: unsaved text is backed up
+    /// and restored without asking at the next start.
+    ExitKeepingUnsaved,
+    /// Windows is logging off or restarting: unsaved text is backed up, as
+    /// at a checkpoint, and offered back at the next start.
+    EndSession,
+}
+
+/// Save the current session to `%APPDATA%\Rivet\session.json`.
 ///
 /// Must be called while all Scintilla child windows are still alive (i.e.
 /// from `WM_CLOSE`, before `DestroyWindow`).  Errors are silently discarded.
-fn save_session(state: &WindowState) {
-    let entries: Vec<crate::session::TabEntry> = state
+///
+/// `hwnd`, the window that owns the session, writes the tabs of every open
+/// window: its own first, then each other window's in the order they were
+/// opened, so a crash or logoff loses none of them.  They all come back in
+/// one window.
+///
+/// Except on a plain `Exit` the text of each dirty tab is also copied to the
+/// backup folder, for crash recovery or to be restored at the next start.
+/// Other windows are still open at an exit of this one, so their unsaved
+/// text is backed up whatever `save` is.  Backups the saved session no
+/// longer names are deleted either way.  Only the text is captured here: the
+/// session writer's thread writes it.  An exit save is queued behind any
+/// checkpoint and waited for, so it is the last write.
+fn save_session(hwnd: HWND, state: &mut WindowState, save: SessionSave) {
+    let mut tabs = session_entries(state);
+    let mut backups = if save == SessionSave::Exit {
+        Vec::new()
+    } else {
+        unsaved_texts(state)
+    };
+    for other in WINDOWS.with(|w| w.borrow().clone()) {
+        if other == hwnd {
+            continue;
+        }
+        // SAFETY: every window in WINDOWS is a live main window of this thread.
+        let ptr = unsafe { GetWindowLongPtrW(other, GWLP_USERDATA) } as *const WindowState;
+        // SAFETY: a main window's GWLP_USERDATA is its WindowState or null,
+        // and only read here; it is a different allocation from `state`.
+        let Some(other) = (unsafe { ptr.as_ref() }) else {
+            continue;
+        };
+        let base = tabs.len();
+        backups.extend(
+            unsaved_texts(other)
+                .into_iter()
+                .map(|(i, text)| (base + i, text)),
+        );
+        tabs.extend(session_entries(other).into_iter().map(|mut entry| {
+            entry.order = entry.order.map(|order| base + order);
+            entry
+        }));
+    }
+    let snapshot = crate::session::Snapshot {
+        tabs,
+        backups,
+        active_tab: state.app.active_idx,
+        dark_mode: state.dark_mode,
+        tab_position: state.tab_position.as_u8(),
+        kept_unsaved: save == SessionSave::ExitKeepingUnsaved,
+    };
+    if save != SessionSave::Checkpoint {
+        match state.session_writer.take() {
+            Some(writer) => writer.finish(snapshot),
+            None => {
+                let _ = snapshot.write();
+            }
+        }
+        return;
+    }
+    if state.session_writer.is_none() {
+        state.session_writer = crate::session::writer::SessionWriter::spawn().ok();
+    }
+    match &state.session_writer {
+        Some(writer) => writer.send(snapshot),
+        None => {
+            let _ = snapshot.write();
+        }
+    }
+}
+
+/// The text of each dirty tab of `state` to back up, by tab index.  Large
+/// and viewer documents are never backed up: copying them every checkpoint
+/// would stall the UI thread.
+fn unsaved_texts(state: &WindowState) -> Vec<(usize, Vec<u8>)> {
+    state
         .app
         .tabs
         .iter()
         .enumerate()
-        .map(|(i, doc)| crate::session::TabEntry {
-            path: doc.path.as_ref().map(|p| p.to_string_lossy().into_owned()),
-            caret_pos: state.sci_views[i].caret_pos(),
-            scroll_line: state.sci_views[i].first_visible_line(),
-            encoding: doc.encoding.as_str().to_owned(),
-            eol: doc.eol.as_str().to_owned(),
+        .filter(|&(i, doc)| {
+            doc.dirty && !doc.degraded.large_file && viewer_index(state, i).is_none()
         })
-        .collect();
+        .map(|(i, _)| (i, state.sci_views[i].get_text()))
+        .collect()
+}
 
-    let _ = crate::session::save(
-        &entries,
-        state.app.active_idx,
-        state.dark_mode,
-        state.tab_position.as_u8(),
-    );
+/// One session entry per open tab, in strip order.
+fn session_entries(state: &WindowState) -> Vec<crate::session::TabEntry> {
+    state
+        .app
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, doc)| crate::session::TabEntry::new(doc, &state.sci_views[i], i))
+        .collect()
 }
 
 /// Re-open the tabs recorded in the session file.
 ///
-/// Called once from `run()` after the main window is visible.  Entries without
-/// a path (untitled buffers) and entries whose file no longer exists on disk
-/// are silently skipped.  On any error the function returns early, leaving the
+/// Called once from `run()` after the main window is visible.  Tabs are
+/// reopened in their saved strip order.  Entries without a path (untitled
+/// buffers) and entries whose file no longer exists on disk are silently
+/// skipped.  On any error the function returns early, leaving the
 /// initial untitled tab intact.
 ///
+/// The tabs are left closed when `clean` (`--clean` on the command line) or
+/// the File > On Startup setting says so; dark mode and the tab position
+/// are restored either way.
+///
 /// # Safety
 /// `hwnd` must be the valid main-window handle; `state` must point to a live
 /// `WindowState`.
-unsafe fn restore_session(hwnd: HWND, state: &mut WindowState) {
+unsafe fn restore_session(hwnd: HWND, state: &mut WindowState, clean: bool) {
     let Some(sf) = crate::session::load() else {
         return;
     };
 
     // Restore dark mode BEFORE loading files so each apply_highlighting call
-    // uses the correct palette.  Always apply so light-mode sessions override
-    // the dark-mode default set in post_create_init.
-    state.dark_mode = sf.dark_mode;
-    apply_title_bar_dark(hwnd, sf.dark_mode);
-    update_dark_mode_checkmark(hwnd, sf.dark_mode);
-
-    // Restore tab position.
-    let pos = TabPosition::from_u8(sf.tab_position);
+    // uses the correct palette.
+    apply_window_prefs(
+        hwnd,
+        state,
+        sf.dark_mode,
+        TabPosition::from_u8(sf.tab_position),
+    );
+
+    // Backups in the session mean the last exit was not clean, or kept its
+    // unsaved changes on purpose.  Unsaved text is offered back (or, when
+    // kept, simply restored) even when the tabs would not otherwise reopen.
+    let recover = sf.has_backups()
+        && (sf.kept_unsaved || {
+            let report = crate::crash::crashes_dir().and_then(|d| crate::crash::take_pending(&d));
+            confirm_recovery(hwnd, report.as_deref())
+        });
+    let reopen = recover
+        || (!clean
+            && !sf.tabs.is_empty()
+            && match state.settings.restore_session {
+                RestoreSession::Always => true,
+                RestoreSession::Never => false,
+                RestoreSession::Ask => confirm_reopen(hwnd, sf.tabs.len()),
+            });
+    if reopen {
+        restore_tabs(hwnd, state, &sf, recover);
+    }
+    if !recover {
+        if let Some(dir) = crate::session::backup_dir() {
+            crate::session::prune_backups(&dir, &[]);
+        }
+    }
+}
+
+/// Apply dark mode and the tab position to a freshly created window, as
+/// restored from the session or taken from the window that opened it.
+///
+/// Dark mode is always applied so a light setting overrides the dark-mode
+/// default set in `post_create_init`; while it follows the system theme,
+/// the Windows app mode wins over `dark`.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be its state.
+unsafe fn apply_window_prefs(hwnd: HWND, state: &mut WindowState, dark: bool, pos: TabPosition) {
+    let dark = system_dark_mode(&state.settings).unwrap_or(dark);
+    state.dark_mode = dark;
+    apply_title_bar_dark(hwnd, dark_chrome(state));
+    apply_dark_controls(state);
+    apply_accent_highlights(state);
+    update_dark_mode_checkmark(state.menu_bar, dark);
+
     if pos != state.tab_position {
         state.tab_position = pos;
         set_tab_style(state.hwnd_tab, pos);
-        update_tab_position_checkmarks(hwnd, pos);
+        update_tab_position_checkmarks(state.menu_bar, pos);
+        refresh_frame(hwnd);
     }
+}
 
-    let mut opened_any = false;
+/// Ask whether to reopen the `count` tabs of the last session (File > On
+/// Startup > Ask).
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn confirm_reopen(hwnd: HWND, count: usize) -> bool {
+    let text = match count {
+        1 => "Reopen the file from your last session?".to_owned(),
+        n => format!("Reopen the {n} files from your last session?"),
+    };
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = MessageBoxW(
+        hwnd,
+        PCWSTR(wide.as_ptr()),
+        w!("Rivet"),
+        MB_YESNO | MB_ICONQUESTION,
+    );
+    result == IDYES
+}
 
-    for entry in &sf.tabs {
-        let Some(path_str) = &entry.path else {
-            continue;
-        };
-        let path = std::path::PathBuf::from(path_str);
-        if !path.exists() {
-            continue;
-        }
+/// Ask whether to restore the unsaved changes kept by the last checkpoint
+/// before Rivet stopped unexpectedly.  `report` is the crash report written
+/// at the time, if the crash handler got to run.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle.
+unsafe fn confirm_recovery(hwnd: HWND, report: Option<&std::path::Path>) -> bool {
+    let mut text = String::from(
+        "Rivet did not shut down cleanly last time.\n\n\
+         Restore the unsaved changes from before it closed?",
+    );
+    if let Some(path) = report {
+        text.push_str(&format!(
+            "\n\nA crash report was saved to:\n{}",
+            path.display()
+        ));
+    }
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = MessageBoxW(
+        hwnd,
+        PCWSTR(wide.as_ptr()),
+        w!("Rivet"),
+        MB_YESNO | MB_ICONWARNING,
+    );
+    result == IDYES
+}
 
-        let bytes = match std::fs::read(&path) {
-            Ok(b) => b,
-            Err(_) => continue,
-        };
+/// Open the tabs recorded in `sf` in their saved strip order and activate
+/// the saved active tab.  The first file reuses the active tab, which must
+/// be a clean untitled one.  With `recover`, tabs saved with a backup get
+/// their unsaved text back, untitled ones included.  Returns `false` if no
+/// tab could be reopened.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn restore_tabs(
+    hwnd: HWND,
+    state: &mut WindowState,
+    sf: &crate::session::SessionFile,
+    recover: bool,
+) -> bool {
+    use crate::session::restore::Source;
 
-        if !opened_any {
-            // Reuse the initial untitled tab for the first restored file.
-            load_file_into_active_tab(hwnd, state, path, &bytes);
-        } else {
-            open_file_in_new_tab(hwnd, state, path, &bytes);
+    let backup_dir = crate::session::backup_dir().filter(|_| recover);
+    let mut opened_any = false;
+    // Files that exist but could not be read, reported once at the end.
+    let mut failed: Vec<(std::path::PathBuf, FileProblem)> = Vec::new();
+    // Tab index to activate: the saved active tab if it reopened, otherwise
+    // the nearest reopened tab before it in strip order.
+    let mut target = 0;
+
+    for tab in crate::session::restore::reopen(sf, backup_dir.as_deref()) {
+        let tab = match tab {
+            Ok(tab) => tab,
+            Err(fail) => {
+                failed.push(fail);
+                continue;
+            }
+        };
+        match tab.source {
+            Source::Untitled => {
+                if opened_any {
+                    open_untitled_tab(hwnd, state);
+                }
+            }
+            Source::Viewer(path) => {
+                if let Err(e) = open_viewer(hwnd, state, path.clone()) {
+                    failed.push((path, FileProblem::of(&e)));
+                    continue;
+                }
+            }
+            Source::File {
+                path,
+                bytes,
+                code_page,
+            } => {
+                if !opened_any {
+                    // Reuse the initial untitled tab for the first restored file.
+                    load_file_into_active_tab(hwnd, state, path, &bytes, code_page);
+                } else {
+                    open_file_in_new_tab(hwnd, state, path, &bytes, code_page);
+                }
+            }
         }
 
+        if let Some(text) = &tab.backup {
+            restore_backup(hwnd, state, text);
+        }
         // Restore caret and scroll.  SCI_GOTOPOS clamps to document length
         // if the position is beyond the end of file, so no bounds check needed.
         let idx = state.app.active_idx;
-        state.sci_views[idx].set_caret_pos(entry.caret_pos);
-        state.sci_views[idx].set_first_visible_line(entry.scroll_line);
-
+        if viewer_index(state, idx).is_none() {
+            state.sci_views[idx].set_caret_pos(tab.caret_pos);
+            state.sci_views[idx].set_first_visible_line(tab.scroll_line);
+        }
+        if tab.up_to_active {
+            target = idx;
+        }
         opened_any = true;
     }
 
+    if !failed.is_empty() {
+        let mut text = String::from("These files from your last session could not be reopened:\n");
+        for (path, problem) in &failed {
+            text.push_str(&format!(
+                "\n{} \u{2014} {}",
+                path.display(),
+                problem.reason()
+            ));
+        }
+        text.push_str("\n\nOpen them again with File > Open once they are available.");
+        show_error_dialog(&text);
+    }
     if !opened_any {
+        return false;
+    }
+
+    activate_tab(hwnd, state, target);
+    update_window_title(hwnd, &state.app);
+    update_status_bar(state);
+    true
+}
+
+/// Replace the active tab's text with recovered unsaved `text`.  The text
+/// is set as an undoable edit, so the tab is dirty and Undo returns to the
+/// file on disk.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn restore_backup(hwnd: HWND, state: &mut WindowState, text: &[u8]) {
+    let idx = state.app.active_idx;
+    state.sci_views[idx].set_text(text);
+    state.app.tabs[idx].dirty = true;
+    sync_tab_label(state, idx);
+    update_window_title(hwnd, &state.app);
+}
+
+// ── Named workspaces ──────────────────────────────────────────────────────────
+
+/// Handle File > Save Workspace…: pick a name and write the open tabs to it.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_workspace_save(hwnd: HWND, state: &mut WindowState) {
+    let names = crate::session::list_workspaces();
+    let Some(name) = crate::platform::win32::workspace_dialog::show_workspace_dialog(
+        hwnd,
+        "Save Workspace",
+        &names,
+        false,
+    ) else {
+        return;
+    };
+    if names.contains(&name) {
+        let msg = format!("Workspace \"{name}\" already exists.\n\nReplace it?");
+        let wide: Vec<u16> = msg.encode_utf16().chain(std::iter::once(0)).collect();
+        let result = MessageBoxW(
+            hwnd,
+            PCWSTR(wide.as_ptr()),
+            w!("Rivet"),
+            MB_YESNO | MB_ICONWARNING,
+        );
+        if result != IDYES {
+            return;
+        }
+    }
+    if let Err(e) = crate::session::save_workspace(
+        &name,
+        &session_entries(state),
+        state.app.active_idx,
+        state.dark_mode,
+        state.tab_position.as_u8(),
+    ) {
+        show_error_dialog(&format!("Could not save workspace \"{name}\":\n{e}"));
         return;
     }
+    state.workspace_root =
+        crate::session::common_root(state.app.tabs.iter().filter_map(|d| d.path.as_deref()));
+}
 
-    // Restore the active tab (clamped to the number of tabs we actually opened).
-    let target = sf.active_tab.min(state.app.tab_count() - 1);
-    if target != state.app.active_idx {
-        state.sci_views[state.app.active_idx].show(false);
-        state.app.active_idx = target;
-        state.sci_views[target].show(true);
-        let _ = SendMessageW(state.hwnd_tab, TCM_SETCURSEL, WPARAM(target), LPARAM(0));
-        let eol = state.sci_views[target].eol_mode();
-        state.app.active_doc_mut().eol = eol;
+/// Handle File > Open Workspace…: close every tab (one prompt for unsaved
+/// changes) and open the workspace's files in their place.
+///
+/// Only the tabs are restored; theme and tab position stay as they are.
+///
+/// # Safety
+/// `hwnd` must be the valid main-window handle; `state` must be valid.
+unsafe fn handle_workspace_open(hwnd: HWND, state: &mut WindowState) {
+    let names = crate::session::list_workspaces();
+    if names.is_empty() {
+        let _ = MessageBoxW(
+            hwnd,
+            w!("No workspaces have been saved yet.\n\nUse File > Save Workspace\u{2026} first."),
+            w!("Rivet"),
+            MB_OK,
+        );
+        return;
+    }
+    let Some(name) = crate::platform::win32::workspace_dialog::show_workspace_dialog(
+        hwnd,
+        "Open Workspace",
+        &names,
+        true,
+    ) else {
+        return;
+    };
+    let Some(sf) = crate::session::load_workspace(&name) else {
+        show_error_dialog(&format!("Could not read workspace \"{name}\"."));
+        return;
+    };
 
-        let mut rc = RECT::default();
-        let _ = GetClientRect(hwnd, &mut rc);
-        layout_children(state, rc.right, rc.bottom);
+    save_scratch_tabs(state);
+    let dirty: Vec<String> = state
+        .app
+        .tabs
+        .iter()
+        .filter(|doc| doc.dirty)
+        .map(|doc| doc.display_name())
+        .collect();
+    if !dirty.is_empty()
+        && !confirm_discard_all(hwnd, &dirty, "Discard all and open the workspace?")
+    {
+        return;
+    }
+    // Already confirmed above; stop handle_close_tab asking again.
+    for doc in &mut state.app.tabs {
+        doc.dirty = false;
+    }
+    for idx in (0..state.app.tab_count()).rev() {
+        handle_close_tab(hwnd, state, idx);
     }
 
-    update_window_title(hwnd, &state.app);
-    update_status_bar(state);
+    state.workspace_root = crate::session::common_root(
+        sf.tabs
+            .iter()
+            .filter_map(|t| t.path.as_deref())
+            .map(std::path::Path::new),
+    );
+    if !restore_tabs(hwnd, state, &sf, false) {
+        show_error_dialog(&format!(
+            "None of the files in workspace \"{name}\" could be opened."
+        ));
+    }
 }
 
 // ── Error helpers ─────────────────────────────────────────────────────────────
@@ -2657,3 +9990,24 @@ fn last_error(function: &'static str) -> RivetError {
         code: code.0,
     }
 }
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A default the parser refuses would leave its command silently
+    /// unbound.
+    #[test]
+    fn every_default_shortcut_parses() {
+        for cmd in COMMANDS.iter().filter(|c| !c.default.is_empty()) {
+            assert!(
+                crate::keymap::Chord::parse(cmd.default).is_some(),
+                "{}: {:?}",
+                cmd.name,
+                cmd.default
+            );
+        }
+    }
+}
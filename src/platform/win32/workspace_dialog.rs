@@ -0,0 +1,173 @@
+// ── Workspace picker dialog ───────────────────────────────────────────────────
+//
+// Modal dialog shared by File > Save Workspace… and File > Open Workspace…: a
+// list of saved workspace names plus a Name edit.  Clicking a name copies it
+// into the edit; double-clicking confirms it.
+
+#![allow(unsafe_code)]
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Diagnostics::Debug::MessageBeep,
+    UI::WindowsAndMessaging::{
+        DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SendMessageW,
+        SetWindowLongPtrW, DLGTEMPLATE, GWLP_USERDATA, MESSAGEBOX_STYLE, WM_COMMAND, WM_INITDIALOG,
+    },
+};
+
+use crate::platform::win32::dlg::{
+    get_item_text, module_instance, set_item_text, DlgTemplate, ATOM_LISTBOX, IDCANCEL, IDOK,
+    LBS_NOTIFY, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+};
+
+// ── Control IDs ───────────────────────────────────────────────────────────────
+
+const ID_LIST: u16 = 100;
+const ID_NAME: u16 = 101;
+
+// ── List-box messages (winuser.h) ─────────────────────────────────────────────
+
+const LB_ADDSTRING: u32 = 0x0180;
+const LB_GETCURSEL: u32 = 0x0188;
+/// `HIWORD(wParam)` of `WM_COMMAND` when the list-box selection changes.
+const LBN_SELCHANGE: u32 = 1;
+/// `HIWORD(wParam)` of `WM_COMMAND` when a list-box item is double-clicked.
+const LBN_DBLCLK: u32 = 2;
+
+/// Data shared with `workspace_dlg_proc` through `GWLP_USERDATA`.
+struct WorkspaceParams<'a> {
+    names: &'a [String],
+    /// Only accept a name from `names` (Open); otherwise any valid name.
+    must_exist: bool,
+    /// Written by the dialog procedure when the user confirms.
+    result: Option<String>,
+}
+
+/// Show the workspace picker titled `title`, listing `names`.
+///
+/// Returns the chosen name, or `None` if the user cancelled.  Invalid names
+/// (and, with `must_exist`, names not in the list) are refused with a beep.
+pub(crate) fn show_workspace_dialog(
+    hwnd_parent: HWND,
+    title: &str,
+    names: &[String],
+    must_exist: bool,
+) -> Option<String> {
+    let mut t = DlgTemplate::new(title, 200, 135);
+    t.control(
+        ATOM_LISTBOX,
+        ID_LIST,
+        LBS_NOTIFY | WS_BORDER | WS_VSCROLL | WS_TABSTOP,
+        7,
+        7,
+        186,
+        80,
+        "",
+    )
+    .label(7, 96, 30, 9, "&Name:")
+    .edit(ID_NAME, 40, 94, 153, 14)
+    .button(IDOK, 88, 114, 50, 14, "OK", true)
+    .button(IDCANCEL, 143, 114, 50, 14, "Cancel", false);
+    let template = t.build();
+
+    let mut params = WorkspaceParams {
+        names,
+        must_exist,
+        result: None,
+    };
+
+    // SAFETY: template is a well-formed DLGTEMPLATE; workspace_dlg_proc is a
+    // valid DLGPROC; params outlives the modal loop.
+    unsafe {
+        let _ = DialogBoxIndirectParamW(
+            module_instance(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            hwnd_parent,
+            Some(workspace_dlg_proc),
+            LPARAM(&mut params as *mut WorkspaceParams as isize),
+        );
+    }
+    params.result
+}
+
+/// Name of the selected list item, if any.
+///
+/// # Safety
+/// `hwnd` must be the live workspace dialog.
+unsafe fn selected_name(hwnd: HWND, names: &[String]) -> Option<&str> {
+    let list = GetDlgItem(hwnd, ID_LIST as i32).ok()?;
+    let r = SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    names.get(usize::try_from(r).ok()?).map(String::as_str)
+}
+
+/// Accept the Name box if it holds an acceptable name; beep otherwise.
+///
+/// # Safety
+/// `hwnd` must be the live workspace dialog.
+unsafe fn try_confirm(hwnd: HWND, params: &mut WorkspaceParams) {
+    let name = get_item_text(hwnd, ID_NAME as i32).trim().to_owned();
+    let ok = crate::session::is_valid_workspace_name(&name)
+        && (!params.must_exist || params.names.contains(&name));
+    if ok {
+        params.result = Some(name);
+        let _ = EndDialog(hwnd, 1);
+    } else {
+        let _ = MessageBeep(MESSAGEBOX_STYLE(0xFFFF_FFFF));
+    }
+}
+
+/// Dialog procedure for `show_workspace_dialog`.
+///
+/// # Safety
+/// Called by Windows with valid arguments for the lifetime of the dialog.
+unsafe extern "system" fn workspace_dlg_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> isize {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam.0);
+            let params = &*(lparam.0 as *const WorkspaceParams);
+            if let Ok(list) = GetDlgItem(hwnd, ID_LIST as i32) {
+                for name in params.names {
+                    let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+                    let _ = SendMessageW(
+                        list,
+                        LB_ADDSTRING,
+                        WPARAM(0),
+                        LPARAM(wide.as_ptr() as isize),
+                    );
+                }
+            }
+            1
+        }
+        WM_COMMAND => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WorkspaceParams;
+            if ptr.is_null() {
+                return 0;
+            }
+            let params = &mut *ptr;
+            let id = (wparam.0 & 0xFFFF) as u16;
+            let code = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            match id {
+                ID_LIST if code == LBN_SELCHANGE || code == LBN_DBLCLK => {
+                    if let Some(name) = selected_name(hwnd, params.names) {
+                        set_item_text(hwnd, ID_NAME as i32, name);
+                        if code == LBN_DBLCLK {
+                            try_confirm(hwnd, params);
+                        }
+                    }
+                }
+                IDOK => try_confirm(hwnd, params),
+                IDCANCEL => {
+                    let _ = EndDialog(hwnd, 0);
+                }
+                _ => {}
+            }
+            0
+        }
+        _ => 0,
+    }
+}
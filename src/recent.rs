@@ -0,0 +1,158 @@
+// ── Recent files ──────────────────────────────────────────────────────────────
+//
+// The most-recently-used file list, persisted to `%APPDATA%\Rivet\recent.json`.
+// Files enter it when opened or saved under a new name; the taskbar jump list
+// (`platform::win32::jumplist`) shows it.  Kept apart from `settings.json`,
+// which holds only preferences the user edits deliberately.
+// No `unsafe` — pure safe Rust + serde_json.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Most files remembered.
+pub(crate) const MAX_RECENT: usize = 10;
+
+const RECENT_VERSION: u32 = 1;
+
+/// Recently used files, newest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RecentFiles {
+    #[serde(default)]
+    paths: Vec<PathBuf>,
+}
+
+/// On-disk form of `recent.json`.
+#[derive(Serialize, Deserialize)]
+struct RecentFile {
+    version: u32,
+    #[serde(flatten)]
+    recent: RecentFiles,
+}
+
+impl RecentFiles {
+    pub(crate) fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Move `path` to the front, dropping the oldest entry past
+    /// [`MAX_RECENT`].
+    pub(crate) fn push(&mut self, path: &Path) {
+        self.remove(path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT);
+    }
+
+    /// Forget `path`.  Returns whether it was listed.
+    pub(crate) fn remove(&mut self, path: &Path) -> bool {
+        let before = self.paths.len();
+        self.paths.retain(|p| !same_path(p, path));
+        self.paths.len() != before
+    }
+}
+
+/// NTFS paths compare case-insensitively.
+fn same_path(a: &Path, b: &Path) -> bool {
+    a.as_os_str().eq_ignore_ascii_case(b.as_os_str())
+}
+
+/// Return the path to the list: `%APPDATA%\Rivet\recent.json`.
+pub(crate) fn recent_path() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("recent.json");
+    Some(p)
+}
+
+/// Write `recent` to `%APPDATA%\Rivet\recent.json`.
+pub(crate) fn save(recent: &RecentFiles) -> io::Result<()> {
+    let path =
+        recent_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+    save_to(&path, recent)
+}
+
+/// Write `recent` to `path`, creating its folder.
+pub(crate) fn save_to(path: &Path, recent: &RecentFiles) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = RecentFile {
+        version: RECENT_VERSION,
+        recent: recent.clone(),
+    };
+    serde_json::to_writer_pretty(fs::File::create(path)?, &file).map_err(io::Error::other)
+}
+
+/// Read the list; empty if the file is missing, unparsable, or from another
+/// version.
+pub(crate) fn load() -> RecentFiles {
+    recent_path()
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+}
+
+/// Read the list at `path`; empty under the same conditions as [`load`].
+pub(crate) fn load_from(path: &Path) -> RecentFiles {
+    fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<RecentFile>(&data).ok())
+        .filter(|f| f.version == RECENT_VERSION)
+        .map(|f| f.recent)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_moves_existing_entry_to_front() {
+        let mut r = RecentFiles::default();
+        r.push(Path::new(r"C:\a.txt"));
+        r.push(Path::new(r"C:\b.txt"));
+        r.push(Path::new(r"c:\A.TXT"));
+        assert_eq!(
+            r.paths(),
+            [PathBuf::from(r"c:\A.TXT"), PathBuf::from(r"C:\b.txt")]
+        );
+    }
+
+    #[test]
+    fn push_caps_the_list() {
+        let mut r = RecentFiles::default();
+        for i in 0..MAX_RECENT + 3 {
+            r.push(Path::new(&format!(r"C:\{i}.txt")));
+        }
+        assert_eq!(r.paths().len(), MAX_RECENT);
+        assert_eq!(
+            r.paths()[0],
+            PathBuf::from(format!(r"C:\{}.txt", MAX_RECENT + 2))
+        );
+    }
+
+    #[test]
+    fn remove_reports_whether_listed() {
+        let mut r = RecentFiles::default();
+        r.push(Path::new(r"C:\a.txt"));
+        assert!(!r.remove(Path::new(r"C:\b.txt")));
+        assert!(r.remove(Path::new(r"C:\A.txt")));
+        assert!(r.paths().is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let path = std::env::temp_dir().join(format!("rivet-recent-{}.json", std::process::id()));
+        let mut r = RecentFiles::default();
+        r.push(Path::new(r"C:\a.txt"));
+        r.push(Path::new(r"D:\b.rs"));
+        save_to(&path, &r).expect("save");
+        assert_eq!(load_from(&path), r);
+        fs::write(&path, r#"{"version":99,"paths":["x"]}"#).unwrap();
+        assert_eq!(load_from(&path), RecentFiles::default());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,44 @@
+// ── Scratch tab ───────────────────────────────────────────────────────────────
+//
+// The global scratchpad hot key (`scratch_hotkey` in settings.json, default
+// Win+Shift+N) brings Rivet forward on a "Scratch" tab backed by
+// `%APPDATA%\Rivet\scratch.txt`.  The tab saves itself at every session
+// checkpoint and when it is closed, so its text carries over from one
+// session to the next without ever asking to be saved.
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Hot key used when settings.json does not name one.
+pub(crate) const DEFAULT_HOTKEY: &str = "Win+Shift+N";
+
+/// Return the path to the scratch file: `%APPDATA%\Rivet\scratch.txt`.
+pub(crate) fn scratch_path() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("scratch.txt");
+    Some(p)
+}
+
+/// Whether `path` is the scratch file.
+pub(crate) fn is_scratch(path: &Path) -> bool {
+    scratch_path().is_some_and(|p| p == path)
+}
+
+/// Return the scratch file's path, creating an empty file (and its folder)
+/// the first time.
+pub(crate) fn ensure_file() -> io::Result<PathBuf> {
+    let path =
+        scratch_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+    if !path.is_file() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, b"")?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,167 @@
+// ── Find All ──────────────────────────────────────────────────────────────────
+//
+// Every match of a needle in one document, and the text of the results panel
+// that lists them: a summary row followed by one `line: text` row per match.
+// Row `n + 1` of the listing is hit `n`, so the Win32 layer maps a clicked
+// row back to a match without parsing it.  The panel's Copy and Export
+// actions write the hits in `grep -n` form instead, for pasting elsewhere.
+//
+// No `unsafe` — pure safe Rust.
+
+use super::Needle;
+
+/// Longest line text shown in a row, in bytes; longer lines are cut.
+const MAX_ROW_TEXT: usize = 200;
+
+/// One match: its byte offset and 0-based line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Hit {
+    pub(crate) pos: usize,
+    pub(crate) line: usize,
+}
+
+/// Non-overlapping matches of `needle` in `text`, left to right, stopping
+/// after `limit`.
+pub(crate) fn find_all(text: &[u8], needle: &Needle, limit: usize) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    if needle.len() == 0 {
+        return hits;
+    }
+    let (mut i, mut line, mut counted) = (0, 0, 0);
+    while i + needle.len() <= text.len() && hits.len() < limit {
+        if needle.matches_at(text, i, true, true) {
+            line += text[counted..i].iter().filter(|&&b| b == b'\n').count();
+            counted = i;
+            hits.push(Hit { pos: i, line });
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    hits
+}
+
+/// The panel text for `hits` of `term` in `text`.  `truncated` notes that
+/// the search stopped at its limit.
+pub(crate) fn listing(text: &[u8], term: &str, hits: &[Hit], truncated: bool) -> Vec<u8> {
+    let mut out = match (hits.len(), truncated) {
+        (1, _) => format!("1 match for \u{201C}{term}\u{201D}"),
+        (n, false) => format!("{n} matches for \u{201C}{term}\u{201D}"),
+        (n, true) => format!("First {n} matches for \u{201C}{term}\u{201D}"),
+    }
+    .into_bytes();
+    for hit in hits {
+        out.push(b'\n');
+        let start = text[..hit.pos]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let end = text[hit.pos..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+            .map_or(text.len(), |i| hit.pos + i);
+        out.extend_from_slice(format!("{:>6}: ", hit.line + 1).as_bytes());
+        out.extend_from_slice(row_text(&text[start..end]));
+    }
+    out
+}
+
+/// The lines holding `hits` as `grep -n` prints them, `label:LINE: text`,
+/// each ending with `eol`.  A line with several matches appears once and
+/// keeps its full text.  Lines past the end of `text`, edited away since
+/// the search, are left out.
+pub(crate) fn grep_lines(label: &str, text: &[u8], hits: &[Hit], eol: &[u8]) -> Vec<u8> {
+    let mut wanted = hits.iter().map(|h| h.line).peekable();
+    let mut out = Vec::new();
+    for (i, line) in text.split(|&b| b == b'\n').enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if next != i {
+            continue;
+        }
+        while wanted.next_if_eq(&i).is_some() {}
+        out.extend_from_slice(format!("{label}:{}: ", i + 1).as_bytes());
+        out.extend_from_slice(line.strip_suffix(b"\r").unwrap_or(line));
+        out.extend_from_slice(eol);
+    }
+    out
+}
+
+/// `line` without leading blanks, cut to `MAX_ROW_TEXT` bytes on a UTF-8
+/// character boundary.
+fn row_text(line: &[u8]) -> &[u8] {
+    let line = &line[line
+        .iter()
+        .position(|&b| b != b' ' && b != b'\t')
+        .unwrap_or(line.len())..];
+    if line.len() <= MAX_ROW_TEXT {
+        return line;
+    }
+    // Back up over continuation bytes (10xxxxxx) to a character start.
+    let mut end = MAX_ROW_TEXT;
+    while end > 0 && line[end] & 0xC0 == 0x80 {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_every_match_with_its_line() {
+        let text = b"foo bar\nbar foo foo\n\nfoobar foo";
+        let hits = find_all(text, &Needle::new(b"foo", true, true), usize::MAX);
+        let lines: Vec<_> = hits.iter().map(|h| h.line).collect();
+        assert_eq!(lines, [0, 1, 1, 3]);
+        assert_eq!(hits[3].pos, 28);
+        assert_eq!(
+            find_all(text, &Needle::new(b"FOO", false, false), 2).len(),
+            2
+        );
+        assert!(find_all(text, &Needle::new(b"", true, false), 9).is_empty());
+    }
+
+    #[test]
+    fn listing_has_a_row_per_hit() {
+        let text = b"  alpha\r\nbeta alpha\r\n";
+        let hits = find_all(text, &Needle::new(b"alpha", true, false), usize::MAX);
+        let all = String::from_utf8(listing(text, "alpha", &hits, false)).unwrap();
+        let rows: Vec<_> = all.lines().collect();
+        assert_eq!(
+            rows,
+            [
+                "2 matches for \u{201C}alpha\u{201D}",
+                "     1: alpha",
+                "     2: beta alpha"
+            ]
+        );
+        let one = String::from_utf8(listing(text, "beta", &hits[..1], true)).unwrap();
+        assert!(one.starts_with("1 match for"));
+    }
+
+    #[test]
+    fn grep_lines_list_each_matching_line_once() {
+        let text = b"  alpha alpha\r\nbeta\r\nalpha beta\r\n";
+        let hits = find_all(text, &Needle::new(b"alpha", true, false), usize::MAX);
+        assert_eq!(hits.len(), 3);
+        let out = grep_lines(r"C:\a.txt", text, &hits, b"\n");
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "C:\\a.txt:1:   alpha alpha\nC:\\a.txt:3: alpha beta\n"
+        );
+        // A hit whose line has since been deleted is skipped.
+        let stale = [Hit { pos: 0, line: 9 }];
+        assert!(grep_lines("a", text, &stale, b"\n").is_empty());
+    }
+
+    #[test]
+    fn long_rows_are_cut_on_a_character_boundary() {
+        let line = "\u{e9}".repeat(MAX_ROW_TEXT);
+        let cut = row_text(line.as_bytes());
+        assert_eq!(cut.len(), MAX_ROW_TEXT);
+        assert!(std::str::from_utf8(cut).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+// ── Search history ────────────────────────────────────────────────────────────
+//
+// The last find and replace strings, newest first, saved in `settings.json`.
+// The Find / Replace dialogs step through them with Up / Down and list them
+// on Alt+Down.  Stepping is keyed on the text in the field rather than a
+// cursor, so typing over an entry and pressing Up starts again from the
+// newest.
+//
+// No `unsafe` — pure safe Rust.
+
+use serde::{Deserialize, Serialize};
+
+/// Entries kept per list.
+pub(crate) const MAX_ENTRIES: usize = 20;
+
+/// One list of strings, newest first, without duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct History(Vec<String>);
+
+impl History {
+    /// Make `entry` the newest, dropping an older copy and anything beyond
+    /// `MAX_ENTRIES`.  Returns whether the list changed.
+    pub(crate) fn push(&mut self, entry: &str) -> bool {
+        if entry.is_empty() || self.0.first().is_some_and(|e| e == entry) {
+            return false;
+        }
+        self.0.retain(|e| e != entry);
+        self.0.insert(0, entry.to_owned());
+        self.0.truncate(MAX_ENTRIES);
+        true
+    }
+
+    /// The entries, newest first.
+    pub(crate) fn entries(&self) -> &[String] {
+        &self.0
+    }
+
+    /// The entry after `current` going `older` (Up) or newer (Down).  Text
+    /// not in the list steps to the newest entry going older and nowhere
+    /// going newer.
+    pub(crate) fn step(&self, current: &str, older: bool) -> Option<&str> {
+        let next = match (self.0.iter().position(|e| e == current), older) {
+            (Some(i), true) => i + 1,
+            (Some(i), false) => i.checked_sub(1)?,
+            (None, true) => 0,
+            (None, false) => return None,
+        };
+        self.0.get(next).map(String::as_str)
+    }
+}
+
+/// Both lists, as stored in `settings.json`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SearchHistory {
+    pub(crate) find: History,
+    pub(crate) replace: History,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_moves_repeats_to_the_front_and_caps_the_list() {
+        let mut h = History::default();
+        assert!(h.push("a"));
+        assert!(h.push("b"));
+        assert!(!h.push("b"));
+        assert!(!h.push(""));
+        assert!(h.push("a"));
+        assert_eq!(h.entries(), ["a", "b"]);
+        for i in 0..30 {
+            h.push(&i.to_string());
+        }
+        assert_eq!(h.entries().len(), MAX_ENTRIES);
+        assert_eq!(h.entries()[0], "29");
+    }
+
+    #[test]
+    fn step_walks_older_and_newer() {
+        let mut h = History::default();
+        for e in ["old", "mid", "new"] {
+            h.push(e);
+        }
+        assert_eq!(h.step("typed", true), Some("new"));
+        assert_eq!(h.step("new", true), Some("mid"));
+        assert_eq!(h.step("old", true), None);
+        assert_eq!(h.step("mid", false), Some("new"));
+        assert_eq!(h.step("new", false), None);
+        assert_eq!(h.step("typed", false), None);
+    }
+
+    #[test]
+    fn stored_as_plain_lists() {
+        let mut h = SearchHistory::default();
+        h.find.push("x");
+        let json = serde_json::to_string(&h).unwrap();
+        assert_eq!(json, r#"{"find":["x"],"replace":[]}"#);
+        let back: SearchHistory = serde_json::from_str(r#"{"find":["y"]}"#).unwrap();
+        assert_eq!(back.find.entries(), ["y"]);
+    }
+}
@@ -1,8 +1,14 @@
 // ── Search options ────────────────────────────────────────────────────────────
 //
-// Pure-Rust struct mirroring the FINDREPLACEW dialog flags.
+// Pure-Rust struct mirroring the FINDREPLACEW dialog flags, the byte
+// matcher shared by searches that run outside Scintilla, the helpers
+// that seed a search from the selection, and Extended mode's escapes.
 // No Win32 imports; usable from any module.
 
+pub(crate) mod find_all; // Find All results listing
+pub(crate) mod history; // find / replace strings kept across sessions
+pub(crate) mod replace; // Replace All on a worker thread
+
 /// Parameters for a single search operation.
 ///
 /// Populated from the Win32 Find / Replace dialog flags and stored so that
@@ -14,3 +20,238 @@ pub(crate) struct SearchOptions {
     pub(crate) whole_word: bool,
     pub(crate) forward: bool,
 }
+
+// ── Search needle ─────────────────────────────────────────────────────────────
+
+/// A search term plus the Find dialog options that apply to it.
+pub(crate) struct Needle {
+    /// The term; case-folded (see [`fold`]) when `match_case` is off.
+    bytes: Vec<u8>,
+    match_case: bool,
+    whole_word: bool,
+    /// The term is ASCII, so byte-wise folding of the text is enough.
+    ascii: bool,
+}
+
+impl Needle {
+    /// Without `match_case`, letters match in either case as in Scintilla's
+    /// UTF-8 search: É finds é, Ä finds ä.
+    pub(crate) fn new(text: &[u8], match_case: bool, whole_word: bool) -> Self {
+        let bytes = if match_case {
+            text.to_vec()
+        } else {
+            fold(text)
+        };
+        Self {
+            ascii: bytes.is_ascii(),
+            bytes,
+            match_case,
+            whole_word,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Does the needle match `buf` at `i`?  `at_start` / `at_end` say whether
+    /// the buffer edges are the file edges (for whole-word checks).
+    pub(crate) fn matches_at(&self, buf: &[u8], i: usize, at_start: bool, at_end: bool) -> bool {
+        let n = self.bytes.len();
+        let Some(hay) = buf.get(i..i + n) else {
+            return false;
+        };
+        let equal = if self.match_case {
+            hay == self.bytes.as_slice()
+        } else if self.ascii {
+            // Only ASCII folds to ASCII, so multi-byte text cannot match.
+            hay.iter()
+                .zip(&self.bytes)
+                .all(|(h, b)| h.to_ascii_lowercase() == *b)
+        } else {
+            // A byte that folds alike must lead the text; most positions
+            // fail here without folding anything.
+            let lead = |h: u8, b: u8| {
+                if b < 0x80 {
+                    h.to_ascii_lowercase() == b
+                } else {
+                    h >= 0xC0
+                }
+            };
+            lead(hay[0], self.bytes[0]) && fold(hay) == self.bytes
+        };
+        if !equal || !self.whole_word {
+            return equal;
+        }
+        let before = match i.checked_sub(1) {
+            Some(j) => !is_word_byte(buf[j]),
+            None => at_start,
+        };
+        let after = match buf.get(i + n) {
+            Some(&b) => !is_word_byte(b),
+            None => at_end,
+        };
+        before && after
+    }
+}
+
+/// `text` with each letter lowercased, where its lowercase is one
+/// character of the same UTF-8 length, so offsets into the folded text are
+/// offsets into `text`.  That covers the letters of European, Greek and
+/// Cyrillic scripts; the few that change length (the Kelvin sign, dotted
+/// İ) are left as they are.  Bytes that are not UTF-8 are kept.
+fn fold(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for chunk in text.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            let mut lower = c.to_lowercase();
+            let folded = match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            };
+            out.extend_from_slice(folded.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        out.extend_from_slice(chunk.invalid());
+    }
+    out
+}
+
+/// Scintilla's default word characters: ASCII alphanumerics, `_`, and every
+/// byte of a multi-byte UTF-8 sequence.
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+// ── Seeding a search ──────────────────────────────────────────────────────────
+
+/// The byte range of the word in `line` touching offset `at` (the caret may
+/// sit just after it), or `None` if `at` is not next to a word.
+pub(crate) fn word_around(line: &[u8], at: usize) -> Option<std::ops::Range<usize>> {
+    let at = at.min(line.len());
+    let start = at
+        - line[..at]
+            .iter()
+            .rev()
+            .take_while(|&&b| is_word_byte(b))
+            .count();
+    let end = at + line[at..].iter().take_while(|&&b| is_word_byte(b)).count();
+    (start < end).then_some(start..end)
+}
+
+/// The Find field's text for a selection: `None` when nothing or more than
+/// one line is selected.  Otherwise UTF-16 cut to at most `max_units` code
+/// units, never between the halves of a surrogate pair.
+pub(crate) fn seed_text(selected: &[u8], max_units: usize) -> Option<Vec<u16>> {
+    if selected.is_empty() || selected.contains(&b'\n') || selected.contains(&b'\r') {
+        return None;
+    }
+    let mut units = Vec::new();
+    for c in String::from_utf8_lossy(selected).chars() {
+        let mut buf = [0u16; 2];
+        let encoded = c.encode_utf16(&mut buf);
+        if units.len() + encoded.len() > max_units {
+            break;
+        }
+        units.extend_from_slice(encoded);
+    }
+    (!units.is_empty()).then_some(units)
+}
+
+// ── Extended mode ─────────────────────────────────────────────────────────────
+
+/// Expand the escapes of Search > Extended Mode in a find or replace string:
+/// `\n`, `\r`, `\t`, `\0`, `\\` and `\xNN` (two hex digits, the character
+/// U+00NN, so `\xE9` is `é` in the UTF-8 document rather than a stray byte).
+/// Any other backslash, including an incomplete `\x`, is kept as typed.
+pub(crate) fn unescape(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        if let (b'\\', Some(b'x')) = (text[i], text.get(i + 1)) {
+            if let Some(byte) = text.get(i + 2..i + 4).and_then(hex_byte) {
+                let mut buf = [0u8; 2];
+                out.extend_from_slice(char::from(byte).encode_utf8(&mut buf).as_bytes());
+                i += 4;
+                continue;
+            }
+        }
+        let (byte, used) = match (text[i], text.get(i + 1)) {
+            (b'\\', Some(b'n')) => (b'\n', 2),
+            (b'\\', Some(b'r')) => (b'\r', 2),
+            (b'\\', Some(b't')) => (b'\t', 2),
+            (b'\\', Some(b'0')) => (0, 2),
+            (b'\\', Some(b'\\')) => (b'\\', 2),
+            (b, _) => (b, 1),
+        };
+        out.push(byte);
+        i += used;
+    }
+    out
+}
+
+/// The byte spelled by two hex digits.
+fn hex_byte(digits: &[u8]) -> Option<u8> {
+    let text = std::str::from_utf8(digits).ok()?;
+    if !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u8::from_str_radix(text, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_word_at_the_caret() {
+        let line = b"let foo_bar = baz;";
+        assert_eq!(word_around(line, 6), Some(4..11));
+        assert_eq!(word_around(line, 11), Some(4..11));
+        assert_eq!(word_around(line, 4), Some(4..11));
+        assert_eq!(word_around(line, 12), None);
+        assert_eq!(word_around(line, 99), None);
+        assert_eq!(word_around(b"", 0), None);
+    }
+
+    #[test]
+    fn unescapes_extended_sequences() {
+        assert_eq!(unescape(br"a\r\nb\tc"), b"a\r\nb\tc");
+        assert_eq!(unescape(br"\0\x41\x7e"), b"\0A~");
+        assert_eq!(unescape(br"C:\\dir"), br"C:\dir");
+        assert_eq!(unescape(br"plain"), b"plain");
+    }
+
+    #[test]
+    fn hex_escapes_above_ascii_are_utf8() {
+        assert_eq!(unescape(br"caf\xE9"), "café".as_bytes());
+        assert_eq!(unescape(br"\xff"), "ÿ".as_bytes());
+    }
+
+    #[test]
+    fn keeps_unknown_and_incomplete_escapes() {
+        assert_eq!(unescape(br"\q"), br"\q");
+        assert_eq!(unescape(br"\x4"), br"\x4");
+        assert_eq!(unescape(br"\xZZ"), br"\xZZ");
+        assert_eq!(unescape(br"\x+1"), br"\x+1");
+        assert_eq!(unescape(br"end\"), br"end\");
+    }
+
+    #[test]
+    fn seed_text_is_one_line_and_fits_the_buffer() {
+        assert_eq!(
+            seed_text(b"needle", 511),
+            Some("needle".encode_utf16().collect())
+        );
+        assert_eq!(seed_text(b"", 511), None);
+        assert_eq!(seed_text(b"two\r\nlines", 511), None);
+        assert_eq!(
+            seed_text(b"abcdef", 3),
+            Some("abc".encode_utf16().collect())
+        );
+        // U+1F600 is a surrogate pair; a half would not fit.
+        assert_eq!(
+            seed_text("a\u{1F600}".as_bytes(), 2),
+            Some(vec![u16::from(b'a')])
+        );
+    }
+}
@@ -0,0 +1,360 @@
+// ── Background Replace All ────────────────────────────────────────────────────
+//
+// Replace All on a large document would freeze the UI if it drove Scintilla
+// one match at a time.  Instead the UI thread hands the whole text to a
+// `ReplaceJob`, which builds the replaced text on a worker thread and calls a
+// `notify` callback as it progresses and when it finishes; the Win32 layer
+// turns that into a posted message and applies the result as one target
+// replacement inside one undo action.
+//
+// Format > Convert line endings in Large File Mode uses the same job with
+// `convert_eols` as the work, so a huge document does not hang either.
+//
+// Replace All in Open Documents runs the same scan on the UI thread, one
+// tab at a time (`replace_now`), and reports with `open_documents_summary`.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use super::Needle;
+
+/// Bytes scanned between progress updates.
+const PROGRESS_STEP: usize = 1024 * 1024;
+
+/// The result of a finished job: the new text and the number of replacements.
+pub(crate) struct Replaced {
+    pub(crate) text: Vec<u8>,
+    pub(crate) count: usize,
+}
+
+/// UI-side handle to a running Replace All.
+///
+/// Dropping the handle cancels the job.
+pub(crate) struct ReplaceJob {
+    rx: Receiver<Replaced>,
+    cancel: Arc<AtomicBool>,
+    percent: Arc<AtomicU8>,
+    /// The replacing thread; taken by `take_worker` at shutdown.
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ReplaceJob {
+    /// Start replacing every match of `needle` in `text` with `replacement`.
+    ///
+    /// `notify` runs on the worker whenever the percentage changes and once
+    /// more when the result is ready.
+    pub(crate) fn spawn(
+        text: Vec<u8>,
+        needle: Needle,
+        replacement: Vec<u8>,
+        notify: impl Fn() + Send + 'static,
+    ) -> io::Result<Self> {
+        Self::start(notify, move |cancel, progress| {
+            replace_all(&text, &needle, &replacement, cancel, progress)
+        })
+    }
+
+    /// Start rewriting every line ending in `text` as `eol`.  The count in
+    /// the result is the number of line endings that changed.
+    pub(crate) fn spawn_eol(
+        text: Vec<u8>,
+        eol: &'static [u8],
+        notify: impl Fn() + Send + 'static,
+    ) -> io::Result<Self> {
+        Self::start(notify, move |cancel, progress| {
+            convert_eols(&text, eol, cancel, progress)
+        })
+    }
+
+    /// Run `work` on a worker thread, publishing its progress.
+    fn start(
+        notify: impl Fn() + Send + 'static,
+        work: impl FnOnce(&AtomicBool, &mut dyn FnMut(u8)) -> Option<Replaced> + Send + 'static,
+    ) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let percent = Arc::new(AtomicU8::new(0));
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_percent = Arc::clone(&percent);
+        let worker = std::thread::Builder::new()
+            .name("rivet-replace".to_owned())
+            .spawn(move || {
+                let mut progress = |p: u8| {
+                    worker_percent.store(p, Ordering::Relaxed);
+                    notify();
+                };
+                if let Some(done) = work(&worker_cancel, &mut progress) {
+                    // The UI may have dropped the job meanwhile.
+                    let _ = tx.send(done);
+                    notify();
+                }
+            })?;
+        Ok(Self {
+            rx,
+            cancel,
+            percent,
+            worker: Some(worker),
+        })
+    }
+
+    /// The result, once the worker has finished.
+    pub(crate) fn try_result(&self) -> Option<Replaced> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Percentage of the text scanned so far.
+    pub(crate) fn percent(&self) -> u8 {
+        self.percent.load(Ordering::Relaxed)
+    }
+
+    /// The replacing thread, so shutdown can wait for it after dropping `self`.
+    pub(crate) fn take_worker(&mut self) -> Option<JoinHandle<()>> {
+        self.worker.take()
+    }
+}
+
+impl Drop for ReplaceJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Replace every non-overlapping match, scanning left to right like
+/// Scintilla's target search.  `None` if `cancel` was set.
+fn replace_all(
+    text: &[u8],
+    needle: &Needle,
+    replacement: &[u8],
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(u8),
+) -> Option<Replaced> {
+    let n = needle.len();
+    let mut out = Vec::with_capacity(text.len());
+    let mut count = 0;
+    let mut copied = 0;
+    let mut i = 0;
+    let mut next_report = PROGRESS_STEP;
+    let mut reported = 0;
+    while n > 0 && i + n <= text.len() {
+        if i >= next_report {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            next_report += PROGRESS_STEP;
+            let p = (i * 100 / text.len()) as u8;
+            if p != reported {
+                reported = p;
+                progress(p);
+            }
+        }
+        if needle.matches_at(text, i, true, true) {
+            out.extend_from_slice(&text[copied..i]);
+            out.extend_from_slice(replacement);
+            count += 1;
+            i += n;
+            copied = i;
+        } else {
+            i += 1;
+        }
+    }
+    out.extend_from_slice(&text[copied..]);
+    Some(Replaced { text: out, count })
+}
+
+/// Rewrite every CRLF, LF and CR in `text` as `eol`, like Scintilla's
+/// `SCI_CONVERTEOLS`.  `None` if `cancel` was set.
+fn convert_eols(
+    text: &[u8],
+    eol: &[u8],
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(u8),
+) -> Option<Replaced> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut count = 0;
+    let mut i = 0;
+    let mut next_report = PROGRESS_STEP;
+    let mut reported = 0;
+    while let Some(at) = text[i..].iter().position(|&b| b == b'\r' || b == b'\n') {
+        let at = i + at;
+        if at >= next_report {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            next_report = at + PROGRESS_STEP;
+            let p = (at * 100 / text.len()) as u8;
+            if p != reported {
+                reported = p;
+                progress(p);
+            }
+        }
+        let len = if text[at..].starts_with(b"\r\n") {
+            2
+        } else {
+            1
+        };
+        out.extend_from_slice(&text[i..at]);
+        out.extend_from_slice(eol);
+        if text[at..at + len] != *eol {
+            count += 1;
+        }
+        i = at + len;
+    }
+    out.extend_from_slice(&text[i..]);
+    Some(Replaced { text: out, count })
+}
+
+// ── Replace in all open documents ─────────────────────────────────────────────
+
+/// Replace every match in `text` on the calling thread, for Replace All in
+/// Open Documents.
+pub(crate) fn replace_now(text: &[u8], needle: &Needle, replacement: &[u8]) -> Replaced {
+    replace_all(
+        text,
+        needle,
+        replacement,
+        &AtomicBool::new(false),
+        &mut |_| {},
+    )
+    .unwrap_or(Replaced {
+        text: text.to_vec(),
+        count: 0,
+    })
+}
+
+/// Report for Replace All in Open Documents: the total, then one line per
+/// document changed, then the documents that could not be changed.
+pub(crate) fn open_documents_summary(counts: &[(String, usize)], skipped: &[String]) -> String {
+    let changed: Vec<_> = counts.iter().filter(|(_, n)| *n > 0).collect();
+    let total: usize = changed.iter().map(|(_, n)| n).sum();
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+    let mut out = format!(
+        "{} made in {}.",
+        plural(total, "replacement"),
+        plural(changed.len(), "document")
+    );
+    if !changed.is_empty() {
+        out.push('\n');
+        for (name, n) in changed {
+            out.push_str(&format!("\n{name}: {n}"));
+        }
+    }
+    if !skipped.is_empty() {
+        out.push_str("\n\nNot changed (read-only, monitored or still loading):");
+        for name in skipped {
+            out.push_str(&format!("\n{name}"));
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &[u8], needle: Needle, repl: &[u8]) -> (Vec<u8>, usize) {
+        let done = replace_all(text, &needle, repl, &AtomicBool::new(false), &mut |_| {}).unwrap();
+        (done.text, done.count)
+    }
+
+    #[test]
+    fn replaces_non_overlapping_left_to_right() {
+        let (text, count) = run(b"aaaa", Needle::new(b"aa", true, false), b"b");
+        assert_eq!((text.as_slice(), count), (b"bb".as_slice(), 2));
+    }
+
+    #[test]
+    fn replacement_is_not_rescanned() {
+        let (text, count) = run(b"x.x", Needle::new(b"x", true, false), b"xx");
+        assert_eq!((text.as_slice(), count), (b"xx.xx".as_slice(), 2));
+    }
+
+    #[test]
+    fn honours_case_and_whole_word() {
+        let needle = Needle::new(b"CAT", false, true);
+        let (text, count) = run(b"cat concat Cat cat_", needle, b"dog");
+        assert_eq!(text, b"dog concat dog cat_");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn ignores_case_beyond_ascii() {
+        let needle = Needle::new("élan".as_bytes(), false, false);
+        let (text, count) = run("Élan, ÉLAN and élan".as_bytes(), needle, b"x");
+        assert_eq!(String::from_utf8(text).unwrap(), "x, x and x");
+        assert_eq!(count, 3);
+        let needle = Needle::new("ÄR".as_bytes(), true, false);
+        assert_eq!(run("är ÄR".as_bytes(), needle, b"y").1, 1);
+    }
+
+    #[test]
+    fn no_match_returns_text_unchanged() {
+        let (text, count) = run(b"hello", Needle::new(b"z", true, false), b"y");
+        assert_eq!((text.as_slice(), count), (b"hello".as_slice(), 0));
+    }
+
+    #[test]
+    fn cancel_stops_long_scan() {
+        let text = vec![b'a'; 3 * PROGRESS_STEP];
+        let needle = Needle::new(b"b", true, false);
+        let cancel = AtomicBool::new(true);
+        assert!(replace_all(&text, &needle, b"c", &cancel, &mut |_| {}).is_none());
+    }
+
+    #[test]
+    fn converts_every_line_ending() {
+        let text = b"a\r\nb\nc\rd\n\r";
+        let done = convert_eols(text, b"\n", &AtomicBool::new(false), &mut |_| {}).unwrap();
+        assert_eq!(done.text, b"a\nb\nc\nd\n\n");
+        assert_eq!(done.count, 3);
+        let done = convert_eols(b"x", b"\r\n", &AtomicBool::new(false), &mut |_| {}).unwrap();
+        assert_eq!((done.text.as_slice(), done.count), (b"x".as_slice(), 0));
+    }
+
+    #[test]
+    fn summary_lists_changed_and_skipped_documents() {
+        let counts = [
+            ("a.txt".to_owned(), 3),
+            ("b.txt".to_owned(), 0),
+            ("c.rs".to_owned(), 1),
+        ];
+        assert_eq!(
+            open_documents_summary(&counts, &["log.txt".to_owned()]),
+            "4 replacements made in 2 documents.\n\na.txt: 3\nc.rs: 1\n\n\
+             Not changed (read-only, monitored or still loading):\nlog.txt"
+        );
+        assert_eq!(
+            open_documents_summary(&counts[1..2], &[]),
+            "0 replacements made in 0 documents."
+        );
+        let (text, count) = {
+            let done = replace_now(b"a-a", &Needle::new(b"a", true, false), b"b");
+            (done.text, done.count)
+        };
+        assert_eq!((text.as_slice(), count), (b"b-b".as_slice(), 2));
+    }
+
+    #[test]
+    fn job_reports_result() {
+        let job = ReplaceJob::spawn(
+            b"one two one".to_vec(),
+            Needle::new(b"one", true, false),
+            b"1".to_vec(),
+            || {},
+        )
+        .unwrap();
+        let done = job.rx.recv().unwrap();
+        assert_eq!(done.text, b"1 two 1");
+        assert_eq!(done.count, 2);
+    }
+}
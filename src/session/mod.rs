@@ -1,12 +1,27 @@
 // ── Session persistence ───────────────────────────────────────────────────────
 //
-// Reads and writes `%APPDATA%\Rivet\session.json`.
+// Reads and writes `%APPDATA%\Rivet\session.json`, plus named workspaces —
+// the same format saved on demand under `%APPDATA%\Rivet\workspaces\`.
+// The periodic checkpoint also copies unsaved text to
+// `%APPDATA%\Rivet\backup\` so it survives a crash; with File > On
+// Startup > Keep Unsaved Changes an exit does the same on purpose.
+// Checkpoints are captured as a `Snapshot` and written by `writer`'s thread;
+// session and workspace files are replaced through a temporary file, never
+// truncated in place.
 // No `unsafe` — pure safe Rust + serde_json.
 
-use std::{fs, io, path::PathBuf};
+pub(crate) mod restore; // session file → tabs to reopen
+pub(crate) mod writer; // checkpoints written on a worker thread
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{app::DocumentState, editor::EditorView};
+
 // ── On-disk types ─────────────────────────────────────────────────────────────
 
 /// Root of the JSON session file.
@@ -14,12 +29,17 @@ use serde::{Deserialize, Serialize};
 pub(crate) struct SessionFile {
     pub(crate) version: u32,
     pub(crate) tabs: Vec<TabEntry>,
+    /// `order` of the tab that was active (the array index in old files).
     pub(crate) active_tab: usize,
     #[serde(default)] // backward-compat: old files without this field parse as false
     pub(crate) dark_mode: bool,
     /// 0 = Top, 1 = Left, 2 = Right.
     #[serde(default)]
     pub(crate) tab_position: u8,
+    /// The backups were kept by a clean exit rather than left by a crash,
+    /// so they are restored without asking.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) kept_unsaved: bool,
 }
 
 /// One entry per open tab.
@@ -35,6 +55,51 @@ pub(crate) struct TabEntry {
     pub(crate) encoding: String,
     /// EOL label, e.g. `"CRLF"`.
     pub(crate) eol: String,
+    /// Position of the tab in the tab strip.  Restore sorts by this rather
+    /// than by array position; files written before it existed omit it and
+    /// fall back to the array index (see [`SessionFile::tabs_in_order`]).
+    #[serde(default)]
+    pub(crate) order: Option<usize>,
+    /// File in [`backup_dir`] holding the tab's unsaved text (UTF-8), set
+    /// only by checkpoints taken while the tab was dirty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) backup: Option<String>,
+}
+
+impl TabEntry {
+    /// The entry for `doc`, shown in `view` at strip position `order`.
+    pub(crate) fn new(doc: &DocumentState, view: &dyn EditorView, order: usize) -> Self {
+        Self {
+            path: doc.path.as_ref().map(|p| p.to_string_lossy().into_owned()),
+            caret_pos: view.caret_pos(),
+            scroll_line: view.first_visible_line(),
+            encoding: doc.encoding.as_str().to_owned(),
+            eol: doc.eol.as_str().to_owned(),
+            order: Some(order),
+            backup: None,
+        }
+    }
+}
+
+impl SessionFile {
+    /// Whether any tab has a backup, i.e. the last checkpoint was taken with
+    /// unsaved changes and no clean exit followed.
+    pub(crate) fn has_backups(&self) -> bool {
+        self.tabs.iter().any(|t| t.backup.is_some())
+    }
+
+    /// Tab entries sorted by their strip position, each paired with that
+    /// position.  Ties keep file order.
+    pub(crate) fn tabs_in_order(&self) -> Vec<(usize, &TabEntry)> {
+        let mut tabs: Vec<(usize, &TabEntry)> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.order.unwrap_or(i), t))
+            .collect();
+        tabs.sort_by_key(|&(order, _)| order);
+        tabs
+    }
 }
 
 // ── Format version ────────────────────────────────────────────────────────────
@@ -54,35 +119,145 @@ pub(crate) fn session_path() -> Option<PathBuf> {
     Some(p)
 }
 
-// ── Save ──────────────────────────────────────────────────────────────────────
+/// Return the directory holding named workspaces:
+/// `%APPDATA%\Rivet\workspaces`.
+pub(crate) fn workspaces_dir() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("workspaces");
+    Some(p)
+}
 
-/// Write the session to `%APPDATA%\Rivet\session.json`.
+/// Return the directory holding unsaved-text backups:
+/// `%APPDATA%\Rivet\backup`.
+pub(crate) fn backup_dir() -> Option<PathBuf> {
+    let appdata = std::env::var_os("APPDATA")?;
+    let mut p = PathBuf::from(appdata);
+    p.push("Rivet");
+    p.push("backup");
+    Some(p)
+}
+
+/// Whether `name` can be used as a workspace name, i.e. as a file stem.
 ///
-/// Creates the `Rivet` directory if it does not exist.
-/// The caller (`window.rs`) silently discards any returned error.
-pub(crate) fn save(
+/// Rejects blank names, characters Windows forbids in file names, and a
+/// trailing dot or space (which Windows would silently strip).
+pub(crate) fn is_valid_workspace_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && !name.ends_with(['.', ' '])
+        && !name
+            .chars()
+            .any(|c| c.is_control() || "<>:\"/\\|?*".contains(c))
+}
+
+fn workspace_path(name: &str) -> io::Result<PathBuf> {
+    if !is_valid_workspace_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid workspace name",
+        ));
+    }
+    let mut p = workspaces_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+    p.push(format!("{name}.json"));
+    Ok(p)
+}
+
+// ── Save ──────────────────────────────────────────────────────────────────────
+
+/// Everything a session save writes, captured on the UI thread so that
+/// serializing and writing can happen on [`writer::SessionWriter`]'s thread.
+pub(crate) struct Snapshot {
+    pub(crate) tabs: Vec<TabEntry>,
+    /// Unsaved text to back up, by index into `tabs`.
+    pub(crate) backups: Vec<(usize, Vec<u8>)>,
+    pub(crate) active_tab: usize,
+    pub(crate) dark_mode: bool,
+    pub(crate) tab_position: u8,
+    /// See [`SessionFile::kept_unsaved`].
+    pub(crate) kept_unsaved: bool,
+}
+
+impl Snapshot {
+    /// Write the backups into [`backup_dir`], delete the ones no tab names
+    /// any more, then write `%APPDATA%\Rivet\session.json`.
+    pub(crate) fn write(mut self) -> io::Result<()> {
+        if let Some(dir) = backup_dir() {
+            for (i, text) in &self.backups {
+                self.tabs[*i].backup = write_backup(&dir, *i, text).ok();
+            }
+            let keep: Vec<&str> = self
+                .tabs
+                .iter()
+                .filter_map(|e| e.backup.as_deref())
+                .collect();
+            prune_backups(&dir, &keep);
+        }
+        let path = session_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
+        let sf = SessionFile {
+            version: SESSION_VERSION,
+            tabs: self.tabs,
+            active_tab: self.active_tab,
+            dark_mode: self.dark_mode,
+            tab_position: self.tab_position,
+            kept_unsaved: self.kept_unsaved,
+        };
+        write_file(&path, &sf)
+    }
+}
+
+/// Write the tabs as the workspace `name`, replacing any existing one.
+pub(crate) fn save_workspace(
+    name: &str,
     tabs: &[TabEntry],
     active_tab: usize,
     dark_mode: bool,
     tab_position: u8,
 ) -> io::Result<()> {
-    let path =
-        session_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA not set"))?;
-
-    if let Some(dir) = path.parent() {
-        fs::create_dir_all(dir)?;
-    }
+    save_to(
+        &workspace_path(name)?,
+        tabs,
+        active_tab,
+        dark_mode,
+        tab_position,
+    )
+}
 
+/// Write a session file to `path`, creating its folder.
+pub(crate) fn save_to(
+    path: &Path,
+    tabs: &[TabEntry],
+    active_tab: usize,
+    dark_mode: bool,
+    tab_position: u8,
+) -> io::Result<()> {
     let sf = SessionFile {
         version: SESSION_VERSION,
         tabs: tabs.to_vec(),
         active_tab,
         dark_mode,
         tab_position,
+        kept_unsaved: false,
     };
+    write_file(path, &sf)
+}
 
-    let file = fs::File::create(&path)?;
-    serde_json::to_writer_pretty(file, &sf).map_err(io::Error::other)
+/// Write `sf` to `path` through a temporary file renamed over it, so a
+/// crash mid-write leaves the previous file intact.
+fn write_file(path: &Path, sf: &SessionFile) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let file = fs::File::create(&tmp)?;
+    serde_json::to_writer_pretty(&file, sf).map_err(io::Error::other)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)
 }
 
 // ── Load ──────────────────────────────────────────────────────────────────────
@@ -92,8 +267,17 @@ pub(crate) fn save(
 /// Returns `None` on any error: file missing, JSON parse failure, or an
 /// unrecognised version number.  The app continues with a fresh untitled tab.
 pub(crate) fn load() -> Option<SessionFile> {
-    let path = session_path()?;
-    let data = fs::read(&path).ok()?;
+    load_from(&session_path()?)
+}
+
+/// Read the workspace `name`; `None` under the same conditions as [`load`].
+pub(crate) fn load_workspace(name: &str) -> Option<SessionFile> {
+    load_from(&workspace_path(name).ok()?)
+}
+
+/// Read the session file at `path`; `None` under the same conditions as [`load`].
+pub(crate) fn load_from(path: &Path) -> Option<SessionFile> {
+    let data = fs::read(path).ok()?;
     let sf: SessionFile = serde_json::from_slice(&data).ok()?;
     if sf.version != SESSION_VERSION {
         return None;
@@ -101,6 +285,82 @@ pub(crate) fn load() -> Option<SessionFile> {
     Some(sf)
 }
 
+/// The deepest folder containing every file in `paths`: the folder a
+/// workspace is shown relative to.  `None` without paths or when they share
+/// no folder (e.g. different drives).
+pub(crate) fn common_root<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Option<PathBuf> {
+    let mut root: Option<PathBuf> = None;
+    for path in paths {
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty())?;
+        root = Some(match root {
+            None => dir.to_path_buf(),
+            Some(r) => r
+                .ancestors()
+                .find(|a| dir.starts_with(a))
+                .filter(|a| !a.as_os_str().is_empty())?
+                .to_path_buf(),
+        });
+    }
+    root
+}
+
+// ── Workspace list ────────────────────────────────────────────────────────────
+
+/// Names of the saved workspaces, sorted case-insensitively.  Empty if the
+/// directory does not exist yet.
+pub(crate) fn list_workspaces() -> Vec<String> {
+    workspaces_dir()
+        .map(|dir| names_in(&dir))
+        .unwrap_or_default()
+}
+
+fn names_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "json"))
+        .filter_map(|p| Some(p.file_stem()?.to_str()?.to_owned()))
+        .collect();
+    names.sort_by_key(|n| n.to_lowercase());
+    names
+}
+
+// ── Backups ───────────────────────────────────────────────────────────────────
+
+/// Write the unsaved `text` of the tab at strip position `order` into `dir`
+/// and return the file name to record in its [`TabEntry::backup`].
+pub(crate) fn write_backup(dir: &Path, order: usize, text: &[u8]) -> io::Result<String> {
+    fs::create_dir_all(dir)?;
+    let name = format!("tab-{order}.txt");
+    fs::write(dir.join(&name), text)?;
+    Ok(name)
+}
+
+/// Read the backup `name` from `dir`.  `None` if it is missing or `name` is
+/// not a plain file name (the session file is user-editable).
+pub(crate) fn read_backup(dir: &Path, name: &str) -> Option<Vec<u8>> {
+    if Path::new(name).file_name()? != name {
+        return None;
+    }
+    fs::read(dir.join(name)).ok()
+}
+
+/// Delete every backup in `dir` except those named in `keep`.
+pub(crate) fn prune_backups(dir: &Path, keep: &[&str]) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        if !keep.iter().any(|k| name == *k) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -114,6 +374,8 @@ mod tests {
             scroll_line: 2,
             encoding: "UTF-8".to_owned(),
             eol: "CRLF".to_owned(),
+            order: None,
+            backup: None,
         }
     }
 
@@ -125,6 +387,7 @@ mod tests {
             active_tab: 1,
             dark_mode: true,
             tab_position: 0,
+            kept_unsaved: false,
         };
         let json = serde_json::to_string(&sf).expect("serialize");
         let sf2: SessionFile = serde_json::from_str(&json).expect("deserialize");
@@ -149,6 +412,7 @@ mod tests {
             active_tab: 0,
             dark_mode: false,
             tab_position: 0,
+            kept_unsaved: false,
         };
         let json = serde_json::to_string(&sf).expect("serialize");
         let sf2: SessionFile = serde_json::from_str(&json).expect("deserialize");
@@ -174,6 +438,7 @@ mod tests {
             active_tab: 0,
             dark_mode: false,
             tab_position: 0,
+            kept_unsaved: false,
         };
         let json = serde_json::to_string(&sf).expect("serialize");
         let parsed: SessionFile = serde_json::from_str(&json).expect("deserialize");
@@ -181,6 +446,95 @@ mod tests {
         assert_ne!(parsed.version, SESSION_VERSION);
     }
 
+    #[test]
+    fn tabs_sorted_by_explicit_order() {
+        let mut a = make_tab(Some("a"));
+        a.order = Some(2);
+        let mut b = make_tab(Some("b"));
+        b.order = Some(0);
+        let mut c = make_tab(Some("c"));
+        c.order = Some(1);
+        let sf = SessionFile {
+            version: SESSION_VERSION,
+            tabs: vec![a, b, c],
+            active_tab: 0,
+            dark_mode: false,
+            tab_position: 0,
+            kept_unsaved: false,
+        };
+        let paths: Vec<_> = sf
+            .tabs_in_order()
+            .iter()
+            .map(|(o, t)| (*o, t.path.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (0, "b".to_owned()),
+                (1, "c".to_owned()),
+                (2, "a".to_owned())
+            ]
+        );
+    }
+
+    /// Files written before `order` existed fall back to array position.
+    #[test]
+    fn missing_order_uses_array_index() {
+        let json = r#"{"version":1,"active_tab":1,"tabs":[
+            {"path":"x","caret_pos":0,"scroll_line":0,"encoding":"UTF-8","eol":"CRLF"},
+            {"path":"y","caret_pos":0,"scroll_line":0,"encoding":"UTF-8","eol":"CRLF"}]}"#;
+        let sf: SessionFile = serde_json::from_str(json).expect("deserialize old format");
+        let orders: Vec<usize> = sf.tabs_in_order().iter().map(|(o, _)| *o).collect();
+        assert_eq!(orders, [0, 1]);
+    }
+
+    #[test]
+    fn workspace_names_validated() {
+        assert!(is_valid_workspace_name("Client work"));
+        assert!(is_valid_workspace_name("v1.2"));
+        assert!(!is_valid_workspace_name(""));
+        assert!(!is_valid_workspace_name("   "));
+        assert!(!is_valid_workspace_name("a/b"));
+        assert!(!is_valid_workspace_name("what?"));
+        assert!(!is_valid_workspace_name("trailing."));
+        assert!(!is_valid_workspace_name("tab\there"));
+    }
+
+    #[test]
+    fn workspace_files_roundtrip_and_list() {
+        let dir = std::env::temp_dir().join(format!("rivet-ws-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(names_in(&dir).is_empty(), "missing dir lists nothing");
+
+        let tabs = [make_tab(Some("C:\\a.txt"))];
+        save_to(&dir.join("beta.json"), &tabs, 0, true, 2).expect("save beta");
+        save_to(&dir.join("Alpha.json"), &[], 0, false, 0).expect("save alpha");
+        fs::write(dir.join("notes.txt"), b"x").expect("write stray file");
+
+        assert_eq!(names_in(&dir), ["Alpha", "beta"]);
+        let sf = load_from(&dir.join("beta.json")).expect("load beta");
+        assert_eq!(sf.tabs[0].path.as_deref(), Some("C:\\a.txt"));
+        assert_eq!(sf.tab_position, 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn common_root_of_workspace_files() {
+        let p = |s: &str| PathBuf::from(s);
+        let files = [p("/w/src/a.rs"), p("/w/src/ui/b.rs"), p("/w/README.md")];
+        assert_eq!(
+            common_root(files.iter().map(PathBuf::as_path)),
+            Some(p("/w"))
+        );
+        assert_eq!(common_root([Path::new("/w/x/a.txt")]), Some(p("/w/x")));
+        assert_eq!(
+            common_root([Path::new("a.txt"), Path::new("/w/b.txt")]),
+            None
+        );
+        assert_eq!(common_root(std::iter::empty()), None);
+    }
+
     #[test]
     fn tab_entry_with_none_path_roundtrips() {
         let sf = SessionFile {
@@ -189,9 +543,68 @@ mod tests {
             active_tab: 0,
             dark_mode: false,
             tab_position: 0,
+            kept_unsaved: false,
         };
         let json = serde_json::to_string(&sf).expect("serialize");
         let sf2: SessionFile = serde_json::from_str(&json).expect("deserialize");
         assert_eq!(sf2.tabs[0].path, None);
     }
+
+    #[test]
+    fn backups_roundtrip_and_prune() {
+        let dir = std::env::temp_dir().join(format!("rivet-backup-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let a = write_backup(&dir, 0, b"unsaved a").expect("write a");
+        let b = write_backup(&dir, 3, b"unsaved b").expect("write b");
+        assert_eq!(read_backup(&dir, &b).as_deref(), Some(&b"unsaved b"[..]));
+        assert_eq!(read_backup(&dir, "../session.json"), None);
+
+        prune_backups(&dir, &[b.as_str()]);
+        assert_eq!(read_backup(&dir, &a), None);
+        assert!(read_backup(&dir, &b).is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn backup_is_omitted_when_absent() {
+        let json = serde_json::to_string(&make_tab(None)).expect("serialize");
+        assert!(!json.contains("backup"));
+        let mut tab = make_tab(Some("C:\\a.txt"));
+        tab.backup = Some("tab-0.txt".to_owned());
+        let sf = SessionFile {
+            version: SESSION_VERSION,
+            tabs: vec![tab],
+            active_tab: 0,
+            dark_mode: false,
+            tab_position: 0,
+            kept_unsaved: false,
+        };
+        let sf2: SessionFile =
+            serde_json::from_str(&serde_json::to_string(&sf).unwrap()).expect("deserialize");
+        assert!(sf2.has_backups());
+        assert!(!sf2.kept_unsaved);
+    }
+
+    #[test]
+    fn kept_unsaved_roundtrips_and_defaults_to_false() {
+        let mut tab = make_tab(Some("C:\\a.txt"));
+        tab.backup = Some("tab-0.txt".to_owned());
+        let sf = SessionFile {
+            version: SESSION_VERSION,
+            tabs: vec![tab],
+            active_tab: 0,
+            dark_mode: false,
+            tab_position: 0,
+            kept_unsaved: true,
+        };
+        let json = serde_json::to_string(&sf).expect("serialize");
+        let sf2: SessionFile = serde_json::from_str(&json).expect("deserialize");
+        assert!(sf2.kept_unsaved && sf2.has_backups());
+
+        let old = json.replace(",\"kept_unsaved\":true", "");
+        assert!(!old.contains("kept_unsaved"));
+        let sf3: SessionFile = serde_json::from_str(&old).expect("deserialize");
+        assert!(!sf3.kept_unsaved);
+    }
 }
@@ -0,0 +1,204 @@
+// ── Reopening a saved session ─────────────────────────────────────────────────
+//
+// Turns a `SessionFile` into the tabs to open, in saved strip order, with
+// each file's bytes read and its backup (if recovering) attached.  The main
+// window's `restore_tabs` and the headless harness both walk this list, so
+// which tabs come back, in what form and which one is activated is decided
+// here once; only how a tab is put on screen differs between them.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::path::{Path, PathBuf};
+
+use super::{read_backup, SessionFile, TabEntry};
+use crate::{app::Encoding, editor::HUGE_FILE_THRESHOLD_BYTES, error::FileProblem};
+
+/// How a saved tab comes back.
+pub(crate) enum Source {
+    /// An untitled or since-deleted document: only its backup returns.
+    Untitled,
+    /// A file too large to load, reopened in the viewer at its start.
+    Viewer(PathBuf),
+    /// A file read whole.  A file last saved in a chosen code page is read
+    /// back in it; ANSI is detected as usual.
+    File {
+        path: PathBuf,
+        bytes: Vec<u8>,
+        code_page: Option<Encoding>,
+    },
+}
+
+/// One tab to reopen.
+pub(crate) struct Reopened {
+    pub(crate) source: Source,
+    /// Unsaved text recovered from the backup folder.
+    pub(crate) backup: Option<Vec<u8>>,
+    pub(crate) caret_pos: usize,
+    pub(crate) scroll_line: usize,
+    /// Whether the tab is the saved active tab or comes before it in strip
+    /// order.  The last such tab reopened is activated, so a lost active tab
+    /// falls back to the nearest one before it.
+    pub(crate) up_to_active: bool,
+}
+
+/// The tabs of `sf` to reopen, in strip order.  Backups are read from
+/// `backup_dir` when recovering.  A file that exists but cannot be read (or
+/// reached) is an `Err` to report; a deleted file or an untitled tab without
+/// a backup is left out.
+pub(crate) fn reopen<'a>(
+    sf: &'a SessionFile,
+    backup_dir: Option<&'a Path>,
+) -> impl Iterator<Item = Result<Reopened, (PathBuf, FileProblem)>> + 'a {
+    sf.tabs_in_order()
+        .into_iter()
+        .filter_map(move |(order, entry)| reopen_one(entry, order <= sf.active_tab, backup_dir))
+}
+
+fn reopen_one(
+    entry: &TabEntry,
+    up_to_active: bool,
+    backup_dir: Option<&Path>,
+) -> Option<Result<Reopened, (PathBuf, FileProblem)>> {
+    let backup = backup_dir
+        .zip(entry.backup.as_deref())
+        .and_then(|(dir, name)| read_backup(dir, name));
+    let tab = |source, backup| Reopened {
+        source,
+        backup,
+        caret_pos: entry.caret_pos,
+        scroll_line: entry.scroll_line,
+        up_to_active,
+    };
+    let path = entry
+        .path
+        .as_deref()
+        .map(|p| crate::paths::normalize(Path::new(p)));
+    // A file on a share that cannot be reached is not the same as a deleted
+    // one: report it instead of dropping it quietly.
+    let meta = match path.as_deref().map(std::fs::metadata) {
+        Some(Ok(meta)) => meta,
+        Some(Err(e)) if FileProblem::of(&e) != FileProblem::NotFound => {
+            return Some(Err((path?, FileProblem::of(&e))));
+        }
+        _ => return backup.map(|text| Ok(tab(Source::Untitled, Some(text)))),
+    };
+    let path = path?;
+    if meta.len() > HUGE_FILE_THRESHOLD_BYTES {
+        return Some(Ok(tab(Source::Viewer(path), None)));
+    }
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) => return Some(Err((path, FileProblem::of(&e)))),
+    };
+    let code_page =
+        Encoding::from_str(&entry.encoding).filter(|e| matches!(e, Encoding::CodePage(_)));
+    Some(Ok(tab(
+        Source::File {
+            path,
+            bytes,
+            code_page,
+        },
+        backup,
+    )))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::session::{write_backup, SESSION_VERSION};
+
+    fn tab(path: Option<&Path>, order: usize, backup: Option<String>) -> TabEntry {
+        TabEntry {
+            path: path.map(|p| p.to_string_lossy().into_owned()),
+            caret_pos: 3,
+            scroll_line: 1,
+            encoding: "UTF-8".to_owned(),
+            eol: "LF".to_owned(),
+            order: Some(order),
+            backup,
+        }
+    }
+
+    fn session(tabs: Vec<TabEntry>, active_tab: usize) -> SessionFile {
+        SessionFile {
+            version: SESSION_VERSION,
+            tabs,
+            active_tab,
+            dark_mode: false,
+            tab_position: 0,
+            kept_unsaved: false,
+        }
+    }
+
+    #[test]
+    fn reopens_in_strip_order_and_skips_what_is_gone() {
+        let dir = std::env::temp_dir().join(format!("rivet-reopen-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.txt");
+        let b = dir.join("b.txt");
+        fs::write(&a, b"alpha").unwrap();
+        fs::write(&b, b"bravo").unwrap();
+        // Strip order: b, gone (active), untitled, a.
+        let sf = session(
+            vec![
+                tab(Some(&a), 3, None),
+                tab(Some(&dir.join("gone.txt")), 1, None),
+                tab(None, 2, None),
+                tab(Some(&b), 0, None),
+            ],
+            1,
+        );
+
+        let tabs: Vec<Reopened> = reopen(&sf, None).map(Result::unwrap).collect();
+        let paths: Vec<&Path> = tabs
+            .iter()
+            .map(|t| match &t.source {
+                Source::File { path, bytes, .. } => {
+                    assert!(!bytes.is_empty());
+                    path.as_path()
+                }
+                _ => panic!("expected a file"),
+            })
+            .collect();
+        assert_eq!(paths, [b.as_path(), a.as_path()]);
+        // The active tab is gone, so the one before it is activated.
+        assert!(tabs[0].up_to_active);
+        assert!(!tabs[1].up_to_active);
+        assert_eq!((tabs[0].caret_pos, tabs[0].scroll_line), (3, 1));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recovers_backups_only_when_asked() {
+        let dir = std::env::temp_dir().join(format!("rivet-recover-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let file = dir.join("a.txt");
+        let name = write_backup(&dir, 0, b"unsaved").unwrap();
+        fs::write(&file, b"saved").unwrap();
+        let sf = session(
+            vec![
+                tab(Some(&file), 0, Some(name.clone())),
+                tab(None, 1, Some(name)),
+            ],
+            0,
+        );
+
+        let tabs: Vec<Reopened> = reopen(&sf, Some(&dir)).map(Result::unwrap).collect();
+        assert_eq!(tabs.len(), 2);
+        assert!(matches!(tabs[0].source, Source::File { .. }));
+        assert_eq!(tabs[0].backup.as_deref(), Some(&b"unsaved"[..]));
+        assert!(matches!(tabs[1].source, Source::Untitled));
+
+        let tabs: Vec<Reopened> = reopen(&sf, None).map(Result::unwrap).collect();
+        assert_eq!(tabs.len(), 1);
+        assert!(tabs[0].backup.is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,127 @@
+// ── Background session writes ─────────────────────────────────────────────────
+//
+// The 30-second checkpoint used to serialize the session and copy unsaved
+// text to the backup folder on the UI thread.  Now the UI thread only
+// captures a `Snapshot` (tab entries plus the text of dirty tabs) and hands
+// it to a `SessionWriter`, whose thread does the JSON and the file I/O.
+// Snapshots that queue up behind a slow disk are skipped in favour of the
+// newest.  At exit the final save goes through the writer too (`finish`),
+// which is then joined without a time limit, so nothing queued can land
+// after it.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+    thread::JoinHandle,
+};
+
+use super::Snapshot;
+
+/// UI-side handle to the session-writing thread.
+pub(crate) struct SessionWriter {
+    tx: Sender<Snapshot>,
+    worker: JoinHandle<()>,
+}
+
+impl SessionWriter {
+    /// Start the thread that writes snapshots with [`Snapshot::write`].
+    /// Write errors are discarded, as a missed checkpoint is retried by the
+    /// next one.
+    pub(crate) fn spawn() -> io::Result<Self> {
+        Self::spawn_with(|snapshot| {
+            let _ = snapshot.write();
+        })
+    }
+
+    fn spawn_with(write: impl Fn(Snapshot) + Send + 'static) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("rivet-session".to_owned())
+            .spawn(move || {
+                while let Some(snapshot) = newest(&rx) {
+                    write(snapshot);
+                }
+            })?;
+        Ok(Self { tx, worker })
+    }
+
+    /// Queue `snapshot` to be written.
+    pub(crate) fn send(&self, snapshot: Snapshot) {
+        // The thread only stops once `self` is closed.
+        let _ = self.tx.send(snapshot);
+    }
+
+    /// Write `last` after anything still queued (or instead of it) and wait
+    /// for the thread to exit.  Not bounded by the shutdown timeout: a
+    /// checkpoint left running could be renamed over the exit save.
+    pub(crate) fn finish(self, last: Snapshot) {
+        self.send(last);
+        self.close();
+    }
+
+    /// Stop taking snapshots and wait until the thread has written the
+    /// newest one queued and exited.
+    pub(crate) fn close(self) {
+        drop(self.tx);
+        let _ = self.worker.join();
+    }
+}
+
+/// Block for the next snapshot, then skip to the newest one queued.  `None`
+/// once the writer is closed and the queue is empty.
+fn newest(rx: &Receiver<Snapshot>) -> Option<Snapshot> {
+    let mut snapshot = rx.recv().ok()?;
+    while let Ok(next) = rx.try_recv() {
+        snapshot = next;
+    }
+    Some(snapshot)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(active_tab: usize) -> Snapshot {
+        Snapshot {
+            tabs: Vec::new(),
+            backups: Vec::new(),
+            active_tab,
+            dark_mode: false,
+            tab_position: 0,
+            kept_unsaved: false,
+        }
+    }
+
+    #[test]
+    fn newest_snapshot_is_written_before_close_returns() {
+        let (tx, rx) = mpsc::channel();
+        let writer = SessionWriter::spawn_with(move |s| tx.send(s.active_tab).unwrap()).unwrap();
+        for i in 0..5 {
+            writer.send(snapshot(i));
+        }
+        writer.close();
+        let written: Vec<usize> = rx.try_iter().collect();
+        assert_eq!(written.last(), Some(&4));
+        assert!(written.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn finish_writes_the_final_snapshot_last() {
+        let (tx, rx) = mpsc::channel();
+        let writer = SessionWriter::spawn_with(move |s| {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            tx.send(s.active_tab).unwrap();
+        })
+        .unwrap();
+        for i in 0..3 {
+            writer.send(snapshot(i));
+        }
+        writer.finish(snapshot(99));
+        let written: Vec<usize> = rx.try_iter().collect();
+        assert_eq!(written.last(), Some(&99));
+    }
+}
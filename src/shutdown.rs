@@ -0,0 +1,71 @@
+// ── Orderly shutdown ──────────────────────────────────────────────────────────
+//
+// Background jobs (chunked loads, Replace All, the task pool's workers) run
+// on their own threads.  On exit the Win32 layer takes each job's thread
+// handle, drops the job — which sets its cancel flag and hangs up its
+// channel — and then waits here for the threads against one deadline.  A
+// wedged worker therefore cannot hang the exit, and no worker is still
+// running when the session checkpoint, always the last write, is saved.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Longest the exit waits for background threads in total.
+pub(crate) const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Interval between checks on threads that have not finished yet.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Wait for every thread in `threads` to finish, for at most `timeout`.
+///
+/// Finished threads are joined (a panic in one is ignored).  Returns how many
+/// were still running at the deadline; those are left detached.
+pub(crate) fn join_all(mut threads: Vec<JoinHandle<()>>, timeout: Duration) -> usize {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let (done, running): (Vec<_>, Vec<_>) =
+            threads.into_iter().partition(JoinHandle::is_finished);
+        for t in done {
+            let _ = t.join();
+        }
+        threads = running;
+        let now = Instant::now();
+        if threads.is_empty() || now >= deadline {
+            return threads.len();
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn joins_threads_that_finish() {
+        let threads = (0..3)
+            .map(|_| thread::spawn(|| thread::sleep(Duration::from_millis(5))))
+            .collect();
+        assert_eq!(join_all(threads, Duration::from_secs(5)), 0);
+    }
+
+    #[test]
+    fn gives_up_on_stuck_threads_at_the_deadline() {
+        let (tx, rx) = mpsc::channel::<()>();
+        let stuck = thread::spawn(move || {
+            let _ = rx.recv();
+        });
+        let quick = thread::spawn(|| {});
+        let start = Instant::now();
+        assert_eq!(join_all(vec![stuck, quick], Duration::from_millis(50)), 1);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(tx);
+    }
+}
@@ -0,0 +1,139 @@
+// ── File monitoring (tail -f) ─────────────────────────────────────────────────
+//
+// A monitored tab remembers how many bytes of its file it has shown.  Each
+// poll reads whatever was appended since, decodes it in the document's
+// encoding with a resumed `StreamDecoder`, and hands the UTF-8 to the view.
+// A file that shrinks (log rotation, truncation) starts over from the top.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::{
+    app::{AnsiCodec, Encoding},
+    loader::StreamDecoder,
+};
+
+/// Most bytes taken in one poll, so a burst of output cannot stall the UI;
+/// the rest arrives on later polls.
+const MAX_POLL_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Outcome of one poll.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TailEvent {
+    /// Nothing new.
+    Unchanged,
+    /// New text (UTF-8) to append to the view.
+    Appended(Vec<u8>),
+    /// The file shrank.  Clear the view; the whole file follows as
+    /// `Appended` on the next polls.
+    Truncated,
+}
+
+/// Follows appends to one file.
+pub(crate) struct Tail {
+    /// Bytes of the file already shown.
+    offset: u64,
+    decoder: StreamDecoder,
+    /// Code page for an ANSI file, kept for restarting after a truncation.
+    ansi: AnsiCodec,
+}
+
+impl Tail {
+    /// Follow a file whose first `offset` bytes are already shown, decoded as
+    /// `encoding`.
+    pub(crate) fn new(offset: u64, encoding: Encoding, ansi: AnsiCodec) -> Self {
+        Self {
+            offset,
+            decoder: StreamDecoder::resume(encoding, ansi),
+            ansi,
+        }
+    }
+
+    /// Check `path` for appended bytes.
+    pub(crate) fn poll(&mut self, path: &Path) -> io::Result<TailEvent> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            // Re-detect the BOM from the top.
+            self.decoder = StreamDecoder::new(self.ansi);
+            return Ok(TailEvent::Truncated);
+        }
+        if len == self.offset {
+            return Ok(TailEvent::Unchanged);
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take((len - self.offset).min(MAX_POLL_BYTES))
+            .read_to_end(&mut buf)?;
+        self.offset += buf.len() as u64;
+        Ok(TailEvent::Appended(self.decoder.push(&buf)))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn temp(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn append(path: &Path, bytes: &[u8]) {
+        let mut f = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        f.write_all(bytes).unwrap();
+    }
+
+    #[test]
+    fn follows_appends() {
+        let path = temp("rivet_tail_follows.log", b"one\n");
+        let mut tail = Tail::new(4, Encoding::Utf8, AnsiCodec::default());
+        assert_eq!(tail.poll(&path).unwrap(), TailEvent::Unchanged);
+        append(&path, b"two\n");
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            TailEvent::Appended(b"two\n".to_vec())
+        );
+        assert_eq!(tail.poll(&path).unwrap(), TailEvent::Unchanged);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn restarts_after_truncation() {
+        let path = temp("rivet_tail_truncated.log", b"old old old\n");
+        let mut tail = Tail::new(12, Encoding::Utf8, AnsiCodec::default());
+        std::fs::write(&path, b"new\n").unwrap();
+        assert_eq!(tail.poll(&path).unwrap(), TailEvent::Truncated);
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            TailEvent::Appended(b"new\n".to_vec())
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn decodes_utf16_appends() {
+        let path = temp("rivet_tail_utf16.log", &[0xFF, 0xFE, b'a', 0]);
+        let mut tail = Tail::new(4, Encoding::Utf16Le, AnsiCodec::default());
+        // Half a code unit first: held back until the rest arrives.
+        append(&path, b"b");
+        assert_eq!(tail.poll(&path).unwrap(), TailEvent::Appended(Vec::new()));
+        append(&path, &[0]);
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            TailEvent::Appended(b"b".to_vec())
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
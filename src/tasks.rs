@@ -0,0 +1,158 @@
+// ── Background task pool ──────────────────────────────────────────────────────
+//
+// Short jobs that must not block the UI (a git gutter diff, and whatever
+// follows) run on a small fixed pool of worker threads instead of a thread
+// each.  A job is any `FnOnce() + Send`; it reports back by posting to the
+// window through `platform::win32::bridge::UiSender`, so it never needs to
+// know about the window's state or its message numbers.
+//
+// Long jobs with their own cancel flag and progress (chunked loads, Replace
+// All) keep a thread each: they would hold a pool thread for their whole run.
+//
+// On exit `close` stops the pool: jobs still queued are dropped unrun, the
+// ones running finish, and the threads are handed to `shutdown::join_all`.
+//
+// No `unsafe` — pure safe Rust.
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+/// Most threads in a pool; fewer on machines with fewer cores.
+const MAX_THREADS: usize = 4;
+
+/// A queued job.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of worker threads fed from one queue.
+pub(crate) struct Pool {
+    tx: Option<Sender<Job>>,
+    /// Set by `close`: workers drop queued jobs instead of running them.
+    closing: Arc<AtomicBool>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Pool {
+    /// Start a pool sized to the machine: one thread per core, at least two
+    /// and at most `MAX_THREADS`.
+    pub(crate) fn new() -> io::Result<Self> {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self::with_threads(cores.clamp(2, MAX_THREADS))
+    }
+
+    /// Start a pool of `threads` workers (at least one).
+    pub(crate) fn with_threads(threads: usize) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        let closing = Arc::new(AtomicBool::new(false));
+        let mut workers = Vec::new();
+        for i in 0..threads.max(1) {
+            let rx = Arc::clone(&rx);
+            let closing = Arc::clone(&closing);
+            let worker = std::thread::Builder::new()
+                .name(format!("rivet-task-{i}"))
+                .spawn(move || work(&rx, &closing))?;
+            workers.push(worker);
+        }
+        Ok(Self {
+            tx: Some(tx),
+            closing,
+            workers,
+        })
+    }
+
+    /// Queue `job` to run on the next free worker.  Returns `false`, dropping
+    /// the job, if no worker is left to run it.
+    pub(crate) fn spawn(&self, job: impl FnOnce() + Send + 'static) -> bool {
+        self.tx
+            .as_ref()
+            .is_some_and(|tx| tx.send(Box::new(job)).is_ok())
+    }
+
+    /// Stop taking jobs and drop the queued ones; return the worker threads
+    /// so shutdown can wait for the jobs still running.
+    pub(crate) fn close(mut self) -> Vec<JoinHandle<()>> {
+        self.closing.store(true, Ordering::Relaxed);
+        self.tx = None;
+        std::mem::take(&mut self.workers)
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        // Hanging up lets the workers exit once the queue is empty; they are
+        // not waited for.
+        self.closing.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Worker body: run jobs until the queue hangs up, skipping them once the
+/// pool is closing.
+fn work(rx: &Mutex<Receiver<Job>>, closing: &AtomicBool) {
+    loop {
+        // The lock is held only while waiting, never while a job runs.  A
+        // worker that panicked in a job did so outside the lock, so it is
+        // never poisoned; recover anyway rather than stop the pool.
+        let job = match rx.lock() {
+            Ok(rx) => rx.recv(),
+            Err(poisoned) => poisoned.into_inner().recv(),
+        };
+        let Ok(job) = job else {
+            return;
+        };
+        if !closing.load(Ordering::Relaxed) {
+            job();
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn runs_every_job() {
+        let pool = Pool::with_threads(2).unwrap();
+        let (tx, rx) = mpsc::channel();
+        for i in 0..10 {
+            let tx = tx.clone();
+            assert!(pool.spawn(move || tx.send(i).unwrap()));
+        }
+        let mut got: Vec<i32> = (0..10).map(|_| rx.recv().unwrap()).collect();
+        got.sort_unstable();
+        assert_eq!(got, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn close_drops_queued_jobs_and_returns_threads() {
+        let pool = Pool::with_threads(1).unwrap();
+        let (started_tx, started) = mpsc::channel();
+        let (release_tx, release) = mpsc::channel::<()>();
+        pool.spawn(move || {
+            started_tx.send(()).unwrap();
+            let _ = release.recv();
+        });
+        let (ran_tx, ran) = mpsc::channel::<()>();
+        pool.spawn(move || ran_tx.send(()).unwrap());
+        started.recv().unwrap();
+
+        let threads = pool.close();
+        assert_eq!(threads.len(), 1);
+        release_tx.send(()).unwrap();
+        assert_eq!(
+            crate::shutdown::join_all(threads, Duration::from_secs(5)),
+            0
+        );
+        // The queued job was dropped, hanging up its sender unrun.
+        assert!(ran.recv().is_err());
+    }
+}
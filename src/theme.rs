@@ -10,167 +10,166 @@
 //     the API.
 
 use crate::{
-    editor::scintilla::{
-        messages::{
-            SCE_BAT_COMMAND,
-            // SCLEX_BATCH token styles
-            SCE_BAT_COMMENT,
-            SCE_BAT_LABEL,
-            SCE_BAT_OPERATOR,
-            SCE_BAT_WORD,
-            SCE_CSS_CLASS,
-            SCE_CSS_COMMENT,
-            SCE_CSS_DOUBLESTRING,
-            SCE_CSS_ID,
-            SCE_CSS_IDENTIFIER,
-            SCE_CSS_IMPORTANT,
-            SCE_CSS_OPERATOR,
-            SCE_CSS_PSEUDOCLASS,
-            SCE_CSS_SINGLESTRING,
-            // SCLEX_CSS token styles
-            SCE_CSS_TAG,
-            SCE_CSS_VALUE,
-            SCE_C_CHARACTER,
-            // SCLEX_CPP token styles
-            SCE_C_COMMENT,
-            SCE_C_COMMENTDOC,
-            SCE_C_COMMENTLINE,
-            SCE_C_NUMBER,
-            SCE_C_OPERATOR,
-            SCE_C_PREPROCESSOR,
-            SCE_C_REGEX,
-            SCE_C_STRING,
-            SCE_C_WORD,
-            SCE_C_WORD2,
-            SCE_DIFF_ADDED,
-            SCE_DIFF_COMMAND,
-            // SCLEX_DIFF token styles
-            SCE_DIFF_COMMENT,
-            SCE_DIFF_DELETED,
-            SCE_DIFF_HEADER,
-            SCE_DIFF_POSITION,
-            SCE_H_ATTRIBUTE,
-            SCE_H_COMMENT,
-            SCE_H_DOUBLESTRING,
-            SCE_H_SINGLESTRING,
-            // SCLEX_HTML / SCLEX_XML token styles
-            SCE_H_TAG,
-            SCE_H_TAGEND,
-            SCE_JSON_KEYWORD,
-            // SCLEX_JSON token styles
-            SCE_JSON_NUMBER,
-            SCE_JSON_OPERATOR,
-            SCE_JSON_PROPERTYNAME,
-            SCE_JSON_STRING,
-            // SCLEX_MAKEFILE token styles
-            SCE_MAKE_COMMENT,
-            SCE_MAKE_OPERATOR,
-            SCE_MAKE_PREPROCESSOR,
-            SCE_MAKE_TARGET,
-            SCE_MARKDOWN_BLOCKQUOTE,
-            SCE_MARKDOWN_CODE,
-            SCE_MARKDOWN_CODE2,
-            SCE_MARKDOWN_CODEBK,
-            SCE_MARKDOWN_EM1,
-            SCE_MARKDOWN_EM2,
-            SCE_MARKDOWN_HEADER1,
-            SCE_MARKDOWN_HEADER2,
-            SCE_MARKDOWN_HEADER3,
-            SCE_MARKDOWN_HEADER4,
-            SCE_MARKDOWN_HEADER5,
-            SCE_MARKDOWN_HEADER6,
-            SCE_MARKDOWN_HRULE,
-            SCE_MARKDOWN_LINK,
-            SCE_MARKDOWN_OLIST_ITEM,
-            SCE_MARKDOWN_STRIKEOUT,
-            // SCLEX_MARKDOWN token styles
-            SCE_MARKDOWN_STRONG1,
-            SCE_MARKDOWN_STRONG2,
-            SCE_MARKDOWN_ULIST_ITEM,
-            SCE_POWERSHELL_CHARACTER,
-            SCE_POWERSHELL_CMDLET,
-            // SCLEX_POWERSHELL token styles
-            SCE_POWERSHELL_COMMENT,
-            SCE_POWERSHELL_COMMENTSTREAM,
-            SCE_POWERSHELL_FUNCTION,
-            SCE_POWERSHELL_HERE_CHARACTER,
-            SCE_POWERSHELL_HERE_STRING,
-            SCE_POWERSHELL_KEYWORD,
-            SCE_POWERSHELL_NUMBER,
-            SCE_POWERSHELL_OPERATOR,
-            SCE_POWERSHELL_STRING,
-            SCE_POWERSHELL_VARIABLE,
-            // SCLEX_PROPERTIES token styles
-            SCE_PROPS_COMMENT,
-            SCE_PROPS_KEY,
-            SCE_PROPS_SECTION,
-            SCE_P_CHARACTER,
-            SCE_P_CLASSNAME,
-            // SCLEX_PYTHON token styles
-            SCE_P_COMMENTLINE,
-            SCE_P_DECORATOR,
-            SCE_P_DEFNAME,
-            SCE_P_NUMBER,
-            SCE_P_OPERATOR,
-            SCE_P_STRING,
-            SCE_P_TRIPLE,
-            SCE_P_TRIPLEDOUBLE,
-            SCE_P_WORD,
-            SCE_RUST_CHARACTER,
-            // SCLEX_RUST token styles
-            SCE_RUST_COMMENTBLOCK,
-            SCE_RUST_COMMENTBLOCKDOC,
-            SCE_RUST_COMMENTLINE,
-            SCE_RUST_COMMENTLINEDOC,
-            SCE_RUST_LIFETIME,
-            SCE_RUST_MACRO,
-            SCE_RUST_NUMBER,
-            SCE_RUST_OPERATOR,
-            SCE_RUST_STRING,
-            SCE_RUST_STRINGR,
-            SCE_RUST_WORD,
-            SCE_RUST_WORD2,
-            SCE_SH_CHARACTER,
-            // SCLEX_BASH token styles
-            SCE_SH_COMMENTLINE,
-            SCE_SH_NUMBER,
-            SCE_SH_OPERATOR,
-            SCE_SH_SCALAR,
-            SCE_SH_STRING,
-            SCE_SH_WORD,
-            SCE_SQL_CHARACTER,
-            // SCLEX_SQL token styles
-            SCE_SQL_COMMENT,
-            SCE_SQL_COMMENTDOC,
-            SCE_SQL_COMMENTLINE,
-            SCE_SQL_NUMBER,
-            SCE_SQL_OPERATOR,
-            SCE_SQL_STRING,
-            SCE_SQL_WORD,
-            SCE_TOML_BOOL,
-            // SCLEX_TOML token styles
-            SCE_TOML_COMMENT,
-            SCE_TOML_KEY,
-            SCE_TOML_NUMBER,
-            SCE_TOML_SECTIONTITLE,
-            SCE_TOML_STRING,
-            SCE_TOML_STRINGMULTILINE,
-            // SCLEX_YAML token styles
-            SCE_YAML_COMMENT,
-            SCE_YAML_DOCUMENT,
-            SCE_YAML_IDENTIFIER,
-            SCE_YAML_KEYWORD,
-            SCE_YAML_NUMBER,
-            SCE_YAML_OPERATOR,
-            SCE_YAML_TEXT,
-            STYLE_DEFAULT,
-            STYLE_LINENUMBER,
-        },
-        ScintillaView,
+    editor::scintilla::messages::{
+        SCE_BAT_COMMAND,
+        // SCLEX_BATCH token styles
+        SCE_BAT_COMMENT,
+        SCE_BAT_LABEL,
+        SCE_BAT_OPERATOR,
+        SCE_BAT_WORD,
+        SCE_CSS_CLASS,
+        SCE_CSS_COMMENT,
+        SCE_CSS_DOUBLESTRING,
+        SCE_CSS_ID,
+        SCE_CSS_IDENTIFIER,
+        SCE_CSS_IMPORTANT,
+        SCE_CSS_OPERATOR,
+        SCE_CSS_PSEUDOCLASS,
+        SCE_CSS_SINGLESTRING,
+        // SCLEX_CSS token styles
+        SCE_CSS_TAG,
+        SCE_CSS_VALUE,
+        SCE_C_CHARACTER,
+        // SCLEX_CPP token styles
+        SCE_C_COMMENT,
+        SCE_C_COMMENTDOC,
+        SCE_C_COMMENTLINE,
+        SCE_C_NUMBER,
+        SCE_C_OPERATOR,
+        SCE_C_PREPROCESSOR,
+        SCE_C_REGEX,
+        SCE_C_STRING,
+        SCE_C_WORD,
+        SCE_C_WORD2,
+        SCE_DIFF_ADDED,
+        SCE_DIFF_COMMAND,
+        // SCLEX_DIFF token styles
+        SCE_DIFF_COMMENT,
+        SCE_DIFF_DELETED,
+        SCE_DIFF_HEADER,
+        SCE_DIFF_POSITION,
+        SCE_H_ATTRIBUTE,
+        SCE_H_COMMENT,
+        SCE_H_DOUBLESTRING,
+        SCE_H_SINGLESTRING,
+        // SCLEX_HTML / SCLEX_XML token styles
+        SCE_H_TAG,
+        SCE_H_TAGEND,
+        SCE_JSON_KEYWORD,
+        // SCLEX_JSON token styles
+        SCE_JSON_NUMBER,
+        SCE_JSON_OPERATOR,
+        SCE_JSON_PROPERTYNAME,
+        SCE_JSON_STRING,
+        // SCLEX_MAKEFILE token styles
+        SCE_MAKE_COMMENT,
+        SCE_MAKE_OPERATOR,
+        SCE_MAKE_PREPROCESSOR,
+        SCE_MAKE_TARGET,
+        SCE_MARKDOWN_BLOCKQUOTE,
+        SCE_MARKDOWN_CODE,
+        SCE_MARKDOWN_CODE2,
+        SCE_MARKDOWN_CODEBK,
+        SCE_MARKDOWN_EM1,
+        SCE_MARKDOWN_EM2,
+        SCE_MARKDOWN_HEADER1,
+        SCE_MARKDOWN_HEADER2,
+        SCE_MARKDOWN_HEADER3,
+        SCE_MARKDOWN_HEADER4,
+        SCE_MARKDOWN_HEADER5,
+        SCE_MARKDOWN_HEADER6,
+        SCE_MARKDOWN_HRULE,
+        SCE_MARKDOWN_LINK,
+        SCE_MARKDOWN_OLIST_ITEM,
+        SCE_MARKDOWN_STRIKEOUT,
+        // SCLEX_MARKDOWN token styles
+        SCE_MARKDOWN_STRONG1,
+        SCE_MARKDOWN_STRONG2,
+        SCE_MARKDOWN_ULIST_ITEM,
+        SCE_POWERSHELL_CHARACTER,
+        SCE_POWERSHELL_CMDLET,
+        // SCLEX_POWERSHELL token styles
+        SCE_POWERSHELL_COMMENT,
+        SCE_POWERSHELL_COMMENTSTREAM,
+        SCE_POWERSHELL_FUNCTION,
+        SCE_POWERSHELL_HERE_CHARACTER,
+        SCE_POWERSHELL_HERE_STRING,
+        SCE_POWERSHELL_KEYWORD,
+        SCE_POWERSHELL_NUMBER,
+        SCE_POWERSHELL_OPERATOR,
+        SCE_POWERSHELL_STRING,
+        SCE_POWERSHELL_VARIABLE,
+        // SCLEX_PROPERTIES token styles
+        SCE_PROPS_COMMENT,
+        SCE_PROPS_KEY,
+        SCE_PROPS_SECTION,
+        SCE_P_CHARACTER,
+        SCE_P_CLASSNAME,
+        // SCLEX_PYTHON token styles
+        SCE_P_COMMENTLINE,
+        SCE_P_DECORATOR,
+        SCE_P_DEFNAME,
+        SCE_P_NUMBER,
+        SCE_P_OPERATOR,
+        SCE_P_STRING,
+        SCE_P_TRIPLE,
+        SCE_P_TRIPLEDOUBLE,
+        SCE_P_WORD,
+        SCE_RUST_CHARACTER,
+        // SCLEX_RUST token styles
+        SCE_RUST_COMMENTBLOCK,
+        SCE_RUST_COMMENTBLOCKDOC,
+        SCE_RUST_COMMENTLINE,
+        SCE_RUST_COMMENTLINEDOC,
+        SCE_RUST_LIFETIME,
+        SCE_RUST_MACRO,
+        SCE_RUST_NUMBER,
+        SCE_RUST_OPERATOR,
+        SCE_RUST_STRING,
+        SCE_RUST_STRINGR,
+        SCE_RUST_WORD,
+        SCE_RUST_WORD2,
+        SCE_SH_CHARACTER,
+        // SCLEX_BASH token styles
+        SCE_SH_COMMENTLINE,
+        SCE_SH_NUMBER,
+        SCE_SH_OPERATOR,
+        SCE_SH_SCALAR,
+        SCE_SH_STRING,
+        SCE_SH_WORD,
+        SCE_SQL_CHARACTER,
+        // SCLEX_SQL token styles
+        SCE_SQL_COMMENT,
+        SCE_SQL_COMMENTDOC,
+        SCE_SQL_COMMENTLINE,
+        SCE_SQL_NUMBER,
+        SCE_SQL_OPERATOR,
+        SCE_SQL_STRING,
+        SCE_SQL_WORD,
+        SCE_TOML_BOOL,
+        // SCLEX_TOML token styles
+        SCE_TOML_COMMENT,
+        SCE_TOML_KEY,
+        SCE_TOML_NUMBER,
+        SCE_TOML_SECTIONTITLE,
+        SCE_TOML_STRING,
+        SCE_TOML_STRINGMULTILINE,
+        // SCLEX_YAML token styles
+        SCE_YAML_COMMENT,
+        SCE_YAML_DOCUMENT,
+        SCE_YAML_IDENTIFIER,
+        SCE_YAML_KEYWORD,
+        SCE_YAML_NUMBER,
+        SCE_YAML_OPERATOR,
+        SCE_YAML_TEXT,
+        STYLE_DEFAULT,
+        STYLE_LINENUMBER,
     },
     languages::Language,
 };
 
+use crate::editor::EditorView;
+
 // ── Colour macro ──────────────────────────────────────────────────────────────
 
 /// Convert 0xRRGGBB → Scintilla's BGR COLORREF.
@@ -264,6 +263,25 @@ const DARK: Palette = Palette {
 
 // ── Public entry point ────────────────────────────────────────────────────────
 
+/// Editor background colour (`COLORREF` layout) for the light or dark theme.
+/// Also used to erase the main window behind the children.
+pub(crate) fn background(dark: bool) -> u32 {
+    if dark {
+        DARK.bg
+    } else {
+        LIGHT.bg
+    }
+}
+
+/// Default text colour (`COLORREF` layout) for the light or dark theme.
+pub(crate) fn foreground(dark: bool) -> u32 {
+    if dark {
+        DARK.fg
+    } else {
+        LIGHT.fg
+    }
+}
+
 /// Apply a light or dark theme to `sci` for the given `language`.
 ///
 /// When `dark` is `true` the VS Code Dark+-inspired palette is used; when
@@ -274,7 +292,7 @@ const DARK: Palette = Palette {
 /// 2. Call `style_clear_all` to clone those into all 256 slots.
 /// 3. Override `STYLE_LINENUMBER`.
 /// 4. Dispatch to the per-lexer function to set token colours.
-pub(crate) fn apply_theme(sci: &ScintillaView, language: Language, dark: bool) {
+pub(crate) fn apply_theme(sci: &dyn EditorView, language: Language, dark: bool) {
     let p = if dark { &DARK } else { &LIGHT };
     apply_default_styles(sci, p);
     match language {
@@ -300,9 +318,147 @@ pub(crate) fn apply_theme(sci: &ScintillaView, language: Language, dark: bool) {
     }
 }
 
+/// Box colour (`COLORREF` layout) behind the other occurrences of the word
+/// at the caret.
+pub(crate) fn occurrence_highlight(dark: bool) -> u32 {
+    if dark {
+        rgb!(0x6E, 0x76, 0x81)
+    } else {
+        rgb!(0x9E, 0xC3, 0xEB)
+    }
+}
+
+/// Margin strip colours (`COLORREF` layout) on changed lines: orange for
+/// changes since the last save, green for saved ones.
+pub(crate) fn change_markers(dark: bool) -> (u32, u32) {
+    if dark {
+        (rgb!(0xE0, 0x8A, 0x2E), rgb!(0x4C, 0xA8, 0x5A))
+    } else {
+        (rgb!(0xFF, 0x8C, 0x00), rgb!(0x2E, 0x9E, 0x44))
+    }
+}
+
+/// Git gutter colours (`COLORREF` layout): green for added lines, blue for
+/// modified ones, red where lines were deleted.
+pub(crate) fn git_markers(dark: bool) -> (u32, u32, u32) {
+    if dark {
+        (
+            rgb!(0x4C, 0xA8, 0x5A),
+            rgb!(0x4A, 0x8F, 0xD9),
+            rgb!(0xE0, 0x5A, 0x5A),
+        )
+    } else {
+        (
+            rgb!(0x2E, 0x9E, 0x44),
+            rgb!(0x1F, 0x6F, 0xC5),
+            rgb!(0xD1, 0x24, 0x2F),
+        )
+    }
+}
+
+// ── Accent highlights ─────────────────────────────────────────────────────────
+
+/// Selection and caret-line backgrounds derived from the Windows accent
+/// colour, toned down against the editor background so text stays legible.
+pub(crate) struct AccentHighlights {
+    pub(crate) selection: u32,
+    pub(crate) caret_line: u32,
+}
+
+pub(crate) fn accent_highlights(accent: u32, dark: bool) -> AccentHighlights {
+    let bg = background(dark);
+    AccentHighlights {
+        selection: blend(accent, bg, 45),
+        caret_line: blend(accent, bg, 12),
+    }
+}
+
+/// Mix `percent`% of COLORREF `a` into COLORREF `b`, channel by channel.
+fn blend(a: u32, b: u32, percent: u32) -> u32 {
+    (0..3).fold(0, |acc, i| {
+        let shift = i * 8;
+        let ca = (a >> shift) & 0xFF;
+        let cb = (b >> shift) & 0xFF;
+        acc | (((ca * percent + cb * (100 - percent)) / 100) << shift)
+    })
+}
+
+// ── High contrast ─────────────────────────────────────────────────────────────
+
+/// Windows system colours (`COLORREF` layout), used instead of the light and
+/// dark palettes while High Contrast mode is on (or the user asks for them).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SystemColours {
+    /// `COLOR_WINDOW`
+    pub(crate) window: u32,
+    /// `COLOR_WINDOWTEXT`
+    pub(crate) window_text: u32,
+    /// `COLOR_HIGHLIGHT`
+    pub(crate) highlight: u32,
+    /// `COLOR_HIGHLIGHTTEXT`
+    pub(crate) highlight_text: u32,
+    /// `COLOR_GRAYTEXT`
+    pub(crate) gray_text: u32,
+    /// `COLOR_BTNFACE`
+    pub(crate) button_face: u32,
+    /// `COLOR_BTNTEXT`
+    pub(crate) button_text: u32,
+}
+
+impl SystemColours {
+    /// Every token in the window text colour: syntax colours would undo the
+    /// contrast the user chose, so highlighting is off.
+    fn palette(&self) -> Palette {
+        let t = self.window_text;
+        Palette {
+            bg: self.window,
+            fg: t,
+            line_num_bg: self.button_face,
+            line_num_fg: self.button_text,
+            comment: t,
+            keyword: t,
+            keyword2: t,
+            string: t,
+            number: t,
+            preproc: t,
+            operator: t,
+            label: t,
+            regex: t,
+            tag: t,
+            attr: t,
+            section: t,
+            key: t,
+            diff_add: t,
+            diff_del: t,
+            diff_hdr: t,
+            md_header: t,
+            md_code: t,
+            yaml_key: t,
+        }
+    }
+
+    /// Margin strip colours on changed lines (as [`change_markers`]):
+    /// highlight for unsaved changes, grey text for saved ones.
+    pub(crate) fn change_markers(&self) -> (u32, u32) {
+        (self.highlight, self.gray_text)
+    }
+
+    /// Git gutter colours (as [`git_markers`]).  High Contrast themes have
+    /// no green or red, so added and modified share the highlight and
+    /// deletions use the text colour.
+    pub(crate) fn git_markers(&self) -> (u32, u32, u32) {
+        (self.highlight, self.highlight, self.window_text)
+    }
+}
+
+/// Style `sci` with the system colours; see [`SystemColours::palette`].
+pub(crate) fn apply_system_theme(sci: &dyn EditorView, colours: &SystemColours) {
+    apply_default_styles(sci, &colours.palette());
+}
+
 // ── Default styles ────────────────────────────────────────────────────────────
 
-fn apply_default_styles(sci: &ScintillaView, p: &Palette) {
+fn apply_default_styles(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(STYLE_DEFAULT, p.fg);
     sci.style_set_back(STYLE_DEFAULT, p.bg);
     sci.style_set_font(STYLE_DEFAULT, b"Consolas\0");
@@ -316,7 +472,7 @@ fn apply_default_styles(sci: &ScintillaView, p: &Palette) {
 
 // ── Per-lexer theme functions ─────────────────────────────────────────────────
 
-fn apply_cpp_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_cpp_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_C_COMMENT, p.comment);
     sci.style_set_fore(SCE_C_COMMENTLINE, p.comment);
     sci.style_set_fore(SCE_C_COMMENTDOC, p.comment);
@@ -331,7 +487,7 @@ fn apply_cpp_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_C_REGEX, p.regex);
 }
 
-fn apply_python_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_python_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_P_COMMENTLINE, p.comment);
     sci.style_set_fore(SCE_P_NUMBER, p.number);
     sci.style_set_fore(SCE_P_STRING, p.string);
@@ -346,7 +502,7 @@ fn apply_python_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_P_DECORATOR, p.preproc);
 }
 
-fn apply_rust_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_rust_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_RUST_COMMENTBLOCK, p.comment);
     sci.style_set_fore(SCE_RUST_COMMENTLINE, p.comment);
     sci.style_set_fore(SCE_RUST_COMMENTBLOCKDOC, p.comment);
@@ -363,7 +519,7 @@ fn apply_rust_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_RUST_MACRO, p.preproc);
 }
 
-fn apply_html_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_html_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_H_TAG, p.tag);
     sci.style_set_bold(SCE_H_TAG, true);
     sci.style_set_fore(SCE_H_TAGEND, p.tag);
@@ -374,7 +530,7 @@ fn apply_html_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_H_COMMENT, p.comment);
 }
 
-fn apply_css_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_css_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_CSS_TAG, p.tag);
     sci.style_set_fore(SCE_CSS_CLASS, p.keyword);
     sci.style_set_bold(SCE_CSS_CLASS, true);
@@ -391,7 +547,7 @@ fn apply_css_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_CSS_DOUBLESTRING, p.string);
 }
 
-fn apply_json_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_json_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_JSON_NUMBER, p.number);
     sci.style_set_fore(SCE_JSON_STRING, p.string);
     sci.style_set_fore(SCE_JSON_PROPERTYNAME, p.keyword);
@@ -400,7 +556,7 @@ fn apply_json_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_JSON_KEYWORD, p.keyword2);
 }
 
-fn apply_sql_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_sql_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_SQL_COMMENT, p.comment);
     sci.style_set_fore(SCE_SQL_COMMENTLINE, p.comment);
     sci.style_set_fore(SCE_SQL_COMMENTDOC, p.comment);
@@ -412,7 +568,7 @@ fn apply_sql_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_SQL_OPERATOR, p.operator);
 }
 
-fn apply_toml_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_toml_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_TOML_COMMENT, p.comment);
     sci.style_set_fore(SCE_TOML_SECTIONTITLE, p.section);
     sci.style_set_bold(SCE_TOML_SECTIONTITLE, true);
@@ -424,14 +580,14 @@ fn apply_toml_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_bold(SCE_TOML_BOOL, true);
 }
 
-fn apply_ini_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_ini_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_PROPS_COMMENT, p.comment);
     sci.style_set_fore(SCE_PROPS_SECTION, p.section);
     sci.style_set_bold(SCE_PROPS_SECTION, true);
     sci.style_set_fore(SCE_PROPS_KEY, p.key);
 }
 
-fn apply_batch_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_batch_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_BAT_COMMENT, p.comment);
     sci.style_set_fore(SCE_BAT_WORD, p.keyword);
     sci.style_set_bold(SCE_BAT_WORD, true);
@@ -440,7 +596,7 @@ fn apply_batch_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_BAT_OPERATOR, p.operator);
 }
 
-fn apply_makefile_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_makefile_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_MAKE_COMMENT, p.comment);
     sci.style_set_fore(SCE_MAKE_PREPROCESSOR, p.preproc);
     sci.style_set_fore(SCE_MAKE_TARGET, p.keyword);
@@ -448,7 +604,7 @@ fn apply_makefile_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_MAKE_OPERATOR, p.operator);
 }
 
-fn apply_diff_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_diff_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_DIFF_COMMENT, p.comment);
     sci.style_set_fore(SCE_DIFF_COMMAND, p.preproc);
     sci.style_set_fore(SCE_DIFF_HEADER, p.diff_hdr);
@@ -458,7 +614,7 @@ fn apply_diff_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_DIFF_ADDED, p.diff_add);
 }
 
-fn apply_shell_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_shell_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_SH_COMMENTLINE, p.comment);
     sci.style_set_fore(SCE_SH_NUMBER, p.number);
     sci.style_set_fore(SCE_SH_WORD, p.keyword);
@@ -469,7 +625,7 @@ fn apply_shell_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_SH_SCALAR, p.keyword2);
 }
 
-fn apply_markdown_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_markdown_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_MARKDOWN_STRONG1, p.fg);
     sci.style_set_bold(SCE_MARKDOWN_STRONG1, true);
     sci.style_set_fore(SCE_MARKDOWN_STRONG2, p.fg);
@@ -496,7 +652,7 @@ fn apply_markdown_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_MARKDOWN_CODEBK, p.md_code);
 }
 
-fn apply_yaml_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_yaml_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_YAML_COMMENT, p.comment);
     sci.style_set_fore(SCE_YAML_IDENTIFIER, p.yaml_key);
     sci.style_set_bold(SCE_YAML_IDENTIFIER, true);
@@ -508,7 +664,7 @@ fn apply_yaml_theme(sci: &ScintillaView, p: &Palette) {
     sci.style_set_fore(SCE_YAML_OPERATOR, p.operator);
 }
 
-fn apply_powershell_theme(sci: &ScintillaView, p: &Palette) {
+fn apply_powershell_theme(sci: &dyn EditorView, p: &Palette) {
     sci.style_set_fore(SCE_POWERSHELL_COMMENT, p.comment);
     sci.style_set_fore(SCE_POWERSHELL_COMMENTSTREAM, p.comment);
     sci.style_set_fore(SCE_POWERSHELL_STRING, p.string);
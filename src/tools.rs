@@ -0,0 +1,163 @@
+// ── External tools ────────────────────────────────────────────────────────────
+//
+// User-configurable command lines shown in the Tools menu.  Placeholder
+// expansion is pure Rust; the actual `CreateProcessW` launch lives in
+// `platform::win32::process`.
+
+pub(crate) mod hash; // Tools > Generate Hash… digests
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// One entry in the Tools menu.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExternalTool {
+    /// Menu label, e.g. `"Open in Explorer"`.
+    pub(crate) name: String,
+    /// Command line template; see [`PLACEHOLDERS`] for the supported tokens.
+    pub(crate) command: String,
+}
+
+/// Built-in tools written to a fresh `settings.json`.
+pub(crate) fn default_tools() -> Vec<ExternalTool> {
+    let tool = |name: &str, command: &str| ExternalTool {
+        name: name.to_owned(),
+        command: command.to_owned(),
+    };
+    vec![
+        tool("Open in Explorer", r#"explorer.exe /select,"$(FULL_PATH)""#),
+        tool("Open cmd Here", r#"cmd.exe /K cd /d "$(FILE_DIR)""#),
+        tool("Run with Python", r#"cmd.exe /K python "$(FULL_PATH)""#),
+    ]
+}
+
+// ── Placeholders ──────────────────────────────────────────────────────────────
+
+/// Every token understood by [`expand`], in the order shown to the user.
+pub(crate) const PLACEHOLDERS: &[&str] = &[
+    "$(FULL_PATH)",
+    "$(FILE_DIR)",
+    "$(FILE_NAME)",
+    "$(NAME_PART)",
+    "$(EXT_PART)",
+    "$(LINE)",
+    "$(COLUMN)",
+    "$(SELECTION)",
+];
+
+/// Editor state substituted into a command line.
+pub(crate) struct ToolContext<'a> {
+    /// Path of the active document, or `None` for an untitled buffer.
+    pub(crate) path: Option<&'a Path>,
+    /// 1-based caret line.
+    pub(crate) line: usize,
+    /// 1-based caret column.
+    pub(crate) column: usize,
+    /// Currently selected text (may be empty).
+    pub(crate) selection: &'a str,
+}
+
+/// Substitute every placeholder in `template` with its value from `ctx`.
+///
+/// Path-derived placeholders expand to the empty string for untitled buffers.
+/// Unknown `$(…)` tokens are left untouched so typos are visible in the
+/// launched process rather than silently vanishing.
+pub(crate) fn expand(template: &str, ctx: &ToolContext<'_>) -> String {
+    let path_part = |f: fn(&Path) -> Option<&std::ffi::OsStr>| {
+        ctx.path
+            .and_then(f)
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let full_path = ctx
+        .path
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_dir = ctx
+        .path
+        .and_then(Path::parent)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let values: [(&str, String); 8] = [
+        ("$(FULL_PATH)", full_path),
+        ("$(FILE_DIR)", file_dir),
+        ("$(FILE_NAME)", path_part(Path::file_name)),
+        ("$(NAME_PART)", path_part(Path::file_stem)),
+        ("$(EXT_PART)", path_part(Path::extension)),
+        ("$(LINE)", ctx.line.to_string()),
+        ("$(COLUMN)", ctx.column.to_string()),
+        ("$(SELECTION)", ctx.selection.to_owned()),
+    ];
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    'outer: while let Some(start) = rest.find("$(") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        for (token, value) in &values {
+            if let Some(tail) = rest.strip_prefix(token) {
+                out.push_str(value);
+                rest = tail;
+                continue 'outer;
+            }
+        }
+        // Not a known token: copy the `$(` literally and keep scanning.
+        out.push_str("$(");
+        rest = &rest[2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(path: Option<&Path>) -> ToolContext<'_> {
+        ToolContext {
+            path,
+            line: 12,
+            column: 4,
+            selection: "word",
+        }
+    }
+
+    #[test]
+    fn expands_path_placeholders() {
+        let p = Path::new(r"C:\src\main.rs");
+        let out = expand(
+            "$(FULL_PATH)|$(FILE_DIR)|$(FILE_NAME)|$(NAME_PART)|$(EXT_PART)",
+            &ctx(Some(p)),
+        );
+        assert_eq!(out, r"C:\src\main.rs|C:\src|main.rs|main|rs");
+    }
+
+    #[test]
+    fn expands_caret_and_selection() {
+        let out = expand("-n$(LINE) -c$(COLUMN) \"$(SELECTION)\"", &ctx(None));
+        assert_eq!(out, "-n12 -c4 \"word\"");
+    }
+
+    #[test]
+    fn untitled_path_placeholders_are_empty() {
+        assert_eq!(expand("[$(FULL_PATH)][$(FILE_DIR)]", &ctx(None)), "[][]");
+    }
+
+    #[test]
+    fn unknown_tokens_are_preserved() {
+        assert_eq!(expand("$(NOPE) $(LINE)", &ctx(None)), "$(NOPE) 12");
+        assert_eq!(expand("trailing $(", &ctx(None)), "trailing $(");
+    }
+
+    #[test]
+    fn placeholder_list_matches_expander() {
+        for token in PLACEHOLDERS {
+            let out = expand(token, &ctx(Some(Path::new(r"C:\a\b.txt"))));
+            assert_ne!(&out, token, "{token} was not expanded");
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// ── Selection transforms ──────────────────────────────────────────────────────
+//
+// The codecs behind Tools > Transform Selection: Base64, URL percent-encoding
+// and HTML entities, each way.  Decoders refuse input that is malformed or
+// that would decode to bytes that are not UTF-8, so a document never gains
+// invalid text.  No `unsafe` — pure safe Rust.
+
+/// One Tools > Transform Selection command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Transform {
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    HtmlEscape,
+    HtmlUnescape,
+}
+
+/// Apply `transform` to `text`; `None` if it cannot be decoded.
+pub(crate) fn apply(transform: Transform, text: &[u8]) -> Option<Vec<u8>> {
+    let out = match transform {
+        Transform::Base64Encode => return Some(base64_encode(text)),
+        Transform::Base64Decode => base64_decode(text)?,
+        Transform::UrlEncode => return Some(url_encode(text)),
+        Transform::UrlDecode => url_decode(text)?,
+        Transform::HtmlEscape => return Some(html_escape(text)),
+        Transform::HtmlUnescape => html_unescape(text),
+    };
+    std::str::from_utf8(&out).is_ok().then_some(out)
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard Base64 with `=` padding, on one line.
+fn base64_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode Base64, standard or URL-safe, ignoring whitespace and with or
+/// without padding.
+fn base64_decode(text: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    let mut padding = false;
+    for &c in text.iter().filter(|c| !c.is_ascii_whitespace()) {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            _ => return None,
+        };
+        if padding {
+            return None;
+        }
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    // Six leftover bits cannot come from whole bytes.
+    (bits < 6).then_some(out)
+}
+
+/// Percent-encode everything but the RFC 3986 unreserved characters.
+fn url_encode(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for &b in text {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b);
+        } else {
+            out.extend_from_slice(format!("%{b:02X}").as_bytes());
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes.  `+` is left alone; a `%` not followed by two hex
+/// digits is an error.
+fn url_decode(text: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            out.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(text[i]);
+            i += 1;
+        }
+    }
+    Some(out)
+}
+
+/// Escape the five characters that are special in HTML text and attributes.
+fn html_escape(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for &b in text {
+        match b {
+            b'&' => out.extend_from_slice(b"&amp;"),
+            b'<' => out.extend_from_slice(b"&lt;"),
+            b'>' => out.extend_from_slice(b"&gt;"),
+            b'"' => out.extend_from_slice(b"&quot;"),
+            b'\'' => out.extend_from_slice(b"&#39;"),
+            _ => out.push(b),
+        }
+    }
+    out
+}
+
+/// The character a named or numeric entity body (between `&` and `;`)
+/// stands for.
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{A0}',
+        "copy" => '\u{A9}',
+        "reg" => '\u{AE}',
+        "hellip" => '\u{2026}',
+        "mdash" => '\u{2014}',
+        "ndash" => '\u{2013}',
+        _ => return None,
+    })
+}
+
+/// Replace named and numeric entities; unknown ones are left as they are.
+fn html_unescape(text: &[u8]) -> Vec<u8> {
+    /// Longest entity body looked for, e.g. `#x10FFFF`.
+    const MAX_NAME: usize = 10;
+    let mut out = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == b'&' {
+            let body = &text[i + 1..text.len().min(i + 2 + MAX_NAME)];
+            let decoded = body.iter().position(|&b| b == b';').and_then(|end| {
+                let c = entity(std::str::from_utf8(&body[..end]).ok()?)?;
+                Some((c, end))
+            });
+            if let Some((c, end)) = decoded {
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                i += end + 2;
+                continue;
+            }
+        }
+        out.push(text[i]);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(t: Transform, s: &str) -> Option<String> {
+        apply(t, s.as_bytes()).map(|v| String::from_utf8(v).unwrap())
+    }
+
+    #[test]
+    fn base64_round_trips_and_rejects_garbage() {
+        use Transform::*;
+        for (plain, coded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v")] {
+            assert_eq!(run(Base64Encode, plain).as_deref(), Some(coded));
+            assert_eq!(run(Base64Decode, coded).as_deref(), Some(plain));
+        }
+        assert_eq!(run(Base64Decode, "Zm9v\r\nYmFy").as_deref(), Some("foobar"));
+        assert_eq!(run(Base64Decode, "Zm8").as_deref(), Some("fo"));
+        assert_eq!(run(Base64Decode, "Zm9v!"), None);
+        assert_eq!(run(Base64Decode, "Z"), None);
+        // 0xFF 0xFE is not UTF-8.
+        assert_eq!(run(Base64Decode, "//4="), None);
+    }
+
+    #[test]
+    fn url_percent_encoding() {
+        use Transform::*;
+        assert_eq!(
+            run(UrlEncode, "a b&c=é~").as_deref(),
+            Some("a%20b%26c%3D%C3%A9~")
+        );
+        assert_eq!(
+            run(UrlDecode, "a%20b%26c%3d%C3%A9+").as_deref(),
+            Some("a b&c=é+")
+        );
+        assert_eq!(run(UrlDecode, "100%"), None);
+        assert_eq!(run(UrlDecode, "%zz"), None);
+        assert_eq!(run(UrlDecode, "%+1"), None);
+    }
+
+    #[test]
+    fn html_entities() {
+        use Transform::*;
+        assert_eq!(
+            run(HtmlEscape, "<a href=\"x\">Tom's & Jerry</a>").as_deref(),
+            Some("&lt;a href=&quot;x&quot;&gt;Tom&#39;s &amp; Jerry&lt;/a&gt;")
+        );
+        assert_eq!(
+            run(
+                HtmlUnescape,
+                "&lt;b&gt; &amp;amp; &#233;&#x2014;&nbsp;&bogus; & x"
+            )
+            .as_deref(),
+            Some("<b> &amp; é—\u{A0}&bogus; & x")
+        );
+    }
+}
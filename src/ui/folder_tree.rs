@@ -0,0 +1,104 @@
+// ── Folder panel contents ─────────────────────────────────────────────────────
+//
+// Directory listing and name checks behind View > Folder Panel.  The tree
+// control itself lives in `platform::win32::folder_panel`; it lists one
+// folder at a time as the user expands it, so nothing here recurses.
+// No Win32 calls here.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// One item of a folder listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) is_dir: bool,
+}
+
+/// The contents of `dir`: folders first, then files, each sorted by name
+/// ignoring case as Explorer does.  Entries whose type cannot be read are
+/// left out.
+pub(crate) fn list_dir(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries: Vec<Entry> = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|e| {
+            let is_dir = e.file_type().ok()?.is_dir();
+            Some(Entry {
+                name: e.file_name().to_string_lossy().into_owned(),
+                path: e.path(),
+                is_dir,
+            })
+        })
+        .collect();
+    entries.sort_by_cached_key(|e| (!e.is_dir, e.name.to_lowercase()));
+    Ok(entries)
+}
+
+/// Whether `name` can be used as a file name on Windows: not empty, no
+/// path separators or reserved characters, and no trailing dot or space.
+pub(crate) fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.ends_with(['.', ' '])
+        && !name
+            .chars()
+            .any(|c| c < ' ' || matches!(c, '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+}
+
+/// A name for a new file in `dir` that is not taken: `new.txt`, then
+/// `new 2.txt`, `new 3.txt`, …
+pub(crate) fn unused_name(dir: &Path) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => "new.txt".to_owned(),
+            n => format!("new {n}.txt"),
+        })
+        .find(|name| !dir.join(name).exists())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_come_first_then_names_ignoring_case() {
+        let dir = std::env::temp_dir().join(format!("rivet-folder-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        for name in ["b.txt", "A.txt", "new.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let names: Vec<_> = list_dir(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["src", "A.txt", "b.txt", "new.txt"]);
+        assert_eq!(unused_name(&dir), "new 2.txt");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn names_with_reserved_characters_are_rejected() {
+        assert!(valid_name("notes.md"));
+        assert!(valid_name(".gitignore"));
+        for bad in [
+            "",
+            ".",
+            "..",
+            "a/b",
+            r"a\b",
+            "a:b",
+            "what?",
+            "trailing.",
+            "space ",
+        ] {
+            assert!(!valid_name(bad), "{bad:?}");
+        }
+    }
+}
@@ -2,4 +2,8 @@
 //
 // High-level UI state that lives above the Win32 layer.  No `unsafe` here.
 
+pub(crate) mod folder_tree;
+pub(crate) mod status;
+pub(crate) mod switcher;
 pub mod tabs;
+pub(crate) mod toolbar; // fuzzy filtering for the quick tab switcher
@@ -0,0 +1,100 @@
+// ── Status bar text ───────────────────────────────────────────────────────────
+//
+// Formatting for the status-bar parts that are not plain labels.  The parts
+// themselves are laid out and filled by `platform::win32::window`.
+//
+// While a long operation runs in the active tab (a Large File Mode load, a
+// background Replace All or line-ending conversion) it is a `Task`: the
+// position part names it and a progress bar with a Cancel button
+// (`platform::win32::progress`) sits over the part's right end.
+// No Win32 calls here.
+
+/// A long operation running on a worker thread, as the status bar shows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Task {
+    /// What it is doing, e.g. `"Loading"`.
+    pub(crate) label: &'static str,
+    /// 0–100.
+    pub(crate) percent: u8,
+}
+
+/// The position part's text while `task` runs: `" Loading… 42%"`.
+pub(crate) fn task_text(task: &Task) -> String {
+    format!(" {}\u{2026} {}%", task.label, task.percent.min(100))
+}
+
+/// `bytes` as a short human-readable size: `"512 B"`, `"1.5 KB"`,
+/// `"12.0 MB"`.  Units are powers of 1024, matching Explorer.
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// The accessible name for status-bar part `part` showing `text`: the text
+/// with what it is spelled out, e.g. `"Encoding: UTF-8"` rather than a bare
+/// `"UTF-8"`, and `INS` / `OVR` as words.  Part 0 (the position) already
+/// says what it is and is only trimmed.
+pub(crate) fn part_accessible_name(part: usize, text: &str) -> String {
+    let text = text.trim();
+    match part {
+        1 => format!("File size: {text}"),
+        2 => format!("Language: {text}"),
+        3 => format!("Line endings: {text}"),
+        4 => format!("Encoding: {text}"),
+        5 => match text {
+            "INS" => "Insert mode".to_owned(),
+            "OVR" => "Overtype mode".to_owned(),
+            _ => text.to_owned(),
+        },
+        _ => text.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_pick_the_largest_whole_unit() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(1023), "1023 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(12 * 1024 * 1024), "12.0 MB");
+        assert_eq!(human_size(3 << 30), "3.0 GB");
+        assert_eq!(human_size(u64::MAX), "16777216.0 TB");
+    }
+
+    #[test]
+    fn task_text_names_the_task_and_clamps_percent() {
+        let task = Task {
+            label: "Replacing",
+            percent: 42,
+        };
+        assert_eq!(task_text(&task), " Replacing\u{2026} 42%");
+        let task = Task {
+            label: "Loading",
+            percent: 250,
+        };
+        assert_eq!(task_text(&task), " Loading\u{2026} 100%");
+    }
+
+    #[test]
+    fn part_names_say_what_the_part_is() {
+        assert_eq!(
+            part_accessible_name(0, " Ln 3/10, Col 5, Pos 40"),
+            "Ln 3/10, Col 5, Pos 40"
+        );
+        assert_eq!(part_accessible_name(4, " UTF-8"), "Encoding: UTF-8");
+        assert_eq!(part_accessible_name(3, " CRLF"), "Line endings: CRLF");
+        assert_eq!(part_accessible_name(5, " OVR"), "Overtype mode");
+    }
+}
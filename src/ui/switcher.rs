@@ -0,0 +1,132 @@
+// ── Quick tab switcher ────────────────────────────────────────────────────────
+//
+// Fuzzy filtering for the open-tab picker (View > Switch to Tab…).  The
+// popup itself lives in `platform::win32::switcher_dialog`; this module only
+// decides which tabs match the typed text and in what order.
+// No Win32 calls here.
+
+use std::path::Path;
+
+use crate::app::DocumentState;
+
+/// One open tab as listed by the switcher.
+pub(crate) struct SwitcherEntry {
+    /// File name, or `"Untitled"`.
+    pub(crate) name: String,
+    /// Path as shown in the tab tooltip; empty for untitled documents.
+    pub(crate) path: String,
+}
+
+impl SwitcherEntry {
+    /// Build the entry for `doc`, with its path shown relative to `root`.
+    pub(crate) fn new(doc: &DocumentState, root: Option<&Path>) -> Self {
+        Self {
+            name: doc
+                .path
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Untitled".to_owned()),
+            path: doc
+                .path
+                .as_ref()
+                .map(|_| doc.display_path(root))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Extra score for a match that lands in the file name rather than only in
+/// the path, so `main` ranks `main.rs` above `src/main/lib.rs`.
+const NAME_BONUS: u32 = 8;
+
+/// Score `query` as a case-insensitive subsequence of `text`.
+///
+/// `None` if some query character is missing.  Otherwise each matched
+/// character scores 1, plus 2 when it directly follows the previous match and
+/// 3 when it starts a word (after `/ \ . _ -` or a space).  Whitespace in the
+/// query is ignored.
+pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut prev: Option<usize> = None;
+    for q in query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let i = from + text[from..].iter().position(|&c| c == q)?;
+        score += 1;
+        if i > 0 && prev == Some(i - 1) {
+            score += 2;
+        }
+        if i == 0 || matches!(text[i - 1], '/' | '\\' | '.' | '_' | '-' | ' ') {
+            score += 3;
+        }
+        prev = Some(i);
+        from = i + 1;
+    }
+    Some(score)
+}
+
+/// Indices of the entries matching `query`, best match first.
+///
+/// An empty query lists every entry in tab order; equal scores keep tab order.
+pub(crate) fn filter(query: &str, entries: &[SwitcherEntry]) -> Vec<usize> {
+    let mut scored: Vec<(u32, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| {
+            let name = fuzzy_score(query, &e.name).map(|s| s + NAME_BONUS);
+            let path = fuzzy_score(query, &e.path);
+            name.max(path).map(|s| (s, i))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, path: &str) -> SwitcherEntry {
+        SwitcherEntry {
+            name: name.to_owned(),
+            path: path.to_owned(),
+        }
+    }
+
+    #[test]
+    fn subsequence_matching_is_case_insensitive() {
+        assert!(fuzzy_score("WNd", "window.rs").is_some());
+        assert!(fuzzy_score("w r", "window.rs").is_some());
+        assert_eq!(fuzzy_score("rw", "window.rs"), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn word_starts_and_runs_score_higher() {
+        let run = fuzzy_score("win", "window.rs").unwrap();
+        let scattered = fuzzy_score("win", "wxixn.rs").unwrap();
+        assert!(run > scattered);
+        let start = fuzzy_score("rs", "a.rs").unwrap();
+        let middle = fuzzy_score("rs", "cars").unwrap();
+        assert!(start > middle);
+    }
+
+    #[test]
+    fn filter_ranks_name_matches_first() {
+        let entries = [
+            entry("lib.rs", "src/main/lib.rs"),
+            entry("main.rs", "src/main.rs"),
+            entry("README.md", "README.md"),
+        ];
+        assert_eq!(filter("main", &entries), [1, 0]);
+        assert_eq!(filter("", &entries), [0, 1, 2]);
+        assert!(filter("zzz", &entries).is_empty());
+    }
+}
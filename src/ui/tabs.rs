@@ -5,6 +5,15 @@
 
 use crate::app::DocumentState;
 
+/// The file name, or `"Untitled"`.
+fn tab_name(doc: &DocumentState) -> String {
+    doc.path
+        .as_deref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Untitled".to_owned())
+}
+
 /// Compute the display label for a tab from its document state.
 ///
 /// Format:
@@ -12,16 +21,85 @@ use crate::app::DocumentState;
 /// - Untitled, dirty  → `"*Untitled"`
 /// - Named, clean     → `"filename.txt"`
 /// - Named, dirty     → `"*filename.txt"`
+/// - Read-only        → `"filename.txt [RO]"`
+/// - Undone past save → `"*filename.txt [Before Save]"`
 pub(crate) fn tab_label(doc: &DocumentState) -> String {
-    let name = doc
-        .path
-        .as_deref()
-        .and_then(|p| p.file_name())
-        .map(|n| n.to_string_lossy().into_owned())
-        .unwrap_or_else(|| "Untitled".to_owned());
+    let mut name = tab_name(doc);
+    if doc.dirty && doc.undone_past_save {
+        name.push_str(" [Before Save]");
+    }
+    let name = if doc.read_only {
+        format!("{name} [RO]")
+    } else {
+        name
+    };
     if doc.dirty {
         format!("*{name}")
     } else {
         name
     }
 }
+
+/// The name screen readers announce for a tab: the label's markers spelled
+/// out, e.g. `"notes.txt, modified, read-only"`.
+pub(crate) fn tab_accessible_name(doc: &DocumentState) -> String {
+    let mut name = tab_name(doc);
+    if doc.dirty {
+        name.push_str(", modified");
+        if doc.undone_past_save {
+            name.push_str(" by undoing past the save");
+        }
+    }
+    if doc.read_only {
+        name.push_str(", read-only");
+    }
+    name
+}
+
+/// The file name whose type picks the tab's icon (View > File Icons in
+/// Tabs).  An untitled document looks like the plain text it starts as.
+pub(crate) fn tab_icon_name(doc: &DocumentState) -> String {
+    match doc.path.as_deref().and_then(|p| p.file_name()) {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => "Untitled.txt".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessible_name_spells_out_markers() {
+        let mut doc = DocumentState::new_untitled();
+        assert_eq!(tab_accessible_name(&doc), "Untitled");
+        doc.path = Some("dir/notes.txt".into());
+        doc.dirty = true;
+        doc.read_only = true;
+        assert_eq!(tab_label(&doc), "*notes.txt [RO]");
+        assert_eq!(tab_accessible_name(&doc), "notes.txt, modified, read-only");
+    }
+
+    #[test]
+    fn undo_past_save_is_marked_while_dirty() {
+        let mut doc = DocumentState::new_untitled();
+        doc.path = Some("notes.txt".into());
+        doc.undone_past_save = true;
+        assert_eq!(tab_label(&doc), "notes.txt");
+        doc.dirty = true;
+        assert_eq!(tab_label(&doc), "*notes.txt [Before Save]");
+        assert_eq!(
+            tab_accessible_name(&doc),
+            "notes.txt, modified by undoing past the save"
+        );
+    }
+
+    #[test]
+    fn icon_name_treats_untitled_as_text() {
+        let mut doc = DocumentState::new_untitled();
+        assert_eq!(tab_icon_name(&doc), "Untitled.txt");
+        doc.path = Some("dir/main.rs".into());
+        doc.dirty = true;
+        assert_eq!(tab_icon_name(&doc), "main.rs");
+    }
+}
@@ -0,0 +1,93 @@
+// ── Toolbar buttons ───────────────────────────────────────────────────────────
+//
+// What the optional toolbar (View > Toolbar) shows.  `settings.json` lists
+// the buttons by command-palette name (`"file.save"`), with `"|"` for a
+// separator; this module reads that list and picks each button's icon.
+// The control itself is `platform::win32::toolbar`.  No Win32 calls here.
+
+/// A separator in `ToolbarSettings::buttons`.
+pub(crate) const SEPARATOR: &str = "|";
+
+/// The buttons shown until the user picks their own.
+pub(crate) fn default_buttons() -> Vec<String> {
+    [
+        "file.new",
+        "file.open",
+        "file.save",
+        SEPARATOR,
+        "edit.undo",
+        "edit.redo",
+        SEPARATOR,
+        "search.find",
+        "search.replace",
+        SEPARATOR,
+        "view.word_wrap",
+    ]
+    .map(str::to_owned)
+    .to_vec()
+}
+
+/// A picture from the system's standard toolbar bitmap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Icon {
+    New,
+    Open,
+    Save,
+    Undo,
+    Redo,
+    Cut,
+    Copy,
+    Paste,
+    Find,
+    Replace,
+    Properties,
+}
+
+/// The icon for command `name`; commands without one show their name.
+pub(crate) fn icon(name: &str) -> Option<Icon> {
+    Some(match name {
+        "file.new" => Icon::New,
+        "file.open" => Icon::Open,
+        "file.save" => Icon::Save,
+        "file.properties" => Icon::Properties,
+        "edit.undo" => Icon::Undo,
+        "edit.redo" => Icon::Redo,
+        "edit.cut" => Icon::Cut,
+        "edit.copy" => Icon::Copy,
+        "edit.paste" => Icon::Paste,
+        "search.find" => Icon::Find,
+        "search.replace" => Icon::Replace,
+        _ => return None,
+    })
+}
+
+/// The text on (or in the tooltip of) a button for a command labelled
+/// `label` in the palette: the part after the menu name, without any
+/// trailing ellipsis.
+pub(crate) fn button_text(label: &str) -> &str {
+    let text = label.split_once(": ").map_or(label, |(_, rest)| rest);
+    text.trim_end_matches('\u{2026}').trim_end_matches("...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_drops_the_menu_name() {
+        assert_eq!(button_text("View: Word Wrap"), "Word Wrap");
+        assert_eq!(button_text("Tools: Run\u{2026}"), "Run");
+        assert_eq!(button_text("Undo"), "Undo");
+    }
+
+    #[test]
+    fn default_buttons_have_icons_except_word_wrap() {
+        let buttons = default_buttons();
+        let missing: Vec<&str> = buttons
+            .iter()
+            .map(String::as_str)
+            .filter(|&b| b != SEPARATOR && icon(b).is_none())
+            .collect();
+        assert_eq!(missing, ["view.word_wrap"]);
+    }
+}